@@ -144,6 +144,18 @@ impl AgentLoop {
             provider_options: None,
             request_id: Some(ctx.task_id.clone()),
             trace_context: None,
+            partial_json: None,
+            fallback_models: None,
+            logprobs: None,
+            top_logprobs: None,
+            estimate_usage: false,
+            metadata: None,
+            resume_on_disconnect: false,
+            allowed_tools: None,
+            stream_tool_call_deltas: false,
+            completion_webhook: None,
+            persist_to_session: None,
+            idempotency_key: None,
         };
 
         // Run stream
@@ -250,6 +262,14 @@ impl AgentLoop {
                     text,
                 });
             }
+            StreamEvent::ReasoningSummaryDelta { id, text } => {
+                // Runtime consumers render summaries as reasoning text
+                let _ = self.event_sender.send(RuntimeEvent::ReasoningDelta {
+                    session_id: ctx.session_id.clone(),
+                    id,
+                    text,
+                });
+            }
             StreamEvent::ReasoningEnd { id } => {
                 // Emit reasoning end event
                 let _ = self.event_sender.send(RuntimeEvent::ReasoningEnd {
@@ -276,7 +296,7 @@ impl AgentLoop {
             StreamEvent::Done { finish_reason } => {
                 state.finish_reason = finish_reason;
             }
-            StreamEvent::Error { message } => {
+            StreamEvent::Error { message, .. } => {
                 state.has_error = true;
                 state.error_message = Some(message);
             }
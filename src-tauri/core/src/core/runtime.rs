@@ -356,6 +356,18 @@ impl CoreRuntime {
                     });
                     messages.push(assistant_message);
 
+                    if let Err(e) = self
+                        .session_manager
+                        .maybe_set_auto_title(&task.session_id)
+                        .await
+                    {
+                        log::warn!(
+                            "[Runtime] Failed to set auto title for session {}: {}",
+                            task.session_id,
+                            e
+                        );
+                    }
+
                     self.complete_task(&task, RuntimeTaskState::Completed, None, &event_sender)
                         .await;
                     break;
@@ -48,6 +48,7 @@ impl SessionManager {
             status: SessionStatus::Created,
             created_at: now,
             updated_at: now,
+            pinned_at: None,
             last_event_id: None,
             metadata: None,
         };
@@ -223,6 +224,27 @@ impl SessionManager {
         Ok(())
     }
 
+    /// Derive a title from the first user message once the first exchange is done;
+    /// a no-op for sessions that already have a title
+    pub async fn maybe_set_auto_title(&self, session_id: &str) -> Result<(), String> {
+        let Some(title) = self
+            .storage
+            .chat_history
+            .maybe_set_auto_title(session_id)
+            .await?
+        else {
+            return Ok(());
+        };
+
+        let active = self.active_sessions.read().await;
+        if let Some(state) = active.get(session_id) {
+            let mut state = state.write().await;
+            state.session.title = Some(title);
+        }
+
+        Ok(())
+    }
+
     /// Add a message to a session
     pub async fn add_message(&self, message: Message) -> Result<(), String> {
         // Persist message
@@ -258,10 +280,12 @@ impl SessionManager {
         status: Option<SessionStatus>,
         limit: Option<usize>,
         offset: Option<usize>,
+        include_archived: bool,
+        tags: Option<Vec<String>>,
     ) -> Result<Vec<Session>, String> {
         self.storage
             .chat_history
-            .list_sessions(project_id, status, limit, offset)
+            .list_sessions(project_id, status, limit, offset, include_archived, tags)
             .await
     }
 
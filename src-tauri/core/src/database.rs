@@ -1,10 +1,11 @@
 // Database module using libsql for Turso integration
 use libsql::Builder;
 use serde::{Deserialize, Serialize};
+use std::future::Future;
 use std::path::Path;
 use std::sync::Arc;
-use tauri::State;
-use tokio::sync::Mutex;
+use tauri::{Manager, State};
+use tokio::sync::{Mutex, OwnedMutexGuard};
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct QueryResult {
@@ -55,6 +56,10 @@ impl Database {
         Ok(())
     }
 
+    pub async fn is_connected(&self) -> bool {
+        self.conn.lock().await.is_some()
+    }
+
     pub async fn execute(
         &self,
         sql: &str,
@@ -183,51 +188,7 @@ impl Database {
     ) -> Result<QueryResult, String> {
         let lock = self.conn.lock().await;
         let conn = lock.as_ref().ok_or("Database not connected")?;
-
-        // Convert JSON values to libsql Values
-        let libsql_params: Vec<libsql::Value> = params.iter().map(json_to_libsql_value).collect();
-
-        let stmt = conn
-            .prepare(sql)
-            .await
-            .map_err(|e| format!("Prepare error: {}", e))?;
-
-        let mut rows_result = stmt
-            .query(libsql_params)
-            .await
-            .map_err(|e| format!("Query error: {}", e))?;
-
-        let mut rows = Vec::new();
-
-        while let Some(row) = rows_result
-            .next()
-            .await
-            .map_err(|e| format!("Row fetch error: {}", e))?
-        {
-            let mut row_obj = serde_json::Map::new();
-
-            // Get column count
-            let column_count = row.column_count();
-
-            for i in 0..column_count {
-                let value = row
-                    .get_value(i)
-                    .map_err(|e| format!("Get value error: {}", e))?;
-                let column_name = row
-                    .column_name(i)
-                    .unwrap_or(&format!("column_{}", i))
-                    .to_string();
-
-                row_obj.insert(column_name, libsql_value_to_json(&value));
-            }
-
-            rows.push(serde_json::Value::Object(row_obj));
-        }
-
-        Ok(QueryResult {
-            rows,
-            rows_affected: 0,
-        })
+        query_rows(conn, sql, &params).await
     }
 
     pub async fn batch(
@@ -244,6 +205,105 @@ impl Database {
         Ok(results)
     }
 
+    /// Run `f` inside BEGIN/COMMIT, rolling back if it returns an error.
+    /// The connection stays locked for the whole transaction, so statements must
+    /// go through the provided `Transaction` rather than `self`.
+    pub async fn transaction<F, Fut, T>(&self, f: F) -> Result<T, String>
+    where
+        F: FnOnce(Transaction) -> Fut,
+        Fut: Future<Output = Result<T, String>>,
+    {
+        let guard = Arc::new(self.conn.clone().lock_owned().await);
+        let tx = Transaction {
+            guard: guard.clone(),
+        };
+        tx.execute("BEGIN", vec![]).await?;
+
+        let result = match f(tx.clone()).await {
+            Ok(value) => tx.execute("COMMIT", vec![]).await.map(|_| value),
+            Err(e) => Err(e),
+        };
+        if result.is_err() {
+            if let Err(e) = tx.execute("ROLLBACK", vec![]).await {
+                log::warn!("Failed to roll back transaction: {}", e);
+            }
+        }
+        result
+    }
+
+    /// Apply the registry's pending migrations, each in its own transaction.
+    /// Returns the migrations applied by this call as `version: name`.
+    pub async fn run_migrations(
+        &self,
+        registry: &crate::storage::migrations::MigrationRegistry,
+    ) -> Result<Vec<String>, String> {
+        crate::storage::migrations::MigrationRunner::new(self, registry)
+            .migrate()
+            .await
+    }
+
+    /// Write a consistent copy of the database to `dest` while staying connected.
+    /// libsql does not expose SQLite's online backup API, so this uses
+    /// `VACUUM INTO`, which takes the same consistent snapshot inside a read
+    /// transaction without blocking other readers and writers.
+    pub async fn backup_to(&self, dest: &Path) -> Result<(), String> {
+        if dest.exists() {
+            return Err(format!("Backup target '{}' already exists", dest.display()));
+        }
+        if let Some(parent) = dest.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| {
+                format!(
+                    "Failed to create backup directory '{}': {}",
+                    parent.display(),
+                    e
+                )
+            })?;
+        }
+        self.execute(
+            "VACUUM INTO ?",
+            vec![serde_json::json!(dest.to_string_lossy())],
+        )
+        .await
+        .map_err(|e| format!("Failed to back up '{}': {}", self.db_path, e))?;
+        Ok(())
+    }
+
+    /// Stage the backup at `src` to replace this database on the next launch.
+    /// The backup is checked first; the live file is left alone because other
+    /// connections (the server storage, the frontend) may still have it open.
+    /// `apply_pending_restore` swaps it in before anything connects.
+    pub async fn restore_from(&self, src: &Path) -> Result<(), String> {
+        validate_database_file(src).await?;
+
+        let db_path = Path::new(&self.db_path);
+        let (Some(parent), Some(file_name)) = (db_path.parent(), db_path.file_name()) else {
+            return Err(format!("Invalid database path '{}'", self.db_path));
+        };
+        let staging_dir = parent.join(RESTORE_STAGING_DIR);
+        std::fs::create_dir_all(&staging_dir).map_err(|e| {
+            format!(
+                "Failed to create restore directory '{}': {}",
+                staging_dir.display(),
+                e
+            )
+        })?;
+        // Copy under a temporary name so a half-written file is never applied
+        let staged = staging_dir.join(file_name);
+        let partial = staging_dir.join(format!("{}.partial", file_name.to_string_lossy()));
+        std::fs::copy(src, &partial)
+            .and_then(|_| std::fs::rename(&partial, &staged))
+            .map_err(|e| {
+                let _ = std::fs::remove_file(&partial);
+                format!(
+                    "Failed to stage restore of '{}' from '{}': {}",
+                    self.db_path,
+                    src.display(),
+                    e
+                )
+            })?;
+        Ok(())
+    }
+
     /// Close the database connection gracefully
     /// This should be called when the application exits to release file handles
     #[allow(dead_code)]
@@ -277,6 +337,145 @@ impl Database {
     }
 }
 
+/// Handle for issuing statements inside `Database::transaction`
+#[derive(Clone)]
+pub struct Transaction {
+    guard: Arc<OwnedMutexGuard<Option<libsql::Connection>>>,
+}
+
+impl Transaction {
+    fn conn(&self) -> Result<&libsql::Connection, String> {
+        let conn: &Option<libsql::Connection> = &self.guard;
+        conn.as_ref()
+            .ok_or_else(|| "Database not connected".to_string())
+    }
+
+    /// Run several `;`-separated statements without parameters, e.g. a migration
+    pub async fn execute_batch(&self, sql: &str) -> Result<(), String> {
+        self.conn()?
+            .execute_batch(sql)
+            .await
+            .map_err(|e| format!("Execute error: {}", e))?;
+        Ok(())
+    }
+
+    pub async fn execute(
+        &self,
+        sql: &str,
+        params: Vec<serde_json::Value>,
+    ) -> Result<QueryResult, String> {
+        let conn = self.conn()?;
+        let sql_trimmed = sql.trim_start().to_uppercase();
+        if sql_trimmed.starts_with("SELECT") || sql_trimmed.starts_with("PRAGMA") {
+            return query_rows(conn, sql, &params).await;
+        }
+
+        let libsql_params: Vec<libsql::Value> = params.iter().map(json_to_libsql_value).collect();
+        let rows_affected = conn
+            .execute(sql, libsql_params)
+            .await
+            .map_err(|e| format!("Execute error: {}", e))?;
+        Ok(QueryResult {
+            rows: vec![],
+            rows_affected,
+        })
+    }
+
+    pub async fn query(
+        &self,
+        sql: &str,
+        params: Vec<serde_json::Value>,
+    ) -> Result<QueryResult, String> {
+        query_rows(self.conn()?, sql, &params).await
+    }
+}
+
+async fn query_rows(
+    conn: &libsql::Connection,
+    sql: &str,
+    params: &[serde_json::Value],
+) -> Result<QueryResult, String> {
+    // Convert JSON values to libsql Values
+    let libsql_params: Vec<libsql::Value> = params.iter().map(json_to_libsql_value).collect();
+
+    let stmt = conn
+        .prepare(sql)
+        .await
+        .map_err(|e| format!("Prepare error: {}", e))?;
+
+    let mut rows_result = stmt
+        .query(libsql_params)
+        .await
+        .map_err(|e| format!("Query error: {}", e))?;
+
+    let mut rows = Vec::new();
+
+    while let Some(row) = rows_result
+        .next()
+        .await
+        .map_err(|e| format!("Row fetch error: {}", e))?
+    {
+        let mut row_obj = serde_json::Map::new();
+
+        // Get column count
+        let column_count = row.column_count();
+
+        for i in 0..column_count {
+            let value = row
+                .get_value(i)
+                .map_err(|e| format!("Get value error: {}", e))?;
+            let column_name = row
+                .column_name(i)
+                .unwrap_or(&format!("column_{}", i))
+                .to_string();
+
+            row_obj.insert(column_name, libsql_value_to_json(&value));
+        }
+
+        rows.push(serde_json::Value::Object(row_obj));
+    }
+
+    Ok(QueryResult {
+        rows,
+        rows_affected: 0,
+    })
+}
+
+/// Check that `path` is a readable SQLite database that passes an integrity check
+async fn validate_database_file(path: &Path) -> Result<(), String> {
+    use std::io::Read;
+
+    let mut header = [0u8; 16];
+    std::fs::File::open(path)
+        .and_then(|mut file| file.read_exact(&mut header))
+        .map_err(|e| format!("Cannot read backup '{}': {}", path.display(), e))?;
+    if &header != b"SQLite format 3\0" {
+        return Err(format!("'{}' is not a SQLite database", path.display()));
+    }
+
+    let db = Builder::new_local(path)
+        .build()
+        .await
+        .map_err(|e| format!("Failed to open backup '{}': {}", path.display(), e))?;
+    let conn = db
+        .connect()
+        .map_err(|e| format!("Failed to open backup '{}': {}", path.display(), e))?;
+    let result = query_rows(&conn, "PRAGMA integrity_check", &[]).await?;
+    let status = result
+        .rows
+        .first()
+        .and_then(|row| row.get("integrity_check"))
+        .and_then(|v| v.as_str());
+    if status != Some("ok") {
+        return Err(format!(
+            "Backup '{}' failed integrity check: {}",
+            path.display(),
+            status.unwrap_or("no result")
+        ));
+    }
+    Ok(())
+}
+
 // Convert serde_json::Value to libsql::Value
 fn json_to_libsql_value(v: &serde_json::Value) -> libsql::Value {
     match v {
@@ -355,6 +554,187 @@ pub async fn db_batch(
     db.batch(statements).await
 }
 
+/// Databases stored next to the main database that are included in backups.
+/// Traces live in the main database, so they are backed up with it.
+pub const BACKUP_DATABASE_FILES: &[&str] = &["chat_history.db", "settings.db", "agents.db"];
+
+/// Folder in the data directory holding restored databases until the next launch
+pub const RESTORE_STAGING_DIR: &str = "pending-restore";
+
+fn main_database_file(db: &Database) -> Result<String, String> {
+    Path::new(&db.db_path)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .ok_or_else(|| format!("Invalid database path '{}'", db.db_path))
+}
+
+/// Back up the main database and the storage databases in `data_dir` into a new
+/// timestamped folder under `dest_dir`, returning that folder
+pub async fn backup_databases(
+    db: &Database,
+    data_dir: &Path,
+    dest_dir: &Path,
+) -> Result<std::path::PathBuf, String> {
+    let folder = dest_dir.join(format!(
+        "talkcody-backup-{}",
+        chrono::Local::now().format("%Y%m%d-%H%M%S")
+    ));
+    if folder.exists() {
+        return Err(format!(
+            "Backup folder '{}' already exists",
+            folder.display()
+        ));
+    }
+
+    db.backup_to(&folder.join(main_database_file(db)?)).await?;
+    for name in BACKUP_DATABASE_FILES {
+        let path = data_dir.join(name);
+        if !path.exists() {
+            continue;
+        }
+        let source = Database::new(path.to_string_lossy().to_string());
+        source.connect().await?;
+        let result = source.backup_to(&folder.join(name)).await;
+        source.close().await?;
+        result?;
+    }
+
+    log::info!("Databases backed up to {}", folder.display());
+    Ok(folder)
+}
+
+/// Stage every database found in a folder produced by `backup_databases`.
+/// Nothing changes until `apply_pending_restore` runs on the next launch.
+pub async fn restore_databases(
+    db: &Database,
+    data_dir: &Path,
+    src_dir: &Path,
+) -> Result<Vec<String>, String> {
+    let main_file = main_database_file(db)?;
+    let mut found: Vec<&str> = Vec::new();
+    if src_dir.join(&main_file).exists() {
+        found.push(&main_file);
+    }
+    found.extend(
+        BACKUP_DATABASE_FILES
+            .iter()
+            .copied()
+            .filter(|name| src_dir.join(name).exists()),
+    );
+    if found.is_empty() {
+        return Err(format!(
+            "No database backups found in '{}'",
+            src_dir.display()
+        ));
+    }
+
+    // Validate everything up front so a bad file does not leave a half-restored set
+    for name in &found {
+        validate_database_file(&src_dir.join(name)).await?;
+    }
+
+    // A previous restore that was never applied must not mix with this one
+    let staging_dir = data_dir.join(RESTORE_STAGING_DIR);
+    if staging_dir.exists() {
+        std::fs::remove_dir_all(&staging_dir).map_err(|e| {
+            format!(
+                "Failed to clear restore directory '{}': {}",
+                staging_dir.display(),
+                e
+            )
+        })?;
+    }
+    for name in &found {
+        let src = src_dir.join(name);
+        if *name == main_file {
+            db.restore_from(&src).await?;
+        } else {
+            let target = Database::new(data_dir.join(name).to_string_lossy().to_string());
+            target.restore_from(&src).await?;
+        }
+    }
+
+    log::info!(
+        "Databases from {} staged for restore on next launch",
+        src_dir.display()
+    );
+    Ok(found.into_iter().map(String::from).collect())
+}
+
+/// Swap staged databases into `data_dir`. Must run at startup before any
+/// connection to them is opened; returns the names of the restored files.
+pub fn apply_pending_restore(data_dir: &Path) -> Result<Vec<String>, String> {
+    let staging_dir = data_dir.join(RESTORE_STAGING_DIR);
+    if !staging_dir.is_dir() {
+        return Ok(Vec::new());
+    }
+    let entries = std::fs::read_dir(&staging_dir).map_err(|e| {
+        format!(
+            "Failed to read restore directory '{}': {}",
+            staging_dir.display(),
+            e
+        )
+    })?;
+
+    let mut restored = Vec::new();
+    for entry in entries {
+        let entry = entry.map_err(|e| format!("Failed to read restore entry: {}", e))?;
+        let name = entry.file_name().to_string_lossy().to_string();
+        if !name.ends_with(".db") {
+            continue;
+        }
+        let target = data_dir.join(&name);
+        // The old WAL belongs to the replaced file and must not be replayed onto the backup
+        for suffix in ["-wal", "-shm"] {
+            let _ = std::fs::remove_file(format!("{}{}", target.to_string_lossy(), suffix));
+        }
+        std::fs::rename(entry.path(), &target).map_err(|e| {
+            format!(
+                "Failed to restore '{}' from '{}': {}",
+                target.display(),
+                entry.path().display(),
+                e
+            )
+        })?;
+        restored.push(name);
+    }
+    restored.sort();
+
+    std::fs::remove_dir_all(&staging_dir).map_err(|e| {
+        format!(
+            "Failed to clear restore directory '{}': {}",
+            staging_dir.display(),
+            e
+        )
+    })?;
+    log::info!("Restored databases from backup: {:?}", restored);
+    Ok(restored)
+}
+
+#[tauri::command]
+pub async fn db_backup(
+    app: tauri::AppHandle,
+    db: State<'_, Arc<Database>>,
+    dest_dir: String,
+) -> Result<String, String> {
+    let data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let folder = backup_databases(&db, &data_dir, Path::new(&dest_dir)).await?;
+    Ok(folder.to_string_lossy().to_string())
+}
+
+#[tauri::command]
+pub async fn db_restore(
+    app: tauri::AppHandle,
+    db: State<'_, Arc<Database>>,
+    src_dir: String,
+) -> Result<Vec<String>, String> {
+    let data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+    let staged = restore_databases(&db, &data_dir, Path::new(&src_dir)).await?;
+    // The databases are still open, so the swap happens in the next launch's setup
+    app.request_restart();
+    Ok(staged)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1089,4 +1469,206 @@ mod tests {
             );
         }
     }
+
+    async fn count_rows(database: &Database) -> i64 {
+        database
+            .query("SELECT COUNT(*) AS count FROM test", vec![])
+            .await
+            .unwrap()
+            .rows[0]["count"]
+            .as_i64()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_transaction_commits_all_statements() {
+        let temp_dir = TempDir::new().unwrap();
+        let database = Database::new(temp_dir.path().join("tx.db").to_string_lossy().to_string());
+        database.connect().await.unwrap();
+        database
+            .execute(
+                "CREATE TABLE test (id INTEGER PRIMARY KEY, data TEXT)",
+                vec![],
+            )
+            .await
+            .unwrap();
+
+        let inserted = database
+            .transaction(|tx| async move {
+                for i in 0..3 {
+                    tx.execute(
+                        "INSERT INTO test (id, data) VALUES (?, ?)",
+                        vec![serde_json::json!(i), serde_json::json!("row")],
+                    )
+                    .await?;
+                }
+                let rows = tx.query("SELECT id FROM test", vec![]).await?;
+                Ok(rows.rows.len())
+            })
+            .await
+            .unwrap();
+
+        assert_eq!(inserted, 3);
+        assert_eq!(count_rows(&database).await, 3);
+    }
+
+    #[tokio::test]
+    async fn test_transaction_error_leaves_database_unchanged() {
+        let temp_dir = TempDir::new().unwrap();
+        let database = Database::new(temp_dir.path().join("tx.db").to_string_lossy().to_string());
+        database.connect().await.unwrap();
+        database
+            .execute(
+                "CREATE TABLE test (id INTEGER PRIMARY KEY, data TEXT)",
+                vec![],
+            )
+            .await
+            .unwrap();
+        database
+            .execute("INSERT INTO test (id, data) VALUES (1, 'original')", vec![])
+            .await
+            .unwrap();
+
+        let result: Result<(), String> = database
+            .transaction(|tx| async move {
+                tx.execute("INSERT INTO test (id, data) VALUES (2, 'new')", vec![])
+                    .await?;
+                tx.execute("UPDATE test SET data = 'changed' WHERE id = 1", vec![])
+                    .await?;
+                Err("forced failure".to_string())
+            })
+            .await;
+
+        assert_eq!(result.unwrap_err(), "forced failure");
+        assert_eq!(count_rows(&database).await, 1);
+        let row = database
+            .query("SELECT data FROM test WHERE id = 1", vec![])
+            .await
+            .unwrap();
+        assert_eq!(row.rows[0]["data"], "original");
+
+        // The connection is usable again once the transaction has rolled back
+        database
+            .execute("INSERT INTO test (id, data) VALUES (3, 'after')", vec![])
+            .await
+            .unwrap();
+        assert_eq!(count_rows(&database).await, 2);
+    }
+
+    async fn populated_database(path: std::path::PathBuf, rows: i64) -> Database {
+        let database = Database::new(path.to_string_lossy().to_string());
+        database.connect().await.unwrap();
+        database
+            .execute(
+                "CREATE TABLE test (id INTEGER PRIMARY KEY, data TEXT)",
+                vec![],
+            )
+            .await
+            .unwrap();
+        for i in 0..rows {
+            database
+                .execute(
+                    "INSERT INTO test (id, data) VALUES (?, ?)",
+                    vec![
+                        serde_json::json!(i),
+                        serde_json::json!(format!("data_{}", i)),
+                    ],
+                )
+                .await
+                .unwrap();
+        }
+        database
+    }
+
+    #[tokio::test]
+    async fn test_backup_restores_all_rows_into_fresh_database() {
+        let temp_dir = TempDir::new().unwrap();
+        let source = populated_database(temp_dir.path().join("source.db"), 25).await;
+        let backup_path = temp_dir.path().join("backups").join("source.db");
+        source.backup_to(&backup_path).await.unwrap();
+        assert!(source.backup_to(&backup_path).await.is_err());
+
+        let data_dir = temp_dir.path().join("data");
+        let fresh = Database::new(data_dir.join("fresh.db").to_string_lossy().to_string());
+        fresh.connect().await.unwrap();
+        fresh.restore_from(&backup_path).await.unwrap();
+        // Nothing is swapped while the database is open
+        assert!(fresh
+            .query("SELECT COUNT(*) AS count FROM test", vec![])
+            .await
+            .is_err());
+        fresh.close().await.unwrap();
+
+        let restored = apply_pending_restore(&data_dir).unwrap();
+        assert_eq!(restored, vec!["fresh.db"]);
+        assert!(!data_dir.join(RESTORE_STAGING_DIR).exists());
+        fresh.connect().await.unwrap();
+
+        assert_eq!(count_rows(&fresh).await, 25);
+        let original = source
+            .query("SELECT id, data FROM test ORDER BY id", vec![])
+            .await
+            .unwrap();
+        let restored = fresh
+            .query("SELECT id, data FROM test ORDER BY id", vec![])
+            .await
+            .unwrap();
+        assert_eq!(original.rows, restored.rows);
+    }
+
+    #[tokio::test]
+    async fn test_restore_rejects_invalid_backup() {
+        let temp_dir = TempDir::new().unwrap();
+        let database = populated_database(temp_dir.path().join("keep.db"), 3).await;
+        let bogus = temp_dir.path().join("bogus.db");
+        std::fs::write(&bogus, b"definitely not sqlite").unwrap();
+
+        assert!(database.restore_from(&bogus).await.is_err());
+        assert!(database
+            .restore_from(&temp_dir.path().join("missing.db"))
+            .await
+            .is_err());
+        assert!(!temp_dir.path().join(RESTORE_STAGING_DIR).exists());
+        assert_eq!(count_rows(&database).await, 3);
+    }
+
+    #[tokio::test]
+    async fn test_backup_and_restore_database_folder() {
+        let temp_dir = TempDir::new().unwrap();
+        let data_dir = temp_dir.path().join("data");
+        let main = populated_database(data_dir.join("talkcody.db"), 4).await;
+        let chat = populated_database(data_dir.join("chat_history.db"), 7).await;
+        chat.close().await.unwrap();
+
+        let folder = backup_databases(&main, &data_dir, &temp_dir.path().join("backups"))
+            .await
+            .unwrap();
+        assert!(folder.join("talkcody.db").exists());
+        assert!(folder.join("chat_history.db").exists());
+        assert!(!folder.join("settings.db").exists());
+
+        main.execute("DELETE FROM test", vec![]).await.unwrap();
+        std::fs::remove_file(data_dir.join("chat_history.db")).unwrap();
+
+        let staged = restore_databases(&main, &data_dir, &folder).await.unwrap();
+        assert_eq!(staged, vec!["talkcody.db", "chat_history.db"]);
+        // Live files stay untouched until the next launch applies the restore
+        assert_eq!(count_rows(&main).await, 0);
+        assert!(!data_dir.join("chat_history.db").exists());
+
+        main.close().await.unwrap();
+        let restored = apply_pending_restore(&data_dir).unwrap();
+        assert_eq!(restored, vec!["chat_history.db", "talkcody.db"]);
+        assert!(apply_pending_restore(&data_dir).unwrap().is_empty());
+        main.connect().await.unwrap();
+        assert_eq!(count_rows(&main).await, 4);
+        let chat = Database::new(
+            data_dir
+                .join("chat_history.db")
+                .to_string_lossy()
+                .to_string(),
+        );
+        chat.connect().await.unwrap();
+        assert_eq!(count_rows(&chat).await, 7);
+    }
 }
@@ -6,10 +6,12 @@ use open_lark::service::im::v1::message::UpdateMessageRequest;
 use rand::Rng;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
-use std::path::PathBuf;
+use std::collections::{HashMap, VecDeque};
+use std::future::Future;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use std::thread;
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use tauri::{AppHandle, Emitter, Manager, Runtime, State};
 use tokio::runtime::Builder;
 use tokio::sync::{watch, Mutex};
@@ -26,6 +28,40 @@ const FEISHU_MEDIA_PREFIX: &str = "feishu";
 const DEFAULT_ERROR_BACKOFF_MS: u64 = 1500;
 const MAX_ERROR_BACKOFF_MS: u64 = 30000;
 const MAX_FEISHU_MEDIA_BYTES: u64 = 20 * 1024 * 1024;
+/// Downloaded media older than this is removed by the periodic sweep
+const ATTACHMENT_RETENTION: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+const ATTACHMENT_CLEANUP_INTERVAL_MS: u64 = 60 * 60 * 1000;
+/// Stickers can't be fetched through the message resource API, so they become text
+const STICKER_PLACEHOLDER: &str = "[sticker]";
+const DEFAULT_EDIT_INTERVAL_MS: u64 = 700;
+/// Feishu rejects interactive card content larger than 30KB
+const MAX_CARD_CONTENT_BYTES: usize = 30 * 1024;
+/// Outbound burst size and sustained calls per second shared by all sends and edits
+const OUTBOUND_BURST: f64 = 5.0;
+const OUTBOUND_PER_SECOND: f64 = 5.0;
+/// Feishu's "request frequency limit" error code
+const FREQUENCY_LIMIT_CODE: &str = "99991400";
+const MAX_FREQUENCY_LIMIT_RETRIES: u32 = 3;
+const CARD_TRUNCATION_NOTICE: &str = "\n\n*(Message truncated: exceeds Feishu's 30KB card limit)*";
+/// Inbound message ids remembered for redelivery detection, and for how long
+const DEFAULT_DEDUP_CAPACITY: usize = 500;
+const DEFAULT_DEDUP_TTL_SECS: u64 = 600;
+/// Inbound attachment types accepted when `allowed_attachment_mime_types` is unset.
+/// A trailing `/*` matches the whole family.
+const DEFAULT_ALLOWED_ATTACHMENT_MIME_TYPES: &[&str] = &[
+    "image/*",
+    "audio/*",
+    "video/*",
+    "text/plain",
+    "application/pdf",
+    "application/zip",
+    "application/msword",
+    "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+    "application/vnd.ms-excel",
+    "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+    "application/vnd.ms-powerpoint",
+    "application/vnd.openxmlformats-officedocument.presentationml.presentation",
+];
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -36,6 +72,22 @@ pub struct FeishuConfig {
     pub encrypt_key: String,
     pub verification_token: String,
     pub allowed_open_ids: Vec<String>,
+    /// Also accept group chats where the bot is @-mentioned; replies go to the chat id
+    #[serde(default)]
+    pub allow_group_chats: bool,
+    /// Minimum spacing between streamed edits of one message (default 700ms)
+    #[serde(default)]
+    pub edit_interval_ms: Option<u64>,
+    /// Recent inbound message ids kept to drop redelivered events (default 500)
+    #[serde(default)]
+    pub dedup_capacity: Option<usize>,
+    /// How long an inbound message id is remembered (default 10 minutes)
+    #[serde(default)]
+    pub dedup_ttl_secs: Option<u64>,
+    /// MIME types accepted for inbound attachments, detected from the file's
+    /// leading bytes; `image/*` style entries match a family
+    #[serde(default)]
+    pub allowed_attachment_mime_types: Option<Vec<String>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -54,10 +106,12 @@ pub struct FeishuRemoteAttachment {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct FeishuInboundMessage {
+    /// Reply target: the sender's open_id for p2p, the group's chat_id for groups
     pub chat_id: String,
     pub message_id: String,
     pub text: String,
     pub open_id: String,
+    pub chat_type: String,
     pub date: i64,
     pub attachments: Option<Vec<FeishuRemoteAttachment>>,
 }
@@ -67,6 +121,9 @@ pub struct FeishuInboundMessage {
 pub struct FeishuSendMessageRequest {
     pub open_id: String,
     pub text: String,
+    /// Reply to this message id instead of starting a new top-level message
+    #[serde(default)]
+    pub reply_to: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -90,7 +147,15 @@ pub struct FeishuGateway {
     last_error: Option<String>,
     last_error_at_ms: Option<i64>,
     backoff_ms: u64,
+    /// Connection attempts after the first since the app started
+    reconnect_count: u64,
+    /// When the most recent websocket session was opened
+    last_connected_at_ms: Option<i64>,
+    consecutive_failures: u32,
     stop_tx: Option<watch::Sender<bool>>,
+    updater: Option<Arc<FeishuMessageUpdater>>,
+    limiter: Arc<FeishuRateLimiter>,
+    dedup: Arc<FeishuMessageDeduper>,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -102,9 +167,25 @@ enum FeishuSenderKind {
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum FeishuChatKind {
     P2p,
+    Group,
     Other,
 }
 
+/// A single `@` mention from `message.mentions`
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+struct FeishuMention {
+    #[serde(default)]
+    key: String,
+    #[serde(default)]
+    id: FeishuMentionId,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+struct FeishuMentionId {
+    #[serde(default)]
+    open_id: Option<String>,
+}
+
 impl FeishuGateway {
     pub fn new() -> Self {
         Self {
@@ -114,12 +195,249 @@ impl FeishuGateway {
             last_error: None,
             last_error_at_ms: None,
             backoff_ms: DEFAULT_ERROR_BACKOFF_MS,
+            reconnect_count: 0,
+            last_connected_at_ms: None,
+            consecutive_failures: 0,
             stop_tx: None,
+            updater: None,
+            limiter: Arc::new(FeishuRateLimiter::default()),
+            dedup: Arc::new(FeishuMessageDeduper::default()),
+        }
+    }
+}
+
+/// Bounded set of recently seen inbound message ids. Feishu may redeliver
+/// `im.message.receive_v1` after a reconnect; ids are forgotten once they are
+/// older than the TTL or pushed out by newer ones.
+#[derive(Debug)]
+struct FeishuMessageDeduper {
+    window: std::sync::Mutex<DedupWindow>,
+}
+
+#[derive(Debug)]
+struct DedupWindow {
+    capacity: usize,
+    ttl: Duration,
+    seen: HashMap<String, Instant>,
+    order: VecDeque<(String, Instant)>,
+}
+
+impl DedupWindow {
+    fn evict(&mut self, now: Instant) {
+        while let Some((message_id, seen_at)) = self.order.front() {
+            let expired = now.saturating_duration_since(*seen_at) >= self.ttl;
+            if !expired && self.order.len() <= self.capacity {
+                break;
+            }
+            self.seen.remove(message_id);
+            self.order.pop_front();
+        }
+    }
+}
+
+impl Default for FeishuMessageDeduper {
+    fn default() -> Self {
+        Self::new(
+            DEFAULT_DEDUP_CAPACITY,
+            Duration::from_secs(DEFAULT_DEDUP_TTL_SECS),
+        )
+    }
+}
+
+impl FeishuMessageDeduper {
+    fn new(capacity: usize, ttl: Duration) -> Self {
+        Self {
+            window: std::sync::Mutex::new(DedupWindow {
+                capacity: capacity.max(1),
+                ttl,
+                seen: HashMap::new(),
+                order: VecDeque::new(),
+            }),
+        }
+    }
+
+    /// Apply the size/TTL from `config`, keeping ids already seen
+    fn configure(&self, config: &FeishuConfig) {
+        if let Ok(mut window) = self.window.lock() {
+            window.capacity = config
+                .dedup_capacity
+                .unwrap_or(DEFAULT_DEDUP_CAPACITY)
+                .max(1);
+            window.ttl =
+                Duration::from_secs(config.dedup_ttl_secs.unwrap_or(DEFAULT_DEDUP_TTL_SECS));
+            window.evict(Instant::now());
+        }
+    }
+
+    /// Record `message_id`; returns false if it was already seen within the window
+    fn first_seen(&self, message_id: &str, now: Instant) -> bool {
+        if message_id.is_empty() {
+            return true;
+        }
+        let Ok(mut window) = self.window.lock() else {
+            return true;
+        };
+        window.evict(now);
+        if window.seen.contains_key(message_id) {
+            return false;
+        }
+        window.seen.insert(message_id.to_string(), now);
+        window.order.push_back((message_id.to_string(), now));
+        window.evict(now);
+        true
+    }
+}
+
+/// Token bucket: holds up to `capacity` tokens, refilled continuously at `refill_per_sec`
+#[derive(Debug)]
+struct TokenBucket {
+    capacity: f64,
+    tokens: f64,
+    refill_per_sec: f64,
+    last_refill: Instant,
+}
+
+impl TokenBucket {
+    fn new(capacity: f64, refill_per_sec: f64) -> Self {
+        Self {
+            capacity,
+            tokens: capacity,
+            refill_per_sec,
+            last_refill: Instant::now(),
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now
+            .saturating_duration_since(self.last_refill)
+            .as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.refill_per_sec).min(self.capacity);
+        self.last_refill = now;
+    }
+
+    /// Take a token, or return how long until one is available
+    fn try_acquire(&mut self, now: Instant) -> Result<(), Duration> {
+        self.refill(now);
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            return Ok(());
+        }
+        Err(Duration::from_secs_f64(
+            (1.0 - self.tokens) / self.refill_per_sec,
+        ))
+    }
+}
+
+/// Shared limiter for outbound Feishu API calls
+#[derive(Debug)]
+pub struct FeishuRateLimiter {
+    bucket: std::sync::Mutex<TokenBucket>,
+    frequency_limit_retries: std::sync::atomic::AtomicU64,
+    throttled_until_ms: std::sync::atomic::AtomicI64,
+}
+
+impl Default for FeishuRateLimiter {
+    fn default() -> Self {
+        Self::new(OUTBOUND_BURST, OUTBOUND_PER_SECOND)
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeishuThrottleStatus {
+    pub available_tokens: f64,
+    pub capacity: f64,
+    pub frequency_limit_retries: u64,
+    pub throttled_until_ms: Option<i64>,
+}
+
+impl FeishuRateLimiter {
+    fn new(capacity: f64, refill_per_sec: f64) -> Self {
+        Self {
+            bucket: std::sync::Mutex::new(TokenBucket::new(capacity, refill_per_sec)),
+            frequency_limit_retries: std::sync::atomic::AtomicU64::new(0),
+            throttled_until_ms: std::sync::atomic::AtomicI64::new(0),
+        }
+    }
+
+    /// Wait until a token is available
+    async fn acquire(&self) {
+        loop {
+            let wait = match self.bucket.lock() {
+                Ok(mut bucket) => match bucket.try_acquire(Instant::now()) {
+                    Ok(()) => return,
+                    Err(wait) => wait,
+                },
+                Err(_) => return,
+            };
+            sleep(wait).await;
+        }
+    }
+
+    fn status(&self) -> FeishuThrottleStatus {
+        use std::sync::atomic::Ordering;
+        let (available_tokens, capacity) = match self.bucket.lock() {
+            Ok(mut bucket) => {
+                bucket.refill(Instant::now());
+                (bucket.tokens, bucket.capacity)
+            }
+            Err(_) => (0.0, 0.0),
+        };
+        let throttled_until_ms = self.throttled_until_ms.load(Ordering::Relaxed);
+        FeishuThrottleStatus {
+            available_tokens,
+            capacity,
+            frequency_limit_retries: self.frequency_limit_retries.load(Ordering::Relaxed),
+            throttled_until_ms: (throttled_until_ms > now_ms()).then_some(throttled_until_ms),
         }
     }
 }
 
-type FeishuGatewayState = Arc<Mutex<FeishuGateway>>;
+fn is_frequency_limit_error(error: &str) -> bool {
+    error.contains(FREQUENCY_LIMIT_CODE)
+}
+
+/// Run `call` under the outbound limiter, retrying with backoff when Feishu
+/// answers with its frequency-limit error
+async fn call_with_rate_limit<T, F, Fut>(
+    limiter: &FeishuRateLimiter,
+    initial_backoff_ms: u64,
+    mut call: F,
+) -> Result<T, String>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<T, String>>,
+{
+    use std::sync::atomic::Ordering;
+    let mut backoff_ms = initial_backoff_ms;
+    let mut attempt = 0;
+    loop {
+        limiter.acquire().await;
+        match call().await {
+            Err(error)
+                if is_frequency_limit_error(&error) && attempt < MAX_FREQUENCY_LIMIT_RETRIES =>
+            {
+                attempt += 1;
+                limiter
+                    .frequency_limit_retries
+                    .fetch_add(1, Ordering::Relaxed);
+                limiter
+                    .throttled_until_ms
+                    .store(now_ms() + backoff_ms as i64, Ordering::Relaxed);
+                log::warn!(
+                    "[FeishuGateway] Frequency limited, retry {} in {}ms",
+                    attempt,
+                    backoff_ms
+                );
+                sleep(Duration::from_millis(backoff_ms)).await;
+                backoff_ms = compute_backoff_ms(backoff_ms);
+            }
+            result => return result,
+        }
+    }
+}
+
+pub type FeishuGatewayState = Arc<Mutex<FeishuGateway>>;
 
 fn now_ms() -> i64 {
     SystemTime::now()
@@ -131,12 +449,21 @@ fn now_ms() -> i64 {
 fn record_error_state(state: &mut FeishuGateway, message: impl Into<String>) {
     state.last_error = Some(message.into());
     state.last_error_at_ms = Some(now_ms());
+    state.consecutive_failures = state.consecutive_failures.saturating_add(1);
 }
 
 fn clear_error_state(state: &mut FeishuGateway) {
     state.last_error = None;
     state.last_error_at_ms = None;
     state.backoff_ms = DEFAULT_ERROR_BACKOFF_MS;
+    state.consecutive_failures = 0;
+}
+
+fn record_connection_attempt(state: &mut FeishuGateway) {
+    if state.last_connected_at_ms.is_some() {
+        state.reconnect_count = state.reconnect_count.saturating_add(1);
+    }
+    state.last_connected_at_ms = Some(now_ms());
 }
 
 fn compute_backoff_ms(current: u64) -> u64 {
@@ -174,10 +501,59 @@ fn sender_kind(sender_type: &str) -> FeishuSenderKind {
 }
 
 fn chat_kind(chat_type: &str) -> FeishuChatKind {
-    if chat_type == "p2p" {
-        FeishuChatKind::P2p
+    match chat_type {
+        "p2p" => FeishuChatKind::P2p,
+        "group" => FeishuChatKind::Group,
+        _ => FeishuChatKind::Other,
+    }
+}
+
+fn parse_mentions(mentions: &Value) -> Vec<FeishuMention> {
+    serde_json::from_value::<Option<Vec<FeishuMention>>>(mentions.clone())
+        .ok()
+        .flatten()
+        .unwrap_or_default()
+}
+
+fn is_bot_mentioned(mentions: &[FeishuMention], bot_open_id: &str) -> bool {
+    !bot_open_id.is_empty()
+        && mentions
+            .iter()
+            .any(|mention| mention.id.open_id.as_deref() == Some(bot_open_id))
+}
+
+/// Remove `@_user_N` placeholders Feishu leaves in the text for each mention
+fn strip_mention_keys(text: &str, mentions: &[FeishuMention]) -> String {
+    let mut stripped = text.to_string();
+    for mention in mentions.iter().filter(|mention| !mention.key.is_empty()) {
+        stripped = stripped.replace(&mention.key, "");
+    }
+    stripped.trim().to_string()
+}
+
+/// Conversation an inbound message belongs to, or None if the gateway should ignore it.
+/// P2p chats are keyed by the sender's open_id; groups (when enabled, and only when the
+/// bot is mentioned) by the chat id.
+fn resolve_conversation_id(
+    kind: FeishuChatKind,
+    allow_group_chats: bool,
+    bot_mentioned: bool,
+    open_id: &str,
+    chat_id: &str,
+) -> Option<String> {
+    match kind {
+        FeishuChatKind::P2p => Some(open_id.to_string()),
+        FeishuChatKind::Group if allow_group_chats && bot_mentioned => Some(chat_id.to_string()),
+        _ => None,
+    }
+}
+
+/// Feishu `receive_id_type` for a reply target: group chat ids start with `oc_`
+fn receive_id_type(receive_id: &str) -> &'static str {
+    if receive_id.starts_with("oc_") {
+        "chat_id"
     } else {
-        FeishuChatKind::Other
+        "open_id"
     }
 }
 
@@ -188,7 +564,74 @@ async fn attachments_root<R: Runtime>(
         .path()
         .app_data_dir()
         .map_err(|e| format!("Failed to get app data dir: {}", e))?;
-    Ok(Some(app_data_dir.join(FEISHU_ATTACHMENTS_DIR)))
+    // Keep downloads in their own subdirectory: the shared attachments dir also
+    // holds files the user attached in the app, which the sweep must not touch
+    Ok(Some(
+        app_data_dir
+            .join(FEISHU_ATTACHMENTS_DIR)
+            .join(FEISHU_MEDIA_PREFIX),
+    ))
+}
+
+/// Delete files in `attachments_dir` last modified more than `older_than` ago and
+/// return how many were removed. Inbound messages are handed to the frontend as
+/// soon as their media is saved and the gateway keeps no queue of unprocessed
+/// ones, so age is the only signal; the retention is far longer than any hand-off.
+async fn cleanup_attachments(
+    attachments_dir: &Path,
+    older_than: Duration,
+) -> Result<usize, String> {
+    let mut entries = match tokio::fs::read_dir(attachments_dir).await {
+        Ok(entries) => entries,
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+        Err(error) => return Err(format!("Failed to read attachments dir: {}", error)),
+    };
+
+    let now = SystemTime::now();
+    let mut removed = 0;
+    while let Some(entry) = entries
+        .next_entry()
+        .await
+        .map_err(|e| format!("Failed to read attachments dir: {}", e))?
+    {
+        let Ok(metadata) = entry.metadata().await else {
+            continue;
+        };
+        if !metadata.is_file() {
+            continue;
+        }
+        let expired = metadata
+            .modified()
+            .ok()
+            .and_then(|modified| now.duration_since(modified).ok())
+            .is_some_and(|age| age > older_than);
+        if !expired {
+            continue;
+        }
+        match tokio::fs::remove_file(entry.path()).await {
+            Ok(()) => removed += 1,
+            Err(error) => log::warn!(
+                "[FeishuGateway] Failed to remove attachment {}: {}",
+                entry.path().display(),
+                error
+            ),
+        }
+    }
+    Ok(removed)
+}
+
+/// Sweep stale downloads now and then once per interval until the gateway stops
+async fn run_attachment_cleanup(attachments_dir: PathBuf, mut stop_rx: watch::Receiver<bool>) {
+    loop {
+        match cleanup_attachments(&attachments_dir, ATTACHMENT_RETENTION).await {
+            Ok(0) => {}
+            Ok(removed) => log::info!("[FeishuGateway] Removed {} stale attachments", removed),
+            Err(error) => log::warn!("[FeishuGateway] Attachment cleanup failed: {}", error),
+        }
+        if !sleep_unless_stopped(&mut stop_rx, ATTACHMENT_CLEANUP_INTERVAL_MS).await {
+            break;
+        }
+    }
 }
 
 async fn save_attachment_file(
@@ -208,7 +651,8 @@ async fn save_attachment_file(
 
 fn build_attachment_filename(prefix: &str, original_name: Option<&str>, suffix: &str) -> String {
     let safe_name = original_name
-        .map(|name| name.replace('/', "_"))
+        .map(sanitize_attachment_name)
+        .filter(|name| !name.is_empty())
         .unwrap_or_else(|| format!("{}-{}", prefix, suffix));
     if safe_name.contains('.') {
         safe_name
@@ -217,6 +661,94 @@ fn build_attachment_filename(prefix: &str, original_name: Option<&str>, suffix:
     }
 }
 
+/// Reduce a sender-supplied file name to a single safe path component: path
+/// separators, `.`/`..` segments, control and reserved characters are removed
+fn sanitize_attachment_name(name: &str) -> String {
+    let joined = name
+        .split(['/', '\\'])
+        .map(str::trim)
+        .filter(|segment| !segment.is_empty() && *segment != "." && *segment != "..")
+        .collect::<Vec<_>>()
+        .join("_");
+    let mut cleaned: String = joined
+        .chars()
+        .filter(|c| !c.is_control())
+        .map(|c| match c {
+            ':' | '*' | '?' | '"' | '<' | '>' | '|' => '_',
+            other => other,
+        })
+        .collect();
+    while cleaned.contains("..") {
+        cleaned = cleaned.replace("..", ".");
+    }
+    // No hidden files or names that start like a relative path
+    cleaned
+        .trim_start_matches(['.', ' '])
+        .trim_end_matches([' ', '.'])
+        .to_string()
+}
+
+/// Detect an attachment's MIME type from its leading bytes, ignoring what the
+/// sender declared. Undetected content is `text/plain` when it is valid UTF-8.
+fn sniff_attachment_mime(data: &[u8]) -> String {
+    match infer::get(data) {
+        Some(kind) => kind.mime_type().to_string(),
+        None if std::str::from_utf8(data).is_ok() => "text/plain".to_string(),
+        None => "application/octet-stream".to_string(),
+    }
+}
+
+fn mime_type_allowed(allowed: &[String], mime_type: &str) -> bool {
+    allowed.iter().any(|entry| {
+        let entry = entry.trim().to_ascii_lowercase();
+        match entry.strip_suffix("/*") {
+            Some(family) => mime_type
+                .split_once('/')
+                .is_some_and(|(kind, _)| kind == family),
+            None => entry == mime_type,
+        }
+    })
+}
+
+/// Check a downloaded attachment against the MIME allowlist and the family its
+/// message type implies; returns the sniffed MIME type
+fn validate_inbound_attachment(
+    message_type: &str,
+    data: &[u8],
+    allowed: Option<&[String]>,
+) -> Result<String, String> {
+    let mime_type = sniff_attachment_mime(data);
+    let expected_family = match message_type {
+        "image" => Some("image"),
+        "audio" => Some("audio"),
+        "video" | "media" => Some("video"),
+        _ => None,
+    };
+    if let Some(family) = expected_family {
+        if !mime_type.starts_with(&format!("{}/", family)) {
+            return Err(format!(
+                "{} message content is {}, not {}/*",
+                message_type, mime_type, family
+            ));
+        }
+    }
+    let defaults;
+    let allowed = match allowed {
+        Some(allowed) => allowed,
+        None => {
+            defaults = DEFAULT_ALLOWED_ATTACHMENT_MIME_TYPES
+                .iter()
+                .map(|mime| mime.to_string())
+                .collect::<Vec<_>>();
+            &defaults
+        }
+    };
+    if !mime_type_allowed(allowed, &mime_type) {
+        return Err(format!("attachment type {} is not allowed", mime_type));
+    }
+    Ok(mime_type)
+}
+
 /// Response from Feishu tenant access token endpoint
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct TenantAccessTokenResponse {
@@ -264,6 +796,45 @@ async fn get_tenant_access_token(app_id: &str, app_secret: &str) -> Result<Strin
         .ok_or_else(|| "No tenant_access_token in response".to_string())
 }
 
+#[derive(Debug, Clone, Deserialize)]
+struct BotInfoResponse {
+    code: i32,
+    #[serde(default)]
+    msg: String,
+    bot: Option<BotInfo>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct BotInfo {
+    #[serde(default)]
+    open_id: String,
+}
+
+/// Look up the bot's own open_id, used to recognise mentions in group chats
+async fn get_bot_open_id(app_id: &str, app_secret: &str) -> Result<String, String> {
+    let tenant_token = get_tenant_access_token(app_id, app_secret).await?;
+    let response = reqwest::Client::new()
+        .get("https://open.feishu.cn/open-apis/bot/v3/info")
+        .header("Authorization", format!("Bearer {}", tenant_token))
+        .send()
+        .await
+        .map_err(|e| format!("HTTP request failed: {}", e))?;
+    let info: BotInfoResponse = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse bot info response: {}", e))?;
+    if info.code != 0 {
+        return Err(format!(
+            "Bot info request failed: {} - {}",
+            info.code, info.msg
+        ));
+    }
+    info.bot
+        .map(|bot| bot.open_id)
+        .filter(|open_id| !open_id.is_empty())
+        .ok_or_else(|| "No bot open_id in response".to_string())
+}
+
 /// Download resource from message using Feishu API
 /// Uses /open-apis/im/v1/messages/{message_id}/resources/{file_key} endpoint
 async fn download_message_resource(
@@ -318,12 +889,61 @@ fn parse_text_content(content: &str) -> String {
         .unwrap_or_else(|| content.to_string())
 }
 
+/// Text standing in for message types that carry no downloadable content
+fn placeholder_text(message_type: &str) -> Option<&'static str> {
+    match message_type {
+        "sticker" => Some(STICKER_PLACEHOLDER),
+        _ => None,
+    }
+}
+
+fn video_filename(parsed: Option<&Value>, file_key: &str) -> String {
+    let filename_from_content = parsed
+        .and_then(|value| value.get("file_name"))
+        .and_then(|value| value.as_str());
+    build_attachment_filename(
+        FEISHU_MEDIA_PREFIX,
+        filename_from_content.or(Some(&format!("video-{}.mp4", file_key))),
+        "video",
+    )
+}
+
+fn video_attachment(
+    parsed: Option<&Value>,
+    file_key: &str,
+    filename: String,
+    file_path: String,
+    mime_type: String,
+    size: u64,
+) -> FeishuRemoteAttachment {
+    // Feishu reports video duration in milliseconds
+    let duration_seconds = parsed
+        .and_then(|value| value.get("duration"))
+        .and_then(|value| value.as_u64())
+        .map(|duration_ms| (duration_ms / 1000) as u32);
+    let caption = parsed
+        .and_then(|value| value.get("file_name"))
+        .and_then(|value| value.as_str())
+        .map(|name| name.to_string());
+    FeishuRemoteAttachment {
+        id: file_key.to_string(),
+        attachment_type: "video".to_string(),
+        mime_type,
+        file_path,
+        filename,
+        size,
+        duration_seconds,
+        caption,
+    }
+}
+
 async fn build_message_payload(
     app_handle: &AppHandle,
     client: &LarkClient,
     message_type: &str,
     content: &str,
     message_id: &str,
+    allowed_mime_types: Option<&[String]>,
 ) -> Result<(String, Vec<FeishuRemoteAttachment>), String> {
     let mut text_parts: Vec<String> = Vec::new();
     let mut attachments: Vec<FeishuRemoteAttachment> = Vec::new();
@@ -340,6 +960,10 @@ async fn build_message_payload(
         text_parts.push(text.to_string());
     }
 
+    if let Some(placeholder) = placeholder_text(message_type) {
+        text_parts.push(placeholder.to_string());
+    }
+
     let Some(attachments_dir) = attachments_root(app_handle).await? else {
         return Ok((text_parts.join("\n"), attachments));
     };
@@ -355,7 +979,18 @@ async fn build_message_payload(
             match download_message_resource(client, message_id, image_key, "image").await {
                 Ok(image_data) => {
                     let size = image_data.len() as u64;
-                    if size <= MAX_FEISHU_MEDIA_BYTES {
+                    let mime_type =
+                        validate_inbound_attachment(message_type, &image_data, allowed_mime_types)
+                            .inspect_err(|reason| {
+                                log::warn!(
+                                    "[FeishuGateway] Rejecting image {}: {}",
+                                    image_key,
+                                    reason
+                                )
+                            });
+                    if let Some(mime_type) =
+                        mime_type.ok().filter(|_| size <= MAX_FEISHU_MEDIA_BYTES)
+                    {
                         let filename = build_attachment_filename(
                             FEISHU_MEDIA_PREFIX,
                             Some(&format!("image-{}", image_key)),
@@ -368,7 +1003,7 @@ async fn build_message_payload(
                             attachment_type: "image".to_string(),
                             file_path: saved_path,
                             filename,
-                            mime_type: "image/png".to_string(),
+                            mime_type,
                             size,
                             duration_seconds: None,
                             caption: None,
@@ -394,7 +1029,19 @@ async fn build_message_payload(
             match download_message_resource(client, message_id, file_key, message_type).await {
                 Ok(file_data) => {
                     let size = file_data.len() as u64;
-                    if size <= MAX_FEISHU_MEDIA_BYTES {
+                    let mime_type =
+                        validate_inbound_attachment(message_type, &file_data, allowed_mime_types)
+                            .inspect_err(|reason| {
+                                log::warn!(
+                                    "[FeishuGateway] Rejecting {} {}: {}",
+                                    message_type,
+                                    file_key,
+                                    reason
+                                )
+                            });
+                    if let Some(mime_type) =
+                        mime_type.ok().filter(|_| size <= MAX_FEISHU_MEDIA_BYTES)
+                    {
                         let filename_from_content = parsed
                             .as_ref()
                             .and_then(|value| value.get("file_name"))
@@ -417,11 +1064,7 @@ async fn build_message_payload(
                             attachment_type: attachment_type.to_string(),
                             file_path: saved_path,
                             filename,
-                            mime_type: if message_type == "audio" {
-                                "audio/mpeg".to_string()
-                            } else {
-                                "application/octet-stream".to_string()
-                            },
+                            mime_type,
                             size,
                             duration_seconds: None,
                             caption,
@@ -435,6 +1078,50 @@ async fn build_message_payload(
         }
     }
 
+    if message_type == "video" {
+        if let Some(file_key) = parsed
+            .as_ref()
+            .and_then(|value| value.get("file_key"))
+            .and_then(|value| value.as_str())
+        {
+            match download_message_resource(client, message_id, file_key, "file").await {
+                Ok(video_data) => {
+                    let size = video_data.len() as u64;
+                    match validate_inbound_attachment(message_type, &video_data, allowed_mime_types)
+                    {
+                        Err(reason) => {
+                            log::warn!("[FeishuGateway] Rejecting video {}: {}", file_key, reason)
+                        }
+                        Ok(mime_type) if size <= MAX_FEISHU_MEDIA_BYTES => {
+                            let filename = video_filename(parsed.as_ref(), file_key);
+                            let saved_path =
+                                save_attachment_file(&attachments_dir, &filename, &video_data)
+                                    .await?;
+                            attachments.push(video_attachment(
+                                parsed.as_ref(),
+                                file_key,
+                                filename,
+                                saved_path,
+                                mime_type,
+                                size,
+                            ));
+                        }
+                        Ok(_) => {
+                            log::warn!(
+                                "[FeishuGateway] Skipping video over size cap bytes={}",
+                                size
+                            );
+                        }
+                    }
+                }
+                Err(error) => {
+                    log::warn!("[FeishuGateway] Failed to download video: {}", error);
+                    text_parts.push(format!("[Video: {}]", file_key));
+                }
+            }
+        }
+    }
+
     if message_type == "file" && attachments.is_empty() {
         text_parts.push(format!("[file: {}]", message_id));
     }
@@ -442,13 +1129,208 @@ async fn build_message_payload(
     Ok((text_parts.join("\n").trim().to_string(), attachments))
 }
 
+/// Replaces the text of a sent message; abstracted so edit throttling can be tested
+#[async_trait::async_trait]
+pub trait FeishuMessageEditor: Send + Sync {
+    async fn edit(&self, message_id: &str, text: &str) -> Result<(), String>;
+}
+
+struct LarkMessageEditor {
+    client: LarkClient,
+    limiter: Arc<FeishuRateLimiter>,
+}
+
+#[async_trait::async_trait]
+impl FeishuMessageEditor for LarkMessageEditor {
+    async fn edit(&self, message_id: &str, text: &str) -> Result<(), String> {
+        edit_message_text(&self.client, &self.limiter, message_id, text).await
+    }
+}
+
+async fn edit_message_text(
+    client: &LarkClient,
+    limiter: &FeishuRateLimiter,
+    message_id: &str,
+    text: &str,
+) -> Result<(), String> {
+    let content = serde_json::json!({ "text": text }).to_string();
+    call_with_rate_limit(limiter, DEFAULT_ERROR_BACKOFF_MS, move || {
+        let update_request = UpdateMessageRequest::builder()
+            .content(content.clone())
+            .build();
+        async move {
+            client
+                .im
+                .v1
+                .message
+                .update(message_id, update_request, None)
+                .await
+                .map_err(|error| format!("Feishu edit message failed: {error:?}"))?;
+
+            Ok(())
+        }
+    })
+    .await
+}
+
+#[derive(Debug, Default)]
+struct StreamedMessage {
+    pending: Option<String>,
+    last_sent: Option<String>,
+    last_sent_at: Option<Instant>,
+    scheduled: bool,
+}
+
+/// Coalesces streamed text for a message into at most one edit per interval.
+/// The newest text always wins and is sent on the trailing edge; text identical
+/// to what was last sent is never re-sent.
+pub struct FeishuMessageUpdater {
+    editor: Arc<dyn FeishuMessageEditor>,
+    interval: Duration,
+    messages: Mutex<HashMap<String, StreamedMessage>>,
+    // Serializes edits so a slow in-flight edit cannot land after a newer one
+    send_lock: Mutex<()>,
+}
+
+impl std::fmt::Debug for FeishuMessageUpdater {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("FeishuMessageUpdater")
+            .field("interval", &self.interval)
+            .finish_non_exhaustive()
+    }
+}
+
+impl FeishuMessageUpdater {
+    pub fn new(editor: Arc<dyn FeishuMessageEditor>, interval: Duration) -> Self {
+        Self {
+            editor,
+            interval,
+            messages: Mutex::new(HashMap::new()),
+            send_lock: Mutex::new(()),
+        }
+    }
+
+    /// Queue `text` as the latest content of `message_id`
+    pub async fn update(self: &Arc<Self>, message_id: &str, text: &str) {
+        let wait = {
+            let mut messages = self.messages.lock().await;
+            let entry = messages.entry(message_id.to_string()).or_default();
+            if entry.last_sent.as_deref() == Some(text) {
+                entry.pending = None;
+                return;
+            }
+            entry.pending = Some(text.to_string());
+            if entry.scheduled {
+                return;
+            }
+            entry.scheduled = true;
+            entry
+                .last_sent_at
+                .map(|sent_at| self.interval.saturating_sub(sent_at.elapsed()))
+                .unwrap_or(Duration::ZERO)
+        };
+
+        let updater = self.clone();
+        let message_id = message_id.to_string();
+        tokio::spawn(async move { updater.drain(message_id, wait).await });
+    }
+
+    /// Send any pending text now and forget the message
+    pub async fn finish(&self, message_id: &str) -> Result<(), String> {
+        let _send = self.send_lock.lock().await;
+        let entry = self.messages.lock().await.remove(message_id);
+        let Some(entry) = entry else {
+            return Ok(());
+        };
+        match entry.pending {
+            Some(text) if entry.last_sent.as_deref() != Some(text.as_str()) => {
+                self.editor.edit(message_id, &text).await
+            }
+            _ => Ok(()),
+        }
+    }
+
+    async fn drain(&self, message_id: String, mut wait: Duration) {
+        loop {
+            sleep(wait).await;
+            let _send = self.send_lock.lock().await;
+            let text = {
+                let mut messages = self.messages.lock().await;
+                let Some(entry) = messages.get_mut(&message_id) else {
+                    // Finished while we were waiting
+                    return;
+                };
+                match entry.pending.take() {
+                    Some(text) => text,
+                    None => {
+                        entry.scheduled = false;
+                        return;
+                    }
+                }
+            };
+
+            let result = self.editor.edit(&message_id, &text).await;
+            if let Err(error) = &result {
+                log::warn!(
+                    "[FeishuGateway] Streamed edit failed message_id={}: {}",
+                    message_id,
+                    error
+                );
+            }
+
+            let mut messages = self.messages.lock().await;
+            let Some(entry) = messages.get_mut(&message_id) else {
+                return;
+            };
+            entry.last_sent_at = Some(Instant::now());
+            if result.is_ok() {
+                entry.last_sent = Some(text);
+            } else if entry.pending.is_none() {
+                // Retry the failed text on the next tick unless newer text replaced it
+                entry.pending = Some(text);
+            }
+            if entry.pending.is_none() {
+                entry.scheduled = false;
+                return;
+            }
+            wait = self.interval;
+        }
+    }
+}
+
 async fn run_ws_loop(
     app_handle: AppHandle,
     state: FeishuGatewayState,
     stop_rx: watch::Receiver<bool>,
 ) {
+    match attachments_root(&app_handle).await {
+        Ok(Some(attachments_dir)) => {
+            tokio::spawn(run_attachment_cleanup(attachments_dir, stop_rx.clone()));
+        }
+        Ok(None) => {}
+        Err(error) => log::warn!("[FeishuGateway] Attachment cleanup disabled: {}", error),
+    }
+
+    let connection_state = state.clone();
+    run_gateway_loop(state, stop_rx, move |config| {
+        start_ws_connection(app_handle.clone(), connection_state.clone(), config)
+    })
+    .await;
+}
+
+/// Drive the gateway until it is stopped or disabled. `connect` runs one
+/// websocket session; it is dropped mid-flight when a stop is signalled so the
+/// runtime thread can exit and release its resources.
+async fn run_gateway_loop<F, Fut>(
+    state: FeishuGatewayState,
+    mut stop_rx: watch::Receiver<bool>,
+    mut connect: F,
+) where
+    F: FnMut(FeishuConfig) -> Fut,
+    Fut: Future<Output = Result<(), String>>,
+{
     loop {
-        if stop_rx.has_changed().unwrap_or(false) && *stop_rx.borrow() {
+        if *stop_rx.borrow() {
             break;
         }
 
@@ -461,14 +1343,23 @@ async fn run_ws_loop(
             break;
         }
 
-        if !config.enabled || config.app_id.is_empty() || config.app_secret.is_empty() {
+        if !config.enabled {
+            log::info!("[FeishuGateway] Gateway disabled, shutting down runtime");
+            let mut gateway = state.lock().await;
+            gateway.running = false;
+            gateway.stop_tx = None;
+            break;
+        }
+
+        if config.app_id.is_empty() || config.app_secret.is_empty() {
             log::debug!(
-                "[FeishuGateway] Skipping ws loop tick (enabled={}, app_id_set={}, app_secret_set={})",
-                config.enabled,
+                "[FeishuGateway] Skipping ws loop tick (app_id_set={}, app_secret_set={})",
                 !config.app_id.is_empty(),
                 !config.app_secret.is_empty()
             );
-            sleep(Duration::from_millis(DEFAULT_ERROR_BACKOFF_MS)).await;
+            if !sleep_unless_stopped(&mut stop_rx, DEFAULT_ERROR_BACKOFF_MS).await {
+                break;
+            }
             continue;
         }
 
@@ -476,7 +1367,14 @@ async fn run_ws_loop(
             "[FeishuGateway] Starting ws connection (allowed_open_ids={})",
             config.allowed_open_ids.len()
         );
-        let result = start_ws_connection(app_handle.clone(), state.clone(), config.clone()).await;
+        record_connection_attempt(&mut *state.lock().await);
+        let result = tokio::select! {
+            result = connect(config.clone()) => result,
+            _ = stop_rx.changed() => {
+                log::info!("[FeishuGateway] Stop signalled, closing ws connection");
+                break;
+            }
+        };
         if let Err(error) = result {
             let backoff = {
                 let mut gateway = state.lock().await;
@@ -484,13 +1382,45 @@ async fn run_ws_loop(
                 gateway.backoff_ms = compute_backoff_ms(gateway.backoff_ms);
                 gateway.backoff_ms
             };
-            sleep(Duration::from_millis(backoff)).await;
+            if !sleep_unless_stopped(&mut stop_rx, backoff).await {
+                break;
+            }
         } else {
             let mut gateway = state.lock().await;
             clear_error_state(&mut gateway);
             gateway.backoff_ms = backoff_ms;
         }
     }
+    log::info!("[FeishuGateway] Run loop exited");
+}
+
+/// Sleep for `duration_ms`, returning false early if a stop is signalled
+async fn sleep_unless_stopped(stop_rx: &mut watch::Receiver<bool>, duration_ms: u64) -> bool {
+    tokio::select! {
+        _ = sleep(Duration::from_millis(duration_ms)) => true,
+        _ = stop_rx.changed() => false,
+    }
+}
+
+/// Signal the runtime thread to exit and mark the gateway as stopped
+/// Signal the connection task to stop and mark the gateway stopped; returns
+/// whether it was running
+fn signal_stop(gateway: &mut FeishuGateway) -> bool {
+    let was_running = gateway.running;
+    if let Some(stop_tx) = gateway.stop_tx.take() {
+        let _ = stop_tx.send(true);
+    }
+    gateway.running = false;
+    was_running
+}
+
+async fn stop_gateway(state: &FeishuGatewayState) -> bool {
+    signal_stop(&mut state.lock().await)
+}
+
+/// `stop_gateway` for sync contexts such as app exit; must not run on an async runtime
+pub fn stop_gateway_blocking(state: &FeishuGatewayState) -> bool {
+    signal_stop(&mut state.blocking_lock())
 }
 
 async fn start_ws_connection(
@@ -501,15 +1431,36 @@ async fn start_ws_connection(
     let client = Arc::new(build_client(&config)?);
     let ws_config = Arc::new(client.config.clone());
     let open_id_allowlist = config.allowed_open_ids.clone();
+    let allowed_mime_types = config.allowed_attachment_mime_types.clone();
     let verification_token = config.verification_token.clone();
     let encrypt_key = config.encrypt_key.clone();
+    let allow_group_chats = config.allow_group_chats;
+    let bot_open_id = if allow_group_chats {
+        match get_bot_open_id(&config.app_id, &config.app_secret).await {
+            Ok(open_id) => open_id,
+            Err(error) => {
+                log::warn!(
+                    "[FeishuGateway] Group chats enabled but bot open_id lookup failed: {}",
+                    error
+                );
+                String::new()
+            }
+        }
+    } else {
+        String::new()
+    };
+
+    let dedup = state.lock().await.dedup.clone();
 
     let handler_app = app_handle.clone();
     let handler = EventDispatcherHandler::builder()
         .register_p2_im_message_receive_v1(move |event| {
             let client = client.clone();
+            let dedup = dedup.clone();
             let app_handle = handler_app.clone();
             let open_id_allowlist = open_id_allowlist.clone();
+            let allowed_mime_types = allowed_mime_types.clone();
+            let bot_open_id = bot_open_id.clone();
             let state = state.clone();
             tokio::spawn(async move {
                 let sender = event.event.sender;
@@ -522,19 +1473,37 @@ async fn start_ws_connection(
                 }
 
                 let message = event.event.message;
-                if chat_kind(&message.chat_type) != FeishuChatKind::P2p {
+                if !dedup.first_seen(&message.message_id, Instant::now()) {
                     log::debug!(
-                        "[FeishuGateway] Ignoring non-p2p chat type={}",
-                        message.chat_type
+                        "[FeishuGateway] Dropping redelivered message message_id={}",
+                        message.message_id
                     );
                     return;
                 }
 
                 let open_id = sender.sender_id.open_id;
-                if !is_open_id_allowed(&open_id_allowlist, &open_id) {
+                let mentions = parse_mentions(
+                    &serde_json::to_value(&message.mentions).unwrap_or(Value::Null),
+                );
+                let Some(conversation_id) = resolve_conversation_id(
+                    chat_kind(&message.chat_type),
+                    allow_group_chats,
+                    is_bot_mentioned(&mentions, &bot_open_id),
+                    &open_id,
+                    &message.chat_id,
+                ) else {
                     log::debug!(
-                        "[FeishuGateway] Open id not in allowlist open_id={} count={}",
-                        open_id,
+                        "[FeishuGateway] Ignoring chat type={} (group chats allowed={})",
+                        message.chat_type,
+                        allow_group_chats
+                    );
+                    return;
+                };
+
+                if !is_open_id_allowed(&open_id_allowlist, &conversation_id) {
+                    log::debug!(
+                        "[FeishuGateway] Conversation not in allowlist id={} count={}",
+                        conversation_id,
                         open_id_allowlist.len()
                     );
                     return;
@@ -547,12 +1516,13 @@ async fn start_ws_connection(
                     message.message_type
                 );
 
-                let (text, attachments) = match build_message_payload(
+                let (mut text, attachments) = match build_message_payload(
                     &app_handle,
                     &client,
                     &message.message_type,
                     &message.content,
                     &message.message_id,
+                    allowed_mime_types.as_deref(),
                 )
                 .await
                 {
@@ -563,6 +1533,10 @@ async fn start_ws_connection(
                     }
                 };
 
+                if !mentions.is_empty() {
+                    text = strip_mention_keys(&text, &mentions);
+                }
+
                 if text.trim().is_empty() && attachments.is_empty() {
                     log::debug!(
                         "[FeishuGateway] Ignoring empty message open_id={} message_id={}",
@@ -587,10 +1561,11 @@ async fn start_ws_connection(
 
                 let message_id = message.message_id.clone();
                 let payload = FeishuInboundMessage {
-                    chat_id: open_id.clone(),
+                    chat_id: conversation_id,
                     message_id: message_id.clone(),
                     text,
                     open_id: open_id.clone(),
+                    chat_type: message.chat_type.clone(),
                     date,
                     attachments: if attachments.is_empty() {
                         None
@@ -649,6 +1624,15 @@ pub async fn feishu_set_config(
     {
         let mut gateway = state.lock().await;
         gateway.config = config.clone();
+        gateway.updater = None;
+        gateway.dedup.configure(&config);
+    }
+
+    if !config.enabled {
+        if stop_gateway(state.inner()).await {
+            log::info!("[FeishuGateway] Disabled via config, stopping gateway");
+        }
+        return Ok(());
     }
 
     if config.enabled && !config.app_id.is_empty() && !config.app_secret.is_empty() {
@@ -719,11 +1703,7 @@ pub async fn feishu_start(
 
 #[tauri::command]
 pub async fn feishu_stop(state: State<'_, FeishuGatewayState>) -> Result<(), String> {
-    let mut gateway = state.lock().await;
-    if let Some(stop_tx) = gateway.stop_tx.take() {
-        let _ = stop_tx.send(true);
-    }
-    gateway.running = false;
+    stop_gateway(state.inner()).await;
     log::info!("[FeishuGateway] Stop requested");
     Ok(())
 }
@@ -736,20 +1716,47 @@ pub struct FeishuGatewayStatus {
     pub last_error: Option<String>,
     pub last_error_at_ms: Option<i64>,
     pub backoff_ms: u64,
+    pub reconnect_count: u64,
+    pub last_connected_at_ms: Option<i64>,
+    pub consecutive_failures: u32,
+    pub throttle: FeishuThrottleStatus,
 }
 
 #[tauri::command]
 pub async fn feishu_get_status(
     state: State<'_, FeishuGatewayState>,
 ) -> Result<FeishuGatewayStatus, String> {
+    Ok(gateway_status(state.inner()).await)
+}
+
+pub async fn gateway_status(state: &FeishuGatewayState) -> FeishuGatewayStatus {
     let gateway = state.lock().await;
-    Ok(FeishuGatewayStatus {
+    FeishuGatewayStatus {
         running: gateway.running,
         last_event_at_ms: gateway.last_event_at_ms,
         last_error: gateway.last_error.clone(),
         last_error_at_ms: gateway.last_error_at_ms,
         backoff_ms: gateway.backoff_ms,
-    })
+        reconnect_count: gateway.reconnect_count,
+        last_connected_at_ms: gateway.last_connected_at_ms,
+        consecutive_failures: gateway.consecutive_failures,
+        throttle: gateway.limiter.status(),
+    }
+}
+
+/// Drop the current websocket session and start a fresh one with backoff reset
+#[tauri::command]
+pub async fn feishu_reconnect(
+    app_handle: AppHandle,
+    state: State<'_, FeishuGatewayState>,
+) -> Result<(), String> {
+    let enabled = state.lock().await.config.enabled;
+    if !enabled {
+        return Err("Feishu gateway is disabled".to_string());
+    }
+    stop_gateway(state.inner()).await;
+    log::info!("[FeishuGateway] Manual reconnect requested");
+    start_gateway(app_handle, state.inner().clone()).await
 }
 
 #[tauri::command]
@@ -763,9 +1770,9 @@ pub async fn feishu_send_message(
     state: State<'_, FeishuGatewayState>,
     request: FeishuSendMessageRequest,
 ) -> Result<FeishuSendMessageResponse, String> {
-    let config = {
+    let (config, limiter) = {
         let gateway = state.lock().await;
-        gateway.config.clone()
+        (gateway.config.clone(), gateway.limiter.clone())
     };
 
     let client = build_client(&config)?;
@@ -774,37 +1781,450 @@ pub async fn feishu_send_message(
         request.open_id,
         request.text.len()
     );
-    let body = CreateMessageRequestBody::builder()
-        .receive_id(request.open_id.clone())
-        .msg_type("text")
-        .content(serde_json::json!({ "text": request.text }).to_string())
-        .build();
-    let req = CreateMessageRequest::builder()
-        .receive_id_type("open_id")
-        .request_body(body)
-        .build();
+    let content = serde_json::json!({ "text": request.text }).to_string();
+    match request
+        .reply_to
+        .as_deref()
+        .filter(|message_id| !message_id.is_empty())
+    {
+        Some(parent_id) => reply_message(&client, &limiter, parent_id, "text", content).await,
+        None => create_message(&client, &limiter, &request.open_id, "text", content).await,
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct ReplyMessageResponse {
+    code: i32,
+    #[serde(default)]
+    msg: String,
+    data: Option<ReplyMessageData>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct ReplyMessageData {
+    message_id: String,
+}
+
+/// URL and body for `POST /im/v1/messages/{message_id}/reply`
+fn build_reply_request(parent_message_id: &str, msg_type: &str, content: &str) -> (String, Value) {
+    (
+        format!(
+            "https://open.feishu.cn/open-apis/im/v1/messages/{}/reply",
+            parent_message_id
+        ),
+        json!({
+            "msg_type": msg_type,
+            "content": content,
+            "uuid": uuid::Uuid::new_v4().to_string(),
+        }),
+    )
+}
+
+/// Reply to `parent_message_id` so the response is attached to the source message
+async fn reply_message(
+    client: &LarkClient,
+    limiter: &FeishuRateLimiter,
+    parent_message_id: &str,
+    msg_type: &str,
+    content: String,
+) -> Result<FeishuSendMessageResponse, String> {
+    let (url, body) = build_reply_request(parent_message_id, msg_type, &content);
+    let http_client = reqwest::Client::new();
+    let (url, body, http_client) = (&url, &body, &http_client);
+    call_with_rate_limit(limiter, DEFAULT_ERROR_BACKOFF_MS, move || async move {
+        let tenant_token =
+            get_tenant_access_token(&client.config.app_id, &client.config.app_secret).await?;
+        let response = http_client
+            .post(url)
+            .header("Authorization", format!("Bearer {}", tenant_token))
+            .json(body)
+            .send()
+            .await
+            .map_err(|e| format!("HTTP request failed: {}", e))?;
+        let reply: ReplyMessageResponse = response
+            .json()
+            .await
+            .map_err(|e| format!("Failed to parse reply response: {}", e))?;
+        if reply.code != 0 {
+            return Err(format!(
+                "Feishu reply message failed: {} - {}",
+                reply.code, reply.msg
+            ));
+        }
+        reply
+            .data
+            .map(|data| FeishuSendMessageResponse {
+                message_id: data.message_id,
+            })
+            .ok_or_else(|| "No message_id in reply response".to_string())
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn feishu_reply_message(
+    state: State<'_, FeishuGatewayState>,
+    message_id: String,
+    text: String,
+) -> Result<FeishuSendMessageResponse, String> {
+    let (config, limiter) = {
+        let gateway = state.lock().await;
+        (gateway.config.clone(), gateway.limiter.clone())
+    };
+
+    let client = build_client(&config)?;
+    log::debug!(
+        "[FeishuGateway] replyMessage message_id={} text_len={}",
+        message_id,
+        text.len()
+    );
+    let content = serde_json::json!({ "text": text }).to_string();
+    reply_message(&client, &limiter, &message_id, "text", content).await
+}
+
+async fn create_message(
+    client: &LarkClient,
+    limiter: &FeishuRateLimiter,
+    receive_id: &str,
+    msg_type: &str,
+    content: String,
+) -> Result<FeishuSendMessageResponse, String> {
+    call_with_rate_limit(limiter, DEFAULT_ERROR_BACKOFF_MS, move || {
+        let body = CreateMessageRequestBody::builder()
+            .receive_id(receive_id.to_string())
+            .msg_type(msg_type)
+            .content(content.clone())
+            .build();
+        let req = CreateMessageRequest::builder()
+            .receive_id_type(receive_id_type(receive_id))
+            .request_body(body)
+            .build();
+        async move {
+            let message = client
+                .im
+                .v1
+                .message
+                .create(req, None)
+                .await
+                .map_err(|error| format!("Feishu send message failed: {error:?}"))?;
+
+            Ok(FeishuSendMessageResponse {
+                message_id: message.message_id,
+            })
+        }
+    })
+    .await
+}
+
+fn markdown_card(markdown: &str) -> Value {
+    json!({
+        "config": { "wide_screen_mode": true },
+        "elements": [
+            { "tag": "markdown", "content": markdown }
+        ]
+    })
+}
+
+/// Truncate `text` to at most `max_bytes` without splitting a UTF-8 character
+fn truncate_to_char_boundary(text: &str, max_bytes: usize) -> &str {
+    if text.len() <= max_bytes {
+        return text;
+    }
+    let mut end = max_bytes;
+    while !text.is_char_boundary(end) {
+        end -= 1;
+    }
+    &text[..end]
+}
+
+/// Wrap markdown in an interactive card, truncating with a notice so the
+/// serialized card stays under Feishu's content limit
+fn build_markdown_card(markdown: &str) -> String {
+    let card = markdown_card(markdown).to_string();
+    if card.len() <= MAX_CARD_CONTENT_BYTES {
+        return card;
+    }
+
+    let mut budget = MAX_CARD_CONTENT_BYTES
+        .saturating_sub(markdown_card(CARD_TRUNCATION_NOTICE).to_string().len());
+    loop {
+        let truncated = format!(
+            "{}{}",
+            truncate_to_char_boundary(markdown, budget).trim_end(),
+            CARD_TRUNCATION_NOTICE
+        );
+        let card = markdown_card(&truncated).to_string();
+        // JSON escaping can expand the text; shrink by the overshoot and retry
+        if card.len() <= MAX_CARD_CONTENT_BYTES || budget == 0 {
+            return card;
+        }
+        budget = budget.saturating_sub(card.len() - MAX_CARD_CONTENT_BYTES);
+    }
+}
+
+fn parse_card_json(card_json: Value) -> Result<String, String> {
+    let card = match card_json {
+        Value::String(raw) => serde_json::from_str::<Value>(&raw)
+            .map_err(|e| format!("Invalid Feishu card JSON: {}", e))?,
+        other => other,
+    };
+    if !card.is_object() {
+        return Err("Feishu card must be a JSON object".to_string());
+    }
+    let content = card.to_string();
+    if content.len() > MAX_CARD_CONTENT_BYTES {
+        return Err(format!(
+            "Feishu card is {} bytes, exceeding the {} byte limit",
+            content.len(),
+            MAX_CARD_CONTENT_BYTES
+        ));
+    }
+    Ok(content)
+}
+
+#[tauri::command]
+pub async fn feishu_send_card(
+    state: State<'_, FeishuGatewayState>,
+    open_id: String,
+    card_json: Value,
+) -> Result<FeishuSendMessageResponse, String> {
+    let (config, limiter) = {
+        let gateway = state.lock().await;
+        (gateway.config.clone(), gateway.limiter.clone())
+    };
+
+    let client = build_client(&config)?;
+    let content = parse_card_json(card_json)?;
+    log::debug!(
+        "[FeishuGateway] sendCard open_id={} content_len={}",
+        open_id,
+        content.len()
+    );
+    create_message(&client, &limiter, &open_id, "interactive", content).await
+}
+
+#[tauri::command]
+pub async fn feishu_send_markdown(
+    state: State<'_, FeishuGatewayState>,
+    open_id: String,
+    markdown: String,
+) -> Result<FeishuSendMessageResponse, String> {
+    let (config, limiter) = {
+        let gateway = state.lock().await;
+        (gateway.config.clone(), gateway.limiter.clone())
+    };
+
+    let client = build_client(&config)?;
+    log::debug!(
+        "[FeishuGateway] sendMarkdown open_id={} markdown_len={}",
+        open_id,
+        markdown.len()
+    );
+    create_message(
+        &client,
+        &limiter,
+        &open_id,
+        "interactive",
+        build_markdown_card(&markdown),
+    )
+    .await
+}
+
+/// Image formats accepted by the Feishu image upload API
+const SUPPORTED_IMAGE_MIME_TYPES: &[&str] = &[
+    "image/jpeg",
+    "image/png",
+    "image/webp",
+    "image/gif",
+    "image/tiff",
+    "image/bmp",
+    "image/vnd.microsoft.icon",
+];
+const IMAGE_UPLOAD_URL: &str = "https://open.feishu.cn/open-apis/im/v1/images";
+
+/// A local image checked and loaded for upload
+#[derive(Debug, Clone)]
+struct OutboundImage {
+    filename: String,
+    mime_type: &'static str,
+    data: Vec<u8>,
+}
 
-    let message = client
-        .im
-        .v1
-        .message
-        .create(req, None)
+/// Check the file exists, fits under the media cap and is a supported image, then load it
+async fn load_outbound_image(file_path: &str) -> Result<OutboundImage, String> {
+    let path = std::path::Path::new(file_path);
+    let metadata = tokio::fs::metadata(path)
         .await
-        .map_err(|error| format!("Feishu send message failed: {error:?}"))?;
+        .map_err(|e| format!("Image not found at {}: {}", file_path, e))?;
+    if !metadata.is_file() {
+        return Err(format!("Not a file: {}", file_path));
+    }
+    if metadata.len() > MAX_FEISHU_MEDIA_BYTES {
+        return Err(format!(
+            "Image is {} bytes, larger than the {} byte Feishu limit",
+            metadata.len(),
+            MAX_FEISHU_MEDIA_BYTES
+        ));
+    }
 
-    Ok(FeishuSendMessageResponse {
-        message_id: message.message_id,
+    let data = tokio::fs::read(path)
+        .await
+        .map_err(|e| format!("Failed to read image {}: {}", file_path, e))?;
+    let mime_type = infer::get(&data)
+        .map(|kind| kind.mime_type())
+        .and_then(|mime| {
+            SUPPORTED_IMAGE_MIME_TYPES
+                .iter()
+                .copied()
+                .find(|supported| *supported == mime)
+        })
+        .ok_or_else(|| format!("Unsupported image type: {}", file_path))?;
+    let filename = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| "image".to_string());
+
+    Ok(OutboundImage {
+        filename,
+        mime_type,
+        data,
     })
 }
 
+fn image_message_content(image_key: &str) -> String {
+    json!({ "image_key": image_key }).to_string()
+}
+
+/// Uploads images and sends messages; abstracted so the upload-then-send flow can be tested
+#[async_trait::async_trait]
+trait FeishuImageSender: Send + Sync {
+    /// Upload an image for use in messages, returning its `image_key`
+    async fn upload_image(&self, image: &OutboundImage) -> Result<String, String>;
+    async fn send(
+        &self,
+        receive_id: &str,
+        msg_type: &str,
+        content: String,
+    ) -> Result<FeishuSendMessageResponse, String>;
+}
+
+struct LarkImageSender {
+    client: LarkClient,
+    limiter: Arc<FeishuRateLimiter>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct ImageUploadResponse {
+    code: i32,
+    #[serde(default)]
+    msg: String,
+    data: Option<ImageUploadData>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+struct ImageUploadData {
+    image_key: String,
+}
+
+#[async_trait::async_trait]
+impl FeishuImageSender for LarkImageSender {
+    async fn upload_image(&self, image: &OutboundImage) -> Result<String, String> {
+        let client = &self.client;
+        let http_client = reqwest::Client::new();
+        let http_client = &http_client;
+        call_with_rate_limit(
+            &self.limiter,
+            DEFAULT_ERROR_BACKOFF_MS,
+            move || async move {
+                let tenant_token =
+                    get_tenant_access_token(&client.config.app_id, &client.config.app_secret)
+                        .await?;
+                let part = reqwest::multipart::Part::bytes(image.data.clone())
+                    .file_name(image.filename.clone())
+                    .mime_str(image.mime_type)
+                    .map_err(|e| format!("Invalid image mime type: {}", e))?;
+                let form = reqwest::multipart::Form::new()
+                    .text("image_type", "message")
+                    .part("image", part);
+                let response = http_client
+                    .post(IMAGE_UPLOAD_URL)
+                    .header("Authorization", format!("Bearer {}", tenant_token))
+                    .multipart(form)
+                    .send()
+                    .await
+                    .map_err(|e| format!("HTTP request failed: {}", e))?;
+                let upload: ImageUploadResponse = response
+                    .json()
+                    .await
+                    .map_err(|e| format!("Failed to parse image upload response: {}", e))?;
+                if upload.code != 0 {
+                    return Err(format!(
+                        "Feishu image upload failed: {} - {}",
+                        upload.code, upload.msg
+                    ));
+                }
+                upload
+                    .data
+                    .map(|data| data.image_key)
+                    .ok_or_else(|| "No image_key in upload response".to_string())
+            },
+        )
+        .await
+    }
+
+    async fn send(
+        &self,
+        receive_id: &str,
+        msg_type: &str,
+        content: String,
+    ) -> Result<FeishuSendMessageResponse, String> {
+        create_message(&self.client, &self.limiter, receive_id, msg_type, content).await
+    }
+}
+
+async fn send_image_with(
+    sender: &dyn FeishuImageSender,
+    receive_id: &str,
+    file_path: &str,
+) -> Result<FeishuSendMessageResponse, String> {
+    let image = load_outbound_image(file_path).await?;
+    let image_key = sender.upload_image(&image).await?;
+    sender
+        .send(receive_id, "image", image_message_content(&image_key))
+        .await
+}
+
+#[tauri::command]
+pub async fn feishu_send_image(
+    state: State<'_, FeishuGatewayState>,
+    open_id: String,
+    file_path: String,
+) -> Result<FeishuSendMessageResponse, String> {
+    let (config, limiter) = {
+        let gateway = state.lock().await;
+        (gateway.config.clone(), gateway.limiter.clone())
+    };
+
+    let sender = LarkImageSender {
+        client: build_client(&config)?,
+        limiter,
+    };
+    log::debug!(
+        "[FeishuGateway] sendImage open_id={} file_path={}",
+        open_id,
+        file_path
+    );
+    send_image_with(&sender, &open_id, &file_path).await
+}
+
 #[tauri::command]
 pub async fn feishu_edit_message(
     state: State<'_, FeishuGatewayState>,
     request: FeishuEditMessageRequest,
 ) -> Result<(), String> {
-    let config = {
+    let (config, limiter) = {
         let gateway = state.lock().await;
-        gateway.config.clone()
+        (gateway.config.clone(), gateway.limiter.clone())
     };
 
     let client = build_client(&config)?;
@@ -813,18 +2233,44 @@ pub async fn feishu_edit_message(
         request.message_id,
         request.text.len()
     );
-    let update_request = UpdateMessageRequest::builder()
-        .content(serde_json::json!({ "text": request.text }).to_string())
-        .build();
+    edit_message_text(&client, &limiter, &request.message_id, &request.text).await
+}
 
-    client
-        .im
-        .v1
-        .message
-        .update(&request.message_id, update_request, None)
-        .await
-        .map_err(|error| format!("Feishu edit message failed: {error:?}"))?;
+/// Stream text into a sent message; edits are throttled to stay under Feishu's
+/// update rate limit. Pass `finished` with the final text to flush it immediately.
+#[tauri::command]
+pub async fn feishu_stream_edit(
+    state: State<'_, FeishuGatewayState>,
+    message_id: String,
+    text: String,
+    finished: Option<bool>,
+) -> Result<(), String> {
+    let updater = {
+        let mut gateway = state.lock().await;
+        match &gateway.updater {
+            Some(updater) => updater.clone(),
+            None => {
+                let interval = Duration::from_millis(
+                    gateway
+                        .config
+                        .edit_interval_ms
+                        .unwrap_or(DEFAULT_EDIT_INTERVAL_MS),
+                );
+                let editor = LarkMessageEditor {
+                    client: build_client(&gateway.config)?,
+                    limiter: gateway.limiter.clone(),
+                };
+                let updater = Arc::new(FeishuMessageUpdater::new(Arc::new(editor), interval));
+                gateway.updater = Some(updater.clone());
+                updater
+            }
+        }
+    };
 
+    updater.update(&message_id, &text).await;
+    if finished.unwrap_or(false) {
+        updater.finish(&message_id).await?;
+    }
     Ok(())
 }
 
@@ -835,10 +2281,22 @@ pub fn default_state() -> FeishuGatewayState {
 #[cfg(test)]
 mod tests {
     use super::{
-        build_attachment_filename, chat_kind, is_open_id_allowed, parse_text_content, sender_kind,
-        FeishuChatKind, FeishuSenderKind,
+        build_attachment_filename, build_markdown_card, build_reply_request, call_with_rate_limit,
+        chat_kind, cleanup_attachments, clear_error_state, default_state, is_bot_mentioned,
+        is_open_id_allowed, parse_card_json, parse_mentions, parse_text_content, placeholder_text,
+        receive_id_type, record_connection_attempt, record_error_state, resolve_conversation_id,
+        run_gateway_loop, send_image_with, sender_kind, stop_gateway, strip_mention_keys,
+        video_attachment, video_filename, FeishuChatKind, FeishuConfig, FeishuGateway,
+        FeishuImageSender, FeishuMessageDeduper, FeishuMessageEditor, FeishuMessageUpdater,
+        FeishuRateLimiter, FeishuSendMessageRequest, FeishuSendMessageResponse, FeishuSenderKind,
+        OutboundImage, TokenBucket, CARD_TRUNCATION_NOTICE, MAX_CARD_CONTENT_BYTES,
+        MAX_FEISHU_MEDIA_BYTES, STICKER_PLACEHOLDER,
     };
     use serde_json::{json, Value};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+    use tokio::sync::watch;
 
     #[test]
     fn open_id_allowlist_allows_when_empty() {
@@ -860,7 +2318,70 @@ mod tests {
     #[test]
     fn chat_kind_filters_non_p2p() {
         assert_eq!(chat_kind("p2p"), FeishuChatKind::P2p);
-        assert_eq!(chat_kind("group"), FeishuChatKind::Other);
+        assert_eq!(chat_kind("group"), FeishuChatKind::Group);
+    }
+
+    #[test]
+    fn detects_bot_mention_by_open_id() {
+        let mentions = parse_mentions(&json!([
+            {
+                "key": "@_user_1",
+                "id": { "open_id": "ou_bot", "union_id": "on_bot", "user_id": null },
+                "name": "TalkCody",
+                "tenant_key": "tenant"
+            },
+            {
+                "key": "@_user_2",
+                "id": { "open_id": "ou_alice" },
+                "name": "Alice"
+            }
+        ]));
+        assert_eq!(mentions.len(), 2);
+        assert!(is_bot_mentioned(&mentions, "ou_bot"));
+        assert!(!is_bot_mentioned(&mentions, "ou_other_bot"));
+        assert!(!is_bot_mentioned(&mentions, ""));
+        assert!(parse_mentions(&Value::Null).is_empty());
+        assert_eq!(
+            strip_mention_keys("@_user_1 summarize this @_user_2", &mentions),
+            "summarize this"
+        );
+    }
+
+    #[test]
+    fn routes_group_chats_only_when_enabled_and_mentioned() {
+        assert_eq!(
+            resolve_conversation_id(FeishuChatKind::P2p, false, false, "ou_a", "oc_1"),
+            Some("ou_a".to_string())
+        );
+        assert_eq!(
+            resolve_conversation_id(FeishuChatKind::Group, false, true, "ou_a", "oc_1"),
+            None
+        );
+        assert_eq!(
+            resolve_conversation_id(FeishuChatKind::Group, true, false, "ou_a", "oc_1"),
+            None
+        );
+        assert_eq!(
+            resolve_conversation_id(FeishuChatKind::Group, true, true, "ou_a", "oc_1"),
+            Some("oc_1".to_string())
+        );
+        assert_eq!(
+            resolve_conversation_id(FeishuChatKind::Other, true, true, "ou_a", "oc_1"),
+            None
+        );
+        assert!(!FeishuConfig::default().allow_group_chats);
+    }
+
+    #[test]
+    fn replies_use_chat_id_for_groups() {
+        assert_eq!(
+            receive_id_type("oc_81441e708eef38e9246d6b0bf3e312e0"),
+            "chat_id"
+        );
+        assert_eq!(
+            receive_id_type("ou_f86fe8ddd1a732594c55a11c379f173c"),
+            "open_id"
+        );
     }
 
     // Test for parsing Feishu message with null user_id (the bug fix)
@@ -984,7 +2505,7 @@ mod tests {
     #[test]
     fn test_chat_kind_edge_cases() {
         assert_eq!(chat_kind("p2p"), FeishuChatKind::P2p);
-        assert_eq!(chat_kind("group"), FeishuChatKind::Other);
+        assert_eq!(chat_kind("group"), FeishuChatKind::Group);
         assert_eq!(chat_kind("thread"), FeishuChatKind::Other);
         assert_eq!(chat_kind(""), FeishuChatKind::Other);
     }
@@ -1016,9 +2537,67 @@ mod tests {
 
     #[test]
     fn test_build_attachment_filename_with_path_traversal() {
-        // Security test: path traversal attempt should be sanitized by replacing / with _
+        // Security test: traversal segments are dropped, leaving a single path component
         let filename = build_attachment_filename("feishu", Some("../../../etc/passwd"), "image");
-        assert_eq!(filename, ".._.._.._etc_passwd"); // / is replaced with _ to prevent path traversal
+        assert_eq!(filename, "etc_passwd.bin");
+    }
+
+    #[test]
+    fn test_build_attachment_filename_strips_unsafe_names() {
+        let cases = [
+            (
+                "..\\..\\windows\\system32\\cmd.exe",
+                "windows_system32_cmd.exe",
+            ),
+            ("/etc/shadow", "etc_shadow.bin"),
+            ("report..pdf", "report.pdf"),
+            (".bashrc", "bashrc.bin"),
+            ("bad\u{0}name\n.txt", "badname.txt"),
+            ("a:b*c?.md", "a_b_c_.md"),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(
+                build_attachment_filename("feishu", Some(input), "file"),
+                expected,
+                "input {:?}",
+                input
+            );
+        }
+        // Nothing usable left falls back to the generated name
+        assert_eq!(
+            build_attachment_filename("feishu", Some("../.."), "file"),
+            "feishu-file.bin"
+        );
+    }
+
+    #[test]
+    fn test_validate_inbound_attachment_uses_magic_bytes() {
+        let png: &[u8] = &[0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A, 0, 0, 0, 0];
+        assert_eq!(
+            validate_inbound_attachment("image", png, None).unwrap(),
+            "image/png"
+        );
+
+        // An image message whose bytes are a PDF is rejected
+        let pdf = b"%PDF-1.7\n1 0 obj";
+        assert!(validate_inbound_attachment("image", pdf, None).is_err());
+        assert_eq!(
+            validate_inbound_attachment("file", pdf, None).unwrap(),
+            "application/pdf"
+        );
+
+        // Executables are not on the default list, whatever the file name claims
+        let elf: &[u8] = &[0x7F, b'E', b'L', b'F', 2, 1, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+        assert!(validate_inbound_attachment("file", elf, None).is_err());
+
+        assert_eq!(
+            validate_inbound_attachment("file", b"fn main() {}", None).unwrap(),
+            "text/plain"
+        );
+
+        let only_images = vec!["image/*".to_string()];
+        assert!(validate_inbound_attachment("file", pdf, Some(&only_images)).is_err());
+        assert!(validate_inbound_attachment("image", png, Some(&only_images)).is_ok());
     }
 
     #[test]
@@ -1113,4 +2692,548 @@ mod tests {
             "img_v3_02uo_f3d7117e-a8bc-4b7c-b423-6d9a54bdbd4g"
         );
     }
+
+    #[tokio::test]
+    async fn duplicate_message_id_is_emitted_once() {
+        let dedup = Arc::new(FeishuMessageDeduper::default());
+        let emitted = Arc::new(AtomicUsize::new(0));
+        let mut handles = Vec::new();
+        for _ in 0..8 {
+            let dedup = dedup.clone();
+            let emitted = emitted.clone();
+            handles.push(tokio::spawn(async move {
+                if dedup.first_seen("om_redelivered", std::time::Instant::now()) {
+                    emitted.fetch_add(1, Ordering::SeqCst);
+                }
+            }));
+        }
+        for handle in handles {
+            handle.await.unwrap();
+        }
+        assert_eq!(emitted.load(Ordering::SeqCst), 1);
+        assert!(dedup.first_seen("om_other", std::time::Instant::now()));
+    }
+
+    #[test]
+    fn dedup_forgets_ids_after_ttl_or_capacity() {
+        let dedup = FeishuMessageDeduper::new(2, Duration::from_secs(60));
+        let start = std::time::Instant::now();
+        assert!(dedup.first_seen("om_1", start));
+        assert!(!dedup.first_seen("om_1", start + Duration::from_secs(59)));
+        assert!(dedup.first_seen("om_1", start + Duration::from_secs(60)));
+
+        let later = start + Duration::from_secs(120);
+        assert!(dedup.first_seen("om_2", later));
+        assert!(dedup.first_seen("om_3", later));
+        assert!(dedup.first_seen("om_4", later));
+        // om_2 was pushed out by the capacity limit
+        assert!(dedup.first_seen("om_2", later));
+        assert!(!dedup.first_seen("om_4", later));
+    }
+
+    #[test]
+    fn dedup_configure_applies_config_limits() {
+        let dedup = FeishuMessageDeduper::default();
+        let now = std::time::Instant::now();
+        assert!(dedup.first_seen("om_1", now));
+        assert!(dedup.first_seen("om_2", now));
+        dedup.configure(&FeishuConfig {
+            dedup_capacity: Some(1),
+            ..Default::default()
+        });
+        assert!(!dedup.first_seen("om_2", now));
+        assert!(dedup.first_seen("om_1", now));
+    }
+
+    fn enabled_config() -> FeishuConfig {
+        FeishuConfig {
+            enabled: true,
+            app_id: "cli_test".to_string(),
+            app_secret: "secret".to_string(),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn disabling_config_exits_run_loop() {
+        let state = default_state();
+        let (stop_tx, stop_rx) = watch::channel(false);
+        {
+            let mut gateway = state.lock().await;
+            gateway.config = enabled_config();
+            gateway.running = true;
+            gateway.stop_tx = Some(stop_tx);
+        }
+
+        // A connection that fails immediately keeps the loop cycling through backoff
+        let handle = tokio::spawn(run_gateway_loop(state.clone(), stop_rx, |_| async {
+            Err("connect failed".to_string())
+        }));
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(!handle.is_finished());
+
+        state.lock().await.config.enabled = false;
+        stop_gateway(&state).await;
+
+        tokio::time::timeout(Duration::from_secs(1), handle)
+            .await
+            .expect("run loop should exit after disable")
+            .unwrap();
+        let gateway = state.lock().await;
+        assert!(!gateway.running);
+        assert!(gateway.stop_tx.is_none());
+    }
+
+    #[tokio::test]
+    async fn disabled_config_exits_without_stop_signal() {
+        let state = default_state();
+        let (_stop_tx, stop_rx) = watch::channel(false);
+        {
+            let mut gateway = state.lock().await;
+            gateway.config = enabled_config();
+            gateway.config.enabled = false;
+            gateway.running = true;
+        }
+
+        tokio::time::timeout(
+            Duration::from_secs(1),
+            run_gateway_loop(state.clone(), stop_rx, |_| {
+                std::future::pending::<Result<(), String>>()
+            }),
+        )
+        .await
+        .expect("disabled gateway should not keep the runtime alive");
+        assert!(!state.lock().await.running);
+    }
+
+    #[derive(Default)]
+    struct MockEditor {
+        calls: AtomicUsize,
+        last_text: std::sync::Mutex<Option<String>>,
+    }
+
+    #[async_trait::async_trait]
+    impl FeishuMessageEditor for MockEditor {
+        async fn edit(&self, _message_id: &str, text: &str) -> Result<(), String> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            *self.last_text.lock().unwrap() = Some(text.to_string());
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn rapid_stream_edits_are_coalesced() {
+        let editor = Arc::new(MockEditor::default());
+        let updater = Arc::new(FeishuMessageUpdater::new(
+            editor.clone(),
+            Duration::from_millis(50),
+        ));
+
+        let mut text = String::new();
+        for index in 0..200 {
+            text.push_str(&format!("token{} ", index));
+            updater.update("om_1", &text).await;
+            if index % 20 == 0 {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        }
+        // Let the trailing edit land
+        tokio::time::sleep(Duration::from_millis(150)).await;
+
+        let calls = editor.calls.load(Ordering::SeqCst);
+        assert!((1..=4).contains(&calls), "unexpected edit count {}", calls);
+        assert_eq!(
+            editor.last_text.lock().unwrap().as_deref(),
+            Some(text.as_str())
+        );
+
+        // Re-sending the same text is a no-op
+        updater.update("om_1", &text).await;
+        tokio::time::sleep(Duration::from_millis(80)).await;
+        assert_eq!(editor.calls.load(Ordering::SeqCst), calls);
+    }
+
+    #[tokio::test]
+    async fn finish_flushes_final_text_immediately() {
+        let editor = Arc::new(MockEditor::default());
+        let updater = Arc::new(FeishuMessageUpdater::new(
+            editor.clone(),
+            Duration::from_secs(10),
+        ));
+
+        updater.update("om_2", "partial").await;
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        updater.update("om_2", "partial answer").await;
+        updater.update("om_2", "final answer").await;
+        updater.finish("om_2").await.expect("finish");
+
+        assert_eq!(editor.calls.load(Ordering::SeqCst), 2);
+        assert_eq!(
+            editor.last_text.lock().unwrap().as_deref(),
+            Some("final answer")
+        );
+    }
+
+    #[test]
+    fn markdown_is_wrapped_in_interactive_card() {
+        let markdown = "**Done**\n\n```rust\nfn main() {}\n```";
+        let card: Value = serde_json::from_str(&build_markdown_card(markdown)).unwrap();
+        assert_eq!(card["config"]["wide_screen_mode"], true);
+        assert_eq!(card["elements"][0]["tag"], "markdown");
+        assert_eq!(card["elements"][0]["content"], markdown);
+        assert_eq!(card["elements"].as_array().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn oversized_markdown_is_truncated_with_notice() {
+        // Quotes and multi-byte characters expand or straddle byte boundaries
+        let markdown = "\"代码\" ".repeat(10_000);
+        let content = build_markdown_card(&markdown);
+        assert!(content.len() <= MAX_CARD_CONTENT_BYTES);
+
+        let card: Value = serde_json::from_str(&content).unwrap();
+        let text = card["elements"][0]["content"].as_str().unwrap();
+        assert!(text.ends_with(CARD_TRUNCATION_NOTICE));
+        assert!(markdown.starts_with(text.trim_end_matches(CARD_TRUNCATION_NOTICE)));
+    }
+
+    #[test]
+    fn card_json_accepts_objects_and_strings() {
+        let card = json!({ "elements": [] });
+        assert_eq!(parse_card_json(card.clone()).unwrap(), card.to_string());
+        assert_eq!(
+            parse_card_json(Value::String(card.to_string())).unwrap(),
+            card.to_string()
+        );
+        assert!(parse_card_json(json!([1, 2])).is_err());
+        assert!(parse_card_json(Value::String("not json".to_string())).is_err());
+    }
+
+    #[test]
+    fn token_bucket_refills_over_time() {
+        let start = std::time::Instant::now();
+        let mut bucket = TokenBucket::new(2.0, 4.0);
+        bucket.last_refill = start;
+
+        assert!(bucket.try_acquire(start).is_ok());
+        assert!(bucket.try_acquire(start).is_ok());
+        let wait = bucket
+            .try_acquire(start)
+            .expect_err("bucket should be empty");
+        assert_eq!(wait, Duration::from_millis(250));
+
+        // A quarter second refills one token at 4/s
+        let later = start + Duration::from_millis(250);
+        assert!(bucket.try_acquire(later).is_ok());
+        assert!(bucket.try_acquire(later).is_err());
+
+        // Refill never exceeds capacity
+        let much_later = later + Duration::from_secs(10);
+        bucket.refill(much_later);
+        assert_eq!(bucket.tokens, 2.0);
+    }
+
+    #[tokio::test]
+    async fn frequency_limit_error_is_retried() {
+        let limiter = FeishuRateLimiter::new(10.0, 10.0);
+        let attempts = AtomicUsize::new(0);
+        let counter = &attempts;
+
+        let result = call_with_rate_limit(&limiter, 1, move || async move {
+            if counter.fetch_add(1, Ordering::SeqCst) == 0 {
+                Err("Feishu send message failed: ApiError { code: 99991400, msg: \"request trigger frequency limit\" }".to_string())
+            } else {
+                Ok("om_sent")
+            }
+        })
+        .await;
+
+        assert_eq!(result, Ok("om_sent"));
+        assert_eq!(attempts.load(Ordering::SeqCst), 2);
+        assert_eq!(limiter.status().frequency_limit_retries, 1);
+    }
+
+    #[tokio::test]
+    async fn other_errors_are_not_retried() {
+        let limiter = FeishuRateLimiter::new(10.0, 10.0);
+        let attempts = AtomicUsize::new(0);
+        let counter = &attempts;
+
+        let result: Result<(), String> = call_with_rate_limit(&limiter, 1, move || async move {
+            counter.fetch_add(1, Ordering::SeqCst);
+            Err("Feishu send message failed: invalid receive_id".to_string())
+        })
+        .await;
+
+        assert!(result.is_err());
+        assert_eq!(attempts.load(Ordering::SeqCst), 1);
+        assert_eq!(limiter.status().frequency_limit_retries, 0);
+    }
+
+    #[test]
+    fn reply_request_targets_parent_message() {
+        let content = json!({ "text": "hi" }).to_string();
+        let (url, body) = build_reply_request("om_parent", "text", &content);
+        assert_eq!(
+            url,
+            "https://open.feishu.cn/open-apis/im/v1/messages/om_parent/reply"
+        );
+        assert_eq!(body["msg_type"], "text");
+        assert_eq!(body["content"], content);
+        assert!(body["uuid"].as_str().is_some_and(|uuid| !uuid.is_empty()));
+    }
+
+    #[test]
+    fn send_request_reply_to_is_optional() {
+        let request: FeishuSendMessageRequest =
+            serde_json::from_value(json!({ "openId": "ou_a", "text": "hi" })).unwrap();
+        assert!(request.reply_to.is_none());
+
+        let request: FeishuSendMessageRequest = serde_json::from_value(
+            json!({ "openId": "ou_a", "text": "hi", "replyTo": "om_parent" }),
+        )
+        .unwrap();
+        assert_eq!(request.reply_to.as_deref(), Some("om_parent"));
+    }
+
+    #[test]
+    fn error_state_tracks_consecutive_failures() {
+        let mut gateway = FeishuGateway::new();
+        record_error_state(&mut gateway, "connect failed");
+        record_error_state(&mut gateway, "connect failed again");
+        assert_eq!(gateway.consecutive_failures, 2);
+        assert_eq!(gateway.last_error.as_deref(), Some("connect failed again"));
+        assert!(gateway.last_error_at_ms.is_some());
+
+        gateway.backoff_ms = 12_000;
+        clear_error_state(&mut gateway);
+        assert_eq!(gateway.consecutive_failures, 0);
+        assert!(gateway.last_error.is_none());
+        assert_eq!(gateway.backoff_ms, super::DEFAULT_ERROR_BACKOFF_MS);
+    }
+
+    #[test]
+    fn connection_attempts_count_reconnects_after_the_first() {
+        let mut gateway = FeishuGateway::new();
+        record_connection_attempt(&mut gateway);
+        assert_eq!(gateway.reconnect_count, 0);
+        assert!(gateway.last_connected_at_ms.is_some());
+
+        record_connection_attempt(&mut gateway);
+        record_connection_attempt(&mut gateway);
+        assert_eq!(gateway.reconnect_count, 2);
+    }
+
+    #[tokio::test]
+    async fn run_loop_counts_failures_and_reconnects() {
+        let state = default_state();
+        let (stop_tx, stop_rx) = watch::channel(false);
+        {
+            let mut gateway = state.lock().await;
+            gateway.config = enabled_config();
+            gateway.running = true;
+        }
+
+        let handle = tokio::spawn(run_gateway_loop(state.clone(), stop_rx, |_| async {
+            Err("connect failed".to_string())
+        }));
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        let _ = stop_tx.send(true);
+        tokio::time::timeout(Duration::from_secs(1), handle)
+            .await
+            .expect("run loop should exit after stop")
+            .unwrap();
+
+        let gateway = state.lock().await;
+        assert_eq!(gateway.consecutive_failures, 1);
+        assert_eq!(gateway.reconnect_count, 0);
+        assert!(gateway.last_connected_at_ms.is_some());
+    }
+
+    #[test]
+    fn video_message_becomes_video_attachment() {
+        let content: Value = serde_json::from_str(
+            r#"{"file_key":"file_v3_video","image_key":"img_cover","file_name":"demo.MOV","duration":12500}"#,
+        )
+        .unwrap();
+        let filename = video_filename(Some(&content), "file_v3_video");
+        assert_eq!(filename, "demo.MOV");
+
+        let attachment = video_attachment(
+            Some(&content),
+            "file_v3_video",
+            filename,
+            "/tmp/attachments/demo.MOV".to_string(),
+            "video/quicktime".to_string(),
+            2048,
+        );
+        assert_eq!(attachment.id, "file_v3_video");
+        assert_eq!(attachment.attachment_type, "video");
+        assert_eq!(attachment.mime_type, "video/quicktime");
+        assert_eq!(attachment.duration_seconds, Some(12));
+        assert_eq!(attachment.caption.as_deref(), Some("demo.MOV"));
+        assert_eq!(attachment.size, 2048);
+    }
+
+    #[test]
+    fn video_without_file_name_gets_mp4_filename() {
+        let content: Value = serde_json::from_str(r#"{"file_key":"file_v3_video"}"#).unwrap();
+        let filename = video_filename(Some(&content), "file_v3_video");
+        assert_eq!(filename, "video-file_v3_video.mp4");
+
+        let attachment = video_attachment(
+            Some(&content),
+            "file_v3_video",
+            filename,
+            "/tmp/attachments/video-file_v3_video.mp4".to_string(),
+            "video/mp4".to_string(),
+            1,
+        );
+        assert_eq!(attachment.mime_type, "video/mp4");
+        assert!(attachment.duration_seconds.is_none());
+        assert!(attachment.caption.is_none());
+    }
+
+    #[test]
+    fn sticker_messages_map_to_placeholder() {
+        assert_eq!(placeholder_text("sticker"), Some(STICKER_PLACEHOLDER));
+        assert_eq!(placeholder_text("text"), None);
+        assert_eq!(placeholder_text("video"), None);
+    }
+
+    /// Minimal PNG signature followed by an IHDR chunk header
+    const PNG_BYTES: &[u8] = b"\x89PNG\r\n\x1a\n\x00\x00\x00\rIHDR\x00\x00\x00\x01\x00\x00\x00\x01";
+
+    #[derive(Default)]
+    struct MockImageSender {
+        calls: std::sync::Mutex<Vec<String>>,
+    }
+
+    #[async_trait::async_trait]
+    impl FeishuImageSender for MockImageSender {
+        async fn upload_image(&self, image: &OutboundImage) -> Result<String, String> {
+            self.calls.lock().unwrap().push(format!(
+                "upload {} {} {}",
+                image.filename,
+                image.mime_type,
+                image.data.len()
+            ));
+            Ok("img_v2_mock".to_string())
+        }
+
+        async fn send(
+            &self,
+            receive_id: &str,
+            msg_type: &str,
+            content: String,
+        ) -> Result<FeishuSendMessageResponse, String> {
+            self.calls
+                .lock()
+                .unwrap()
+                .push(format!("send {} {} {}", receive_id, msg_type, content));
+            Ok(FeishuSendMessageResponse {
+                message_id: "om_image".to_string(),
+            })
+        }
+    }
+
+    #[tokio::test]
+    async fn send_image_uploads_then_sends_image_key() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("chart.png");
+        std::fs::write(&path, PNG_BYTES).unwrap();
+
+        let sender = MockImageSender::default();
+        let response = send_image_with(&sender, "ou_user", path.to_str().unwrap())
+            .await
+            .unwrap();
+
+        assert_eq!(response.message_id, "om_image");
+        let calls = sender.calls.lock().unwrap();
+        assert_eq!(
+            *calls,
+            vec![
+                format!("upload chart.png image/png {}", PNG_BYTES.len()),
+                format!(
+                    "send ou_user image {}",
+                    json!({ "image_key": "img_v2_mock" })
+                ),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn send_image_rejects_missing_oversized_and_unsupported_files() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let sender = MockImageSender::default();
+
+        let missing = dir.path().join("missing.png");
+        assert!(
+            send_image_with(&sender, "ou_user", missing.to_str().unwrap())
+                .await
+                .is_err()
+        );
+
+        let text = dir.path().join("notes.png");
+        std::fs::write(&text, "not an image").unwrap();
+        let error = send_image_with(&sender, "ou_user", text.to_str().unwrap())
+            .await
+            .unwrap_err();
+        assert!(error.contains("Unsupported image type"));
+
+        let oversized = dir.path().join("huge.png");
+        let file = std::fs::File::create(&oversized).unwrap();
+        file.set_len(MAX_FEISHU_MEDIA_BYTES + 1).unwrap();
+        let error = send_image_with(&sender, "ou_user", oversized.to_str().unwrap())
+            .await
+            .unwrap_err();
+        assert!(error.contains("larger than"));
+
+        assert!(sender.calls.lock().unwrap().is_empty());
+    }
+
+    fn write_with_age(dir: &std::path::Path, name: &str, age: Duration) -> std::path::PathBuf {
+        let path = dir.join(name);
+        std::fs::write(&path, b"media").unwrap();
+        let file = std::fs::File::options().write(true).open(&path).unwrap();
+        file.set_modified(std::time::SystemTime::now() - age)
+            .unwrap();
+        path
+    }
+
+    #[tokio::test]
+    async fn cleanup_attachments_removes_only_stale_files() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let day = Duration::from_secs(24 * 60 * 60);
+        let stale_image = write_with_age(dir.path(), "image-old.bin", 10 * day);
+        let stale_video = write_with_age(dir.path(), "video-old.mp4", 8 * day);
+        let recent = write_with_age(dir.path(), "image-new.bin", day);
+        let fresh = write_with_age(dir.path(), "file-now.pdf", Duration::ZERO);
+        let subdir = dir.path().join("nested");
+        std::fs::create_dir(&subdir).unwrap();
+
+        let removed = cleanup_attachments(dir.path(), 7 * day).await.unwrap();
+
+        assert_eq!(removed, 2);
+        assert!(!stale_image.exists());
+        assert!(!stale_video.exists());
+        assert!(recent.exists());
+        assert!(fresh.exists());
+        assert!(subdir.exists());
+
+        // Nothing left to remove on a second pass
+        assert_eq!(cleanup_attachments(dir.path(), 7 * day).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn cleanup_attachments_tolerates_missing_dir() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let missing = dir.path().join("never-created");
+        assert_eq!(
+            cleanup_attachments(&missing, Duration::from_secs(1))
+                .await
+                .unwrap(),
+            0
+        );
+    }
 }
@@ -168,6 +168,7 @@ mod tests {
             headers: None,
             extra_body: None,
             auth_type: AuthType::Bearer,
+            body_transforms: None,
         };
         let registry = ProviderRegistry::new(vec![provider_config]);
 
@@ -191,6 +192,9 @@ mod tests {
                         cache_creation: None,
                     }),
                     context_length: Some(8192),
+                    aliases: Vec::new(),
+                    deprecated: None,
+                    deprecation_message: None,
                 },
             )]),
         };
@@ -166,6 +166,7 @@ mod tests {
             headers: None,
             extra_body: None,
             auth_type: AuthType::Bearer,
+            body_transforms: None,
         };
         let registry = ProviderRegistry::new(vec![provider_config]);
 
@@ -189,6 +190,9 @@ mod tests {
                         cache_creation: None,
                     }),
                     context_length: Some(8192),
+                    aliases: Vec::new(),
+                    deprecated: None,
+                    deprecation_message: None,
                 },
             )]),
         };
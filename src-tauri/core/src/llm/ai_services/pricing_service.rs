@@ -130,6 +130,9 @@ mod tests {
                 cache_creation: cache_creation.map(|s| s.to_string()),
             }),
             context_length: None,
+            aliases: Vec::new(),
+            deprecated: None,
+            deprecation_message: None,
         }
     }
 
@@ -40,7 +40,7 @@ impl StreamCollector {
                             full_text.push_str(&text);
                         }
                         StreamEvent::Done { .. } => break,
-                        StreamEvent::Error { message } => {
+                        StreamEvent::Error { message, .. } => {
                             return Err(format!("Stream error: {}", message));
                         }
                         _ => {} // Ignore other events like Usage, ToolCall, etc.
@@ -82,7 +82,7 @@ impl StreamCollector {
                     delta_count += 1;
                     full_text.push_str(&text);
                 }
-                StreamEvent::Error { message } => {
+                StreamEvent::Error { message, .. } => {
                     log::error!("Stream error: {}", message);
                 }
                 _ => {}
@@ -116,6 +116,18 @@ impl StreamCollector {
             provider_options: None,
             request_id: None,
             trace_context: None,
+            partial_json: None,
+            fallback_models: None,
+            logprobs: None,
+            top_logprobs: None,
+            estimate_usage: false,
+            metadata: None,
+            resume_on_disconnect: false,
+            allowed_tools: None,
+            stream_tool_call_deltas: false,
+            completion_webhook: None,
+            persist_to_session: None,
+            idempotency_key: None,
         }
     }
 }
@@ -212,6 +224,8 @@ mod tests {
             }),
             Ok(StreamEvent::Error {
                 message: "Something went wrong".to_string(),
+                provider_error: None,
+                kind: None,
             }),
         ];
 
@@ -48,6 +48,8 @@ impl StreamRunner {
             top_k: request.top_k,
             provider_options: request.provider_options.as_ref(),
             trace_context: request.trace_context.as_ref(),
+            logprobs: request.logprobs,
+            top_logprobs: request.top_logprobs,
         };
 
         let built_request = provider.build_complete_request(&provider_ctx).await?;
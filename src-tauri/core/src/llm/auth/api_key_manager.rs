@@ -1,8 +1,14 @@
+use crate::llm::auth::clock_skew;
+use crate::llm::auth::secret_store::{
+    default_secret_store, is_sensitive_key, SecretStore, SettingsBackend, SENSITIVE_OAUTH_FIELDS,
+    SETTINGS_BACKEND_KEY,
+};
 use crate::llm::types::CustomProvidersConfiguration;
 use crate::llm::types::{AuthType, ModelsConfiguration, ProviderConfig};
 use reqwest::Client;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::{Duration, Instant, SystemTime};
@@ -26,11 +32,50 @@ const GITHUB_COPILOT_EDITOR_VERSION: &str = "vscode/1.105.1";
 const GITHUB_COPILOT_PLUGIN_VERSION: &str = "copilot-chat/0.35.0";
 const GITHUB_COPILOT_INTEGRATION_ID: &str = "vscode-chat";
 const GITHUB_COPILOT_TOKEN_BUFFER_SECONDS: i64 = 60;
+const OAUTH_TOKEN_BUFFER_SECONDS: i64 = 60;
+
+/// Setting that lets provider API keys fall back to the `api_key_name` env var (default on)
+pub const ALLOW_ENV_API_KEYS_KEY: &str = "allow_env_api_keys";
+
+/// Setting holding a URL to periodically fetch the models config from
+pub const MODELS_CONFIG_REMOTE_URL_KEY: &str = "models_config_remote_url";
+const MODELS_CONFIG_REMOTE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Key prefixes of the built-in OAuth logins
+const BUILTIN_OAUTH_PREFIXES: [&str; 4] = ["openai", "claude", "github_copilot", "qwen"];
+
+/// Account backed by the legacy unsuffixed `{prefix}_oauth_*` keys
+pub const DEFAULT_OAUTH_ACCOUNT: &str = "default";
+
+/// Format version written by `export_settings`; imports of newer versions are rejected
+const SETTINGS_EXPORT_VERSION: u32 = 1;
+
+/// Settings, and optionally secrets, bundled for moving to another machine
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SettingsExport {
+    pub version: u32,
+    pub exported_at: String,
+    pub includes_secrets: bool,
+    pub settings: BTreeMap<String, String>,
+    #[serde(default)]
+    pub custom_providers: Option<CustomProvidersConfiguration>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SettingsImportSummary {
+    pub imported: usize,
+    /// Settings left alone because they already had a value and overwrite was off
+    pub skipped: usize,
+    pub custom_providers_imported: usize,
+}
 
 pub struct ApiKeyManager {
     db: Arc<Database>,
     app_data_dir: PathBuf,
     models_cache: RwLock<Option<ModelsCacheEntry>>,
+    secret_store: Arc<dyn SecretStore>,
 }
 
 impl std::fmt::Debug for ApiKeyManager {
@@ -53,6 +98,7 @@ impl Clone for ApiKeyManager {
             db: self.db.clone(),
             app_data_dir: self.app_data_dir.clone(),
             models_cache: RwLock::new(None),
+            secret_store: self.secret_store.clone(),
         }
     }
 }
@@ -63,9 +109,16 @@ impl ApiKeyManager {
             db,
             app_data_dir,
             models_cache: RwLock::new(None),
+            secret_store: default_secret_store(),
         }
     }
 
+    /// Use `store` for secrets when the keyring backend is selected
+    pub fn with_secret_store(mut self, store: Arc<dyn SecretStore>) -> Self {
+        self.secret_store = store;
+        self
+    }
+
     /// Load models configuration with caching (5 minutes TTL)
     pub async fn load_models_config(&self) -> Result<ModelsConfiguration, String> {
         let custom_models_mtime = self.custom_models_modified_time().await?;
@@ -116,6 +169,52 @@ impl ApiKeyManager {
         *cache = None;
     }
 
+    /// Fetch a models config from `url` and make it the stored base config.
+    /// The existing config is left untouched unless the response is a valid,
+    /// non-empty `ModelsConfiguration`.
+    pub async fn refresh_models_from_remote(
+        &self,
+        url: &str,
+    ) -> Result<ModelsConfiguration, String> {
+        let client = Client::builder()
+            .timeout(MODELS_CONFIG_REMOTE_TIMEOUT)
+            .build()
+            .map_err(|e| format!("Failed to build HTTP client: {}", e))?;
+        let response = client
+            .get(url)
+            .send()
+            .await
+            .map_err(|e| format!("Failed to fetch remote models config: {}", e))?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(format!(
+                "Failed to fetch remote models config: HTTP {}",
+                status
+            ));
+        }
+        let raw = response
+            .text()
+            .await
+            .map_err(|e| format!("Failed to read remote models config: {}", e))?;
+        let config = serde_json::from_str::<ModelsConfiguration>(&raw)
+            .map_err(|e| format!("Failed to parse remote models config: {}", e))?;
+        if config.models.is_empty() {
+            return Err("Remote models config contains no models".to_string());
+        }
+
+        let content = serde_json::to_string(&config)
+            .map_err(|e| format!("Failed to serialize models config: {}", e))?;
+        self.set_setting("models_config_json", &content).await?;
+        self.clear_models_cache().await;
+        log::info!(
+            "Refreshed models config from {} (version {}, {} models)",
+            url,
+            config.version,
+            config.models.len()
+        );
+        Ok(config)
+    }
+
     fn custom_providers_path(&self) -> PathBuf {
         self.app_data_dir.join(CUSTOM_PROVIDERS_FILENAME)
     }
@@ -168,6 +267,24 @@ impl ApiKeyManager {
     }
 
     pub async fn get_setting(&self, key: &str) -> Result<Option<String>, String> {
+        if is_sensitive_key(key) && self.settings_backend().await? == SettingsBackend::Keyring {
+            // Migration and `set_setting` leave no database row behind, so a row here
+            // was written past the keyring and is newer than what the keyring holds.
+            // Empty rows are the frontend's seeded defaults and never replace a secret.
+            if let Some(value) = self
+                .get_db_setting(key)
+                .await?
+                .filter(|value| !value.is_empty())
+            {
+                self.set_setting(key, &value).await?;
+                return Ok(Some(value));
+            }
+            return self.secret_store.get(key);
+        }
+        self.get_db_setting(key).await
+    }
+
+    async fn get_db_setting(&self, key: &str) -> Result<Option<String>, String> {
         let result = self
             .db
             .query(SETTINGS_SELECT, vec![Value::String(key.to_string())])
@@ -182,6 +299,18 @@ impl ApiKeyManager {
     }
 
     pub async fn set_setting(&self, key: &str, value: &str) -> Result<(), String> {
+        if is_sensitive_key(key) && self.settings_backend().await? == SettingsBackend::Keyring {
+            if value.is_empty() {
+                self.secret_store.delete(key)?;
+            } else {
+                self.secret_store.set(key, value)?;
+            }
+            return self.delete_db_setting(key).await;
+        }
+        self.set_db_setting(key, value).await
+    }
+
+    async fn set_db_setting(&self, key: &str, value: &str) -> Result<(), String> {
         let now = chrono::Utc::now().timestamp_millis();
         self.db
             .execute(
@@ -196,6 +325,239 @@ impl ApiKeyManager {
         Ok(())
     }
 
+    async fn delete_db_setting(&self, key: &str) -> Result<(), String> {
+        self.db
+            .execute(
+                "DELETE FROM settings WHERE key = $1",
+                vec![Value::String(key.to_string())],
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// Backend currently holding sensitive keys; the selector itself always lives in the database
+    pub async fn settings_backend(&self) -> Result<SettingsBackend, String> {
+        Ok(self
+            .get_db_setting(SETTINGS_BACKEND_KEY)
+            .await?
+            .map(|value| SettingsBackend::parse(&value))
+            .unwrap_or_default())
+    }
+
+    /// Move every sensitive setting from the database into the keyring and switch
+    /// the backend over. Returns the number of keys moved.
+    ///
+    /// All secrets are copied and read back before the backend is switched, and
+    /// the database rows are only deleted after that, so a keyring failure
+    /// partway through leaves the database backend untouched.
+    pub async fn migrate_secrets_to_keyring(&self) -> Result<usize, String> {
+        let rows = self
+            .db
+            .query("SELECT key, value FROM settings", vec![])
+            .await?;
+        let secrets: Vec<(String, String)> = rows
+            .rows
+            .iter()
+            .filter_map(|row| {
+                let key = row.get("key").and_then(|v| v.as_str())?;
+                let value = row
+                    .get("value")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or_default();
+                is_sensitive_key(key).then(|| (key.to_string(), value.to_string()))
+            })
+            .collect();
+
+        for (key, value) in &secrets {
+            if !value.is_empty() {
+                self.secret_store.set(key, value)?;
+            }
+        }
+        for (key, value) in &secrets {
+            if !value.is_empty() && self.secret_store.get(key)?.as_deref() != Some(value) {
+                return Err(format!("Keyring did not keep {}; migration aborted", key));
+            }
+        }
+
+        self.set_db_setting(SETTINGS_BACKEND_KEY, SettingsBackend::Keyring.as_str())
+            .await?;
+        for (key, _) in &secrets {
+            self.delete_db_setting(key).await?;
+        }
+
+        let moved = secrets
+            .iter()
+            .filter(|(_, value)| !value.is_empty())
+            .count();
+        log::info!("Moved {} secrets into the OS keyring", moved);
+        Ok(moved)
+    }
+
+    /// Snapshot the settings table and custom providers. Without `include_secrets`,
+    /// API keys, OAuth tokens and custom provider keys are left out. The backend
+    /// selector is machine-specific and never exported.
+    pub async fn export_settings(&self, include_secrets: bool) -> Result<SettingsExport, String> {
+        let rows = self
+            .db
+            .query("SELECT key, value FROM settings", vec![])
+            .await?;
+        let mut settings = BTreeMap::new();
+        let mut db_keys = Vec::new();
+        for row in rows.rows {
+            let key = row.get("key").and_then(|v| v.as_str()).unwrap_or_default();
+            let value = row
+                .get("value")
+                .and_then(|v| v.as_str())
+                .unwrap_or_default();
+            if key.is_empty() || key == SETTINGS_BACKEND_KEY {
+                continue;
+            }
+            db_keys.push(key.to_string());
+            if !include_secrets && is_sensitive_key(key) {
+                continue;
+            }
+            settings.insert(key.to_string(), value.to_string());
+        }
+
+        // Secrets moved to the keyring are no longer in the table
+        if include_secrets && self.settings_backend().await? == SettingsBackend::Keyring {
+            for key in self.keyring_secret_keys(&db_keys).await? {
+                if let Some(value) = self.secret_store.get(&key)? {
+                    if !value.is_empty() {
+                        settings.entry(key).or_insert(value);
+                    }
+                }
+            }
+        }
+
+        let mut custom_providers = self.load_custom_providers().await?;
+        if !include_secrets {
+            for provider in custom_providers.providers.values_mut() {
+                provider.api_key.clear();
+            }
+        }
+
+        Ok(SettingsExport {
+            version: SETTINGS_EXPORT_VERSION,
+            exported_at: chrono::Utc::now().to_rfc3339(),
+            includes_secrets: include_secrets,
+            settings,
+            custom_providers: (!custom_providers.providers.is_empty()).then_some(custom_providers),
+        })
+    }
+
+    /// Sensitive keys that may be held in the keyring. It cannot be enumerated,
+    /// so candidates are the API keys of every known provider and the OAuth
+    /// tokens of every account under the built-in prefixes and any prefix seen
+    /// among `db_keys`.
+    async fn keyring_secret_keys(&self, db_keys: &[String]) -> Result<BTreeSet<String>, String> {
+        let mut provider_ids: Vec<String> =
+            crate::llm::providers::provider_configs::builtin_providers()
+                .into_iter()
+                .map(|provider| provider.id)
+                .collect();
+        provider_ids.extend(self.load_custom_providers().await?.providers.into_keys());
+        let mut keys: BTreeSet<String> = provider_ids
+            .iter()
+            .map(|provider_id| format!("api_key_{}", provider_id))
+            .collect();
+
+        let mut prefixes: BTreeSet<String> = BUILTIN_OAUTH_PREFIXES
+            .iter()
+            .map(|prefix| prefix.to_string())
+            .collect();
+        prefixes.extend(db_keys.iter().filter_map(|key| {
+            key.split_once("_oauth_")
+                .map(|(prefix, _)| prefix.to_string())
+        }));
+        for prefix in prefixes {
+            let mut labels = self.stored_oauth_accounts(&prefix).await?;
+            labels.push(DEFAULT_OAUTH_ACCOUNT.to_string());
+            for label in &labels {
+                for field in SENSITIVE_OAUTH_FIELDS {
+                    keys.insert(Self::oauth_account_key(&prefix, field, label));
+                }
+            }
+        }
+        Ok(keys)
+    }
+
+    /// Restore an export. Without `overwrite`, settings and custom providers that
+    /// already exist here are kept and only missing ones are added.
+    pub async fn import_settings(
+        &self,
+        export: &SettingsExport,
+        overwrite: bool,
+    ) -> Result<SettingsImportSummary, String> {
+        if export.version == 0 || export.version > SETTINGS_EXPORT_VERSION {
+            return Err(format!(
+                "Unsupported settings export version {}",
+                export.version
+            ));
+        }
+        if let Some(key) = export
+            .settings
+            .keys()
+            .find(|key| key.trim().is_empty() || key.as_str() == SETTINGS_BACKEND_KEY)
+        {
+            return Err(format!("Invalid setting key in export: {:?}", key));
+        }
+        if !export.includes_secrets {
+            if let Some(key) = export.settings.keys().find(|key| is_sensitive_key(key)) {
+                return Err(format!(
+                    "Export without secrets must not contain secret setting {:?}",
+                    key
+                ));
+            }
+        }
+
+        let mut summary = SettingsImportSummary::default();
+        for (key, value) in &export.settings {
+            if !overwrite
+                && self
+                    .get_setting(key)
+                    .await?
+                    .is_some_and(|existing| !existing.is_empty())
+            {
+                summary.skipped += 1;
+                continue;
+            }
+            self.set_setting(key, value).await?;
+            summary.imported += 1;
+        }
+
+        if let Some(incoming) = &export.custom_providers {
+            let mut current = self.load_custom_providers().await?;
+            for (id, provider) in &incoming.providers {
+                if id != &provider.id {
+                    return Err(format!(
+                        "Custom provider {} does not match its id {}",
+                        id, provider.id
+                    ));
+                }
+                let existing = current.providers.get(id);
+                if !overwrite && existing.is_some() {
+                    continue;
+                }
+                let mut provider = provider.clone();
+                // A blanked key means the export left it out, not that it should be cleared
+                if !export.includes_secrets || provider.api_key.trim().is_empty() {
+                    provider.api_key = existing
+                        .map(|existing| existing.api_key.clone())
+                        .unwrap_or_default();
+                }
+                current.providers.insert(id.clone(), provider);
+                summary.custom_providers_imported += 1;
+            }
+            if summary.custom_providers_imported > 0 {
+                current.version = chrono::Utc::now().to_rfc3339();
+                self.save_custom_providers(&current).await?;
+            }
+        }
+
+        Ok(summary)
+    }
+
     pub async fn load_api_keys(&self) -> Result<HashMap<String, String>, String> {
         let mut api_keys = HashMap::new();
         let keys = self
@@ -218,9 +580,66 @@ impl ApiKeyManager {
             }
         }
 
+        // The keyring cannot be enumerated, so probe the provider ids we know about
+        if self.settings_backend().await? == SettingsBackend::Keyring {
+            let mut provider_ids: Vec<String> =
+                crate::llm::providers::provider_configs::builtin_providers()
+                    .into_iter()
+                    .map(|provider| provider.id)
+                    .collect();
+            if let Ok(custom) = self.load_custom_providers().await {
+                provider_ids.extend(custom.providers.into_keys());
+            }
+            for provider_id in provider_ids {
+                if api_keys.contains_key(&provider_id) {
+                    continue;
+                }
+                if let Some(value) = self.secret_store.get(&format!("api_key_{}", provider_id))? {
+                    if !value.is_empty() {
+                        api_keys.insert(provider_id, value);
+                    }
+                }
+            }
+        }
+
+        // Stored keys win; the environment only fills providers without one
+        if self.env_api_keys_allowed().await? {
+            for provider in crate::llm::providers::provider_configs::builtin_providers() {
+                if api_keys.contains_key(&provider.id) {
+                    continue;
+                }
+                if let Some(value) = Self::env_api_key(&provider) {
+                    api_keys.insert(provider.id, value);
+                }
+            }
+        }
+
         Ok(api_keys)
     }
 
+    async fn env_api_keys_allowed(&self) -> Result<bool, String> {
+        Ok(self
+            .get_setting(ALLOW_ENV_API_KEYS_KEY)
+            .await?
+            .map(|value| !matches!(value.trim(), "false" | "0"))
+            .unwrap_or(true))
+    }
+
+    /// Read the provider's `api_key_name` from the environment for key-based auth types
+    fn env_api_key(provider: &ProviderConfig) -> Option<String> {
+        if matches!(provider.auth_type, AuthType::None | AuthType::TalkCodyJwt) {
+            return None;
+        }
+        let name = provider.api_key_name.trim();
+        if name.is_empty() {
+            return None;
+        }
+        std::env::var(name)
+            .ok()
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+    }
+
     pub async fn load_custom_providers(&self) -> Result<CustomProvidersConfiguration, String> {
         let path = self.custom_providers_path();
 
@@ -297,7 +716,8 @@ impl ApiKeyManager {
                 Ok(ProviderCredentials::Token(token))
             }
             AuthType::Bearer | AuthType::ApiKey | AuthType::OAuthBearer => {
-                if provider.supports_oauth {
+                // Custom providers may hold a token from the generic OAuth flow
+                if provider.supports_oauth || !Self::is_builtin_provider_id(&provider.id) {
                     if let Some(token) = self.get_oauth_token(&provider.id).await? {
                         if !token.trim().is_empty() {
                             return Ok(ProviderCredentials::Token(token));
@@ -321,6 +741,12 @@ impl ApiKeyManager {
                     }
                 }
 
+                if self.env_api_keys_allowed().await? {
+                    if let Some(api_key) = Self::env_api_key(provider) {
+                        return Ok(ProviderCredentials::Token(api_key));
+                    }
+                }
+
                 Err(format!(
                     "API key not configured for provider {}",
                     provider.id
@@ -331,17 +757,174 @@ impl ApiKeyManager {
 
     async fn get_oauth_token(&self, provider_id: &str) -> Result<Option<String>, String> {
         match provider_id {
-            "openai" => self.get_setting("openai_oauth_access_token").await,
-            "anthropic" => self.get_setting("claude_oauth_access_token").await,
+            "openai" | "anthropic" => self.get_valid_oauth_token(provider_id).await,
             "github_copilot" => match self.get_valid_github_copilot_token().await {
                 Ok(token) => Ok(Some(token)),
                 Err(_) => self.get_setting(GITHUB_COPILOT_COPILOT_TOKEN_KEY).await,
             },
+            "qwen_code" => Ok(self
+                .get_oauth_setting("qwen", "access_token")
+                .await?
+                .filter(|value| !value.trim().is_empty())),
+
+            _ => self.get_valid_generic_oauth_token(provider_id).await,
+        }
+    }
+
+    /// Ids of built-in providers and of the built-in OAuth logins, which generic
+    /// OAuth providers may not reuse
+    pub fn is_builtin_provider_id(provider_id: &str) -> bool {
+        BUILTIN_OAUTH_PREFIXES.contains(&provider_id)
+            || matches!(provider_id, "anthropic" | "qwen_code")
+            || crate::llm::providers::provider_configs::builtin_providers()
+                .iter()
+                .any(|provider| provider.id == provider_id)
+    }
+
+    /// Token from the generic OAuth flow, refreshed at the provider's configured
+    /// token URL when within the expiry buffer. Falls back to the stored token if
+    /// refresh fails.
+    async fn get_valid_generic_oauth_token(
+        &self,
+        provider_id: &str,
+    ) -> Result<Option<String>, String> {
+        use crate::llm::auth::oauth::generic_oauth_key;
+
+        let Ok(access_token_key) = generic_oauth_key(provider_id, "access_token") else {
+            return Ok(None);
+        };
+        let Some(access_token) = self
+            .get_setting(&access_token_key)
+            .await?
+            .filter(|value| !value.trim().is_empty())
+        else {
+            return Ok(None);
+        };
+
+        let expires_at = self
+            .get_setting(&generic_oauth_key(provider_id, "expires_at")?)
+            .await?
+            .and_then(|value| value.parse::<i64>().ok());
+        let Some(expires_at) = expires_at else {
+            return Ok(Some(access_token));
+        };
+        if chrono::Utc::now().timestamp() + OAUTH_TOKEN_BUFFER_SECONDS < expires_at {
+            return Ok(Some(access_token));
+        }
+
+        let refresh_token = self
+            .get_setting(&generic_oauth_key(provider_id, "refresh_token")?)
+            .await?
+            .unwrap_or_default();
+        if refresh_token.trim().is_empty() {
+            log::warn!(
+                "OAuth token for {} is expiring but no refresh token is stored",
+                provider_id
+            );
+            return Ok(Some(access_token));
+        }
+
+        log::info!("OAuth token for {} is near expiry, refreshing", provider_id);
+        let client = Client::builder()
+            .timeout(Duration::from_secs(20))
+            .build()
+            .map_err(|e| format!("Failed to build HTTP client: {}", e))?;
+        match crate::llm::auth::oauth::refresh_generic_oauth_tokens(
+            &client,
+            provider_id,
+            &refresh_token,
+            self,
+        )
+        .await
+        {
+            Ok(response) => Ok(Some(response.access_token)),
+            Err(e) => {
+                log::warn!("Failed to refresh OAuth token for {}: {}", provider_id, e);
+                Ok(Some(access_token))
+            }
+        }
+    }
+
+    /// Return the stored OAuth access token, refreshing it first when it is
+    /// within the expiry buffer. Falls back to the stored token if refresh fails.
+    pub async fn get_valid_oauth_token(&self, provider_id: &str) -> Result<Option<String>, String> {
+        let prefix = match provider_id {
+            "openai" => "openai",
+            "anthropic" => "claude",
+            _ => return Ok(None),
+        };
+
+        let access_token = self
+            .get_oauth_setting(prefix, "access_token")
+            .await?
+            .filter(|value| !value.trim().is_empty());
+        let Some(access_token) = access_token else {
+            return Ok(None);
+        };
+
+        // expires_at is stored in seconds for OpenAI and Claude
+        let expires_at = self
+            .get_oauth_setting(prefix, "expires_at")
+            .await?
+            .and_then(|value| value.parse::<i64>().ok());
+        let Some(expires_at) = expires_at else {
+            return Ok(Some(access_token));
+        };
+
+        let now = chrono::Utc::now().timestamp();
+        if now + OAUTH_TOKEN_BUFFER_SECONDS < expires_at {
+            return Ok(Some(access_token));
+        }
+
+        let refresh_token = self
+            .get_oauth_setting(prefix, "refresh_token")
+            .await?
+            .unwrap_or_default();
+        if refresh_token.trim().is_empty() {
+            log::warn!(
+                "OAuth token for {} is expiring but no refresh token is stored",
+                provider_id
+            );
+            return Ok(Some(access_token));
+        }
+
+        log::info!("OAuth token for {} is near expiry, refreshing", provider_id);
+        let client = Client::builder()
+            .timeout(Duration::from_secs(20))
+            .build()
+            .map_err(|e| format!("Failed to build HTTP client: {}", e))?;
+
+        let refreshed = match provider_id {
+            "openai" => {
+                crate::llm::auth::oauth::refresh_openai_oauth_tokens(&client, &refresh_token, self)
+                    .await
+                    .map(|response| response.access_token)
+            }
+            _ => {
+                crate::llm::auth::oauth::refresh_claude_oauth_tokens(&client, &refresh_token, self)
+                    .await
+                    .map(|response| response.access_token)
+            }
+        };
 
-            _ => Ok(None),
+        match refreshed {
+            Ok(token) => Ok(Some(token)),
+            Err(e) => {
+                log::warn!("Failed to refresh OAuth token for {}: {}", provider_id, e);
+                Ok(Some(access_token))
+            }
         }
     }
 
+    /// GitHub Enterprise domain of the Copilot login, or None for github.com
+    pub async fn github_copilot_enterprise_domain(&self) -> Result<Option<String>, String> {
+        // Older logins stored the domain as entered, so normalize again on read
+        Ok(self
+            .get_setting(GITHUB_COPILOT_ENTERPRISE_URL_KEY)
+            .await?
+            .and_then(|value| normalize_github_enterprise_domain(&value).ok().flatten()))
+    }
+
     async fn get_valid_github_copilot_token(&self) -> Result<String, String> {
         let access_token = self
             .get_setting(GITHUB_COPILOT_ACCESS_TOKEN_KEY)
@@ -367,25 +950,17 @@ impl ApiKeyManager {
             }
         }
 
-        let enterprise_url = self
-            .get_setting(GITHUB_COPILOT_ENTERPRISE_URL_KEY)
-            .await?
-            .filter(|value| !value.trim().is_empty());
+        let enterprise_domain = self.github_copilot_enterprise_domain().await?;
 
         let client = Client::builder()
             .timeout(Duration::from_secs(20))
             .build()
             .map_err(|e| format!("Failed to build HTTP client: {}", e))?;
 
-        let base_domain = enterprise_url
-            .as_deref()
-            .map(normalize_domain)
-            .unwrap_or_else(|| "github.com".to_string());
-
         let token_url = if let Ok(override_url) = std::env::var("TALKCODY_COPILOT_TOKEN_URL") {
             override_url
         } else {
-            format!("https://api.{}/copilot_internal/v2/token", base_domain)
+            github_copilot_token_url(enterprise_domain.as_deref().unwrap_or("github.com"))
         };
 
         let response = client
@@ -412,6 +987,7 @@ impl ApiKeyManager {
             ));
         }
 
+        let response_headers = response.headers().clone();
         let payload: serde_json::Value = response
             .json()
             .await
@@ -426,7 +1002,10 @@ impl ApiKeyManager {
             .and_then(|value| value.as_i64())
             .ok_or("Missing Copilot expires_at in response")?;
 
-        let expires_at_ms = expires_at * 1000;
+        // expires_at comes from GitHub's clock; store it in the local clock's frame
+        let (expires_at_ms, skew) =
+            clock_skew::server_expiry_to_local_ms(expires_at, &response_headers, "GitHub");
+        self.record_clock_skew(skew).await?;
 
         self.set_setting(GITHUB_COPILOT_COPILOT_TOKEN_KEY, &token)
             .await?;
@@ -436,6 +1015,131 @@ impl ApiKeyManager {
         Ok(token)
     }
 
+    /// Settings key for an OAuth field of one account. The default account keeps
+    /// the legacy unsuffixed keys so existing logins keep working.
+    pub fn oauth_account_key(prefix: &str, field: &str, label: &str) -> String {
+        if label == DEFAULT_OAUTH_ACCOUNT {
+            format!("{}_oauth_{}", prefix, field)
+        } else {
+            format!("{}_oauth_{}_{}", prefix, field, label)
+        }
+    }
+
+    pub async fn active_oauth_account(&self, prefix: &str) -> Result<String, String> {
+        Ok(self
+            .get_setting(&format!("{}_oauth_active_account", prefix))
+            .await?
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+            .unwrap_or_else(|| DEFAULT_OAUTH_ACCOUNT.to_string()))
+    }
+
+    /// Read an OAuth field (`access_token`, `expires_at`, ...) for the active account
+    pub async fn get_oauth_setting(
+        &self,
+        prefix: &str,
+        field: &str,
+    ) -> Result<Option<String>, String> {
+        let label = self.active_oauth_account(prefix).await?;
+        self.get_setting(&Self::oauth_account_key(prefix, field, &label))
+            .await
+    }
+
+    /// Write an OAuth field for the active account
+    pub async fn set_oauth_setting(
+        &self,
+        prefix: &str,
+        field: &str,
+        value: &str,
+    ) -> Result<(), String> {
+        let label = self.active_oauth_account(prefix).await?;
+        self.set_setting(&Self::oauth_account_key(prefix, field, &label), value)
+            .await
+    }
+
+    async fn stored_oauth_accounts(&self, prefix: &str) -> Result<Vec<String>, String> {
+        Ok(self
+            .get_setting(&format!("{}_oauth_accounts", prefix))
+            .await?
+            .and_then(|raw| serde_json::from_str::<Vec<String>>(&raw).ok())
+            .unwrap_or_default())
+    }
+
+    /// All known account labels; the default account is listed when it holds tokens
+    pub async fn list_oauth_accounts(&self, prefix: &str) -> Result<Vec<String>, String> {
+        let mut accounts = Vec::new();
+        for field in ["access_token", "refresh_token"] {
+            let key = Self::oauth_account_key(prefix, field, DEFAULT_OAUTH_ACCOUNT);
+            if self
+                .get_setting(&key)
+                .await?
+                .is_some_and(|value| !value.trim().is_empty())
+            {
+                accounts.push(DEFAULT_OAUTH_ACCOUNT.to_string());
+                break;
+            }
+        }
+        accounts.extend(self.stored_oauth_accounts(prefix).await?);
+        Ok(accounts)
+    }
+
+    /// Register an account label (if new) and make it the active account
+    pub async fn add_oauth_account(&self, prefix: &str, label: &str) -> Result<(), String> {
+        let label = validate_oauth_account_label(label)?;
+        if label != DEFAULT_OAUTH_ACCOUNT {
+            let mut accounts = self.stored_oauth_accounts(prefix).await?;
+            if !accounts.iter().any(|existing| existing == label) {
+                accounts.push(label.to_string());
+                let raw = serde_json::to_string(&accounts)
+                    .map_err(|e| format!("Failed to serialize OAuth accounts: {}", e))?;
+                self.set_setting(&format!("{}_oauth_accounts", prefix), &raw)
+                    .await?;
+            }
+        }
+        self.set_setting(&format!("{}_oauth_active_account", prefix), label)
+            .await
+    }
+
+    /// Make an existing account the active one
+    pub async fn switch_oauth_account(&self, prefix: &str, label: &str) -> Result<(), String> {
+        let label = validate_oauth_account_label(label)?;
+        let accounts = self.list_oauth_accounts(prefix).await?;
+        if label != DEFAULT_OAUTH_ACCOUNT && !accounts.iter().any(|existing| existing == label) {
+            return Err(format!("Unknown OAuth account: {}", label));
+        }
+        self.set_setting(&format!("{}_oauth_active_account", prefix), label)
+            .await
+    }
+
+    /// Forget an account label; the active pointer falls back to the default account
+    pub async fn remove_oauth_account(&self, prefix: &str, label: &str) -> Result<(), String> {
+        if label != DEFAULT_OAUTH_ACCOUNT {
+            let mut accounts = self.stored_oauth_accounts(prefix).await?;
+            accounts.retain(|existing| existing != label);
+            let raw = serde_json::to_string(&accounts)
+                .map_err(|e| format!("Failed to serialize OAuth accounts: {}", e))?;
+            self.set_setting(&format!("{}_oauth_accounts", prefix), &raw)
+                .await?;
+        }
+        if self.active_oauth_account(prefix).await? == label {
+            self.set_setting(
+                &format!("{}_oauth_active_account", prefix),
+                DEFAULT_OAUTH_ACCOUNT,
+            )
+            .await?;
+        }
+        Ok(())
+    }
+
+    /// Persist the most recently observed clock skew so the UI can surface it
+    pub async fn record_clock_skew(&self, skew_seconds: i64) -> Result<(), String> {
+        self.set_setting(
+            clock_skew::CLOCK_SKEW_SETTING_KEY,
+            &skew_seconds.to_string(),
+        )
+        .await
+    }
+
     pub async fn has_oauth_token(&self, provider_id: &str) -> Result<bool, String> {
         Ok(self
             .get_oauth_token(provider_id)
@@ -452,7 +1156,7 @@ impl ApiKeyManager {
         if provider_id != "openai" {
             return Ok(());
         }
-        if let Some(account_id) = self.get_setting("openai_oauth_account_id").await? {
+        if let Some(account_id) = self.get_oauth_setting("openai", "account_id").await? {
             if !account_id.trim().is_empty() {
                 headers.insert("chatgpt-account-id".to_string(), account_id);
             }
@@ -462,12 +1166,12 @@ impl ApiKeyManager {
 
     pub async fn load_oauth_tokens(&self) -> Result<HashMap<String, String>, String> {
         let mut tokens = HashMap::new();
-        if let Some(token) = self.get_setting("openai_oauth_access_token").await? {
+        if let Some(token) = self.get_oauth_setting("openai", "access_token").await? {
             if !token.trim().is_empty() {
                 tokens.insert("openai".to_string(), token);
             }
         }
-        if let Some(token) = self.get_setting("claude_oauth_access_token").await? {
+        if let Some(token) = self.get_oauth_setting("claude", "access_token").await? {
             if !token.trim().is_empty() {
                 tokens.insert("anthropic".to_string(), token);
             }
@@ -485,6 +1189,30 @@ impl ApiKeyManager {
     }
 }
 
+fn validate_oauth_account_label(label: &str) -> Result<&str, String> {
+    let label = label.trim();
+    if label.is_empty()
+        || !label
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        return Err(format!("Invalid OAuth account label: {}", label));
+    }
+    Ok(label)
+}
+
+/// Map a provider id to the settings prefix used for its OAuth keys
+pub fn oauth_settings_prefix(provider: &str) -> Result<&'static str, String> {
+    match provider {
+        "openai" => Ok("openai"),
+        "anthropic" | "claude" => Ok("claude"),
+        _ => Err(format!(
+            "OAuth accounts are not supported for provider: {}",
+            provider
+        )),
+    }
+}
+
 pub fn normalize_domain(url: &str) -> String {
     url.trim()
         .trim_start_matches("https://")
@@ -493,6 +1221,38 @@ pub fn normalize_domain(url: &str) -> String {
         .to_string()
 }
 
+/// Host of a GitHub Enterprise deployment from user input such as
+/// `https://company.ghe.com/` or `company.ghe.com/login`. Blank input and
+/// github.com itself yield `None`, meaning the public endpoints.
+pub fn normalize_github_enterprise_domain(input: &str) -> Result<Option<String>, String> {
+    let domain = normalize_domain(input);
+    let host = domain
+        .split(['/', '?', '#'])
+        .next()
+        .unwrap_or_default()
+        .to_ascii_lowercase();
+    if host.is_empty() || host == "github.com" || host == "www.github.com" {
+        return Ok(None);
+    }
+    if !host.contains('.') || host.chars().any(|c| c.is_whitespace() || c == '@') {
+        return Err(format!(
+            "Invalid GitHub Enterprise domain: {}",
+            input.trim()
+        ));
+    }
+    Ok(Some(host))
+}
+
+/// REST API base of a GitHub domain, e.g. `https://api.company.ghe.com`
+pub(crate) fn github_api_base_url(domain: &str) -> String {
+    format!("https://api.{}", domain)
+}
+
+/// Copilot token exchange endpoint for a GitHub domain
+pub(crate) fn github_copilot_token_url(domain: &str) -> String {
+    format!("{}/copilot_internal/v2/token", github_api_base_url(domain))
+}
+
 #[derive(Debug)]
 pub enum ProviderCredentials {
     None,
@@ -517,13 +1277,54 @@ impl LlmState {
 }
 
 #[tauri::command]
-pub async fn llm_set_setting(
+pub async fn llm_get_setting(
     key: String,
-    value: String,
     state: State<'_, LlmState>,
-) -> Result<(), String> {
+) -> Result<Option<String>, String> {
     let api_keys = state.api_keys.lock().await;
-    api_keys.set_setting(&key, &value).await
+    api_keys.get_setting(&key).await
+}
+
+#[tauri::command]
+pub async fn llm_set_setting(
+    key: String,
+    value: String,
+    state: State<'_, LlmState>,
+) -> Result<(), String> {
+    let api_keys = state.api_keys.lock().await;
+    api_keys.set_setting(&key, &value).await
+}
+
+/// Export settings and custom providers as a JSON blob for another machine
+#[tauri::command]
+pub async fn settings_export(
+    include_secrets: bool,
+    state: State<'_, LlmState>,
+) -> Result<String, String> {
+    let api_keys = state.api_keys.lock().await;
+    let export = api_keys.export_settings(include_secrets).await?;
+    serde_json::to_string_pretty(&export)
+        .map_err(|e| format!("Failed to serialize settings export: {}", e))
+}
+
+/// Restore a blob produced by `settings_export`
+#[tauri::command]
+pub async fn settings_import(
+    blob: String,
+    overwrite: bool,
+    state: State<'_, LlmState>,
+) -> Result<SettingsImportSummary, String> {
+    let export: SettingsExport =
+        serde_json::from_str(&blob).map_err(|e| format!("Invalid settings export: {}", e))?;
+    let api_keys = state.api_keys.lock().await;
+    api_keys.import_settings(&export, overwrite).await
+}
+
+/// Move stored API keys and OAuth tokens into the OS keyring
+#[tauri::command]
+pub async fn llm_migrate_secrets_to_keyring(state: State<'_, LlmState>) -> Result<usize, String> {
+    let api_keys = state.api_keys.lock().await;
+    api_keys.migrate_secrets_to_keyring().await
 }
 
 #[cfg(test)]
@@ -557,6 +1358,74 @@ mod tests {
         }
     }
 
+    fn serve_once(body: &'static str) -> (String, std::thread::JoinHandle<()>) {
+        let server = tiny_http::Server::http("127.0.0.1:0").expect("server");
+        let url = match server.server_addr() {
+            tiny_http::ListenAddr::IP(addr) => format!("http://{}/models.json", addr),
+            _ => panic!("Expected IP SocketAddr"),
+        };
+        let handle = std::thread::spawn(move || {
+            if let Ok(request) = server.recv() {
+                let _ = request.respond(tiny_http::Response::from_string(body));
+            }
+        });
+        (url, handle)
+    }
+
+    #[tokio::test]
+    async fn refresh_models_from_remote_updates_stored_config() {
+        let ctx = setup().await;
+        let (url, server) = serve_once(
+            r#"{"version":"2099.1","models":{"remote-model":{"name":"Remote","providers":["openai"],"providerMappings":null,"pricing":null,"context_length":null}}}"#,
+        );
+
+        let config = ctx
+            .api_keys
+            .refresh_models_from_remote(&url)
+            .await
+            .expect("refresh");
+        server.join().expect("server join");
+        assert_eq!(config.version, "2099.1");
+
+        let stored = ctx
+            .api_keys
+            .get_setting("models_config_json")
+            .await
+            .expect("read stored config")
+            .expect("stored config");
+        let stored: ModelsConfiguration = serde_json::from_str(&stored).expect("parse stored");
+        assert_eq!(stored.version, "2099.1");
+        assert!(stored.models.contains_key("remote-model"));
+        let loaded = ctx.api_keys.load_models_config().await.expect("load");
+        assert!(loaded.models.contains_key("remote-model"));
+    }
+
+    #[tokio::test]
+    async fn refresh_models_from_remote_keeps_config_on_malformed_response() {
+        let ctx = setup().await;
+        let previous = r#"{"version":"1","models":{"gpt-4o":{"name":"GPT-4o","providers":["openai"],"providerMappings":null,"pricing":null,"context_length":null}}}"#;
+        ctx.api_keys
+            .set_setting("models_config_json", previous)
+            .await
+            .expect("seed config");
+        let (url, server) = serve_once(r#"{"version":"2","models":"#);
+
+        let err = ctx
+            .api_keys
+            .refresh_models_from_remote(&url)
+            .await
+            .unwrap_err();
+        server.join().expect("server join");
+        assert!(err.contains("Failed to parse remote models config"));
+
+        let stored = ctx
+            .api_keys
+            .get_setting("models_config_json")
+            .await
+            .expect("read stored config");
+        assert_eq!(stored.as_deref(), Some(previous));
+    }
+
     #[tokio::test]
     async fn github_copilot_refreshes_expired_token() {
         let ctx = setup().await;
@@ -628,6 +1497,212 @@ mod tests {
         std::env::remove_var("TALKCODY_COPILOT_TOKEN_URL");
     }
 
+    #[test]
+    fn normalize_domain_strips_scheme_and_trailing_slash() {
+        assert_eq!(
+            normalize_domain("https://github.mycorp.com/"),
+            "github.mycorp.com"
+        );
+        assert_eq!(
+            normalize_domain("http://github.mycorp.com"),
+            "github.mycorp.com"
+        );
+        assert_eq!(normalize_domain("github.mycorp.com"), "github.mycorp.com");
+        assert_eq!(normalize_domain("  ghe-host  "), "ghe-host");
+        assert_eq!(normalize_domain(""), "");
+    }
+
+    #[test]
+    fn normalizes_github_enterprise_domain() {
+        for input in [
+            "https://company.ghe.com",
+            "company.ghe.com/",
+            "  HTTPS://Company.GHE.com/login/device  ",
+            "http://company.ghe.com?next=/",
+        ] {
+            assert_eq!(
+                normalize_github_enterprise_domain(input)
+                    .unwrap()
+                    .as_deref(),
+                Some("company.ghe.com"),
+                "input: {}",
+                input
+            );
+        }
+        assert_eq!(
+            normalize_github_enterprise_domain("github.example.com:8443")
+                .unwrap()
+                .as_deref(),
+            Some("github.example.com:8443")
+        );
+        for public in ["", "   ", "https://github.com/", "www.github.com"] {
+            assert_eq!(normalize_github_enterprise_domain(public).unwrap(), None);
+        }
+        assert!(normalize_github_enterprise_domain("not a domain").is_err());
+        assert!(normalize_github_enterprise_domain("localhost").is_err());
+        assert!(normalize_github_enterprise_domain("user@company.ghe.com").is_err());
+    }
+
+    #[test]
+    fn github_copilot_token_url_targets_enterprise_api_host() {
+        assert_eq!(
+            github_copilot_token_url("github.com"),
+            "https://api.github.com/copilot_internal/v2/token"
+        );
+        assert_eq!(
+            github_copilot_token_url("company.ghe.com"),
+            "https://api.company.ghe.com/copilot_internal/v2/token"
+        );
+        assert_eq!(
+            github_api_base_url("github.mycorp.com"),
+            "https://api.github.mycorp.com"
+        );
+    }
+
+    /// Serve a single token response on a local port and return its URL
+    fn spawn_token_server(response_body: String) -> (String, std::thread::JoinHandle<bool>) {
+        let server = tiny_http::Server::http("127.0.0.1:0").expect("server");
+        let port = match server.server_addr() {
+            tiny_http::ListenAddr::IP(socket_addr) => socket_addr.port(),
+            _ => panic!("Expected IP SocketAddr"),
+        };
+        let handle = std::thread::spawn(move || {
+            match server.recv_timeout(std::time::Duration::from_secs(10)) {
+                Ok(Some(request)) => {
+                    let response = tiny_http::Response::from_string(response_body).with_header(
+                        tiny_http::Header::from_bytes(
+                            &b"Content-Type"[..],
+                            &b"application/json"[..],
+                        )
+                        .expect("header"),
+                    );
+                    let _ = request.respond(response);
+                    true
+                }
+                _ => false,
+            }
+        });
+        (format!("http://127.0.0.1:{}/oauth/token", port), handle)
+    }
+
+    #[tokio::test]
+    async fn openai_oauth_refreshes_token_near_expiry() {
+        let ctx = setup().await;
+        let (token_url, server_handle) = spawn_token_server(
+            "{\"access_token\":\"new-openai-token\",\"refresh_token\":\"new-refresh\",\"expires_in\":3600}"
+                .to_string(),
+        );
+        std::env::set_var("TALKCODY_OPENAI_TOKEN_URL", &token_url);
+
+        let backdated = chrono::Utc::now().timestamp() - 10;
+        ctx.api_keys
+            .set_setting("openai_oauth_access_token", "old-openai-token")
+            .await
+            .expect("set access token");
+        ctx.api_keys
+            .set_setting("openai_oauth_refresh_token", "refresh")
+            .await
+            .expect("set refresh token");
+        ctx.api_keys
+            .set_setting("openai_oauth_expires_at", &backdated.to_string())
+            .await
+            .expect("set expires");
+
+        let token = ctx
+            .api_keys
+            .get_valid_oauth_token("openai")
+            .await
+            .expect("get token");
+        assert_eq!(token.as_deref(), Some("new-openai-token"));
+        assert!(server_handle.join().expect("server join"));
+
+        let stored_refresh = ctx
+            .api_keys
+            .get_setting("openai_oauth_refresh_token")
+            .await
+            .expect("read refresh")
+            .unwrap_or_default();
+        assert_eq!(stored_refresh, "new-refresh");
+        let stored_expires = ctx
+            .api_keys
+            .get_setting("openai_oauth_expires_at")
+            .await
+            .expect("read expires")
+            .and_then(|value| value.parse::<i64>().ok())
+            .expect("expires parsed");
+        assert!(stored_expires > chrono::Utc::now().timestamp());
+
+        std::env::remove_var("TALKCODY_OPENAI_TOKEN_URL");
+    }
+
+    #[tokio::test]
+    async fn claude_oauth_refreshes_token_near_expiry() {
+        let ctx = setup().await;
+        let (token_url, server_handle) = spawn_token_server(
+            "{\"access_token\":\"new-claude-token\",\"expires_in\":3600}".to_string(),
+        );
+        std::env::set_var("TALKCODY_CLAUDE_TOKEN_URL", &token_url);
+
+        // Inside the 60s buffer but not yet expired
+        let near_expiry = chrono::Utc::now().timestamp() + 30;
+        ctx.api_keys
+            .set_setting("claude_oauth_access_token", "old-claude-token")
+            .await
+            .expect("set access token");
+        ctx.api_keys
+            .set_setting("claude_oauth_refresh_token", "refresh")
+            .await
+            .expect("set refresh token");
+        ctx.api_keys
+            .set_setting("claude_oauth_expires_at", &near_expiry.to_string())
+            .await
+            .expect("set expires");
+
+        let token = ctx
+            .api_keys
+            .get_valid_oauth_token("anthropic")
+            .await
+            .expect("get token");
+        assert_eq!(token.as_deref(), Some("new-claude-token"));
+        assert!(server_handle.join().expect("server join"));
+
+        // Refresh token is kept when the response omits it
+        let stored_refresh = ctx
+            .api_keys
+            .get_setting("claude_oauth_refresh_token")
+            .await
+            .expect("read refresh")
+            .unwrap_or_default();
+        assert_eq!(stored_refresh, "refresh");
+
+        std::env::remove_var("TALKCODY_CLAUDE_TOKEN_URL");
+    }
+
+    #[tokio::test]
+    async fn oauth_token_not_refreshed_when_far_from_expiry() {
+        let ctx = setup().await;
+        let expires_at = chrono::Utc::now().timestamp() + 3600;
+        ctx.api_keys
+            .set_setting("claude_oauth_access_token", "current-token")
+            .await
+            .expect("set access token");
+        ctx.api_keys
+            .set_setting("claude_oauth_refresh_token", "refresh")
+            .await
+            .expect("set refresh token");
+        ctx.api_keys
+            .set_setting("claude_oauth_expires_at", &expires_at.to_string())
+            .await
+            .expect("set expires");
+
+        let token = ctx
+            .api_keys
+            .get_valid_oauth_token("anthropic")
+            .await
+            .expect("get token");
+        assert_eq!(token.as_deref(), Some("current-token"));
+    }
+
     fn provider_config(id: &str, auth_type: AuthType, supports_oauth: bool) -> ProviderConfig {
         ProviderConfig {
             id: id.to_string(),
@@ -643,6 +1718,7 @@ mod tests {
             headers: None,
             extra_body: None,
             auth_type,
+            body_transforms: None,
         }
     }
 
@@ -689,6 +1765,58 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn custom_provider_uses_refreshed_generic_oauth_token() {
+        let ctx = setup().await;
+        let (token_url, server_handle) = spawn_token_server(
+            "{\"access_token\":\"new-gateway-token\",\"expires_in\":3600}".to_string(),
+        );
+        let config = serde_json::json!({
+            "clientId": "gateway-client",
+            "authUrl": "https://gateway.example.com/oauth/authorize",
+            "tokenUrl": token_url,
+            "redirectUri": "http://localhost:1455/auth/callback",
+        });
+        let backdated = chrono::Utc::now().timestamp() - 10;
+        for (key, value) in [
+            ("generic_gateway_oauth_config", config.to_string()),
+            (
+                "generic_gateway_oauth_access_token",
+                "old-gateway-token".to_string(),
+            ),
+            (
+                "generic_gateway_oauth_refresh_token",
+                "gateway-refresh".to_string(),
+            ),
+            ("generic_gateway_oauth_expires_at", backdated.to_string()),
+        ] {
+            ctx.api_keys
+                .set_setting(key, &value)
+                .await
+                .expect("seed generic oauth");
+        }
+
+        let provider = provider_config("gateway", AuthType::Bearer, false);
+        let creds = ctx
+            .api_keys
+            .get_credentials(&provider)
+            .await
+            .expect("credentials");
+        match creds {
+            ProviderCredentials::Token(token) => assert_eq!(token, "new-gateway-token"),
+            _ => panic!("Expected token credentials"),
+        }
+        assert!(server_handle.join().expect("server join"));
+        assert_eq!(
+            ctx.api_keys
+                .get_setting("generic_gateway_oauth_refresh_token")
+                .await
+                .unwrap()
+                .as_deref(),
+            Some("gateway-refresh")
+        );
+    }
+
     #[tokio::test]
     async fn get_credentials_falls_back_to_api_key() {
         let ctx = setup().await;
@@ -704,6 +1832,310 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn get_credentials_uses_env_var_when_no_stored_key() {
+        let ctx = setup().await;
+        let mut provider = provider_config("env-provider", AuthType::Bearer, false);
+        provider.api_key_name = "TALKCODY_TEST_ENV_PROVIDER_API_KEY".to_string();
+        std::env::set_var(&provider.api_key_name, "env-key");
+
+        match ctx.api_keys.get_credentials(&provider).await {
+            Ok(ProviderCredentials::Token(value)) => assert_eq!(value, "env-key"),
+            _ => panic!("Unexpected credentials"),
+        }
+
+        ctx.api_keys
+            .set_setting("api_key_env-provider", "stored-key")
+            .await
+            .expect("set api key");
+        match ctx.api_keys.get_credentials(&provider).await {
+            Ok(ProviderCredentials::Token(value)) => assert_eq!(value, "stored-key"),
+            _ => panic!("Unexpected credentials"),
+        }
+
+        ctx.api_keys
+            .set_setting("api_key_env-provider", "")
+            .await
+            .expect("clear api key");
+        ctx.api_keys
+            .set_setting(ALLOW_ENV_API_KEYS_KEY, "false")
+            .await
+            .expect("disable env keys");
+        assert!(ctx.api_keys.get_credentials(&provider).await.is_err());
+
+        std::env::remove_var(&provider.api_key_name);
+    }
+
+    #[tokio::test]
+    async fn load_api_keys_falls_back_to_env_for_builtin_providers() {
+        let ctx = setup().await;
+        std::env::set_var("VOLCENGINE_API_KEY", "volc-env-key");
+
+        let keys = ctx.api_keys.load_api_keys().await.expect("load keys");
+        assert_eq!(
+            keys.get("volcengine").map(String::as_str),
+            Some("volc-env-key")
+        );
+
+        ctx.api_keys
+            .set_setting("api_key_volcengine", "volc-db-key")
+            .await
+            .expect("set api key");
+        let keys = ctx.api_keys.load_api_keys().await.expect("load keys");
+        assert_eq!(
+            keys.get("volcengine").map(String::as_str),
+            Some("volc-db-key")
+        );
+
+        std::env::remove_var("VOLCENGINE_API_KEY");
+    }
+
+    #[derive(Default)]
+    struct MockKeyring {
+        entries: std::sync::Mutex<HashMap<String, String>>,
+        /// Number of writes that succeed before every later one fails
+        writes_before_failure: Option<std::sync::atomic::AtomicUsize>,
+    }
+
+    impl SecretStore for MockKeyring {
+        fn get(&self, key: &str) -> Result<Option<String>, String> {
+            Ok(self.entries.lock().unwrap().get(key).cloned())
+        }
+
+        fn set(&self, key: &str, value: &str) -> Result<(), String> {
+            if let Some(remaining) = &self.writes_before_failure {
+                let left = remaining.load(std::sync::atomic::Ordering::SeqCst);
+                if left == 0 {
+                    return Err(format!("Failed to write {} to keyring: locked", key));
+                }
+                remaining.store(left - 1, std::sync::atomic::Ordering::SeqCst);
+            }
+            self.entries
+                .lock()
+                .unwrap()
+                .insert(key.to_string(), value.to_string());
+            Ok(())
+        }
+
+        fn delete(&self, key: &str) -> Result<(), String> {
+            self.entries.lock().unwrap().remove(key);
+            Ok(())
+        }
+    }
+
+    async fn setup_with_keyring() -> (TestContext, Arc<MockKeyring>) {
+        let ctx = setup().await;
+        let keyring = Arc::new(MockKeyring::default());
+        let api_keys = ctx.api_keys.with_secret_store(keyring.clone());
+        (
+            TestContext {
+                _dir: ctx._dir,
+                api_keys,
+            },
+            keyring,
+        )
+    }
+
+    #[tokio::test]
+    async fn database_backend_keeps_secrets_out_of_keyring() {
+        let (ctx, keyring) = setup_with_keyring().await;
+        assert_eq!(
+            ctx.api_keys.settings_backend().await.unwrap(),
+            SettingsBackend::Database
+        );
+
+        ctx.api_keys
+            .set_setting("api_key_openai", "sk-db")
+            .await
+            .expect("set api key");
+
+        assert!(keyring.entries.lock().unwrap().is_empty());
+        assert_eq!(
+            ctx.api_keys.get_db_setting("api_key_openai").await.unwrap(),
+            Some("sk-db".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn keyring_backend_stores_only_sensitive_keys() {
+        let (ctx, keyring) = setup_with_keyring().await;
+        ctx.api_keys
+            .set_setting(SETTINGS_BACKEND_KEY, "keyring")
+            .await
+            .expect("select keyring");
+
+        ctx.api_keys
+            .set_setting("api_key_deepseek", "sk-keyring")
+            .await
+            .expect("set api key");
+        ctx.api_keys
+            .set_setting("base_url_deepseek", "https://example.com")
+            .await
+            .expect("set base url");
+
+        assert_eq!(
+            keyring.entries.lock().unwrap().get("api_key_deepseek"),
+            Some(&"sk-keyring".to_string())
+        );
+        assert!(!keyring
+            .entries
+            .lock()
+            .unwrap()
+            .contains_key("base_url_deepseek"));
+        assert_eq!(
+            ctx.api_keys
+                .get_db_setting("api_key_deepseek")
+                .await
+                .unwrap(),
+            None
+        );
+        assert_eq!(
+            ctx.api_keys.get_setting("api_key_deepseek").await.unwrap(),
+            Some("sk-keyring".to_string())
+        );
+        let keys = ctx.api_keys.load_api_keys().await.expect("load keys");
+        assert_eq!(keys.get("deepseek").map(String::as_str), Some("sk-keyring"));
+    }
+
+    #[tokio::test]
+    async fn migrate_secrets_moves_database_keys_into_keyring() {
+        let (ctx, keyring) = setup_with_keyring().await;
+        ctx.api_keys
+            .set_setting("api_key_openai", "sk-migrate")
+            .await
+            .expect("set api key");
+        ctx.api_keys
+            .set_setting("openai_oauth_refresh_token", "refresh")
+            .await
+            .expect("set refresh token");
+        ctx.api_keys
+            .set_setting("openai_oauth_expires_at", "123")
+            .await
+            .expect("set expiry");
+
+        let moved = ctx
+            .api_keys
+            .migrate_secrets_to_keyring()
+            .await
+            .expect("migrate");
+
+        assert_eq!(moved, 2);
+        assert_eq!(
+            ctx.api_keys.settings_backend().await.unwrap(),
+            SettingsBackend::Keyring
+        );
+        {
+            let entries = keyring.entries.lock().unwrap();
+            assert_eq!(
+                entries.get("api_key_openai"),
+                Some(&"sk-migrate".to_string())
+            );
+            assert_eq!(
+                entries.get("openai_oauth_refresh_token"),
+                Some(&"refresh".to_string())
+            );
+        }
+        assert_eq!(
+            ctx.api_keys.get_db_setting("api_key_openai").await.unwrap(),
+            None
+        );
+        assert_eq!(
+            ctx.api_keys
+                .get_setting("openai_oauth_expires_at")
+                .await
+                .unwrap(),
+            Some("123".to_string())
+        );
+        assert_eq!(
+            ctx.api_keys.get_setting("api_key_openai").await.unwrap(),
+            Some("sk-migrate".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn database_write_after_migration_replaces_keyring_value() {
+        let (ctx, keyring) = setup_with_keyring().await;
+        ctx.api_keys
+            .set_setting("api_key_openai", "sk-old")
+            .await
+            .expect("set api key");
+        ctx.api_keys
+            .migrate_secrets_to_keyring()
+            .await
+            .expect("migrate");
+
+        // A client writing the settings table directly, bypassing the keyring
+        ctx.api_keys
+            .set_db_setting("api_key_openai", "sk-new")
+            .await
+            .expect("write database row");
+
+        assert_eq!(
+            ctx.api_keys.get_setting("api_key_openai").await.unwrap(),
+            Some("sk-new".to_string())
+        );
+        assert_eq!(
+            keyring.entries.lock().unwrap().get("api_key_openai"),
+            Some(&"sk-new".to_string())
+        );
+        assert_eq!(
+            ctx.api_keys.get_db_setting("api_key_openai").await.unwrap(),
+            None
+        );
+
+        // The frontend seeds empty defaults; those must not clear the secret
+        ctx.api_keys
+            .set_db_setting("api_key_openai", "")
+            .await
+            .expect("seed empty default");
+        assert_eq!(
+            ctx.api_keys.get_setting("api_key_openai").await.unwrap(),
+            Some("sk-new".to_string())
+        );
+    }
+
+    #[tokio::test]
+    async fn failed_migration_keeps_database_secrets() {
+        let ctx = setup().await;
+        let keyring = Arc::new(MockKeyring {
+            writes_before_failure: Some(std::sync::atomic::AtomicUsize::new(1)),
+            ..Default::default()
+        });
+        let api_keys = ctx.api_keys.with_secret_store(keyring.clone());
+        for (key, value) in [
+            ("api_key_openai", "sk-openai"),
+            ("api_key_deepseek", "sk-deepseek"),
+            ("openai_oauth_refresh_token", "refresh"),
+        ] {
+            api_keys.set_setting(key, value).await.expect("seed secret");
+        }
+
+        assert!(api_keys.migrate_secrets_to_keyring().await.is_err());
+
+        assert_eq!(
+            api_keys.settings_backend().await.unwrap(),
+            SettingsBackend::Database
+        );
+        for (key, value) in [
+            ("api_key_openai", "sk-openai"),
+            ("api_key_deepseek", "sk-deepseek"),
+            ("openai_oauth_refresh_token", "refresh"),
+        ] {
+            assert_eq!(
+                api_keys.get_db_setting(key).await.unwrap().as_deref(),
+                Some(value),
+                "{}",
+                key
+            );
+            assert_eq!(
+                api_keys.get_setting(key).await.unwrap().as_deref(),
+                Some(value),
+                "{}",
+                key
+            );
+        }
+    }
+
     #[tokio::test]
     async fn get_credentials_none_auth() {
         let ctx = setup().await;
@@ -739,4 +2171,372 @@ mod tests {
             .expect("no header");
         assert!(other_headers.get("chatgpt-account-id").is_none());
     }
+
+    #[tokio::test]
+    async fn oauth_accounts_add_switch_and_list() {
+        let ctx = setup().await;
+        // Legacy single-account keys surface as the default account
+        ctx.api_keys
+            .set_setting("openai_oauth_access_token", "legacy-token")
+            .await
+            .expect("set legacy token");
+        assert_eq!(
+            ctx.api_keys.list_oauth_accounts("openai").await.unwrap(),
+            vec![DEFAULT_OAUTH_ACCOUNT.to_string()]
+        );
+
+        ctx.api_keys
+            .add_oauth_account("openai", "work")
+            .await
+            .expect("add account");
+        ctx.api_keys
+            .set_oauth_setting("openai", "access_token", "work-token")
+            .await
+            .expect("set work token");
+        assert_eq!(
+            ctx.api_keys
+                .get_setting("openai_oauth_access_token_work")
+                .await
+                .unwrap(),
+            Some("work-token".to_string())
+        );
+        assert_eq!(
+            ctx.api_keys.list_oauth_accounts("openai").await.unwrap(),
+            vec!["default".to_string(), "work".to_string()]
+        );
+        assert_eq!(
+            ctx.api_keys.get_oauth_token("openai").await.unwrap(),
+            Some("work-token".to_string())
+        );
+
+        ctx.api_keys
+            .switch_oauth_account("openai", DEFAULT_OAUTH_ACCOUNT)
+            .await
+            .expect("switch to default");
+        assert_eq!(
+            ctx.api_keys.get_oauth_token("openai").await.unwrap(),
+            Some("legacy-token".to_string())
+        );
+
+        assert!(ctx
+            .api_keys
+            .switch_oauth_account("openai", "personal")
+            .await
+            .is_err());
+        assert_eq!(
+            ctx.api_keys.active_oauth_account("openai").await.unwrap(),
+            DEFAULT_OAUTH_ACCOUNT
+        );
+    }
+
+    #[tokio::test]
+    async fn removing_active_oauth_account_falls_back_to_default() {
+        let ctx = setup().await;
+        ctx.api_keys
+            .add_oauth_account("claude", "work")
+            .await
+            .expect("add account");
+        ctx.api_keys
+            .remove_oauth_account("claude", "work")
+            .await
+            .expect("remove account");
+        assert_eq!(
+            ctx.api_keys.active_oauth_account("claude").await.unwrap(),
+            DEFAULT_OAUTH_ACCOUNT
+        );
+        assert!(ctx
+            .api_keys
+            .list_oauth_accounts("claude")
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    /// Manager with its own app data dir so custom providers don't leak between tests
+    async fn transfer_context() -> (TempDir, ApiKeyManager) {
+        let dir = TempDir::new().expect("temp dir");
+        let db = Arc::new(Database::new(
+            dir.path().join("settings.db").to_string_lossy().to_string(),
+        ));
+        db.connect().await.expect("db connect");
+        db.execute(
+            "CREATE TABLE IF NOT EXISTS settings (key TEXT PRIMARY KEY, value TEXT, updated_at INTEGER)",
+            vec![],
+        )
+        .await
+        .expect("create settings");
+        let api_keys = ApiKeyManager::new(db, dir.path().to_path_buf());
+        (dir, api_keys)
+    }
+
+    fn custom_provider(id: &str, api_key: &str) -> crate::llm::types::CustomProviderConfig {
+        crate::llm::types::CustomProviderConfig {
+            id: id.to_string(),
+            name: id.to_string(),
+            provider_type: crate::llm::types::CustomProviderType::OpenAiCompatible,
+            base_url: format!("https://{}.example.com/v1", id),
+            api_key: api_key.to_string(),
+            enabled: true,
+            description: None,
+        }
+    }
+
+    async fn seed_transfer_source(api_keys: &ApiKeyManager) {
+        for (key, value) in [
+            ("theme", "dark"),
+            ("base_url_ollama", "http://10.0.0.2:11434"),
+            ("api_key_openai", "sk-openai"),
+            ("github_copilot_oauth_access_token", "gho_abc"),
+            ("github_copilot_oauth_enterprise_url", "company.ghe.com"),
+            (SETTINGS_BACKEND_KEY, "database"),
+        ] {
+            api_keys
+                .set_setting(key, value)
+                .await
+                .expect("seed setting");
+        }
+        let mut providers = HashMap::new();
+        providers.insert("acme".to_string(), custom_provider("acme", "acme-key"));
+        api_keys
+            .save_custom_providers(&CustomProvidersConfiguration {
+                version: "1".to_string(),
+                providers,
+            })
+            .await
+            .expect("save custom providers");
+    }
+
+    #[tokio::test]
+    async fn settings_round_trip_with_secrets() {
+        let (_src_dir, source) = transfer_context().await;
+        seed_transfer_source(&source).await;
+
+        let export = source.export_settings(true).await.expect("export");
+        assert!(export.includes_secrets);
+        assert!(!export.settings.contains_key(SETTINGS_BACKEND_KEY));
+        let blob = serde_json::to_string(&export).expect("serialize");
+
+        let (_dst_dir, target) = transfer_context().await;
+        let parsed: SettingsExport = serde_json::from_str(&blob).expect("parse");
+        let summary = target
+            .import_settings(&parsed, false)
+            .await
+            .expect("import");
+        assert_eq!(summary.imported, 5);
+        assert_eq!(summary.custom_providers_imported, 1);
+
+        for (key, value) in [
+            ("theme", "dark"),
+            ("api_key_openai", "sk-openai"),
+            ("github_copilot_oauth_access_token", "gho_abc"),
+            ("github_copilot_oauth_enterprise_url", "company.ghe.com"),
+        ] {
+            assert_eq!(
+                target.get_setting(key).await.unwrap().as_deref(),
+                Some(value),
+                "{}",
+                key
+            );
+        }
+        let providers = target.load_custom_providers().await.unwrap().providers;
+        assert_eq!(providers["acme"].api_key, "acme-key");
+    }
+
+    #[tokio::test]
+    async fn settings_export_without_secrets_omits_credentials() {
+        let (_src_dir, source) = transfer_context().await;
+        seed_transfer_source(&source).await;
+
+        let export = source.export_settings(false).await.expect("export");
+        assert!(!export.includes_secrets);
+        assert_eq!(
+            export
+                .settings
+                .keys()
+                .map(String::as_str)
+                .collect::<Vec<_>>(),
+            vec![
+                "base_url_ollama",
+                "github_copilot_oauth_enterprise_url",
+                "theme"
+            ]
+        );
+        let custom = export.custom_providers.as_ref().expect("custom providers");
+        assert_eq!(custom.providers["acme"].api_key, "");
+
+        let (_dst_dir, target) = transfer_context().await;
+        target
+            .import_settings(&export, false)
+            .await
+            .expect("import");
+        assert_eq!(
+            target.get_setting("theme").await.unwrap().as_deref(),
+            Some("dark")
+        );
+        assert_eq!(target.get_setting("api_key_openai").await.unwrap(), None);
+        assert_eq!(
+            target
+                .get_setting("github_copilot_oauth_access_token")
+                .await
+                .unwrap(),
+            None
+        );
+        let providers = target.load_custom_providers().await.unwrap().providers;
+        assert_eq!(providers["acme"].base_url, "https://acme.example.com/v1");
+    }
+
+    #[tokio::test]
+    async fn settings_export_reads_oauth_tokens_back_from_keyring() {
+        let (_src_dir, source) = transfer_context().await;
+        let keyring = Arc::new(MockKeyring::default());
+        let source = source.with_secret_store(keyring.clone());
+        seed_transfer_source(&source).await;
+        source.migrate_secrets_to_keyring().await.expect("migrate");
+        source
+            .add_oauth_account("claude", "work")
+            .await
+            .expect("add account");
+        source
+            .set_oauth_setting("claude", "refresh_token", "claude-refresh")
+            .await
+            .expect("set refresh token");
+        assert!(keyring
+            .entries
+            .lock()
+            .unwrap()
+            .contains_key("claude_oauth_refresh_token_work"));
+
+        let export = source.export_settings(true).await.expect("export");
+        for (key, value) in [
+            ("api_key_openai", "sk-openai"),
+            ("github_copilot_oauth_access_token", "gho_abc"),
+            ("claude_oauth_refresh_token_work", "claude-refresh"),
+            ("github_copilot_oauth_enterprise_url", "company.ghe.com"),
+        ] {
+            assert_eq!(
+                export.settings.get(key).map(String::as_str),
+                Some(value),
+                "{}",
+                key
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn settings_import_respects_overwrite_flag() {
+        let (_src_dir, source) = transfer_context().await;
+        seed_transfer_source(&source).await;
+        let export = source.export_settings(true).await.expect("export");
+
+        let (_dst_dir, target) = transfer_context().await;
+        target.set_setting("theme", "light").await.unwrap();
+        let mut providers = HashMap::new();
+        providers.insert("acme".to_string(), custom_provider("acme", "local-key"));
+        target
+            .save_custom_providers(&CustomProvidersConfiguration {
+                version: "1".to_string(),
+                providers,
+            })
+            .await
+            .unwrap();
+
+        let summary = target.import_settings(&export, false).await.unwrap();
+        assert_eq!(summary.skipped, 1);
+        assert_eq!(summary.imported, 4);
+        assert_eq!(summary.custom_providers_imported, 0);
+        assert_eq!(
+            target.get_setting("theme").await.unwrap().as_deref(),
+            Some("light")
+        );
+        assert_eq!(
+            target.load_custom_providers().await.unwrap().providers["acme"].api_key,
+            "local-key"
+        );
+
+        let summary = target.import_settings(&export, true).await.unwrap();
+        assert_eq!(summary.skipped, 0);
+        assert_eq!(summary.imported, 5);
+        assert_eq!(summary.custom_providers_imported, 1);
+        assert_eq!(
+            target.get_setting("theme").await.unwrap().as_deref(),
+            Some("dark")
+        );
+        assert_eq!(
+            target.load_custom_providers().await.unwrap().providers["acme"].api_key,
+            "acme-key"
+        );
+    }
+
+    #[tokio::test]
+    async fn settings_import_without_secrets_keeps_target_keys() {
+        let (_src_dir, source) = transfer_context().await;
+        seed_transfer_source(&source).await;
+        let export = source.export_settings(false).await.expect("export");
+
+        let (_dst_dir, target) = transfer_context().await;
+        target
+            .set_setting("api_key_openai", "sk-target")
+            .await
+            .unwrap();
+        let mut providers = HashMap::new();
+        providers.insert("acme".to_string(), custom_provider("acme", "target-key"));
+        target
+            .save_custom_providers(&CustomProvidersConfiguration {
+                version: "1".to_string(),
+                providers,
+            })
+            .await
+            .unwrap();
+
+        let summary = target.import_settings(&export, true).await.unwrap();
+        assert_eq!(summary.custom_providers_imported, 1);
+        let providers = target.load_custom_providers().await.unwrap().providers;
+        assert_eq!(providers["acme"].api_key, "target-key");
+        assert_eq!(providers["acme"].base_url, "https://acme.example.com/v1");
+        assert_eq!(
+            target
+                .get_setting("api_key_openai")
+                .await
+                .unwrap()
+                .as_deref(),
+            Some("sk-target")
+        );
+
+        let mut tampered = export.clone();
+        tampered
+            .settings
+            .insert("api_key_openai".to_string(), "sk-injected".to_string());
+        assert!(target.import_settings(&tampered, true).await.is_err());
+        assert_eq!(
+            target
+                .get_setting("api_key_openai")
+                .await
+                .unwrap()
+                .as_deref(),
+            Some("sk-target")
+        );
+    }
+
+    #[tokio::test]
+    async fn settings_import_rejects_invalid_exports() {
+        let (_dir, target) = transfer_context().await;
+        assert!(serde_json::from_str::<SettingsExport>(r#"{"settings":{}}"#).is_err());
+        assert!(serde_json::from_str::<SettingsExport>(
+            r#"{"version":1,"exportedAt":"x","includesSecrets":false,"settings":{"theme":7}}"#
+        )
+        .is_err());
+
+        let mut export: SettingsExport = serde_json::from_str(
+            r#"{"version":2,"exportedAt":"x","includesSecrets":false,"settings":{"theme":"dark"}}"#,
+        )
+        .unwrap();
+        assert!(target.import_settings(&export, true).await.is_err());
+
+        export.version = 1;
+        export
+            .settings
+            .insert(SETTINGS_BACKEND_KEY.to_string(), "keyring".to_string());
+        assert!(target.import_settings(&export, true).await.is_err());
+        assert_eq!(target.get_setting("theme").await.unwrap(), None);
+    }
 }
@@ -0,0 +1,117 @@
+use reqwest::header::{HeaderMap, DATE};
+
+/// Skew below this is treated as network latency / header rounding and ignored
+pub const CLOCK_SKEW_THRESHOLD_SECONDS: i64 = 30;
+
+/// Settings key holding the last significant skew (server minus local, in seconds)
+pub const CLOCK_SKEW_SETTING_KEY: &str = "oauth_clock_skew_seconds";
+
+/// Estimate skew (server minus local, seconds) from an HTTP `Date` response header
+pub fn skew_from_date_header(headers: &HeaderMap, local_now: i64) -> Option<i64> {
+    let value = headers.get(DATE)?.to_str().ok()?;
+    let server_now = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    Some(server_now.timestamp() - local_now)
+}
+
+/// Estimate skew (server minus local, seconds) from a freshly issued JWT's `iat` claim
+pub fn skew_from_jwt_iat(token: &str, local_now: i64) -> Option<i64> {
+    use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+
+    let payload = token.split('.').nth(1)?;
+    let decoded = URL_SAFE_NO_PAD.decode(payload.trim_end_matches('=')).ok()?;
+    let claims: serde_json::Value = serde_json::from_slice(&decoded).ok()?;
+    let issued_at = claims.get("iat")?.as_i64()?;
+    Some(issued_at - local_now)
+}
+
+/// Keep only skew large enough to matter, warning when it is detected
+pub fn significant_skew(skew: Option<i64>, source: &str) -> i64 {
+    match skew {
+        Some(skew) if skew.abs() > CLOCK_SKEW_THRESHOLD_SECONDS => {
+            log::warn!(
+                "Local clock differs from {} by {} seconds; adjusting token expiry. Check the system time settings.",
+                source,
+                skew
+            );
+            skew
+        }
+        _ => 0,
+    }
+}
+
+/// Convert a server-issued absolute timestamp (seconds) into the local clock's frame
+pub fn to_local_timestamp(server_timestamp: i64, skew: i64) -> i64 {
+    server_timestamp - skew
+}
+
+/// Convert an absolute server expiry (seconds) into local milliseconds using the
+/// response `Date` header. Returns the adjusted expiry and the skew that was applied.
+pub fn server_expiry_to_local_ms(expires_at: i64, headers: &HeaderMap, source: &str) -> (i64, i64) {
+    let local_now = chrono::Utc::now().timestamp();
+    let skew = significant_skew(skew_from_date_header(headers, local_now), source);
+    (to_local_timestamp(expires_at, skew) * 1000, skew)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    #[test]
+    fn skew_from_date_header_compares_against_local_time() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            DATE,
+            HeaderValue::from_static("Thu, 01 Jan 2026 00:10:00 GMT"),
+        );
+        let local_now = 1_767_225_600; // 2026-01-01T00:00:00Z
+        assert_eq!(skew_from_date_header(&headers, local_now), Some(600));
+        assert_eq!(skew_from_date_header(&HeaderMap::new(), local_now), None);
+    }
+
+    #[test]
+    fn skew_from_jwt_iat_reads_issued_at_claim() {
+        use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+        let payload = URL_SAFE_NO_PAD.encode(br#"{"iat":1000}"#);
+        let token = format!("header.{}.signature", payload);
+        assert_eq!(skew_from_jwt_iat(&token, 1300), Some(-300));
+        assert_eq!(skew_from_jwt_iat("not-a-jwt", 1300), None);
+    }
+
+    #[test]
+    fn small_skew_is_ignored() {
+        assert_eq!(significant_skew(Some(5), "test"), 0);
+        assert_eq!(significant_skew(None, "test"), 0);
+        assert_eq!(significant_skew(Some(-900), "test"), -900);
+    }
+
+    #[test]
+    fn server_expiry_is_shifted_into_local_frame() {
+        let local_now = chrono::Utc::now().timestamp();
+        let server_now = chrono::DateTime::from_timestamp(local_now + 900, 0).unwrap();
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            DATE,
+            HeaderValue::from_str(&server_now.to_rfc2822()).unwrap(),
+        );
+
+        let (expires_at_ms, skew) =
+            server_expiry_to_local_ms(local_now + 900 + 600, &headers, "test");
+        assert!((895..=905).contains(&skew));
+        let remaining_secs = expires_at_ms / 1000 - local_now;
+        assert!((595..=605).contains(&remaining_secs));
+    }
+
+    #[test]
+    fn skew_adjusts_effective_expiry() {
+        let local_now = 1_000_000;
+        // Server clock runs 15 minutes ahead; token expires 10 minutes after server "now"
+        let skew = 900;
+        let server_expires_at = local_now + skew + 600;
+        let local_expires_at = to_local_timestamp(server_expires_at, skew);
+        assert_eq!(local_expires_at, local_now + 600);
+
+        // Without adjustment the token would look valid for 25 minutes
+        assert!(server_expires_at - local_now > 1200);
+    }
+}
@@ -1,3 +1,6 @@
 pub mod api_key_manager;
+pub mod clock_skew;
 pub mod oauth;
 pub mod openai_usage;
+pub mod qwen_oauth;
+pub mod secret_store;
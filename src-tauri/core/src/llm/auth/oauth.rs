@@ -1,4 +1,10 @@
-use crate::llm::auth::api_key_manager::{normalize_domain, ApiKeyManager, LlmState};
+use crate::llm::auth::api_key_manager::{
+    github_copilot_token_url, normalize_github_enterprise_domain, oauth_settings_prefix,
+    ApiKeyManager, LlmState,
+};
+use crate::llm::auth::clock_skew;
+use crate::llm::auth::qwen_oauth::QWEN_OAUTH_PREFIX;
+use crate::oauth_callback_server;
 use rand::RngCore;
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
@@ -18,6 +24,12 @@ const CLAUDE_REDIRECT_URI: &str = "http://localhost:1455/auth/callback";
 const CLAUDE_AUTH_URL: &str = "https://claude.ai/oauth/authorize";
 const CLAUDE_TOKEN_URL: &str = "https://claude.ai/oauth/token";
 
+// RFC 7009 revocation endpoints. `None` where the provider publishes no endpoint
+// usable by a public client; disconnect then only clears local state.
+const OPENAI_REVOKE_URL: Option<&str> = Some("https://auth.openai.com/oauth/revoke");
+const CLAUDE_REVOKE_URL: Option<&str> = None;
+const OAUTH_REVOKE_TIMEOUT: Duration = Duration::from_secs(10);
+
 const GITHUB_COPILOT_ACCESS_TOKEN_KEY: &str = "github_copilot_oauth_access_token";
 const GITHUB_COPILOT_COPILOT_TOKEN_KEY: &str = "github_copilot_oauth_copilot_token";
 const GITHUB_COPILOT_EXPIRES_AT_KEY: &str = "github_copilot_oauth_expires_at";
@@ -28,6 +40,14 @@ const GITHUB_COPILOT_USER_AGENT: &str = "GitHubCopilotChat/0.35.0";
 const GITHUB_COPILOT_EDITOR_VERSION: &str = "vscode/1.105.1";
 const GITHUB_COPILOT_PLUGIN_VERSION: &str = "copilot-chat/0.35.0";
 const GITHUB_COPILOT_INTEGRATION_ID: &str = "vscode-chat";
+const GITHUB_DEVICE_CODE_GRANT_TYPE: &str = "urn:ietf:params:oauth:grant-type:device_code";
+/// Assumed polling interval when the client does not report its current one
+const GITHUB_DEVICE_DEFAULT_INTERVAL_SECS: i64 = 5;
+/// RFC 8628 section 3.5: increase the interval by 5 seconds on `slow_down`
+const GITHUB_DEVICE_SLOW_DOWN_STEP_SECS: i64 = 5;
+
+const OPENAI_TOKEN_URL_ENV: &str = "TALKCODY_OPENAI_TOKEN_URL";
+const CLAUDE_TOKEN_URL_ENV: &str = "TALKCODY_CLAUDE_TOKEN_URL";
 
 const OAUTH_STATE_TIMEOUT: Duration = Duration::from_secs(600); // 10 minutes
 
@@ -74,7 +94,7 @@ async fn validate_oauth_state(state: &str) -> bool {
 }
 
 /// Generate a random code verifier for PKCE (32 bytes = 256 bits)
-fn generate_code_verifier() -> String {
+pub(crate) fn generate_code_verifier() -> String {
     let mut bytes = [0u8; 32];
     rand::thread_rng().fill_bytes(&mut bytes);
     base64_url_encode(&bytes)
@@ -94,7 +114,7 @@ fn base64_url_encode(bytes: &[u8]) -> String {
 }
 
 /// Generate PKCE code challenge from verifier (SHA256 hash, base64url encoded)
-fn code_challenge(verifier: &str) -> String {
+pub(crate) fn code_challenge(verifier: &str) -> String {
     let mut hasher = Sha256::new();
     hasher.update(verifier.as_bytes());
     let result = hasher.finalize();
@@ -134,6 +154,25 @@ fn base64_url_decode(input: &str) -> Result<Vec<u8>, String> {
         .map_err(|e| format!("Base64 decode error: {}", e))
 }
 
+/// OpenAI token endpoint, overridable for tests
+fn openai_token_url() -> String {
+    std::env::var(OPENAI_TOKEN_URL_ENV).unwrap_or_else(|_| OPENAI_TOKEN_URL.to_string())
+}
+
+/// Claude token endpoint, overridable for tests
+fn claude_token_url() -> String {
+    std::env::var(CLAUDE_TOKEN_URL_ENV).unwrap_or_else(|_| CLAUDE_TOKEN_URL.to_string())
+}
+
+/// Use the code pasted by the user, or wait for the one captured by the
+/// callback listener started for this flow's state
+async fn authorization_code(code: String, state: &str) -> Result<String, String> {
+    if !code.trim().is_empty() {
+        return Ok(code);
+    }
+    oauth_callback_server::wait_for_callback_code(state).await
+}
+
 // ============================================================================
 // OpenAI OAuth
 // ============================================================================
@@ -218,12 +257,17 @@ pub async fn llm_openai_oauth_start(
 
 #[derive(Deserialize)]
 pub struct OpenAIOAuthCompleteRequest {
+    /// Empty when the code is captured by the callback listener
+    #[serde(default)]
     pub code: String,
     pub verifier: String,
     #[serde(rename = "expectedState")]
     pub expected_state: Option<String>,
     #[serde(rename = "redirectUri")]
     pub redirect_uri: Option<String>,
+    /// Store the tokens under this account label and make it active
+    #[serde(rename = "accountLabel")]
+    pub account_label: Option<String>,
 }
 
 #[derive(Deserialize)]
@@ -260,6 +304,7 @@ pub async fn llm_openai_oauth_complete(
     if !validate_oauth_state(&expected_state).await {
         return Err("Invalid or expired OAuth state".to_string());
     }
+    let code = authorization_code(request.code, &expected_state).await?;
 
     let client = reqwest::Client::new();
 
@@ -270,13 +315,13 @@ pub async fn llm_openai_oauth_complete(
     let params = [
         ("grant_type", "authorization_code"),
         ("client_id", OPENAI_CLIENT_ID),
-        ("code", &request.code),
+        ("code", &code),
         ("redirect_uri", &redirect_uri),
         ("code_verifier", &request.verifier),
     ];
 
     let response = client
-        .post(OPENAI_TOKEN_URL)
+        .post(openai_token_url())
         .header("Content-Type", "application/x-www-form-urlencoded")
         .form(&params)
         .send()
@@ -314,17 +359,22 @@ pub async fn llm_openai_oauth_complete(
 
     // Save to settings
     let api_keys = state.api_keys.lock().await;
+    if let Some(label) = request.account_label.as_deref() {
+        api_keys.add_oauth_account("openai", label).await?;
+    }
     api_keys
-        .set_setting("openai_oauth_access_token", &access_token)
+        .set_oauth_setting("openai", "access_token", &access_token)
         .await?;
     api_keys
-        .set_setting("openai_oauth_refresh_token", &refresh_token)
+        .set_oauth_setting("openai", "refresh_token", &refresh_token)
         .await?;
     api_keys
-        .set_setting("openai_oauth_expires_at", &expires_at.to_string())
+        .set_oauth_setting("openai", "expires_at", &expires_at.to_string())
         .await?;
     if let Some(ref id) = account_id {
-        api_keys.set_setting("openai_oauth_account_id", id).await?;
+        api_keys
+            .set_oauth_setting("openai", "account_id", id)
+            .await?;
     }
 
     Ok(OpenAIOAuthCompleteResponse {
@@ -362,7 +412,7 @@ pub(crate) async fn refresh_openai_oauth_tokens(
     ];
 
     let response = client
-        .post(OPENAI_TOKEN_URL)
+        .post(openai_token_url())
         .header("Content-Type", "application/x-www-form-urlencoded")
         .form(&params)
         .send()
@@ -398,18 +448,25 @@ pub(crate) async fn refresh_openai_oauth_tokens(
     let expires_at = chrono::Utc::now().timestamp() + expires_in;
 
     let account_id = extract_openai_account_id(&access_token);
+    let skew = clock_skew::significant_skew(
+        clock_skew::skew_from_jwt_iat(&access_token, chrono::Utc::now().timestamp()),
+        "OpenAI",
+    );
+    api_keys.record_clock_skew(skew).await?;
 
     api_keys
-        .set_setting("openai_oauth_access_token", &access_token)
+        .set_oauth_setting("openai", "access_token", &access_token)
         .await?;
     api_keys
-        .set_setting("openai_oauth_refresh_token", &refresh_token)
+        .set_oauth_setting("openai", "refresh_token", &refresh_token)
         .await?;
     api_keys
-        .set_setting("openai_oauth_expires_at", &expires_at.to_string())
+        .set_oauth_setting("openai", "expires_at", &expires_at.to_string())
         .await?;
     if let Some(ref id) = account_id {
-        api_keys.set_setting("openai_oauth_account_id", id).await?;
+        api_keys
+            .set_oauth_setting("openai", "account_id", id)
+            .await?;
     }
 
     Ok(OpenAIOAuthRefreshResponse {
@@ -436,7 +493,7 @@ pub async fn llm_openai_oauth_refresh_from_store(
 ) -> Result<OpenAIOAuthRefreshResponse, String> {
     let api_keys = state.api_keys.lock().await;
     let refresh_token = api_keys
-        .get_setting("openai_oauth_refresh_token")
+        .get_oauth_setting("openai", "refresh_token")
         .await?
         .unwrap_or_default();
 
@@ -448,18 +505,99 @@ pub async fn llm_openai_oauth_refresh_from_store(
     refresh_openai_oauth_tokens(&client, &refresh_token, &api_keys).await
 }
 
+/// Best-effort RFC 7009 revocation. Failures are logged and ignored so that
+/// disconnecting always succeeds locally.
+async fn revoke_oauth_token(
+    revoke_url: Option<&str>,
+    client_id: &str,
+    token: &str,
+    token_type_hint: &str,
+) {
+    let Some(revoke_url) = revoke_url else {
+        return;
+    };
+    if token.trim().is_empty() {
+        return;
+    }
+
+    let client = match reqwest::Client::builder()
+        .timeout(OAUTH_REVOKE_TIMEOUT)
+        .build()
+    {
+        Ok(client) => client,
+        Err(e) => {
+            log::warn!("Failed to build HTTP client for token revocation: {}", e);
+            return;
+        }
+    };
+
+    let params = [
+        ("token", token),
+        ("token_type_hint", token_type_hint),
+        ("client_id", client_id),
+    ];
+    match client.post(revoke_url).form(&params).send().await {
+        Ok(response) if response.status().is_success() => {
+            log::info!("Revoked OAuth {} at {}", token_type_hint, revoke_url);
+        }
+        Ok(response) => {
+            log::warn!(
+                "OAuth {} revocation at {} returned {}",
+                token_type_hint,
+                revoke_url,
+                response.status()
+            );
+        }
+        Err(e) => {
+            log::warn!(
+                "OAuth {} revocation at {} failed: {}",
+                token_type_hint,
+                revoke_url,
+                e
+            );
+        }
+    }
+}
+
+/// Revoke the active account's tokens (best effort), then clear them locally
+async fn disconnect_oauth_account(
+    api_keys: &ApiKeyManager,
+    prefix: &str,
+    client_id: &str,
+    revoke_url: Option<&str>,
+    fields: &[&str],
+) -> Result<(), String> {
+    // Revoking the refresh token first invalidates the whole grant on most servers
+    for (field, hint) in [
+        ("refresh_token", "refresh_token"),
+        ("access_token", "access_token"),
+    ] {
+        let token = api_keys
+            .get_oauth_setting(prefix, field)
+            .await?
+            .unwrap_or_default();
+        revoke_oauth_token(revoke_url, client_id, &token, hint).await;
+    }
+
+    for field in fields {
+        api_keys.set_oauth_setting(prefix, field, "").await?;
+    }
+    let active = api_keys.active_oauth_account(prefix).await?;
+    api_keys.remove_oauth_account(prefix, &active).await?;
+    Ok(())
+}
+
 #[tauri::command]
 pub async fn llm_openai_oauth_disconnect(state: State<'_, LlmState>) -> Result<(), String> {
     let api_keys = state.api_keys.lock().await;
-    api_keys
-        .set_setting("openai_oauth_access_token", "")
-        .await?;
-    api_keys
-        .set_setting("openai_oauth_refresh_token", "")
-        .await?;
-    api_keys.set_setting("openai_oauth_expires_at", "").await?;
-    api_keys.set_setting("openai_oauth_account_id", "").await?;
-    Ok(())
+    disconnect_oauth_account(
+        &api_keys,
+        "openai",
+        OPENAI_CLIENT_ID,
+        OPENAI_REVOKE_URL,
+        &["access_token", "refresh_token", "expires_at", "account_id"],
+    )
+    .await
 }
 
 // ============================================================================
@@ -507,9 +645,14 @@ pub async fn llm_claude_oauth_start() -> Result<ClaudeOAuthStartResponse, String
 
 #[derive(Deserialize)]
 pub struct ClaudeOAuthCompleteRequest {
+    /// Empty when the code is captured by the callback listener
+    #[serde(default)]
     pub code: String,
     pub verifier: String,
     pub state: String,
+    /// Store the tokens under this account label and make it active
+    #[serde(rename = "accountLabel")]
+    pub account_label: Option<String>,
 }
 
 #[derive(Serialize)]
@@ -529,19 +672,20 @@ pub async fn llm_claude_oauth_complete(
     if !validate_oauth_state(&request.state).await {
         return Err("Invalid or expired OAuth state".to_string());
     }
+    let code = authorization_code(request.code, &request.state).await?;
 
     let client = reqwest::Client::new();
 
     let params = [
         ("grant_type", "authorization_code"),
         ("client_id", CLAUDE_CLIENT_ID),
-        ("code", &request.code),
+        ("code", &code),
         ("redirect_uri", CLAUDE_REDIRECT_URI),
         ("code_verifier", &request.verifier),
     ];
 
     let response = client
-        .post(CLAUDE_TOKEN_URL)
+        .post(claude_token_url())
         .header("Content-Type", "application/x-www-form-urlencoded")
         .form(&params)
         .send()
@@ -577,14 +721,17 @@ pub async fn llm_claude_oauth_complete(
 
     // Save to settings
     let api_keys = state.api_keys.lock().await;
+    if let Some(label) = request.account_label.as_deref() {
+        api_keys.add_oauth_account("claude", label).await?;
+    }
     api_keys
-        .set_setting("claude_oauth_access_token", &access_token)
+        .set_oauth_setting("claude", "access_token", &access_token)
         .await?;
     api_keys
-        .set_setting("claude_oauth_refresh_token", &refresh_token)
+        .set_oauth_setting("claude", "refresh_token", &refresh_token)
         .await?;
     api_keys
-        .set_setting("claude_oauth_expires_at", &expires_at.to_string())
+        .set_oauth_setting("claude", "expires_at", &expires_at.to_string())
         .await?;
 
     Ok(ClaudeOAuthCompleteResponse {
@@ -607,21 +754,19 @@ pub struct ClaudeOAuthRefreshResponse {
     pub expires_at: i64,
 }
 
-#[tauri::command]
-pub async fn llm_claude_oauth_refresh(
-    request: ClaudeOAuthRefreshRequest,
-    state: State<'_, LlmState>,
+pub(crate) async fn refresh_claude_oauth_tokens(
+    client: &reqwest::Client,
+    refresh_token: &str,
+    api_keys: &ApiKeyManager,
 ) -> Result<ClaudeOAuthRefreshResponse, String> {
-    let client = reqwest::Client::new();
-
     let params = [
         ("grant_type", "refresh_token"),
         ("client_id", CLAUDE_CLIENT_ID),
-        ("refresh_token", &request.refresh_token),
+        ("refresh_token", refresh_token),
     ];
 
     let response = client
-        .post(CLAUDE_TOKEN_URL)
+        .post(claude_token_url())
         .header("Content-Type", "application/x-www-form-urlencoded")
         .form(&params)
         .send()
@@ -637,6 +782,10 @@ pub async fn llm_claude_oauth_refresh(
         return Err(format!("Token refresh failed ({}): {}", status, text));
     }
 
+    let skew = clock_skew::significant_skew(
+        clock_skew::skew_from_date_header(response.headers(), chrono::Utc::now().timestamp()),
+        "Claude",
+    );
     let token_response: serde_json::Value = response
         .json()
         .await
@@ -651,21 +800,20 @@ pub async fn llm_claude_oauth_refresh(
     let refresh_token = token_response["refresh_token"]
         .as_str()
         .map(|s| s.to_string())
-        .unwrap_or(request.refresh_token);
+        .unwrap_or(refresh_token.to_string());
 
     let expires_in = token_response["expires_in"].as_i64().unwrap_or(3600);
     let expires_at = chrono::Utc::now().timestamp() + expires_in;
 
-    // Save to settings
-    let api_keys = state.api_keys.lock().await;
+    api_keys.record_clock_skew(skew).await?;
     api_keys
-        .set_setting("claude_oauth_access_token", &access_token)
+        .set_oauth_setting("claude", "access_token", &access_token)
         .await?;
     api_keys
-        .set_setting("claude_oauth_refresh_token", &refresh_token)
+        .set_oauth_setting("claude", "refresh_token", &refresh_token)
         .await?;
     api_keys
-        .set_setting("claude_oauth_expires_at", &expires_at.to_string())
+        .set_oauth_setting("claude", "expires_at", &expires_at.to_string())
         .await?;
 
     Ok(ClaudeOAuthRefreshResponse {
@@ -675,17 +823,27 @@ pub async fn llm_claude_oauth_refresh(
     })
 }
 
+#[tauri::command]
+pub async fn llm_claude_oauth_refresh(
+    request: ClaudeOAuthRefreshRequest,
+    state: State<'_, LlmState>,
+) -> Result<ClaudeOAuthRefreshResponse, String> {
+    let api_keys = state.api_keys.lock().await;
+    let client = reqwest::Client::new();
+    refresh_claude_oauth_tokens(&client, &request.refresh_token, &api_keys).await
+}
+
 #[tauri::command]
 pub async fn llm_claude_oauth_disconnect(state: State<'_, LlmState>) -> Result<(), String> {
     let api_keys = state.api_keys.lock().await;
-    api_keys
-        .set_setting("claude_oauth_access_token", "")
-        .await?;
-    api_keys
-        .set_setting("claude_oauth_refresh_token", "")
-        .await?;
-    api_keys.set_setting("claude_oauth_expires_at", "").await?;
-    Ok(())
+    disconnect_oauth_account(
+        &api_keys,
+        "claude",
+        CLAUDE_CLIENT_ID,
+        CLAUDE_REVOKE_URL,
+        &["access_token", "refresh_token", "expires_at"],
+    )
+    .await
 }
 
 // ============================================================================
@@ -722,6 +880,9 @@ pub struct GitHubCopilotOAuthStartResponse {
 pub struct GitHubCopilotOAuthPollRequest {
     pub device_code: String,
     pub enterprise_url: Option<String>,
+    /// Seconds the client currently waits between polls
+    #[serde(default)]
+    pub interval: Option<i64>,
 }
 
 #[derive(Deserialize)]
@@ -729,6 +890,7 @@ struct GitHubCopilotAccessTokenResponse {
     access_token: Option<String>,
     error: Option<String>,
     error_description: Option<String>,
+    interval: Option<i64>,
 }
 
 #[derive(Serialize)]
@@ -749,6 +911,9 @@ pub struct GitHubCopilotTokenExchangeResponse {
     pub tokens: Option<GitHubCopilotOAuthTokens>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub error: Option<String>,
+    /// Seconds to wait before the next poll, set when GitHub asked to slow down
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub interval: Option<i64>,
 }
 
 #[derive(Serialize)]
@@ -764,20 +929,173 @@ pub struct GitHubCopilotOAuthTokenSnapshot {
     pub enterprise_url: Option<String>,
 }
 
+/// Outcome of a single device flow token request
+#[derive(Debug, PartialEq)]
+enum GitHubDevicePollOutcome {
+    Approved(String),
+    Pending,
+    /// Carries the interval GitHub asked for, when it sent one
+    SlowDown(Option<i64>),
+    Failed(String),
+}
+
 fn github_copilot_domain(enterprise_url: Option<&str>) -> String {
     enterprise_url
-        .map(normalize_domain)
-        .filter(|value| !value.trim().is_empty())
+        .and_then(|value| normalize_github_enterprise_domain(value).ok().flatten())
         .unwrap_or_else(|| "github.com".to_string())
 }
 
+/// Validate an optional GitHub Enterprise domain from the frontend, rejecting
+/// malformed input instead of silently falling back to github.com.
+fn github_enterprise_domain(enterprise_url: Option<&str>) -> Result<Option<String>, String> {
+    match enterprise_url {
+        Some(value) => normalize_github_enterprise_domain(value),
+        None => Ok(None),
+    }
+}
+
+/// Interval in seconds to poll at after a `slow_down`: GitHub's value when it
+/// sent one, otherwise the client's current interval widened by the RFC step.
+fn github_slow_down_interval(requested: Option<i64>, current: Option<i64>) -> i64 {
+    requested.filter(|secs| *secs > 0).unwrap_or_else(|| {
+        current
+            .filter(|secs| *secs > 0)
+            .unwrap_or(GITHUB_DEVICE_DEFAULT_INTERVAL_SECS)
+            + GITHUB_DEVICE_SLOW_DOWN_STEP_SECS
+    })
+}
+
+fn github_device_token_url(domain: &str) -> String {
+    format!("https://{}/login/oauth/access_token", domain)
+}
+
+/// Classify a device flow token response. GitHub reports pending and
+/// slow_down as errors in a 200 response rather than with a 4xx status.
+fn classify_github_token_response(status: u16, body: &str) -> GitHubDevicePollOutcome {
+    if !(200..300).contains(&status) {
+        return GitHubDevicePollOutcome::Failed(format!(
+            "Token request failed ({}): {}",
+            status, body
+        ));
+    }
+    let data: GitHubCopilotAccessTokenResponse = match serde_json::from_str(body) {
+        Ok(data) => data,
+        Err(e) => {
+            return GitHubDevicePollOutcome::Failed(format!(
+                "Failed to parse access token response: {}",
+                e
+            ))
+        }
+    };
+    if let Some(access_token) = data.access_token.filter(|token| !token.is_empty()) {
+        return GitHubDevicePollOutcome::Approved(access_token);
+    }
+    match data.error.as_deref() {
+        Some("authorization_pending") => GitHubDevicePollOutcome::Pending,
+        Some("slow_down") => GitHubDevicePollOutcome::SlowDown(data.interval),
+        Some("expired_token") => {
+            GitHubDevicePollOutcome::Failed("Device code expired, please try again".to_string())
+        }
+        Some("access_denied") => {
+            GitHubDevicePollOutcome::Failed("Authorization was denied".to_string())
+        }
+        Some(error) => GitHubDevicePollOutcome::Failed(
+            data.error_description
+                .unwrap_or_else(|| format!("OAuth error: {}", error)),
+        ),
+        None => GitHubDevicePollOutcome::Failed("Unknown OAuth response".to_string()),
+    }
+}
+
+async fn request_github_device_token(
+    client: &reqwest::Client,
+    token_url: &str,
+    device_code: &str,
+) -> Result<GitHubDevicePollOutcome, String> {
+    let response = client
+        .post(token_url)
+        .header("Content-Type", "application/json")
+        .header("Accept", "application/json")
+        .header("User-Agent", GITHUB_COPILOT_USER_AGENT)
+        .json(&serde_json::json!({
+            "client_id": GITHUB_COPILOT_CLIENT_ID,
+            "device_code": device_code,
+            "grant_type": GITHUB_DEVICE_CODE_GRANT_TYPE
+        }))
+        .send()
+        .await
+        .map_err(|e| format!("Token request failed: {}", e))?;
+    let status = response.status().as_u16();
+    let body = response.text().await.unwrap_or_default();
+    Ok(classify_github_token_response(status, &body))
+}
+
+async fn request_github_device_code(
+    client: &reqwest::Client,
+    domain: &str,
+) -> Result<GitHubCopilotDeviceCodeResponse, String> {
+    let url = format!("https://{}/login/device/code", domain);
+    let response = client
+        .post(&url)
+        .header("Content-Type", "application/json")
+        .header("Accept", "application/json")
+        .header("User-Agent", GITHUB_COPILOT_USER_AGENT)
+        .header("Editor-Version", GITHUB_COPILOT_EDITOR_VERSION)
+        .header("Editor-Plugin-Version", GITHUB_COPILOT_PLUGIN_VERSION)
+        .header("Copilot-Integration-Id", GITHUB_COPILOT_INTEGRATION_ID)
+        .json(&serde_json::json!({
+            "client_id": GITHUB_COPILOT_CLIENT_ID,
+            "scope": "read:user"
+        }))
+        .send()
+        .await
+        .map_err(|e| format!("Device code request failed: {}", e))?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let text = response
+            .text()
+            .await
+            .unwrap_or_else(|_| "Unknown error".to_string());
+        return Err(format!("Device code request failed ({}): {}", status, text));
+    }
+
+    response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse device code response: {}", e))
+}
+
+async fn store_github_copilot_tokens(
+    api_keys: &ApiKeyManager,
+    tokens: &GitHubCopilotOAuthTokens,
+) -> Result<(), String> {
+    api_keys
+        .set_setting(GITHUB_COPILOT_ACCESS_TOKEN_KEY, &tokens.access_token)
+        .await?;
+    api_keys
+        .set_setting(GITHUB_COPILOT_COPILOT_TOKEN_KEY, &tokens.copilot_token)
+        .await?;
+    api_keys
+        .set_setting(
+            GITHUB_COPILOT_EXPIRES_AT_KEY,
+            &tokens.expires_at.to_string(),
+        )
+        .await?;
+    api_keys
+        .set_setting(
+            GITHUB_COPILOT_ENTERPRISE_URL_KEY,
+            tokens.enterprise_url.as_deref().unwrap_or(""),
+        )
+        .await
+}
+
 async fn github_copilot_api_token(
     client: &reqwest::Client,
     access_token: &str,
     enterprise_url: Option<&str>,
 ) -> Result<(String, i64), String> {
-    let domain = github_copilot_domain(enterprise_url);
-    let url = format!("https://api.{}/copilot_internal/v2/token", domain);
+    let url = github_copilot_token_url(&github_copilot_domain(enterprise_url));
 
     let response = client
         .get(&url)
@@ -803,6 +1121,7 @@ async fn github_copilot_api_token(
         ));
     }
 
+    let response_headers = response.headers().clone();
     let payload: serde_json::Value = response
         .json()
         .await
@@ -817,46 +1136,20 @@ async fn github_copilot_api_token(
         .and_then(|value| value.as_i64())
         .ok_or("Missing Copilot expires_at in response")?;
 
-    Ok((token, expires_at * 1000))
+    // expires_at comes from GitHub's clock; return it in the local clock's frame
+    let (expires_at_ms, _skew) =
+        clock_skew::server_expiry_to_local_ms(expires_at, &response_headers, "GitHub");
+    Ok((token, expires_at_ms))
 }
 
 #[tauri::command]
 pub async fn llm_github_copilot_oauth_start_device_code(
     request: GitHubCopilotOAuthStartRequest,
 ) -> Result<GitHubCopilotOAuthStartResponse, String> {
-    let domain = github_copilot_domain(request.enterprise_url.as_deref());
-    let url = format!("https://{}/login/device/code", domain);
-
+    let enterprise_domain = github_enterprise_domain(request.enterprise_url.as_deref())?;
+    let domain = enterprise_domain.as_deref().unwrap_or("github.com");
     let client = reqwest::Client::new();
-    let response = client
-        .post(&url)
-        .header("Content-Type", "application/json")
-        .header("Accept", "application/json")
-        .header("User-Agent", GITHUB_COPILOT_USER_AGENT)
-        .header("Editor-Version", GITHUB_COPILOT_EDITOR_VERSION)
-        .header("Editor-Plugin-Version", GITHUB_COPILOT_PLUGIN_VERSION)
-        .header("Copilot-Integration-Id", GITHUB_COPILOT_INTEGRATION_ID)
-        .json(&serde_json::json!({
-            "client_id": GITHUB_COPILOT_CLIENT_ID,
-            "scope": "read:user"
-        }))
-        .send()
-        .await
-        .map_err(|e| format!("Device code request failed: {}", e))?;
-
-    if !response.status().is_success() {
-        let status = response.status();
-        let text = response
-            .text()
-            .await
-            .unwrap_or_else(|_| "Unknown error".to_string());
-        return Err(format!("Device code request failed ({}): {}", status, text));
-    }
-
-    let data: GitHubCopilotDeviceCodeResponse = response
-        .json()
-        .await
-        .map_err(|e| format!("Failed to parse device code response: {}", e))?;
+    let data = request_github_device_code(&client, domain).await?;
 
     Ok(GitHubCopilotOAuthStartResponse {
         device_code: data.device_code,
@@ -872,100 +1165,64 @@ pub async fn llm_github_copilot_oauth_poll_device_code(
     request: GitHubCopilotOAuthPollRequest,
     state: State<'_, LlmState>,
 ) -> Result<GitHubCopilotTokenExchangeResponse, String> {
-    let domain = github_copilot_domain(request.enterprise_url.as_deref());
-    let url = format!("https://{}/login/oauth/access_token", domain);
+    let enterprise_domain = github_enterprise_domain(request.enterprise_url.as_deref())?;
+    let domain = enterprise_domain.as_deref().unwrap_or("github.com");
 
     let client = reqwest::Client::new();
-    let response = client
-        .post(&url)
-        .header("Content-Type", "application/json")
-        .header("Accept", "application/json")
-        .header("User-Agent", GITHUB_COPILOT_USER_AGENT)
-        .json(&serde_json::json!({
-            "client_id": GITHUB_COPILOT_CLIENT_ID,
-            "device_code": request.device_code,
-            "grant_type": "urn:ietf:params:oauth:grant-type:device_code"
-        }))
-        .send()
-        .await
-        .map_err(|e| format!("Token request failed: {}", e))?;
-
-    if !response.status().is_success() {
-        let status = response.status();
-        let text = response
-            .text()
-            .await
-            .unwrap_or_else(|_| "Unknown error".to_string());
-        return Ok(GitHubCopilotTokenExchangeResponse {
-            result_type: "failed".to_string(),
-            tokens: None,
-            error: Some(format!("Token request failed ({}): {}", status, text)),
-        });
-    }
-
-    let data: GitHubCopilotAccessTokenResponse = response
-        .json()
-        .await
-        .map_err(|e| format!("Failed to parse access token response: {}", e))?;
-
-    if let Some(access_token) = data.access_token {
-        let (copilot_token, expires_at_ms) =
-            github_copilot_api_token(&client, &access_token, request.enterprise_url.as_deref())
-                .await?;
-
-        let api_keys = state.api_keys.lock().await;
-        api_keys
-            .set_setting(GITHUB_COPILOT_ACCESS_TOKEN_KEY, &access_token)
-            .await?;
-        api_keys
-            .set_setting(GITHUB_COPILOT_COPILOT_TOKEN_KEY, &copilot_token)
-            .await?;
-        api_keys
-            .set_setting(GITHUB_COPILOT_EXPIRES_AT_KEY, &expires_at_ms.to_string())
-            .await?;
-        api_keys
-            .set_setting(
-                GITHUB_COPILOT_ENTERPRISE_URL_KEY,
-                request.enterprise_url.as_deref().unwrap_or(""),
-            )
-            .await?;
-
-        return Ok(GitHubCopilotTokenExchangeResponse {
-            result_type: "success".to_string(),
-            tokens: Some(GitHubCopilotOAuthTokens {
+    let outcome = request_github_device_token(
+        &client,
+        &github_device_token_url(domain),
+        &request.device_code,
+    )
+    .await?;
+
+    match outcome {
+        GitHubDevicePollOutcome::Approved(access_token) => {
+            let (copilot_token, expires_at_ms) =
+                github_copilot_api_token(&client, &access_token, Some(domain)).await?;
+            let tokens = GitHubCopilotOAuthTokens {
                 access_token,
                 copilot_token,
                 expires_at: expires_at_ms,
-                enterprise_url: request.enterprise_url,
-            }),
-            error: None,
-        });
-    }
+                enterprise_url: enterprise_domain,
+            };
+
+            let api_keys = state.api_keys.lock().await;
+            store_github_copilot_tokens(&api_keys, &tokens).await?;
 
-    if let Some(error) = data.error {
-        if error == "authorization_pending" || error == "slow_down" {
-            return Ok(GitHubCopilotTokenExchangeResponse {
+            Ok(GitHubCopilotTokenExchangeResponse {
+                result_type: "success".to_string(),
+                tokens: Some(tokens),
+                error: None,
+                interval: None,
+            })
+        }
+        GitHubDevicePollOutcome::Pending => Ok(GitHubCopilotTokenExchangeResponse {
+            result_type: "pending".to_string(),
+            tokens: None,
+            error: None,
+            interval: None,
+        }),
+        GitHubDevicePollOutcome::SlowDown(requested) => {
+            let interval = github_slow_down_interval(requested, request.interval);
+            log::debug!(
+                "GitHub OAuth asked to slow down, polling every {}s",
+                interval
+            );
+            Ok(GitHubCopilotTokenExchangeResponse {
                 result_type: "pending".to_string(),
                 tokens: None,
                 error: None,
-            });
+                interval: Some(interval),
+            })
         }
-
-        let message = data
-            .error_description
-            .unwrap_or_else(|| format!("OAuth error: {}", error));
-        return Ok(GitHubCopilotTokenExchangeResponse {
+        GitHubDevicePollOutcome::Failed(message) => Ok(GitHubCopilotTokenExchangeResponse {
             result_type: "failed".to_string(),
             tokens: None,
             error: Some(message),
-        });
+            interval: None,
+        }),
     }
-
-    Ok(GitHubCopilotTokenExchangeResponse {
-        result_type: "failed".to_string(),
-        tokens: None,
-        error: Some("Unknown OAuth response".to_string()),
-    })
 }
 
 #[tauri::command]
@@ -1005,9 +1262,21 @@ pub async fn llm_github_copilot_oauth_refresh(
     })
 }
 
-#[tauri::command]
-pub async fn llm_github_copilot_oauth_disconnect(state: State<'_, LlmState>) -> Result<(), String> {
-    let api_keys = state.api_keys.lock().await;
+/// GitHub only lets an OAuth app delete a token with its client secret
+/// (`DELETE /applications/{client_id}/token`), which a device-flow client does
+/// not have. The user-to-server token can still be revoked from the account's
+/// "Authorized OAuth Apps" settings, so disconnect only clears local state.
+async fn disconnect_github_copilot(api_keys: &ApiKeyManager) -> Result<(), String> {
+    let has_access_token = api_keys
+        .get_setting(GITHUB_COPILOT_ACCESS_TOKEN_KEY)
+        .await?
+        .is_some_and(|value| !value.trim().is_empty());
+    if has_access_token {
+        log::info!(
+            "GitHub Copilot has no client-side revocation endpoint; clearing local tokens only"
+        );
+    }
+
     api_keys
         .set_setting(GITHUB_COPILOT_ACCESS_TOKEN_KEY, "")
         .await?;
@@ -1023,6 +1292,12 @@ pub async fn llm_github_copilot_oauth_disconnect(state: State<'_, LlmState>) ->
     Ok(())
 }
 
+#[tauri::command]
+pub async fn llm_github_copilot_oauth_disconnect(state: State<'_, LlmState>) -> Result<(), String> {
+    let api_keys = state.api_keys.lock().await;
+    disconnect_github_copilot(&api_keys).await
+}
+
 #[tauri::command]
 pub async fn llm_github_copilot_oauth_tokens(
     state: State<'_, LlmState>,
@@ -1054,62 +1329,418 @@ pub async fn llm_github_copilot_oauth_tokens(
 }
 
 // ============================================================================
-// OAuth Status
+// Generic OAuth (user-configured PKCE providers)
 // ============================================================================
 
-/// OAuth status response - does NOT include sensitive tokens
-#[derive(Serialize, Default)]
+/// User-supplied OAuth2 PKCE configuration, stored as JSON under
+/// `generic_{provider_id}_oauth_config`
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
-pub struct OAuthProviderStatus {
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub expires_at: Option<i64>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub account_id: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub is_connected: Option<bool>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub has_refresh_token: Option<bool>,
+pub struct GenericOAuthConfig {
+    pub client_id: String,
+    pub auth_url: String,
+    pub token_url: String,
+    pub redirect_uri: String,
+    #[serde(default)]
+    pub scopes: Vec<String>,
 }
 
-#[derive(Serialize)]
-#[serde(rename_all = "camelCase")]
-pub struct OAuthStatusResponse {
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub openai: Option<OAuthProviderStatus>,
+/// Settings key of a generic OAuth field. Generic providers live under their own
+/// `generic_` prefix so they can never overwrite a built-in login.
+pub(crate) fn generic_oauth_key(provider_id: &str, suffix: &str) -> Result<String, String> {
+    let provider_id = provider_id.trim();
+    if provider_id.is_empty()
+        || !provider_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        return Err(format!("Invalid OAuth provider id: {}", provider_id));
+    }
+    if ApiKeyManager::is_builtin_provider_id(provider_id) {
+        return Err(format!(
+            "Generic OAuth is not available for built-in provider {}",
+            provider_id
+        ));
+    }
+    Ok(format!("generic_{}_oauth_{}", provider_id, suffix))
+}
+
+pub(crate) async fn load_generic_oauth_config(
+    api_keys: &ApiKeyManager,
+    provider_id: &str,
+) -> Result<GenericOAuthConfig, String> {
+    let raw = api_keys
+        .get_setting(&generic_oauth_key(provider_id, "config")?)
+        .await?
+        .filter(|value| !value.trim().is_empty())
+        .ok_or_else(|| format!("No OAuth config found for provider {}", provider_id))?;
+    serde_json::from_str(&raw)
+        .map_err(|e| format!("Invalid OAuth config for provider {}: {}", provider_id, e))
+}
+
+fn build_generic_authorize_url(
+    config: &GenericOAuthConfig,
+    challenge: &str,
+    state: &str,
+) -> Result<String, String> {
+    let mut url = url::Url::parse(&config.auth_url)
+        .map_err(|e| format!("Invalid OAuth authorize URL: {}", e))?;
+    {
+        let mut query = url.query_pairs_mut();
+        query
+            .append_pair("response_type", "code")
+            .append_pair("client_id", &config.client_id)
+            .append_pair("redirect_uri", &config.redirect_uri);
+        if !config.scopes.is_empty() {
+            query.append_pair("scope", &config.scopes.join(" "));
+        }
+        query
+            .append_pair("code_challenge", challenge)
+            .append_pair("code_challenge_method", "S256")
+            .append_pair("state", state);
+    }
+    Ok(url.to_string())
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GenericOAuthStartResponse {
+    pub url: String,
+    pub verifier: String,
+    pub state: String,
+}
+
+#[derive(Deserialize)]
+pub struct GenericOAuthCompleteRequest {
+    /// Empty when the code is captured by the callback listener
+    #[serde(default)]
+    pub code: String,
+    pub verifier: String,
+    pub state: String,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GenericOAuthCompleteResponse {
+    pub access_token: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub refresh_token: Option<String>,
+    pub expires_at: i64,
+}
+
+#[tauri::command]
+pub async fn llm_generic_oauth_set_config(
+    provider_id: String,
+    config: GenericOAuthConfig,
+    state: State<'_, LlmState>,
+) -> Result<(), String> {
+    url::Url::parse(&config.auth_url).map_err(|e| format!("Invalid OAuth authorize URL: {}", e))?;
+    url::Url::parse(&config.token_url).map_err(|e| format!("Invalid OAuth token URL: {}", e))?;
+    let raw = serde_json::to_string(&config)
+        .map_err(|e| format!("Failed to serialize OAuth config: {}", e))?;
+    let api_keys = state.api_keys.lock().await;
+    api_keys
+        .set_setting(&generic_oauth_key(&provider_id, "config")?, &raw)
+        .await
+}
+
+#[tauri::command]
+pub async fn llm_generic_oauth_start(
+    provider_id: String,
+    state: State<'_, LlmState>,
+) -> Result<GenericOAuthStartResponse, String> {
+    let config = {
+        let api_keys = state.api_keys.lock().await;
+        load_generic_oauth_config(&api_keys, &provider_id).await?
+    };
+
+    let verifier = generate_code_verifier();
+    let challenge = code_challenge(&verifier);
+    let oauth_state = generate_state();
+    let url = build_generic_authorize_url(&config, &challenge, &oauth_state)?;
+
+    // Store state for CSRF protection
+    store_oauth_state(oauth_state.clone()).await;
+
+    Ok(GenericOAuthStartResponse {
+        url,
+        verifier,
+        state: oauth_state,
+    })
+}
+
+#[tauri::command]
+pub async fn llm_generic_oauth_complete(
+    provider_id: String,
+    request: GenericOAuthCompleteRequest,
+    state: State<'_, LlmState>,
+) -> Result<GenericOAuthCompleteResponse, String> {
+    // Validate state for CSRF protection
+    if !validate_oauth_state(&request.state).await {
+        return Err("Invalid or expired OAuth state".to_string());
+    }
+    let code = authorization_code(request.code, &request.state).await?;
+
+    let api_keys = state.api_keys.lock().await;
+    let config = load_generic_oauth_config(&api_keys, &provider_id).await?;
+
+    let client = reqwest::Client::new();
+    let params = [
+        ("grant_type", "authorization_code"),
+        ("client_id", config.client_id.as_str()),
+        ("code", &code),
+        ("redirect_uri", config.redirect_uri.as_str()),
+        ("code_verifier", &request.verifier),
+    ];
+
+    let response = client
+        .post(&config.token_url)
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .form(&params)
+        .send()
+        .await
+        .map_err(|e| format!("Token request failed: {}", e))?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let text = response
+            .text()
+            .await
+            .unwrap_or_else(|_| "Unknown error".to_string());
+        return Err(format!("Token exchange failed ({}): {}", status, text));
+    }
+
+    let token_response: serde_json::Value = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse token response: {}", e))?;
+
+    store_generic_oauth_tokens(&api_keys, &provider_id, &token_response, None).await
+}
+
+/// Exchange a generic provider's refresh token at its configured token URL
+pub(crate) async fn refresh_generic_oauth_tokens(
+    client: &reqwest::Client,
+    provider_id: &str,
+    refresh_token: &str,
+    api_keys: &ApiKeyManager,
+) -> Result<GenericOAuthCompleteResponse, String> {
+    let config = load_generic_oauth_config(api_keys, provider_id).await?;
+    let params = [
+        ("grant_type", "refresh_token"),
+        ("client_id", config.client_id.as_str()),
+        ("refresh_token", refresh_token),
+    ];
+
+    let response = client
+        .post(&config.token_url)
+        .header("Content-Type", "application/x-www-form-urlencoded")
+        .form(&params)
+        .send()
+        .await
+        .map_err(|e| format!("Refresh request failed: {}", e))?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let text = response
+            .text()
+            .await
+            .unwrap_or_else(|_| "Unknown error".to_string());
+        return Err(format!("Token refresh failed ({}): {}", status, text));
+    }
+
+    let token_response: serde_json::Value = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse refresh response: {}", e))?;
+
+    store_generic_oauth_tokens(api_keys, provider_id, &token_response, Some(refresh_token)).await
+}
+
+/// Save the tokens of a token endpoint response. `previous_refresh_token` is
+/// kept when a refresh response does not rotate it.
+async fn store_generic_oauth_tokens(
+    api_keys: &ApiKeyManager,
+    provider_id: &str,
+    token_response: &serde_json::Value,
+    previous_refresh_token: Option<&str>,
+) -> Result<GenericOAuthCompleteResponse, String> {
+    let access_token = token_response["access_token"]
+        .as_str()
+        .ok_or("Missing access_token in response")?
+        .to_string();
+    let refresh_token = token_response["refresh_token"]
+        .as_str()
+        .or(previous_refresh_token)
+        .map(|s| s.to_string());
+
+    let expires_in = token_response["expires_in"].as_i64().unwrap_or(3600);
+    let expires_at = chrono::Utc::now().timestamp() + expires_in;
+
+    api_keys
+        .set_setting(
+            &generic_oauth_key(provider_id, "access_token")?,
+            &access_token,
+        )
+        .await?;
+    if let Some(ref token) = refresh_token {
+        api_keys
+            .set_setting(&generic_oauth_key(provider_id, "refresh_token")?, token)
+            .await?;
+    }
+    api_keys
+        .set_setting(
+            &generic_oauth_key(provider_id, "expires_at")?,
+            &expires_at.to_string(),
+        )
+        .await?;
+
+    Ok(GenericOAuthCompleteResponse {
+        access_token,
+        refresh_token,
+        expires_at,
+    })
+}
+
+// ============================================================================
+// OAuth Status
+// ============================================================================
+
+/// OAuth status response - does NOT include sensitive tokens
+#[derive(Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct OAuthProviderStatus {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub account_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub is_connected: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub has_refresh_token: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub accounts: Option<Vec<OAuthAccountStatus>>,
+}
+
+/// Per-account OAuth metadata - does NOT include sensitive tokens
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct OAuthAccountStatus {
+    pub label: String,
+    pub is_active: bool,
+    pub is_connected: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<i64>,
+}
+
+async fn oauth_account_statuses(
+    api_keys: &ApiKeyManager,
+    prefix: &str,
+) -> Result<Vec<OAuthAccountStatus>, String> {
+    let active = api_keys.active_oauth_account(prefix).await?;
+    let mut statuses = Vec::new();
+    for label in api_keys.list_oauth_accounts(prefix).await? {
+        let access_token = api_keys
+            .get_setting(&ApiKeyManager::oauth_account_key(
+                prefix,
+                "access_token",
+                &label,
+            ))
+            .await?
+            .filter(|s| !s.is_empty());
+        let expires_at = api_keys
+            .get_setting(&ApiKeyManager::oauth_account_key(
+                prefix,
+                "expires_at",
+                &label,
+            ))
+            .await?
+            .and_then(|s| s.parse::<i64>().ok());
+        statuses.push(OAuthAccountStatus {
+            is_active: label == active,
+            is_connected: access_token.is_some(),
+            expires_at,
+            label,
+        });
+    }
+    Ok(statuses)
+}
+
+#[tauri::command]
+pub async fn llm_oauth_list_accounts(
+    provider: String,
+    state: State<'_, LlmState>,
+) -> Result<Vec<OAuthAccountStatus>, String> {
+    let prefix = oauth_settings_prefix(&provider)?;
+    let api_keys = state.api_keys.lock().await;
+    oauth_account_statuses(&api_keys, prefix).await
+}
+
+#[tauri::command]
+pub async fn llm_oauth_switch_account(
+    provider: String,
+    label: String,
+    state: State<'_, LlmState>,
+) -> Result<(), String> {
+    let prefix = oauth_settings_prefix(&provider)?;
+    let api_keys = state.api_keys.lock().await;
+    api_keys.switch_oauth_account(prefix, &label).await
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OAuthStatusResponse {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub openai: Option<OAuthProviderStatus>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub anthropic: Option<OAuthProviderStatus>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub github_copilot: Option<OAuthProviderStatus>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub qwen: Option<OAuthProviderStatus>,
 }
 
 #[tauri::command]
 pub async fn llm_oauth_status(state: State<'_, LlmState>) -> Result<OAuthStatusResponse, String> {
     let api_keys = state.api_keys.lock().await;
+    oauth_status(&api_keys).await
+}
 
+/// Connection metadata for every OAuth provider, without any tokens
+pub async fn oauth_status(api_keys: &ApiKeyManager) -> Result<OAuthStatusResponse, String> {
     // OpenAI status - only return metadata, not tokens
     let openai_access = api_keys
-        .get_setting("openai_oauth_access_token")
+        .get_oauth_setting("openai", "access_token")
         .await?
         .filter(|s| !s.is_empty());
     let openai_refresh = api_keys
-        .get_setting("openai_oauth_refresh_token")
+        .get_oauth_setting("openai", "refresh_token")
         .await?
         .filter(|s| !s.is_empty());
     let openai_expires = api_keys
-        .get_setting("openai_oauth_expires_at")
+        .get_oauth_setting("openai", "expires_at")
         .await?
         .and_then(|s| s.parse::<i64>().ok());
     let openai_account = api_keys
-        .get_setting("openai_oauth_account_id")
+        .get_oauth_setting("openai", "account_id")
         .await?
         .filter(|s| !s.is_empty());
 
+    let openai_accounts = oauth_account_statuses(api_keys, "openai").await?;
+
     let openai = if openai_access.is_some() || openai_refresh.is_some() {
         Some(OAuthProviderStatus {
             expires_at: openai_expires,
             account_id: openai_account,
             is_connected: Some(true),
             has_refresh_token: Some(openai_refresh.is_some()),
+            accounts: Some(openai_accounts),
+        })
+    } else if !openai_accounts.is_empty() {
+        Some(OAuthProviderStatus {
+            is_connected: Some(false),
+            accounts: Some(openai_accounts),
+            ..Default::default()
         })
     } else {
         None
@@ -1117,20 +1748,29 @@ pub async fn llm_oauth_status(state: State<'_, LlmState>) -> Result<OAuthStatusR
 
     // Anthropic status - only return metadata, not tokens
     let anthropic_access = api_keys
-        .get_setting("claude_oauth_access_token")
+        .get_oauth_setting("claude", "access_token")
         .await?
         .filter(|s| !s.is_empty());
     let anthropic_expires = api_keys
-        .get_setting("claude_oauth_expires_at")
+        .get_oauth_setting("claude", "expires_at")
         .await?
         .and_then(|s| s.parse::<i64>().ok());
 
+    let anthropic_accounts = oauth_account_statuses(api_keys, "claude").await?;
+
     let anthropic = if anthropic_access.is_some() {
         Some(OAuthProviderStatus {
             expires_at: anthropic_expires,
             account_id: None,
             is_connected: Some(true),
             has_refresh_token: None,
+            accounts: Some(anthropic_accounts),
+        })
+    } else if !anthropic_accounts.is_empty() {
+        Some(OAuthProviderStatus {
+            is_connected: Some(false),
+            accounts: Some(anthropic_accounts),
+            ..Default::default()
         })
     } else {
         None
@@ -1155,10 +1795,27 @@ pub async fn llm_oauth_status(state: State<'_, LlmState>) -> Result<OAuthStatusR
         None
     };
 
+    // Qwen status - only return metadata
+    let qwen_access = api_keys
+        .get_oauth_setting(QWEN_OAUTH_PREFIX, "access_token")
+        .await?
+        .filter(|s| !s.is_empty());
+    let qwen_expires = api_keys
+        .get_oauth_setting(QWEN_OAUTH_PREFIX, "expires_at")
+        .await?
+        .and_then(|s| s.parse::<i64>().ok());
+
+    let qwen = qwen_access.map(|_| OAuthProviderStatus {
+        expires_at: qwen_expires,
+        is_connected: Some(true),
+        ..Default::default()
+    });
+
     Ok(OAuthStatusResponse {
         openai,
         anthropic,
         github_copilot,
+        qwen,
     })
 }
 
@@ -1273,4 +1930,392 @@ mod tests {
         let token = format!("{}.{}.", header, payload);
         assert_eq!(extract_openai_account_id(&token), None);
     }
+
+    fn fake_generic_config() -> GenericOAuthConfig {
+        GenericOAuthConfig {
+            client_id: "gateway-client".to_string(),
+            auth_url: "https://gateway.example.com/oauth/authorize?tenant=dev".to_string(),
+            token_url: "https://gateway.example.com/oauth/token".to_string(),
+            redirect_uri: "http://localhost:1455/auth/callback".to_string(),
+            scopes: vec!["openid".to_string(), "offline_access".to_string()],
+        }
+    }
+
+    #[test]
+    fn generic_authorize_url_includes_pkce_params() {
+        let url = build_generic_authorize_url(&fake_generic_config(), "challenge-1", "state-1")
+            .expect("valid url");
+        let parsed = url::Url::parse(&url).unwrap();
+        let pairs: std::collections::HashMap<_, _> = parsed.query_pairs().into_owned().collect();
+
+        assert!(url.starts_with("https://gateway.example.com/oauth/authorize?"));
+        assert_eq!(pairs.get("tenant").map(String::as_str), Some("dev"));
+        assert_eq!(pairs.get("response_type").map(String::as_str), Some("code"));
+        assert_eq!(
+            pairs.get("client_id").map(String::as_str),
+            Some("gateway-client")
+        );
+        assert_eq!(
+            pairs.get("redirect_uri").map(String::as_str),
+            Some("http://localhost:1455/auth/callback")
+        );
+        assert_eq!(
+            pairs.get("scope").map(String::as_str),
+            Some("openid offline_access")
+        );
+        assert_eq!(
+            pairs.get("code_challenge").map(String::as_str),
+            Some("challenge-1")
+        );
+        assert_eq!(
+            pairs.get("code_challenge_method").map(String::as_str),
+            Some("S256")
+        );
+        assert_eq!(pairs.get("state").map(String::as_str), Some("state-1"));
+    }
+
+    #[test]
+    fn generic_authorize_url_omits_empty_scope_and_rejects_bad_url() {
+        let mut config = fake_generic_config();
+        config.scopes.clear();
+        let url = build_generic_authorize_url(&config, "c", "s").unwrap();
+        assert!(!url.contains("scope="));
+
+        config.auth_url = "not a url".to_string();
+        assert!(build_generic_authorize_url(&config, "c", "s").is_err());
+    }
+
+    #[test]
+    fn generic_oauth_keys_are_namespaced_by_provider() {
+        assert_eq!(
+            generic_oauth_key("my-gateway", "access_token").unwrap(),
+            "generic_my-gateway_oauth_access_token"
+        );
+        assert!(generic_oauth_key("", "config").is_err());
+        assert!(generic_oauth_key("bad id", "config").is_err());
+        for builtin in [
+            "openai",
+            "anthropic",
+            "claude",
+            "github_copilot",
+            "qwen_code",
+        ] {
+            assert!(
+                generic_oauth_key(builtin, "access_token").is_err(),
+                "{}",
+                builtin
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn generic_oauth_state_is_single_use() {
+        let state = generate_state();
+        store_oauth_state(state.clone()).await;
+
+        assert!(validate_oauth_state(&state).await);
+        assert!(!validate_oauth_state(&state).await);
+        assert!(!validate_oauth_state("never-issued").await);
+    }
+
+    async fn test_api_keys() -> (tempfile::TempDir, ApiKeyManager) {
+        let dir = tempfile::TempDir::new().unwrap();
+        let db = std::sync::Arc::new(crate::database::Database::new(
+            dir.path().join("test.db").to_string_lossy().to_string(),
+        ));
+        db.connect().await.unwrap();
+        db.execute(
+            "CREATE TABLE IF NOT EXISTS settings (key TEXT PRIMARY KEY, value TEXT, updated_at INTEGER)",
+            vec![],
+        )
+        .await
+        .unwrap();
+        let api_keys = ApiKeyManager::new(db, dir.path().to_path_buf());
+        (dir, api_keys)
+    }
+
+    #[tokio::test]
+    async fn generic_oauth_config_round_trips_through_settings() {
+        let (_dir, api_keys) = test_api_keys().await;
+
+        assert!(load_generic_oauth_config(&api_keys, "gateway")
+            .await
+            .is_err());
+
+        let raw = serde_json::to_string(&fake_generic_config()).unwrap();
+        api_keys
+            .set_setting("generic_gateway_oauth_config", &raw)
+            .await
+            .unwrap();
+        let loaded = load_generic_oauth_config(&api_keys, "gateway")
+            .await
+            .unwrap();
+        assert_eq!(loaded.client_id, "gateway-client");
+        assert_eq!(loaded.token_url, "https://gateway.example.com/oauth/token");
+    }
+
+    #[tokio::test]
+    async fn disconnect_clears_settings_when_revocation_fails() {
+        let (_dir, api_keys) = test_api_keys().await;
+        for (field, value) in [
+            ("access_token", "access-1"),
+            ("refresh_token", "refresh-1"),
+            ("expires_at", "1700000000"),
+        ] {
+            api_keys
+                .set_oauth_setting("openai", field, value)
+                .await
+                .unwrap();
+        }
+
+        let server = tiny_http::Server::http("127.0.0.1:0").expect("server");
+        let revoke_url = match server.server_addr() {
+            tiny_http::ListenAddr::IP(addr) => format!("http://{}/oauth/revoke", addr),
+            _ => panic!("Expected IP SocketAddr"),
+        };
+        let server_handle = std::thread::spawn(move || {
+            use std::io::Read;
+            let mut bodies = Vec::new();
+            while let Ok(Some(mut request)) = server.recv_timeout(Duration::from_secs(5)) {
+                let mut body = String::new();
+                let _ = request.as_reader().read_to_string(&mut body);
+                bodies.push(body);
+                let _ =
+                    request.respond(tiny_http::Response::from_string("boom").with_status_code(500));
+                if bodies.len() == 2 {
+                    break;
+                }
+            }
+            bodies
+        });
+
+        disconnect_oauth_account(
+            &api_keys,
+            "openai",
+            OPENAI_CLIENT_ID,
+            Some(&revoke_url),
+            &["access_token", "refresh_token", "expires_at"],
+        )
+        .await
+        .expect("disconnect succeeds despite revoke failure");
+
+        let bodies = server_handle.join().unwrap();
+        assert_eq!(bodies.len(), 2);
+        assert!(bodies[0].contains("token=refresh-1"));
+        assert!(bodies[0].contains("token_type_hint=refresh_token"));
+        assert!(bodies[1].contains("token=access-1"));
+
+        for field in ["access_token", "refresh_token", "expires_at"] {
+            assert_eq!(
+                api_keys.get_oauth_setting("openai", field).await.unwrap(),
+                Some(String::new())
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn disconnect_without_revoke_endpoint_still_clears_settings() {
+        let (_dir, api_keys) = test_api_keys().await;
+        api_keys
+            .set_setting(GITHUB_COPILOT_ACCESS_TOKEN_KEY, "gho_token")
+            .await
+            .unwrap();
+        api_keys
+            .set_oauth_setting("claude", "access_token", "claude-token")
+            .await
+            .unwrap();
+
+        disconnect_github_copilot(&api_keys).await.unwrap();
+        disconnect_oauth_account(
+            &api_keys,
+            "claude",
+            CLAUDE_CLIENT_ID,
+            CLAUDE_REVOKE_URL,
+            &["access_token", "refresh_token", "expires_at"],
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(
+            api_keys
+                .get_setting(GITHUB_COPILOT_ACCESS_TOKEN_KEY)
+                .await
+                .unwrap(),
+            Some(String::new())
+        );
+        assert_eq!(
+            api_keys
+                .get_oauth_setting("claude", "access_token")
+                .await
+                .unwrap(),
+            Some(String::new())
+        );
+    }
+
+    /// Serve the given (status, body) responses in order from a mock GitHub token endpoint
+    fn mock_github_token_endpoint(
+        responses: Vec<(u16, &'static str)>,
+    ) -> (
+        String,
+        std::sync::Arc<std::sync::atomic::AtomicUsize>,
+        std::thread::JoinHandle<()>,
+    ) {
+        let server = tiny_http::Server::http("127.0.0.1:0").expect("server");
+        let url = match server.server_addr() {
+            tiny_http::ListenAddr::IP(addr) => {
+                format!("http://{}/login/oauth/access_token", addr)
+            }
+            _ => panic!("Expected IP SocketAddr"),
+        };
+        let hits = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counter = hits.clone();
+        let handle = std::thread::spawn(move || {
+            use std::io::Read;
+            for (status, body) in responses {
+                let Ok(Some(mut request)) = server.recv_timeout(Duration::from_secs(5)) else {
+                    return;
+                };
+                let mut payload = String::new();
+                let _ = request.as_reader().read_to_string(&mut payload);
+                assert!(payload.contains("\"device_code\":\"device-1\""));
+                counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                let _ = request.respond(
+                    tiny_http::Response::from_string(body)
+                        .with_status_code(status)
+                        .with_header(
+                            tiny_http::Header::from_bytes(
+                                &b"Content-Type"[..],
+                                &b"application/json"[..],
+                            )
+                            .expect("header"),
+                        ),
+                );
+            }
+        });
+        (url, hits, handle)
+    }
+
+    #[test]
+    fn classifies_github_device_flow_responses() {
+        assert_eq!(
+            classify_github_token_response(200, r#"{"error":"authorization_pending"}"#),
+            GitHubDevicePollOutcome::Pending
+        );
+        assert_eq!(
+            classify_github_token_response(200, r#"{"error":"slow_down","interval":10}"#),
+            GitHubDevicePollOutcome::SlowDown(Some(10))
+        );
+        assert_eq!(
+            classify_github_token_response(
+                200,
+                r#"{"access_token":"gho_abc","token_type":"bearer"}"#
+            ),
+            GitHubDevicePollOutcome::Approved("gho_abc".to_string())
+        );
+        assert_eq!(
+            classify_github_token_response(
+                200,
+                r#"{"error":"incorrect_device_code","error_description":"The device_code provided is not valid."}"#
+            ),
+            GitHubDevicePollOutcome::Failed("The device_code provided is not valid.".to_string())
+        );
+        assert!(matches!(
+            classify_github_token_response(200, r#"{"error":"expired_token"}"#),
+            GitHubDevicePollOutcome::Failed(message) if message.contains("expired")
+        ));
+        assert!(matches!(
+            classify_github_token_response(502, "Bad Gateway"),
+            GitHubDevicePollOutcome::Failed(_)
+        ));
+    }
+
+    #[test]
+    fn github_copilot_domain_uses_normalized_enterprise_host() {
+        assert_eq!(github_copilot_domain(None), "github.com");
+        assert_eq!(github_copilot_domain(Some("")), "github.com");
+        assert_eq!(
+            github_copilot_domain(Some("https://Company.ghe.com/")),
+            "company.ghe.com"
+        );
+        assert_eq!(
+            github_device_token_url(&github_copilot_domain(Some("company.ghe.com"))),
+            "https://company.ghe.com/login/oauth/access_token"
+        );
+    }
+
+    #[tokio::test]
+    async fn github_device_poll_widens_interval_on_slow_down() {
+        let (url, hits, handle) = mock_github_token_endpoint(vec![
+            (200, r#"{"error":"slow_down","interval":10}"#),
+            (200, r#"{"error":"slow_down"}"#),
+        ]);
+        let client = reqwest::Client::new();
+        assert_eq!(
+            request_github_device_token(&client, &url, "device-1")
+                .await
+                .unwrap(),
+            GitHubDevicePollOutcome::SlowDown(Some(10))
+        );
+        assert_eq!(
+            request_github_device_token(&client, &url, "device-1")
+                .await
+                .unwrap(),
+            GitHubDevicePollOutcome::SlowDown(None)
+        );
+        handle.join().unwrap();
+        assert_eq!(hits.load(std::sync::atomic::Ordering::SeqCst), 2);
+
+        assert_eq!(github_slow_down_interval(Some(10), Some(5)), 10);
+        assert_eq!(github_slow_down_interval(None, Some(7)), 12);
+        assert_eq!(github_slow_down_interval(None, None), 10);
+        assert_eq!(github_slow_down_interval(Some(0), Some(0)), 10);
+    }
+
+    #[tokio::test]
+    async fn github_device_flow_rejects_invalid_enterprise_domain() {
+        let err = llm_github_copilot_oauth_start_device_code(GitHubCopilotOAuthStartRequest {
+            enterprise_url: Some("not a domain".to_string()),
+        })
+        .await
+        .err()
+        .expect("invalid domain");
+        assert!(err.contains("Invalid GitHub Enterprise domain"));
+        assert_eq!(
+            github_enterprise_domain(Some("https://Company.ghe.com/")).unwrap(),
+            Some("company.ghe.com".to_string())
+        );
+        assert_eq!(github_enterprise_domain(Some("github.com")).unwrap(), None);
+        assert_eq!(github_enterprise_domain(None).unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn stored_copilot_tokens_keep_normalized_enterprise_domain() {
+        let (_dir, api_keys) = test_api_keys().await;
+        let tokens = GitHubCopilotOAuthTokens {
+            access_token: "gho_abc".to_string(),
+            copilot_token: "tid=copilot".to_string(),
+            expires_at: 1_700_000_000_000,
+            enterprise_url: normalize_github_enterprise_domain("https://Company.ghe.com/").unwrap(),
+        };
+        store_github_copilot_tokens(&api_keys, &tokens)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            api_keys
+                .get_setting(GITHUB_COPILOT_ENTERPRISE_URL_KEY)
+                .await
+                .unwrap()
+                .as_deref(),
+            Some("company.ghe.com")
+        );
+        assert_eq!(
+            api_keys
+                .get_setting(GITHUB_COPILOT_ACCESS_TOKEN_KEY)
+                .await
+                .unwrap()
+                .as_deref(),
+            Some("gho_abc")
+        );
+    }
 }
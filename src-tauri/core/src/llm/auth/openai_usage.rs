@@ -54,7 +54,7 @@ fn is_auth_error(status: reqwest::StatusCode) -> bool {
 
 async fn load_refresh_token(api_keys: &ApiKeyManager) -> Result<Option<String>, String> {
     let refresh_token = api_keys
-        .get_setting("openai_oauth_refresh_token")
+        .get_oauth_setting("openai", "refresh_token")
         .await?
         .unwrap_or_default();
     Ok((!refresh_token.trim().is_empty()).then_some(refresh_token))
@@ -71,7 +71,7 @@ async fn refresh_access_token(
 
 pub async fn fetch_openai_oauth_usage(api_keys: &ApiKeyManager) -> Result<Value, String> {
     let token = api_keys
-        .get_setting("openai_oauth_access_token")
+        .get_oauth_setting("openai", "access_token")
         .await?
         .unwrap_or_default();
     let refresh_token = load_refresh_token(api_keys).await?;
@@ -0,0 +1,427 @@
+// Qwen OAuth (RFC 8628 device authorization grant with PKCE)
+// The start command requests a device code; the poll command blocks until the
+// user approves in the browser, the code expires, or the server rejects it.
+
+use crate::llm::auth::api_key_manager::{ApiKeyManager, LlmState};
+use crate::llm::auth::oauth::{code_challenge, generate_code_verifier};
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+use tauri::State;
+use tokio::sync::Mutex;
+
+const QWEN_CLIENT_ID: &str = "f0304373b74a44d2b584a3fb70ca9e56";
+const QWEN_DEVICE_CODE_URL: &str = "https://chat.qwen.ai/api/v1/oauth2/device/code";
+const QWEN_TOKEN_URL: &str = "https://chat.qwen.ai/api/v1/oauth2/token";
+const QWEN_OAUTH_SCOPE: &str = "openid profile email model.completion";
+const DEVICE_CODE_GRANT_TYPE: &str = "urn:ietf:params:oauth:grant-type:device_code";
+
+pub const QWEN_OAUTH_PREFIX: &str = "qwen";
+
+/// Default polling interval when the server does not specify one
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(5);
+/// RFC 8628 section 3.5: increase the interval by 5 seconds on `slow_down`
+const SLOW_DOWN_STEP: Duration = Duration::from_secs(5);
+
+/// Device authorization waiting for the user, consumed by the poll command
+struct PendingDeviceAuthorization {
+    device_code: String,
+    verifier: String,
+    interval: Duration,
+    deadline: Instant,
+}
+
+static PENDING_QWEN_DEVICE: OnceLock<Mutex<Option<PendingDeviceAuthorization>>> = OnceLock::new();
+
+fn pending_device() -> &'static Mutex<Option<PendingDeviceAuthorization>> {
+    PENDING_QWEN_DEVICE.get_or_init(|| Mutex::new(None))
+}
+
+#[derive(Deserialize)]
+struct QwenDeviceCodeResponse {
+    device_code: String,
+    user_code: String,
+    verification_uri: String,
+    verification_uri_complete: Option<String>,
+    expires_in: u64,
+    interval: Option<u64>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QwenOAuthStartResponse {
+    pub user_code: String,
+    pub verification_uri: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub verification_uri_complete: Option<String>,
+    pub expires_in: u64,
+}
+
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+struct QwenTokenResponse {
+    access_token: String,
+    refresh_token: Option<String>,
+    expires_in: Option<i64>,
+    resource_url: Option<String>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QwenOAuthTokens {
+    pub expires_at: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resource_url: Option<String>,
+}
+
+/// Outcome of a single token endpoint request
+#[derive(Debug, PartialEq)]
+enum DevicePollOutcome {
+    Approved(QwenTokenResponse),
+    Pending,
+    SlowDown,
+    Failed(String),
+}
+
+/// Classify a token endpoint response per RFC 8628 section 3.5
+fn classify_token_response(status: u16, body: &str) -> DevicePollOutcome {
+    let payload: serde_json::Value = match serde_json::from_str(body) {
+        Ok(value) => value,
+        Err(_) => {
+            return DevicePollOutcome::Failed(format!(
+                "Token request failed ({}): {}",
+                status, body
+            ))
+        }
+    };
+
+    if let Some(error) = payload.get("error").and_then(|v| v.as_str()) {
+        return match error {
+            "authorization_pending" => DevicePollOutcome::Pending,
+            "slow_down" => DevicePollOutcome::SlowDown,
+            "expired_token" => {
+                DevicePollOutcome::Failed("Device code expired, please try again".to_string())
+            }
+            "access_denied" => DevicePollOutcome::Failed("Authorization was denied".to_string()),
+            _ => DevicePollOutcome::Failed(
+                payload
+                    .get("error_description")
+                    .and_then(|v| v.as_str())
+                    .map(|s| s.to_string())
+                    .unwrap_or_else(|| format!("OAuth error: {}", error)),
+            ),
+        };
+    }
+
+    if !(200..300).contains(&status) {
+        return DevicePollOutcome::Failed(format!("Token request failed ({}): {}", status, body));
+    }
+
+    match serde_json::from_value::<QwenTokenResponse>(payload) {
+        Ok(tokens) if !tokens.access_token.is_empty() => DevicePollOutcome::Approved(tokens),
+        _ => DevicePollOutcome::Failed("Token response missing access_token".to_string()),
+    }
+}
+
+/// Poll the token endpoint until the user approves, the server rejects the
+/// request, or `deadline` passes. `slow_down` permanently widens the interval.
+async fn poll_device_token(
+    client: &reqwest::Client,
+    token_url: &str,
+    pending: &PendingDeviceAuthorization,
+    slow_down_step: Duration,
+) -> Result<QwenTokenResponse, String> {
+    let mut interval = pending.interval;
+    loop {
+        if Instant::now() + interval > pending.deadline {
+            return Err("Timed out waiting for Qwen authorization".to_string());
+        }
+        tokio::time::sleep(interval).await;
+
+        let response = client
+            .post(token_url)
+            .header("Accept", "application/json")
+            .form(&[
+                ("grant_type", DEVICE_CODE_GRANT_TYPE),
+                ("client_id", QWEN_CLIENT_ID),
+                ("device_code", pending.device_code.as_str()),
+                ("code_verifier", pending.verifier.as_str()),
+            ])
+            .send()
+            .await
+            .map_err(|e| format!("Token request failed: {}", e))?;
+        let status = response.status().as_u16();
+        let body = response.text().await.unwrap_or_default();
+
+        match classify_token_response(status, &body) {
+            DevicePollOutcome::Approved(tokens) => return Ok(tokens),
+            DevicePollOutcome::Pending => {}
+            DevicePollOutcome::SlowDown => {
+                interval += slow_down_step;
+                log::debug!(
+                    "Qwen OAuth asked to slow down, polling every {:?}",
+                    interval
+                );
+            }
+            DevicePollOutcome::Failed(message) => return Err(message),
+        }
+    }
+}
+
+async fn store_qwen_tokens(
+    api_keys: &ApiKeyManager,
+    tokens: &QwenTokenResponse,
+) -> Result<i64, String> {
+    // expires_at is stored in seconds, matching the OpenAI and Claude flows
+    let expires_at = chrono::Utc::now().timestamp() + tokens.expires_in.unwrap_or(3600);
+    api_keys
+        .set_oauth_setting(QWEN_OAUTH_PREFIX, "access_token", &tokens.access_token)
+        .await?;
+    api_keys
+        .set_oauth_setting(
+            QWEN_OAUTH_PREFIX,
+            "refresh_token",
+            tokens.refresh_token.as_deref().unwrap_or(""),
+        )
+        .await?;
+    api_keys
+        .set_oauth_setting(QWEN_OAUTH_PREFIX, "expires_at", &expires_at.to_string())
+        .await?;
+    api_keys
+        .set_oauth_setting(
+            QWEN_OAUTH_PREFIX,
+            "resource_url",
+            tokens.resource_url.as_deref().unwrap_or(""),
+        )
+        .await?;
+    Ok(expires_at)
+}
+
+#[tauri::command]
+pub async fn llm_qwen_oauth_start() -> Result<QwenOAuthStartResponse, String> {
+    let verifier = generate_code_verifier();
+    let challenge = code_challenge(&verifier);
+
+    let client = reqwest::Client::new();
+    let response = client
+        .post(QWEN_DEVICE_CODE_URL)
+        .header("Accept", "application/json")
+        .form(&[
+            ("client_id", QWEN_CLIENT_ID),
+            ("scope", QWEN_OAUTH_SCOPE),
+            ("code_challenge", challenge.as_str()),
+            ("code_challenge_method", "S256"),
+        ])
+        .send()
+        .await
+        .map_err(|e| format!("Device code request failed: {}", e))?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let text = response
+            .text()
+            .await
+            .unwrap_or_else(|_| "Unknown error".to_string());
+        return Err(format!("Device code request failed ({}): {}", status, text));
+    }
+
+    let data: QwenDeviceCodeResponse = response
+        .json()
+        .await
+        .map_err(|e| format!("Failed to parse device code response: {}", e))?;
+
+    *pending_device().lock().await = Some(PendingDeviceAuthorization {
+        device_code: data.device_code,
+        verifier,
+        interval: data
+            .interval
+            .map(Duration::from_secs)
+            .unwrap_or(DEFAULT_POLL_INTERVAL),
+        deadline: Instant::now() + Duration::from_secs(data.expires_in),
+    });
+
+    Ok(QwenOAuthStartResponse {
+        user_code: data.user_code,
+        verification_uri: data.verification_uri,
+        verification_uri_complete: data.verification_uri_complete,
+        expires_in: data.expires_in,
+    })
+}
+
+#[tauri::command]
+pub async fn llm_qwen_oauth_poll(state: State<'_, LlmState>) -> Result<QwenOAuthTokens, String> {
+    let pending = pending_device()
+        .lock()
+        .await
+        .take()
+        .ok_or("No Qwen device authorization in progress")?;
+
+    let client = reqwest::Client::new();
+    let tokens = poll_device_token(&client, QWEN_TOKEN_URL, &pending, SLOW_DOWN_STEP).await?;
+
+    let api_keys = state.api_keys.lock().await;
+    let expires_at = store_qwen_tokens(&api_keys, &tokens).await?;
+    Ok(QwenOAuthTokens {
+        expires_at,
+        resource_url: tokens.resource_url,
+    })
+}
+
+#[tauri::command]
+pub async fn llm_qwen_oauth_disconnect(state: State<'_, LlmState>) -> Result<(), String> {
+    pending_device().lock().await.take();
+    let api_keys = state.api_keys.lock().await;
+    for field in [
+        "access_token",
+        "refresh_token",
+        "expires_at",
+        "resource_url",
+    ] {
+        api_keys
+            .set_oauth_setting(QWEN_OAUTH_PREFIX, field, "")
+            .await?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    /// Serve the given (status, body) responses in order from a mock token endpoint
+    fn mock_token_endpoint(
+        responses: Vec<(u16, &'static str)>,
+    ) -> (String, Arc<AtomicUsize>, std::thread::JoinHandle<()>) {
+        let server = tiny_http::Server::http("127.0.0.1:0").expect("server");
+        let url = match server.server_addr() {
+            tiny_http::ListenAddr::IP(addr) => format!("http://{}/oauth2/token", addr),
+            _ => panic!("Expected IP SocketAddr"),
+        };
+        let hits = Arc::new(AtomicUsize::new(0));
+        let counter = hits.clone();
+        let handle = std::thread::spawn(move || {
+            for (status, body) in responses {
+                let Ok(Some(request)) = server.recv_timeout(Duration::from_secs(5)) else {
+                    return;
+                };
+                counter.fetch_add(1, Ordering::SeqCst);
+                let _ = request.respond(
+                    tiny_http::Response::from_string(body)
+                        .with_status_code(status)
+                        .with_header(
+                            tiny_http::Header::from_bytes(
+                                &b"Content-Type"[..],
+                                &b"application/json"[..],
+                            )
+                            .expect("header"),
+                        ),
+                );
+            }
+        });
+        (url, hits, handle)
+    }
+
+    fn pending(interval_ms: u64, timeout_ms: u64) -> PendingDeviceAuthorization {
+        PendingDeviceAuthorization {
+            device_code: "device-1".to_string(),
+            verifier: "verifier-1".to_string(),
+            interval: Duration::from_millis(interval_ms),
+            deadline: Instant::now() + Duration::from_millis(timeout_ms),
+        }
+    }
+
+    #[test]
+    fn classifies_device_flow_responses() {
+        assert_eq!(
+            classify_token_response(400, r#"{"error":"authorization_pending"}"#),
+            DevicePollOutcome::Pending
+        );
+        assert_eq!(
+            classify_token_response(400, r#"{"error":"slow_down"}"#),
+            DevicePollOutcome::SlowDown
+        );
+        assert!(matches!(
+            classify_token_response(400, r#"{"error":"expired_token"}"#),
+            DevicePollOutcome::Failed(_)
+        ));
+        assert!(matches!(
+            classify_token_response(502, "Bad Gateway"),
+            DevicePollOutcome::Failed(_)
+        ));
+        match classify_token_response(
+            200,
+            r#"{"access_token":"at","refresh_token":"rt","expires_in":7200,"resource_url":"portal.qwen.ai"}"#,
+        ) {
+            DevicePollOutcome::Approved(tokens) => {
+                assert_eq!(tokens.access_token, "at");
+                assert_eq!(tokens.refresh_token.as_deref(), Some("rt"));
+                assert_eq!(tokens.expires_in, Some(7200));
+            }
+            other => panic!("unexpected outcome: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn polling_waits_through_pending_and_slow_down() {
+        let (url, hits, handle) = mock_token_endpoint(vec![
+            (400, r#"{"error":"authorization_pending"}"#),
+            (400, r#"{"error":"slow_down"}"#),
+            (200, r#"{"access_token":"qwen-at","expires_in":3600}"#),
+        ]);
+
+        let started = Instant::now();
+        let tokens = poll_device_token(
+            &reqwest::Client::new(),
+            &url,
+            &pending(10, 5_000),
+            Duration::from_millis(100),
+        )
+        .await
+        .expect("approved");
+        handle.join().unwrap();
+
+        assert_eq!(tokens.access_token, "qwen-at");
+        assert_eq!(hits.load(Ordering::SeqCst), 3);
+        // Two base intervals plus one widened interval after slow_down
+        assert!(started.elapsed() >= Duration::from_millis(120));
+    }
+
+    #[tokio::test]
+    async fn polling_stops_on_terminal_error() {
+        let (url, hits, handle) = mock_token_endpoint(vec![
+            (400, r#"{"error":"authorization_pending"}"#),
+            (400, r#"{"error":"access_denied"}"#),
+        ]);
+
+        let err = poll_device_token(
+            &reqwest::Client::new(),
+            &url,
+            &pending(10, 5_000),
+            Duration::from_millis(10),
+        )
+        .await
+        .unwrap_err();
+        handle.join().unwrap();
+
+        assert!(err.contains("denied"));
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+    }
+
+    #[tokio::test]
+    async fn polling_times_out_when_never_approved() {
+        let (url, _hits, _handle) =
+            mock_token_endpoint(vec![(400, r#"{"error":"authorization_pending"}"#); 20]);
+
+        let err = poll_device_token(
+            &reqwest::Client::new(),
+            &url,
+            &pending(20, 70),
+            Duration::from_millis(10),
+        )
+        .await
+        .unwrap_err();
+
+        assert!(err.contains("Timed out"));
+    }
+}
@@ -0,0 +1,123 @@
+// Where sensitive settings (API keys, OAuth tokens) live.
+// The default backend keeps everything in the settings table; the keyring backend
+// moves secrets into the OS credential store and leaves the rest in the database.
+
+use std::sync::Arc;
+
+/// Service name secrets are filed under in the OS keyring
+pub const KEYRING_SERVICE: &str = "talkcody";
+
+/// Setting selecting the backend for sensitive keys (`database` or `keyring`)
+pub const SETTINGS_BACKEND_KEY: &str = "settings_backend";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SettingsBackend {
+    #[default]
+    Database,
+    Keyring,
+}
+
+impl SettingsBackend {
+    pub fn parse(value: &str) -> Self {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "keyring" => Self::Keyring,
+            _ => Self::Database,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Database => "database",
+            Self::Keyring => "keyring",
+        }
+    }
+}
+
+/// OAuth fields holding credentials; the rest (expiry, account ids) are plain settings
+pub const SENSITIVE_OAUTH_FIELDS: [&str; 3] = ["access_token", "refresh_token", "copilot_token"];
+
+/// Settings that hold credentials and belong in the keyring when it is enabled
+pub fn is_sensitive_key(key: &str) -> bool {
+    if key.starts_with("api_key_") {
+        return true;
+    }
+    key.contains("_oauth_")
+        && SENSITIVE_OAUTH_FIELDS
+            .iter()
+            .any(|field| key.contains(field))
+}
+
+/// Key-value store for secrets, keyed by setting name
+pub trait SecretStore: Send + Sync {
+    fn get(&self, key: &str) -> Result<Option<String>, String>;
+    fn set(&self, key: &str, value: &str) -> Result<(), String>;
+    fn delete(&self, key: &str) -> Result<(), String>;
+}
+
+/// OS keyring (Keychain, Credential Manager, Secret Service)
+#[derive(Debug, Default)]
+pub struct KeyringStore;
+
+impl KeyringStore {
+    fn entry(key: &str) -> Result<keyring::Entry, String> {
+        keyring::Entry::new(KEYRING_SERVICE, key)
+            .map_err(|e| format!("Failed to open keyring entry {}: {}", key, e))
+    }
+}
+
+impl SecretStore for KeyringStore {
+    fn get(&self, key: &str) -> Result<Option<String>, String> {
+        match Self::entry(key)?.get_password() {
+            Ok(value) => Ok(Some(value)),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(e) => Err(format!("Failed to read {} from keyring: {}", key, e)),
+        }
+    }
+
+    fn set(&self, key: &str, value: &str) -> Result<(), String> {
+        Self::entry(key)?
+            .set_password(value)
+            .map_err(|e| format!("Failed to write {} to keyring: {}", key, e))
+    }
+
+    fn delete(&self, key: &str) -> Result<(), String> {
+        match Self::entry(key)?.delete_credential() {
+            Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+            Err(e) => Err(format!("Failed to delete {} from keyring: {}", key, e)),
+        }
+    }
+}
+
+pub fn default_secret_store() -> Arc<dyn SecretStore> {
+    Arc::new(KeyringStore)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classifies_sensitive_keys() {
+        assert!(is_sensitive_key("api_key_openai"));
+        assert!(is_sensitive_key("openai_oauth_access_token"));
+        assert!(is_sensitive_key("claude_oauth_refresh_token_work"));
+        assert!(is_sensitive_key("github_copilot_oauth_copilot_token"));
+        assert!(!is_sensitive_key("openai_oauth_expires_at"));
+        assert!(!is_sensitive_key("base_url_openai"));
+        assert!(!is_sensitive_key(SETTINGS_BACKEND_KEY));
+    }
+
+    #[test]
+    fn parses_backend_with_database_default() {
+        assert_eq!(SettingsBackend::parse("keyring"), SettingsBackend::Keyring);
+        assert_eq!(
+            SettingsBackend::parse(" Keyring "),
+            SettingsBackend::Keyring
+        );
+        assert_eq!(
+            SettingsBackend::parse("database"),
+            SettingsBackend::Database
+        );
+        assert_eq!(SettingsBackend::parse(""), SettingsBackend::Database);
+    }
+}
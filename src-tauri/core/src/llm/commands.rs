@@ -13,15 +13,22 @@ use crate::llm::ai_services::types::{
 use crate::llm::auth::api_key_manager::LlmState;
 use crate::llm::models::model_registry::ModelRegistry;
 use crate::llm::models::model_sync;
+use crate::llm::providers::connection_test::{test_provider_connection, ProviderTestError};
+use crate::llm::providers::health_monitor::{HealthStatus, ProviderHealthMonitor};
+use crate::llm::streaming::stream_error::StreamError;
 use crate::llm::streaming::stream_handler::StreamHandler;
+use crate::llm::streaming::stream_registry;
 use crate::llm::transcription::service::TranscriptionService;
 use crate::llm::transcription::types::TranscriptionContext;
 use crate::llm::types::{
-    AvailableModel, CustomProviderConfig, ImageDownloadRequest, ImageDownloadResponse,
-    ImageGenerationRequest, ImageGenerationResponse, ModelsConfiguration, StreamResponse,
+    AvailableModel, ConfigWarning, CustomProviderConfig, ImageDownloadRequest,
+    ImageDownloadResponse, ImageGenerationRequest, ImageGenerationResponse, ModelCapabilityFilter,
+    ModelsConfiguration, ProviderAvailability, RequestPreview, StreamEvent, StreamResponse,
     StreamTextRequest, TranscriptionRequest, TranscriptionResponse,
 };
-use tauri::{Manager, State, Window};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tauri::{Emitter, Manager, State, Window};
 
 #[tauri::command]
 pub async fn llm_get_provider_configs(
@@ -64,26 +71,99 @@ pub async fn llm_stream_text(
         .unwrap_or_else(|| "0".to_string());
 
     let request_id_clone = request_id.clone();
+    let window_label = window.label().to_string();
+    let task_window_label = window_label.clone();
     // Spawn the streaming process in a background task so the command returns immediately
-    tauri::async_runtime::spawn(async move {
+    let handle = tauri::async_runtime::spawn(async move {
         if let Err(e) = handler
-            .stream_completion(window, request, request_id_clone)
+            .stream_completion(window, request, request_id_clone.clone())
             .await
         {
             log::error!("[llm_stream_text] Stream error: {}", e);
         }
+        stream_registry::finish_stream(&task_window_label, &request_id_clone);
     });
+    // Tracked per window so closing or cancelling in one window leaves others running
+    stream_registry::register_stream(&window_label, &request_id, handle);
 
     Ok(StreamResponse { request_id })
 }
 
+/// Abort a stream started by the calling window; returns false if it is not running
+#[tauri::command]
+pub async fn llm_cancel_stream(window: Window, request_id: String) -> Result<bool, String> {
+    let cancelled = stream_registry::cancel_stream(window.label(), &request_id);
+    if cancelled {
+        log::info!(
+            "[llm_cancel_stream] Cancelled stream {} in window {}",
+            request_id,
+            window.label()
+        );
+        let error = StreamError::Cancelled;
+        let _ = window.emit(
+            &format!("llm-stream-{}", request_id),
+            &StreamEvent::Error {
+                message: error.to_string(),
+                provider_error: None,
+                kind: Some(error.kind()),
+            },
+        );
+    }
+    Ok(cancelled)
+}
+
+/// Build the request `llm_stream_text` would send without sending it
+#[tauri::command]
+pub async fn llm_preview_request(
+    request: StreamTextRequest,
+    state: State<'_, LlmState>,
+) -> Result<RequestPreview, String> {
+    let (registry, api_keys) = {
+        let registry = state.registry.lock().await;
+        let api_keys = state.api_keys.lock().await;
+        (registry.clone(), api_keys.clone())
+    };
+
+    StreamHandler::new(registry, api_keys)
+        .build_preview(&request)
+        .await
+}
+
 #[tauri::command]
 pub async fn llm_list_available_models(
+    filter: Option<ModelCapabilityFilter>,
     state: State<'_, LlmState>,
 ) -> Result<Vec<AvailableModel>, String> {
     let registry = state.registry.lock().await;
     let api_keys = state.api_keys.lock().await;
-    ModelRegistry::compute_available_models(&api_keys, &registry).await
+    match filter {
+        Some(filter) => {
+            ModelRegistry::compute_available_models_filtered(&api_keys, &registry, &filter).await
+        }
+        None => ModelRegistry::compute_available_models(&api_keys, &registry).await,
+    }
+}
+
+/// Check a provider's credentials and endpoint; returns the round-trip latency in ms
+#[tauri::command]
+pub async fn llm_test_provider(
+    provider_id: String,
+    state: State<'_, LlmState>,
+) -> Result<u64, ProviderTestError> {
+    let (registry, api_keys) = {
+        let registry = state.registry.lock().await;
+        let api_keys = state.api_keys.lock().await;
+        (registry.clone(), api_keys.clone())
+    };
+    test_provider_connection(&registry, &api_keys, &provider_id).await
+}
+
+/// Latest background health check per provider; stale results are omitted
+#[tauri::command]
+pub fn llm_provider_health(
+    monitor: State<'_, Arc<ProviderHealthMonitor>>,
+) -> Result<HashMap<String, HealthStatus>, String> {
+    Ok(monitor.statuses(chrono::Utc::now().timestamp_millis()))
 }
 
 #[tauri::command]
@@ -121,6 +201,7 @@ pub async fn llm_register_custom_provider(
         headers: None,
         extra_body: None,
         auth_type: crate::llm::types::AuthType::Bearer,
+        body_transforms: None,
     });
     Ok(())
 }
@@ -157,6 +238,28 @@ pub async fn llm_is_model_available(
     Ok(!model_key.is_empty() && !provider_id.is_empty())
 }
 
+/// Availability of every provider, with the reason, in one call
+#[tauri::command]
+pub async fn llm_provider_availability(
+    state: State<'_, LlmState>,
+) -> Result<HashMap<String, ProviderAvailability>, String> {
+    let registry = state.registry.lock().await;
+    let api_keys = state.api_keys.lock().await;
+    ModelRegistry::compute_provider_availability(&api_keys, &registry).await
+}
+
+/// Dangling provider references and colliding model names in the models config
+#[tauri::command]
+pub async fn llm_validate_models_config(
+    state: State<'_, LlmState>,
+) -> Result<Vec<ConfigWarning>, String> {
+    let registry = state.registry.lock().await;
+    let api_keys = state.api_keys.lock().await;
+    let custom_providers = api_keys.load_custom_providers().await?;
+    let models = ModelRegistry::load_models_config(&api_keys).await?;
+    Ok(models.validate(&registry, &custom_providers))
+}
+
 #[tauri::command]
 pub async fn llm_transcribe_audio(
     request: TranscriptionRequest,
@@ -258,6 +258,7 @@ mod tests {
             headers: None,
             extra_body: None,
             auth_type: crate::llm::types::AuthType::Bearer,
+            body_transforms: None,
         };
         let client = AIGatewayImageClient::new(config);
 
@@ -284,6 +285,7 @@ mod tests {
             headers: None,
             extra_body: None,
             auth_type: crate::llm::types::AuthType::Bearer,
+            body_transforms: None,
         };
         let client = AIGatewayImageClient::new(config);
 
@@ -410,6 +410,7 @@ mod tests {
             headers: None,
             extra_body: None,
             auth_type: crate::llm::types::AuthType::Bearer,
+            body_transforms: None,
         };
         let _client = DashScopeImageClient::new(config);
     }
@@ -69,6 +69,7 @@ fn openai_image_client_constructs() {
         headers: None,
         extra_body: None,
         auth_type: AuthType::Bearer,
+        body_transforms: None,
     };
     let _client = OpenAiImageClient::new(config);
     let _image: GeneratedImage = GeneratedImage {
@@ -114,6 +115,7 @@ async fn setup_test_context() -> (
             headers: None,
             extra_body: None,
             auth_type: AuthType::Bearer,
+            body_transforms: None,
         },
         ProviderConfig {
             id: "google".to_string(),
@@ -129,6 +131,7 @@ async fn setup_test_context() -> (
             headers: None,
             extra_body: None,
             auth_type: AuthType::Bearer,
+            body_transforms: None,
         },
     ];
     let registry = ProviderRegistry::new(providers);
@@ -148,6 +151,9 @@ async fn setup_test_context() -> (
             provider_mappings: None,
             pricing: None,
             context_length: Some(65536),
+            aliases: Vec::new(),
+            deprecated: None,
+            deprecation_message: None,
         },
     );
     models.insert(
@@ -163,6 +169,9 @@ async fn setup_test_context() -> (
             provider_mappings: None,
             pricing: None,
             context_length: None,
+            aliases: Vec::new(),
+            deprecated: None,
+            deprecation_message: None,
         },
     );
     models.insert(
@@ -178,6 +187,9 @@ async fn setup_test_context() -> (
             provider_mappings: None,
             pricing: None,
             context_length: Some(8192),
+            aliases: Vec::new(),
+            deprecated: None,
+            deprecation_message: None,
         },
     );
 
@@ -321,6 +333,9 @@ async fn resolve_image_generator_model_returns_error_when_no_image_model_availab
             provider_mappings: None,
             pricing: None,
             context_length: Some(65536),
+            aliases: Vec::new(),
+            deprecated: None,
+            deprecation_message: None,
         },
     );
 
@@ -379,6 +394,7 @@ async fn resolve_image_generator_model_finds_volcengine_model() {
         headers: None,
         extra_body: None,
         auth_type: AuthType::Bearer,
+        body_transforms: None,
     }];
     let registry = ProviderRegistry::new(providers);
 
@@ -397,6 +413,9 @@ async fn resolve_image_generator_model_finds_volcengine_model() {
             provider_mappings: None,
             pricing: None,
             context_length: None,
+            aliases: Vec::new(),
+            deprecated: None,
+            deprecation_message: None,
         },
     );
 
@@ -463,6 +482,7 @@ async fn resolve_image_generator_model_finds_alibaba_model() {
         headers: None,
         extra_body: None,
         auth_type: AuthType::Bearer,
+        body_transforms: None,
     }];
     let registry = ProviderRegistry::new(providers);
 
@@ -481,6 +501,9 @@ async fn resolve_image_generator_model_finds_alibaba_model() {
             provider_mappings: None,
             pricing: None,
             context_length: None,
+            aliases: Vec::new(),
+            deprecated: None,
+            deprecation_message: None,
         },
     );
 
@@ -547,6 +570,7 @@ async fn resolve_image_generator_model_finds_zhipu_image_model() {
         headers: None,
         extra_body: None,
         auth_type: AuthType::Bearer,
+        body_transforms: None,
     }];
     let registry = ProviderRegistry::new(providers);
 
@@ -565,6 +589,9 @@ async fn resolve_image_generator_model_finds_zhipu_image_model() {
             provider_mappings: None,
             pricing: None,
             context_length: None,
+            aliases: Vec::new(),
+            deprecated: None,
+            deprecation_message: None,
         },
     );
 
@@ -228,6 +228,7 @@ mod tests {
             headers: None,
             extra_body: None,
             auth_type: crate::llm::types::AuthType::Bearer,
+            body_transforms: None,
         };
         let _client = VolcengineImageClient::new(config);
     }
@@ -248,6 +249,7 @@ mod tests {
             headers: None,
             extra_body: None,
             auth_type: crate::llm::types::AuthType::Bearer,
+            body_transforms: None,
         };
         let client = VolcengineImageClient::new(config);
 
@@ -276,6 +278,7 @@ mod tests {
             headers: None,
             extra_body: None,
             auth_type: crate::llm::types::AuthType::Bearer,
+            body_transforms: None,
         };
         let client = VolcengineImageClient::new(config);
 
@@ -308,6 +311,7 @@ mod tests {
             headers: None,
             extra_body: None,
             auth_type: crate::llm::types::AuthType::Bearer,
+            body_transforms: None,
         };
         let client = VolcengineImageClient::new(config);
 
@@ -340,6 +344,7 @@ mod tests {
             headers: None,
             extra_body: None,
             auth_type: crate::llm::types::AuthType::Bearer,
+            body_transforms: None,
         };
         let client = VolcengineImageClient::new(config);
 
@@ -188,6 +188,7 @@ mod tests {
             headers: None,
             extra_body: None,
             auth_type: crate::llm::types::AuthType::Bearer,
+            body_transforms: None,
         };
         let _client = ZhipuImageClient::new(config);
     }
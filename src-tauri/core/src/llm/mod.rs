@@ -6,6 +6,7 @@ pub mod models;
 pub mod protocols;
 pub mod providers;
 pub mod streaming;
+pub mod support_bundle;
 pub mod testing;
 pub mod tracing;
 pub mod transcription;
@@ -1,2 +1,3 @@
 pub mod model_registry;
 pub mod model_sync;
+pub mod tokenizer;
@@ -1,12 +1,69 @@
 use crate::llm::auth::api_key_manager::ApiKeyManager;
+use crate::llm::models::tokenizer::default_tokenizer;
 use crate::llm::providers::provider_registry::ProviderRegistry;
-use crate::llm::types::{AvailableModel, CustomProvidersConfiguration, ModelsConfiguration};
-use std::collections::HashMap;
+use crate::llm::types::{
+    AvailabilityReason, AvailableModel, ConfigWarning, ContentPart, CustomProvidersConfiguration,
+    Message, MessageContent, ModelCapabilityFilter, ModelPricing, ModelsConfiguration,
+    ProviderAvailability, ToolDefinition,
+};
+use std::collections::{BTreeMap, HashMap};
 #[cfg(test)]
 use std::sync::Arc;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
 
 pub struct ModelRegistry;
 
+/// Local servers whose loaded models are discovered at runtime
+const LOCAL_PROVIDER_IDS: [&str; 2] = ["ollama", "lmstudio"];
+
+/// How long a local server's discovered model list is reused
+const LOCAL_DISCOVERY_CACHE_TTL: Duration = Duration::from_secs(30);
+
+/// Local servers answer fast or not at all
+const LOCAL_DISCOVERY_TIMEOUT: Duration = Duration::from_secs(2);
+
+type LocalModelCache = Mutex<HashMap<String, (Instant, Vec<AvailableModel>)>>;
+
+fn local_model_cache() -> &'static LocalModelCache {
+    static CACHE: OnceLock<LocalModelCache> = OnceLock::new();
+    CACHE.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Upper bound on edits for fuzzy model-name matching
+const MAX_FUZZY_EDIT_DISTANCE: usize = 3;
+
+/// Flat token cost assumed for each image or video attachment
+const MEDIA_PART_TOKENS: u64 = 1_000;
+
+/// Input tokens counted per output token when blending prices; prompts usually
+/// outweigh completions in agent workloads
+const BLENDED_INPUT_WEIGHT: f64 = 3.0;
+
+/// Weighted per-token price of a model, or `None` when either rate is missing
+fn blended_price(pricing: &ModelPricing) -> Option<f64> {
+    let rate = |value: &str| value.trim().parse::<f64>().ok().filter(|v| v.is_finite());
+    let input = rate(&pricing.input)?;
+    let output = rate(&pricing.output)?;
+    Some((input * BLENDED_INPUT_WEIGHT + output) / (BLENDED_INPUT_WEIGHT + 1.0))
+}
+
+/// Levenshtein distance between two strings, by character
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b_chars: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b_chars.len()).collect();
+    let mut current = vec![0; b_chars.len() + 1];
+    for (i, a_char) in a.chars().enumerate() {
+        current[0] = i + 1;
+        for (j, b_char) in b_chars.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b_chars.len()]
+}
+
 impl ModelRegistry {
     pub async fn load_models_config(
         api_keys: &ApiKeyManager,
@@ -44,6 +101,41 @@ impl ModelRegistry {
         api_keys: &ApiKeyManager,
         registry: &ProviderRegistry,
     ) -> Result<Vec<AvailableModel>, String> {
+        Self::compute_available_models_filtered(
+            api_keys,
+            registry,
+            &ModelCapabilityFilter::default(),
+        )
+        .await
+    }
+
+    /// Available models restricted to those meeting the capability filter.
+    /// Deprecated models are left out unless `include_deprecated` is set.
+    pub async fn compute_available_models_filtered(
+        api_keys: &ApiKeyManager,
+        registry: &ProviderRegistry,
+        filter: &ModelCapabilityFilter,
+    ) -> Result<Vec<AvailableModel>, String> {
+        let (models, available) =
+            Self::compute_available_models_with_config(api_keys, registry).await?;
+        Ok(Self::filter_available_models(available, &models, filter))
+    }
+
+    fn filter_available_models(
+        available: Vec<AvailableModel>,
+        config: &ModelsConfiguration,
+        filter: &ModelCapabilityFilter,
+    ) -> Vec<AvailableModel> {
+        available
+            .into_iter()
+            .filter(|model| filter.matches(model, config.models.get(&model.key)))
+            .collect()
+    }
+
+    async fn compute_available_models_with_config(
+        api_keys: &ApiKeyManager,
+        registry: &ProviderRegistry,
+    ) -> Result<(ModelsConfiguration, Vec<AvailableModel>), String> {
         let models = Self::load_models_config(api_keys).await?;
         log::info!(
             "[ModelRegistry] Loaded {} models from config",
@@ -81,17 +173,164 @@ impl ModelRegistry {
             registered_providers
         );
 
-        let available = Self::compute_available_models_internal(
+        let mut available = Self::compute_available_models_internal(
             &models,
             &api_key_map,
             registry,
             &custom_providers,
         );
+
+        for provider_id in LOCAL_PROVIDER_IDS {
+            let Some(provider) = registry.provider(provider_id) else {
+                continue;
+            };
+            if !Self::provider_available(provider_id, &api_key_map, registry, &custom_providers) {
+                continue;
+            }
+            let base_url = api_keys
+                .get_setting(&format!("base_url_{}", provider_id))
+                .await?
+                .filter(|url| !url.trim().is_empty())
+                .unwrap_or_else(|| provider.base_url.clone());
+            let discovered = Self::discover_local_models(provider_id, &base_url).await;
+            available = Self::merge_discovered_models(available, discovered);
+        }
+
         log::info!(
             "[ModelRegistry] Computed {} available models",
             available.len()
         );
-        Ok(available)
+        Ok((models, available))
+    }
+
+    /// Models currently loaded on a local Ollama or LM Studio server, as synthetic
+    /// `AvailableModel`s. Unreachable servers yield an empty list.
+    pub async fn discover_local_models(provider_id: &str, base_url: &str) -> Vec<AvailableModel> {
+        let cache_key = format!("{}|{}", provider_id, base_url);
+        if let Ok(cache) = local_model_cache().lock() {
+            if let Some((fetched_at, models)) = cache.get(&cache_key) {
+                if fetched_at.elapsed() < LOCAL_DISCOVERY_CACHE_TTL {
+                    return models.clone();
+                }
+            }
+        }
+
+        let model_ids = match Self::fetch_local_model_ids(provider_id, base_url).await {
+            Ok(ids) => ids,
+            Err(e) => {
+                log::debug!(
+                    "[ModelRegistry] Local model discovery for {} at {} failed: {}",
+                    provider_id,
+                    base_url,
+                    e
+                );
+                Vec::new()
+            }
+        };
+
+        let provider_name = crate::llm::providers::provider_configs::builtin_providers()
+            .into_iter()
+            .find(|provider| provider.id == provider_id)
+            .map(|provider| provider.name)
+            .unwrap_or_else(|| provider_id.to_string());
+        let models: Vec<AvailableModel> = model_ids
+            .into_iter()
+            .map(|id| AvailableModel {
+                key: id.clone(),
+                name: id,
+                provider: provider_id.to_string(),
+                provider_name: provider_name.clone(),
+                image_input: false,
+                image_output: false,
+                audio_input: false,
+                video_input: false,
+                input_pricing: None,
+            })
+            .collect();
+
+        if let Ok(mut cache) = local_model_cache().lock() {
+            cache.insert(cache_key, (Instant::now(), models.clone()));
+        }
+        models
+    }
+
+    async fn fetch_local_model_ids(
+        provider_id: &str,
+        base_url: &str,
+    ) -> Result<Vec<String>, String> {
+        let url = Self::local_models_url(provider_id, base_url);
+        let client = reqwest::Client::builder()
+            .timeout(LOCAL_DISCOVERY_TIMEOUT)
+            .build()
+            .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+        let response = client.get(&url).send().await.map_err(|e| e.to_string())?;
+        if !response.status().is_success() {
+            return Err(format!("HTTP {} from {}", response.status().as_u16(), url));
+        }
+        let body: serde_json::Value = response.json().await.map_err(|e| e.to_string())?;
+        if provider_id == "ollama" {
+            Ok(Self::parse_ollama_tags(&body))
+        } else {
+            Ok(Self::parse_openai_models(&body))
+        }
+    }
+
+    /// Ollama lists models at `/api/tags` on the server root; others use `/v1/models`
+    fn local_models_url(provider_id: &str, base_url: &str) -> String {
+        let trimmed = base_url.trim_end_matches('/');
+        let root = trimmed.strip_suffix("/v1").unwrap_or(trimmed);
+        if provider_id == "ollama" {
+            format!("{}/api/tags", root)
+        } else {
+            format!("{}/v1/models", root)
+        }
+    }
+
+    fn parse_ollama_tags(body: &serde_json::Value) -> Vec<String> {
+        body.get("models")
+            .and_then(|models| models.as_array())
+            .map(|models| {
+                models
+                    .iter()
+                    .filter_map(|model| {
+                        model
+                            .get("name")
+                            .or_else(|| model.get("model"))
+                            .and_then(|name| name.as_str())
+                            .map(str::to_string)
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    fn parse_openai_models(body: &serde_json::Value) -> Vec<String> {
+        body.get("data")
+            .and_then(|data| data.as_array())
+            .map(|data| {
+                data.iter()
+                    .filter_map(|model| model.get("id").and_then(|id| id.as_str()))
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Add discovered models the configured list does not already offer for that provider
+    fn merge_discovered_models(
+        mut available: Vec<AvailableModel>,
+        discovered: Vec<AvailableModel>,
+    ) -> Vec<AvailableModel> {
+        for model in discovered {
+            let exists = available
+                .iter()
+                .any(|existing| existing.provider == model.provider && existing.key == model.key);
+            if !exists {
+                available.push(model);
+            }
+        }
+        available.sort_by(|a, b| a.name.cmp(&b.name));
+        available
     }
 
     fn compute_available_models_internal(
@@ -151,6 +390,37 @@ impl ModelRegistry {
         result
     }
 
+    /// The available model meeting `filter` with the lowest blended price.
+    /// Models without usable pricing only win when nothing priced matches.
+    pub fn cheapest_available(
+        filter: &ModelCapabilityFilter,
+        config: &ModelsConfiguration,
+        api_keys: &HashMap<String, String>,
+        registry: &ProviderRegistry,
+        custom_providers: &CustomProvidersConfiguration,
+    ) -> Option<AvailableModel> {
+        let available =
+            Self::compute_available_models_internal(config, api_keys, registry, custom_providers);
+        Self::filter_available_models(available, config, filter)
+            .into_iter()
+            .map(|model| {
+                let price = config
+                    .models
+                    .get(&model.key)
+                    .and_then(|model_cfg| model_cfg.pricing.as_ref())
+                    .and_then(blended_price);
+                (price, model)
+            })
+            // `min_by` keeps the first of equal candidates, so ties stay in name order
+            .min_by(|(a, _), (b, _)| match (a, b) {
+                (Some(a), Some(b)) => a.total_cmp(b),
+                (Some(_), None) => std::cmp::Ordering::Less,
+                (None, Some(_)) => std::cmp::Ordering::Greater,
+                (None, None) => std::cmp::Ordering::Equal,
+            })
+            .map(|(_, model)| model)
+    }
+
     pub fn resolve_provider_model_name(
         model_key: &str,
         provider_id: &str,
@@ -166,6 +436,44 @@ impl ModelRegistry {
         model_key.to_string()
     }
 
+    /// Like `get_model_provider`, also returning the deprecation notice when
+    /// the resolved model is flagged `deprecated`
+    pub fn get_model_provider_with_warning(
+        model_identifier: &str,
+        api_keys: &HashMap<String, String>,
+        registry: &ProviderRegistry,
+        custom_providers: &CustomProvidersConfiguration,
+        config: &ModelsConfiguration,
+    ) -> Result<(String, String, Option<String>), String> {
+        let (model_key, provider_id) = Self::get_model_provider(
+            model_identifier,
+            api_keys,
+            registry,
+            custom_providers,
+            config,
+        )?;
+        let warning = Self::deprecation_warning(&model_key, config);
+        if let Some(ref warning) = warning {
+            log::warn!("[ModelRegistry] {}", warning);
+        }
+        Ok((model_key, provider_id, warning))
+    }
+
+    /// Notice for a deprecated model, using its configured message when present
+    pub fn deprecation_warning(model_key: &str, config: &ModelsConfiguration) -> Option<String> {
+        let model_cfg = config.models.get(model_key)?;
+        if !model_cfg.is_deprecated() {
+            return None;
+        }
+        Some(
+            model_cfg
+                .deprecation_message
+                .clone()
+                .filter(|message| !message.trim().is_empty())
+                .unwrap_or_else(|| format!("Model {} is deprecated", model_key)),
+        )
+    }
+
     pub fn get_model_provider(
         model_identifier: &str,
         api_keys: &HashMap<String, String>,
@@ -191,6 +499,27 @@ impl ModelRegistry {
             ));
         }
 
+        if let Some(model_key) = Self::resolve_model_key(
+            model_identifier,
+            api_keys,
+            registry,
+            custom_providers,
+            config,
+        )? {
+            log::info!(
+                "[ModelRegistry] Resolved model {} to {}",
+                model_identifier,
+                model_key
+            );
+            return Self::get_model_provider(
+                &model_key,
+                api_keys,
+                registry,
+                custom_providers,
+                config,
+            );
+        }
+
         for provider_id in registry.providers().iter().map(|p| p.id.clone()) {
             if Self::provider_available(&provider_id, api_keys, registry, custom_providers) {
                 return Ok((model_identifier.to_string(), provider_id));
@@ -207,12 +536,286 @@ impl ModelRegistry {
         ))
     }
 
+    /// Estimate prompt tokens with the model's tokenizer (four characters per
+    /// token when none matches); media attachments are counted at a flat cost
+    pub fn estimate_input_tokens(
+        messages: &[Message],
+        tools: Option<&[ToolDefinition]>,
+        model: &str,
+    ) -> u64 {
+        let mut text = String::new();
+        let mut media_parts = 0u64;
+        for message in messages {
+            let parts = match message {
+                Message::System { content, .. } => {
+                    text.push_str(content);
+                    continue;
+                }
+                Message::User { content, .. } | Message::Assistant { content, .. } => match content
+                {
+                    MessageContent::Text(content) => {
+                        text.push_str(content);
+                        continue;
+                    }
+                    MessageContent::Parts(parts) => parts,
+                },
+                Message::Tool { content, .. } => content,
+            };
+            for part in parts {
+                match part {
+                    ContentPart::Text { text: part_text }
+                    | ContentPart::Reasoning {
+                        text: part_text, ..
+                    } => text.push_str(part_text),
+                    ContentPart::Image { .. } | ContentPart::Video { .. } => media_parts += 1,
+                    ContentPart::ToolCall {
+                        tool_name, input, ..
+                    } => {
+                        text.push_str(tool_name);
+                        text.push_str(&input.to_string());
+                    }
+                    ContentPart::ToolResult {
+                        tool_name, output, ..
+                    } => {
+                        text.push_str(tool_name);
+                        text.push_str(&output.to_string());
+                    }
+                }
+            }
+        }
+        for tool in tools.unwrap_or_default() {
+            text.push_str(&tool.name);
+            text.push_str(tool.description.as_deref().unwrap_or_default());
+            text.push_str(&tool.parameters.to_string());
+        }
+        default_tokenizer().count_tokens(&text, model) as u64 + media_parts * MEDIA_PART_TOKENS
+    }
+
+    /// Reject requests whose estimated prompt plus `max_tokens` cannot fit in the
+    /// model's context window. Models without a configured `context_length` pass.
+    pub fn check_context_budget(
+        model_key: &str,
+        estimated_input_tokens: u64,
+        max_tokens: Option<i32>,
+        config: &ModelsConfiguration,
+    ) -> Result<(), String> {
+        let Some(context_length) = config
+            .models
+            .get(model_key)
+            .and_then(|model_cfg| model_cfg.context_length)
+        else {
+            return Ok(());
+        };
+        let max_output = max_tokens.unwrap_or(0).max(0) as u64;
+        let required = estimated_input_tokens + max_output;
+        if required > context_length as u64 {
+            return Err(format!(
+                "Request exceeds the context window of model {}: ~{} prompt tokens + {} max output tokens = ~{} tokens, limit is {}",
+                model_key, estimated_input_tokens, max_output, required, context_length
+            ));
+        }
+        Ok(())
+    }
+
+    /// Walk `candidates` in order and return the first `(model_key, provider_id)`
+    /// whose provider is available. Explicit `model@provider` candidates are only
+    /// accepted when that provider is available.
+    pub fn resolve_with_fallback(
+        candidates: &[String],
+        api_keys: &HashMap<String, String>,
+        registry: &ProviderRegistry,
+        custom_providers: &CustomProvidersConfiguration,
+        config: &ModelsConfiguration,
+    ) -> Result<(String, String), String> {
+        let mut failures = Vec::new();
+        for candidate in candidates {
+            let resolved =
+                Self::get_model_provider(candidate, api_keys, registry, custom_providers, config)
+                    .and_then(|(model_key, provider_id)| {
+                        if Self::provider_available(
+                            &provider_id,
+                            api_keys,
+                            registry,
+                            custom_providers,
+                        ) {
+                            Ok((model_key, provider_id))
+                        } else {
+                            Err(format!("Provider {} is not available", provider_id))
+                        }
+                    });
+            match resolved {
+                Ok(resolved) => return Ok(resolved),
+                Err(error) => {
+                    log::debug!(
+                        "[ModelRegistry] Fallback candidate {} unavailable: {}",
+                        candidate,
+                        error
+                    );
+                    failures.push(format!("{}: {}", candidate, error));
+                }
+            }
+        }
+
+        if failures.is_empty() {
+            return Err("No fallback models configured".to_string());
+        }
+        Err(format!(
+            "No available model in fallback chain ({})",
+            failures.join("; ")
+        ))
+    }
+
+    /// Resolve a model name that missed the exact lookup: first by alias, then
+    /// case-insensitively, then by closest edit distance among available models.
+    /// Returns `Ok(None)` when nothing is close enough and an error listing the
+    /// candidates when the match is ambiguous.
+    fn resolve_model_key(
+        model_identifier: &str,
+        api_keys: &HashMap<String, String>,
+        registry: &ProviderRegistry,
+        custom_providers: &CustomProvidersConfiguration,
+        config: &ModelsConfiguration,
+    ) -> Result<Option<String>, String> {
+        let needle = model_identifier.trim().to_lowercase();
+        if needle.is_empty() {
+            return Ok(None);
+        }
+
+        let alias_matches: Vec<&String> = config
+            .models
+            .iter()
+            .filter(|(_, model_cfg)| {
+                model_cfg
+                    .aliases
+                    .iter()
+                    .any(|alias| alias.to_lowercase() == needle)
+            })
+            .map(|(key, _)| key)
+            .collect();
+        if let Some(key) = Self::unambiguous_match(model_identifier, alias_matches)? {
+            return Ok(Some(key));
+        }
+
+        let available: Vec<&String> = config
+            .models
+            .iter()
+            .filter(|(_, model_cfg)| {
+                model_cfg.providers.iter().any(|provider_id| {
+                    Self::provider_available(provider_id, api_keys, registry, custom_providers)
+                })
+            })
+            .map(|(key, _)| key)
+            .collect();
+
+        let case_matches: Vec<&String> = available
+            .iter()
+            .copied()
+            .filter(|key| key.to_lowercase() == needle)
+            .collect();
+        if let Some(key) = Self::unambiguous_match(model_identifier, case_matches)? {
+            return Ok(Some(key));
+        }
+
+        let max_distance = (needle.chars().count() / 4).clamp(1, MAX_FUZZY_EDIT_DISTANCE);
+        let mut best_distance = usize::MAX;
+        let mut closest: Vec<&String> = Vec::new();
+        for key in available {
+            let distance = edit_distance(&needle, &key.to_lowercase());
+            if distance > max_distance {
+                continue;
+            }
+            if distance < best_distance {
+                best_distance = distance;
+                closest.clear();
+            }
+            if distance == best_distance {
+                closest.push(key);
+            }
+        }
+        Self::unambiguous_match(model_identifier, closest)
+    }
+
+    fn unambiguous_match(
+        model_identifier: &str,
+        mut matches: Vec<&String>,
+    ) -> Result<Option<String>, String> {
+        match matches.len() {
+            0 => Ok(None),
+            1 => Ok(Some(matches[0].clone())),
+            _ => {
+                matches.sort();
+                let candidates: Vec<&str> = matches.iter().map(|key| key.as_str()).collect();
+                Err(format!(
+                    "Model {} is ambiguous, did you mean one of: {}",
+                    model_identifier,
+                    candidates.join(", ")
+                ))
+            }
+        }
+    }
+
     fn provider_available(
         provider_id: &str,
         api_keys: &HashMap<String, String>,
         registry: &ProviderRegistry,
         custom_providers: &CustomProvidersConfiguration,
     ) -> bool {
+        Self::provider_availability(
+            provider_id,
+            api_keys,
+            &HashMap::new(),
+            registry,
+            custom_providers,
+        )
+        .available
+    }
+
+    /// Availability of every registered and custom provider, keyed by provider id
+    pub async fn compute_provider_availability(
+        api_keys: &ApiKeyManager,
+        registry: &ProviderRegistry,
+    ) -> Result<HashMap<String, ProviderAvailability>, String> {
+        let custom_providers = api_keys.load_custom_providers().await?;
+        let api_key_map = api_keys.load_api_keys().await?;
+        let oauth_tokens = api_keys.load_oauth_tokens().await?;
+
+        let provider_ids = registry
+            .providers()
+            .iter()
+            .map(|provider| provider.id.clone())
+            .chain(custom_providers.providers.keys().cloned());
+
+        Ok(provider_ids
+            .map(|provider_id| {
+                let availability = Self::provider_availability(
+                    &provider_id,
+                    &api_key_map,
+                    &oauth_tokens,
+                    registry,
+                    &custom_providers,
+                );
+                (provider_id, availability)
+            })
+            .collect())
+    }
+
+    /// Whether a provider can be used and why; `provider_available` is the boolean view
+    fn provider_availability(
+        provider_id: &str,
+        api_keys: &HashMap<String, String>,
+        oauth_tokens: &HashMap<String, String>,
+        registry: &ProviderRegistry,
+        custom_providers: &CustomProvidersConfiguration,
+    ) -> ProviderAvailability {
+        let available = |reason| ProviderAvailability {
+            available: true,
+            reason,
+        };
+        let unavailable = |reason| ProviderAvailability {
+            available: false,
+            reason,
+        };
+
         if let Some(custom) = custom_providers.providers.get(provider_id) {
             let has_key = !custom.api_key.trim().is_empty();
             log::debug!(
@@ -221,7 +824,11 @@ impl ModelRegistry {
                 custom.enabled,
                 has_key
             );
-            return custom.enabled && has_key;
+            return if custom.enabled && has_key {
+                available(AvailabilityReason::HasApiKey)
+            } else {
+                unavailable(AvailabilityReason::MissingCredentials)
+            };
         }
 
         if let Some(provider) = registry.provider(provider_id) {
@@ -243,32 +850,36 @@ impl ModelRegistry {
                         provider_id,
                         enabled
                     );
-                    return enabled;
+                    return if enabled {
+                        available(AvailabilityReason::NoneAuth)
+                    } else {
+                        unavailable(AvailabilityReason::DisabledLocal)
+                    };
                 }
                 log::debug!(
                     "[ModelRegistry] Provider {} is None auth type, always available",
                     provider_id
                 );
-                return true;
+                return available(AvailabilityReason::NoneAuth);
             }
             if provider.auth_type == crate::llm::types::AuthType::TalkCodyJwt {
                 log::debug!(
                     "[ModelRegistry] Provider {} is TalkCody JWT, available without credentials",
                     provider_id
                 );
-                return true;
+                return available(AvailabilityReason::TalkCodyJwt);
             }
             if let Some(value) = api_keys.get(provider_id) {
                 if !value.trim().is_empty() {
                     log::debug!("[ModelRegistry] Provider {} has credentials", provider_id);
-                    return true;
+                    return available(AvailabilityReason::HasApiKey);
                 }
             }
             if provider.supports_oauth {
-                if let Some(token) = api_keys.get(provider_id) {
+                if let Some(token) = oauth_tokens.get(provider_id) {
                     if !token.trim().is_empty() {
                         log::debug!("[ModelRegistry] Provider {} has OAuth token", provider_id);
-                        return true;
+                        return available(AvailabilityReason::HasOAuth);
                     }
                 }
             }
@@ -283,7 +894,67 @@ impl ModelRegistry {
             );
         }
 
-        false
+        unavailable(AvailabilityReason::MissingCredentials)
+    }
+}
+
+impl ModelsConfiguration {
+    /// Report provider references that resolve to nothing and model names that
+    /// collide, in model-key order
+    pub fn validate(
+        &self,
+        registry: &ProviderRegistry,
+        custom_providers: &CustomProvidersConfiguration,
+    ) -> Vec<ConfigWarning> {
+        let known = |provider_id: &str| {
+            registry.provider(provider_id).is_some()
+                || custom_providers.providers.contains_key(provider_id)
+        };
+        let mut keys: Vec<&String> = self.models.keys().collect();
+        keys.sort();
+
+        let mut warnings = Vec::new();
+        for key in &keys {
+            let model_cfg = &self.models[*key];
+            if !model_cfg.providers.iter().any(|id| known(id.as_str())) {
+                warnings.push(ConfigWarning::UnknownProviders {
+                    model: key.to_string(),
+                    providers: model_cfg.providers.clone(),
+                });
+            }
+            if let Some(mappings) = &model_cfg.provider_mappings {
+                let mut unknown: Vec<&String> =
+                    mappings.keys().filter(|id| !known(id.as_str())).collect();
+                unknown.sort();
+                warnings.extend(unknown.into_iter().map(|provider| {
+                    ConfigWarning::UnknownMappingProvider {
+                        model: key.to_string(),
+                        provider: provider.clone(),
+                    }
+                }));
+            }
+        }
+
+        // Lookups fall back to case-insensitive and alias matches, so any name
+        // shared by two models makes resolution ambiguous
+        let mut claimed: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for key in &keys {
+            let names = std::iter::once(key.as_str())
+                .chain(self.models[*key].aliases.iter().map(String::as_str));
+            for name in names {
+                let models = claimed.entry(name.trim().to_lowercase()).or_default();
+                if !models.contains(*key) {
+                    models.push(key.to_string());
+                }
+            }
+        }
+        warnings.extend(
+            claimed
+                .into_iter()
+                .filter(|(_, models)| models.len() > 1)
+                .map(|(key, models)| ConfigWarning::DuplicateModelKey { key, models }),
+        );
+        warnings
     }
 }
 
@@ -337,6 +1008,7 @@ mod tests {
             headers: None,
             extra_body: None,
             auth_type,
+            body_transforms: None,
         }
     }
 
@@ -367,6 +1039,9 @@ mod tests {
                     cache_creation: None,
                 }),
                 context_length: None,
+                aliases: Vec::new(),
+                deprecated: None,
+                deprecation_message: None,
             },
         );
         ModelsConfiguration {
@@ -420,6 +1095,9 @@ mod tests {
                 cache_creation: None,
             }),
             context_length: None,
+            aliases: Vec::new(),
+            deprecated: None,
+            deprecation_message: None,
         };
         let custom_config = ModelsConfiguration {
             version: "custom".to_string(),
@@ -598,6 +1276,190 @@ mod tests {
         assert!(!available.is_empty());
     }
 
+    #[test]
+    fn parse_ollama_tags_lists_model_names() {
+        let body = serde_json::json!({
+            "models": [
+                {"name": "llama3:8b", "model": "llama3:8b", "size": 4661224676u64},
+                {"model": "qwen2.5-coder:7b"},
+                {"size": 1}
+            ]
+        });
+        assert_eq!(
+            ModelRegistry::parse_ollama_tags(&body),
+            vec!["llama3:8b".to_string(), "qwen2.5-coder:7b".to_string()]
+        );
+        assert!(ModelRegistry::parse_ollama_tags(&serde_json::json!({})).is_empty());
+    }
+
+    #[test]
+    fn parse_openai_models_lists_model_ids() {
+        let body = serde_json::json!({
+            "object": "list",
+            "data": [
+                {"id": "qwen2.5-7b-instruct", "object": "model", "owned_by": "organization_owner"},
+                {"id": "text-embedding-nomic-embed-text-v1.5", "object": "model"}
+            ]
+        });
+        assert_eq!(
+            ModelRegistry::parse_openai_models(&body),
+            vec![
+                "qwen2.5-7b-instruct".to_string(),
+                "text-embedding-nomic-embed-text-v1.5".to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn local_models_url_uses_server_root() {
+        assert_eq!(
+            ModelRegistry::local_models_url("ollama", "http://127.0.0.1:11434/v1"),
+            "http://127.0.0.1:11434/api/tags"
+        );
+        assert_eq!(
+            ModelRegistry::local_models_url("lmstudio", "http://127.0.0.1:1234/v1/"),
+            "http://127.0.0.1:1234/v1/models"
+        );
+        assert_eq!(
+            ModelRegistry::local_models_url("lmstudio", "http://localhost:1234"),
+            "http://localhost:1234/v1/models"
+        );
+    }
+
+    #[tokio::test]
+    async fn discover_local_models_returns_empty_when_unreachable() {
+        let models = ModelRegistry::discover_local_models("ollama", "http://127.0.0.1:9/v1").await;
+        assert!(models.is_empty());
+    }
+
+    #[test]
+    fn merge_discovered_models_skips_configured_duplicates() {
+        let model = |key: &str, provider: &str| AvailableModel {
+            key: key.to_string(),
+            name: key.to_string(),
+            provider: provider.to_string(),
+            provider_name: provider.to_string(),
+            image_input: false,
+            image_output: false,
+            audio_input: false,
+            video_input: false,
+            input_pricing: None,
+        };
+        let merged = ModelRegistry::merge_discovered_models(
+            vec![model("llama3", "ollama"), model("gpt-4o", "openai")],
+            vec![model("llama3", "ollama"), model("mistral", "ollama")],
+        );
+        let keys: Vec<_> = merged
+            .iter()
+            .map(|m| format!("{}-{}", m.key, m.provider))
+            .collect();
+        assert_eq!(
+            keys,
+            vec!["gpt-4o-openai", "llama3-ollama", "mistral-ollama"]
+        );
+    }
+
+    #[test]
+    fn provider_availability_reports_reason_for_each_branch() {
+        let mut oauth_provider = provider_config("openai", crate::llm::types::AuthType::Bearer);
+        oauth_provider.supports_oauth = true;
+        let registry = ProviderRegistry::new(vec![
+            provider_config("deepseek", crate::llm::types::AuthType::Bearer),
+            oauth_provider,
+            provider_config("ollama", crate::llm::types::AuthType::None),
+            provider_config("lmstudio", crate::llm::types::AuthType::None),
+            provider_config("local", crate::llm::types::AuthType::None),
+            provider_config("talkcody", crate::llm::types::AuthType::TalkCodyJwt),
+            provider_config("anthropic", crate::llm::types::AuthType::ApiKey),
+        ]);
+        let api_keys = HashMap::from([
+            ("deepseek".to_string(), "key".to_string()),
+            ("ollama".to_string(), "enabled".to_string()),
+        ]);
+        let oauth_tokens = HashMap::from([
+            ("openai".to_string(), "oauth-token".to_string()),
+            ("anthropic".to_string(), "oauth-token".to_string()),
+        ]);
+        let custom_providers = CustomProvidersConfiguration {
+            version: "1".to_string(),
+            providers: HashMap::new(),
+        };
+
+        let check = |provider_id: &str| {
+            ModelRegistry::provider_availability(
+                provider_id,
+                &api_keys,
+                &oauth_tokens,
+                &registry,
+                &custom_providers,
+            )
+        };
+        let expect = |available, reason| ProviderAvailability { available, reason };
+
+        assert_eq!(
+            check("deepseek"),
+            expect(true, AvailabilityReason::HasApiKey)
+        );
+        assert_eq!(check("openai"), expect(true, AvailabilityReason::HasOAuth));
+        assert_eq!(check("ollama"), expect(true, AvailabilityReason::NoneAuth));
+        assert_eq!(check("local"), expect(true, AvailabilityReason::NoneAuth));
+        assert_eq!(
+            check("lmstudio"),
+            expect(false, AvailabilityReason::DisabledLocal)
+        );
+        assert_eq!(
+            check("talkcody"),
+            expect(true, AvailabilityReason::TalkCodyJwt)
+        );
+        // OAuth tokens only count for providers that support OAuth
+        assert_eq!(
+            check("anthropic"),
+            expect(false, AvailabilityReason::MissingCredentials)
+        );
+        assert_eq!(
+            check("unknown"),
+            expect(false, AvailabilityReason::MissingCredentials)
+        );
+    }
+
+    #[test]
+    fn provider_availability_checks_custom_provider_key_and_enabled() {
+        let registry = ProviderRegistry::new(vec![]);
+        let custom = |api_key: &str, enabled| CustomProviderConfig {
+            id: "custom".to_string(),
+            name: "Custom".to_string(),
+            provider_type: CustomProviderType::OpenAiCompatible,
+            base_url: "https://custom".to_string(),
+            api_key: api_key.to_string(),
+            enabled,
+            description: None,
+        };
+        let check = |config: CustomProviderConfig| {
+            let custom_providers = CustomProvidersConfiguration {
+                version: "1".to_string(),
+                providers: HashMap::from([("custom".to_string(), config)]),
+            };
+            ModelRegistry::provider_availability(
+                "custom",
+                &HashMap::new(),
+                &HashMap::new(),
+                &registry,
+                &custom_providers,
+            )
+        };
+
+        assert_eq!(
+            check(custom("custom-key", true)).reason,
+            AvailabilityReason::HasApiKey
+        );
+        assert!(check(custom("custom-key", true)).available);
+        assert_eq!(
+            check(custom("  ", true)).reason,
+            AvailabilityReason::MissingCredentials
+        );
+        assert!(!check(custom("custom-key", false)).available);
+    }
+
     #[test]
     fn get_model_provider_prefers_model_config_providers_over_registry_order() {
         let mut config = build_models_config();
@@ -630,4 +1492,516 @@ mod tests {
         assert_eq!(model, "gpt-4o");
         assert_eq!(provider, "openai");
     }
+
+    fn sonnet_model(name: &str, aliases: &[&str]) -> ModelConfig {
+        ModelConfig {
+            name: name.to_string(),
+            image_input: false,
+            image_output: false,
+            audio_input: false,
+            video_input: false,
+            interleaved: false,
+            providers: vec!["anthropic".to_string()],
+            provider_mappings: None,
+            pricing: None,
+            context_length: None,
+            aliases: aliases.iter().map(|alias| alias.to_string()).collect(),
+            deprecated: None,
+            deprecation_message: None,
+        }
+    }
+
+    fn fuzzy_fixture() -> (
+        ModelsConfiguration,
+        ProviderRegistry,
+        HashMap<String, String>,
+        CustomProvidersConfiguration,
+    ) {
+        let mut config = build_models_config();
+        config.models.get_mut("gpt-4o").unwrap().aliases = vec!["gpt4o".to_string()];
+        config.models.insert(
+            "claude-sonnet-4".to_string(),
+            sonnet_model("Claude Sonnet 4", &[]),
+        );
+        config.models.insert(
+            "claude-sonnet-4-5".to_string(),
+            sonnet_model("Claude Sonnet 4.5", &["sonnet"]),
+        );
+        let registry = ProviderRegistry::new(vec![
+            provider_config("openai", crate::llm::types::AuthType::Bearer),
+            provider_config("anthropic", crate::llm::types::AuthType::Bearer),
+        ]);
+        let api_keys = HashMap::from([
+            ("openai".to_string(), "key".to_string()),
+            ("anthropic".to_string(), "key".to_string()),
+        ]);
+        let custom_providers = CustomProvidersConfiguration {
+            version: "1".to_string(),
+            providers: HashMap::new(),
+        };
+        (config, registry, api_keys, custom_providers)
+    }
+
+    #[test]
+    fn get_model_provider_resolves_alias_and_near_miss() {
+        let (config, registry, api_keys, custom_providers) = fuzzy_fixture();
+        let resolve = |name: &str| {
+            ModelRegistry::get_model_provider(
+                name,
+                &api_keys,
+                &registry,
+                &custom_providers,
+                &config,
+            )
+        };
+
+        assert_eq!(
+            resolve("GPT4o").unwrap(),
+            ("gpt-4o".to_string(), "openai".to_string())
+        );
+        assert_eq!(
+            resolve("sonnet").unwrap(),
+            ("claude-sonnet-4-5".to_string(), "anthropic".to_string())
+        );
+        assert_eq!(
+            resolve("Claude-Sonnet-4").unwrap(),
+            ("claude-sonnet-4".to_string(), "anthropic".to_string())
+        );
+        assert_eq!(
+            resolve("gpt-4p").unwrap(),
+            ("gpt-4o".to_string(), "openai".to_string())
+        );
+    }
+
+    #[test]
+    fn get_model_provider_rejects_ambiguous_fuzzy_match() {
+        let (config, registry, api_keys, custom_providers) = fuzzy_fixture();
+        let err = ModelRegistry::get_model_provider(
+            "claude-sonnet-4-",
+            &api_keys,
+            &registry,
+            &custom_providers,
+            &config,
+        )
+        .unwrap_err();
+        assert!(err.contains("ambiguous"));
+        assert!(err.contains("claude-sonnet-4, claude-sonnet-4-5"));
+    }
+
+    #[test]
+    fn resolve_with_fallback_skips_unavailable_candidates() {
+        let mut config = build_models_config();
+        config.models.get_mut("gpt-4o").unwrap().providers = vec!["openai".to_string()];
+        config.models.insert(
+            "claude-sonnet-4".to_string(),
+            sonnet_model("Claude Sonnet 4", &[]),
+        );
+        let registry = ProviderRegistry::new(vec![
+            provider_config("openai", crate::llm::types::AuthType::Bearer),
+            provider_config("anthropic", crate::llm::types::AuthType::Bearer),
+            provider_config("ollama", crate::llm::types::AuthType::None),
+        ]);
+        let custom_providers = CustomProvidersConfiguration {
+            version: "1".to_string(),
+            providers: HashMap::new(),
+        };
+        let candidates = vec![
+            "gpt-4o".to_string(),
+            "claude-sonnet-4".to_string(),
+            "llama3@ollama".to_string(),
+        ];
+
+        let api_keys = HashMap::from([("ollama".to_string(), "enabled".to_string())]);
+        let resolved = ModelRegistry::resolve_with_fallback(
+            &candidates,
+            &api_keys,
+            &registry,
+            &custom_providers,
+            &config,
+        )
+        .expect("third candidate should resolve");
+        assert_eq!(resolved, ("llama3".to_string(), "ollama".to_string()));
+
+        let err = ModelRegistry::resolve_with_fallback(
+            &candidates,
+            &HashMap::new(),
+            &registry,
+            &custom_providers,
+            &config,
+        )
+        .unwrap_err();
+        assert!(err.contains("gpt-4o"));
+        assert!(err.contains("claude-sonnet-4"));
+        assert!(err.contains("Provider ollama is not available"));
+    }
+
+    #[test]
+    fn capability_filter_excludes_text_only_models() {
+        let mut config = build_models_config();
+        config.models.get_mut("gpt-4o").unwrap().providers = vec!["openai".to_string()];
+        let mut vision = sonnet_model("Claude Sonnet 4", &[]);
+        vision.image_input = true;
+        vision.context_length = Some(200_000);
+        config.models.insert("claude-sonnet-4".to_string(), vision);
+        let registry = ProviderRegistry::new(vec![
+            provider_config("openai", crate::llm::types::AuthType::Bearer),
+            provider_config("anthropic", crate::llm::types::AuthType::Bearer),
+        ]);
+        let api_keys = HashMap::from([
+            ("openai".to_string(), "key".to_string()),
+            ("anthropic".to_string(), "key".to_string()),
+        ]);
+        let custom_providers = CustomProvidersConfiguration {
+            version: "1".to_string(),
+            providers: HashMap::new(),
+        };
+        let available = ModelRegistry::compute_available_models_internal(
+            &config,
+            &api_keys,
+            &registry,
+            &custom_providers,
+        );
+        assert_eq!(available.len(), 2);
+
+        let keys = |filter: ModelCapabilityFilter| -> Vec<String> {
+            ModelRegistry::filter_available_models(available.clone(), &config, &filter)
+                .into_iter()
+                .map(|model| model.key)
+                .collect()
+        };
+        assert_eq!(
+            keys(ModelCapabilityFilter {
+                image_input: true,
+                ..Default::default()
+            }),
+            vec!["claude-sonnet-4".to_string()]
+        );
+        assert_eq!(
+            keys(ModelCapabilityFilter {
+                min_context_length: Some(128_000),
+                ..Default::default()
+            }),
+            vec!["claude-sonnet-4".to_string()]
+        );
+        assert!(keys(ModelCapabilityFilter {
+            audio_input: true,
+            ..Default::default()
+        })
+        .is_empty());
+        assert_eq!(keys(ModelCapabilityFilter::default()).len(), 2);
+    }
+
+    fn priced(model: &mut ModelConfig, input: &str, output: &str) {
+        model.pricing = Some(ModelPricing {
+            input: input.to_string(),
+            output: output.to_string(),
+            cached_input: None,
+            cache_creation: None,
+        });
+    }
+
+    fn cheapest_fixture() -> (
+        ModelsConfiguration,
+        ProviderRegistry,
+        HashMap<String, String>,
+        CustomProvidersConfiguration,
+    ) {
+        let mut config = build_models_config();
+        config.models.get_mut("gpt-4o").unwrap().providers = vec!["openai".to_string()];
+        let mut haiku = sonnet_model("Claude Haiku", &[]);
+        priced(&mut haiku, "0.8", "4");
+        config.models.insert("claude-haiku".to_string(), haiku);
+        let mut sonnet = sonnet_model("Claude Sonnet 4", &[]);
+        sonnet.image_input = true;
+        priced(&mut sonnet, "3", "15");
+        config.models.insert("claude-sonnet-4".to_string(), sonnet);
+        // Cheapest overall, but its provider has no key
+        let mut unavailable = sonnet_model("Free Model", &[]);
+        unavailable.providers = vec!["groq".to_string()];
+        priced(&mut unavailable, "0", "0");
+        config.models.insert("free-model".to_string(), unavailable);
+
+        let registry = ProviderRegistry::new(vec![
+            provider_config("openai", crate::llm::types::AuthType::Bearer),
+            provider_config("anthropic", crate::llm::types::AuthType::Bearer),
+            provider_config("groq", crate::llm::types::AuthType::Bearer),
+        ]);
+        let api_keys = HashMap::from([
+            ("openai".to_string(), "key".to_string()),
+            ("anthropic".to_string(), "key".to_string()),
+        ]);
+        let custom_providers = CustomProvidersConfiguration {
+            version: "1".to_string(),
+            providers: HashMap::new(),
+        };
+        (config, registry, api_keys, custom_providers)
+    }
+
+    #[test]
+    fn cheapest_available_picks_lowest_blended_price() {
+        let (config, registry, api_keys, custom_providers) = cheapest_fixture();
+        // gpt-4o blends to 1.25, haiku to 1.6, sonnet to 6.0
+        let cheapest = ModelRegistry::cheapest_available(
+            &ModelCapabilityFilter::default(),
+            &config,
+            &api_keys,
+            &registry,
+            &custom_providers,
+        )
+        .expect("cheapest model");
+        assert_eq!(cheapest.key, "gpt-4o");
+        assert_eq!(cheapest.provider, "openai");
+    }
+
+    #[test]
+    fn cheapest_available_respects_capability_filter() {
+        let (config, registry, api_keys, custom_providers) = cheapest_fixture();
+        let cheapest = ModelRegistry::cheapest_available(
+            &ModelCapabilityFilter {
+                image_input: true,
+                ..Default::default()
+            },
+            &config,
+            &api_keys,
+            &registry,
+            &custom_providers,
+        )
+        .expect("cheapest vision model");
+        assert_eq!(cheapest.key, "claude-sonnet-4");
+
+        assert!(ModelRegistry::cheapest_available(
+            &ModelCapabilityFilter {
+                audio_input: true,
+                ..Default::default()
+            },
+            &config,
+            &api_keys,
+            &registry,
+            &custom_providers,
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn cheapest_available_sorts_unpriced_models_last() {
+        let (mut config, registry, api_keys, custom_providers) = cheapest_fixture();
+        config.models.get_mut("gpt-4o").unwrap().pricing = None;
+        config
+            .models
+            .get_mut("claude-haiku")
+            .unwrap()
+            .pricing
+            .as_mut()
+            .unwrap()
+            .output = "n/a".to_string();
+        let cheapest = ModelRegistry::cheapest_available(
+            &ModelCapabilityFilter::default(),
+            &config,
+            &api_keys,
+            &registry,
+            &custom_providers,
+        )
+        .expect("cheapest model");
+        assert_eq!(cheapest.key, "claude-sonnet-4");
+    }
+
+    fn deprecated_fixture() -> (
+        ModelsConfiguration,
+        ProviderRegistry,
+        HashMap<String, String>,
+        CustomProvidersConfiguration,
+    ) {
+        let (mut config, registry, api_keys, custom_providers) = cheapest_fixture();
+        let mut retired = sonnet_model("GPT-4 32k", &[]);
+        retired.providers = vec!["openai".to_string()];
+        retired.deprecated = Some(true);
+        retired.deprecation_message = Some("gpt-4-32k was retired; use gpt-4o".to_string());
+        config.models.insert("gpt-4-32k".to_string(), retired);
+        (config, registry, api_keys, custom_providers)
+    }
+
+    #[test]
+    fn deprecated_models_are_hidden_unless_included() {
+        let (config, registry, api_keys, custom_providers) = deprecated_fixture();
+        let available = ModelRegistry::compute_available_models_internal(
+            &config,
+            &api_keys,
+            &registry,
+            &custom_providers,
+        );
+        let keys = |filter: ModelCapabilityFilter| -> Vec<String> {
+            ModelRegistry::filter_available_models(available.clone(), &config, &filter)
+                .into_iter()
+                .map(|model| model.key)
+                .collect()
+        };
+
+        assert!(!keys(ModelCapabilityFilter::default()).contains(&"gpt-4-32k".to_string()));
+        assert!(keys(ModelCapabilityFilter {
+            include_deprecated: true,
+            ..Default::default()
+        })
+        .contains(&"gpt-4-32k".to_string()));
+    }
+
+    #[test]
+    fn deprecated_model_resolves_with_warning_when_requested() {
+        let (config, registry, api_keys, custom_providers) = deprecated_fixture();
+        let (model, provider, warning) = ModelRegistry::get_model_provider_with_warning(
+            "gpt-4-32k",
+            &api_keys,
+            &registry,
+            &custom_providers,
+            &config,
+        )
+        .expect("resolve deprecated model");
+        assert_eq!(model, "gpt-4-32k");
+        assert_eq!(provider, "openai");
+        assert_eq!(
+            warning.as_deref(),
+            Some("gpt-4-32k was retired; use gpt-4o")
+        );
+
+        let (_, _, warning) = ModelRegistry::get_model_provider_with_warning(
+            "gpt-4o",
+            &api_keys,
+            &registry,
+            &custom_providers,
+            &config,
+        )
+        .expect("resolve current model");
+        assert!(warning.is_none());
+    }
+
+    #[test]
+    fn deprecation_warning_falls_back_to_default_message() {
+        let (mut config, ..) = deprecated_fixture();
+        config
+            .models
+            .get_mut("gpt-4-32k")
+            .unwrap()
+            .deprecation_message = None;
+        assert_eq!(
+            ModelRegistry::deprecation_warning("gpt-4-32k", &config).as_deref(),
+            Some("Model gpt-4-32k is deprecated")
+        );
+    }
+
+    #[test]
+    fn check_context_budget_accepts_requests_within_window() {
+        let mut config = build_models_config();
+        config.models.get_mut("gpt-4o").unwrap().context_length = Some(8_000);
+        let messages = vec![Message::User {
+            content: MessageContent::Text("a".repeat(4_000)),
+            provider_options: None,
+        }];
+        // No tiktoken encoding for this model, so the four-chars-per-token heuristic applies
+        let estimated = ModelRegistry::estimate_input_tokens(&messages, None, "claude-sonnet-4");
+        assert_eq!(estimated, 1_000);
+
+        assert!(
+            ModelRegistry::check_context_budget("gpt-4o", estimated, Some(4_096), &config).is_ok()
+        );
+        assert!(ModelRegistry::check_context_budget("gpt-4o", estimated, None, &config).is_ok());
+        // Models without a configured window are never rejected
+        assert!(
+            ModelRegistry::check_context_budget("unknown", 1_000_000, Some(4_096), &config).is_ok()
+        );
+    }
+
+    #[test]
+    fn check_context_budget_rejects_requests_over_window() {
+        let mut config = build_models_config();
+        config.models.get_mut("gpt-4o").unwrap().context_length = Some(8_000);
+
+        let err =
+            ModelRegistry::check_context_budget("gpt-4o", 6_000, Some(4_096), &config).unwrap_err();
+        assert!(err.contains("gpt-4o"));
+        assert!(err.contains("~10096 tokens"));
+        assert!(err.contains("limit is 8000"));
+    }
+
+    #[test]
+    fn edit_distance_counts_single_edits() {
+        assert_eq!(edit_distance("gpt-4o", "gpt-4o"), 0);
+        assert_eq!(edit_distance("gpt4o", "gpt-4o"), 1);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "abc"), 3);
+    }
+
+    fn validation_fixture() -> (
+        ModelsConfiguration,
+        ProviderRegistry,
+        CustomProvidersConfiguration,
+    ) {
+        let registry = ProviderRegistry::new(vec![provider_config(
+            "openai",
+            crate::llm::types::AuthType::Bearer,
+        )]);
+        let custom_providers = CustomProvidersConfiguration {
+            version: "1".to_string(),
+            providers: HashMap::new(),
+        };
+        (build_models_config(), registry, custom_providers)
+    }
+
+    #[test]
+    fn validate_reports_model_with_only_unknown_providers() {
+        let (mut config, registry, custom_providers) = validation_fixture();
+        let mut orphan = sonnet_model("Orphan", &[]);
+        orphan.providers = vec!["acme".to_string(), "nowhere".to_string()];
+        config.models.insert("orphan".to_string(), orphan);
+
+        let warnings = config.validate(&registry, &custom_providers);
+        assert!(warnings.contains(&ConfigWarning::UnknownProviders {
+            model: "orphan".to_string(),
+            providers: vec!["acme".to_string(), "nowhere".to_string()],
+        }));
+        // gpt-4o still has openai, so its unknown ollama/custom entries are tolerated
+        assert!(!warnings.iter().any(|warning| matches!(
+            warning,
+            ConfigWarning::UnknownProviders { model, .. } if model == "gpt-4o"
+        )));
+    }
+
+    #[test]
+    fn validate_reports_mapping_to_unknown_provider() {
+        let (config, registry, custom_providers) = validation_fixture();
+
+        let warnings = config.validate(&registry, &custom_providers);
+        assert_eq!(
+            warnings,
+            vec![ConfigWarning::UnknownMappingProvider {
+                model: "gpt-4o".to_string(),
+                provider: "ollama".to_string(),
+            }]
+        );
+
+        let registry = ProviderRegistry::new(vec![
+            provider_config("openai", crate::llm::types::AuthType::Bearer),
+            provider_config("ollama", crate::llm::types::AuthType::None),
+        ]);
+        assert!(config.validate(&registry, &custom_providers).is_empty());
+    }
+
+    #[test]
+    fn validate_reports_colliding_model_names() {
+        let (mut config, registry, custom_providers) = validation_fixture();
+        let mut upper = sonnet_model("GPT-4o upper", &[]);
+        upper.providers = vec!["openai".to_string()];
+        config.models.insert("GPT-4o".to_string(), upper);
+        let mut aliased = sonnet_model("Mini", &["gpt-4o"]);
+        aliased.providers = vec!["openai".to_string()];
+        config.models.insert("gpt-4o-mini".to_string(), aliased);
+
+        let warnings = config.validate(&registry, &custom_providers);
+        assert!(warnings.contains(&ConfigWarning::DuplicateModelKey {
+            key: "gpt-4o".to_string(),
+            models: vec![
+                "GPT-4o".to_string(),
+                "gpt-4o".to_string(),
+                "gpt-4o-mini".to_string(),
+            ],
+        }));
+    }
 }
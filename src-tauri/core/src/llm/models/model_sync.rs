@@ -1,4 +1,4 @@
-use crate::llm::auth::api_key_manager::ApiKeyManager;
+use crate::llm::auth::api_key_manager::{ApiKeyManager, MODELS_CONFIG_REMOTE_URL_KEY};
 use crate::llm::types::ModelsConfiguration;
 use reqwest::Client;
 use serde::Deserialize;
@@ -190,6 +190,30 @@ pub async fn check_for_updates(
     Ok(true)
 }
 
+/// Refresh from the user-configured remote URL when one is set,
+/// otherwise check the TalkCody API for a newer version
+async fn sync_models(
+    app: &AppHandle,
+    api_keys: &ApiKeyManager,
+    app_data_dir: &Path,
+) -> Result<bool, String> {
+    let remote_url = api_keys
+        .get_setting(MODELS_CONFIG_REMOTE_URL_KEY)
+        .await?
+        .map(|url| url.trim().to_string())
+        .filter(|url| !url.is_empty());
+    let Some(remote_url) = remote_url else {
+        return check_for_updates(app, api_keys, app_data_dir).await;
+    };
+
+    let config = api_keys.refresh_models_from_remote(&remote_url).await?;
+    write_models_cache_file(app_data_dir, &config).await?;
+    if let Err(error) = app.emit("modelsUpdated", ()) {
+        log::warn!("[ModelSync] Failed to emit modelsUpdated event: {}", error);
+    }
+    Ok(true)
+}
+
 pub fn start_background_sync(app: AppHandle, api_keys: ApiKeyManager, app_data_dir: PathBuf) {
     if STARTED.swap(true, Ordering::SeqCst) {
         log::info!("[ModelSync] Background sync already started");
@@ -202,14 +226,14 @@ pub fn start_background_sync(app: AppHandle, api_keys: ApiKeyManager, app_data_d
     );
 
     tauri::async_runtime::spawn(async move {
-        if let Err(error) = check_for_updates(&app, &api_keys, &app_data_dir).await {
+        if let Err(error) = sync_models(&app, &api_keys, &app_data_dir).await {
             log::warn!("[ModelSync] Initial update check failed: {}", error);
         }
 
         let mut interval = tokio::time::interval(CHECK_INTERVAL);
         loop {
             interval.tick().await;
-            if let Err(error) = check_for_updates(&app, &api_keys, &app_data_dir).await {
+            if let Err(error) = sync_models(&app, &api_keys, &app_data_dir).await {
                 log::warn!("[ModelSync] Background update check failed: {}", error);
             }
         }
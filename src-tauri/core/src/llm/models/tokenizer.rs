@@ -0,0 +1,143 @@
+// Prompt and output token counting. OpenAI-family models are counted with their
+// tiktoken encoding; every other model falls back to a characters-per-token ratio.
+
+use std::sync::OnceLock;
+use tiktoken_rs::CoreBPE;
+
+/// Rough characters-per-token ratio used when no tokenizer matches the model
+pub const CHARS_PER_TOKEN: usize = 4;
+
+pub trait Tokenizer: Send + Sync {
+    fn count_tokens(&self, text: &str, model: &str) -> usize;
+}
+
+/// `chars / 4`, rounded up
+#[derive(Debug, Default, Clone, Copy)]
+pub struct HeuristicTokenizer;
+
+impl Tokenizer for HeuristicTokenizer {
+    fn count_tokens(&self, text: &str, _model: &str) -> usize {
+        text.chars().count().div_ceil(CHARS_PER_TOKEN)
+    }
+}
+
+/// tiktoken for models with a known OpenAI encoding, the heuristic otherwise
+#[derive(Debug, Default, Clone, Copy)]
+pub struct TiktokenTokenizer;
+
+impl TiktokenTokenizer {
+    pub fn supports(model: &str) -> bool {
+        encoding_for_model(model).is_some()
+    }
+}
+
+impl Tokenizer for TiktokenTokenizer {
+    fn count_tokens(&self, text: &str, model: &str) -> usize {
+        match encoding_for_model(model) {
+            Some(bpe) => bpe.encode_with_special_tokens(text).len(),
+            None => HeuristicTokenizer.count_tokens(text, model),
+        }
+    }
+}
+
+/// Tokenizer used for context-budget checks and live usage estimates
+pub fn default_tokenizer() -> &'static dyn Tokenizer {
+    &TiktokenTokenizer
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Encoding {
+    O200kBase,
+    Cl100kBase,
+}
+
+/// Encoding for a model name, ignoring any `vendor/` prefix used by routers
+fn encoding_name(model: &str) -> Option<Encoding> {
+    let name = model.rsplit('/').next().unwrap_or(model).to_lowercase();
+    const O200K_PREFIXES: [&str; 9] = [
+        "gpt-4o",
+        "chatgpt-4o",
+        "gpt-4.1",
+        "gpt-4.5",
+        "gpt-5",
+        "gpt-oss",
+        "o1",
+        "o3",
+        "o4",
+    ];
+    const CL100K_PREFIXES: [&str; 4] = [
+        "gpt-4",
+        "gpt-3.5",
+        "text-embedding-3",
+        "text-embedding-ada-002",
+    ];
+    if O200K_PREFIXES.iter().any(|prefix| name.starts_with(prefix)) {
+        Some(Encoding::O200kBase)
+    } else if CL100K_PREFIXES
+        .iter()
+        .any(|prefix| name.starts_with(prefix))
+    {
+        Some(Encoding::Cl100kBase)
+    } else {
+        None
+    }
+}
+
+/// Encoders are expensive to build, so each is loaded once and reused
+fn encoding_for_model(model: &str) -> Option<&'static CoreBPE> {
+    static O200K: OnceLock<Option<CoreBPE>> = OnceLock::new();
+    static CL100K: OnceLock<Option<CoreBPE>> = OnceLock::new();
+
+    let (cell, load): (_, fn() -> anyhow::Result<CoreBPE>) = match encoding_name(model)? {
+        Encoding::O200kBase => (&O200K, tiktoken_rs::o200k_base),
+        Encoding::Cl100kBase => (&CL100K, tiktoken_rs::cl100k_base),
+    };
+    cell.get_or_init(|| {
+        load()
+            .map_err(|e| log::warn!("[Tokenizer] Failed to load encoding for {}: {}", model, e))
+            .ok()
+    })
+    .as_ref()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn selects_encoding_by_model_name() {
+        assert_eq!(encoding_name("gpt-4o-mini"), Some(Encoding::O200kBase));
+        assert_eq!(encoding_name("openai/gpt-5"), Some(Encoding::O200kBase));
+        assert_eq!(encoding_name("o3-mini"), Some(Encoding::O200kBase));
+        assert_eq!(encoding_name("gpt-4-turbo"), Some(Encoding::Cl100kBase));
+        assert_eq!(encoding_name("GPT-3.5-turbo"), Some(Encoding::Cl100kBase));
+        assert_eq!(encoding_name("claude-sonnet-4"), None);
+    }
+
+    #[test]
+    fn tiktoken_counts_known_strings() {
+        let tokenizer = TiktokenTokenizer;
+        assert_eq!(tokenizer.count_tokens("tiktoken is great!", "gpt-4"), 6);
+        assert_eq!(tokenizer.count_tokens("hello world", "gpt-4"), 2);
+        assert_eq!(tokenizer.count_tokens("hello world", "gpt-4o"), 2);
+        assert_eq!(tokenizer.count_tokens("", "gpt-4o"), 0);
+    }
+
+    #[test]
+    fn unknown_models_fall_back_to_heuristic() {
+        let text = "a".repeat(4_001);
+        assert!(!TiktokenTokenizer::supports("claude-sonnet-4"));
+        assert_eq!(
+            TiktokenTokenizer.count_tokens(&text, "claude-sonnet-4"),
+            HeuristicTokenizer.count_tokens(&text, "claude-sonnet-4")
+        );
+        assert_eq!(HeuristicTokenizer.count_tokens(&text, "any"), 1_001);
+    }
+
+    #[test]
+    fn encoders_are_cached() {
+        let first = encoding_for_model("gpt-4o").expect("o200k encoder") as *const CoreBPE;
+        let second = encoding_for_model("gpt-4.1").expect("o200k encoder") as *const CoreBPE;
+        assert_eq!(first, second);
+    }
+}
@@ -1,3 +1,4 @@
+use crate::llm::protocols::request_builder::CLAUDE_DEFAULT_MAX_TOKENS;
 use crate::llm::protocols::{LlmProtocol, ProtocolStreamState, ToolCallAccum};
 use crate::llm::types::{ContentPart, Message, MessageContent, StreamEvent, ToolDefinition};
 use serde_json::{json, Value};
@@ -168,7 +169,7 @@ impl LlmProtocol for ClaudeProtocol {
             "model": model,
             "messages": self.build_messages(messages),
             "stream": true,
-            "max_tokens": max_tokens.unwrap_or(1024)
+            "max_tokens": max_tokens.unwrap_or(CLAUDE_DEFAULT_MAX_TOKENS)
         });
 
         if let Some(system) = system {
@@ -0,0 +1,462 @@
+use crate::llm::protocols::{
+    header_builder::{HeaderBuildContext, ProtocolHeaderBuilder},
+    request_builder::{ProtocolRequestBuilder, RequestBuildContext},
+    stream_parser::{self, ProtocolStreamParser, StreamParseContext, StreamParseState},
+    LlmProtocol, ProtocolStreamState,
+};
+use crate::llm::types::{ContentPart, Message, MessageContent, StreamEvent, ToolDefinition};
+use serde_json::{json, Value};
+use std::collections::HashMap;
+
+/// Cohere chat API (`/v1/chat`): the latest user turn goes in `message`, earlier
+/// turns in `chat_history`, and system prompts in `preamble`
+pub struct CohereProtocol;
+
+impl CohereProtocol {
+    fn content_text(&self, content: &MessageContent) -> String {
+        match content {
+            MessageContent::Text(text) => text.clone(),
+            MessageContent::Parts(parts) => self.parts_text(parts),
+        }
+    }
+
+    fn parts_text(&self, parts: &[ContentPart]) -> String {
+        let mut texts = Vec::new();
+        for part in parts {
+            match part {
+                ContentPart::Text { text } => texts.push(text.clone()),
+                ContentPart::ToolCall {
+                    tool_name, input, ..
+                } => texts.push(format!("[tool call {}]: {}", tool_name, input)),
+                ContentPart::ToolResult {
+                    tool_name, output, ..
+                } => texts.push(format!("[tool result {}]: {}", tool_name, output)),
+                // Images, video and reasoning have no equivalent in the chat history
+                _ => {}
+            }
+        }
+        texts.join("\n")
+    }
+
+    /// Split the history into (preamble, chat_history, message)
+    fn build_messages(&self, messages: &[Message]) -> (Option<String>, Vec<Value>, String) {
+        let mut preamble: Vec<&str> = Vec::new();
+        let mut history: Vec<(&'static str, String)> = Vec::new();
+        for msg in messages {
+            match msg {
+                Message::System { content, .. } => preamble.push(content),
+                Message::User { content, .. } => history.push(("USER", self.content_text(content))),
+                Message::Assistant { content, .. } => {
+                    history.push(("CHATBOT", self.content_text(content)))
+                }
+                Message::Tool { content, .. } => history.push(("USER", self.parts_text(content))),
+            }
+        }
+
+        let message = match history.last() {
+            Some(("USER", _)) => history.pop().map(|(_, text)| text).unwrap_or_default(),
+            _ => String::new(),
+        };
+        let chat_history = history
+            .into_iter()
+            .filter(|(_, text)| !text.is_empty())
+            .map(|(role, text)| json!({ "role": role, "message": text }))
+            .collect();
+        let preamble = (!preamble.is_empty()).then(|| preamble.join("\n\n"));
+        (preamble, chat_history, message)
+    }
+
+    fn map_finish_reason(reason: &str) -> String {
+        match reason {
+            "COMPLETE" => "stop".to_string(),
+            "MAX_TOKENS" => "length".to_string(),
+            other => other.to_ascii_lowercase(),
+        }
+    }
+}
+
+// ============================================================================
+// New Modular Trait Implementations
+// ============================================================================
+
+impl ProtocolRequestBuilder for CohereProtocol {
+    fn build_request(&self, ctx: RequestBuildContext) -> Result<Value, String> {
+        let (preamble, chat_history, message) = self.build_messages(ctx.messages);
+        if message.is_empty() {
+            return Err("Cohere requires the conversation to end with a user message".to_string());
+        }
+        if ctx.tools.is_some_and(|tools| !tools.is_empty()) {
+            log::debug!("[Cohere] Tool definitions are not supported and were omitted");
+        }
+
+        let mut body = json!({
+            "model": ctx.model,
+            "message": message,
+            "chat_history": chat_history,
+            "stream": true
+        });
+
+        if let Some(preamble) = preamble {
+            body["preamble"] = json!(preamble);
+        }
+        if let Some(temperature) = ctx.temperature {
+            body["temperature"] = json!(temperature);
+        }
+        if let Some(max_tokens) = ctx.max_tokens {
+            body["max_tokens"] = json!(max_tokens);
+        }
+        if let Some(top_p) = ctx.top_p {
+            body["p"] = json!(top_p);
+        }
+        if let Some(top_k) = ctx.top_k {
+            body["k"] = json!(top_k);
+        }
+
+        if let Some(extra) = ctx.extra_body {
+            if let (Some(obj), Some(extra_obj)) = (body.as_object_mut(), extra.as_object()) {
+                for (k, v) in extra_obj {
+                    obj.insert(k.to_string(), v.clone());
+                }
+            }
+        }
+
+        Ok(body)
+    }
+}
+
+impl ProtocolStreamParser for CohereProtocol {
+    fn parse_stream_event(
+        &self,
+        ctx: StreamParseContext,
+        state: &mut StreamParseState,
+    ) -> Result<Option<StreamEvent>, String> {
+        let payload: Value = serde_json::from_str(ctx.data).map_err(|e| e.to_string())?;
+        let event_type = payload
+            .get("event_type")
+            .and_then(|v| v.as_str())
+            .or(ctx.event_type)
+            .unwrap_or_default();
+
+        match event_type {
+            "text-generation" => {
+                let text = payload
+                    .get("text")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or_default();
+                if !text.is_empty() {
+                    if !state.text_started {
+                        state.text_started = true;
+                        state.pending_events.push(StreamEvent::TextStart);
+                    }
+                    state.pending_events.push(StreamEvent::TextDelta {
+                        text: text.to_string(),
+                    });
+                }
+            }
+            "stream-end" => {
+                let finish_reason = payload
+                    .get("finish_reason")
+                    .or_else(|| payload.pointer("/response/finish_reason"))
+                    .and_then(|v| v.as_str())
+                    .map(Self::map_finish_reason);
+                state.finish_reason = finish_reason.clone();
+
+                // `tokens` counts the full prompt; `billed_units` only what was charged
+                let meta = payload.pointer("/response/meta");
+                let tokens = meta
+                    .and_then(|meta| meta.get("tokens"))
+                    .or_else(|| meta.and_then(|meta| meta.get("billed_units")));
+                if let Some(tokens) = tokens {
+                    let input_tokens = tokens
+                        .get("input_tokens")
+                        .and_then(|v| v.as_f64())
+                        .unwrap_or(0.0) as i32;
+                    let output_tokens = tokens
+                        .get("output_tokens")
+                        .and_then(|v| v.as_f64())
+                        .unwrap_or(0.0) as i32;
+                    state.pending_events.push(StreamEvent::Usage {
+                        input_tokens,
+                        output_tokens,
+                        total_tokens: Some(input_tokens + output_tokens),
+                        cached_input_tokens: None,
+                        cache_creation_input_tokens: None,
+                    });
+                }
+                state
+                    .pending_events
+                    .push(StreamEvent::Done { finish_reason });
+            }
+            // stream-start, search/citation and tool events carry no text
+            _ => {}
+        }
+
+        if let Some(event) = state.pending_events.first().cloned() {
+            state.pending_events.remove(0);
+            return Ok(Some(event));
+        }
+
+        Ok(None)
+    }
+}
+
+impl ProtocolHeaderBuilder for CohereProtocol {
+    fn build_base_headers(&self, ctx: HeaderBuildContext) -> HashMap<String, String> {
+        let mut headers = HashMap::new();
+        headers.insert("Content-Type".to_string(), "application/json".to_string());
+        if let Some(token) = ctx.oauth_token.or(ctx.api_key) {
+            headers.insert("Authorization".to_string(), format!("Bearer {}", token));
+        }
+        if let Some(extra) = ctx.extra_headers {
+            for (k, v) in extra {
+                headers.insert(k.to_string(), v.to_string());
+            }
+        }
+        headers
+    }
+}
+
+// ============================================================================
+// Legacy Trait Implementation (delegates to modular traits)
+// ============================================================================
+
+impl LlmProtocol for CohereProtocol {
+    fn name(&self) -> &str {
+        "cohere"
+    }
+
+    fn endpoint_path(&self) -> &'static str {
+        "chat"
+    }
+
+    fn build_request(
+        &self,
+        model: &str,
+        messages: &[Message],
+        tools: Option<&[ToolDefinition]>,
+        temperature: Option<f32>,
+        max_tokens: Option<i32>,
+        top_p: Option<f32>,
+        top_k: Option<i32>,
+        provider_options: Option<&Value>,
+        extra_body: Option<&Value>,
+    ) -> Result<Value, String> {
+        let ctx = RequestBuildContext {
+            model,
+            messages,
+            tools,
+            temperature,
+            max_tokens,
+            top_p,
+            top_k,
+            provider_options,
+            extra_body,
+            logprobs: None,
+            top_logprobs: None,
+        };
+        ProtocolRequestBuilder::build_request(self, ctx)
+    }
+
+    fn parse_stream_event(
+        &self,
+        event_type: Option<&str>,
+        data: &str,
+        state: &mut ProtocolStreamState,
+    ) -> Result<Option<StreamEvent>, String> {
+        let ctx = StreamParseContext { event_type, data };
+        let mut new_state = stream_parser::StreamParseState {
+            finish_reason: state.finish_reason.clone(),
+            text_started: state.text_started,
+            pending_events: std::mem::take(&mut state.pending_events),
+            ..Default::default()
+        };
+
+        let result = ProtocolStreamParser::parse_stream_event(self, ctx, &mut new_state);
+
+        // Sync state back
+        state.finish_reason = new_state.finish_reason;
+        state.text_started = new_state.text_started;
+        state.pending_events = new_state.pending_events;
+
+        result
+    }
+
+    fn build_headers(
+        &self,
+        api_key: Option<&str>,
+        oauth_token: Option<&str>,
+        extra_headers: Option<&HashMap<String, String>>,
+    ) -> HashMap<String, String> {
+        let ctx = HeaderBuildContext {
+            api_key,
+            oauth_token,
+            extra_headers,
+        };
+        ProtocolHeaderBuilder::build_base_headers(self, ctx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(data: &str, state: &mut StreamParseState) -> Option<StreamEvent> {
+        ProtocolStreamParser::parse_stream_event(
+            &CohereProtocol,
+            StreamParseContext {
+                event_type: None,
+                data,
+            },
+            state,
+        )
+        .expect("parse event")
+    }
+
+    fn drain(state: &mut StreamParseState) -> Vec<StreamEvent> {
+        std::mem::take(&mut state.pending_events)
+    }
+
+    #[test]
+    fn build_request_maps_messages_to_cohere_format() {
+        let messages = vec![
+            Message::System {
+                content: "Be brief.".to_string(),
+                provider_options: None,
+            },
+            Message::User {
+                content: MessageContent::Text("Hi".to_string()),
+                provider_options: None,
+            },
+            Message::Assistant {
+                content: MessageContent::Parts(vec![ContentPart::Text {
+                    text: "Hello!".to_string(),
+                }]),
+                provider_options: None,
+            },
+            Message::User {
+                content: MessageContent::Text("What is Rust?".to_string()),
+                provider_options: None,
+            },
+        ];
+        let body = ProtocolRequestBuilder::build_request(
+            &CohereProtocol,
+            RequestBuildContext {
+                model: "command-r-plus",
+                messages: &messages,
+                tools: None,
+                temperature: Some(0.3),
+                max_tokens: Some(256),
+                top_p: Some(0.9),
+                top_k: Some(40),
+                provider_options: None,
+                extra_body: None,
+                logprobs: None,
+                top_logprobs: None,
+            },
+        )
+        .expect("build request");
+
+        assert_eq!(body["model"], json!("command-r-plus"));
+        assert_eq!(body["message"], json!("What is Rust?"));
+        assert_eq!(body["preamble"], json!("Be brief."));
+        assert_eq!(
+            body["chat_history"],
+            json!([
+                { "role": "USER", "message": "Hi" },
+                { "role": "CHATBOT", "message": "Hello!" }
+            ])
+        );
+        assert_eq!(body["stream"], json!(true));
+        assert_eq!(body["max_tokens"], json!(256));
+        assert_eq!(body["p"], json!(0.9f32));
+        assert_eq!(body["k"], json!(40));
+    }
+
+    #[test]
+    fn build_request_requires_trailing_user_message() {
+        let messages = vec![Message::Assistant {
+            content: MessageContent::Text("Hello!".to_string()),
+            provider_options: None,
+        }];
+        let result = LlmProtocol::build_request(
+            &CohereProtocol,
+            "command-r",
+            &messages,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+            None,
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn build_headers_uses_bearer_key() {
+        let headers = LlmProtocol::build_headers(&CohereProtocol, Some("co-key"), None, None);
+        assert_eq!(
+            headers.get("Authorization").map(String::as_str),
+            Some("Bearer co-key")
+        );
+    }
+
+    #[test]
+    fn parse_stream_translates_captured_events() {
+        let mut state = StreamParseState::default();
+
+        let start = r#"{"is_finished":false,"event_type":"stream-start","generation_id":"6f3e4c1a-2b7d-4f0e-9a51-8c2d7e5b9f10"}"#;
+        assert!(parse(start, &mut state).is_none());
+
+        let first = r#"{"is_finished":false,"event_type":"text-generation","text":"Rust is"}"#;
+        assert!(matches!(
+            parse(first, &mut state),
+            Some(StreamEvent::TextStart)
+        ));
+        match drain(&mut state).as_slice() {
+            [StreamEvent::TextDelta { text }] => assert_eq!(text, "Rust is"),
+            other => panic!("Unexpected events: {:?}", other),
+        }
+
+        let second =
+            r#"{"is_finished":false,"event_type":"text-generation","text":" a language."}"#;
+        match parse(second, &mut state) {
+            Some(StreamEvent::TextDelta { text }) => assert_eq!(text, " a language."),
+            other => panic!("Unexpected event: {:?}", other),
+        }
+
+        let end = r#"{"is_finished":true,"event_type":"stream-end","response":{"response_id":"d4a1c3b2-0e9f-4a8b-b7c6-5d4e3f2a1b0c","text":"Rust is a language.","generation_id":"6f3e4c1a-2b7d-4f0e-9a51-8c2d7e5b9f10","chat_history":[{"role":"USER","message":"What is Rust?"},{"role":"CHATBOT","message":"Rust is a language."}],"finish_reason":"COMPLETE","meta":{"api_version":{"version":"1"},"billed_units":{"input_tokens":4,"output_tokens":5},"tokens":{"input_tokens":70,"output_tokens":5}}},"finish_reason":"COMPLETE"}"#;
+        match parse(end, &mut state) {
+            Some(StreamEvent::Usage {
+                input_tokens,
+                output_tokens,
+                total_tokens,
+                ..
+            }) => {
+                assert_eq!(input_tokens, 70);
+                assert_eq!(output_tokens, 5);
+                assert_eq!(total_tokens, Some(75));
+            }
+            other => panic!("Unexpected event: {:?}", other),
+        }
+        match drain(&mut state).as_slice() {
+            [StreamEvent::Done { finish_reason }] => {
+                assert_eq!(finish_reason.as_deref(), Some("stop"))
+            }
+            other => panic!("Unexpected events: {:?}", other),
+        }
+        assert_eq!(state.finish_reason.as_deref(), Some("stop"));
+    }
+
+    #[test]
+    fn parse_stream_maps_max_tokens_finish_reason() {
+        let mut state = StreamParseState::default();
+        let end = r#"{"is_finished":true,"event_type":"stream-end","finish_reason":"MAX_TOKENS","response":{"meta":{"billed_units":{"input_tokens":12,"output_tokens":256}}}}"#;
+        match parse(end, &mut state) {
+            Some(StreamEvent::Usage { input_tokens, .. }) => assert_eq!(input_tokens, 12),
+            other => panic!("Unexpected event: {:?}", other),
+        }
+        assert_eq!(state.finish_reason.as_deref(), Some("length"));
+    }
+}
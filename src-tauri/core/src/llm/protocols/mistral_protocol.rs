@@ -0,0 +1,494 @@
+use crate::llm::protocols::{
+    header_builder::{HeaderBuildContext, ProtocolHeaderBuilder},
+    openai_protocol::OpenAiProtocol,
+    request_builder::{ProtocolRequestBuilder, RequestBuildContext},
+    stream_parser::{self, ProtocolStreamParser, StreamParseContext, StreamParseState},
+    LlmProtocol, ProtocolStreamState,
+};
+use crate::llm::types::{Message, StreamEvent, ToolDefinition};
+use serde_json::{json, Value};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+
+/// Length of the tool call ids Mistral accepts (`^[a-zA-Z0-9]{9}$`)
+const MISTRAL_TOOL_CALL_ID_LEN: usize = 9;
+
+const BASE62: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+/// Mistral chat completions: OpenAI-shaped, but it rejects unknown fields,
+/// requires 9-character alphanumeric tool call ids, and streams every tool call
+/// whole without reliable `index` values
+pub struct MistralProtocol;
+
+/// Map a tool call id to the form Mistral accepts. Ids already in that form are
+/// kept; others (e.g. from another provider earlier in the conversation) are
+/// hashed so an assistant call and its tool result still match.
+pub fn mistral_tool_call_id(id: &str) -> String {
+    if id.len() == MISTRAL_TOOL_CALL_ID_LEN && id.bytes().all(|b| b.is_ascii_alphanumeric()) {
+        return id.to_string();
+    }
+    Sha256::digest(id.as_bytes())
+        .iter()
+        .take(MISTRAL_TOOL_CALL_ID_LEN)
+        .map(|byte| BASE62[*byte as usize % BASE62.len()] as char)
+        .collect()
+}
+
+impl MistralProtocol {
+    fn normalize_messages(&self, messages: &mut [Value]) {
+        for message in messages.iter_mut() {
+            if let Some(obj) = message.as_object_mut() {
+                // OpenAI-compatible reasoning replay is an unknown field to Mistral
+                obj.remove("reasoning_content");
+            }
+            let tool_call_id = message
+                .get("tool_call_id")
+                .and_then(|v| v.as_str())
+                .map(mistral_tool_call_id);
+            if let Some(id) = tool_call_id {
+                message["tool_call_id"] = json!(id);
+            }
+            if let Some(calls) = message.get_mut("tool_calls").and_then(|v| v.as_array_mut()) {
+                for call in calls {
+                    let id = call
+                        .get("id")
+                        .and_then(|v| v.as_str())
+                        .map(mistral_tool_call_id);
+                    if let Some(id) = id {
+                        call["id"] = json!(id);
+                    }
+                }
+            }
+        }
+
+        // A trailing assistant turn is only accepted as a prefix to continue from
+        if let Some(last) = messages.last_mut() {
+            if last.get("role").and_then(|v| v.as_str()) == Some("assistant") {
+                last["prefix"] = json!(true);
+            }
+        }
+    }
+
+    /// Give each streamed tool call a stable `index` by arrival order of its id,
+    /// so the OpenAI accumulator keeps every call in `tool_call_order`
+    fn normalize_tool_call_indices(&self, delta: &mut Value, state: &StreamParseState) {
+        let Some(entries) = delta.get_mut("tool_calls").and_then(|v| v.as_array_mut()) else {
+            return;
+        };
+        let mut order = state.tool_call_order.clone();
+        for entry in entries {
+            let id = entry
+                .get("id")
+                .and_then(|v| v.as_str())
+                .filter(|id| !id.is_empty())
+                .map(str::to_string);
+            let index = match id {
+                Some(id) => match order.iter().position(|key| *key == id) {
+                    Some(position) => position,
+                    None => {
+                        order.push(id);
+                        order.len() - 1
+                    }
+                },
+                // A fragment without an id continues the latest call
+                None => order.len().saturating_sub(1),
+            };
+            entry["index"] = json!(index);
+        }
+    }
+
+    /// Flatten Magistral-style content chunks into `content` and `reasoning_content`
+    fn flatten_content_chunks(&self, delta: &mut Value) {
+        let Some(chunks) = delta.get("content").and_then(|v| v.as_array()) else {
+            return;
+        };
+        let mut text = String::new();
+        let mut reasoning = String::new();
+        for chunk in chunks {
+            match chunk.get("type").and_then(|v| v.as_str()) {
+                Some("text") => {
+                    text.push_str(chunk.get("text").and_then(|v| v.as_str()).unwrap_or(""));
+                }
+                Some("thinking") => {
+                    for part in chunk
+                        .get("thinking")
+                        .and_then(|v| v.as_array())
+                        .into_iter()
+                        .flatten()
+                    {
+                        reasoning.push_str(part.get("text").and_then(|v| v.as_str()).unwrap_or(""));
+                    }
+                }
+                _ => {}
+            }
+        }
+        delta["content"] = json!(text);
+        if !reasoning.is_empty() {
+            delta["reasoning_content"] = json!(reasoning);
+        }
+    }
+}
+
+// ============================================================================
+// New Modular Trait Implementations
+// ============================================================================
+
+impl ProtocolRequestBuilder for MistralProtocol {
+    fn build_request(&self, ctx: RequestBuildContext) -> Result<Value, String> {
+        // top_k, logprobs and OpenAI reasoning options are rejected as extra fields
+        let mut body = ProtocolRequestBuilder::build_request(
+            &OpenAiProtocol,
+            RequestBuildContext {
+                top_k: None,
+                provider_options: None,
+                extra_body: None,
+                logprobs: None,
+                top_logprobs: None,
+                ..ctx
+            },
+        )?;
+        if let Some(obj) = body.as_object_mut() {
+            // Mistral reports usage on the final chunk without being asked
+            obj.remove("stream_options");
+        }
+        if let Some(messages) = body.get_mut("messages").and_then(|v| v.as_array_mut()) {
+            self.normalize_messages(messages);
+        }
+
+        if let Some(mistral_opts) = ctx.provider_options.and_then(|opts| opts.get("mistral")) {
+            if let Some(safe_prompt) = mistral_opts.get("safePrompt").and_then(|v| v.as_bool()) {
+                body["safe_prompt"] = json!(safe_prompt);
+            }
+        }
+
+        if let Some(extra) = ctx.extra_body.and_then(|extra| extra.as_object()) {
+            if let Some(obj) = body.as_object_mut() {
+                for (k, v) in extra {
+                    obj.insert(k.to_string(), v.clone());
+                }
+            }
+        }
+
+        Ok(body)
+    }
+}
+
+impl ProtocolStreamParser for MistralProtocol {
+    fn parse_stream_event(
+        &self,
+        ctx: StreamParseContext,
+        state: &mut StreamParseState,
+    ) -> Result<Option<StreamEvent>, String> {
+        if self.is_done_event(ctx.data) {
+            return ProtocolStreamParser::parse_stream_event(&OpenAiProtocol, ctx, state);
+        }
+
+        let mut payload: Value = serde_json::from_str(ctx.data).map_err(|e| e.to_string())?;
+        if let Some(delta) = payload
+            .get_mut("choices")
+            .and_then(|v| v.as_array_mut())
+            .and_then(|choices| choices.first_mut())
+            .and_then(|choice| choice.get_mut("delta"))
+        {
+            self.flatten_content_chunks(delta);
+            self.normalize_tool_call_indices(delta, state);
+        }
+
+        let data = payload.to_string();
+        ProtocolStreamParser::parse_stream_event(
+            &OpenAiProtocol,
+            StreamParseContext {
+                event_type: ctx.event_type,
+                data: &data,
+            },
+            state,
+        )
+    }
+}
+
+impl ProtocolHeaderBuilder for MistralProtocol {
+    fn build_base_headers(&self, ctx: HeaderBuildContext) -> HashMap<String, String> {
+        ProtocolHeaderBuilder::build_base_headers(&OpenAiProtocol, ctx)
+    }
+}
+
+// ============================================================================
+// Legacy Trait Implementation (delegates to modular traits)
+// ============================================================================
+
+impl LlmProtocol for MistralProtocol {
+    fn name(&self) -> &str {
+        "mistral"
+    }
+
+    fn endpoint_path(&self) -> &'static str {
+        "chat/completions"
+    }
+
+    fn build_request(
+        &self,
+        model: &str,
+        messages: &[Message],
+        tools: Option<&[ToolDefinition]>,
+        temperature: Option<f32>,
+        max_tokens: Option<i32>,
+        top_p: Option<f32>,
+        top_k: Option<i32>,
+        provider_options: Option<&Value>,
+        extra_body: Option<&Value>,
+    ) -> Result<Value, String> {
+        let ctx = RequestBuildContext {
+            model,
+            messages,
+            tools,
+            temperature,
+            max_tokens,
+            top_p,
+            top_k,
+            provider_options,
+            extra_body,
+            logprobs: None,
+            top_logprobs: None,
+        };
+        ProtocolRequestBuilder::build_request(self, ctx)
+    }
+
+    fn parse_stream_event(
+        &self,
+        event_type: Option<&str>,
+        data: &str,
+        state: &mut ProtocolStreamState,
+    ) -> Result<Option<StreamEvent>, String> {
+        let ctx = StreamParseContext { event_type, data };
+        let mut new_state = stream_parser::StreamParseState {
+            finish_reason: state.finish_reason.clone(),
+            text_started: state.text_started,
+            reasoning_started: state.reasoning_started,
+            reasoning_id: state.reasoning_id.clone(),
+            pending_events: std::mem::take(&mut state.pending_events),
+            tool_calls: std::mem::take(&mut state.tool_calls),
+            tool_call_order: std::mem::take(&mut state.tool_call_order),
+            emitted_tool_calls: std::mem::take(&mut state.emitted_tool_calls),
+            tool_call_index_map: std::mem::take(&mut state.tool_call_index_map),
+            interleaved: state.interleaved,
+            resume: state.resume,
+            last_event_id: state.last_event_id.clone(),
+            tool_call_deltas: state.tool_call_deltas,
+            ..Default::default()
+        };
+
+        let result = ProtocolStreamParser::parse_stream_event(self, ctx, &mut new_state);
+
+        // Sync state back
+        state.finish_reason = new_state.finish_reason;
+        state.text_started = new_state.text_started;
+        state.reasoning_started = new_state.reasoning_started;
+        state.reasoning_id = new_state.reasoning_id;
+        state.pending_events = new_state.pending_events;
+        state.tool_calls = new_state.tool_calls;
+        state.tool_call_order = new_state.tool_call_order;
+        state.emitted_tool_calls = new_state.emitted_tool_calls;
+        state.tool_call_index_map = new_state.tool_call_index_map;
+
+        result
+    }
+
+    fn build_headers(
+        &self,
+        api_key: Option<&str>,
+        oauth_token: Option<&str>,
+        extra_headers: Option<&HashMap<String, String>>,
+    ) -> HashMap<String, String> {
+        let ctx = HeaderBuildContext {
+            api_key,
+            oauth_token,
+            extra_headers,
+        };
+        ProtocolHeaderBuilder::build_base_headers(self, ctx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::types::{ContentPart, MessageContent};
+
+    fn parse_all(chunks: &[&str]) -> Vec<StreamEvent> {
+        let mut state = StreamParseState::default();
+        let mut events = Vec::new();
+        for data in chunks {
+            let event = ProtocolStreamParser::parse_stream_event(
+                &MistralProtocol,
+                StreamParseContext {
+                    event_type: None,
+                    data,
+                },
+                &mut state,
+            )
+            .expect("parse event");
+            events.extend(event);
+            events.append(&mut state.pending_events);
+        }
+        events
+    }
+
+    fn tool_calls(events: &[StreamEvent]) -> Vec<(String, String, Value)> {
+        events
+            .iter()
+            .filter_map(|event| match event {
+                StreamEvent::ToolCall {
+                    tool_call_id,
+                    tool_name,
+                    input,
+                    ..
+                } => Some((tool_call_id.clone(), tool_name.clone(), input.clone())),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn parse_stream_keeps_order_of_multi_tool_call_response() {
+        // Captured from api.mistral.ai: both calls arrive in one delta, each with index 0
+        let events = parse_all(&[
+            r#"{"id":"cmpl-1","object":"chat.completion.chunk","created":1730000000,"model":"mistral-large-latest","choices":[{"index":0,"delta":{"role":"assistant","content":""},"finish_reason":null}]}"#,
+            r#"{"id":"cmpl-1","object":"chat.completion.chunk","created":1730000000,"model":"mistral-large-latest","choices":[{"index":0,"delta":{"tool_calls":[{"id":"D681PevKs","function":{"name":"read_file","arguments":"{\"path\": \"src/main.rs\"}"},"index":0},{"id":"x8G2kQp1Z","function":{"name":"list_dir","arguments":"{\"path\": \"src\"}"},"index":0}]},"finish_reason":"tool_calls"}],"usage":{"prompt_tokens":120,"total_tokens":162,"completion_tokens":42}}"#,
+            "[DONE]",
+        ]);
+
+        assert_eq!(
+            tool_calls(&events),
+            vec![
+                (
+                    "D681PevKs".to_string(),
+                    "read_file".to_string(),
+                    json!({ "path": "src/main.rs" })
+                ),
+                (
+                    "x8G2kQp1Z".to_string(),
+                    "list_dir".to_string(),
+                    json!({ "path": "src" })
+                ),
+            ]
+        );
+        assert!(events.iter().any(|event| matches!(
+            event,
+            StreamEvent::Usage {
+                input_tokens: 120,
+                output_tokens: 42,
+                ..
+            }
+        )));
+        assert!(matches!(
+            events.last(),
+            Some(StreamEvent::Done { finish_reason }) if finish_reason.as_deref() == Some("tool_calls")
+        ));
+    }
+
+    #[test]
+    fn parse_stream_keeps_order_of_tool_calls_across_chunks() {
+        let events = parse_all(&[
+            r#"{"id":"cmpl-2","choices":[{"index":0,"delta":{"tool_calls":[{"id":"aaaaaaaa1","function":{"name":"first","arguments":"{}"},"index":0}]},"finish_reason":null}]}"#,
+            r#"{"id":"cmpl-2","choices":[{"index":0,"delta":{"tool_calls":[{"id":"bbbbbbbb2","function":{"name":"second","arguments":"{\"n\": 2}"},"index":0}]},"finish_reason":null}]}"#,
+            r#"{"id":"cmpl-2","choices":[{"index":0,"delta":{"content":""},"finish_reason":"tool_calls"}]}"#,
+            "[DONE]",
+        ]);
+
+        let names: Vec<String> = tool_calls(&events)
+            .into_iter()
+            .map(|(_, name, _)| name)
+            .collect();
+        assert_eq!(names, vec!["first".to_string(), "second".to_string()]);
+    }
+
+    #[test]
+    fn parse_stream_flattens_thinking_chunks() {
+        let events = parse_all(&[
+            r#"{"id":"cmpl-3","choices":[{"index":0,"delta":{"content":[{"type":"thinking","thinking":[{"type":"text","text":"Let me check."}]}]},"finish_reason":null}]}"#,
+            r#"{"id":"cmpl-3","choices":[{"index":0,"delta":{"content":"Done."},"finish_reason":"stop"}]}"#,
+            "[DONE]",
+        ]);
+
+        assert!(events.iter().any(
+            |event| matches!(event, StreamEvent::ReasoningDelta { text, .. } if text == "Let me check.")
+        ));
+        assert!(events
+            .iter()
+            .any(|event| matches!(event, StreamEvent::TextDelta { text } if text == "Done.")));
+    }
+
+    #[test]
+    fn build_request_normalizes_tool_call_ids_and_options() {
+        let messages = vec![
+            Message::User {
+                content: MessageContent::Text("read main".to_string()),
+                provider_options: None,
+            },
+            Message::Assistant {
+                content: MessageContent::Parts(vec![ContentPart::ToolCall {
+                    tool_call_id: "call_abc123def456".to_string(),
+                    tool_name: "read_file".to_string(),
+                    input: json!({ "path": "main.rs" }),
+                    provider_metadata: None,
+                }]),
+                provider_options: None,
+            },
+            Message::Tool {
+                content: vec![ContentPart::ToolResult {
+                    tool_call_id: "call_abc123def456".to_string(),
+                    tool_name: "read_file".to_string(),
+                    output: json!({ "type": "text", "value": "fn main() {}" }),
+                }],
+                provider_options: None,
+            },
+            Message::Assistant {
+                content: MessageContent::Text("The file".to_string()),
+                provider_options: None,
+            },
+        ];
+        let provider_options = json!({ "mistral": { "safePrompt": true } });
+        let body = ProtocolRequestBuilder::build_request(
+            &MistralProtocol,
+            RequestBuildContext {
+                model: "mistral-large-latest",
+                messages: &messages,
+                tools: None,
+                temperature: Some(0.3),
+                max_tokens: None,
+                top_p: None,
+                top_k: Some(40),
+                provider_options: Some(&provider_options),
+                extra_body: None,
+                logprobs: Some(true),
+                top_logprobs: Some(2),
+            },
+        )
+        .expect("build request");
+
+        let call_id = body["messages"][1]["tool_calls"][0]["id"]
+            .as_str()
+            .expect("tool call id");
+        assert_eq!(call_id.len(), 9);
+        assert!(call_id.bytes().all(|b| b.is_ascii_alphanumeric()));
+        assert_eq!(body["messages"][2]["tool_call_id"], json!(call_id));
+        assert_eq!(body["messages"][3]["prefix"], json!(true));
+        assert!(body["messages"][0].get("prefix").is_none());
+
+        assert_eq!(body["safe_prompt"], json!(true));
+        assert_eq!(body["temperature"], json!(0.3));
+        for field in ["stream_options", "top_k", "logprobs", "top_logprobs"] {
+            assert!(body.get(field).is_none(), "{} should be omitted", field);
+        }
+    }
+
+    #[test]
+    fn tool_call_ids_already_in_mistral_form_are_kept() {
+        assert_eq!(mistral_tool_call_id("D681PevKs"), "D681PevKs");
+        let mapped = mistral_tool_call_id("toolu_01A09q90qw90lq917835lq9");
+        assert_eq!(
+            mapped,
+            mistral_tool_call_id("toolu_01A09q90qw90lq917835lq9")
+        );
+        assert_ne!(mapped, mistral_tool_call_id("toolu_other"));
+    }
+}
@@ -69,6 +69,157 @@ pub struct ProtocolStreamState {
     pub reasoning_id: Option<String>,
     pub openai_reasoning: HashMap<String, OpenAiReasoningState>,
     pub openai_store: Option<bool>,
+    /// Model emits reasoning and text intermixed (`ModelConfig::interleaved`)
+    pub interleaved: bool,
+    /// Text delivered so far, for resuming after a dropped connection
+    pub resume: ResumeCursor,
+    /// Last SSE `id:` seen, sent back as `Last-Event-ID` when reconnecting
+    pub last_event_id: Option<String>,
+    /// Emit `ToolCallDelta` as arguments arrive (`StreamTextRequest::stream_tool_call_deltas`)
+    pub tool_call_deltas: bool,
+}
+
+impl ProtocolStreamState {
+    pub fn push_reasoning_delta(&mut self, text: &str) {
+        self.delta_ordering().push_reasoning_delta(text);
+    }
+
+    pub fn push_text_delta(&mut self, text: &str) {
+        self.delta_ordering().push_text_delta(text);
+    }
+
+    fn delta_ordering(&mut self) -> DeltaOrdering<'_> {
+        DeltaOrdering {
+            interleaved: self.interleaved,
+            text_started: &mut self.text_started,
+            reasoning_started: &mut self.reasoning_started,
+            reasoning_id: &mut self.reasoning_id,
+            pending_events: &mut self.pending_events,
+        }
+    }
+}
+
+/// Start/end bracketing for reasoning and text deltas, shared by `ProtocolStreamState`
+/// and `StreamParseState`. For interleaved models every switch to text closes the open
+/// reasoning block and the next reasoning delta opens a new one, so blocks keep their
+/// arrival order instead of folding later reasoning into the first block.
+pub(crate) struct DeltaOrdering<'a> {
+    pub interleaved: bool,
+    pub text_started: &'a mut bool,
+    pub reasoning_started: &'a mut bool,
+    pub reasoning_id: &'a mut Option<String>,
+    pub pending_events: &'a mut Vec<StreamEvent>,
+}
+
+impl DeltaOrdering<'_> {
+    pub fn push_reasoning_delta(self, text: &str) {
+        if !*self.reasoning_started {
+            *self.reasoning_started = true;
+            let id = format!("reasoning_{}", uuid::Uuid::new_v4());
+            *self.reasoning_id = Some(id.clone());
+            self.pending_events.push(StreamEvent::ReasoningStart {
+                id,
+                provider_metadata: None,
+            });
+        }
+        if let Some(id) = self.reasoning_id.as_ref() {
+            self.pending_events.push(StreamEvent::ReasoningDelta {
+                id: id.clone(),
+                text: text.to_string(),
+                provider_metadata: None,
+            });
+        }
+    }
+
+    pub fn push_text_delta(self, text: &str) {
+        if self.interleaved && *self.reasoning_started {
+            if let Some(id) = self.reasoning_id.take() {
+                self.pending_events.push(StreamEvent::ReasoningEnd { id });
+            }
+            *self.reasoning_started = false;
+        }
+        if !*self.text_started {
+            *self.text_started = true;
+            self.pending_events.push(StreamEvent::TextStart);
+        }
+        self.pending_events.push(StreamEvent::TextDelta {
+            text: text.to_string(),
+        });
+    }
+}
+
+/// Position in the text already delivered to the client. A reconnected stream is
+/// generated from the start again, so once `begin_resume` is called the replayed
+/// prefix is swallowed and only text past the previous position is emitted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ResumeCursor {
+    /// Characters of text emitted so far
+    pub emitted_text_chars: usize,
+    /// Characters of replayed text still to swallow
+    skip_text_chars: usize,
+    skip_text_start: bool,
+    /// Tool calls cannot be matched up after a replay, so they rule out resuming
+    emitted_tool_call: bool,
+    /// `emitted_text_chars` when the last SSE event id was seen
+    text_chars_at_event_id: usize,
+}
+
+impl ResumeCursor {
+    pub fn can_resume(&self) -> bool {
+        !self.emitted_tool_call
+    }
+
+    pub fn begin_resume(&mut self) {
+        self.skip_text_chars = self.emitted_text_chars;
+        self.skip_text_start = self.emitted_text_chars > 0;
+    }
+
+    /// Everything emitted so far is covered by the latest SSE event id
+    pub fn mark_event_id(&mut self) {
+        self.text_chars_at_event_id = self.emitted_text_chars;
+    }
+
+    /// Resume with `Last-Event-ID`: the server replays only events after that id,
+    /// so just the text emitted since then is swallowed
+    pub fn begin_resume_after_event_id(&mut self) {
+        self.skip_text_chars = self.emitted_text_chars - self.text_chars_at_event_id;
+        self.skip_text_start = self.text_chars_at_event_id == 0 && self.emitted_text_chars > 0;
+    }
+
+    /// Pass an event on its way to the client; `None` when it repeats output
+    /// that was delivered before the reconnect
+    pub fn observe(&mut self, event: StreamEvent) -> Option<StreamEvent> {
+        match event {
+            StreamEvent::TextStart if self.skip_text_start => {
+                self.skip_text_start = false;
+                None
+            }
+            StreamEvent::TextDelta { text } => {
+                let chars = text.chars().count();
+                if self.skip_text_chars > 0 && chars <= self.skip_text_chars {
+                    self.skip_text_chars -= chars;
+                    return None;
+                }
+                let text: String = text.chars().skip(self.skip_text_chars).collect();
+                self.skip_text_chars = 0;
+                self.emitted_text_chars += text.chars().count();
+                Some(StreamEvent::TextDelta { text })
+            }
+            StreamEvent::ReasoningStart { .. }
+            | StreamEvent::ReasoningDelta { .. }
+            | StreamEvent::ReasoningEnd { .. }
+            | StreamEvent::ReasoningSummaryDelta { .. }
+                if self.skip_text_chars > 0 =>
+            {
+                None
+            }
+            StreamEvent::ToolCall { .. } | StreamEvent::ToolCallDelta { .. } => {
+                self.emitted_tool_call = true;
+                Some(event)
+            }
+            other => Some(other),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -82,6 +233,8 @@ pub enum OpenAiReasoningPartStatus {
 pub struct OpenAiReasoningState {
     pub encrypted_content: Option<String>,
     pub summary_parts: HashMap<u64, OpenAiReasoningPartStatus>,
+    /// Summary parts that already received `reasoning_summary_text.delta` text
+    pub streamed_summaries: HashSet<u64>,
 }
 
 #[derive(Default, Clone)]
@@ -93,5 +246,110 @@ pub struct ToolCallAccum {
 }
 
 pub mod claude_protocol;
+pub mod cohere_protocol;
+pub mod mistral_protocol;
 pub mod openai_protocol;
 pub mod openai_responses_protocol;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(value: &str) -> StreamEvent {
+        StreamEvent::TextDelta {
+            text: value.to_string(),
+        }
+    }
+
+    fn collect_text(events: &[StreamEvent]) -> String {
+        events
+            .iter()
+            .filter_map(|event| match event {
+                StreamEvent::TextDelta { text } => Some(text.as_str()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn resume_skips_text_delivered_before_disconnect() {
+        let mut cursor = ResumeCursor::default();
+        let mut delivered: Vec<StreamEvent> = [StreamEvent::TextStart, text("Hel"), text("lo wor")]
+            .into_iter()
+            .filter_map(|event| cursor.observe(event))
+            .collect();
+        assert_eq!(cursor.emitted_text_chars, 9);
+
+        // The connection drops; the re-sent request streams from the start
+        cursor.begin_resume();
+        delivered.extend(
+            [
+                StreamEvent::TextStart,
+                text("Hello"),
+                text(" world!"),
+                text(" Bye."),
+            ]
+            .into_iter()
+            .filter_map(|event| cursor.observe(event)),
+        );
+
+        assert_eq!(collect_text(&delivered), "Hello world! Bye.");
+        assert_eq!(
+            delivered
+                .iter()
+                .filter(|event| matches!(event, StreamEvent::TextStart))
+                .count(),
+            1
+        );
+    }
+
+    #[test]
+    fn resume_swallows_replayed_reasoning_before_text_position() {
+        let mut cursor = ResumeCursor::default();
+        assert!(cursor.observe(text("Answer")).is_some());
+
+        cursor.begin_resume();
+        let reasoning = StreamEvent::ReasoningDelta {
+            id: "r1".to_string(),
+            text: "thinking".to_string(),
+            provider_metadata: None,
+        };
+        assert!(cursor.observe(reasoning.clone()).is_none());
+        assert!(cursor.observe(text("Answer")).is_none());
+        assert!(cursor.observe(reasoning).is_some());
+    }
+
+    #[test]
+    fn resume_after_event_id_skips_only_text_past_the_id() {
+        let mut cursor = ResumeCursor::default();
+        let mut delivered: Vec<StreamEvent> = [StreamEvent::TextStart, text("Hello")]
+            .into_iter()
+            .filter_map(|event| cursor.observe(event))
+            .collect();
+        cursor.mark_event_id();
+        delivered.extend(cursor.observe(text(" wor")));
+
+        // The server resumes after the last id and replays " wor"
+        cursor.begin_resume_after_event_id();
+        delivered.extend(
+            [text(" world"), text("!")]
+                .into_iter()
+                .filter_map(|event| cursor.observe(event)),
+        );
+
+        assert_eq!(collect_text(&delivered), "Hello world!");
+    }
+
+    #[test]
+    fn tool_calls_prevent_resume() {
+        let mut cursor = ResumeCursor::default();
+        assert!(cursor.can_resume());
+        cursor.observe(StreamEvent::ToolCall {
+            tool_call_id: "call_1".to_string(),
+            tool_name: "read_file".to_string(),
+            input: serde_json::json!({}),
+            provider_metadata: None,
+        });
+        assert!(!cursor.can_resume());
+    }
+}
@@ -4,7 +4,9 @@ use crate::llm::protocols::{
     stream_parser::{self, ProtocolStreamParser, StreamParseContext, StreamParseState},
     LlmProtocol, ProtocolStreamState, ToolCallAccum,
 };
-use crate::llm::types::{ContentPart, Message, MessageContent, StreamEvent, ToolDefinition};
+use crate::llm::types::{
+    ContentPart, Message, MessageContent, StreamEvent, TokenLogprob, ToolDefinition, TopLogprob,
+};
 use serde_json::{json, Value};
 use std::collections::HashMap;
 
@@ -233,6 +235,44 @@ impl OpenAiProtocol {
         output.to_string()
     }
 
+    /// Token logprobs from a chunk's `choices[].logprobs.content`
+    fn parse_logprobs(&self, choice: &Value) -> Vec<TokenLogprob> {
+        let Some(content) = choice
+            .get("logprobs")
+            .and_then(|logprobs| logprobs.get("content"))
+            .and_then(|content| content.as_array())
+        else {
+            return Vec::new();
+        };
+        content
+            .iter()
+            .filter_map(|entry| {
+                let token = entry.get("token")?.as_str()?.to_string();
+                let logprob = entry.get("logprob")?.as_f64()?;
+                let top_logprobs = entry
+                    .get("top_logprobs")
+                    .and_then(|v| v.as_array())
+                    .map(|alternatives| {
+                        alternatives
+                            .iter()
+                            .filter_map(|alt| {
+                                Some(TopLogprob {
+                                    token: alt.get("token")?.as_str()?.to_string(),
+                                    logprob: alt.get("logprob")?.as_f64()?,
+                                })
+                            })
+                            .collect()
+                    })
+                    .unwrap_or_default();
+                Some(TokenLogprob {
+                    token,
+                    logprob,
+                    top_logprobs,
+                })
+            })
+            .collect()
+    }
+
     fn build_tools(&self, tools: Option<&[ToolDefinition]>) -> Option<Vec<Value>> {
         let tools = tools?;
         let mut result = Vec::new();
@@ -317,15 +357,25 @@ impl OpenAiProtocol {
             if !name.is_empty() {
                 acc.tool_name = name.to_string();
             }
+            let mut arguments_delta = String::new();
             if let Some(args_val) = args_value {
                 if let Some(args_str) = args_val.as_str() {
                     if !args_str.is_empty() {
                         acc.arguments.push_str(args_str);
+                        arguments_delta = args_str.to_string();
                     }
                 } else if acc.arguments.is_empty() {
                     acc.arguments = args_val.to_string();
+                    arguments_delta = acc.arguments.clone();
                 }
             }
+            if state.tool_call_deltas && !arguments_delta.is_empty() {
+                state.pending_events.push(StreamEvent::ToolCallDelta {
+                    tool_call_id: acc.tool_call_id.clone(),
+                    tool_name: (!acc.tool_name.is_empty()).then(|| acc.tool_name.clone()),
+                    arguments_delta,
+                });
+            }
 
             // Extract thought_signature for Gemini 3 models if present
             if acc.thought_signature.is_none() {
@@ -421,6 +471,12 @@ impl ProtocolRequestBuilder for OpenAiProtocol {
         if let Some(top_k) = ctx.top_k {
             body["top_k"] = json!(top_k);
         }
+        if ctx.logprobs == Some(true) {
+            body["logprobs"] = json!(true);
+            if let Some(top_logprobs) = ctx.top_logprobs {
+                body["top_logprobs"] = json!(top_logprobs);
+            }
+        }
 
         if let Some(options) = ctx.provider_options {
             if let Some(openai_opts) = options.get("openai") {
@@ -528,22 +584,7 @@ impl ProtocolStreamParser for OpenAiProtocol {
 
                 if let Some(reasoning) = reasoning_text {
                     if !reasoning.is_empty() {
-                        if !state.reasoning_started {
-                            state.reasoning_started = true;
-                            state.reasoning_id =
-                                Some(format!("reasoning_{}", uuid::Uuid::new_v4()));
-                            state.pending_events.push(StreamEvent::ReasoningStart {
-                                id: state.reasoning_id.clone().unwrap(),
-                                provider_metadata: None,
-                            });
-                        }
-                        if let Some(ref id) = state.reasoning_id {
-                            state.pending_events.push(StreamEvent::ReasoningDelta {
-                                id: id.clone(),
-                                text: reasoning.to_string(),
-                                provider_metadata: None,
-                            });
-                        }
+                        state.push_reasoning_delta(reasoning);
                     }
                 }
 
@@ -551,19 +592,18 @@ impl ProtocolStreamParser for OpenAiProtocol {
                 // Don't emit it for tool_calls-only or reasoning-only deltas
                 if let Some(content) = delta.get("content").and_then(|v| v.as_str()) {
                     if !content.is_empty() {
-                        if !state.text_started {
-                            state.text_started = true;
-                            state.pending_events.push(StreamEvent::TextStart);
-                        }
-                        state.pending_events.push(StreamEvent::TextDelta {
-                            text: content.to_string(),
-                        });
+                        state.push_text_delta(content);
                     }
                 }
 
                 // Handle tool calls (may come without text content)
                 self.parse_tool_delta(delta, state);
             }
+
+            let tokens = self.parse_logprobs(choice);
+            if !tokens.is_empty() {
+                state.pending_events.push(StreamEvent::Logprobs { tokens });
+            }
         }
 
         if state.finish_reason.as_deref() == Some("tool_calls") {
@@ -640,6 +680,8 @@ impl LlmProtocol for OpenAiProtocol {
             top_k,
             provider_options,
             extra_body,
+            logprobs: None,
+            top_logprobs: None,
         };
         ProtocolRequestBuilder::build_request(self, ctx)
     }
@@ -666,6 +708,10 @@ impl LlmProtocol for OpenAiProtocol {
             current_thinking_id: state.current_thinking_id.clone(),
             openai_reasoning: std::mem::take(&mut state.openai_reasoning),
             openai_store: state.openai_store,
+            interleaved: state.interleaved,
+            resume: state.resume,
+            last_event_id: state.last_event_id.clone(),
+            tool_call_deltas: state.tool_call_deltas,
         };
 
         let result = ProtocolStreamParser::parse_stream_event(self, ctx, &mut new_state);
@@ -1148,6 +1194,101 @@ mod tests {
         assert_eq!(body.get("max_tokens"), Some(&json!(120)));
     }
 
+    #[test]
+    fn build_request_includes_logprobs_when_requested() {
+        let messages = vec![Message::User {
+            content: MessageContent::Text("hi".to_string()),
+            provider_options: None,
+        }];
+        let ctx = RequestBuildContext {
+            model: "gpt-4o",
+            messages: &messages,
+            tools: None,
+            temperature: None,
+            max_tokens: None,
+            top_p: None,
+            top_k: None,
+            provider_options: None,
+            extra_body: None,
+            logprobs: Some(true),
+            top_logprobs: Some(2),
+        };
+        let body = ProtocolRequestBuilder::build_request(&OpenAiProtocol, ctx.clone())
+            .expect("build request");
+        assert_eq!(body.get("logprobs"), Some(&json!(true)));
+        assert_eq!(body.get("top_logprobs"), Some(&json!(2)));
+
+        let body = ProtocolRequestBuilder::build_request(
+            &OpenAiProtocol,
+            RequestBuildContext {
+                logprobs: None,
+                ..ctx
+            },
+        )
+        .expect("build request");
+        assert!(body.get("logprobs").is_none());
+        assert!(body.get("top_logprobs").is_none());
+    }
+
+    #[test]
+    fn parse_stream_emits_logprobs_event() {
+        let protocol = OpenAiProtocol;
+        let mut state = ProtocolStreamState::default();
+        let data = json!({
+            "choices": [{
+                "index": 0,
+                "delta": { "content": "Hello" },
+                "logprobs": {
+                    "content": [{
+                        "token": "Hello",
+                        "logprob": -0.0123,
+                        "bytes": [72, 101, 108, 108, 111],
+                        "top_logprobs": [
+                            { "token": "Hello", "logprob": -0.0123, "bytes": [72, 101, 108, 108, 111] },
+                            { "token": "Hi", "logprob": -4.5, "bytes": [72, 105] }
+                        ]
+                    }]
+                },
+                "finish_reason": null
+            }]
+        });
+
+        let mut events = Vec::new();
+        if let Some(event) =
+            LlmProtocol::parse_stream_event(&protocol, None, &data.to_string(), &mut state)
+                .expect("parse")
+        {
+            events.push(event);
+        }
+        events.append(&mut state.pending_events);
+
+        assert!(matches!(events[0], StreamEvent::TextStart));
+        assert!(matches!(&events[1], StreamEvent::TextDelta { text } if text == "Hello"));
+        match &events[2] {
+            StreamEvent::Logprobs { tokens } => {
+                assert_eq!(
+                    tokens,
+                    &vec![TokenLogprob {
+                        token: "Hello".to_string(),
+                        logprob: -0.0123,
+                        top_logprobs: vec![
+                            TopLogprob {
+                                token: "Hello".to_string(),
+                                logprob: -0.0123,
+                            },
+                            TopLogprob {
+                                token: "Hi".to_string(),
+                                logprob: -4.5,
+                            },
+                        ],
+                    }]
+                );
+            }
+            other => panic!("Unexpected event: {:?}", other),
+        }
+        assert_eq!(events.len(), 3);
+    }
+
     #[test]
     fn build_request_includes_openrouter_reasoning_when_only_openrouter_is_set() {
         let protocol = OpenAiProtocol;
@@ -1231,6 +1372,80 @@ mod tests {
         }
     }
 
+    fn drain_tool_call_chunks(tool_call_deltas: bool) -> Vec<StreamEvent> {
+        let protocol = OpenAiProtocol;
+        let mut state = ProtocolStreamState {
+            tool_call_deltas,
+            ..Default::default()
+        };
+        let chunks = vec![
+            json!({ "choices": [{ "delta": { "tool_calls": [{
+                "index": 0,
+                "id": "call_1",
+                "function": { "name": "readFile", "arguments": "{\"path\":" }
+            }] } }] }),
+            json!({ "choices": [{ "delta": { "tool_calls": [{
+                "index": 0,
+                "function": { "arguments": "\"/tmp/a.rs\"}" }
+            }] } }] }),
+            json!({ "choices": [{ "finish_reason": "tool_calls", "delta": {} }] }),
+        ];
+
+        let mut events = Vec::new();
+        for chunk in &chunks {
+            if let Some(event) =
+                LlmProtocol::parse_stream_event(&protocol, None, &chunk.to_string(), &mut state)
+                    .expect("parse")
+            {
+                events.push(event);
+            }
+            events.append(&mut state.pending_events);
+        }
+        events
+    }
+
+    #[test]
+    fn parse_stream_tool_call_deltas_accumulate_to_final_arguments() {
+        let events = drain_tool_call_chunks(true);
+
+        let mut streamed = String::new();
+        for event in &events {
+            if let StreamEvent::ToolCallDelta {
+                tool_call_id,
+                tool_name,
+                arguments_delta,
+            } = event
+            {
+                assert_eq!(tool_call_id, "call_1");
+                assert_eq!(tool_name.as_deref(), Some("readFile"));
+                streamed.push_str(arguments_delta);
+            }
+        }
+        assert_eq!(streamed, "{\"path\":\"/tmp/a.rs\"}");
+
+        let input = events
+            .iter()
+            .find_map(|event| match event {
+                StreamEvent::ToolCall { input, .. } => Some(input.clone()),
+                _ => None,
+            })
+            .expect("tool call");
+        let streamed: Value = serde_json::from_str(&streamed).expect("valid json");
+        assert_eq!(streamed, input);
+    }
+
+    #[test]
+    fn parse_stream_omits_tool_call_deltas_unless_requested() {
+        let events = drain_tool_call_chunks(false);
+
+        assert!(!events
+            .iter()
+            .any(|event| matches!(event, StreamEvent::ToolCallDelta { .. })));
+        assert!(events
+            .iter()
+            .any(|event| matches!(event, StreamEvent::ToolCall { .. })));
+    }
+
     #[test]
     fn parse_stream_preserves_tool_call_index_order() {
         let protocol = OpenAiProtocol;
@@ -1427,4 +1642,91 @@ mod tests {
             ),
         }
     }
+
+    fn drain_interleaved(interleaved: bool, chunks: &[Value]) -> Vec<StreamEvent> {
+        let protocol = OpenAiProtocol;
+        let mut state = ProtocolStreamState {
+            interleaved,
+            ..Default::default()
+        };
+        let finish = json!({ "choices": [{ "finish_reason": "stop", "delta": {} }] });
+        let mut events = Vec::new();
+        for chunk in chunks
+            .iter()
+            .chain([&finish])
+            .map(Value::to_string)
+            .chain(["[DONE]".to_string()])
+        {
+            if let Some(event) =
+                LlmProtocol::parse_stream_event(&protocol, None, &chunk, &mut state).unwrap()
+            {
+                events.push(event);
+            }
+            events.append(&mut state.pending_events);
+        }
+        events
+    }
+
+    #[test]
+    fn interleaved_reasoning_and_text_keep_arrival_order() {
+        let chunks = vec![
+            json!({ "choices": [{ "delta": { "reasoning_content": "plan" } }] }),
+            json!({ "choices": [{ "delta": { "content": "answer" } }] }),
+            json!({ "choices": [{ "delta": { "reasoning_content": "check" } }] }),
+        ];
+
+        let events = drain_interleaved(true, &chunks);
+        let summary: Vec<String> = events
+            .iter()
+            .map(|event| match event {
+                StreamEvent::ReasoningStart { .. } => "reasoning-start".to_string(),
+                StreamEvent::ReasoningDelta { text, .. } => format!("reasoning:{}", text),
+                StreamEvent::ReasoningEnd { .. } => "reasoning-end".to_string(),
+                StreamEvent::TextStart => "text-start".to_string(),
+                StreamEvent::TextDelta { text } => format!("text:{}", text),
+                StreamEvent::Done { .. } => "done".to_string(),
+                other => panic!("Unexpected event: {:?}", other),
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                "reasoning-start",
+                "reasoning:plan",
+                "reasoning-end",
+                "text-start",
+                "text:answer",
+                "reasoning-start",
+                "reasoning:check",
+                "reasoning-end",
+                "done",
+            ]
+        );
+
+        // Each reasoning block is bracketed by its own id
+        let ids: Vec<&String> = events
+            .iter()
+            .filter_map(|event| match event {
+                StreamEvent::ReasoningStart { id, .. } => Some(id),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(ids.len(), 2);
+        assert_ne!(ids[0], ids[1]);
+    }
+
+    #[test]
+    fn non_interleaved_reasoning_stays_in_one_block() {
+        let chunks = vec![
+            json!({ "choices": [{ "delta": { "reasoning_content": "plan" } }] }),
+            json!({ "choices": [{ "delta": { "content": "answer" } }] }),
+            json!({ "choices": [{ "delta": { "reasoning_content": "check" } }] }),
+        ];
+        let events = drain_interleaved(false, &chunks);
+        let starts = events
+            .iter()
+            .filter(|event| matches!(event, StreamEvent::ReasoningStart { .. }))
+            .count();
+        assert_eq!(starts, 1);
+    }
 }
@@ -4,9 +4,14 @@ use crate::llm::protocols::{
     OpenAiReasoningPartStatus, ProtocolRequestBuilder, ProtocolStreamParser, ProtocolStreamState,
     ToolCallAccum,
 };
+use crate::llm::streaming::provider_error::provider_error_from_value;
 use crate::llm::types::{ContentPart, Message, MessageContent, StreamEvent, ToolDefinition};
 use serde_json::{json, Value};
 
+/// Instructions sent with every Codex request unless overridden in settings
+pub(crate) const CODEX_INSTRUCTIONS: &str =
+    include_str!("../../../../../src/services/codex-instructions.md");
+
 pub struct OpenAiResponsesProtocol;
 
 impl OpenAiResponsesProtocol {
@@ -187,14 +192,12 @@ impl ProtocolRequestBuilder for OpenAiResponsesProtocol {
             }
         }
 
-        let instructions = include_str!("../../../../../src/services/codex-instructions.md");
-
         let mut body = json!({
             "model": Self::normalize_model(ctx.model),
             "input": input_items,
             "store": false,
             "stream": true,
-            "instructions": instructions,
+            "instructions": CODEX_INSTRUCTIONS,
             "text": { "verbosity": "medium" },
             "reasoning": { "effort": "medium", "summary": "auto" },
             "include": ["reasoning.encrypted_content"]
@@ -300,6 +303,10 @@ pub(crate) fn parse_openai_oauth_event(
         reasoning_id: state.reasoning_id.clone(),
         openai_reasoning: std::mem::take(&mut state.openai_reasoning),
         openai_store: state.openai_store,
+        interleaved: state.interleaved,
+        resume: state.resume,
+        last_event_id: state.last_event_id.clone(),
+        tool_call_deltas: state.tool_call_deltas,
     };
 
     let result = parse_openai_oauth_event_legacy(event_type, data, &mut legacy_state);
@@ -508,6 +515,13 @@ pub(crate) fn parse_openai_oauth_event_legacy(
                             }
                         }
                     }
+                    Some("image_generation_call") => {
+                        if let Some(item_id) = item.get("id").and_then(|v| v.as_str()) {
+                            state.pending_events.push(StreamEvent::ImageStart {
+                                id: item_id.to_string(),
+                            });
+                        }
+                    }
                     Some("reasoning") => {
                         let item_id = item
                             .get("id")
@@ -567,15 +581,13 @@ pub(crate) fn parse_openai_oauth_event_legacy(
                                     });
                                 }
 
-                                state.pending_events.push(StreamEvent::ReasoningDelta {
-                                    id: format!("{}:{}", item_id, summary_index),
-                                    text: text.to_string(),
-                                    provider_metadata: Some(serde_json::json!({
-                                        "openai": {
-                                            "itemId": item_id
-                                        }
-                                    })),
-                                });
+                                state
+                                    .pending_events
+                                    .push(StreamEvent::ReasoningSummaryDelta {
+                                        id: format!("{}:{}", item_id, summary_index),
+                                        text: text.to_string(),
+                                    });
+                                active.streamed_summaries.insert(summary_index);
 
                                 let store = state.openai_store.unwrap_or(true);
                                 if store {
@@ -716,15 +728,46 @@ pub(crate) fn parse_openai_oauth_event_legacy(
                 .unwrap_or(0);
             let delta = payload.get("delta").and_then(|v| v.as_str()).unwrap_or("");
             if !delta.is_empty() {
-                state.pending_events.push(StreamEvent::ReasoningDelta {
-                    id: format!("{}:{}", item_id, summary_index),
-                    text: delta.to_string(),
-                    provider_metadata: Some(serde_json::json!({
-                        "openai": {
-                            "itemId": item_id
-                        }
-                    })),
-                });
+                state
+                    .openai_reasoning
+                    .entry(item_id.clone())
+                    .or_default()
+                    .streamed_summaries
+                    .insert(summary_index);
+                state
+                    .pending_events
+                    .push(StreamEvent::ReasoningSummaryDelta {
+                        id: format!("{}:{}", item_id, summary_index),
+                        text: delta.to_string(),
+                    });
+            }
+        }
+        "response.reasoning_summary_text.done" => {
+            log::debug!("[OpenAI OAuth] Reasoning summary text done: {:?}", payload);
+            let item_id = payload
+                .get("item_id")
+                .and_then(|v| v.as_str())
+                .unwrap_or("reasoning")
+                .to_string();
+            let summary_index = payload
+                .get("summary_index")
+                .and_then(|v| v.as_u64())
+                .unwrap_or(0);
+            let text = payload.get("text").and_then(|v| v.as_str()).unwrap_or("");
+            // The final text repeats the deltas; only surface it when none were streamed
+            let first_text = state
+                .openai_reasoning
+                .entry(item_id.clone())
+                .or_default()
+                .streamed_summaries
+                .insert(summary_index);
+            if first_text && !text.is_empty() {
+                state
+                    .pending_events
+                    .push(StreamEvent::ReasoningSummaryDelta {
+                        id: format!("{}:{}", item_id, summary_index),
+                        text: text.to_string(),
+                    });
             }
         }
         "response.reasoning_summary_part.done" => {
@@ -755,9 +798,34 @@ pub(crate) fn parse_openai_oauth_event_legacy(
                 }
             }
         }
+        "response.image_generation_call.partial_image" => {
+            let item_id = payload.get("item_id").and_then(|v| v.as_str());
+            let b64 = payload.get("partial_image_b64").and_then(|v| v.as_str());
+            if let (Some(item_id), Some(b64)) = (item_id, b64) {
+                state.pending_events.push(StreamEvent::ImageDelta {
+                    id: item_id.to_string(),
+                    b64: b64.to_string(),
+                    mime: openai_image_mime(&payload),
+                });
+            }
+        }
         "response.output_item.done" => {
             log::debug!("[OpenAI OAuth] Output item done: {:?}", payload);
             if let Some(item) = payload.get("item") {
+                if item.get("type").and_then(|v| v.as_str()) == Some("image_generation_call") {
+                    if let Some(item_id) = item.get("id").and_then(|v| v.as_str()) {
+                        if let Some(b64) = item.get("result").and_then(|v| v.as_str()) {
+                            state.pending_events.push(StreamEvent::ImageDelta {
+                                id: item_id.to_string(),
+                                b64: b64.to_string(),
+                                mime: openai_image_mime(item),
+                            });
+                        }
+                        state.pending_events.push(StreamEvent::ImageEnd {
+                            id: item_id.to_string(),
+                        });
+                    }
+                }
                 if item.get("type").and_then(|v| v.as_str()) == Some("reasoning") {
                     let item_id = item
                         .get("id")
@@ -1008,15 +1076,17 @@ pub(crate) fn parse_openai_oauth_event_legacy(
             });
         }
         "response.failed" => {
-            let message = payload
-                .get("response")
-                .and_then(|r| r.get("error"))
-                .and_then(|e| e.get("message"))
-                .and_then(|v| v.as_str())
-                .unwrap_or("Response failed")
-                .to_string();
+            let provider_error = payload.get("response").and_then(provider_error_from_value);
+            let message = provider_error
+                .as_ref()
+                .map(|e| e.message.clone())
+                .unwrap_or_else(|| "Response failed".to_string());
             log::error!("[OpenAI OAuth] Response failed: {}", message);
-            state.pending_events.push(StreamEvent::Error { message });
+            state.pending_events.push(StreamEvent::Error {
+                message,
+                provider_error,
+                kind: None,
+            });
         }
         _ => {
             log::debug!("[OpenAI OAuth] Unknown event type: {}", event_type);
@@ -1031,6 +1101,18 @@ pub(crate) fn parse_openai_oauth_event_legacy(
     Ok(None)
 }
 
+/// MIME type of a generated image from its `output_format`, PNG when absent
+fn openai_image_mime(value: &Value) -> String {
+    let format = value
+        .get("output_format")
+        .and_then(|v| v.as_str())
+        .unwrap_or("png");
+    match format {
+        "jpg" => "image/jpeg".to_string(),
+        other => format!("image/{}", other),
+    }
+}
+
 fn parse_openai_oauth_function_call_delta(payload: &Value, state: &mut ProtocolStreamState) {
     let item_id = payload
         .get("item_id")
@@ -1052,6 +1134,13 @@ fn parse_openai_oauth_function_call_delta(payload: &Value, state: &mut ProtocolS
         });
     if !delta.is_empty() {
         acc.arguments.push_str(delta);
+        if state.tool_call_deltas {
+            state.pending_events.push(StreamEvent::ToolCallDelta {
+                tool_call_id: acc.tool_call_id.clone(),
+                tool_name: (!acc.tool_name.is_empty()).then(|| acc.tool_name.clone()),
+                arguments_delta: delta.to_string(),
+            });
+        }
     }
     let index = payload
         .get("index")
@@ -1184,6 +1273,8 @@ impl LlmProtocol for OpenAiResponsesProtocol {
             top_k,
             provider_options,
             extra_body,
+            logprobs: None,
+            top_logprobs: None,
         };
         ProtocolRequestBuilder::build_request(self, ctx)
     }
@@ -1210,6 +1301,10 @@ impl LlmProtocol for OpenAiResponsesProtocol {
             current_thinking_id: state.current_thinking_id.clone(),
             openai_reasoning: std::mem::take(&mut state.openai_reasoning),
             openai_store: state.openai_store,
+            interleaved: state.interleaved,
+            resume: state.resume,
+            last_event_id: state.last_event_id.clone(),
+            tool_call_deltas: state.tool_call_deltas,
         };
 
         let result = ProtocolStreamParser::parse_stream_event(self, ctx, &mut new_state);
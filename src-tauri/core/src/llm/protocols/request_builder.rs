@@ -1,7 +1,8 @@
 // Protocol-level request building trait
 // Handles conversion from internal message types to provider-specific API format
-use crate::llm::types::{Message, ToolDefinition};
-use serde_json::Value;
+use crate::llm::types::{ContentPart, Message, MessageContent, ProtocolType, ToolDefinition};
+use serde_json::{json, Value};
+use std::borrow::Cow;
 
 /// Context for building a request
 #[derive(Debug, Clone)]
@@ -15,6 +16,8 @@ pub struct RequestBuildContext<'a> {
     pub top_k: Option<i32>,
     pub provider_options: Option<&'a Value>,
     pub extra_body: Option<&'a Value>,
+    pub logprobs: Option<bool>,
+    pub top_logprobs: Option<u8>,
 }
 
 /// Trait for building protocol-specific requests
@@ -37,3 +40,310 @@ pub trait ProtocolMessageConverter: Send + Sync {
     /// Build system message (if supported by protocol)
     fn build_system_message(&self, content: &str) -> Option<Value>;
 }
+
+/// A top-level body field the upstream API rejects requests without,
+/// together with the value supplied when the caller left it unset
+#[derive(Debug, Clone, PartialEq)]
+pub struct RequiredRequestField {
+    pub name: &'static str,
+    pub default: Value,
+}
+
+/// Default max_tokens for Anthropic Messages API, which requires the field
+pub const CLAUDE_DEFAULT_MAX_TOKENS: i32 = 1024;
+
+/// Fields each protocol requires in the request body
+pub fn required_request_fields(protocol: ProtocolType) -> Vec<RequiredRequestField> {
+    match protocol {
+        ProtocolType::Claude => vec![RequiredRequestField {
+            name: "max_tokens",
+            default: json!(CLAUDE_DEFAULT_MAX_TOKENS),
+        }],
+        ProtocolType::OpenAiCompatible | ProtocolType::Cohere | ProtocolType::Mistral => Vec::new(),
+    }
+}
+
+/// Fill in defaults for required fields that are missing (or null) in the body.
+/// Fields that are already set are left untouched.
+pub fn apply_required_field_defaults(body: &mut Value, fields: &[RequiredRequestField]) {
+    let Some(obj) = body.as_object_mut() else {
+        return;
+    };
+    for field in fields {
+        let missing = obj.get(field.name).map(Value::is_null).unwrap_or(true);
+        if missing {
+            log::debug!(
+                "Request body missing required field '{}', using default {}",
+                field.name,
+                field.default
+            );
+            obj.insert(field.name.to_string(), field.default.clone());
+        }
+    }
+}
+
+/// Ordering rules a provider enforces on the message history
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MessageRoleConstraints {
+    /// Adjacent messages with the same role are merged into one
+    pub merge_consecutive_roles: bool,
+    /// All system content is merged into a single message at position zero
+    pub leading_system_only: bool,
+}
+
+/// Role constraints for a protocol, refined by known provider restrictions
+pub fn message_role_constraints(
+    protocol: ProtocolType,
+    provider_id: &str,
+) -> MessageRoleConstraints {
+    match (protocol, provider_id) {
+        // Anthropic rejects histories that do not alternate user/assistant turns,
+        // and only the first system message becomes the top-level `system` field
+        (ProtocolType::Claude, _) => MessageRoleConstraints {
+            merge_consecutive_roles: true,
+            leading_system_only: true,
+        },
+        // deepseek-reasoner rejects successive user or assistant messages
+        (ProtocolType::OpenAiCompatible, "deepseek") => MessageRoleConstraints {
+            merge_consecutive_roles: true,
+            leading_system_only: false,
+        },
+        // MiniMax only accepts a system message as the first entry
+        (ProtocolType::OpenAiCompatible, "MiniMax") => MessageRoleConstraints {
+            merge_consecutive_roles: false,
+            leading_system_only: true,
+        },
+        (ProtocolType::OpenAiCompatible, _)
+        | (ProtocolType::Cohere, _)
+        | (ProtocolType::Mistral, _) => MessageRoleConstraints::default(),
+    }
+}
+
+/// Rewrite the history to satisfy the constraints.
+/// Borrows the input unchanged when no rule applies.
+pub fn normalize_message_roles(
+    messages: &[Message],
+    constraints: MessageRoleConstraints,
+) -> Cow<'_, [Message]> {
+    if constraints == MessageRoleConstraints::default() {
+        return Cow::Borrowed(messages);
+    }
+
+    let mut normalized: Vec<Message> = Vec::with_capacity(messages.len());
+    if constraints.leading_system_only {
+        let mut system: Option<Message> = None;
+        for message in messages {
+            match (message, system.as_mut()) {
+                (Message::System { .. }, None) => system = Some(message.clone()),
+                (
+                    Message::System { content, .. },
+                    Some(Message::System {
+                        content: merged, ..
+                    }),
+                ) => {
+                    merged.push_str("\n\n");
+                    merged.push_str(content);
+                }
+                _ => normalized.push(message.clone()),
+            }
+        }
+        if let Some(system) = system {
+            normalized.insert(0, system);
+        }
+    } else {
+        normalized.extend(messages.iter().cloned());
+    }
+
+    if constraints.merge_consecutive_roles {
+        let mut merged: Vec<Message> = Vec::with_capacity(normalized.len());
+        for message in normalized {
+            match merged.last_mut() {
+                Some(previous) if merge_same_role(previous, &message) => {}
+                _ => merged.push(message),
+            }
+        }
+        normalized = merged;
+    }
+
+    Cow::Owned(normalized)
+}
+
+/// Append `next` into `previous` when both have the same role
+fn merge_same_role(previous: &mut Message, next: &Message) -> bool {
+    match (previous, next) {
+        (Message::System { content, .. }, Message::System { content: next, .. }) => {
+            content.push_str("\n\n");
+            content.push_str(next);
+            true
+        }
+        (Message::User { content, .. }, Message::User { content: next, .. })
+        | (Message::Assistant { content, .. }, Message::Assistant { content: next, .. }) => {
+            merge_content(content, next);
+            true
+        }
+        (Message::Tool { content, .. }, Message::Tool { content: next, .. }) => {
+            content.extend(next.iter().cloned());
+            true
+        }
+        _ => false,
+    }
+}
+
+fn merge_content(content: &mut MessageContent, next: &MessageContent) {
+    match (&mut *content, next) {
+        (MessageContent::Text(text), MessageContent::Text(next)) => {
+            text.push_str("\n\n");
+            text.push_str(next);
+        }
+        _ => {
+            let mut parts = content_parts(content);
+            parts.extend(content_parts(next));
+            *content = MessageContent::Parts(parts);
+        }
+    }
+}
+
+fn content_parts(content: &MessageContent) -> Vec<ContentPart> {
+    match content {
+        MessageContent::Text(text) => vec![ContentPart::Text { text: text.clone() }],
+        MessageContent::Parts(parts) => parts.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user(text: &str) -> Message {
+        Message::User {
+            content: MessageContent::Text(text.to_string()),
+            provider_options: None,
+        }
+    }
+
+    fn assistant(text: &str) -> Message {
+        Message::Assistant {
+            content: MessageContent::Text(text.to_string()),
+            provider_options: None,
+        }
+    }
+
+    fn system(text: &str) -> Message {
+        Message::System {
+            content: text.to_string(),
+            provider_options: None,
+        }
+    }
+
+    fn roles_and_text(messages: &[Message]) -> Vec<(String, String)> {
+        messages
+            .iter()
+            .map(|message| {
+                let value = serde_json::to_value(message).unwrap();
+                (
+                    value["role"].as_str().unwrap().to_string(),
+                    value["content"].to_string(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn consecutive_user_messages_are_merged_for_constrained_provider() {
+        let messages = vec![
+            system("rules"),
+            user("first"),
+            user("second"),
+            assistant("reply"),
+            Message::User {
+                content: MessageContent::Parts(vec![ContentPart::Image {
+                    image: "data:image/png;base64,AA".to_string(),
+                }]),
+                provider_options: None,
+            },
+            user("caption"),
+        ];
+        let constraints = message_role_constraints(ProtocolType::OpenAiCompatible, "deepseek");
+        let normalized = normalize_message_roles(&messages, constraints);
+
+        assert_eq!(normalized.len(), 4);
+        assert_eq!(
+            roles_and_text(&normalized)[1],
+            ("user".to_string(), json!("first\n\nsecond").to_string())
+        );
+        match &normalized[3] {
+            Message::User {
+                content: MessageContent::Parts(parts),
+                ..
+            } => {
+                assert_eq!(parts.len(), 2);
+                assert!(matches!(&parts[1], ContentPart::Text { text } if text == "caption"));
+            }
+            other => panic!("unexpected message: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn system_messages_are_moved_to_front_for_constrained_provider() {
+        let messages = vec![
+            user("hello"),
+            system("be brief"),
+            assistant("hi"),
+            system("use markdown"),
+        ];
+        let constraints = message_role_constraints(ProtocolType::OpenAiCompatible, "MiniMax");
+        let normalized = normalize_message_roles(&messages, constraints);
+
+        let roles: Vec<String> = roles_and_text(&normalized)
+            .into_iter()
+            .map(|(role, _)| role)
+            .collect();
+        assert_eq!(roles, vec!["system", "user", "assistant"]);
+        assert!(matches!(
+            &normalized[0],
+            Message::System { content, .. } if content == "be brief\n\nuse markdown"
+        ));
+    }
+
+    #[test]
+    fn unconstrained_provider_history_is_borrowed_unchanged() {
+        let messages = vec![user("a"), user("b"), system("late")];
+        let constraints = message_role_constraints(ProtocolType::OpenAiCompatible, "openai");
+        let normalized = normalize_message_roles(&messages, constraints);
+        assert!(matches!(normalized, Cow::Borrowed(_)));
+        assert_eq!(normalized.len(), 3);
+    }
+
+    #[test]
+    fn claude_missing_max_tokens_is_defaulted() {
+        let mut body = json!({ "model": "claude-3", "messages": [] });
+        apply_required_field_defaults(&mut body, &required_request_fields(ProtocolType::Claude));
+        assert_eq!(body["max_tokens"], json!(CLAUDE_DEFAULT_MAX_TOKENS));
+    }
+
+    #[test]
+    fn claude_null_max_tokens_is_defaulted() {
+        let mut body = json!({ "model": "claude-3", "max_tokens": null });
+        apply_required_field_defaults(&mut body, &required_request_fields(ProtocolType::Claude));
+        assert_eq!(body["max_tokens"], json!(CLAUDE_DEFAULT_MAX_TOKENS));
+    }
+
+    #[test]
+    fn claude_explicit_max_tokens_is_preserved() {
+        let mut body = json!({ "model": "claude-3", "max_tokens": 4096 });
+        apply_required_field_defaults(&mut body, &required_request_fields(ProtocolType::Claude));
+        assert_eq!(body["max_tokens"], json!(4096));
+    }
+
+    #[test]
+    fn openai_compatible_body_is_left_alone() {
+        let mut body = json!({ "model": "gpt-4o", "messages": [] });
+        let before = body.clone();
+        apply_required_field_defaults(
+            &mut body,
+            &required_request_fields(ProtocolType::OpenAiCompatible),
+        );
+        assert_eq!(body, before);
+        assert!(body.get("max_tokens").is_none());
+    }
+}
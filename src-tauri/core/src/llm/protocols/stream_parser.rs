@@ -22,6 +22,14 @@ pub struct StreamParseState {
     // OpenAI Responses reasoning summary tracking
     pub openai_reasoning: std::collections::HashMap<String, super::OpenAiReasoningState>,
     pub openai_store: Option<bool>,
+    /// Model emits reasoning and text intermixed (`ModelConfig::interleaved`)
+    pub interleaved: bool,
+    /// Text delivered so far, for resuming after a dropped connection
+    pub resume: super::ResumeCursor,
+    /// Last SSE `id:` seen, sent back as `Last-Event-ID` when reconnecting
+    pub last_event_id: Option<String>,
+    /// Emit `ToolCallDelta` as arguments arrive (`StreamTextRequest::stream_tool_call_deltas`)
+    pub tool_call_deltas: bool,
 }
 
 impl StreamParseState {
@@ -29,6 +37,24 @@ impl StreamParseState {
     pub fn new() -> Self {
         Self::default()
     }
+
+    pub fn push_reasoning_delta(&mut self, text: &str) {
+        self.delta_ordering().push_reasoning_delta(text);
+    }
+
+    pub fn push_text_delta(&mut self, text: &str) {
+        self.delta_ordering().push_text_delta(text);
+    }
+
+    fn delta_ordering(&mut self) -> super::DeltaOrdering<'_> {
+        super::DeltaOrdering {
+            interleaved: self.interleaved,
+            text_started: &mut self.text_started,
+            reasoning_started: &mut self.reasoning_started,
+            reasoning_id: &mut self.reasoning_id,
+            pending_events: &mut self.pending_events,
+        }
+    }
 }
 
 /// Context for parsing a stream event
@@ -0,0 +1,218 @@
+// Provider body transforms. Self-hosted gateways sometimes need a field
+// renamed or a parameter injected that `extra_body` (a plain merge) cannot
+// express; `ProviderConfig::body_transforms` lists such edits as JSON-pointer
+// rules applied to the built request body.
+
+use crate::llm::types::BodyTransform;
+use serde_json::{Map, Value};
+
+/// Apply `transforms` to `body` in order
+pub fn apply_body_transforms(body: &mut Value, transforms: &[BodyTransform]) -> Result<(), String> {
+    for transform in transforms {
+        match transform {
+            BodyTransform::SetPath { path, value } => set_path(body, path, value.clone())?,
+            BodyTransform::RemovePath { path } => {
+                remove_path(body, path)?;
+            }
+            BodyTransform::RenamePath { from, to } => {
+                if let Some(value) = remove_path(body, from)? {
+                    set_path(body, to, value)?;
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Split a JSON pointer into unescaped tokens; the root pointer is rejected
+/// since replacing the whole body is never what a rule means
+fn pointer_tokens(path: &str) -> Result<Vec<String>, String> {
+    let rest = path
+        .strip_prefix('/')
+        .ok_or_else(|| format!("Body transform path must start with '/': '{}'", path))?;
+    Ok(rest
+        .split('/')
+        .map(|token| token.replace("~1", "/").replace("~0", "~"))
+        .collect())
+}
+
+fn set_path(body: &mut Value, path: &str, value: Value) -> Result<(), String> {
+    let tokens = pointer_tokens(path)?;
+    let (last, parents) = tokens.split_last().expect("pointer has a token");
+    let mut current = body;
+    for token in parents {
+        current = match current {
+            Value::Object(map) => map
+                .entry(token.clone())
+                .or_insert_with(|| Value::Object(Map::new())),
+            Value::Array(items) => array_index(items, token, path)?,
+            _ => {
+                return Err(format!(
+                    "Body transform path '{}' crosses a non-container",
+                    path
+                ))
+            }
+        };
+    }
+    match current {
+        Value::Object(map) => {
+            map.insert(last.clone(), value);
+        }
+        Value::Array(items) => *array_index(items, last, path)? = value,
+        _ => {
+            return Err(format!(
+                "Body transform path '{}' crosses a non-container",
+                path
+            ))
+        }
+    }
+    Ok(())
+}
+
+/// Remove the value at `path`, returning it; a missing path is not an error
+fn remove_path(body: &mut Value, path: &str) -> Result<Option<Value>, String> {
+    let tokens = pointer_tokens(path)?;
+    let (last, parents) = tokens.split_last().expect("pointer has a token");
+    let mut current = body;
+    for token in parents {
+        current = match current {
+            Value::Object(map) => match map.get_mut(token) {
+                Some(value) => value,
+                None => return Ok(None),
+            },
+            Value::Array(items) => match token.parse::<usize>().ok().filter(|i| *i < items.len()) {
+                Some(index) => &mut items[index],
+                None => return Ok(None),
+            },
+            _ => return Ok(None),
+        };
+    }
+    Ok(match current {
+        Value::Object(map) => map.remove(last),
+        Value::Array(items) => match last.parse::<usize>().ok().filter(|i| *i < items.len()) {
+            Some(index) => Some(items.remove(index)),
+            None => None,
+        },
+        _ => None,
+    })
+}
+
+fn array_index<'a>(
+    items: &'a mut [Value],
+    token: &str,
+    path: &str,
+) -> Result<&'a mut Value, String> {
+    let len = items.len();
+    token
+        .parse::<usize>()
+        .ok()
+        .and_then(|index| items.get_mut(index))
+        .ok_or_else(|| {
+            format!(
+                "Body transform path '{}' indexes past the end of an array of {}",
+                path, len
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::protocols::openai_protocol::OpenAiProtocol;
+    use crate::llm::protocols::request_builder::{ProtocolRequestBuilder, RequestBuildContext};
+    use crate::llm::types::{Message, MessageContent};
+    use serde_json::json;
+
+    fn rename(from: &str, to: &str) -> BodyTransform {
+        BodyTransform::RenamePath {
+            from: from.to_string(),
+            to: to.to_string(),
+        }
+    }
+
+    #[test]
+    fn rename_path_moves_max_tokens_to_max_completion_tokens() {
+        let messages = vec![Message::User {
+            content: MessageContent::Text("hi".to_string()),
+            provider_options: None,
+        }];
+        let mut body = OpenAiProtocol
+            .build_request(RequestBuildContext {
+                model: "gpt-4o",
+                messages: &messages,
+                tools: None,
+                temperature: None,
+                max_tokens: Some(512),
+                top_p: None,
+                top_k: None,
+                provider_options: None,
+                extra_body: None,
+                logprobs: None,
+                top_logprobs: None,
+            })
+            .expect("request body");
+        assert_eq!(body["max_tokens"], 512);
+
+        apply_body_transforms(
+            &mut body,
+            &[rename("/max_tokens", "/max_completion_tokens")],
+        )
+        .expect("transform");
+
+        assert!(body.get("max_tokens").is_none());
+        assert_eq!(body["max_completion_tokens"], 512);
+        assert_eq!(body["model"], "gpt-4o");
+    }
+
+    #[test]
+    fn set_and_remove_paths() {
+        let mut body = json!({ "model": "m", "stream_options": { "include_usage": true } });
+        let transforms = vec![
+            BodyTransform::SetPath {
+                path: "/options/num_ctx".to_string(),
+                value: json!(8192),
+            },
+            BodyTransform::RemovePath {
+                path: "/stream_options/include_usage".to_string(),
+            },
+            BodyTransform::RemovePath {
+                path: "/not/there".to_string(),
+            },
+            rename("/missing", "/elsewhere"),
+        ];
+
+        apply_body_transforms(&mut body, &transforms).expect("transform");
+
+        assert_eq!(
+            body,
+            json!({ "model": "m", "stream_options": {}, "options": { "num_ctx": 8192 } })
+        );
+    }
+
+    #[test]
+    fn invalid_paths_are_rejected() {
+        let mut body = json!({ "model": "m" });
+        let set = |path: &str| BodyTransform::SetPath {
+            path: path.to_string(),
+            value: json!(1),
+        };
+
+        assert!(apply_body_transforms(&mut body, &[set("model")]).is_err());
+        assert!(apply_body_transforms(&mut body, &[set("/model/inner")]).is_err());
+        assert_eq!(body, json!({ "model": "m" }));
+    }
+
+    #[test]
+    fn transforms_deserialize_from_config() {
+        let transforms: Vec<BodyTransform> = serde_json::from_value(json!([
+            { "op": "rename_path", "from": "/max_tokens", "to": "/max_completion_tokens" },
+            { "op": "set_path", "path": "/a~1b", "value": true },
+            { "op": "remove_path", "path": "/top_k" }
+        ]))
+        .expect("transforms");
+
+        let mut body = json!({ "max_tokens": 5, "top_k": 3 });
+        apply_body_transforms(&mut body, &transforms).expect("transform");
+        assert_eq!(body, json!({ "max_completion_tokens": 5, "a/b": true }));
+    }
+}
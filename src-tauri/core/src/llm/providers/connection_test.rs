@@ -0,0 +1,264 @@
+// Connection check for a single provider: resolve credentials and base URL the same
+// way a completion would, then issue a lightweight models-list GET.
+
+use crate::llm::auth::api_key_manager::ApiKeyManager;
+use crate::llm::providers::provider::{normalize_provider_base_url, ProviderContext};
+use crate::llm::providers::provider_registry::ProviderRegistry;
+use serde::Serialize;
+use std::time::{Duration, Instant};
+
+const CONNECTION_TEST_TIMEOUT: Duration = Duration::from_secs(15);
+
+/// Why a provider connection check failed
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum ProviderTestError {
+    /// Provider is unknown or its credentials could not be resolved
+    Config { message: String },
+    /// The provider rejected the credentials (401/403)
+    Auth { status: u16, message: String },
+    /// The endpoint does not exist at the resolved base URL (404)
+    NotFound { message: String },
+    /// Any other non-success HTTP status
+    Http { status: u16, message: String },
+    /// The request never got a response (DNS, TLS, refused, timeout)
+    Network { message: String },
+}
+
+impl ProviderTestError {
+    pub fn message(&self) -> &str {
+        match self {
+            Self::Config { message }
+            | Self::Auth { message, .. }
+            | Self::NotFound { message }
+            | Self::Http { message, .. }
+            | Self::Network { message } => message,
+        }
+    }
+
+    fn from_status(status: u16, body: &str) -> Self {
+        let message = if body.trim().is_empty() {
+            format!("HTTP {}", status)
+        } else {
+            format!("HTTP {}: {}", status, body.trim())
+        };
+        match status {
+            401 | 403 => Self::Auth { status, message },
+            404 => Self::NotFound { message },
+            _ => Self::Http { status, message },
+        }
+    }
+}
+
+/// Probe `provider_id` and return the round-trip latency in milliseconds
+pub async fn test_provider_connection(
+    registry: &ProviderRegistry,
+    api_keys: &ApiKeyManager,
+    provider_id: &str,
+) -> Result<u64, ProviderTestError> {
+    let provider =
+        registry
+            .create_provider(provider_id)
+            .ok_or_else(|| ProviderTestError::Config {
+                message: format!("Provider not found: {}", provider_id),
+            })?;
+    let provider_config = provider.config();
+    let ctx = ProviderContext {
+        provider_config,
+        api_key_manager: api_keys,
+        model: "",
+        messages: &[],
+        tools: None,
+        temperature: None,
+        max_tokens: None,
+        top_p: None,
+        top_k: None,
+        provider_options: None,
+        trace_context: None,
+        logprobs: None,
+        top_logprobs: None,
+    };
+
+    let config_error = |message: String| ProviderTestError::Config { message };
+    let base_url = provider
+        .resolve_base_url(&ctx)
+        .await
+        .map_err(config_error)?;
+    let credentials = provider
+        .get_credentials(api_keys)
+        .await
+        .map_err(config_error)?;
+    let headers = provider
+        .build_headers(&ctx, &credentials)
+        .await
+        .map_err(config_error)?;
+    let url = format!(
+        "{}/models",
+        normalize_provider_base_url(&base_url, provider_config).trim_end_matches('/')
+    );
+
+    let client = reqwest::Client::builder()
+        .timeout(CONNECTION_TEST_TIMEOUT)
+        .build()
+        .map_err(|e| ProviderTestError::Network {
+            message: format!("Failed to create HTTP client: {}", e),
+        })?;
+    let mut request = client.get(&url);
+    for (key, value) in &headers {
+        request = request.header(key, value);
+    }
+
+    let started_at = Instant::now();
+    let response = request
+        .send()
+        .await
+        .map_err(|e| ProviderTestError::Network {
+            message: e.to_string(),
+        })?;
+    let latency_ms = started_at.elapsed().as_millis() as u64;
+
+    let status = response.status();
+    if status.is_success() {
+        log::info!(
+            "[Provider Test] {} reachable at {} in {}ms",
+            provider_id,
+            url,
+            latency_ms
+        );
+        return Ok(latency_ms);
+    }
+
+    let body = response.text().await.unwrap_or_default();
+    log::warn!(
+        "[Provider Test] {} returned HTTP {} from {}",
+        provider_id,
+        status.as_u16(),
+        url
+    );
+    Err(ProviderTestError::from_status(status.as_u16(), &body))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::Database;
+    use crate::llm::types::{AuthType, ProtocolType, ProviderConfig};
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    async fn setup() -> (TempDir, ApiKeyManager) {
+        let dir = TempDir::new().expect("temp dir");
+        let db_path = dir.path().join("provider-test.db");
+        let db = Arc::new(Database::new(db_path.to_string_lossy().to_string()));
+        db.connect().await.expect("db connect");
+        db.execute(
+            "CREATE TABLE IF NOT EXISTS settings (key TEXT PRIMARY KEY, value TEXT, updated_at INTEGER)",
+            vec![],
+        )
+        .await
+        .expect("create settings");
+        let api_keys = ApiKeyManager::new(db, std::path::PathBuf::from("/tmp"));
+        api_keys
+            .set_setting("api_key_mock-provider", "test-key")
+            .await
+            .expect("set api key");
+        (dir, api_keys)
+    }
+
+    fn registry_for(base_url: String) -> ProviderRegistry {
+        ProviderRegistry::new(vec![ProviderConfig {
+            id: "mock-provider".to_string(),
+            name: "Mock".to_string(),
+            protocol: ProtocolType::OpenAiCompatible,
+            base_url,
+            api_key_name: "MOCK_PROVIDER_API_KEY".to_string(),
+            supports_oauth: false,
+            supports_coding_plan: false,
+            supports_international: false,
+            coding_plan_base_url: None,
+            international_base_url: None,
+            headers: None,
+            extra_body: None,
+            auth_type: AuthType::Bearer,
+            body_transforms: None,
+        }])
+    }
+
+    /// Answer one request with `status`, capturing the request path and auth header
+    fn serve_once(status: u16) -> (String, std::thread::JoinHandle<(String, Option<String>)>) {
+        let server = tiny_http::Server::http("127.0.0.1:0").expect("server");
+        let url = match server.server_addr() {
+            tiny_http::ListenAddr::IP(addr) => format!("http://{}/v1", addr),
+            _ => panic!("Expected IP SocketAddr"),
+        };
+        let handle = std::thread::spawn(move || {
+            let request = server.recv().expect("request");
+            let path = request.url().to_string();
+            let auth = request
+                .headers()
+                .iter()
+                .find(|header| header.field.equiv("Authorization"))
+                .map(|header| header.value.to_string());
+            let _ = request.respond(
+                tiny_http::Response::from_string("{\"data\":[]}").with_status_code(status),
+            );
+            (path, auth)
+        });
+        (url, handle)
+    }
+
+    #[tokio::test]
+    async fn reports_latency_when_provider_accepts_credentials() {
+        let (_dir, api_keys) = setup().await;
+        let (url, server) = serve_once(200);
+
+        let result = test_provider_connection(&registry_for(url), &api_keys, "mock-provider").await;
+        let (path, auth) = server.join().expect("server join");
+
+        assert!(result.is_ok(), "unexpected error: {:?}", result);
+        assert_eq!(path, "/v1/models");
+        assert_eq!(auth.as_deref(), Some("Bearer test-key"));
+    }
+
+    #[tokio::test]
+    async fn classifies_unauthorized_as_auth_failure() {
+        let (_dir, api_keys) = setup().await;
+        let (url, server) = serve_once(401);
+
+        let result = test_provider_connection(&registry_for(url), &api_keys, "mock-provider").await;
+        server.join().expect("server join");
+
+        match result {
+            Err(ProviderTestError::Auth { status, .. }) => assert_eq!(status, 401),
+            other => panic!("Expected auth failure, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn classifies_statuses() {
+        assert!(matches!(
+            ProviderTestError::from_status(403, ""),
+            ProviderTestError::Auth { status: 403, .. }
+        ));
+        assert!(matches!(
+            ProviderTestError::from_status(404, "missing"),
+            ProviderTestError::NotFound { .. }
+        ));
+        assert!(matches!(
+            ProviderTestError::from_status(500, ""),
+            ProviderTestError::Http { status: 500, .. }
+        ));
+    }
+
+    #[tokio::test]
+    async fn unknown_provider_is_a_config_error() {
+        let (_dir, api_keys) = setup().await;
+        let result = test_provider_connection(
+            &registry_for("http://127.0.0.1:9".to_string()),
+            &api_keys,
+            "missing",
+        )
+        .await;
+        assert!(matches!(result, Err(ProviderTestError::Config { .. })));
+    }
+}
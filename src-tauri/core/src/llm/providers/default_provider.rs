@@ -4,7 +4,8 @@
 
 use crate::llm::auth::api_key_manager::ApiKeyManager;
 use crate::llm::protocols::{
-    claude_protocol::ClaudeProtocol, header_builder::HeaderBuildContext,
+    claude_protocol::ClaudeProtocol, cohere_protocol::CohereProtocol,
+    header_builder::HeaderBuildContext, mistral_protocol::MistralProtocol,
     openai_protocol::OpenAiProtocol,
 };
 use crate::llm::providers::provider::{
@@ -59,6 +60,52 @@ impl ProtocolImpl for OpenAiProtocolWrapper {
     }
 }
 
+struct CohereProtocolWrapper(CohereProtocol);
+impl ProtocolImpl for CohereProtocolWrapper {
+    fn build_base_headers(&self, ctx: HeaderBuildContext) -> HashMap<String, String> {
+        use crate::llm::protocols::ProtocolHeaderBuilder;
+        ProtocolHeaderBuilder::build_base_headers(&self.0, ctx)
+    }
+    fn build_request(
+        &self,
+        ctx: crate::llm::protocols::request_builder::RequestBuildContext,
+    ) -> Result<Value, String> {
+        use crate::llm::protocols::ProtocolRequestBuilder;
+        ProtocolRequestBuilder::build_request(&self.0, ctx)
+    }
+    fn parse_stream_event(
+        &self,
+        ctx: crate::llm::protocols::stream_parser::StreamParseContext,
+        state: &mut crate::llm::protocols::stream_parser::StreamParseState,
+    ) -> Result<Option<crate::llm::types::StreamEvent>, String> {
+        use crate::llm::protocols::ProtocolStreamParser;
+        ProtocolStreamParser::parse_stream_event(&self.0, ctx, state)
+    }
+}
+
+struct MistralProtocolWrapper(MistralProtocol);
+impl ProtocolImpl for MistralProtocolWrapper {
+    fn build_base_headers(&self, ctx: HeaderBuildContext) -> HashMap<String, String> {
+        use crate::llm::protocols::ProtocolHeaderBuilder;
+        ProtocolHeaderBuilder::build_base_headers(&self.0, ctx)
+    }
+    fn build_request(
+        &self,
+        ctx: crate::llm::protocols::request_builder::RequestBuildContext,
+    ) -> Result<Value, String> {
+        use crate::llm::protocols::ProtocolRequestBuilder;
+        ProtocolRequestBuilder::build_request(&self.0, ctx)
+    }
+    fn parse_stream_event(
+        &self,
+        ctx: crate::llm::protocols::stream_parser::StreamParseContext,
+        state: &mut crate::llm::protocols::stream_parser::StreamParseState,
+    ) -> Result<Option<crate::llm::types::StreamEvent>, String> {
+        use crate::llm::protocols::ProtocolStreamParser;
+        ProtocolStreamParser::parse_stream_event(&self.0, ctx, state)
+    }
+}
+
 struct ClaudeProtocolWrapper(ClaudeProtocol);
 impl ProtocolImpl for ClaudeProtocolWrapper {
     fn build_base_headers(&self, ctx: HeaderBuildContext) -> HashMap<String, String> {
@@ -118,6 +165,10 @@ impl ProtocolImpl for ClaudeProtocolWrapper {
             reasoning_id: state.reasoning_id.clone(),
             openai_reasoning: std::mem::take(&mut state.openai_reasoning),
             openai_store: state.openai_store,
+            interleaved: state.interleaved,
+            resume: state.resume,
+            last_event_id: state.last_event_id.clone(),
+            tool_call_deltas: state.tool_call_deltas,
         };
 
         let result = self
@@ -148,6 +199,8 @@ impl DefaultProvider {
         let protocol: Box<dyn ProtocolImpl> = match config.protocol {
             ProtocolType::OpenAiCompatible => Box::new(OpenAiProtocolWrapper(OpenAiProtocol)),
             ProtocolType::Claude => Box::new(ClaudeProtocolWrapper(ClaudeProtocol)),
+            ProtocolType::Cohere => Box::new(CohereProtocolWrapper(CohereProtocol)),
+            ProtocolType::Mistral => Box::new(MistralProtocolWrapper(MistralProtocol)),
         };
 
         Self {
@@ -222,7 +275,9 @@ mod tests {
     use super::*;
     use crate::database::Database;
     use crate::llm::auth::api_key_manager::ApiKeyManager;
+    use crate::llm::protocols::request_builder::apply_required_field_defaults;
     use crate::llm::types::{AuthType, ProtocolType, ProviderConfig};
+    use serde_json::json;
     use std::sync::Arc;
     use tempfile::TempDir;
 
@@ -241,6 +296,7 @@ mod tests {
             headers: None,
             extra_body: None,
             auth_type,
+            body_transforms: None,
         }
     }
 
@@ -322,4 +378,22 @@ mod tests {
         let error_msg = result.unwrap_err();
         assert!(error_msg.contains("Authentication required"));
     }
+
+    #[test]
+    fn claude_provider_defaults_missing_max_tokens() {
+        let provider = DefaultProvider::new(create_test_config(AuthType::Bearer));
+        let mut body = json!({ "model": "claude-3", "messages": [] });
+        apply_required_field_defaults(&mut body, &provider.required_request_fields());
+        assert_eq!(body["max_tokens"], json!(1024));
+    }
+
+    #[test]
+    fn openai_compatible_provider_leaves_max_tokens_unset() {
+        let mut config = create_test_config(AuthType::Bearer);
+        config.protocol = ProtocolType::OpenAiCompatible;
+        let provider = DefaultProvider::new(config);
+        let mut body = json!({ "model": "gpt-4o", "messages": [] });
+        apply_required_field_defaults(&mut body, &provider.required_request_fields());
+        assert!(body.get("max_tokens").is_none());
+    }
 }
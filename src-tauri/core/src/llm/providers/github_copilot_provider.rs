@@ -1,7 +1,7 @@
 // GitHub Copilot Provider Implementation
 // Handles special headers required by GitHub Copilot API
 
-use crate::llm::auth::api_key_manager::ApiKeyManager;
+use crate::llm::auth::api_key_manager::{github_api_base_url, ApiKeyManager};
 use crate::llm::protocols::{
     header_builder::{HeaderBuildContext, ProtocolHeaderBuilder},
     openai_protocol::OpenAiProtocol,
@@ -17,6 +17,8 @@ use async_trait::async_trait;
 use serde_json::Value;
 use std::collections::HashMap;
 
+const GITHUB_COPILOT_BASE_URL: &str = "https://api.githubcopilot.com";
+
 pub struct GithubCopilotProvider {
     base: BaseProvider,
     protocol: OpenAiProtocol,
@@ -49,9 +51,17 @@ impl Provider for GithubCopilotProvider {
         &self.base.config
     }
 
-    async fn resolve_base_url(&self, _ctx: &ProviderContext<'_>) -> Result<String, String> {
-        // GitHub Copilot uses a fixed base URL
-        Ok("https://api.githubcopilot.com".to_string())
+    async fn resolve_base_url(&self, ctx: &ProviderContext<'_>) -> Result<String, String> {
+        // Enterprise logins talk to their deployment's API host; everyone else
+        // uses the public Copilot endpoint
+        match ctx
+            .api_key_manager
+            .github_copilot_enterprise_domain()
+            .await?
+        {
+            Some(domain) => Ok(github_api_base_url(&domain)),
+            None => Ok(GITHUB_COPILOT_BASE_URL.to_string()),
+        }
     }
 
     async fn resolve_endpoint_path(&self, _ctx: &ProviderContext<'_>) -> String {
@@ -111,3 +121,84 @@ impl Provider for GithubCopilotProvider {
         self.protocol.parse_stream_event(ctx, state)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::Database;
+    use crate::llm::providers::provider_configs::builtin_providers;
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    async fn setup_test_context() -> (TempDir, ApiKeyManager, ProviderConfig) {
+        let dir = TempDir::new().expect("temp dir");
+        let db_path = dir.path().join("test.db");
+        let db = Arc::new(Database::new(db_path.to_string_lossy().to_string()));
+        db.connect().await.expect("db connect");
+        db.execute(
+            "CREATE TABLE IF NOT EXISTS settings (key TEXT PRIMARY KEY, value TEXT, updated_at INTEGER)",
+            vec![],
+        )
+        .await
+        .expect("create settings");
+
+        let api_keys = ApiKeyManager::new(db, dir.path().to_path_buf());
+        let config = builtin_providers()
+            .into_iter()
+            .find(|provider| provider.id == "github_copilot")
+            .expect("github copilot provider");
+        (dir, api_keys, config)
+    }
+
+    async fn resolve(api_keys: &ApiKeyManager, config: &ProviderConfig) -> String {
+        let provider = GithubCopilotProvider::new(config.clone());
+        let ctx = ProviderContext {
+            provider_config: config,
+            api_key_manager: api_keys,
+            model: "gpt-4.1",
+            messages: &[],
+            tools: None,
+            temperature: None,
+            max_tokens: None,
+            top_p: None,
+            top_k: None,
+            provider_options: None,
+            trace_context: None,
+            logprobs: None,
+            top_logprobs: None,
+        };
+        provider
+            .resolve_base_url(&ctx)
+            .await
+            .expect("resolve base url")
+    }
+
+    #[tokio::test]
+    async fn resolve_base_url_defaults_to_public_endpoint() {
+        let (_dir, api_keys, config) = setup_test_context().await;
+        assert_eq!(resolve(&api_keys, &config).await, GITHUB_COPILOT_BASE_URL);
+
+        // Cleared on disconnect
+        api_keys
+            .set_setting("github_copilot_oauth_enterprise_url", "")
+            .await
+            .expect("set setting");
+        assert_eq!(resolve(&api_keys, &config).await, GITHUB_COPILOT_BASE_URL);
+    }
+
+    #[tokio::test]
+    async fn resolve_base_url_uses_enterprise_api_host() {
+        let (_dir, api_keys, config) = setup_test_context().await;
+        api_keys
+            .set_setting(
+                "github_copilot_oauth_enterprise_url",
+                "https://github.mycorp.com/",
+            )
+            .await
+            .expect("set setting");
+        assert_eq!(
+            resolve(&api_keys, &config).await,
+            "https://api.github.mycorp.com"
+        );
+    }
+}
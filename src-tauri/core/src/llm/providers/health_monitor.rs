@@ -0,0 +1,239 @@
+// Background provider health checks: every configured provider is probed with
+// the same request as `llm_test_provider`, and the latest result is cached so
+// the UI can show per-provider status without triggering a check itself.
+
+use crate::llm::auth::api_key_manager::LlmState;
+use crate::llm::models::model_registry::ModelRegistry;
+use crate::llm::providers::connection_test::{test_provider_connection, ProviderTestError};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+pub const DEFAULT_CHECK_INTERVAL: Duration = Duration::from_secs(5 * 60);
+/// Delay between consecutive provider checks within one round
+pub const DEFAULT_STAGGER: Duration = Duration::from_secs(2);
+/// A status older than this many intervals is no longer reported
+const STALE_AFTER_INTERVALS: u32 = 2;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthStatus {
+    pub healthy: bool,
+    pub latency_ms: Option<u64>,
+    /// Unix milliseconds
+    pub checked_at: i64,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct HealthMonitorConfig {
+    pub interval: Duration,
+    pub stagger: Duration,
+}
+
+impl Default for HealthMonitorConfig {
+    fn default() -> Self {
+        Self {
+            interval: DEFAULT_CHECK_INTERVAL,
+            stagger: DEFAULT_STAGGER,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ProviderHealthMonitor {
+    config: HealthMonitorConfig,
+    statuses: Mutex<HashMap<String, HealthStatus>>,
+    started: AtomicBool,
+}
+
+impl ProviderHealthMonitor {
+    pub fn new(config: HealthMonitorConfig) -> Self {
+        Self {
+            config,
+            statuses: Mutex::new(HashMap::new()),
+            started: AtomicBool::new(false),
+        }
+    }
+
+    /// Store the outcome of a check for `provider_id`
+    pub fn record(
+        &self,
+        provider_id: &str,
+        result: Result<u64, ProviderTestError>,
+        checked_at: i64,
+    ) {
+        let status = match result {
+            Ok(latency_ms) => HealthStatus {
+                healthy: true,
+                latency_ms: Some(latency_ms),
+                checked_at,
+                error: None,
+            },
+            Err(error) => HealthStatus {
+                healthy: false,
+                latency_ms: None,
+                checked_at,
+                error: Some(error.message().to_string()),
+            },
+        };
+        if let Ok(mut statuses) = self.statuses.lock() {
+            statuses.insert(provider_id.to_string(), status);
+        }
+    }
+
+    pub fn is_stale(&self, status: &HealthStatus, now: i64) -> bool {
+        let max_age = self.config.interval * STALE_AFTER_INTERVALS;
+        now.saturating_sub(status.checked_at) > max_age.as_millis() as i64
+    }
+
+    /// Cached statuses, leaving out any that are too old to trust
+    pub fn statuses(&self, now: i64) -> HashMap<String, HealthStatus> {
+        let Ok(statuses) = self.statuses.lock() else {
+            return HashMap::new();
+        };
+        statuses
+            .iter()
+            .filter(|(_, status)| !self.is_stale(status, now))
+            .map(|(provider_id, status)| (provider_id.clone(), status.clone()))
+            .collect()
+    }
+
+    /// Offset of the `index`-th check in a round, kept within one interval
+    fn stagger_delay(&self, index: usize) -> Duration {
+        let delay = self.config.stagger.saturating_mul(index as u32);
+        delay.min(self.config.interval)
+    }
+
+    /// Spawn the periodic check loop; later calls are ignored
+    pub fn start(self: &Arc<Self>, app: AppHandle) {
+        if self.started.swap(true, Ordering::SeqCst) {
+            return;
+        }
+        let monitor = self.clone();
+        tauri::async_runtime::spawn(async move {
+            let mut interval = tokio::time::interval(monitor.config.interval);
+            loop {
+                interval.tick().await;
+                monitor.run_round(&app).await;
+            }
+        });
+    }
+
+    async fn run_round(&self, app: &AppHandle) {
+        let Some(state) = app.try_state::<LlmState>() else {
+            return;
+        };
+        let (registry, api_keys) = {
+            let registry = state.registry.lock().await;
+            let api_keys = state.api_keys.lock().await;
+            (registry.clone(), api_keys.clone())
+        };
+
+        let availability =
+            match ModelRegistry::compute_provider_availability(&api_keys, &registry).await {
+                Ok(availability) => availability,
+                Err(e) => {
+                    log::warn!(
+                        "[ProviderHealth] Failed to load provider availability: {}",
+                        e
+                    );
+                    return;
+                }
+            };
+        let mut provider_ids: Vec<String> = availability
+            .into_iter()
+            .filter(|(_, availability)| availability.available)
+            .map(|(provider_id, _)| provider_id)
+            .collect();
+        provider_ids.sort();
+
+        for (index, provider_id) in provider_ids.iter().enumerate() {
+            let delay = if index == 0 {
+                Duration::ZERO
+            } else {
+                self.stagger_delay(index) - self.stagger_delay(index - 1)
+            };
+            tokio::time::sleep(delay).await;
+
+            let result = test_provider_connection(&registry, &api_keys, provider_id).await;
+            if let Err(error) = &result {
+                log::debug!(
+                    "[ProviderHealth] {} unhealthy: {}",
+                    provider_id,
+                    error.message()
+                );
+            }
+            self.record(provider_id, result, chrono::Utc::now().timestamp_millis());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn monitor(interval_secs: u64) -> ProviderHealthMonitor {
+        ProviderHealthMonitor::new(HealthMonitorConfig {
+            interval: Duration::from_secs(interval_secs),
+            stagger: Duration::from_secs(2),
+        })
+    }
+
+    #[test]
+    fn record_updates_cached_status() {
+        let monitor = monitor(60);
+        monitor.record("openai", Ok(120), 1_000);
+        monitor.record(
+            "deepseek",
+            Err(ProviderTestError::Auth {
+                status: 401,
+                message: "HTTP 401".to_string(),
+            }),
+            1_000,
+        );
+
+        let statuses = monitor.statuses(1_500);
+        assert_eq!(
+            statuses["openai"],
+            HealthStatus {
+                healthy: true,
+                latency_ms: Some(120),
+                checked_at: 1_000,
+                error: None,
+            }
+        );
+        assert!(!statuses["deepseek"].healthy);
+        assert_eq!(statuses["deepseek"].error.as_deref(), Some("HTTP 401"));
+
+        monitor.record("deepseek", Ok(80), 2_000);
+        let statuses = monitor.statuses(2_500);
+        assert!(statuses["deepseek"].healthy);
+        assert_eq!(statuses["deepseek"].checked_at, 2_000);
+    }
+
+    #[test]
+    fn stale_statuses_are_not_reported() {
+        let monitor = monitor(60);
+        monitor.record("openai", Ok(100), 0);
+
+        let status = monitor.statuses(0)["openai"].clone();
+        assert!(!monitor.is_stale(&status, 120_000));
+        assert!(monitor.is_stale(&status, 120_001));
+
+        assert!(monitor.statuses(120_000).contains_key("openai"));
+        assert!(monitor.statuses(120_001).is_empty());
+    }
+
+    #[test]
+    fn stagger_delay_spreads_checks_within_interval() {
+        let monitor = monitor(5);
+        assert_eq!(monitor.stagger_delay(0), Duration::ZERO);
+        assert_eq!(monitor.stagger_delay(1), Duration::from_secs(2));
+        assert_eq!(monitor.stagger_delay(2), Duration::from_secs(4));
+        assert_eq!(monitor.stagger_delay(3), Duration::from_secs(5));
+    }
+}
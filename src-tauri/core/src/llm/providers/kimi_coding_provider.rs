@@ -126,6 +126,7 @@ mod tests {
             headers: None,
             extra_body: None,
             auth_type: crate::llm::types::AuthType::Bearer,
+            body_transforms: None,
         }
     }
 
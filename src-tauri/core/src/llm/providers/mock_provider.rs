@@ -0,0 +1,243 @@
+// Offline provider for UI work and demos. It never opens a connection: the stream
+// handler asks it for a scripted event sequence instead of sending a request.
+// It is only registered when `TALKCODY_ENABLE_MOCK_PROVIDER` is set.
+
+use crate::llm::types::{AuthType, ProtocolType, ProviderConfig, StreamEvent};
+use serde_json::Value;
+use std::time::Duration;
+
+pub const MOCK_PROVIDER_ID: &str = "mock";
+pub const MOCK_PROVIDER_ENV: &str = "TALKCODY_ENABLE_MOCK_PROVIDER";
+
+const DEFAULT_MOCK_RESPONSE: &str =
+    "This is a canned response from the mock provider. No API request was made.";
+const DEFAULT_MOCK_DELAY_MS: u64 = 30;
+
+pub fn mock_provider_enabled() -> bool {
+    is_enabled_value(std::env::var(MOCK_PROVIDER_ENV).ok().as_deref())
+}
+
+fn is_enabled_value(value: Option<&str>) -> bool {
+    matches!(
+        value.map(|v| v.trim().to_ascii_lowercase()).as_deref(),
+        Some("1") | Some("true") | Some("yes")
+    )
+}
+
+pub fn mock_provider_config() -> ProviderConfig {
+    ProviderConfig {
+        id: MOCK_PROVIDER_ID.to_string(),
+        name: "Mock (offline)".to_string(),
+        protocol: ProtocolType::OpenAiCompatible,
+        base_url: "http://127.0.0.1/mock".to_string(),
+        api_key_name: "MOCK_ENABLED".to_string(),
+        supports_oauth: false,
+        supports_coding_plan: false,
+        supports_international: false,
+        coding_plan_base_url: None,
+        international_base_url: None,
+        headers: None,
+        extra_body: None,
+        auth_type: AuthType::None,
+        body_transforms: None,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MockToolCall {
+    pub name: String,
+    pub input: Value,
+}
+
+/// Scripted response, configurable through `providerOptions.mock`:
+/// `{ "response": "...", "delayMs": 30, "usage": true, "toolCall": { "name": "...", "input": {} } }`
+#[derive(Debug, Clone)]
+pub struct MockProvider {
+    pub response: String,
+    pub delay: Duration,
+    pub tool_call: Option<MockToolCall>,
+    pub usage: bool,
+}
+
+impl Default for MockProvider {
+    fn default() -> Self {
+        Self {
+            response: DEFAULT_MOCK_RESPONSE.to_string(),
+            delay: Duration::from_millis(DEFAULT_MOCK_DELAY_MS),
+            tool_call: None,
+            usage: false,
+        }
+    }
+}
+
+impl MockProvider {
+    pub fn from_provider_options(provider_options: Option<&Value>) -> Self {
+        let mut provider = Self::default();
+        let Some(options) = provider_options.and_then(|value| value.get("mock")) else {
+            return provider;
+        };
+        if let Some(response) = options.get("response").and_then(|v| v.as_str()) {
+            provider.response = response.to_string();
+        }
+        if let Some(delay_ms) = options.get("delayMs").and_then(|v| v.as_u64()) {
+            provider.delay = Duration::from_millis(delay_ms);
+        }
+        if let Some(usage) = options.get("usage").and_then(|v| v.as_bool()) {
+            provider.usage = usage;
+        }
+        if let Some(tool_call) = options.get("toolCall") {
+            if let Some(name) = tool_call.get("name").and_then(|v| v.as_str()) {
+                provider.tool_call = Some(MockToolCall {
+                    name: name.to_string(),
+                    input: tool_call
+                        .get("input")
+                        .cloned()
+                        .unwrap_or_else(|| serde_json::json!({})),
+                });
+            }
+        }
+        provider
+    }
+
+    /// The full event sequence, ending with `Done`
+    pub fn events(&self) -> Vec<StreamEvent> {
+        let mut events = vec![StreamEvent::TextStart];
+        let chunks: Vec<&str> = self.response.split_inclusive(' ').collect();
+        for chunk in &chunks {
+            events.push(StreamEvent::TextDelta {
+                text: chunk.to_string(),
+            });
+        }
+        if let Some(tool_call) = &self.tool_call {
+            events.push(StreamEvent::ToolCall {
+                tool_call_id: "mock_call_1".to_string(),
+                tool_name: tool_call.name.clone(),
+                input: tool_call.input.clone(),
+                provider_metadata: None,
+            });
+        }
+        if self.usage {
+            let output_tokens = chunks.len() as i32 + i32::from(self.tool_call.is_some());
+            events.push(StreamEvent::Usage {
+                input_tokens: 0,
+                output_tokens,
+                total_tokens: Some(output_tokens),
+                cached_input_tokens: None,
+                cache_creation_input_tokens: None,
+            });
+        }
+        let finish_reason = if self.tool_call.is_some() {
+            "tool_calls"
+        } else {
+            "stop"
+        };
+        events.push(StreamEvent::Done {
+            finish_reason: Some(finish_reason.to_string()),
+        });
+        events
+    }
+
+    /// Hand each scripted event to `emit`, pausing `delay` between events
+    pub async fn stream<F>(&self, mut emit: F)
+    where
+        F: FnMut(StreamEvent),
+    {
+        for (index, event) in self.events().into_iter().enumerate() {
+            if index > 0 && !self.delay.is_zero() {
+                tokio::time::sleep(self.delay).await;
+            }
+            emit(event);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn event_kinds(events: &[StreamEvent]) -> Vec<String> {
+        events
+            .iter()
+            .map(|event| match event {
+                StreamEvent::TextStart => "text-start".to_string(),
+                StreamEvent::TextDelta { text } => format!("text:{}", text),
+                StreamEvent::ToolCall { tool_name, .. } => format!("tool:{}", tool_name),
+                StreamEvent::Usage { output_tokens, .. } => format!("usage:{}", output_tokens),
+                StreamEvent::Done { finish_reason } => {
+                    format!("done:{}", finish_reason.as_deref().unwrap_or_default())
+                }
+                other => format!("{:?}", other),
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn streams_scripted_text_sequence() {
+        let provider = MockProvider::from_provider_options(Some(&json!({
+            "mock": { "response": "Hello mock world", "delayMs": 0 }
+        })));
+        let mut streamed = Vec::new();
+        provider.stream(|event| streamed.push(event)).await;
+
+        assert_eq!(
+            event_kinds(&streamed),
+            vec![
+                "text-start",
+                "text:Hello ",
+                "text:mock ",
+                "text:world",
+                "done:stop"
+            ]
+        );
+    }
+
+    #[test]
+    fn scripts_tool_call_and_usage() {
+        let provider = MockProvider::from_provider_options(Some(&json!({
+            "mock": {
+                "response": "Reading file",
+                "usage": true,
+                "toolCall": { "name": "readFile", "input": { "path": "README.md" } }
+            }
+        })));
+        let events = provider.events();
+
+        assert_eq!(
+            event_kinds(&events),
+            vec![
+                "text-start",
+                "text:Reading ",
+                "text:file",
+                "tool:readFile",
+                "usage:3",
+                "done:tool_calls"
+            ]
+        );
+        match &events[3] {
+            StreamEvent::ToolCall { input, .. } => assert_eq!(input["path"], "README.md"),
+            other => panic!("Expected ToolCall, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn defaults_without_options() {
+        let provider = MockProvider::from_provider_options(None);
+        assert_eq!(provider.delay, Duration::from_millis(DEFAULT_MOCK_DELAY_MS));
+        let events = provider.events();
+        assert!(matches!(events.first(), Some(StreamEvent::TextStart)));
+        assert!(matches!(
+            events.last(),
+            Some(StreamEvent::Done { finish_reason }) if finish_reason.as_deref() == Some("stop")
+        ));
+    }
+
+    #[test]
+    fn only_enabled_by_explicit_opt_in() {
+        assert!(!is_enabled_value(None));
+        assert!(!is_enabled_value(Some("")));
+        assert!(!is_enabled_value(Some("0")));
+        assert!(is_enabled_value(Some("1")));
+        assert!(is_enabled_value(Some("TRUE")));
+    }
+}
@@ -1,3 +1,6 @@
+pub mod body_transform;
+pub mod connection_test;
+pub mod health_monitor;
 pub mod provider;
 pub mod provider_configs;
 pub mod provider_registry;
@@ -6,6 +9,7 @@ pub mod provider_registry;
 pub mod default_provider;
 pub mod github_copilot_provider;
 pub mod kimi_coding_provider;
+pub mod mock_provider;
 pub mod moonshot_provider;
 pub mod openai_provider;
 
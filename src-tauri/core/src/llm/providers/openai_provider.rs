@@ -19,6 +19,9 @@ use async_trait::async_trait;
 use serde_json::Value;
 use std::collections::HashMap;
 
+/// Setting key for instructions sent in place of the bundled Codex instructions
+const CODEX_INSTRUCTIONS_OVERRIDE_KEY: &str = "codex_instructions_override";
+
 pub struct OpenAiProvider {
     base: BaseProvider,
     protocol: OpenAiProtocol,
@@ -63,9 +66,25 @@ impl OpenAiProvider {
             .unwrap_or(false)
     }
 
+    /// Instructions override from settings; blank values keep the bundled text
+    async fn codex_instructions_override(api_key_manager: &ApiKeyManager) -> Option<String> {
+        match api_key_manager
+            .get_setting(CODEX_INSTRUCTIONS_OVERRIDE_KEY)
+            .await
+        {
+            Ok(value) => value.filter(|text| !text.trim().is_empty()),
+            Err(e) => {
+                log::warn!("Failed to read {}: {}", CODEX_INSTRUCTIONS_OVERRIDE_KEY, e);
+                None
+            }
+        }
+    }
+
     /// Build request for OAuth/Codex API format
-    #[cfg(test)]
-    pub(crate) fn build_oauth_request(&self, ctx: &ProviderContext<'_>) -> Result<Value, String> {
+    pub(crate) async fn build_oauth_request(
+        &self,
+        ctx: &ProviderContext<'_>,
+    ) -> Result<Value, String> {
         let request_ctx = RequestBuildContext {
             model: ctx.model,
             messages: ctx.messages,
@@ -76,8 +95,14 @@ impl OpenAiProvider {
             top_k: ctx.top_k,
             provider_options: ctx.provider_options,
             extra_body: ctx.provider_config.extra_body.as_ref(),
+            logprobs: ctx.logprobs,
+            top_logprobs: ctx.top_logprobs,
         };
-        self.responses_protocol.build_request(request_ctx)
+        let mut body = self.responses_protocol.build_request(request_ctx)?;
+        if let Some(instructions) = Self::codex_instructions_override(ctx.api_key_manager).await {
+            body["instructions"] = Value::String(instructions);
+        }
+        Ok(body)
     }
 }
 
@@ -128,7 +153,7 @@ impl Provider for OpenAiProvider {
             match creds {
                 ProviderCredentials::Token(token) => {
                     let account_id = api_key_manager
-                        .get_setting("openai_oauth_account_id")
+                        .get_oauth_setting("openai", "account_id")
                         .await?
                         .or(None);
                     Ok(Creds::OAuth { token, account_id })
@@ -160,7 +185,7 @@ impl Provider for OpenAiProvider {
             // Add account header if available
             if let Some(account_id) = ctx
                 .api_key_manager
-                .get_setting("openai_oauth_account_id")
+                .get_oauth_setting("openai", "account_id")
                 .await?
             {
                 if !account_id.is_empty() {
@@ -173,18 +198,7 @@ impl Provider for OpenAiProvider {
 
     async fn build_request(&self, ctx: &ProviderContext<'_>) -> Result<Value, String> {
         if self.is_oauth_mode(ctx.api_key_manager).await || Self::is_responses_model(ctx.model) {
-            let request_ctx = RequestBuildContext {
-                model: ctx.model,
-                messages: ctx.messages,
-                tools: ctx.tools,
-                temperature: ctx.temperature,
-                max_tokens: ctx.max_tokens,
-                top_p: ctx.top_p,
-                top_k: ctx.top_k,
-                provider_options: ctx.provider_options,
-                extra_body: ctx.provider_config.extra_body.as_ref(),
-            };
-            self.responses_protocol.build_request(request_ctx)
+            self.build_oauth_request(ctx).await
         } else {
             // Use standard protocol request building
             let request_ctx = RequestBuildContext {
@@ -197,6 +211,8 @@ impl Provider for OpenAiProvider {
                 top_k: ctx.top_k,
                 provider_options: ctx.provider_options,
                 extra_body: ctx.provider_config.extra_body.as_ref(),
+                logprobs: ctx.logprobs,
+                top_logprobs: ctx.top_logprobs,
             };
             self.protocol.build_request(request_ctx)
         }
@@ -240,7 +256,7 @@ mod tests {
     use crate::database::Database;
     use crate::llm::auth::api_key_manager::ApiKeyManager;
     use crate::llm::protocols::openai_responses_protocol::{
-        parse_openai_oauth_event_legacy, parse_openai_oauth_function_call_done,
+        parse_openai_oauth_event_legacy, parse_openai_oauth_function_call_done, CODEX_INSTRUCTIONS,
     };
     use crate::llm::protocols::{ProtocolStreamState, ToolCallAccum};
     use crate::llm::types::{ContentPart, Message, MessageContent, StreamTextRequest};
@@ -269,6 +285,7 @@ mod tests {
             headers: None,
             extra_body: None,
             auth_type: crate::llm::types::AuthType::Bearer,
+            body_transforms: None,
         });
 
         let request = StreamTextRequest {
@@ -310,6 +327,18 @@ mod tests {
             provider_options: None,
             request_id: None,
             trace_context: None,
+            partial_json: None,
+            fallback_models: None,
+            logprobs: None,
+            top_logprobs: None,
+            estimate_usage: false,
+            metadata: None,
+            resume_on_disconnect: false,
+            allowed_tools: None,
+            stream_tool_call_deltas: false,
+            completion_webhook: None,
+            persist_to_session: None,
+            idempotency_key: None,
         };
 
         let ctx = ProviderContext {
@@ -324,9 +353,14 @@ mod tests {
             top_k: request.top_k,
             provider_options: request.provider_options.as_ref(),
             trace_context: request.trace_context.as_ref(),
+            logprobs: request.logprobs,
+            top_logprobs: request.top_logprobs,
         };
 
-        let body = provider.build_oauth_request(&ctx).expect("request body");
+        let body = provider
+            .build_oauth_request(&ctx)
+            .await
+            .expect("request body");
         let input = body
             .get("input")
             .and_then(|value| value.as_array())
@@ -360,6 +394,80 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn build_openai_oauth_request_uses_instructions_override() {
+        let dir = TempDir::new().expect("temp dir");
+        let db_path = dir.path().join("talkcody-test.db");
+        let db = Arc::new(Database::new(db_path.to_string_lossy().to_string()));
+        db.connect().await.expect("db connect");
+        db.execute(
+            "CREATE TABLE IF NOT EXISTS settings (key TEXT PRIMARY KEY, value TEXT, updated_at INTEGER)",
+            vec![],
+        )
+        .await
+        .expect("create settings");
+        let api_keys = ApiKeyManager::new(db, std::path::PathBuf::from("/tmp"));
+        let provider = OpenAiProvider::new(ProviderConfig {
+            id: "openai".to_string(),
+            name: "OpenAI".to_string(),
+            protocol: ProtocolType::OpenAiCompatible,
+            base_url: "https://api.openai.com/v1".to_string(),
+            api_key_name: "OPENAI_API_KEY".to_string(),
+            supports_oauth: true,
+            supports_coding_plan: false,
+            supports_international: false,
+            coding_plan_base_url: None,
+            international_base_url: None,
+            headers: None,
+            extra_body: None,
+            auth_type: crate::llm::types::AuthType::Bearer,
+            body_transforms: None,
+        });
+        let messages = vec![Message::User {
+            content: MessageContent::Text("hi".to_string()),
+            provider_options: None,
+        }];
+        let ctx = ProviderContext {
+            provider_config: provider.config(),
+            api_key_manager: &api_keys,
+            model: "gpt-5.2-codex",
+            messages: &messages,
+            tools: None,
+            temperature: None,
+            max_tokens: None,
+            top_p: None,
+            top_k: None,
+            provider_options: None,
+            trace_context: None,
+            logprobs: None,
+            top_logprobs: None,
+        };
+        let instructions = |body: Value| body["instructions"].as_str().map(str::to_string);
+
+        let body = provider.build_oauth_request(&ctx).await.expect("default");
+        assert_eq!(instructions(body).as_deref(), Some(CODEX_INSTRUCTIONS));
+
+        api_keys
+            .set_setting(CODEX_INSTRUCTIONS_OVERRIDE_KEY, "   \n")
+            .await
+            .expect("set blank override");
+        let body = provider.build_oauth_request(&ctx).await.expect("blank");
+        assert_eq!(instructions(body).as_deref(), Some(CODEX_INSTRUCTIONS));
+
+        api_keys
+            .set_setting(
+                CODEX_INSTRUCTIONS_OVERRIDE_KEY,
+                "Follow the team style guide.",
+            )
+            .await
+            .expect("set override");
+        let body = provider.build_oauth_request(&ctx).await.expect("override");
+        assert_eq!(
+            instructions(body).as_deref(),
+            Some("Follow the team style guide.")
+        );
+    }
+
     #[test]
     fn openai_oauth_skips_partial_tool_call_arguments() {
         let mut state = ProtocolStreamState::default();
@@ -421,6 +529,66 @@ mod tests {
         assert!(second.is_none());
     }
 
+    #[test]
+    fn openai_oauth_tool_call_deltas_accumulate_to_final_arguments() {
+        let mut state = ProtocolStreamState {
+            tool_call_deltas: true,
+            ..Default::default()
+        };
+        let added = json!({
+            "item": { "type": "function_call", "id": "item_1", "call_id": "call_1", "name": "readFile" }
+        });
+        let deltas = ["{\"pa", "th\":\"/tm", "p/a\"}"];
+
+        let mut events = Vec::new();
+        parse_openai_oauth_event_legacy(
+            Some("response.output_item.added"),
+            &added.to_string(),
+            &mut state,
+        )
+        .expect("parse added");
+        events.append(&mut state.pending_events);
+        for delta in deltas {
+            let payload = json!({ "item_id": "item_1", "delta": delta });
+            if let Some(event) = parse_openai_oauth_event_legacy(
+                Some("response.function_call_arguments.delta"),
+                &payload.to_string(),
+                &mut state,
+            )
+            .expect("parse delta")
+            {
+                events.push(event);
+            }
+            events.append(&mut state.pending_events);
+        }
+
+        let streamed: String = events
+            .iter()
+            .filter_map(|event| match event {
+                StreamEvent::ToolCallDelta {
+                    tool_call_id,
+                    tool_name,
+                    arguments_delta,
+                } => {
+                    assert_eq!(tool_call_id, "call_1");
+                    assert_eq!(tool_name.as_deref(), Some("readFile"));
+                    Some(arguments_delta.as_str())
+                }
+                _ => None,
+            })
+            .collect();
+        assert_eq!(streamed, deltas.concat());
+
+        let input = events
+            .iter()
+            .find_map(|event| match event {
+                StreamEvent::ToolCall { input, .. } => Some(input.clone()),
+                _ => None,
+            })
+            .expect("tool call");
+        assert_eq!(input, json!({ "path": "/tmp/a" }));
+    }
+
     #[test]
     fn openai_oauth_preserves_tool_call_index_order() {
         let mut state = ProtocolStreamState::default();
@@ -557,6 +725,7 @@ mod tests {
             headers: None,
             extra_body: None,
             auth_type: crate::llm::types::AuthType::Bearer,
+            body_transforms: None,
         });
 
         let request = StreamTextRequest {
@@ -602,6 +771,18 @@ mod tests {
             provider_options: None,
             request_id: None,
             trace_context: None,
+            partial_json: None,
+            fallback_models: None,
+            logprobs: None,
+            top_logprobs: None,
+            estimate_usage: false,
+            metadata: None,
+            resume_on_disconnect: false,
+            allowed_tools: None,
+            stream_tool_call_deltas: false,
+            completion_webhook: None,
+            persist_to_session: None,
+            idempotency_key: None,
         };
 
         let ctx = ProviderContext {
@@ -616,9 +797,14 @@ mod tests {
             top_k: request.top_k,
             provider_options: request.provider_options.as_ref(),
             trace_context: request.trace_context.as_ref(),
+            logprobs: request.logprobs,
+            top_logprobs: request.top_logprobs,
         };
 
-        let body = provider.build_oauth_request(&ctx).expect("request body");
+        let body = provider
+            .build_oauth_request(&ctx)
+            .await
+            .expect("request body");
         let input = body
             .get("input")
             .and_then(|value| value.as_array())
@@ -4,7 +4,10 @@
 use crate::llm::auth::api_key_manager::ApiKeyManager;
 use crate::llm::protocols::{
     header_builder::HeaderBuildContext,
-    request_builder::RequestBuildContext,
+    request_builder::{
+        apply_required_field_defaults, message_role_constraints, normalize_message_roles,
+        required_request_fields, MessageRoleConstraints, RequestBuildContext, RequiredRequestField,
+    },
     stream_parser::{StreamParseContext, StreamParseState},
 };
 use crate::llm::types::ProtocolType;
@@ -28,6 +31,8 @@ pub struct ProviderContext<'a> {
     pub provider_options: Option<&'a Value>,
     #[allow(dead_code)]
     pub trace_context: Option<&'a TraceContext>,
+    pub logprobs: Option<bool>,
+    pub top_logprobs: Option<u8>,
 }
 
 /// Credentials for authentication
@@ -79,6 +84,8 @@ pub trait Provider: Send + Sync {
         match self.protocol_type() {
             ProtocolType::OpenAiCompatible => "chat/completions".to_string(),
             ProtocolType::Claude => "messages".to_string(),
+            ProtocolType::Cohere => "chat".to_string(),
+            ProtocolType::Mistral => "chat/completions".to_string(),
         }
     }
 
@@ -151,11 +158,25 @@ pub trait Provider: Send + Sync {
             top_k,
             provider_options: ctx.provider_options,
             extra_body: ctx.provider_config.extra_body.as_ref(),
+            logprobs: ctx.logprobs,
+            top_logprobs: ctx.top_logprobs,
         };
 
         self.build_protocol_request(request_ctx)
     }
 
+    /// Fields the upstream API requires in the request body
+    /// Defaults to the protocol's requirements; override for provider-specific constraints
+    fn required_request_fields(&self) -> Vec<RequiredRequestField> {
+        required_request_fields(self.protocol_type())
+    }
+
+    /// Message ordering rules the upstream API enforces
+    /// Defaults to the protocol's rules refined by known provider restrictions
+    fn message_role_constraints(&self) -> MessageRoleConstraints {
+        message_role_constraints(self.protocol_type(), self.id())
+    }
+
     /// Build protocol request (delegates to protocol)
     fn build_protocol_request(&self, ctx: RequestBuildContext) -> Result<Value, String>;
 
@@ -205,7 +226,13 @@ pub trait Provider: Send + Sync {
         let normalized_base_url = normalize_provider_base_url(&base_url, ctx.provider_config);
         let credentials = self.get_credentials(ctx.api_key_manager).await?;
         let headers = self.build_headers(ctx, &credentials).await?;
-        let body = self.build_request(ctx).await?;
+        let messages = normalize_message_roles(ctx.messages, self.message_role_constraints());
+        let ctx = &ProviderContext {
+            messages: &messages,
+            ..ctx.clone()
+        };
+        let mut body = self.build_request(ctx).await?;
+        apply_required_field_defaults(&mut body, &self.required_request_fields());
 
         let url = format!(
             "{}/{}",
@@ -217,7 +244,10 @@ pub trait Provider: Send + Sync {
     }
 }
 
-fn normalize_provider_base_url(base_url: &str, provider_config: &ProviderConfig) -> String {
+pub(crate) fn normalize_provider_base_url(
+    base_url: &str,
+    provider_config: &ProviderConfig,
+) -> String {
     let trimmed = base_url.trim_end_matches('/');
     if !is_custom_provider_id(&provider_config.id) {
         return trimmed.to_string();
@@ -273,6 +303,7 @@ mod tests {
             headers: None,
             extra_body: None,
             auth_type: crate::llm::types::AuthType::Bearer,
+            body_transforms: None,
         }
     }
 
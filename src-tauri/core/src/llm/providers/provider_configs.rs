@@ -1,7 +1,8 @@
+use crate::llm::providers::mock_provider::{mock_provider_config, mock_provider_enabled};
 use crate::llm::types::{AuthType, ProtocolType, ProviderConfig};
 
 pub fn builtin_providers() -> Vec<ProviderConfig> {
-    vec![
+    let mut providers = vec![
         ProviderConfig {
             id: "talkcody".to_string(),
             name: "TalkCody Free".to_string(),
@@ -16,6 +17,7 @@ pub fn builtin_providers() -> Vec<ProviderConfig> {
             headers: None,
             extra_body: None,
             auth_type: AuthType::TalkCodyJwt,
+            body_transforms: None,
         },
         ProviderConfig {
             id: "openai".to_string(),
@@ -31,6 +33,7 @@ pub fn builtin_providers() -> Vec<ProviderConfig> {
             headers: None,
             extra_body: None,
             auth_type: AuthType::Bearer,
+            body_transforms: None,
         },
         ProviderConfig {
             id: "github_copilot".to_string(),
@@ -64,6 +67,7 @@ pub fn builtin_providers() -> Vec<ProviderConfig> {
             ),
             extra_body: None,
             auth_type: AuthType::OAuthBearer,
+            body_transforms: None,
         },
         ProviderConfig {
             id: "openRouter".to_string(),
@@ -91,6 +95,7 @@ pub fn builtin_providers() -> Vec<ProviderConfig> {
                 "reasoning": { "enabled": true }
             })),
             auth_type: AuthType::Bearer,
+            body_transforms: None,
         },
         ProviderConfig {
             id: "aiGateway".to_string(),
@@ -116,6 +121,7 @@ pub fn builtin_providers() -> Vec<ProviderConfig> {
             ),
             extra_body: None,
             auth_type: AuthType::Bearer,
+            body_transforms: None,
         },
         ProviderConfig {
             id: "deepseek".to_string(),
@@ -131,6 +137,7 @@ pub fn builtin_providers() -> Vec<ProviderConfig> {
             headers: None,
             extra_body: None,
             auth_type: AuthType::Bearer,
+            body_transforms: None,
         },
         ProviderConfig {
             id: "zhipu".to_string(),
@@ -146,6 +153,7 @@ pub fn builtin_providers() -> Vec<ProviderConfig> {
             headers: None,
             extra_body: None,
             auth_type: AuthType::Bearer,
+            body_transforms: None,
         },
         ProviderConfig {
             id: "zai".to_string(),
@@ -161,6 +169,7 @@ pub fn builtin_providers() -> Vec<ProviderConfig> {
             headers: None,
             extra_body: None,
             auth_type: AuthType::Bearer,
+            body_transforms: None,
         },
         ProviderConfig {
             id: "MiniMax".to_string(),
@@ -176,6 +185,7 @@ pub fn builtin_providers() -> Vec<ProviderConfig> {
             headers: None,
             extra_body: None,
             auth_type: AuthType::ApiKey,
+            body_transforms: None,
         },
         ProviderConfig {
             id: "moonshot".to_string(),
@@ -191,6 +201,7 @@ pub fn builtin_providers() -> Vec<ProviderConfig> {
             headers: None,
             extra_body: None,
             auth_type: AuthType::Bearer,
+            body_transforms: None,
         },
         ProviderConfig {
             id: "kimi_coding".to_string(),
@@ -206,6 +217,7 @@ pub fn builtin_providers() -> Vec<ProviderConfig> {
             headers: None,
             extra_body: None,
             auth_type: AuthType::Bearer,
+            body_transforms: None,
         },
         ProviderConfig {
             id: "groq".to_string(),
@@ -221,6 +233,7 @@ pub fn builtin_providers() -> Vec<ProviderConfig> {
             headers: None,
             extra_body: None,
             auth_type: AuthType::Bearer,
+            body_transforms: None,
         },
         ProviderConfig {
             id: "ollama".to_string(),
@@ -236,6 +249,7 @@ pub fn builtin_providers() -> Vec<ProviderConfig> {
             headers: None,
             extra_body: None,
             auth_type: AuthType::None,
+            body_transforms: None,
         },
         ProviderConfig {
             id: "lmstudio".to_string(),
@@ -251,6 +265,7 @@ pub fn builtin_providers() -> Vec<ProviderConfig> {
             headers: None,
             extra_body: None,
             auth_type: AuthType::None,
+            body_transforms: None,
         },
         ProviderConfig {
             id: "anthropic".to_string(),
@@ -266,6 +281,7 @@ pub fn builtin_providers() -> Vec<ProviderConfig> {
             headers: None,
             extra_body: None,
             auth_type: AuthType::OAuthBearer,
+            body_transforms: None,
         },
         ProviderConfig {
             id: "google".to_string(),
@@ -281,6 +297,39 @@ pub fn builtin_providers() -> Vec<ProviderConfig> {
             headers: None,
             extra_body: None,
             auth_type: AuthType::Bearer,
+            body_transforms: None,
+        },
+        ProviderConfig {
+            id: "cohere".to_string(),
+            name: "Cohere".to_string(),
+            protocol: ProtocolType::Cohere,
+            base_url: "https://api.cohere.ai/v1".to_string(),
+            api_key_name: "COHERE_API_KEY".to_string(),
+            supports_oauth: false,
+            supports_coding_plan: false,
+            supports_international: false,
+            coding_plan_base_url: None,
+            international_base_url: None,
+            headers: None,
+            extra_body: None,
+            auth_type: AuthType::Bearer,
+            body_transforms: None,
+        },
+        ProviderConfig {
+            id: "mistral".to_string(),
+            name: "Mistral".to_string(),
+            protocol: ProtocolType::Mistral,
+            base_url: "https://api.mistral.ai/v1".to_string(),
+            api_key_name: "MISTRAL_API_KEY".to_string(),
+            supports_oauth: false,
+            supports_coding_plan: false,
+            supports_international: false,
+            coding_plan_base_url: None,
+            international_base_url: None,
+            headers: None,
+            extra_body: None,
+            auth_type: AuthType::Bearer,
+            body_transforms: None,
         },
         ProviderConfig {
             id: "volcengine".to_string(),
@@ -296,6 +345,7 @@ pub fn builtin_providers() -> Vec<ProviderConfig> {
             headers: None,
             extra_body: None,
             auth_type: AuthType::Bearer,
+            body_transforms: None,
         },
         ProviderConfig {
             id: "alibaba".to_string(),
@@ -311,6 +361,7 @@ pub fn builtin_providers() -> Vec<ProviderConfig> {
             headers: None,
             extra_body: None,
             auth_type: AuthType::Bearer,
+            body_transforms: None,
         },
         ProviderConfig {
             id: "tavily".to_string(),
@@ -326,6 +377,7 @@ pub fn builtin_providers() -> Vec<ProviderConfig> {
             headers: None,
             extra_body: None,
             auth_type: AuthType::Bearer,
+            body_transforms: None,
         },
         ProviderConfig {
             id: "serper".to_string(),
@@ -341,6 +393,7 @@ pub fn builtin_providers() -> Vec<ProviderConfig> {
             headers: None,
             extra_body: None,
             auth_type: AuthType::Bearer,
+            body_transforms: None,
         },
         ProviderConfig {
             id: "elevenlabs".to_string(),
@@ -356,6 +409,11 @@ pub fn builtin_providers() -> Vec<ProviderConfig> {
             headers: None,
             extra_body: None,
             auth_type: AuthType::Bearer,
+            body_transforms: None,
         },
-    ]
+    ];
+    if mock_provider_enabled() {
+        providers.push(mock_provider_config());
+    }
+    providers
 }
@@ -1,4 +1,7 @@
-use crate::llm::protocols::{claude_protocol::ClaudeProtocol, openai_protocol::OpenAiProtocol};
+use crate::llm::protocols::{
+    claude_protocol::ClaudeProtocol, cohere_protocol::CohereProtocol,
+    mistral_protocol::MistralProtocol, openai_protocol::OpenAiProtocol,
+};
 use crate::llm::providers::{
     DefaultProvider, GithubCopilotProvider, KimiCodingProvider, MoonshotProvider, OpenAiProvider,
     Provider,
@@ -14,6 +17,10 @@ pub struct ProviderRegistry {
     openai_protocol: OpenAiProtocol,
     #[allow(dead_code)]
     claude_protocol: ClaudeProtocol,
+    #[allow(dead_code)]
+    cohere_protocol: CohereProtocol,
+    #[allow(dead_code)]
+    mistral_protocol: MistralProtocol,
 }
 
 impl std::fmt::Debug for ProviderRegistry {
@@ -30,6 +37,8 @@ impl Clone for ProviderRegistry {
             providers: self.providers.clone(),
             openai_protocol: OpenAiProtocol,
             claude_protocol: ClaudeProtocol,
+            cohere_protocol: CohereProtocol,
+            mistral_protocol: MistralProtocol,
         }
     }
 }
@@ -52,6 +61,8 @@ impl ProviderRegistry {
             providers,
             openai_protocol: OpenAiProtocol,
             claude_protocol: ClaudeProtocol,
+            cohere_protocol: CohereProtocol,
+            mistral_protocol: MistralProtocol,
         }
     }
 
@@ -93,6 +104,8 @@ impl ProviderRegistry {
                 Some(LegacyProtocolAdapter::new(&self.openai_protocol))
             }
             ProtocolType::Claude => Some(LegacyProtocolAdapter::new(&self.claude_protocol)),
+            ProtocolType::Cohere => Some(LegacyProtocolAdapter::new(&self.cohere_protocol)),
+            ProtocolType::Mistral => Some(LegacyProtocolAdapter::new(&self.mistral_protocol)),
         }
     }
 }
@@ -188,6 +201,7 @@ mod tests {
             headers: None,
             extra_body: None,
             auth_type: AuthType::Bearer,
+            body_transforms: None,
         }
     }
 
@@ -196,6 +210,8 @@ mod tests {
         let registry = ProviderRegistry::new(Vec::new());
         assert!(registry.protocol(ProtocolType::OpenAiCompatible).is_some());
         assert!(registry.protocol(ProtocolType::Claude).is_some());
+        assert!(registry.protocol(ProtocolType::Cohere).is_some());
+        assert!(registry.protocol(ProtocolType::Mistral).is_some());
     }
 
     #[test]
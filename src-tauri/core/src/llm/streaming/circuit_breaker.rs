@@ -0,0 +1,306 @@
+// Per-provider circuit breaker: after repeated failures a provider is skipped
+// for a cooldown, then a single probe request decides whether it has recovered.
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// Consecutive failures within `DEFAULT_FAILURE_WINDOW` that open the circuit
+pub const DEFAULT_FAILURE_THRESHOLD: u32 = 5;
+pub const DEFAULT_FAILURE_WINDOW: Duration = Duration::from_secs(60);
+/// How long an open circuit fast-fails before letting a probe through
+pub const DEFAULT_COOLDOWN: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitState {
+    Closed,
+    Open,
+    HalfOpen,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct CircuitBreakerConfig {
+    pub failure_threshold: u32,
+    pub failure_window: Duration,
+    pub cooldown: Duration,
+}
+
+impl Default for CircuitBreakerConfig {
+    fn default() -> Self {
+        Self {
+            failure_threshold: DEFAULT_FAILURE_THRESHOLD,
+            failure_window: DEFAULT_FAILURE_WINDOW,
+            cooldown: DEFAULT_COOLDOWN,
+        }
+    }
+}
+
+/// A change of circuit state, recorded on the request's trace span
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct CircuitTransition {
+    pub provider_id: String,
+    pub from: CircuitState,
+    pub to: CircuitState,
+    pub consecutive_failures: u32,
+}
+
+/// Why a request was refused without contacting the provider
+#[derive(Debug, Clone, PartialEq)]
+pub struct CircuitOpen {
+    pub provider_id: String,
+    pub retry_after: Duration,
+}
+
+impl CircuitOpen {
+    pub fn message(&self) -> String {
+        format!(
+            "Provider {} is temporarily unavailable after repeated failures; retrying in {}s",
+            self.provider_id,
+            self.retry_after.as_secs().max(1)
+        )
+    }
+}
+
+#[derive(Debug)]
+struct Circuit {
+    state: CircuitState,
+    consecutive_failures: u32,
+    first_failure_at: Option<Instant>,
+    opened_at: Option<Instant>,
+    probe_started_at: Option<Instant>,
+}
+
+impl Default for Circuit {
+    fn default() -> Self {
+        Self {
+            state: CircuitState::Closed,
+            consecutive_failures: 0,
+            first_failure_at: None,
+            opened_at: None,
+            probe_started_at: None,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ProviderCircuitBreaker {
+    config: CircuitBreakerConfig,
+    circuits: Mutex<HashMap<String, Circuit>>,
+}
+
+/// Breaker shared by every `StreamHandler`, so failures accumulate across requests
+pub fn provider_circuit_breaker() -> Arc<ProviderCircuitBreaker> {
+    static BREAKER: OnceLock<Arc<ProviderCircuitBreaker>> = OnceLock::new();
+    BREAKER
+        .get_or_init(|| Arc::new(ProviderCircuitBreaker::default()))
+        .clone()
+}
+
+impl ProviderCircuitBreaker {
+    pub fn new(config: CircuitBreakerConfig) -> Self {
+        Self {
+            config,
+            circuits: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn state(&self, provider_id: &str) -> CircuitState {
+        self.circuits
+            .lock()
+            .ok()
+            .and_then(|circuits| circuits.get(provider_id).map(|circuit| circuit.state))
+            .unwrap_or(CircuitState::Closed)
+    }
+
+    /// Decide whether a request to `provider_id` may go out. Once the cooldown
+    /// has passed the circuit half-opens and admits one probe at a time.
+    pub fn try_acquire(
+        &self,
+        provider_id: &str,
+        now: Instant,
+    ) -> Result<Option<CircuitTransition>, CircuitOpen> {
+        let Ok(mut circuits) = self.circuits.lock() else {
+            return Ok(None);
+        };
+        let Some(circuit) = circuits.get_mut(provider_id) else {
+            return Ok(None);
+        };
+        let cooldown = self.config.cooldown;
+        match circuit.state {
+            CircuitState::Closed => Ok(None),
+            CircuitState::Open => {
+                let opened_at = circuit.opened_at.unwrap_or(now);
+                let elapsed = now.saturating_duration_since(opened_at);
+                if elapsed < cooldown {
+                    return Err(CircuitOpen {
+                        provider_id: provider_id.to_string(),
+                        retry_after: cooldown - elapsed,
+                    });
+                }
+                circuit.state = CircuitState::HalfOpen;
+                circuit.probe_started_at = Some(now);
+                Ok(Some(CircuitTransition {
+                    provider_id: provider_id.to_string(),
+                    from: CircuitState::Open,
+                    to: CircuitState::HalfOpen,
+                    consecutive_failures: circuit.consecutive_failures,
+                }))
+            }
+            CircuitState::HalfOpen => {
+                // A probe that never reported back frees the slot after another cooldown
+                let probe_elapsed = circuit
+                    .probe_started_at
+                    .map(|started_at| now.saturating_duration_since(started_at))
+                    .unwrap_or(cooldown);
+                if probe_elapsed < cooldown {
+                    return Err(CircuitOpen {
+                        provider_id: provider_id.to_string(),
+                        retry_after: cooldown - probe_elapsed,
+                    });
+                }
+                circuit.probe_started_at = Some(now);
+                Ok(None)
+            }
+        }
+    }
+
+    pub fn record_success(&self, provider_id: &str) -> Option<CircuitTransition> {
+        let mut circuits = self.circuits.lock().ok()?;
+        let circuit = circuits.remove(provider_id)?;
+        (circuit.state != CircuitState::Closed).then(|| CircuitTransition {
+            provider_id: provider_id.to_string(),
+            from: circuit.state,
+            to: CircuitState::Closed,
+            consecutive_failures: 0,
+        })
+    }
+
+    pub fn record_failure(&self, provider_id: &str, now: Instant) -> Option<CircuitTransition> {
+        let mut circuits = self.circuits.lock().ok()?;
+        let circuit = circuits.entry(provider_id.to_string()).or_default();
+        let from = circuit.state;
+        match from {
+            CircuitState::Closed => {
+                let window_expired = circuit.first_failure_at.is_none_or(|first| {
+                    now.saturating_duration_since(first) > self.config.failure_window
+                });
+                if window_expired {
+                    circuit.consecutive_failures = 0;
+                    circuit.first_failure_at = Some(now);
+                }
+                circuit.consecutive_failures += 1;
+                if circuit.consecutive_failures < self.config.failure_threshold.max(1) {
+                    return None;
+                }
+            }
+            CircuitState::HalfOpen => circuit.consecutive_failures += 1,
+            // Requests admitted before the circuit opened may still report in
+            CircuitState::Open => return None,
+        }
+        circuit.state = CircuitState::Open;
+        circuit.opened_at = Some(now);
+        circuit.probe_started_at = None;
+        Some(CircuitTransition {
+            provider_id: provider_id.to_string(),
+            from,
+            to: CircuitState::Open,
+            consecutive_failures: circuit.consecutive_failures,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn breaker() -> ProviderCircuitBreaker {
+        ProviderCircuitBreaker::new(CircuitBreakerConfig {
+            failure_threshold: 3,
+            failure_window: Duration::from_secs(60),
+            cooldown: Duration::from_secs(30),
+        })
+    }
+
+    #[test]
+    fn opens_after_threshold_and_fast_fails() {
+        let breaker = breaker();
+        let start = Instant::now();
+        assert!(breaker.record_failure("openai", start).is_none());
+        assert!(breaker.record_failure("openai", start).is_none());
+        assert!(breaker.try_acquire("openai", start).is_ok());
+
+        let transition = breaker.record_failure("openai", start).unwrap();
+        assert_eq!(transition.from, CircuitState::Closed);
+        assert_eq!(transition.to, CircuitState::Open);
+        assert_eq!(transition.consecutive_failures, 3);
+
+        let rejected = breaker
+            .try_acquire("openai", start + Duration::from_secs(10))
+            .unwrap_err();
+        assert_eq!(rejected.retry_after, Duration::from_secs(20));
+        assert!(rejected.message().contains("openai"));
+        // Other providers are unaffected
+        assert_eq!(breaker.try_acquire("anthropic", start), Ok(None));
+    }
+
+    #[test]
+    fn failures_outside_window_do_not_accumulate() {
+        let breaker = breaker();
+        let start = Instant::now();
+        breaker.record_failure("openai", start);
+        breaker.record_failure("openai", start);
+        let later = start + Duration::from_secs(61);
+        assert!(breaker.record_failure("openai", later).is_none());
+        assert_eq!(breaker.state("openai"), CircuitState::Closed);
+
+        breaker.record_failure("openai", start);
+        assert!(breaker.record_success("openai").is_none());
+        assert!(breaker.record_failure("openai", later).is_none());
+    }
+
+    #[test]
+    fn recovers_through_half_open_probe() {
+        let breaker = breaker();
+        let start = Instant::now();
+        for _ in 0..3 {
+            breaker.record_failure("openai", start);
+        }
+
+        let after_cooldown = start + Duration::from_secs(30);
+        let transition = breaker
+            .try_acquire("openai", after_cooldown)
+            .unwrap()
+            .unwrap();
+        assert_eq!(transition.to, CircuitState::HalfOpen);
+        // Only one probe at a time
+        assert!(breaker.try_acquire("openai", after_cooldown).is_err());
+
+        let transition = breaker.record_success("openai").unwrap();
+        assert_eq!(transition.from, CircuitState::HalfOpen);
+        assert_eq!(transition.to, CircuitState::Closed);
+        assert_eq!(breaker.try_acquire("openai", after_cooldown), Ok(None));
+    }
+
+    #[test]
+    fn failed_probe_reopens_circuit() {
+        let breaker = breaker();
+        let start = Instant::now();
+        for _ in 0..3 {
+            breaker.record_failure("openai", start);
+        }
+        let probe_at = start + Duration::from_secs(31);
+        breaker.try_acquire("openai", probe_at).unwrap();
+
+        let transition = breaker.record_failure("openai", probe_at).unwrap();
+        assert_eq!(transition.from, CircuitState::HalfOpen);
+        assert_eq!(transition.to, CircuitState::Open);
+        assert!(breaker
+            .try_acquire("openai", probe_at + Duration::from_secs(29))
+            .is_err());
+        assert!(breaker
+            .try_acquire("openai", probe_at + Duration::from_secs(30))
+            .is_ok());
+    }
+}
@@ -0,0 +1,303 @@
+// Side-by-side model comparison: one prompt streamed to several models at once.
+// Each model gets its own request id (and `llm-stream-{id}` event channel); the
+// group shares a concurrency cap and is cancelled as a unit.
+
+use crate::llm::auth::api_key_manager::LlmState;
+use crate::llm::streaming::stream_handler::StreamHandler;
+use crate::llm::types::StreamTextRequest;
+use futures_util::StreamExt;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Mutex, OnceLock};
+use tauri::{Emitter, State, Window};
+
+/// Streams running at once within a single comparison group
+pub const MAX_COMPARE_CONCURRENCY: usize = 4;
+
+/// Upper bound on models compared in one group
+pub const MAX_COMPARE_MODELS: usize = 8;
+
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CompareStream {
+    pub model: String,
+    pub request_id: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StreamCompareResponse {
+    pub group_id: String,
+    pub streams: Vec<CompareStream>,
+}
+
+/// Outcome of one stream in a group, emitted on `llm-stream-compare-{group_id}`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CompareStreamResult {
+    pub model: String,
+    pub request_id: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+fn compare_groups() -> &'static Mutex<HashMap<String, tauri::async_runtime::JoinHandle<()>>> {
+    static GROUPS: OnceLock<Mutex<HashMap<String, tauri::async_runtime::JoinHandle<()>>>> =
+        OnceLock::new();
+    GROUPS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Assign each model its own request id within the group
+pub fn plan_compare_streams(
+    group_id: &str,
+    models: &[String],
+) -> Result<Vec<CompareStream>, String> {
+    let mut seen = std::collections::HashSet::new();
+    let models: Vec<&String> = models
+        .iter()
+        .filter(|model| !model.trim().is_empty() && seen.insert(model.trim().to_string()))
+        .collect();
+    if models.is_empty() {
+        return Err("At least one model is required for comparison".to_string());
+    }
+    if models.len() > MAX_COMPARE_MODELS {
+        return Err(format!(
+            "Cannot compare more than {} models at once",
+            MAX_COMPARE_MODELS
+        ));
+    }
+    Ok(models
+        .into_iter()
+        .enumerate()
+        .map(|(index, model)| CompareStream {
+            model: model.trim().to_string(),
+            request_id: format!("{}-{}", group_id, index),
+        })
+        .collect())
+}
+
+/// Run `launch` for every stream with at most `max_concurrency` in flight,
+/// returning results in the order the streams were planned
+pub async fn run_compare_group<F, Fut>(
+    streams: Vec<CompareStream>,
+    max_concurrency: usize,
+    launch: F,
+) -> Vec<CompareStreamResult>
+where
+    F: Fn(CompareStream) -> Fut,
+    Fut: Future<Output = Result<String, String>>,
+{
+    let mut results: Vec<(usize, CompareStreamResult)> =
+        futures_util::stream::iter(streams.into_iter().enumerate())
+            .map(|(index, stream)| {
+                let model = stream.model.clone();
+                let request_id = stream.request_id.clone();
+                let run = launch(stream);
+                async move {
+                    let error = run.await.err();
+                    (
+                        index,
+                        CompareStreamResult {
+                            model,
+                            request_id,
+                            error,
+                        },
+                    )
+                }
+            })
+            .buffer_unordered(max_concurrency.max(1))
+            .collect()
+            .await;
+    results.sort_by_key(|(index, _)| *index);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+/// Abort every stream still running in the group; returns false if the group is unknown
+pub fn cancel_compare_group(group_id: &str) -> bool {
+    let handle = compare_groups()
+        .lock()
+        .ok()
+        .and_then(|mut groups| groups.remove(group_id));
+    match handle {
+        Some(handle) => {
+            handle.abort();
+            true
+        }
+        None => false,
+    }
+}
+
+#[tauri::command]
+pub async fn llm_stream_compare(
+    window: Window,
+    request: StreamTextRequest,
+    models: Vec<String>,
+    state: State<'_, LlmState>,
+) -> Result<StreamCompareResponse, String> {
+    let group_id = uuid::Uuid::new_v4().to_string();
+    let streams = plan_compare_streams(&group_id, &models)?;
+
+    let (registry, api_keys) = {
+        let registry = state.registry.lock().await;
+        let api_keys = state.api_keys.lock().await;
+        (registry.clone(), api_keys.clone())
+    };
+
+    let planned = streams.clone();
+    let task_group_id = group_id.clone();
+    let handle = tauri::async_runtime::spawn(async move {
+        let results = run_compare_group(planned, MAX_COMPARE_CONCURRENCY, |stream| {
+            let handler = StreamHandler::new(registry.clone(), api_keys.clone());
+            let mut request = request.clone();
+            request.model = stream.model;
+            request.request_id = Some(stream.request_id.clone());
+            // Each model is compared as requested, never substituted
+            request.fallback_models = None;
+            let window = window.clone();
+            async move {
+                handler
+                    .stream_completion(window, request, stream.request_id)
+                    .await
+                    .map_err(String::from)
+            }
+        })
+        .await;
+
+        for result in &results {
+            if let Some(error) = &result.error {
+                log::error!(
+                    "[llm_stream_compare] Stream {} ({}) failed: {}",
+                    result.request_id,
+                    result.model,
+                    error
+                );
+            }
+        }
+        let _ = window.emit(&format!("llm-stream-compare-{}", task_group_id), &results);
+        if let Ok(mut groups) = compare_groups().lock() {
+            groups.remove(&task_group_id);
+        }
+    });
+    if let Ok(mut groups) = compare_groups().lock() {
+        groups.insert(group_id.clone(), handle);
+    }
+
+    Ok(StreamCompareResponse { group_id, streams })
+}
+
+#[tauri::command]
+pub async fn llm_stream_compare_cancel(group_id: String) -> Result<bool, String> {
+    Ok(cancel_compare_group(&group_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    #[test]
+    fn plan_assigns_unique_request_ids_and_dedupes_models() {
+        let models = vec![
+            "gpt-4o".to_string(),
+            "claude-sonnet-4".to_string(),
+            "gpt-4o".to_string(),
+            " ".to_string(),
+        ];
+        let streams = plan_compare_streams("group", &models).unwrap();
+        assert_eq!(
+            streams,
+            vec![
+                CompareStream {
+                    model: "gpt-4o".to_string(),
+                    request_id: "group-0".to_string(),
+                },
+                CompareStream {
+                    model: "claude-sonnet-4".to_string(),
+                    request_id: "group-1".to_string(),
+                },
+            ]
+        );
+        assert!(plan_compare_streams("group", &[]).is_err());
+    }
+
+    #[tokio::test]
+    async fn runs_every_stream_within_concurrency_limit() {
+        let models: Vec<String> = (0..6).map(|i| format!("mock-model-{}", i)).collect();
+        let streams = plan_compare_streams("group", &models).unwrap();
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+        let outputs = Arc::new(Mutex::new(HashMap::new()));
+
+        let results = run_compare_group(streams, 2, |stream| {
+            let in_flight = in_flight.clone();
+            let peak = peak.clone();
+            let outputs = outputs.clone();
+            async move {
+                let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(now, Ordering::SeqCst);
+                // Mock provider: stream a few deltas for this model
+                let mut text = String::new();
+                for chunk in ["Hello", " from ", stream.model.as_str()] {
+                    tokio::time::sleep(Duration::from_millis(5)).await;
+                    text.push_str(chunk);
+                }
+                outputs
+                    .lock()
+                    .unwrap()
+                    .insert(stream.request_id.clone(), text);
+                in_flight.fetch_sub(1, Ordering::SeqCst);
+                if stream.model == "mock-model-3" {
+                    return Err("provider unavailable".to_string());
+                }
+                Ok(stream.request_id)
+            }
+        })
+        .await;
+
+        assert_eq!(results.len(), 6);
+        assert!(peak.load(Ordering::SeqCst) <= 2);
+        let outputs = outputs.lock().unwrap();
+        for (index, result) in results.iter().enumerate() {
+            assert_eq!(result.model, format!("mock-model-{}", index));
+            assert_eq!(
+                outputs.get(&result.request_id).map(String::as_str),
+                Some(format!("Hello from mock-model-{}", index).as_str())
+            );
+        }
+        assert_eq!(results[3].error.as_deref(), Some("provider unavailable"));
+        assert!(results
+            .iter()
+            .enumerate()
+            .all(|(index, result)| index == 3 || result.error.is_none()));
+    }
+
+    #[tokio::test]
+    async fn cancelling_group_aborts_running_streams() {
+        let finished = Arc::new(AtomicUsize::new(0));
+        let streams = plan_compare_streams("cancel", &["a".to_string(), "b".to_string()]).unwrap();
+        let counter = finished.clone();
+        let handle = tauri::async_runtime::spawn(async move {
+            run_compare_group(streams, 2, |stream| {
+                let counter = counter.clone();
+                async move {
+                    tokio::time::sleep(Duration::from_secs(5)).await;
+                    counter.fetch_add(1, Ordering::SeqCst);
+                    Ok(stream.request_id)
+                }
+            })
+            .await;
+        });
+        compare_groups()
+            .lock()
+            .unwrap()
+            .insert("cancel-group".to_string(), handle);
+
+        assert!(cancel_compare_group("cancel-group"));
+        assert!(!cancel_compare_group("cancel-group"));
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert_eq!(finished.load(Ordering::SeqCst), 0);
+    }
+}
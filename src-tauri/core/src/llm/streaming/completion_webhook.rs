@@ -0,0 +1,247 @@
+// Completion webhook for headless integrations. When a request names a
+// `completion_webhook`, a JSON summary of the finished stream is POSTed there
+// once it ends. Delivery is best-effort: failures are logged and never change
+// the stream's own result.
+
+use crate::llm::streaming::stream_error::StreamError;
+use crate::llm::types::StreamEvent;
+use serde::Serialize;
+use std::time::Duration;
+
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Token usage reported by the provider, as in `StreamEvent::Usage`
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct CompletionUsage {
+    pub input_tokens: i32,
+    pub output_tokens: i32,
+    pub total_tokens: Option<i32>,
+    pub cached_input_tokens: Option<i32>,
+    pub cache_creation_input_tokens: Option<i32>,
+}
+
+/// Body POSTed to the completion webhook
+#[derive(Debug, Clone, Default, Serialize, PartialEq)]
+pub struct CompletionSummary {
+    pub request_id: String,
+    pub finish_reason: Option<String>,
+    pub usage: Option<CompletionUsage>,
+    pub error: Option<String>,
+}
+
+impl CompletionSummary {
+    pub fn new(request_id: &str) -> Self {
+        Self {
+            request_id: request_id.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Track the usage and finish reason of an event sent to the client
+    pub fn observe(&mut self, event: &StreamEvent) {
+        match event {
+            StreamEvent::Usage {
+                input_tokens,
+                output_tokens,
+                total_tokens,
+                cached_input_tokens,
+                cache_creation_input_tokens,
+            } => {
+                self.usage = Some(CompletionUsage {
+                    input_tokens: *input_tokens,
+                    output_tokens: *output_tokens,
+                    total_tokens: *total_tokens,
+                    cached_input_tokens: *cached_input_tokens,
+                    cache_creation_input_tokens: *cache_creation_input_tokens,
+                });
+            }
+            StreamEvent::Done { finish_reason } => {
+                self.finish_reason = finish_reason.clone();
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Parse a webhook URL, accepting only http(s)
+pub fn validate_webhook_url(url: &str) -> Result<reqwest::Url, String> {
+    let parsed = reqwest::Url::parse(url.trim())
+        .map_err(|e| format!("Invalid completion webhook URL '{}': {}", url, e))?;
+    match parsed.scheme() {
+        "http" | "https" => Ok(parsed),
+        scheme => Err(format!(
+            "Completion webhook URL must use http or https, got '{}'",
+            scheme
+        )),
+    }
+}
+
+/// POST `summary` to `url`; returns whether the webhook accepted it
+pub async fn notify(url: &reqwest::Url, summary: &CompletionSummary) -> bool {
+    let client = match reqwest::Client::builder().timeout(WEBHOOK_TIMEOUT).build() {
+        Ok(client) => client,
+        Err(e) => {
+            log::warn!("[Completion Webhook] Failed to create HTTP client: {}", e);
+            return false;
+        }
+    };
+    match client.post(url.clone()).json(summary).send().await {
+        Ok(response) if response.status().is_success() => true,
+        Ok(response) => {
+            log::warn!(
+                "[Completion Webhook] {} answered {} for request {}",
+                url,
+                response.status(),
+                summary.request_id
+            );
+            false
+        }
+        Err(e) => {
+            log::warn!(
+                "[Completion Webhook] Failed to notify {} for request {}: {}",
+                url,
+                summary.request_id,
+                e
+            );
+            false
+        }
+    }
+}
+
+/// Notify the webhook, if any, with the outcome of a finished stream and hand
+/// the stream's result back untouched
+pub async fn notify_completion(
+    url: Option<&reqwest::Url>,
+    mut summary: CompletionSummary,
+    result: Result<String, StreamError>,
+) -> Result<String, StreamError> {
+    if let Some(url) = url {
+        if let Err(ref error) = result {
+            summary.error = Some(error.to_string());
+        }
+        notify(url, &summary).await;
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::{json, Value};
+    use std::io::Read;
+
+    /// Answer one request with `status`, capturing the posted body
+    fn serve_once(status: u16) -> (reqwest::Url, std::thread::JoinHandle<Value>) {
+        let server = tiny_http::Server::http("127.0.0.1:0").expect("server");
+        let url = match server.server_addr() {
+            tiny_http::ListenAddr::IP(addr) => format!("http://{}/hook", addr),
+            _ => panic!("Expected IP SocketAddr"),
+        };
+        let handle = std::thread::spawn(move || {
+            let mut request = server.recv().expect("request");
+            let mut body = String::new();
+            request
+                .as_reader()
+                .read_to_string(&mut body)
+                .expect("read body");
+            let _ = request.respond(tiny_http::Response::empty(status));
+            serde_json::from_str(&body).expect("json body")
+        });
+        (validate_webhook_url(&url).expect("url"), handle)
+    }
+
+    fn completed_summary() -> CompletionSummary {
+        let mut summary = CompletionSummary::new("req-1");
+        for event in [
+            StreamEvent::TextDelta {
+                text: "hi".to_string(),
+            },
+            StreamEvent::Usage {
+                input_tokens: 12,
+                output_tokens: 3,
+                total_tokens: Some(15),
+                cached_input_tokens: None,
+                cache_creation_input_tokens: None,
+            },
+            StreamEvent::Done {
+                finish_reason: Some("stop".to_string()),
+            },
+        ] {
+            summary.observe(&event);
+        }
+        summary
+    }
+
+    #[tokio::test]
+    async fn completed_stream_posts_summary_to_webhook() {
+        let (url, server) = serve_once(200);
+
+        let result =
+            notify_completion(Some(&url), completed_summary(), Ok("req-1".to_string())).await;
+        let body = server.join().expect("server join");
+
+        assert_eq!(result.expect("stream result"), "req-1");
+        assert_eq!(
+            body,
+            json!({
+                "request_id": "req-1",
+                "finish_reason": "stop",
+                "usage": {
+                    "input_tokens": 12,
+                    "output_tokens": 3,
+                    "total_tokens": 15,
+                    "cached_input_tokens": null,
+                    "cache_creation_input_tokens": null
+                },
+                "error": null
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn failed_stream_reports_error_to_webhook() {
+        let (url, server) = serve_once(200);
+
+        let result = notify_completion(
+            Some(&url),
+            CompletionSummary::new("req-2"),
+            Err(StreamError::Network("connection reset".to_string())),
+        )
+        .await;
+        let body = server.join().expect("server join");
+
+        assert!(result.is_err());
+        assert_eq!(body["request_id"], "req-2");
+        assert!(body["error"]
+            .as_str()
+            .is_some_and(|error| error.contains("connection reset")));
+    }
+
+    #[tokio::test]
+    async fn webhook_failure_does_not_fail_the_stream() {
+        let (url, server) = serve_once(500);
+        let result =
+            notify_completion(Some(&url), completed_summary(), Ok("req-1".to_string())).await;
+        server.join().expect("server join");
+        assert_eq!(result.expect("stream result"), "req-1");
+
+        // Nothing listening on the port at all
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").expect("bind");
+        let port = listener.local_addr().expect("addr").port();
+        drop(listener);
+        let url = validate_webhook_url(&format!("http://127.0.0.1:{}/hook", port)).expect("url");
+        assert!(!notify(&url, &completed_summary()).await);
+        let result =
+            notify_completion(Some(&url), completed_summary(), Ok("req-1".to_string())).await;
+        assert_eq!(result.expect("stream result"), "req-1");
+    }
+
+    #[test]
+    fn webhook_url_must_be_http() {
+        assert!(validate_webhook_url("https://hooks.example.com/done").is_ok());
+        assert!(validate_webhook_url("http://localhost:8080/done").is_ok());
+        assert!(validate_webhook_url("ftp://example.com/done").is_err());
+        assert!(validate_webhook_url("file:///tmp/done").is_err());
+        assert!(validate_webhook_url("not a url").is_err());
+    }
+}
@@ -0,0 +1,482 @@
+// Request deduplication. A stream started with an `idempotency_key` claims the
+// key; a second request with the same key while the first is streaming (or
+// shortly after it finished) is attached to the first stream instead of calling
+// the provider again. It gets the events sent so far replayed, then every later
+// event as it arrives. Failed streams release their key so a retry goes through.
+
+use crate::llm::streaming::stream_error::StreamError;
+use crate::llm::types::StreamEvent;
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// How long a finished stream still answers duplicates of its key
+const RECENT_WINDOW: Duration = Duration::from_secs(30);
+
+/// Delivers events to an attached request, e.g. by emitting to its window
+pub type EventSink = Box<dyn Fn(&StreamEvent) + Send + Sync>;
+
+struct KeyedStream {
+    request_id: String,
+    events: Vec<StreamEvent>,
+    followers: Vec<EventSink>,
+    finished_at: Option<Instant>,
+}
+
+fn keyed_streams() -> &'static Mutex<HashMap<String, KeyedStream>> {
+    static STREAMS: OnceLock<Mutex<HashMap<String, KeyedStream>>> = OnceLock::new();
+    STREAMS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Outcome of claiming an idempotency key
+pub enum Claim {
+    /// No stream holds the key; the caller runs the stream and feeds the lease
+    Leader(IdempotencyLease),
+    /// The sink was attached to the stream with this request id
+    Attached(String),
+}
+
+/// Claim `key` for `request_id`. If another stream holds it, `sink` gets that
+/// stream's events instead and nothing else needs to run.
+pub fn claim(key: &str, request_id: &str, sink: EventSink) -> Claim {
+    let Ok(mut streams) = keyed_streams().lock() else {
+        // A poisoned map only costs deduplication, never the stream itself
+        return Claim::Leader(IdempotencyLease::detached());
+    };
+    streams.retain(|_, stream| {
+        stream
+            .finished_at
+            .is_none_or(|finished_at| finished_at.elapsed() < RECENT_WINDOW)
+    });
+
+    if let Some(stream) = streams.get_mut(key) {
+        for event in &stream.events {
+            sink(event);
+        }
+        if stream.finished_at.is_none() {
+            stream.followers.push(sink);
+        }
+        return Claim::Attached(stream.request_id.clone());
+    }
+
+    streams.insert(
+        key.to_string(),
+        KeyedStream {
+            request_id: request_id.to_string(),
+            events: Vec::new(),
+            followers: Vec::new(),
+            finished_at: None,
+        },
+    );
+    Claim::Leader(IdempotencyLease {
+        key: Some(key.to_string()),
+    })
+}
+
+/// Held by the stream that owns a key; dropping it unfinished (the task was
+/// aborted) tells attached requests the stream was cancelled
+pub struct IdempotencyLease {
+    key: Option<String>,
+}
+
+impl IdempotencyLease {
+    fn detached() -> Self {
+        Self { key: None }
+    }
+
+    /// Pass an event sent to the leader on to attached requests
+    pub fn forward(&self, event: &StreamEvent) {
+        let Some(key) = self.key.as_deref() else {
+            return;
+        };
+        if let Ok(mut streams) = keyed_streams().lock() {
+            if let Some(stream) = streams.get_mut(key) {
+                for follower in &stream.followers {
+                    follower(event);
+                }
+                stream.events.push(event.clone());
+            }
+        }
+    }
+
+    /// Mark the stream finished. A successful stream keeps answering duplicates
+    /// for a short while; a failed one reports the error and frees the key.
+    pub fn finish(mut self, error: Option<&StreamError>) {
+        self.settle(error);
+    }
+
+    fn settle(&mut self, error: Option<&StreamError>) {
+        let Some(key) = self.key.take() else {
+            return;
+        };
+        let Ok(mut streams) = keyed_streams().lock() else {
+            return;
+        };
+        match error {
+            None => {
+                if let Some(stream) = streams.get_mut(&key) {
+                    stream.followers.clear();
+                    stream.finished_at = Some(Instant::now());
+                }
+            }
+            Some(error) => {
+                if let Some(stream) = streams.remove(&key) {
+                    // Errors the leader emitted were already forwarded
+                    if matches!(stream.events.last(), Some(StreamEvent::Error { .. })) {
+                        return;
+                    }
+                    let event = error_event(error);
+                    for follower in &stream.followers {
+                        follower(&event);
+                    }
+                }
+            }
+        }
+    }
+}
+
+impl Drop for IdempotencyLease {
+    fn drop(&mut self) {
+        self.settle(Some(&StreamError::Cancelled));
+    }
+}
+
+fn error_event(error: &StreamError) -> StreamEvent {
+    StreamEvent::Error {
+        message: error.to_string(),
+        provider_error: None,
+        kind: Some(error.kind()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::Database;
+    use crate::llm::auth::api_key_manager::ApiKeyManager;
+    use crate::llm::providers::provider_registry::ProviderRegistry;
+    use crate::llm::streaming::stream_handler::StreamHandler;
+    use crate::llm::types::{
+        AuthType, Message, MessageContent, ProtocolType, ProviderConfig, StreamTextRequest,
+    };
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use tauri::test::MockRuntime;
+    use tauri::Listener;
+    use tempfile::TempDir;
+
+    fn collecting_sink() -> (EventSink, Arc<Mutex<Vec<StreamEvent>>>) {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let sink_events = events.clone();
+        let sink: EventSink = Box::new(move |event| {
+            sink_events.lock().unwrap().push(event.clone());
+        });
+        (sink, events)
+    }
+
+    fn text(text: &str) -> StreamEvent {
+        StreamEvent::TextDelta {
+            text: text.to_string(),
+        }
+    }
+
+    fn texts(events: &[StreamEvent]) -> Vec<String> {
+        events
+            .iter()
+            .filter_map(|event| match event {
+                StreamEvent::TextDelta { text } => Some(text.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    const TEST_PROVIDER_ID: &str = "idempotency_local";
+
+    /// OpenAI-compatible provider stand-in answering every request with `status`
+    /// and `body`, after a delay so a duplicate can arrive mid-stream
+    fn counting_provider(status: u16, body: &'static str) -> (String, Arc<AtomicUsize>) {
+        let server = tiny_http::Server::http("127.0.0.1:0").expect("server");
+        let base_url = match server.server_addr() {
+            tiny_http::ListenAddr::IP(addr) => format!("http://{}/v1", addr),
+            _ => panic!("Expected IP SocketAddr"),
+        };
+        let hits = Arc::new(AtomicUsize::new(0));
+        let server_hits = hits.clone();
+        std::thread::spawn(move || {
+            for request in server.incoming_requests() {
+                server_hits.fetch_add(1, Ordering::SeqCst);
+                std::thread::sleep(Duration::from_millis(200));
+                let response = tiny_http::Response::from_string(body)
+                    .with_status_code(status)
+                    .with_header(
+                        tiny_http::Header::from_bytes(
+                            &b"Content-Type"[..],
+                            &b"text/event-stream"[..],
+                        )
+                        .unwrap(),
+                    );
+                let _ = request.respond(response);
+            }
+        });
+        (base_url, hits)
+    }
+
+    async fn test_handler(base_url: String) -> (TempDir, StreamHandler) {
+        let dir = TempDir::new().expect("temp dir");
+        let db_path = dir.path().join("idempotency.db");
+        let db = Arc::new(Database::new(db_path.to_string_lossy().to_string()));
+        db.connect().await.expect("db connect");
+        db.execute(
+            "CREATE TABLE IF NOT EXISTS settings (key TEXT PRIMARY KEY, value TEXT, updated_at INTEGER)",
+            vec![],
+        )
+        .await
+        .expect("create settings");
+        let api_keys = ApiKeyManager::new(db, dir.path().to_path_buf());
+        let registry = ProviderRegistry::new(vec![ProviderConfig {
+            id: TEST_PROVIDER_ID.to_string(),
+            name: "Idempotency Local".to_string(),
+            protocol: ProtocolType::OpenAiCompatible,
+            base_url,
+            api_key_name: "IDEMPOTENCY_LOCAL_API_KEY".to_string(),
+            supports_oauth: false,
+            supports_coding_plan: false,
+            supports_international: false,
+            coding_plan_base_url: None,
+            international_base_url: None,
+            headers: None,
+            extra_body: None,
+            auth_type: AuthType::None,
+            body_transforms: None,
+        }]);
+        (dir, StreamHandler::new(registry, api_keys))
+    }
+
+    fn keyed_request(key: &str) -> StreamTextRequest {
+        StreamTextRequest {
+            model: format!("test-model@{}", TEST_PROVIDER_ID),
+            messages: vec![Message::User {
+                content: MessageContent::Text("hi".to_string()),
+                provider_options: None,
+            }],
+            tools: None,
+            stream: Some(true),
+            temperature: None,
+            max_tokens: None,
+            top_p: None,
+            top_k: None,
+            provider_options: None,
+            request_id: None,
+            trace_context: None,
+            partial_json: Some(true),
+            fallback_models: None,
+            logprobs: None,
+            top_logprobs: None,
+            estimate_usage: true,
+            metadata: None,
+            resume_on_disconnect: false,
+            allowed_tools: None,
+            stream_tool_call_deltas: false,
+            completion_webhook: None,
+            persist_to_session: None,
+            idempotency_key: Some(key.to_string()),
+        }
+    }
+
+    fn mock_window() -> (tauri::App<MockRuntime>, tauri::Window<MockRuntime>) {
+        let app = tauri::test::mock_app();
+        let webview = tauri::WebviewWindowBuilder::new(
+            &app,
+            "idempotency-test",
+            tauri::WebviewUrl::App("index.html".into()),
+        )
+        .build()
+        .unwrap();
+        let window = webview.as_ref().window();
+        (app, window)
+    }
+
+    /// Collect the events `stream_completion` emits for `request_id`
+    fn stream_events(
+        app: &tauri::App<MockRuntime>,
+        request_id: &str,
+    ) -> Arc<Mutex<Vec<StreamEvent>>> {
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let listener_events = events.clone();
+        app.listen_any(format!("llm-stream-{}", request_id), move |event| {
+            let event: StreamEvent = serde_json::from_str(event.payload()).expect("stream event");
+            listener_events.lock().unwrap().push(event);
+        });
+        events
+    }
+
+    fn as_json(events: &Arc<Mutex<Vec<StreamEvent>>>) -> Vec<serde_json::Value> {
+        events
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|event| serde_json::to_value(event).unwrap())
+            .collect()
+    }
+
+    /// This test uses Tauri test infrastructure that may not work on Windows CI
+    #[tokio::test]
+    #[cfg(not(target_os = "windows"))]
+    async fn duplicate_key_shares_a_single_provider_request() {
+        let (base_url, hits) = counting_provider(
+            200,
+            concat!(
+                "data: {\"choices\":[{\"index\":0,\"delta\":{\"content\":\"{\\\"greeting\\\":\"}}]}\n\n",
+                "data: {\"choices\":[{\"index\":0,\"delta\":{\"content\":\"\\\"hello\\\"}\"},\"finish_reason\":\"stop\"}]}\n\n",
+                "data: [DONE]\n\n",
+            ),
+        );
+        let (_dir, handler) = test_handler(base_url).await;
+        let (app, window) = mock_window();
+        let leader_events = stream_events(&app, "idem-1");
+        let attached_events = stream_events(&app, "idem-2");
+
+        let first = handler.stream_completion(
+            window.clone(),
+            keyed_request("dup-key-1"),
+            "idem-1".to_string(),
+        );
+        let second = async {
+            // Arrive while the first request is waiting on the provider
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            handler
+                .stream_completion(
+                    window.clone(),
+                    keyed_request("dup-key-1"),
+                    "idem-2".to_string(),
+                )
+                .await
+        };
+        let (first, second) = tokio::join!(first, second);
+        assert_eq!(first.expect("first stream"), "idem-1");
+        assert_eq!(second.expect("second stream"), "idem-2");
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+
+        // The attached request sees exactly what the leader's window saw,
+        // including the JsonPartial and UsageDelta events
+        let attached = attached_events.lock().unwrap().clone();
+        assert_eq!(texts(&attached), vec!["{\"greeting\":", "\"hello\"}"]);
+        assert!(attached
+            .iter()
+            .any(|event| matches!(event, StreamEvent::JsonPartial { .. })));
+        assert!(attached
+            .iter()
+            .any(|event| matches!(event, StreamEvent::UsageDelta { .. })));
+        assert!(matches!(attached.last(), Some(StreamEvent::Done { .. })));
+        assert_eq!(as_json(&attached_events), as_json(&leader_events));
+
+        // Shortly after the stream finished the key still answers from the replay
+        let late_events = stream_events(&app, "idem-3");
+        handler
+            .stream_completion(
+                window.clone(),
+                keyed_request("dup-key-1"),
+                "idem-3".to_string(),
+            )
+            .await
+            .expect("late stream");
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+        assert_eq!(as_json(&late_events), as_json(&leader_events));
+
+        // A different key is a different request
+        handler
+            .stream_completion(window, keyed_request("dup-key-2"), "idem-4".to_string())
+            .await
+            .expect("other stream");
+        assert_eq!(hits.load(Ordering::SeqCst), 2);
+    }
+
+    /// This test uses Tauri test infrastructure that may not work on Windows CI
+    #[tokio::test]
+    #[cfg(not(target_os = "windows"))]
+    async fn attached_request_gets_the_leaders_error_once() {
+        let (base_url, hits) = counting_provider(500, r#"{"error":{"message":"overloaded"}}"#);
+        let (_dir, handler) = test_handler(base_url).await;
+        let (app, window) = mock_window();
+        let leader_events = stream_events(&app, "idem-5");
+        let attached_events = stream_events(&app, "idem-6");
+
+        let first = handler.stream_completion(
+            window.clone(),
+            keyed_request("dup-key-5"),
+            "idem-5".to_string(),
+        );
+        let second = async {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            handler
+                .stream_completion(
+                    window.clone(),
+                    keyed_request("dup-key-5"),
+                    "idem-6".to_string(),
+                )
+                .await
+        };
+        let (first, second) = tokio::join!(first, second);
+        assert!(first.is_err());
+        second.expect("attached request");
+        assert_eq!(hits.load(Ordering::SeqCst), 1);
+
+        let attached = attached_events.lock().unwrap().clone();
+        let errors = attached
+            .iter()
+            .filter(|event| matches!(event, StreamEvent::Error { .. }))
+            .count();
+        assert_eq!(errors, 1);
+        assert_eq!(as_json(&attached_events), as_json(&leader_events));
+    }
+
+    #[test]
+    fn attached_request_joins_mid_stream_and_sees_failure() {
+        let (leader_sink, _) = collecting_sink();
+        let Claim::Leader(lease) = claim("dup-key-3", "req-1", leader_sink) else {
+            panic!("Expected to lead a fresh key");
+        };
+        lease.forward(&text("partial"));
+
+        let (sink, events) = collecting_sink();
+        match claim("dup-key-3", "req-2", sink) {
+            Claim::Attached(request_id) => assert_eq!(request_id, "req-1"),
+            Claim::Leader(_) => panic!("Expected to attach to req-1"),
+        }
+        lease.forward(&text(" answer"));
+        lease.finish(Some(&StreamError::Network("reset".to_string())));
+
+        let events = events.lock().unwrap();
+        assert_eq!(texts(&events), vec!["partial", " answer"]);
+        assert!(matches!(events.last(), Some(StreamEvent::Error { .. })));
+
+        // The failed stream released its key, so a retry runs for real
+        let (retry_sink, _) = collecting_sink();
+        assert!(matches!(
+            claim("dup-key-3", "req-3", retry_sink),
+            Claim::Leader(_)
+        ));
+    }
+
+    #[test]
+    fn dropped_lease_cancels_attached_requests() {
+        let (leader_sink, _) = collecting_sink();
+        let Claim::Leader(lease) = claim("dup-key-4", "req-1", leader_sink) else {
+            panic!("Expected to lead a fresh key");
+        };
+        let (sink, events) = collecting_sink();
+        assert!(matches!(
+            claim("dup-key-4", "req-2", sink),
+            Claim::Attached(_)
+        ));
+
+        drop(lease);
+
+        match events.lock().unwrap().last() {
+            Some(StreamEvent::Error { kind, .. }) => {
+                assert_eq!(*kind, Some(StreamError::Cancelled.kind()))
+            }
+            other => panic!("Expected cancellation error, got {:?}", other),
+        }
+    }
+}
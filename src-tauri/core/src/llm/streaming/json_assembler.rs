@@ -0,0 +1,241 @@
+use crate::llm::types::StreamEvent;
+use serde_json::Value;
+
+/// Incrementally assembles a JSON document from streamed text deltas and
+/// produces best-effort parses of the partial object for progressive rendering.
+///
+/// Partials are only attempted when a delta contains a structural boundary
+/// (`,` `}` `]` or `"`), and only emitted when the parsed value changed.
+#[derive(Debug, Default)]
+pub struct JsonStreamAssembler {
+    buffer: String,
+    last_emitted: Option<Value>,
+}
+
+impl JsonStreamAssembler {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feed a text delta; returns a `JsonPartial` event when the partial value changed
+    pub fn push(&mut self, delta: &str) -> Option<StreamEvent> {
+        self.buffer.push_str(delta);
+        if !delta.contains([',', '}', ']', '"']) {
+            return None;
+        }
+        let value = parse_partial_json(&self.buffer)?;
+        self.emit_if_changed(value)
+    }
+
+    /// Parse the complete buffer once the text stream has ended
+    pub fn finish(&mut self) -> Option<StreamEvent> {
+        let document = json_document(&self.buffer)?;
+        let value = serde_json::from_str::<Value>(document.trim())
+            .ok()
+            .or_else(|| parse_partial_json(&self.buffer))?;
+        self.emit_if_changed(value)
+    }
+
+    fn emit_if_changed(&mut self, value: Value) -> Option<StreamEvent> {
+        if self.last_emitted.as_ref() == Some(&value) {
+            return None;
+        }
+        self.last_emitted = Some(value.clone());
+        Some(StreamEvent::JsonPartial { value })
+    }
+}
+
+/// Skip any preamble (e.g. a ```json fence) before the first object or array
+fn json_document(text: &str) -> Option<&str> {
+    let start = text.find(['{', '['])?;
+    Some(&text[start..])
+}
+
+struct Frame {
+    is_object: bool,
+    expect_key: bool,
+}
+
+fn closers(stack: &[Frame]) -> String {
+    stack
+        .iter()
+        .rev()
+        .map(|frame| if frame.is_object { '}' } else { ']' })
+        .collect()
+}
+
+/// Best-effort parse of a truncated JSON document.
+///
+/// Tries the text closed as-is first, then falls back to the last position
+/// where every value was complete. A value string that is still streaming is
+/// kept (closed at its last whole character); an unfinished key is dropped.
+pub fn parse_partial_json(text: &str) -> Option<Value> {
+    let text = json_document(text)?;
+
+    let mut stack: Vec<Frame> = Vec::new();
+    let mut safe_point: Option<(usize, String)> = None;
+    let mut in_string = false;
+    let mut string_is_key = false;
+    let mut escape_remaining = 0usize;
+    // End of the last fully decoded character inside the current value string
+    let mut string_clean_end = 0usize;
+
+    for (i, ch) in text.char_indices() {
+        let next = i + ch.len_utf8();
+        if in_string {
+            if escape_remaining > 0 {
+                if escape_remaining == usize::MAX {
+                    // Character right after the backslash
+                    escape_remaining = if ch == 'u' { 4 } else { 0 };
+                } else {
+                    escape_remaining -= 1;
+                }
+                if escape_remaining == 0 {
+                    string_clean_end = next;
+                }
+                continue;
+            }
+            match ch {
+                '\\' => escape_remaining = usize::MAX,
+                '"' => {
+                    in_string = false;
+                    if !string_is_key {
+                        safe_point = Some((next, closers(&stack)));
+                    }
+                }
+                _ => string_clean_end = next,
+            }
+            continue;
+        }
+
+        match ch {
+            '"' => {
+                in_string = true;
+                string_is_key = stack
+                    .last()
+                    .is_some_and(|frame| frame.is_object && frame.expect_key);
+                string_clean_end = next;
+            }
+            '{' | '[' => {
+                stack.push(Frame {
+                    is_object: ch == '{',
+                    expect_key: ch == '{',
+                });
+                safe_point = Some((next, closers(&stack)));
+            }
+            '}' | ']' => {
+                stack.pop();
+                safe_point = Some((next, closers(&stack)));
+                if stack.is_empty() {
+                    // Ignore anything trailing the top-level document
+                    return serde_json::from_str(&text[..next]).ok();
+                }
+            }
+            ':' => {
+                if let Some(frame) = stack.last_mut() {
+                    frame.expect_key = false;
+                }
+            }
+            ',' => {
+                safe_point = Some((i, closers(&stack)));
+                if let Some(frame) = stack.last_mut() {
+                    frame.expect_key = frame.is_object;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if in_string && !string_is_key {
+        let candidate = format!("{}\"{}", &text[..string_clean_end], closers(&stack));
+        if let Ok(value) = serde_json::from_str(&candidate) {
+            return Some(value);
+        }
+    }
+
+    if !in_string {
+        let candidate = format!("{}{}", text.trim_end(), closers(&stack));
+        if let Ok(value) = serde_json::from_str(&candidate) {
+            return Some(value);
+        }
+    }
+
+    let (end, closing) = safe_point?;
+    serde_json::from_str(&format!("{}{}", &text[..end], closing)).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn partial_values(events: Vec<Option<StreamEvent>>) -> Vec<Value> {
+        events
+            .into_iter()
+            .flatten()
+            .map(|event| match event {
+                StreamEvent::JsonPartial { value } => value,
+                other => panic!("unexpected event: {:?}", other),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn parses_truncated_documents() {
+        assert_eq!(parse_partial_json(r#"{"a": 1, "b"#), Some(json!({"a": 1})));
+        assert_eq!(
+            parse_partial_json(r#"{"a": 1, "b":"#),
+            Some(json!({"a": 1}))
+        );
+        assert_eq!(
+            parse_partial_json(r#"{"title": "Hel"#),
+            Some(json!({"title": "Hel"}))
+        );
+        assert_eq!(
+            parse_partial_json(r#"{"items": [1, 2, {"x": tr"#),
+            Some(json!({"items": [1, 2, {}]}))
+        );
+        assert_eq!(
+            parse_partial_json(r#"{"s": "line\u00"#),
+            Some(json!({"s": "line"}))
+        );
+        assert_eq!(
+            parse_partial_json("```json\n{\"ok\": true}\n```"),
+            Some(json!({"ok": true}))
+        );
+        assert_eq!(parse_partial_json("no json here"), None);
+    }
+
+    #[test]
+    fn emits_partials_and_complete_final_object() {
+        let deltas = [
+            "```json\n{\"name\": \"Ta",
+            "lk\", \"tags\": [\"a\"",
+            ", \"b\"], \"count\"",
+            ": 4",
+            "2}\n```",
+        ];
+        let mut assembler = JsonStreamAssembler::new();
+        let mut events: Vec<Option<StreamEvent>> =
+            deltas.iter().map(|delta| assembler.push(delta)).collect();
+        events.push(assembler.finish());
+
+        let values = partial_values(events);
+        assert_eq!(
+            values,
+            vec![
+                json!({"name": "Ta"}),
+                json!({"name": "Talk", "tags": ["a"]}),
+                json!({"name": "Talk", "tags": ["a", "b"]}),
+                json!({"name": "Talk", "tags": ["a", "b"], "count": 42}),
+            ]
+        );
+    }
+
+    #[test]
+    fn finish_does_not_repeat_unchanged_value() {
+        let mut assembler = JsonStreamAssembler::new();
+        assert!(assembler.push(r#"{"done": true}"#).is_some());
+        assert!(assembler.finish().is_none());
+    }
+}
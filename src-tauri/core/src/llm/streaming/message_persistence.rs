@@ -0,0 +1,293 @@
+// Durable assistant messages. A stream started with `persist_to_session`
+// assembles its assistant message as events are emitted and publishes
+// throttled snapshots of it; chat history subscribes and upserts them, so a
+// crash mid-stream keeps everything up to the last flush.
+
+use crate::llm::types::StreamEvent;
+use crate::storage::{ChatHistoryRepository, Message, MessageContent, MessageRole, ToolCall};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+
+/// Shortest gap between two snapshots of the same stream while text streams in
+const DEFAULT_FLUSH_INTERVAL: Duration = Duration::from_millis(500);
+
+/// The assistant message of one stream as far as it has arrived
+#[derive(Debug, Clone)]
+pub struct StreamMessageSnapshot {
+    pub session_id: String,
+    /// Text reply followed by the tool calls, each only when present
+    pub messages: Vec<Message>,
+}
+
+fn snapshot_sink() -> &'static Mutex<Option<UnboundedSender<StreamMessageSnapshot>>> {
+    static SINK: OnceLock<Mutex<Option<UnboundedSender<StreamMessageSnapshot>>>> = OnceLock::new();
+    SINK.get_or_init(|| Mutex::new(None))
+}
+
+/// Start receiving message snapshots; replaces any previous subscriber
+pub fn subscribe_message_snapshots() -> UnboundedReceiver<StreamMessageSnapshot> {
+    let (tx, rx) = unbounded_channel();
+    if let Ok(mut sink) = snapshot_sink().lock() {
+        *sink = Some(tx);
+    }
+    rx
+}
+
+/// Publish a snapshot; returns false when nobody is listening
+pub fn publish_message_snapshot(snapshot: StreamMessageSnapshot) -> bool {
+    snapshot_sink()
+        .lock()
+        .ok()
+        .and_then(|sink| sink.as_ref().map(|tx| tx.send(snapshot).is_ok()))
+        .unwrap_or(false)
+}
+
+/// Write a snapshot to chat history, replacing earlier snapshots of the same stream
+pub async fn persist_snapshot(
+    chat_history: &ChatHistoryRepository,
+    snapshot: &StreamMessageSnapshot,
+) -> Result<(), String> {
+    for message in &snapshot.messages {
+        chat_history.upsert_message(message).await?;
+    }
+    Ok(())
+}
+
+/// Builds the assistant message from emitted events and decides when to flush
+pub struct StreamMessageAssembler {
+    session_id: String,
+    message_id: String,
+    created_at: i64,
+    text: String,
+    tool_calls: Vec<ToolCall>,
+    flush_interval: Duration,
+    last_flush: Instant,
+    dirty: bool,
+}
+
+impl StreamMessageAssembler {
+    pub fn new(session_id: String) -> Self {
+        Self {
+            session_id,
+            message_id: format!("msg_{}", uuid::Uuid::new_v4()),
+            created_at: chrono::Utc::now().timestamp(),
+            text: String::new(),
+            tool_calls: Vec::new(),
+            flush_interval: DEFAULT_FLUSH_INTERVAL,
+            last_flush: Instant::now(),
+            dirty: false,
+        }
+    }
+
+    pub fn with_flush_interval(mut self, flush_interval: Duration) -> Self {
+        self.flush_interval = flush_interval;
+        self
+    }
+
+    /// Fold in an event sent to the client; returns a snapshot when one is due.
+    /// Text is throttled, while tool calls and `Done` flush right away.
+    pub fn observe(&mut self, event: &StreamEvent) -> Option<StreamMessageSnapshot> {
+        let flush_now = match event {
+            StreamEvent::TextDelta { text } => {
+                self.text.push_str(text);
+                self.dirty = true;
+                self.last_flush.elapsed() >= self.flush_interval
+            }
+            StreamEvent::ToolCall {
+                tool_call_id,
+                tool_name,
+                input,
+                ..
+            } => {
+                self.tool_calls.push(ToolCall {
+                    id: tool_call_id.clone(),
+                    name: tool_name.clone(),
+                    input: input.clone(),
+                });
+                self.dirty = true;
+                true
+            }
+            StreamEvent::Done { .. } => true,
+            _ => false,
+        };
+        if flush_now {
+            self.flush()
+        } else {
+            None
+        }
+    }
+
+    /// Snapshot of whatever has not been flushed yet, e.g. when the stream fails
+    pub fn flush(&mut self) -> Option<StreamMessageSnapshot> {
+        if !self.dirty {
+            return None;
+        }
+        self.dirty = false;
+        self.last_flush = Instant::now();
+
+        let message = |id: String, content| Message {
+            id,
+            session_id: self.session_id.clone(),
+            role: MessageRole::Assistant,
+            content,
+            created_at: self.created_at,
+            tool_call_id: None,
+            parent_id: None,
+        };
+        let mut messages = Vec::new();
+        if !self.text.is_empty() {
+            messages.push(message(
+                self.message_id.clone(),
+                MessageContent::Text {
+                    text: self.text.clone(),
+                },
+            ));
+        }
+        if !self.tool_calls.is_empty() {
+            // The suffix keeps the tool calls ordered after the text they follow
+            messages.push(message(
+                format!("{}_tools", self.message_id),
+                MessageContent::ToolCalls {
+                    calls: self.tool_calls.clone(),
+                },
+            ));
+        }
+        Some(StreamMessageSnapshot {
+            session_id: self.session_id.clone(),
+            messages,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::{Session, SessionStatus, Storage};
+    use serde_json::json;
+    use tempfile::TempDir;
+
+    async fn storage_with_session(dir: &TempDir, session_id: &str) -> Storage {
+        let storage = Storage::new(dir.path().to_path_buf(), dir.path().join("attachments"))
+            .await
+            .expect("storage");
+        let now = chrono::Utc::now().timestamp();
+        storage
+            .chat_history
+            .create_session(&Session {
+                id: session_id.to_string(),
+                project_id: None,
+                title: None,
+                status: SessionStatus::Running,
+                created_at: now,
+                updated_at: now,
+                pinned_at: None,
+                last_event_id: None,
+                metadata: None,
+            })
+            .await
+            .expect("create session");
+        storage
+    }
+
+    fn text(text: &str) -> StreamEvent {
+        StreamEvent::TextDelta {
+            text: text.to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn finished_stream_persists_full_message_despite_throttled_flushes() {
+        let dir = TempDir::new().expect("temp dir");
+        let storage = storage_with_session(&dir, "session-1").await;
+        // Nothing is due before `Done`, so the text deltas flush only once at the end
+        let mut assembler = StreamMessageAssembler::new("session-1".to_string())
+            .with_flush_interval(Duration::from_secs(3600));
+
+        let events = [
+            text("Let me "),
+            text("check."),
+            StreamEvent::ToolCall {
+                tool_call_id: "call_1".to_string(),
+                tool_name: "readFile".to_string(),
+                input: json!({ "path": "README.md" }),
+                provider_metadata: None,
+            },
+            text(" Done"),
+            StreamEvent::Done {
+                finish_reason: Some("tool_calls".to_string()),
+            },
+        ];
+        let mut flushes = 0;
+        for event in &events {
+            if let Some(snapshot) = assembler.observe(event) {
+                flushes += 1;
+                persist_snapshot(&storage.chat_history, &snapshot)
+                    .await
+                    .expect("persist");
+            }
+        }
+        // One partial flush for the tool call, one final flush for `Done`
+        assert_eq!(flushes, 2);
+
+        let messages = storage
+            .chat_history
+            .get_messages("session-1", None, None)
+            .await
+            .expect("messages");
+        assert_eq!(messages.len(), 2);
+        assert!(matches!(
+            &messages[0].content,
+            MessageContent::Text { text } if text == "Let me check. Done"
+        ));
+        assert_eq!(messages[0].role, MessageRole::Assistant);
+        match &messages[1].content {
+            MessageContent::ToolCalls { calls } => {
+                assert_eq!(calls.len(), 1);
+                assert_eq!(calls[0].id, "call_1");
+                assert_eq!(calls[0].input, json!({ "path": "README.md" }));
+            }
+            other => panic!("Unexpected content: {:?}", other),
+        }
+    }
+
+    #[tokio::test]
+    async fn interrupted_stream_keeps_last_partial_flush() {
+        let dir = TempDir::new().expect("temp dir");
+        let storage = storage_with_session(&dir, "session-2").await;
+        let mut assembler = StreamMessageAssembler::new("session-2".to_string())
+            .with_flush_interval(Duration::ZERO);
+
+        for event in [text("partial "), text("answer")] {
+            let snapshot = assembler.observe(&event).expect("flush due");
+            persist_snapshot(&storage.chat_history, &snapshot)
+                .await
+                .expect("persist");
+        }
+        assert!(assembler.flush().is_none());
+
+        let messages = storage
+            .chat_history
+            .get_messages("session-2", None, None)
+            .await
+            .expect("messages");
+        assert_eq!(messages.len(), 1);
+        assert!(matches!(
+            &messages[0].content,
+            MessageContent::Text { text } if text == "partial answer"
+        ));
+    }
+
+    #[tokio::test]
+    async fn subscriber_receives_snapshots() {
+        let mut rx = subscribe_message_snapshots();
+        let mut assembler = StreamMessageAssembler::new("session-3".to_string())
+            .with_flush_interval(Duration::ZERO);
+        let snapshot = assembler.observe(&text("hi")).expect("flush due");
+
+        assert!(publish_message_snapshot(snapshot));
+        let received = rx.recv().await.expect("snapshot");
+        assert_eq!(received.session_id, "session-3");
+        assert_eq!(received.messages.len(), 1);
+    }
+}
@@ -1 +1,14 @@
+pub mod circuit_breaker;
+pub mod compare;
+pub mod completion_webhook;
+pub mod idempotency;
+pub mod json_assembler;
+pub mod message_persistence;
+pub mod provider_error;
+pub mod request_log;
+pub mod stream_error;
 pub mod stream_handler;
+pub mod stream_log;
+pub mod stream_registry;
+pub mod usage_estimator;
+pub mod usage_report;
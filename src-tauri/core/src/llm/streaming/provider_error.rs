@@ -0,0 +1,116 @@
+use crate::llm::types::ProviderError;
+use serde_json::Value;
+
+/// Parse a provider error body into a `ProviderError`.
+///
+/// Understands the common envelopes:
+/// - OpenAI-compatible: `{ "error": { "message", "type", "code" } }`
+/// - Anthropic: `{ "type": "error", "error": { "type", "message" } }`
+/// - Google: `{ "error": { "code": 400, "message", "status" } }`
+/// - Flat: `{ "error": "..." }`, `{ "message": "..." }` or `{ "detail": "..." }`
+///
+/// Falls back to the raw (trimmed) body as the message when it is not JSON
+/// or does not match any known shape.
+pub fn parse_provider_error(body: &str) -> ProviderError {
+    let trimmed = body.trim();
+    serde_json::from_str::<Value>(trimmed)
+        .ok()
+        .and_then(|value| provider_error_from_value(&value))
+        .unwrap_or_else(|| ProviderError {
+            message: trimmed.to_string(),
+            error_type: None,
+            code: None,
+        })
+}
+
+/// Extract a `ProviderError` from an already-parsed JSON error envelope
+pub fn provider_error_from_value(value: &Value) -> Option<ProviderError> {
+    // Some gateways wrap the envelope in an array
+    if let Some(first) = value.as_array().and_then(|items| items.first()) {
+        return provider_error_from_value(first);
+    }
+
+    match value.get("error") {
+        Some(Value::Object(error)) => {
+            let message = error.get("message").and_then(scalar_to_string)?;
+            let error_type = error
+                .get("type")
+                .or_else(|| error.get("status"))
+                .and_then(scalar_to_string);
+            let code = error.get("code").and_then(scalar_to_string);
+            Some(ProviderError {
+                message,
+                error_type,
+                code,
+            })
+        }
+        Some(Value::String(message)) => Some(ProviderError {
+            message: message.clone(),
+            error_type: value.get("type").and_then(scalar_to_string),
+            code: value.get("code").and_then(scalar_to_string),
+        }),
+        _ => {
+            let message = value
+                .get("message")
+                .or_else(|| value.get("detail"))
+                .and_then(scalar_to_string)?;
+            Some(ProviderError {
+                message,
+                error_type: value.get("type").and_then(scalar_to_string),
+                code: value.get("code").and_then(scalar_to_string),
+            })
+        }
+    }
+}
+
+fn scalar_to_string(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) if !s.is_empty() => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_openai_error_envelope() {
+        let body = r#"{"error":{"message":"Rate limit reached","type":"requests","param":null,"code":"rate_limit_exceeded"}}"#;
+        let error = parse_provider_error(body);
+        assert_eq!(error.message, "Rate limit reached");
+        assert_eq!(error.error_type.as_deref(), Some("requests"));
+        assert_eq!(error.code.as_deref(), Some("rate_limit_exceeded"));
+    }
+
+    #[test]
+    fn parses_anthropic_error_envelope() {
+        let body = r#"{"type":"error","error":{"type":"overloaded_error","message":"Overloaded"}}"#;
+        let error = parse_provider_error(body);
+        assert_eq!(error.message, "Overloaded");
+        assert_eq!(error.error_type.as_deref(), Some("overloaded_error"));
+        assert_eq!(error.code, None);
+    }
+
+    #[test]
+    fn parses_google_error_envelope_with_numeric_code() {
+        let body =
+            r#"[{"error":{"code":400,"message":"API key not valid","status":"INVALID_ARGUMENT"}}]"#;
+        let error = parse_provider_error(body);
+        assert_eq!(error.message, "API key not valid");
+        assert_eq!(error.error_type.as_deref(), Some("INVALID_ARGUMENT"));
+        assert_eq!(error.code.as_deref(), Some("400"));
+    }
+
+    #[test]
+    fn falls_back_to_raw_text_for_non_json_body() {
+        let error = parse_provider_error("  <html>502 Bad Gateway</html>\n");
+        assert_eq!(error.message, "<html>502 Bad Gateway</html>");
+        assert_eq!(error.error_type, None);
+        assert_eq!(error.code, None);
+
+        let unknown = parse_provider_error(r#"{"status":"down"}"#);
+        assert_eq!(unknown.message, r#"{"status":"down"}"#);
+    }
+}
@@ -0,0 +1,126 @@
+use crate::llm::auth::api_key_manager::ApiKeyManager;
+use crate::llm::testing::recorder::redact_headers;
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Settings key holding the verbose-logging flag for a provider
+pub fn verbose_logging_setting_key(provider_id: &str) -> String {
+    format!("{}_verbose_logging", provider_id)
+}
+
+/// Decides how much of a provider's request/response traffic ends up in the logs.
+/// Redacted by default; a provider can opt into full logging via its settings flag.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ProviderLogPolicy {
+    verbose: bool,
+}
+
+impl ProviderLogPolicy {
+    pub fn new(verbose: bool) -> Self {
+        Self { verbose }
+    }
+
+    pub async fn load(api_keys: &ApiKeyManager, provider_id: &str) -> Self {
+        let value = match api_keys
+            .get_setting(&verbose_logging_setting_key(provider_id))
+            .await
+        {
+            Ok(value) => value,
+            Err(e) => {
+                log::warn!(
+                    "Failed to read verbose logging flag for {}: {}",
+                    provider_id,
+                    e
+                );
+                None
+            }
+        };
+        Self::new(matches!(
+            value.as_deref().map(str::trim),
+            Some("true") | Some("1")
+        ))
+    }
+
+    pub fn is_verbose(&self) -> bool {
+        self.verbose
+    }
+
+    /// Credentials in the headers are redacted even in verbose mode
+    pub fn request(&self, url: &str, headers: &HashMap<String, String>, body: &Value) -> String {
+        let mut headers: Vec<_> = redact_headers(headers).into_iter().collect();
+        headers.sort();
+        let body = if self.verbose {
+            body.to_string()
+        } else {
+            Self::redacted(body.to_string().len())
+        };
+        format!("POST {} headers={:?} body={}", url, headers, body)
+    }
+
+    pub fn response_body(&self, text: &str) -> String {
+        if self.verbose {
+            text.to_string()
+        } else {
+            Self::redacted(text.len())
+        }
+    }
+
+    pub fn sse_event(&self, raw: &str) -> String {
+        self.response_body(raw)
+    }
+
+    fn redacted(len: usize) -> String {
+        format!("<redacted {} bytes>", len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::Database;
+    use serde_json::json;
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn log_builder_respects_per_provider_flag() {
+        let dir = TempDir::new().expect("temp dir");
+        let db_path = dir.path().join("llm-settings.db");
+        let db = Arc::new(Database::new(db_path.to_string_lossy().to_string()));
+        db.connect().await.expect("db connect");
+        db.execute(
+            "CREATE TABLE IF NOT EXISTS settings (key TEXT PRIMARY KEY, value TEXT, updated_at INTEGER)",
+            vec![],
+        )
+        .await
+        .expect("create settings");
+        let api_keys = ApiKeyManager::new(db, std::path::PathBuf::from("/tmp"));
+        api_keys
+            .set_setting(&verbose_logging_setting_key("openai"), "true")
+            .await
+            .expect("set flag");
+
+        let verbose = ProviderLogPolicy::load(&api_keys, "openai").await;
+        let redacted = ProviderLogPolicy::load(&api_keys, "anthropic").await;
+        assert!(verbose.is_verbose());
+        assert!(!redacted.is_verbose());
+
+        let mut headers = HashMap::new();
+        headers.insert("Authorization".to_string(), "Bearer sk-secret".to_string());
+        let body = json!({ "messages": [{ "role": "user", "content": "hello there" }] });
+
+        let verbose_line = verbose.request("https://api.example.com/v1", &headers, &body);
+        assert!(!verbose_line.contains("sk-secret"));
+        assert!(verbose_line.contains("REDACTED"));
+        assert!(verbose_line.contains("hello there"));
+
+        let redacted_line = redacted.request("https://api.example.com/v1", &headers, &body);
+        assert!(!redacted_line.contains("sk-secret"));
+        assert!(!redacted_line.contains("hello there"));
+        assert!(redacted_line.contains("REDACTED"));
+        assert!(redacted_line.contains("<redacted"));
+
+        assert_eq!(verbose.sse_event("data: hi"), "data: hi");
+        assert_eq!(redacted.sse_event("data: hi"), "<redacted 8 bytes>");
+    }
+}
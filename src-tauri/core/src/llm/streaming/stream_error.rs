@@ -0,0 +1,193 @@
+use crate::llm::types::StreamErrorKind;
+use std::fmt;
+use std::time::Duration;
+
+/// Why `StreamHandler::stream_completion` failed, so callers can react to auth,
+/// rate-limit and network failures differently. Display gives the same text
+/// that is emitted in `StreamEvent::Error`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StreamError {
+    /// The provider rejected the credentials (401/403)
+    Auth {
+        status: u16,
+        body: String,
+    },
+    /// The provider answered 429; `retry_after` comes from the Retry-After header
+    RateLimited {
+        retry_after: Option<Duration>,
+        body: String,
+    },
+    /// The request or the response stream broke before completion
+    Network(String),
+    /// No response, or no stream data, within the allowed time
+    Timeout(String),
+    /// The response could not be parsed as the provider's stream format
+    Protocol(String),
+    /// Any other non-success HTTP status
+    Http {
+        status: u16,
+        body: String,
+    },
+    Cancelled,
+    /// The request could not be built: unknown model or provider, missing
+    /// credentials, or a prompt over the context budget
+    Config(String),
+}
+
+impl StreamError {
+    /// Classify a non-success HTTP response
+    pub fn from_http(status: u16, body: String, retry_after: Option<&str>) -> Self {
+        match status {
+            401 | 403 => Self::Auth { status, body },
+            429 => Self::RateLimited {
+                retry_after: retry_after.and_then(parse_retry_after),
+                body,
+            },
+            408 | 504 => Self::Timeout(format!("HTTP {}: {}", status, body)),
+            _ => Self::Http { status, body },
+        }
+    }
+
+    /// Classify a transport failure reported by reqwest
+    pub fn from_transport(error: &reqwest::Error, context: &str) -> Self {
+        let message = format!("{}: {}", context, error);
+        if error.is_timeout() {
+            Self::Timeout(message)
+        } else {
+            Self::Network(message)
+        }
+    }
+
+    pub fn kind(&self) -> StreamErrorKind {
+        match self {
+            Self::Auth { .. } => StreamErrorKind::Auth,
+            Self::RateLimited { .. } => StreamErrorKind::RateLimited,
+            Self::Network(_) => StreamErrorKind::Network,
+            Self::Timeout(_) => StreamErrorKind::Timeout,
+            Self::Protocol(_) => StreamErrorKind::Protocol,
+            Self::Http { .. } => StreamErrorKind::Http,
+            Self::Cancelled => StreamErrorKind::Cancelled,
+            Self::Config(_) => StreamErrorKind::Config,
+        }
+    }
+}
+
+/// Retry-After in delta-seconds form; HTTP-date values are ignored
+fn parse_retry_after(value: &str) -> Option<Duration> {
+    value.trim().parse::<u64>().ok().map(Duration::from_secs)
+}
+
+impl fmt::Display for StreamError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Auth { status, body } | Self::Http { status, body } => {
+                write!(f, "HTTP {}: {}", status, body)
+            }
+            Self::RateLimited { body, .. } => write!(f, "HTTP 429: {}", body),
+            Self::Network(message)
+            | Self::Timeout(message)
+            | Self::Protocol(message)
+            | Self::Config(message) => f.write_str(message),
+            Self::Cancelled => f.write_str("Stream cancelled"),
+        }
+    }
+}
+
+impl std::error::Error for StreamError {}
+
+impl From<StreamError> for String {
+    fn from(error: StreamError) -> Self {
+        error.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn maps_auth_statuses() {
+        for status in [401, 403] {
+            let error = StreamError::from_http(status, "denied".to_string(), None);
+            assert_eq!(error.kind(), StreamErrorKind::Auth);
+            assert_eq!(error.to_string(), format!("HTTP {}: denied", status));
+        }
+    }
+
+    #[test]
+    fn maps_rate_limit_with_retry_after() {
+        let error = StreamError::from_http(429, "slow down".to_string(), Some(" 12 "));
+        assert_eq!(
+            error,
+            StreamError::RateLimited {
+                retry_after: Some(Duration::from_secs(12)),
+                body: "slow down".to_string(),
+            }
+        );
+        assert_eq!(error.kind(), StreamErrorKind::RateLimited);
+
+        let error =
+            StreamError::from_http(429, String::new(), Some("Wed, 21 Oct 2026 07:28:00 GMT"));
+        assert!(matches!(
+            error,
+            StreamError::RateLimited {
+                retry_after: None,
+                ..
+            }
+        ));
+    }
+
+    #[test]
+    fn maps_gateway_timeouts_and_other_statuses() {
+        assert_eq!(
+            StreamError::from_http(504, "upstream".to_string(), None).kind(),
+            StreamErrorKind::Timeout
+        );
+        assert_eq!(
+            StreamError::from_http(408, String::new(), None).kind(),
+            StreamErrorKind::Timeout
+        );
+        assert_eq!(
+            StreamError::from_http(500, "boom".to_string(), None),
+            StreamError::Http {
+                status: 500,
+                body: "boom".to_string(),
+            }
+        );
+        assert_eq!(
+            StreamError::from_http(400, "bad".to_string(), None).kind(),
+            StreamErrorKind::Http
+        );
+    }
+
+    #[tokio::test]
+    async fn maps_connection_failure_to_network() {
+        let error = reqwest::Client::new()
+            .get("http://127.0.0.1:9/")
+            .send()
+            .await
+            .unwrap_err();
+        let error = StreamError::from_transport(&error, "Request failed");
+        assert_eq!(error.kind(), StreamErrorKind::Network);
+        assert!(error.to_string().starts_with("Request failed: "));
+    }
+
+    #[test]
+    fn parse_failures_are_protocol_errors() {
+        let error = StreamError::Protocol("Invalid UTF-8 in SSE event".to_string());
+        assert_eq!(error.kind(), StreamErrorKind::Protocol);
+        assert_eq!(String::from(error), "Invalid UTF-8 in SSE event");
+    }
+
+    #[test]
+    fn kind_serializes_as_snake_case() {
+        assert_eq!(
+            serde_json::to_value(StreamError::Cancelled.kind()).unwrap(),
+            serde_json::json!("cancelled")
+        );
+        assert_eq!(
+            serde_json::to_value(StreamErrorKind::RateLimited).unwrap(),
+            serde_json::json!("rate_limited")
+        );
+    }
+}
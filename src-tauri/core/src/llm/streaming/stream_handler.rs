@@ -1,43 +1,79 @@
+use crate::llm::ai_services::types::TokenUsage;
 use crate::llm::auth::api_key_manager::ApiKeyManager;
 use crate::llm::protocols::stream_parser::StreamParseState;
+use crate::llm::providers::body_transform::apply_body_transforms;
+use crate::llm::providers::mock_provider::{MockProvider, MOCK_PROVIDER_ID};
 use crate::llm::providers::provider::ProviderContext;
 use crate::llm::providers::provider_registry::ProviderRegistry;
+use crate::llm::streaming::circuit_breaker::{
+    provider_circuit_breaker, CircuitTransition, ProviderCircuitBreaker,
+};
+use crate::llm::streaming::completion_webhook::{self, CompletionSummary};
+use crate::llm::streaming::idempotency::{self, Claim, EventSink, IdempotencyLease};
+use crate::llm::streaming::json_assembler::JsonStreamAssembler;
+use crate::llm::streaming::message_persistence::{
+    publish_message_snapshot, StreamMessageAssembler,
+};
+use crate::llm::streaming::provider_error::parse_provider_error;
+use crate::llm::streaming::request_log::ProviderLogPolicy;
+use crate::llm::streaming::stream_error::StreamError;
+use crate::llm::streaming::stream_log::StreamLog;
+use crate::llm::streaming::usage_estimator::UsageEstimator;
+use crate::llm::streaming::usage_report::{
+    report_session_usage, session_usage_from_tokens, SessionUsageReport,
+};
 use crate::llm::testing::fixtures::FixtureInput;
-use crate::llm::testing::{Recorder, RecordingContext, TestConfig, TestMode};
+use crate::llm::testing::recorder::redact_headers;
+use crate::llm::testing::{replay_base_url, Recorder, RecordingContext, TestConfig, TestMode};
 use crate::llm::tracing::types::{float_attr, int_attr};
 use crate::llm::tracing::TraceWriter;
-use crate::llm::types::{StreamEvent, StreamTextRequest};
+use crate::llm::types::{
+    ProviderError, RequestPreview, StreamEvent, StreamTextRequest, ToolDefinition,
+};
 use futures_util::StreamExt;
 use serde_json;
 use std::collections::HashMap;
 use std::sync::atomic::{AtomicU32, Ordering};
 use std::sync::{Arc, OnceLock};
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use tauri::{Emitter, Manager};
 use tokio::time::timeout;
 
 static REQUEST_COUNTER: AtomicU32 = AtomicU32::new(1000);
 static HTTP_CLIENT: OnceLock<reqwest::Client> = OnceLock::new();
 
+/// Setting key for the TTFT warning threshold in milliseconds (0 disables the warning)
+const TTFT_WARNING_SETTING_KEY: &str = "llm_ttft_warning_ms";
+const DEFAULT_TTFT_WARNING_MS: u64 = 15_000;
+
+/// Setting key for the most bytes buffered while waiting for an SSE delimiter
+const MAX_SSE_BUFFER_SETTING_KEY: &str = "llm_max_sse_buffer_bytes";
+const DEFAULT_MAX_SSE_BUFFER_BYTES: usize = 4 * 1024 * 1024;
+
 /// Token usage info: (input_tokens, output_tokens, total_tokens, cached_input_tokens, cache_creation_input_tokens)
 type TokenUsageInfo = (i32, i32, Option<i32>, Option<i32>, Option<i32>);
 
 pub struct StreamHandler {
     registry: ProviderRegistry,
     api_keys: ApiKeyManager,
+    circuit_breaker: Arc<ProviderCircuitBreaker>,
 }
 
 impl StreamHandler {
     pub fn new(registry: ProviderRegistry, api_keys: ApiKeyManager) -> Self {
-        Self { registry, api_keys }
+        Self {
+            registry,
+            api_keys,
+            circuit_breaker: provider_circuit_breaker(),
+        }
     }
 
-    pub async fn stream_completion(
+    pub async fn stream_completion<R: tauri::Runtime>(
         &self,
-        window: tauri::Window,
+        window: tauri::Window<R>,
         request: StreamTextRequest,
         request_id: String,
-    ) -> Result<String, String> {
+    ) -> Result<String, StreamError> {
         // Use provided request_id if non-zero, otherwise generate one
         let request_id = if request_id != "0" {
             request_id
@@ -45,32 +81,142 @@ impl StreamHandler {
             REQUEST_COUNTER.fetch_add(1, Ordering::SeqCst).to_string()
         };
         let event_name = format!("llm-stream-{}", request_id);
+        let mut emitted = EmittedEvents {
+            client: Self::window_sink(&window, &event_name),
+            summary: CompletionSummary::new(&request_id),
+            persister: request
+                .persist_to_session
+                .clone()
+                .map(StreamMessageAssembler::new),
+            lease: None,
+        };
+        let webhook = match request.completion_webhook.as_deref() {
+            Some(url) => match completion_webhook::validate_webhook_url(url) {
+                Ok(url) => Some(url),
+                Err(message) => return Err(emitted.error(StreamError::Config(message), None)),
+            },
+            None => None,
+        };
+
+        if let Some(key) = request.idempotency_key.as_deref() {
+            match idempotency::claim(key, &request_id, Self::window_sink(&window, &event_name)) {
+                Claim::Leader(lease) => emitted.lease = Some(lease),
+                Claim::Attached(original_request_id) => {
+                    let mut stream_log = StreamLog::new(&request_id, &request.model);
+                    if let Some(trace_id) = request
+                        .trace_context
+                        .as_ref()
+                        .and_then(|context| context.trace_id.as_deref())
+                    {
+                        stream_log.set_trace_id(trace_id);
+                    }
+                    stream_log.info(format_args!(
+                        "Idempotency key matches stream {}, attaching to it",
+                        original_request_id
+                    ));
+                    return Ok(request_id);
+                }
+            }
+        }
+
+        let result = self
+            .run_completion(window, request, request_id, &mut emitted)
+            .await;
+        if let Some(lease) = emitted.lease.take() {
+            lease.finish(result.as_ref().err());
+        }
+        // Whatever arrived before a failure is still worth keeping
+        if let Some(snapshot) = emitted.persister.as_mut().and_then(|p| p.flush()) {
+            publish_message_snapshot(snapshot);
+        }
+        completion_webhook::notify_completion(webhook.as_ref(), emitted.summary, result).await
+    }
+
+    /// Emit events to `window` under `event_name`
+    fn window_sink<R: tauri::Runtime>(window: &tauri::Window<R>, event_name: &str) -> EventSink {
+        let window = window.clone();
+        let event_name = event_name.to_string();
+        Box::new(move |event| {
+            let _ = window.emit(&event_name, event);
+        })
+    }
 
-        log::info!(
-            "[LLM Stream {}] Starting stream completion for model: {}",
-            request_id,
+    async fn run_completion<R: tauri::Runtime>(
+        &self,
+        window: tauri::Window<R>,
+        mut request: StreamTextRequest,
+        request_id: String,
+        emitted: &mut EmittedEvents,
+    ) -> Result<String, StreamError> {
+        let mut stream_log = StreamLog::new(&request_id, &request.model);
+
+        stream_log.info(format_args!(
+            "Starting stream completion for model: {}",
             request.model
+        ));
+        request.tools = Self::allowed_tool_definitions(
+            request.tools.as_deref(),
+            request.allowed_tools.as_deref(),
         );
 
-        let (model_key, provider_id, provider_model_name) =
-            self.resolve_model_info(&request.model).await?;
-        log::info!(
-            "[LLM Stream {}] Resolved model: {}, provider: {}",
-            request_id,
-            model_key,
-            provider_id
-        );
+        let (model_key, provider_id, provider_model_name, fallback_reason) = self
+            .resolve_model_info(&request.model, request.fallback_models.as_deref())
+            .await
+            .map_err(StreamError::Config)?;
+        stream_log.set_provider(&provider_id, &model_key);
+        stream_log.info(format_args!(
+            "Resolved model: {}, provider: {}",
+            model_key, provider_id
+        ));
+        if let Some(ref reason) = fallback_reason {
+            stream_log.warn(format_args!(
+                "Model {} unavailable ({}), fell back to {}@{}",
+                request.model, reason, model_key, provider_id
+            ));
+        }
+        let models = self
+            .api_keys
+            .load_models_config()
+            .await
+            .map_err(StreamError::Config)?;
+        let estimated_input_tokens =
+            crate::llm::models::model_registry::ModelRegistry::estimate_input_tokens(
+                &request.messages,
+                request.tools.as_deref(),
+                &provider_model_name,
+            );
+        if let Err(message) =
+            crate::llm::models::model_registry::ModelRegistry::check_context_budget(
+                &model_key,
+                estimated_input_tokens,
+                request.max_tokens,
+                &models,
+            )
+        {
+            stream_log.warn(format_args!("{}", message));
+            return Err(emitted.error(StreamError::Config(message), None));
+        }
         let provider = self
             .registry
             .create_provider(&provider_id)
-            .ok_or_else(|| format!("Provider not found: {}", provider_id))?;
+            .ok_or_else(|| StreamError::Config(format!("Provider not found: {}", provider_id)))?;
         let provider_config = provider.config();
-        log::info!(
-            "[LLM Stream {}] Found provider: {} with protocol: {:?}",
-            request_id,
-            provider_config.name,
-            provider_config.protocol
-        );
+        stream_log.info(format_args!(
+            "Found provider: {} with protocol: {:?}",
+            provider_config.name, provider_config.protocol
+        ));
+
+        if provider_config.id == MOCK_PROVIDER_ID {
+            let script = MockProvider::from_provider_options(request.provider_options.as_ref());
+            let allowed_tools = request.allowed_tools.as_deref();
+            script
+                .stream(|event| {
+                    let event = Self::reject_disallowed_tool_call(event, allowed_tools);
+                    emitted.emit(&event);
+                })
+                .await;
+            return Ok(request_id);
+        }
 
         let provider_ctx = ProviderContext {
             provider_config,
@@ -84,14 +230,20 @@ impl StreamHandler {
             top_k: request.top_k,
             provider_options: request.provider_options.as_ref(),
             trace_context: request.trace_context.as_ref(),
+            logprobs: request.logprobs,
+            top_logprobs: request.top_logprobs,
         };
 
-        let built_request = provider.build_complete_request(&provider_ctx).await?;
-        log::info!(
-            "[LLM Stream {}] Resolved base URL: {}",
-            request_id,
-            built_request.url
-        );
+        let mut built_request = provider
+            .build_complete_request(&provider_ctx)
+            .await
+            .map_err(StreamError::Config)?;
+        if let Some(transforms) = provider_config.body_transforms.as_deref() {
+            apply_body_transforms(&mut built_request.body, transforms)
+                .map_err(StreamError::Config)?;
+        }
+        stream_log.info(format_args!("Resolved base URL: {}", built_request.url));
+        let log_policy = ProviderLogPolicy::load(&self.api_keys, &provider_id).await;
 
         // Initialize tracing span if trace_context is provided
         let mut trace_span_id: Option<String> = None;
@@ -113,13 +265,13 @@ impl StreamHandler {
             //     request_id, trace_context.trace_id, trace_context.span_name, trace_context.parent_span_id);
             let trace_id = trace_context.trace_id.clone().unwrap_or_else(|| {
                 let new_id = trace_writer.start_trace();
-                log::info!(
-                    "[LLM Stream {}] No trace_id provided, generated new trace: {}",
-                    request_id,
+                stream_log.info(format_args!(
+                    "No trace_id provided, generated new trace: {}",
                     new_id
-                );
+                ));
                 new_id
             });
+            stream_log.set_trace_id(&trace_id);
             // log::info!("[LLM Stream {}] Using trace_id: {}", request_id, trace_id);
 
             let span_name = trace_context
@@ -143,6 +295,20 @@ impl StreamHandler {
                 crate::llm::tracing::types::string_attr(&provider_id),
             );
 
+            if let Some(ref reason) = fallback_reason {
+                attributes.insert(
+                    crate::llm::tracing::types::attributes::GEN_AI_FALLBACK_MODEL.to_string(),
+                    crate::llm::tracing::types::string_attr(format!(
+                        "{}@{}",
+                        model_key, provider_id
+                    )),
+                );
+                attributes.insert(
+                    crate::llm::tracing::types::attributes::GEN_AI_FALLBACK_REASON.to_string(),
+                    crate::llm::tracing::types::string_attr(reason.as_str()),
+                );
+            }
+
             if let Some(t) = request.temperature {
                 attributes.insert(
                     crate::llm::tracing::types::attributes::GEN_AI_REQUEST_TEMPERATURE.to_string(),
@@ -168,14 +334,27 @@ impl StreamHandler {
                 );
             }
 
+            if let Some(ref metadata) = request.metadata {
+                attributes.extend(crate::llm::tracing::types::metadata_attributes(metadata));
+            }
+
             let span_id = trace_writer.start_span(
-                trace_id,
+                trace_id.clone(),
                 trace_context.parent_span_id.clone(),
                 span_name.to_string(),
                 attributes,
             );
             trace_span_id = Some(span_id.clone());
 
+            if let Some(session_id) = trace_context
+                .metadata
+                .as_ref()
+                .and_then(|metadata| metadata.get("session_id"))
+                .filter(|value| !value.is_empty())
+            {
+                trace_writer.set_trace_session(trace_id, session_id.clone());
+            }
+
             // let _parent_exists = trace_context
             //     .parent_span_id
             //     .as_deref()
@@ -203,6 +382,36 @@ impl StreamHandler {
             );
         }
 
+        match self
+            .circuit_breaker
+            .try_acquire(&provider_id, Instant::now())
+        {
+            Ok(transition) => Self::record_circuit_transition(
+                &window,
+                &stream_log,
+                trace_span_id.as_ref(),
+                transition,
+            ),
+            Err(open) => {
+                let message = open.message();
+                stream_log.warn(format_args!("{}", message));
+                if let Some(ref span_id) = trace_span_id {
+                    let trace_writer = window.app_handle().state::<Arc<TraceWriter>>();
+                    trace_writer.add_event(
+                        span_id.clone(),
+                        crate::llm::tracing::types::attributes::ERROR_TYPE.to_string(),
+                        Some(serde_json::json!({
+                            "error_type": "circuit_open",
+                            "provider_id": provider_id,
+                            "retry_after_ms": open.retry_after.as_millis() as u64,
+                            "message": message,
+                        })),
+                    );
+                }
+                return Err(emitted.error(StreamError::Network(message), None));
+            }
+        }
+
         let test_config = TestConfig::from_env();
 
         let base_url = if test_config.mode != TestMode::Off {
@@ -223,8 +432,15 @@ impl StreamHandler {
             .ok()
             .map(|url| url.path().trim_start_matches('/').to_string())
             .unwrap_or_default();
+        // Without an explicit override, replay mode serves recordings from the fixture dir
+        let base_url_override = match test_config.base_url_override.clone() {
+            None if test_config.mode == TestMode::Replay => Some(
+                replay_base_url(&test_config, &provider_config.id).map_err(StreamError::Config)?,
+            ),
+            other => other,
+        };
         let url = if test_config.mode != TestMode::Off {
-            if let Some(override_url) = test_config.base_url_override.as_deref() {
+            if let Some(override_url) = base_url_override.as_deref() {
                 format!("{}/{}", override_url.trim_end_matches('/'), endpoint_path)
             } else {
                 built_request.url.clone()
@@ -272,73 +488,75 @@ impl StreamHandler {
                 .build()
                 .expect("Failed to build HTTP client")
         });
-        log::debug!("[LLM Stream {}] HTTP client ready", request_id);
+        stream_log.debug(format_args!("HTTP client ready"));
 
-        let mut req_builder = client.post(&url);
-        for (key, value) in headers {
-            req_builder = req_builder.header(&key, &value);
-        }
-        req_builder = req_builder
-            .header("Accept", "text/event-stream")
-            .json(&body);
+        let req_builder = Self::build_http_request(client, &url, &headers, &body);
+
+        stream_log.info(format_args!(
+            "Request: {}",
+            log_policy.request(&url, &built_request.headers, &body)
+        ));
 
-        // log::info!("[LLM Stream {}] Sending HTTP request...", request_id);
+        let ttft_warning_threshold = self.ttft_warning_threshold().await;
+        let mut json_assembler = request
+            .partial_json
+            .unwrap_or(false)
+            .then(JsonStreamAssembler::new);
+        let mut usage_estimator = request
+            .estimate_usage
+            .then(|| UsageEstimator::for_model(&provider_model_name));
+        let max_sse_buffer_bytes = self.max_sse_buffer_bytes().await;
 
         // Retry configuration: exponential backoff with max 3 retries
         const MAX_RETRIES: u32 = 3;
         const BASE_DELAY_MS: u64 = 1000;
 
+        // Paired with when its attempt was sent: TTFT excludes earlier attempts and backoff
         let mut response = None;
-        let mut last_error: Option<String> = None;
+        let mut last_error: Option<StreamError> = None;
 
         for attempt in 0..=MAX_RETRIES {
             if attempt > 0 {
                 let delay_ms = BASE_DELAY_MS * (1 << (attempt - 1)); // Exponential backoff: 1s, 2s, 4s
-                log::info!(
-                    "[LLM Stream {}] Retrying request (attempt {}/{}), waiting {}ms",
-                    request_id,
-                    attempt,
-                    MAX_RETRIES,
-                    delay_ms
-                );
+                stream_log.info(format_args!(
+                    "Retrying request (attempt {}/{}), waiting {}ms",
+                    attempt, MAX_RETRIES, delay_ms
+                ));
                 tokio::time::sleep(Duration::from_millis(delay_ms)).await;
             }
 
+            let sent_at = Instant::now();
             match req_builder.try_clone() {
                 Some(builder) => match builder.send().await {
                     Ok(resp) => {
-                        response = Some(resp);
+                        response = Some((resp, sent_at));
                         break;
                     }
                     Err(e) => {
-                        let err_msg = format!("{}", e);
-                        log::warn!(
-                            "[LLM Stream {}] Request attempt {}/{} failed: {}",
-                            request_id,
+                        stream_log.warn(format_args!(
+                            "Request attempt {}/{} failed: {}",
                             attempt + 1,
                             MAX_RETRIES + 1,
-                            err_msg
-                        );
-                        last_error = Some(err_msg);
+                            e
+                        ));
+                        last_error = Some(StreamError::from_transport(&e, "Request failed"));
                     }
                 },
                 None => {
                     // Request body cannot be cloned, try without cloning
                     match req_builder.send().await {
                         Ok(resp) => {
-                            response = Some(resp);
+                            response = Some((resp, sent_at));
                             break;
                         }
                         Err(e) => {
-                            let err_msg = format!("{}", e);
-                            log::warn!(
-                                "[LLM Stream {}] Request attempt {}/{} failed: {}",
-                                request_id,
+                            stream_log.warn(format_args!(
+                                "Request attempt {}/{} failed: {}",
                                 attempt + 1,
                                 MAX_RETRIES + 1,
-                                err_msg
-                            );
-                            last_error = Some(err_msg);
+                                e
+                            ));
+                            last_error = Some(StreamError::from_transport(&e, "Request failed"));
                             // Cannot retry without cloning
                             break;
                         }
@@ -347,25 +565,47 @@ impl StreamHandler {
             }
         }
 
-        let response = response.ok_or_else(|| {
-            let err = last_error.unwrap_or_else(|| "Request failed after all retries".to_string());
-            log::error!("[LLM Stream {}] Request failed: {}", request_id, err);
-            format!("Request failed: {}", err)
-        })?;
+        let (response, sent_at) = match response {
+            Some(response) => response,
+            None => {
+                let err = last_error.unwrap_or_else(|| {
+                    StreamError::Network("Request failed after all retries".to_string())
+                });
+                stream_log.error(format_args!("{}", err));
+                let transition = self
+                    .circuit_breaker
+                    .record_failure(&provider_id, Instant::now());
+                Self::record_circuit_transition(
+                    &window,
+                    &stream_log,
+                    trace_span_id.as_ref(),
+                    transition,
+                );
+                return Err(err);
+            }
+        };
 
         let status = response.status().as_u16();
+        // 5xx counts against the provider; any other answer shows it is reachable
+        let transition = if status >= 500 {
+            self.circuit_breaker
+                .record_failure(&provider_id, Instant::now())
+        } else {
+            self.circuit_breaker.record_success(&provider_id)
+        };
+        Self::record_circuit_transition(&window, &stream_log, trace_span_id.as_ref(), transition);
         if status >= 400 {
             let response_headers = response.headers().clone();
             let text = response.text().await.unwrap_or_default();
-            log::error!(
-                "[LLM Stream {}] HTTP error {}: {}",
-                request_id,
+            stream_log.error(format_args!(
+                "HTTP error {}: {}",
                 status,
-                text
-            );
+                log_policy.response_body(&text)
+            ));
             if let Some(recorder) = recorder.as_mut() {
                 let _ = recorder.finish_error(status, &response_headers, &text);
             }
+            let provider_error = parse_provider_error(&text);
             // Record error in tracing span
             if let Some(ref span_id) = trace_span_id {
                 let trace_writer = window.app_handle().state::<Arc<TraceWriter>>();
@@ -376,46 +616,87 @@ impl StreamHandler {
                         "error_type": "http_error",
                         "status_code": status,
                         "message": text,
+                        "provider_error": provider_error,
                     })),
                 );
             }
-            let error_event = StreamEvent::Error {
-                message: format!("HTTP {}: {}", status, text),
-            };
-            let _ = window.emit(&event_name, &error_event);
-            return Err(format!("HTTP error {}", status));
+            let retry_after = response_headers
+                .get(reqwest::header::RETRY_AFTER)
+                .and_then(|value| value.to_str().ok());
+            return Err(emitted.error(
+                StreamError::from_http(status, text, retry_after),
+                Some(provider_error),
+            ));
         }
 
         let response_headers = response.headers().clone();
         let mut stream = response.bytes_stream();
+        let mut slow_start_watchdog = SlowStartWatchdog::new(ttft_warning_threshold, sent_at);
         let mut buffer: Vec<u8> = Vec::new();
-        let mut state = StreamParseState::default();
+        let mut state = StreamParseState {
+            interleaved: models
+                .models
+                .get(&model_key)
+                .is_some_and(|model| model.interleaved),
+            tool_call_deltas: request.stream_tool_call_deltas,
+            ..Default::default()
+        };
         let mut chunk_count = 0;
         let mut response_text = String::new();
         let stream_timeout = Duration::from_secs(300); // Timeout between chunks
         const STREAM_MAX_RETRIES: u32 = 3;
         const STREAM_BASE_DELAY_MS: u64 = 1000;
         let mut stream_error_retries: u32 = 0;
+        // Reconnects after a dropped connection when `resume_on_disconnect` is set
+        const STREAM_MAX_RESUMES: u32 = 2;
+        let mut stream_resumes: u32 = 0;
+        // Server `retry:` hints are honoured up to this bound
+        const STREAM_MAX_RETRY_HINT: Duration = Duration::from_secs(30);
+        let mut server_retry: Option<Duration> = None;
 
         'stream_loop: loop {
             // Use timeout to prevent hanging on stream.next().await
-            let chunk_result = timeout(stream_timeout, stream.next()).await;
+            let chunk_result =
+                Self::next_chunk(&mut stream, stream_timeout, &mut slow_start_watchdog).await;
 
             let chunk = match chunk_result {
-                Ok(Some(result)) => result,
-                Ok(None) => {
-                    log::info!(
-                        "[LLM Stream {}] Stream ended normally after {} chunks",
-                        request_id,
+                ChunkWait::Item(Some(result)) => result,
+                ChunkWait::SlowStart(event) => {
+                    stream_log.warn(format_args!(
+                        "No content received after {:?}, still waiting",
+                        slow_start_watchdog.elapsed()
+                    ));
+                    if let Some(ref span_id) = trace_span_id {
+                        let trace_writer = window.app_handle().state::<Arc<TraceWriter>>();
+                        trace_writer.add_event(
+                            span_id.clone(),
+                            crate::llm::tracing::types::attributes::GEN_AI_SLOW_START.to_string(),
+                            serde_json::to_value(&event).ok(),
+                        );
+                    }
+                    emitted.emit(&event);
+                    continue;
+                }
+                ChunkWait::Item(None) => {
+                    stream_log.info(format_args!(
+                        "Stream ended normally after {} chunks",
                         chunk_count
-                    );
+                    ));
                     break;
                 }
-                Err(_) => {
-                    log::error!(
-                        "[LLM Stream {}] Stream timeout - no data received for {} seconds",
-                        request_id,
+                ChunkWait::TimedOut => {
+                    stream_log.error(format_args!(
+                        "Stream timeout - no data received for {} seconds",
                         stream_timeout.as_secs()
+                    ));
+                    let transition = self
+                        .circuit_breaker
+                        .record_failure(&provider_id, Instant::now());
+                    Self::record_circuit_transition(
+                        &window,
+                        &stream_log,
+                        trace_span_id.as_ref(),
+                        transition,
                     );
                     // Record error in tracing span
                     if let Some(ref span_id) = trace_span_id {
@@ -430,16 +711,12 @@ impl StreamHandler {
                             })),
                         );
                     }
-                    let error_event = StreamEvent::Error {
-                        message: format!(
+                    return Err(emitted.error(
+                        StreamError::Timeout(format!(
                             "Stream timeout - no data received for {} seconds",
                             stream_timeout.as_secs()
-                        ),
-                    };
-                    let _ = window.emit(&event_name, &error_event);
-                    return Err(format!(
-                        "Stream timeout - no data received for {} seconds",
-                        stream_timeout.as_secs()
+                        )),
+                        None,
                     ));
                 }
             };
@@ -454,25 +731,72 @@ impl StreamHandler {
                         && stream_error_retries < STREAM_MAX_RETRIES
                     {
                         let delay_ms = STREAM_BASE_DELAY_MS * (1u64 << stream_error_retries);
-                        log::warn!(
-                            "[LLM Stream {}] Stream decode error at chunk {}, retrying {}/{} after {}ms: {}",
-                            request_id,
+                        stream_log.warn(format_args!(
+                            "Stream decode error at chunk {}, retrying {}/{} after {}ms: {}",
                             chunk_count,
                             stream_error_retries + 1,
                             STREAM_MAX_RETRIES,
                             delay_ms,
                             err_msg
-                        );
+                        ));
                         stream_error_retries += 1;
                         tokio::time::sleep(Duration::from_millis(delay_ms)).await;
                         continue;
                     }
-                    log::error!(
-                        "[LLM Stream {}] Stream error at chunk {}: {}",
-                        request_id,
-                        chunk_count,
-                        err_msg
-                    );
+                    if request.resume_on_disconnect
+                        && stream_resumes < STREAM_MAX_RESUMES
+                        && state.resume.can_resume()
+                    {
+                        stream_resumes += 1;
+                        stream_log.warn(format_args!(
+                            "Connection dropped at chunk {} after {} chars, resuming {}/{}: {}",
+                            chunk_count,
+                            state.resume.emitted_text_chars,
+                            stream_resumes,
+                            STREAM_MAX_RESUMES,
+                            err_msg
+                        ));
+                        if let Some(delay) = server_retry {
+                            tokio::time::sleep(delay).await;
+                        }
+                        let mut resume_request =
+                            Self::build_http_request(client, &url, &headers, &body);
+                        if let Some(last_event_id) = state.last_event_id.as_deref() {
+                            resume_request = resume_request.header("Last-Event-ID", last_event_id);
+                        }
+                        match resume_request.send().await {
+                            Ok(resumed) if resumed.status().is_success() => {
+                                stream = resumed.bytes_stream();
+                                buffer.clear();
+                                if state.last_event_id.is_some() {
+                                    // The server continues after the last id; keep the parse
+                                    // state and swallow only what it replays past that id
+                                    state.resume.begin_resume_after_event_id();
+                                } else {
+                                    // The provider starts over; the cursor swallows what was already sent
+                                    let mut resume = state.resume;
+                                    resume.begin_resume();
+                                    state = StreamParseState {
+                                        interleaved: state.interleaved,
+                                        tool_call_deltas: state.tool_call_deltas,
+                                        resume,
+                                        ..Default::default()
+                                    };
+                                }
+                                continue;
+                            }
+                            Ok(resumed) => stream_log.warn(format_args!(
+                                "Resume rejected with HTTP {}",
+                                resumed.status().as_u16()
+                            )),
+                            Err(resume_error) => stream_log
+                                .warn(format_args!("Resume request failed: {}", resume_error)),
+                        }
+                    }
+                    stream_log.error(format_args!(
+                        "Stream error at chunk {}: {}",
+                        chunk_count, err_msg
+                    ));
                     // Record error in tracing span
                     if let Some(ref span_id) = trace_span_id {
                         let trace_writer = window.app_handle().state::<Arc<TraceWriter>>();
@@ -486,18 +810,16 @@ impl StreamHandler {
                             })),
                         );
                     }
-                    let error_event = StreamEvent::Error {
-                        message: format!("Stream error: {}", err_msg),
-                    };
-                    let _ = window.emit(&event_name, &error_event);
-                    return Err(format!("Stream error: {}", err_msg));
+                    return Err(
+                        emitted.error(StreamError::from_transport(&e, "Stream error"), None)
+                    );
                 }
             };
 
             stream_error_retries = 0;
 
             if bytes.is_empty() {
-                log::debug!("[LLM Stream {}] Received empty chunk", request_id);
+                stream_log.debug(format_args!("Received empty chunk"));
                 continue;
             }
 
@@ -511,11 +833,7 @@ impl StreamHandler {
                 let event_str = match String::from_utf8(event_bytes) {
                     Ok(s) => s,
                     Err(e) => {
-                        log::error!(
-                            "[LLM Stream {}] Invalid UTF-8 in SSE event: {}",
-                            request_id,
-                            e
-                        );
+                        stream_log.error(format_args!("Invalid UTF-8 in SSE event: {}", e));
                         // Record error in tracing span
                         if let Some(ref span_id) = trace_span_id {
                             let trace_writer = window.app_handle().state::<Arc<TraceWriter>>();
@@ -528,15 +846,35 @@ impl StreamHandler {
                                 })),
                             );
                         }
-                        let error_event = StreamEvent::Error {
-                            message: format!("Invalid UTF-8 in SSE event: {}", e),
-                        };
-                        let _ = window.emit(&event_name, &error_event);
-                        return Err(format!("Invalid UTF-8 in SSE event: {}", e));
+                        return Err(emitted.error(
+                            StreamError::Protocol(format!("Invalid UTF-8 in SSE event: {}", e)),
+                            None,
+                        ));
                     }
                 };
 
                 if let Some(parsed) = Self::parse_sse_event(&event_str) {
+                    if log_policy.is_verbose() {
+                        stream_log.debug(format_args!(
+                            "SSE event {:?}: {}",
+                            parsed.event, parsed.data
+                        ));
+                    }
+                    if let Some(id) = parsed.id.as_deref() {
+                        // An empty id resets the last event id per the SSE spec
+                        state.last_event_id = (!id.is_empty()).then(|| id.to_string());
+                    }
+                    if let Some(retry_ms) = parsed.retry_ms {
+                        server_retry =
+                            Some(Duration::from_millis(retry_ms).min(STREAM_MAX_RETRY_HINT));
+                    }
+                    if !parsed.has_data {
+                        // Field-only block such as a leading `retry:` hint
+                        if parsed.id.is_some() {
+                            state.resume.mark_event_id();
+                        }
+                        continue;
+                    }
                     if let Some(recorder) = recorder.as_mut() {
                         recorder.record_sse_event(parsed.event.as_deref(), &parsed.data);
                     }
@@ -548,6 +886,24 @@ impl StreamHandler {
                             &mut state,
                         )
                         .await;
+                    // Drop output a resumed stream repeats; counts what reaches the client
+                    let allowed_tools = request.allowed_tools.as_deref();
+                    let parsed_result = match parsed_result {
+                        Ok(Some(event)) => Ok(state
+                            .resume
+                            .observe(event)
+                            .map(|event| Self::reject_disallowed_tool_call(event, allowed_tools))),
+                        other => other,
+                    };
+                    let pending = std::mem::take(&mut state.pending_events);
+                    state.pending_events = pending
+                        .into_iter()
+                        .filter_map(|event| state.resume.observe(event))
+                        .map(|event| Self::reject_disallowed_tool_call(event, allowed_tools))
+                        .collect();
+                    if parsed.id.is_some() {
+                        state.resume.mark_event_id();
+                    }
                     match parsed_result {
                         Ok(Some(event)) => {
                             // Capture usage and finish_reason for tracing
@@ -577,7 +933,13 @@ impl StreamHandler {
                                 recorder.record_expected_event(&event);
                             }
                             Self::append_text_delta(&mut response_text, &event);
-                            self.emit_stream_event(&window, &event_name, &request_id, &event);
+                            slow_start_watchdog.observe(&event);
+                            Self::emit_content_event(
+                                emitted,
+                                &event,
+                                json_assembler.as_mut(),
+                                usage_estimator.as_mut(),
+                            );
 
                             if !trace_ttft_emitted {
                                 if let (Some(ref span_id), Some(client_start_ms)) =
@@ -605,50 +967,43 @@ impl StreamHandler {
                                         recorder.record_expected_event(&pending);
                                     }
                                     Self::append_text_delta(&mut response_text, &pending);
-                                    self.emit_stream_event(
-                                        &window,
-                                        &event_name,
-                                        &request_id,
+                                    slow_start_watchdog.observe(&pending);
+                                    Self::emit_content_event(
+                                        emitted,
                                         &pending,
+                                        json_assembler.as_mut(),
+                                        usage_estimator.as_mut(),
                                     );
                                 }
                             }
 
                             if matches!(event, StreamEvent::Done { .. }) {
-                                log::info!(
-                                    "[LLM Stream {}] Done event received, ending stream loop",
-                                    request_id
-                                );
+                                stream_log
+                                    .info(format_args!("Done event received, ending stream loop"));
                                 done_emitted = true;
                                 break 'stream_loop;
                             }
                         }
                         Ok(None) => {
-                            log::debug!(
-                                "[LLM Stream {}] No event emitted from parsed data",
-                                request_id
-                            );
+                            stream_log.debug(format_args!("No event emitted from parsed data"));
                             if !state.pending_events.is_empty() {
                                 for pending in state.pending_events.drain(..) {
                                     if let Some(recorder) = recorder.as_mut() {
                                         recorder.record_expected_event(&pending);
                                     }
                                     Self::append_text_delta(&mut response_text, &pending);
-                                    self.emit_stream_event(
-                                        &window,
-                                        &event_name,
-                                        &request_id,
+                                    slow_start_watchdog.observe(&pending);
+                                    Self::emit_content_event(
+                                        emitted,
                                         &pending,
+                                        json_assembler.as_mut(),
+                                        usage_estimator.as_mut(),
                                     );
                                 }
                             }
                         }
                         Err(err) => {
-                            log::error!(
-                                "[LLM Stream {}] Error parsing stream event: {}",
-                                request_id,
-                                err
-                            );
+                            stream_log.error(format_args!("Error parsing stream event: {}", err));
                             // Record error in tracing span
                             if let Some(ref span_id) = trace_span_id {
                                 let trace_writer = window.app_handle().state::<Arc<TraceWriter>>();
@@ -661,22 +1016,34 @@ impl StreamHandler {
                                     })),
                                 );
                             }
-                            let _ = window.emit(
-                                &event_name,
-                                &StreamEvent::Error {
-                                    message: err.clone(),
-                                },
-                            );
-                            return Err(err);
+                            return Err(emitted.error(StreamError::Protocol(err), None));
                         }
                     }
                 } else {
-                    log::debug!(
-                        "[LLM Stream {}] No SSE event parsed from: {}",
-                        request_id,
-                        event_str
+                    stream_log.debug(format_args!(
+                        "No SSE event parsed from: {}",
+                        log_policy.sse_event(&event_str)
+                    ));
+                }
+            }
+
+            // Whatever is left has no delimiter yet; a server that never sends one
+            // must not be able to grow it until the inter-chunk timeout
+            if let Some(error) = Self::sse_buffer_overflow(buffer.len(), max_sse_buffer_bytes) {
+                stream_log.error(format_args!("{}", error));
+                if let Some(ref span_id) = trace_span_id {
+                    let trace_writer = window.app_handle().state::<Arc<TraceWriter>>();
+                    trace_writer.add_event(
+                        span_id.clone(),
+                        crate::llm::tracing::types::attributes::ERROR_TYPE.to_string(),
+                        Some(serde_json::json!({
+                            "error_type": "sse_buffer_overflow",
+                            "buffered_bytes": buffer.len(),
+                            "message": error.to_string(),
+                        })),
                     );
                 }
+                return Err(emitted.error(error, None));
             }
         }
 
@@ -762,38 +1129,131 @@ impl StreamHandler {
             trace_writer.end_span(span_id.clone(), chrono::Utc::now().timestamp_millis());
         }
 
+        // Hand usage to chat history so it can keep per-session totals
+        if let (Some(session_id), Some((input_tokens, output_tokens, _, cached, cache_creation))) =
+            (Self::session_id(&request), trace_usage)
+        {
+            let token_usage = TokenUsage {
+                input_tokens: input_tokens.max(0) as u32,
+                output_tokens: output_tokens.max(0) as u32,
+                cached_input_tokens: cached.map(|value| value.max(0) as u32),
+                cache_creation_input_tokens: cache_creation.map(|value| value.max(0) as u32),
+            };
+            report_session_usage(SessionUsageReport {
+                session_id,
+                usage: session_usage_from_tokens(&model_key, &token_usage, &models.models),
+                model: model_key.clone(),
+            });
+        }
+
         if !done_emitted {
-            let _ = window.emit(
-                &event_name,
-                &StreamEvent::Done {
-                    finish_reason: state.finish_reason.clone(),
-                },
-            );
+            let done = StreamEvent::Done {
+                finish_reason: state.finish_reason.clone(),
+            };
+            emitted.emit(&done);
         }
 
-        log::info!(
-            "[LLM Stream {}] Stream completion finished successfully",
-            request_id
-        );
+        stream_log.info(format_args!(
+            "Stream completion finished successfully, response: {}",
+            log_policy.response_body(&response_text)
+        ));
         Ok(request_id)
     }
 
+    fn build_http_request(
+        client: &reqwest::Client,
+        url: &str,
+        headers: &HashMap<String, String>,
+        body: &serde_json::Value,
+    ) -> reqwest::RequestBuilder {
+        let mut req_builder = client.post(url);
+        for (key, value) in headers {
+            req_builder = req_builder.header(key, value);
+        }
+        req_builder.header("Accept", "text/event-stream").json(body)
+    }
+
+    /// Narrow `tools` to the names in `allowed`; `None` leaves the tool set untouched
+    fn allowed_tool_definitions(
+        tools: Option<&[ToolDefinition]>,
+        allowed: Option<&[String]>,
+    ) -> Option<Vec<ToolDefinition>> {
+        let tools = tools?;
+        let Some(allowed) = allowed else {
+            return Some(tools.to_vec());
+        };
+        Some(
+            tools
+                .iter()
+                .filter(|tool| allowed.iter().any(|name| name == &tool.name))
+                .cloned()
+                .collect(),
+        )
+    }
+
+    /// Replace a tool call outside the allowlist with an error event, so a
+    /// model inventing a tool name cannot reach the tool runner
+    fn reject_disallowed_tool_call(event: StreamEvent, allowed: Option<&[String]>) -> StreamEvent {
+        let Some(allowed) = allowed else {
+            return event;
+        };
+        match event {
+            StreamEvent::ToolCall { ref tool_name, .. }
+                if !allowed.iter().any(|name| name == tool_name) =>
+            {
+                let error = StreamError::Protocol(format!(
+                    "Model called tool '{}' which is not in the allowed tools",
+                    tool_name
+                ));
+                StreamEvent::Error {
+                    message: error.to_string(),
+                    provider_error: None,
+                    kind: Some(error.kind()),
+                }
+            }
+            other => other,
+        }
+    }
+
+    /// Resolve the requested model, walking `fallback_models` when it has no
+    /// available provider. The last element is the primary resolution error
+    /// when a fallback was chosen.
     async fn resolve_model_info(
         &self,
         model_identifier: &str,
-    ) -> Result<(String, String, String), String> {
+        fallback_models: Option<&[String]>,
+    ) -> Result<(String, String, String, Option<String>), String> {
         let models = self.api_keys.load_models_config().await?;
         let api_keys = self.api_keys.load_api_keys().await?;
         let custom_providers = self.api_keys.load_custom_providers().await?;
 
-        let (model_key, provider_id) =
-            crate::llm::models::model_registry::ModelRegistry::get_model_provider(
+        let primary =
+            crate::llm::models::model_registry::ModelRegistry::get_model_provider_with_warning(
                 model_identifier,
                 &api_keys,
                 &self.registry,
                 &custom_providers,
                 &models,
-            )?;
+            );
+        let (model_key, provider_id, fallback_reason) = match primary {
+            // The deprecation notice is logged by the registry; the model still runs
+            Ok((model_key, provider_id, _deprecation)) => (model_key, provider_id, None),
+            Err(primary_error) => match fallback_models.filter(|models| !models.is_empty()) {
+                Some(candidates) => {
+                    let (model_key, provider_id) =
+                        crate::llm::models::model_registry::ModelRegistry::resolve_with_fallback(
+                            candidates,
+                            &api_keys,
+                            &self.registry,
+                            &custom_providers,
+                            &models,
+                        )
+                        .map_err(|e| format!("{}; {}", primary_error, e))?;
+                    (model_key, provider_id, Some(primary_error))
+                }
+                None => return Err(primary_error),
+            },
+        };
 
         let provider_model_name =
             crate::llm::models::model_registry::ModelRegistry::resolve_provider_model_name(
@@ -802,7 +1262,113 @@ impl StreamHandler {
                 &models,
             );
 
-        Ok((model_key, provider_id, provider_model_name))
+        Ok((model_key, provider_id, provider_model_name, fallback_reason))
+    }
+
+    /// Resolve the model and build the provider request exactly as `stream_completion`
+    /// would, without sending it. Credential headers are redacted.
+    pub async fn build_preview(
+        &self,
+        request: &StreamTextRequest,
+    ) -> Result<RequestPreview, String> {
+        let (_, provider_id, provider_model_name, _) = self
+            .resolve_model_info(&request.model, request.fallback_models.as_deref())
+            .await?;
+        let provider = self
+            .registry
+            .create_provider(&provider_id)
+            .ok_or_else(|| format!("Provider not found: {}", provider_id))?;
+
+        let tools = Self::allowed_tool_definitions(
+            request.tools.as_deref(),
+            request.allowed_tools.as_deref(),
+        );
+        let provider_ctx = ProviderContext {
+            provider_config: provider.config(),
+            api_key_manager: &self.api_keys,
+            model: &provider_model_name,
+            messages: &request.messages,
+            tools: tools.as_deref(),
+            temperature: request.temperature,
+            max_tokens: request.max_tokens,
+            top_p: request.top_p,
+            top_k: request.top_k,
+            provider_options: request.provider_options.as_ref(),
+            trace_context: request.trace_context.as_ref(),
+            logprobs: request.logprobs,
+            top_logprobs: request.top_logprobs,
+        };
+        let built_request = provider.build_complete_request(&provider_ctx).await?;
+
+        Ok(RequestPreview {
+            provider_id,
+            model: provider_model_name,
+            url: built_request.url,
+            headers: redact_headers(&built_request.headers),
+            body: built_request.body,
+        })
+    }
+
+    /// Read the TTFT warning threshold from settings; `None` disables the watchdog
+    async fn ttft_warning_threshold(&self) -> Option<Duration> {
+        let configured = match self.api_keys.get_setting(TTFT_WARNING_SETTING_KEY).await {
+            Ok(value) => value.and_then(|raw| raw.trim().parse::<u64>().ok()),
+            Err(e) => {
+                log::warn!("Failed to read {}: {}", TTFT_WARNING_SETTING_KEY, e);
+                None
+            }
+        };
+        match configured.unwrap_or(DEFAULT_TTFT_WARNING_MS) {
+            0 => None,
+            ms => Some(Duration::from_millis(ms)),
+        }
+    }
+
+    async fn max_sse_buffer_bytes(&self) -> usize {
+        let configured = match self.api_keys.get_setting(MAX_SSE_BUFFER_SETTING_KEY).await {
+            Ok(value) => value.and_then(|raw| raw.trim().parse::<usize>().ok()),
+            Err(e) => {
+                log::warn!("Failed to read {}: {}", MAX_SSE_BUFFER_SETTING_KEY, e);
+                None
+            }
+        };
+        configured
+            .filter(|bytes| *bytes > 0)
+            .unwrap_or(DEFAULT_MAX_SSE_BUFFER_BYTES)
+    }
+
+    /// Error for an SSE buffer that outgrew `max_bytes` without a delimiter
+    fn sse_buffer_overflow(buffered: usize, max_bytes: usize) -> Option<StreamError> {
+        (buffered > max_bytes).then(|| {
+            StreamError::Protocol(format!(
+                "SSE buffer exceeded {} bytes without an event delimiter",
+                max_bytes
+            ))
+        })
+    }
+
+    /// Wait for the next chunk, waking early once if the slow-start threshold passes
+    /// before any content has been seen.
+    async fn next_chunk<S>(
+        stream: &mut S,
+        stream_timeout: Duration,
+        watchdog: &mut SlowStartWatchdog,
+    ) -> ChunkWait<S::Item>
+    where
+        S: futures_util::Stream + Unpin,
+    {
+        if let Some(remaining) = watchdog.remaining() {
+            if remaining < stream_timeout {
+                return match timeout(remaining, stream.next()).await {
+                    Ok(item) => ChunkWait::Item(item),
+                    Err(_) => ChunkWait::SlowStart(watchdog.fire()),
+                };
+            }
+        }
+        match timeout(stream_timeout, stream.next()).await {
+            Ok(item) => ChunkWait::Item(item),
+            Err(_) => ChunkWait::TimedOut,
+        }
     }
 
     /// Find SSE delimiter in buffer, returns (index, delimiter_length)
@@ -822,6 +1388,8 @@ impl StreamHandler {
     fn parse_sse_event(raw: &str) -> Option<SseEvent> {
         let mut event: Option<String> = None;
         let mut data_lines = Vec::new();
+        let mut id: Option<String> = None;
+        let mut retry_ms: Option<u64> = None;
         for line in raw.lines() {
             if let Some(rest) = line.strip_prefix("event:") {
                 event = Some(rest.trim().to_string());
@@ -829,14 +1397,28 @@ impl StreamHandler {
                 // Preserve payload exactly, only removing single optional leading space per SSE spec
                 let data = rest.strip_prefix(' ').unwrap_or(rest);
                 data_lines.push(data.to_string());
+            } else if let Some(rest) = line.strip_prefix("id:") {
+                let value = rest.strip_prefix(' ').unwrap_or(rest);
+                // Ids containing NUL are ignored per the SSE spec
+                if !value.contains('\0') {
+                    id = Some(value.to_string());
+                }
+            } else if let Some(rest) = line.strip_prefix("retry:") {
+                let value = rest.strip_prefix(' ').unwrap_or(rest);
+                if !value.is_empty() && value.bytes().all(|b| b.is_ascii_digit()) {
+                    retry_ms = value.parse().ok();
+                }
             }
         }
-        if data_lines.is_empty() {
+        if data_lines.is_empty() && id.is_none() && retry_ms.is_none() {
             return None;
         }
         Some(SseEvent {
             event,
+            has_data: !data_lines.is_empty(),
             data: data_lines.join("\n"),
+            id,
+            retry_ms,
         })
     }
 
@@ -851,15 +1433,77 @@ impl StreamHandler {
         }
     }
 
-    fn emit_stream_event(
-        &self,
-        window: &tauri::Window,
-        event_name: &str,
-        _request_id: &str,
+    /// Emit a parsed provider event, interleaving `JsonPartial` and `UsageDelta`
+    /// events when the request asked for them. Both are flushed before `Done`.
+    fn emit_content_event(
+        emitted: &mut EmittedEvents,
         event: &StreamEvent,
+        json_assembler: Option<&mut JsonStreamAssembler>,
+        usage_estimator: Option<&mut UsageEstimator>,
     ) {
-        // log::info!("[LLM Stream {}] Emitting event: {:?}", request_id, event);
-        let _ = window.emit(event_name, event);
+        let mut usage_delta = usage_estimator.and_then(|estimator| estimator.observe(event));
+        let is_done = matches!(event, StreamEvent::Done { .. });
+        if is_done {
+            if let Some(delta) = usage_delta.take() {
+                emitted.emit(&delta);
+            }
+        }
+
+        match json_assembler {
+            None => emitted.emit(event),
+            Some(assembler) => {
+                if is_done {
+                    if let Some(partial) = assembler.finish() {
+                        emitted.emit(&partial);
+                    }
+                }
+                emitted.emit(event);
+                if let StreamEvent::TextDelta { text } = event {
+                    if let Some(partial) = assembler.push(text) {
+                        emitted.emit(&partial);
+                    }
+                }
+            }
+        }
+
+        if let Some(delta) = usage_delta {
+            emitted.emit(&delta);
+        }
+    }
+
+    /// Log a breaker state change and attach it to the request's trace span
+    fn record_circuit_transition<R: tauri::Runtime>(
+        window: &tauri::Window<R>,
+        stream_log: &StreamLog,
+        span_id: Option<&String>,
+        transition: Option<CircuitTransition>,
+    ) {
+        let Some(transition) = transition else {
+            return;
+        };
+        stream_log.warn(format_args!(
+            "Circuit for provider {} moved {:?} -> {:?} after {} failures",
+            transition.provider_id, transition.from, transition.to, transition.consecutive_failures
+        ));
+        if let Some(span_id) = span_id {
+            let trace_writer = window.app_handle().state::<Arc<TraceWriter>>();
+            trace_writer.add_event(
+                span_id.clone(),
+                crate::llm::tracing::types::attributes::GEN_AI_CIRCUIT_BREAKER.to_string(),
+                serde_json::to_value(&transition).ok(),
+            );
+        }
+    }
+
+    /// Chat session the request belongs to, as tagged in its trace metadata
+    fn session_id(request: &StreamTextRequest) -> Option<String> {
+        request
+            .trace_context
+            .as_ref()
+            .and_then(|context| context.metadata.as_ref())
+            .and_then(|metadata| metadata.get("session_id"))
+            .filter(|value| !value.is_empty())
+            .cloned()
     }
 
     fn build_response_payload(
@@ -920,6 +1564,99 @@ impl StreamHandler {
 struct SseEvent {
     event: Option<String>,
     data: String,
+    /// False for blocks carrying only `id:` or `retry:`, which dispatch nothing
+    has_data: bool,
+    id: Option<String>,
+    retry_ms: Option<u64>,
+}
+
+enum ChunkWait<T> {
+    Item(Option<T>),
+    SlowStart(StreamEvent),
+    TimedOut,
+}
+
+/// Tracks time-to-first-token and fires a single `SlowStart` warning when the
+/// threshold passes before the first text, reasoning or image event.
+struct SlowStartWatchdog {
+    threshold: Option<Duration>,
+    started_at: Instant,
+    armed: bool,
+}
+
+impl SlowStartWatchdog {
+    fn new(threshold: Option<Duration>, started_at: Instant) -> Self {
+        Self {
+            threshold,
+            started_at,
+            armed: threshold.is_some(),
+        }
+    }
+
+    fn elapsed(&self) -> Duration {
+        self.started_at.elapsed()
+    }
+
+    /// Time left before the warning fires, or `None` once disarmed
+    fn remaining(&self) -> Option<Duration> {
+        if !self.armed {
+            return None;
+        }
+        self.threshold
+            .map(|threshold| threshold.saturating_sub(self.elapsed()))
+    }
+
+    fn observe(&mut self, event: &StreamEvent) {
+        if matches!(
+            event,
+            StreamEvent::TextDelta { .. }
+                | StreamEvent::ReasoningStart { .. }
+                | StreamEvent::ImageStart { .. }
+        ) {
+            self.armed = false;
+        }
+    }
+
+    fn fire(&mut self) -> StreamEvent {
+        self.armed = false;
+        StreamEvent::SlowStart {
+            elapsed_ms: self.elapsed().as_millis() as u64,
+        }
+    }
+}
+
+/// Where every event of a stream goes: the requesting window, the webhook
+/// summary and, when the request asks for them, the chat-history message
+/// assembler and requests attached through an idempotency key.
+struct EmittedEvents {
+    client: EventSink,
+    summary: CompletionSummary,
+    persister: Option<StreamMessageAssembler>,
+    /// Set when the request claimed an idempotency key; feeds attached requests
+    lease: Option<IdempotencyLease>,
+}
+
+impl EmittedEvents {
+    fn emit(&mut self, event: &StreamEvent) {
+        (self.client)(event);
+        self.summary.observe(event);
+        if let Some(lease) = self.lease.as_ref() {
+            lease.forward(event);
+        }
+        if let Some(snapshot) = self.persister.as_mut().and_then(|p| p.observe(event)) {
+            publish_message_snapshot(snapshot);
+        }
+    }
+
+    /// Emit `error` as a `StreamEvent::Error` and hand it back to be returned
+    fn error(&mut self, error: StreamError, provider_error: Option<ProviderError>) -> StreamError {
+        self.emit(&StreamEvent::Error {
+            message: error.to_string(),
+            provider_error,
+            kind: Some(error.kind()),
+        });
+        error
+    }
 }
 
 #[cfg(test)]
@@ -928,8 +1665,8 @@ mod tests {
     use crate::database::Database;
     use crate::llm::auth::api_key_manager::ApiKeyManager;
     use crate::llm::protocols::openai_responses_protocol::{
-        parse_openai_oauth_event_legacy, parse_openai_oauth_function_call_done,
-        OpenAiResponsesProtocol,
+        parse_openai_oauth_event, parse_openai_oauth_event_legacy,
+        parse_openai_oauth_function_call_done, OpenAiResponsesProtocol,
     };
     use crate::llm::protocols::request_builder::{ProtocolRequestBuilder, RequestBuildContext};
     use crate::llm::protocols::{ProtocolStreamState, ToolCallAccum};
@@ -1004,6 +1741,8 @@ mod tests {
             top_k: None,
             provider_options: None,
             trace_context: None,
+            logprobs: None,
+            top_logprobs: None,
         };
 
         let base_url = provider
@@ -1035,6 +1774,7 @@ mod tests {
             headers: None,
             extra_body: None,
             auth_type: crate::llm::types::AuthType::Bearer,
+            body_transforms: None,
         });
 
         let request = StreamTextRequest {
@@ -1052,6 +1792,18 @@ mod tests {
             provider_options: None,
             request_id: None,
             trace_context: None,
+            partial_json: None,
+            fallback_models: None,
+            logprobs: None,
+            top_logprobs: None,
+            estimate_usage: false,
+            metadata: None,
+            resume_on_disconnect: false,
+            allowed_tools: None,
+            stream_tool_call_deltas: false,
+            completion_webhook: None,
+            persist_to_session: None,
+            idempotency_key: None,
         };
 
         let ctx = ProviderContext {
@@ -1066,6 +1818,8 @@ mod tests {
             top_k: request.top_k,
             provider_options: request.provider_options.as_ref(),
             trace_context: request.trace_context.as_ref(),
+            logprobs: request.logprobs,
+            top_logprobs: request.top_logprobs,
         };
 
         let endpoint = provider.resolve_endpoint_path(&ctx).await;
@@ -1102,6 +1856,7 @@ mod tests {
             headers: None,
             extra_body: None,
             auth_type: crate::llm::types::AuthType::Bearer,
+            body_transforms: None,
         });
 
         let request = StreamTextRequest {
@@ -1117,34 +1872,151 @@ mod tests {
             top_p: None,
             top_k: None,
             provider_options: None,
-            request_id: None,
+            request_id: None,
+            trace_context: None,
+            partial_json: None,
+            fallback_models: None,
+            logprobs: None,
+            top_logprobs: None,
+            estimate_usage: false,
+            metadata: None,
+            resume_on_disconnect: false,
+            allowed_tools: None,
+            stream_tool_call_deltas: false,
+            completion_webhook: None,
+            persist_to_session: None,
+            idempotency_key: None,
+        };
+
+        let ctx = ProviderContext {
+            provider_config: provider.config(),
+            api_key_manager: &api_keys,
+            model: &request.model,
+            messages: &request.messages,
+            tools: request.tools.as_deref(),
+            temperature: request.temperature,
+            max_tokens: request.max_tokens,
+            top_p: request.top_p,
+            top_k: request.top_k,
+            provider_options: request.provider_options.as_ref(),
+            trace_context: request.trace_context.as_ref(),
+            logprobs: request.logprobs,
+            top_logprobs: request.top_logprobs,
+        };
+
+        let endpoint = provider.resolve_endpoint_path(&ctx).await;
+        assert_eq!(endpoint, "chat/completions");
+
+        let body = provider.build_request(&ctx).await.expect("build request");
+        assert!(body.get("messages").is_some());
+        assert!(body.get("input").is_none());
+        assert_eq!(
+            body.get("model").and_then(|value| value.as_str()),
+            Some("gpt-4o@openai")
+        );
+    }
+
+    fn tool(name: &str) -> ToolDefinition {
+        ToolDefinition {
+            tool_type: "function".to_string(),
+            name: name.to_string(),
+            description: None,
+            parameters: json!({ "type": "object" }),
+            strict: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn allowed_tools_filters_disallowed_tool_from_request_body() {
+        let dir = TempDir::new().expect("temp dir");
+        let db_path = dir.path().join("talkcody-test.db");
+        let db = Arc::new(Database::new(db_path.to_string_lossy().to_string()));
+        db.connect().await.expect("db connect");
+        let api_keys = ApiKeyManager::new(db, std::path::PathBuf::from("/tmp"));
+
+        let provider = OpenAiProvider::new(ProviderConfig {
+            id: "openai".to_string(),
+            name: "OpenAI".to_string(),
+            protocol: ProtocolType::OpenAiCompatible,
+            base_url: "https://api.openai.com/v1".to_string(),
+            api_key_name: "OPENAI_API_KEY".to_string(),
+            supports_oauth: true,
+            supports_coding_plan: false,
+            supports_international: false,
+            coding_plan_base_url: None,
+            international_base_url: None,
+            headers: None,
+            extra_body: None,
+            auth_type: crate::llm::types::AuthType::Bearer,
+            body_transforms: None,
+        });
+
+        let allowed = vec!["read_file".to_string()];
+        let tools = StreamHandler::allowed_tool_definitions(
+            Some(&[tool("read_file"), tool("bash")]),
+            Some(&allowed),
+        );
+        let messages = vec![Message::User {
+            content: MessageContent::Text("hi".to_string()),
+            provider_options: None,
+        }];
+        let ctx = ProviderContext {
+            provider_config: provider.config(),
+            api_key_manager: &api_keys,
+            model: "gpt-4o",
+            messages: &messages,
+            tools: tools.as_deref(),
+            temperature: None,
+            max_tokens: None,
+            top_p: None,
+            top_k: None,
+            provider_options: None,
             trace_context: None,
+            logprobs: None,
+            top_logprobs: None,
         };
 
-        let ctx = ProviderContext {
-            provider_config: provider.config(),
-            api_key_manager: &api_keys,
-            model: &request.model,
-            messages: &request.messages,
-            tools: request.tools.as_deref(),
-            temperature: request.temperature,
-            max_tokens: request.max_tokens,
-            top_p: request.top_p,
-            top_k: request.top_k,
-            provider_options: request.provider_options.as_ref(),
-            trace_context: request.trace_context.as_ref(),
-        };
+        let body = provider.build_request(&ctx).await.expect("build request");
+        let names: Vec<&str> = body["tools"]
+            .as_array()
+            .expect("tools array")
+            .iter()
+            .filter_map(|tool| tool["function"]["name"].as_str())
+            .collect();
+        assert_eq!(names, vec!["read_file"]);
 
-        let endpoint = provider.resolve_endpoint_path(&ctx).await;
-        assert_eq!(endpoint, "chat/completions");
+        // Without an allowlist the tool set is passed through unchanged
+        let unrestricted =
+            StreamHandler::allowed_tool_definitions(Some(&[tool("read_file"), tool("bash")]), None)
+                .expect("tools");
+        assert_eq!(unrestricted.len(), 2);
+    }
 
-        let body = provider.build_request(&ctx).await.expect("build request");
-        assert!(body.get("messages").is_some());
-        assert!(body.get("input").is_none());
-        assert_eq!(
-            body.get("model").and_then(|value| value.as_str()),
-            Some("gpt-4o@openai")
-        );
+    #[test]
+    fn allowed_tools_rejects_out_of_band_tool_call() {
+        let allowed = vec!["read_file".to_string()];
+        let call = |name: &str| StreamEvent::ToolCall {
+            tool_call_id: "call_1".to_string(),
+            tool_name: name.to_string(),
+            input: json!({}),
+            provider_metadata: None,
+        };
+
+        match StreamHandler::reject_disallowed_tool_call(call("bash"), Some(&allowed)) {
+            StreamEvent::Error { message, kind, .. } => {
+                assert!(message.contains("bash"));
+                assert_eq!(kind, Some(crate::llm::types::StreamErrorKind::Protocol));
+            }
+            other => panic!("expected error event, got {:?}", other),
+        }
+        assert!(matches!(
+            StreamHandler::reject_disallowed_tool_call(call("read_file"), Some(&allowed)),
+            StreamEvent::ToolCall { .. }
+        ));
+        assert!(matches!(
+            StreamHandler::reject_disallowed_tool_call(call("bash"), None),
+            StreamEvent::ToolCall { .. }
+        ));
     }
 
     #[tokio::test]
@@ -1168,6 +2040,7 @@ mod tests {
             headers: None,
             extra_body: None,
             auth_type: crate::llm::types::AuthType::Bearer,
+            body_transforms: None,
         });
 
         let request = StreamTextRequest {
@@ -1209,6 +2082,18 @@ mod tests {
             provider_options: None,
             request_id: None,
             trace_context: None,
+            partial_json: None,
+            fallback_models: None,
+            logprobs: None,
+            top_logprobs: None,
+            estimate_usage: false,
+            metadata: None,
+            resume_on_disconnect: false,
+            allowed_tools: None,
+            stream_tool_call_deltas: false,
+            completion_webhook: None,
+            persist_to_session: None,
+            idempotency_key: None,
         };
 
         let request_ctx = RequestBuildContext {
@@ -1221,6 +2106,8 @@ mod tests {
             top_k: request.top_k,
             provider_options: request.provider_options.as_ref(),
             extra_body: provider.config().extra_body.as_ref(),
+            logprobs: None,
+            top_logprobs: None,
         };
         let body = OpenAiResponsesProtocol
             .build_request(request_ctx)
@@ -1411,6 +2298,261 @@ mod tests {
         assert_eq!(delimiter, Some((11, 4)));
     }
 
+    /// Serve `body` as an event stream to every request and return the base URL
+    fn serve_event_stream(body: String) -> String {
+        let server = tiny_http::Server::http("127.0.0.1:0").expect("server");
+        let base_url = match server.server_addr() {
+            tiny_http::ListenAddr::IP(addr) => format!("http://{}/v1", addr),
+            _ => panic!("Expected IP SocketAddr"),
+        };
+        std::thread::spawn(move || {
+            for request in server.incoming_requests() {
+                let response = tiny_http::Response::from_string(body.clone()).with_header(
+                    tiny_http::Header::from_bytes(&b"Content-Type"[..], &b"text/event-stream"[..])
+                        .unwrap(),
+                );
+                let _ = request.respond(response);
+            }
+        });
+        base_url
+    }
+
+    /// Handler whose only provider is an OpenAI-compatible server at `base_url`
+    async fn local_provider_handler(
+        dir: &TempDir,
+        provider_id: &str,
+        base_url: String,
+        settings: &[(&str, &str)],
+    ) -> StreamHandler {
+        let db_path = dir.path().join("talkcody-test.db");
+        let db = Arc::new(Database::new(db_path.to_string_lossy().to_string()));
+        db.connect().await.expect("db connect");
+        db.execute(
+            "CREATE TABLE IF NOT EXISTS settings (key TEXT PRIMARY KEY, value TEXT, updated_at INTEGER)",
+            vec![],
+        )
+        .await
+        .expect("create settings");
+        let api_keys = ApiKeyManager::new(db, dir.path().to_path_buf());
+        for (key, value) in settings {
+            api_keys.set_setting(key, value).await.expect("set setting");
+        }
+        let registry = ProviderRegistry::new(vec![ProviderConfig {
+            id: provider_id.to_string(),
+            name: provider_id.to_string(),
+            protocol: ProtocolType::OpenAiCompatible,
+            base_url,
+            api_key_name: format!("{}_API_KEY", provider_id.to_ascii_uppercase()),
+            supports_oauth: false,
+            supports_coding_plan: false,
+            supports_international: false,
+            coding_plan_base_url: None,
+            international_base_url: None,
+            headers: None,
+            extra_body: None,
+            auth_type: crate::llm::types::AuthType::None,
+            body_transforms: None,
+        }]);
+        StreamHandler::new(registry, api_keys)
+    }
+
+    /// Serve `body` as an event stream, but reset the first connection so the
+    /// handler has to retry after its backoff
+    fn serve_event_stream_after_reset(body: &'static str) -> String {
+        use std::io::{Read, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").expect("listener");
+        let base_url = format!("http://{}/v1", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            let (first, _) = listener.accept().expect("first connection");
+            drop(first);
+            let (mut stream, _) = listener.accept().expect("second connection");
+            // Read the whole request so closing does not reset the response
+            let mut request = Vec::new();
+            let mut chunk = [0u8; 4096];
+            loop {
+                let read = stream.read(&mut chunk).expect("read request");
+                request.extend_from_slice(&chunk[..read]);
+                let text = String::from_utf8_lossy(&request);
+                if let Some(header_end) = text.find("\r\n\r\n") {
+                    let content_length = text[..header_end]
+                        .lines()
+                        .find_map(|line| {
+                            let (name, value) = line.split_once(':')?;
+                            name.eq_ignore_ascii_case("content-length")
+                                .then(|| value.trim().parse::<usize>().ok())
+                                .flatten()
+                        })
+                        .unwrap_or(0);
+                    if request.len() >= header_end + 4 + content_length {
+                        break;
+                    }
+                }
+                if read == 0 {
+                    break;
+                }
+            }
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            stream
+                .write_all(response.as_bytes())
+                .expect("write response");
+        });
+        base_url
+    }
+
+    /// This test uses Tauri test infrastructure that may not work on Windows CI
+    #[tokio::test]
+    #[cfg(not(target_os = "windows"))]
+    async fn retried_request_is_not_flagged_as_slow_start() {
+        use tauri::Listener;
+
+        let base_url = serve_event_stream_after_reset(concat!(
+            "data: {\"choices\":[{\"index\":0,\"delta\":{\"content\":\"hello\"},\"finish_reason\":\"stop\"}]}\n\n",
+            "data: [DONE]\n\n",
+        ));
+        let dir = TempDir::new().expect("temp dir");
+        // Well under the 1s backoff before the retry, well over the retry's own TTFT
+        let handler = local_provider_handler(
+            &dir,
+            "retry_local",
+            base_url,
+            &[(TTFT_WARNING_SETTING_KEY, "500")],
+        )
+        .await;
+
+        let app = tauri::test::mock_app();
+        let webview = tauri::WebviewWindowBuilder::new(
+            &app,
+            "retry-ttft-test",
+            tauri::WebviewUrl::App("index.html".into()),
+        )
+        .build()
+        .unwrap();
+        let events = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let listener_events = events.clone();
+        app.listen_any("llm-stream-retry-ttft", move |event| {
+            let event: StreamEvent = serde_json::from_str(event.payload()).expect("stream event");
+            listener_events.lock().unwrap().push(event);
+        });
+
+        let started = Instant::now();
+        handler
+            .stream_completion(
+                webview.as_ref().window(),
+                preview_request("test-model@retry_local"),
+                "retry-ttft".to_string(),
+            )
+            .await
+            .expect("stream after retry");
+        assert!(started.elapsed() >= Duration::from_secs(1));
+
+        let events = events.lock().unwrap();
+        assert!(events
+            .iter()
+            .any(|event| matches!(event, StreamEvent::TextDelta { text } if text == "hello")));
+        assert!(!events
+            .iter()
+            .any(|event| matches!(event, StreamEvent::SlowStart { .. })));
+        assert!(matches!(events.last(), Some(StreamEvent::Done { .. })));
+    }
+
+    /// This test uses Tauri test infrastructure that may not work on Windows CI
+    #[tokio::test]
+    #[cfg(not(target_os = "windows"))]
+    async fn sse_buffer_without_delimiter_is_capped() {
+        use tauri::Listener;
+
+        // A server that keeps sending data but never a blank line
+        let base_url = serve_event_stream("data: {\"partial\":\"".repeat(64));
+        let dir = TempDir::new().expect("temp dir");
+        let handler = local_provider_handler(
+            &dir,
+            "sse_cap_local",
+            base_url,
+            &[(MAX_SSE_BUFFER_SETTING_KEY, "64")],
+        )
+        .await;
+
+        let app = tauri::test::mock_app();
+        let webview = tauri::WebviewWindowBuilder::new(
+            &app,
+            "sse-cap-test",
+            tauri::WebviewUrl::App("index.html".into()),
+        )
+        .build()
+        .unwrap();
+        let events = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let listener_events = events.clone();
+        app.listen_any("llm-stream-sse-cap", move |event| {
+            let event: StreamEvent = serde_json::from_str(event.payload()).expect("stream event");
+            listener_events.lock().unwrap().push(event);
+        });
+
+        let request = StreamTextRequest {
+            model: "test-model@sse_cap_local".to_string(),
+            messages: vec![Message::User {
+                content: MessageContent::Text("hi".to_string()),
+                provider_options: None,
+            }],
+            tools: None,
+            stream: Some(true),
+            temperature: None,
+            max_tokens: None,
+            top_p: None,
+            top_k: None,
+            provider_options: None,
+            request_id: None,
+            trace_context: None,
+            partial_json: None,
+            fallback_models: None,
+            logprobs: None,
+            top_logprobs: None,
+            estimate_usage: false,
+            metadata: None,
+            resume_on_disconnect: false,
+            allowed_tools: None,
+            stream_tool_call_deltas: false,
+            completion_webhook: None,
+            persist_to_session: None,
+            idempotency_key: None,
+        };
+        let result = handler
+            .stream_completion(webview.as_ref().window(), request, "sse-cap".to_string())
+            .await;
+
+        match result {
+            Err(StreamError::Protocol(message)) => {
+                assert!(message.contains("64 bytes"), "{}", message);
+            }
+            other => panic!("Expected protocol error, got {:?}", other),
+        }
+        let events = events.lock().unwrap();
+        match events.last() {
+            Some(StreamEvent::Error { kind, .. }) => {
+                assert_eq!(*kind, Some(StreamError::Protocol(String::new()).kind()));
+            }
+            other => panic!("Expected error event, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn sse_buffer_drained_by_delimiters_stays_under_cap() {
+        let max_bytes = 64;
+        let mut buffer: Vec<u8> = Vec::new();
+        for _ in 0..10 {
+            buffer.extend_from_slice(b"data: {\"ok\":true}\n\n");
+            while let Some((idx, delimiter_len)) = StreamHandler::find_sse_delimiter(&buffer) {
+                buffer.drain(..idx + delimiter_len);
+            }
+            assert!(StreamHandler::sse_buffer_overflow(buffer.len(), max_bytes).is_none());
+        }
+        assert!(buffer.is_empty());
+    }
+
     #[test]
     fn build_response_payload_includes_response_text() {
         let payload = StreamHandler::build_response_payload(
@@ -1441,6 +2583,29 @@ mod tests {
         assert_eq!(event.data, "first\nsecond");
     }
 
+    #[test]
+    fn parse_sse_event_reads_id_and_retry() {
+        let raw = "id: evt-42\nretry: 2500\nevent: message\ndata: {\"x\":1}\n";
+        let event = StreamHandler::parse_sse_event(raw).expect("parsed");
+        assert_eq!(event.id.as_deref(), Some("evt-42"));
+        assert_eq!(event.retry_ms, Some(2500));
+        assert!(event.has_data);
+        assert_eq!(event.data, "{\"x\":1}");
+
+        // Field-only block: nothing to dispatch, but the hints are kept
+        let event = StreamHandler::parse_sse_event("retry: 1000\n").expect("parsed");
+        assert!(!event.has_data);
+        assert_eq!(event.retry_ms, Some(1000));
+        assert_eq!(event.id, None);
+
+        // Invalid retry values are ignored; an empty id resets
+        let event = StreamHandler::parse_sse_event("id:\nretry: soon\ndata: x\n").expect("parsed");
+        assert_eq!(event.id.as_deref(), Some(""));
+        assert_eq!(event.retry_ms, None);
+
+        assert!(StreamHandler::parse_sse_event(": keep-alive\n").is_none());
+    }
+
     #[tokio::test]
     async fn resolve_base_url_prefers_coding_plan_setting() {
         let dir = TempDir::new().expect("temp dir");
@@ -1481,6 +2646,8 @@ mod tests {
             top_k: None,
             provider_options: None,
             trace_context: None,
+            logprobs: None,
+            top_logprobs: None,
         };
 
         let base_url = provider
@@ -1715,6 +2882,7 @@ mod tests {
             headers: None,
             extra_body: None,
             auth_type: crate::llm::types::AuthType::Bearer,
+            body_transforms: None,
         });
 
         let request = StreamTextRequest {
@@ -1760,6 +2928,18 @@ mod tests {
             provider_options: None,
             request_id: None,
             trace_context: None,
+            partial_json: None,
+            fallback_models: None,
+            logprobs: None,
+            top_logprobs: None,
+            estimate_usage: false,
+            metadata: None,
+            resume_on_disconnect: false,
+            allowed_tools: None,
+            stream_tool_call_deltas: false,
+            completion_webhook: None,
+            persist_to_session: None,
+            idempotency_key: None,
         };
 
         let request_ctx = RequestBuildContext {
@@ -1772,6 +2952,8 @@ mod tests {
             top_k: request.top_k,
             provider_options: request.provider_options.as_ref(),
             extra_body: provider.config().extra_body.as_ref(),
+            logprobs: None,
+            top_logprobs: None,
         };
         let body = OpenAiResponsesProtocol
             .build_request(request_ctx)
@@ -1943,6 +3125,70 @@ mod tests {
         }
     }
 
+    #[test]
+    fn openai_oauth_parses_image_generation_output() {
+        let mut state = ProtocolStreamState::default();
+        let payloads = [
+            json!({
+                "type": "response.output_item.added",
+                "item": { "type": "image_generation_call", "id": "ig_1", "status": "in_progress" }
+            }),
+            json!({
+                "type": "response.image_generation_call.partial_image",
+                "item_id": "ig_1",
+                "partial_image_index": 0,
+                "partial_image_b64": "cHJldmlldw=="
+            }),
+            json!({
+                "type": "response.output_item.done",
+                "item": {
+                    "type": "image_generation_call",
+                    "id": "ig_1",
+                    "status": "completed",
+                    "output_format": "webp",
+                    "result": "ZmluYWw="
+                }
+            }),
+        ];
+
+        let mut events = Vec::new();
+        for payload in payloads {
+            if let Some(event) =
+                parse_openai_oauth_event_legacy(None, &payload.to_string(), &mut state)
+                    .expect("parse event")
+            {
+                events.push(event);
+            }
+            events.append(&mut state.pending_events);
+        }
+
+        let mut response_text = String::new();
+        for event in &events {
+            StreamHandler::append_text_delta(&mut response_text, event);
+        }
+        assert!(response_text.is_empty());
+
+        assert_eq!(events.len(), 4, "events: {:?}", events);
+        assert!(matches!(&events[0], StreamEvent::ImageStart { id } if id == "ig_1"));
+        match &events[1] {
+            StreamEvent::ImageDelta { id, b64, mime } => {
+                assert_eq!(id, "ig_1");
+                assert_eq!(b64, "cHJldmlldw==");
+                assert_eq!(mime, "image/png");
+            }
+            other => panic!("Expected partial ImageDelta, got {:?}", other),
+        }
+        match &events[2] {
+            StreamEvent::ImageDelta { id, b64, mime } => {
+                assert_eq!(id, "ig_1");
+                assert_eq!(b64, "ZmluYWw=");
+                assert_eq!(mime, "image/webp");
+            }
+            other => panic!("Expected final ImageDelta, got {:?}", other),
+        }
+        assert!(matches!(&events[3], StreamEvent::ImageEnd { id } if id == "ig_1"));
+    }
+
     #[test]
     fn openai_oauth_emits_reasoning_summary_deltas() {
         let mut state = ProtocolStreamState::default();
@@ -1998,11 +3244,11 @@ mod tests {
             .expect("parse event")
             .expect("event");
         match event {
-            StreamEvent::ReasoningDelta { id, text, .. } => {
+            StreamEvent::ReasoningSummaryDelta { id, text } => {
                 assert_eq!(id, "rs_1:0");
                 assert_eq!(text, "Hello");
             }
-            _ => panic!("Expected ReasoningDelta, got {:?}", event),
+            _ => panic!("Expected ReasoningSummaryDelta, got {:?}", event),
         }
 
         let event = parse_openai_oauth_event_legacy(None, &summary_done.to_string(), &mut state)
@@ -2016,6 +3262,106 @@ mod tests {
         }
     }
 
+    #[test]
+    fn openai_oauth_parses_reasoning_summary_delta_sequence() {
+        let mut state = StreamParseState::default();
+        let payloads = [
+            json!({
+                "type": "response.output_item.added",
+                "item": { "type": "reasoning", "id": "rs_3", "encrypted_content": "enc" }
+            }),
+            json!({
+                "type": "response.reasoning_summary_part.added",
+                "item_id": "rs_3",
+                "summary_index": 0
+            }),
+            json!({
+                "type": "response.reasoning_summary_text.delta",
+                "item_id": "rs_3",
+                "summary_index": 0,
+                "delta": "Checking "
+            }),
+            json!({
+                "type": "response.reasoning_summary_text.delta",
+                "item_id": "rs_3",
+                "summary_index": 0,
+                "delta": "the files"
+            }),
+            json!({
+                "type": "response.reasoning_summary_text.done",
+                "item_id": "rs_3",
+                "summary_index": 0,
+                "text": "Checking the files"
+            }),
+            json!({
+                "type": "response.reasoning_summary_part.done",
+                "item_id": "rs_3",
+                "summary_index": 0
+            }),
+        ];
+
+        let mut events = Vec::new();
+        for payload in payloads {
+            let event_type = payload["type"].as_str().map(str::to_string);
+            if let Some(event) =
+                parse_openai_oauth_event(event_type.as_deref(), &payload.to_string(), &mut state)
+                    .expect("parse event")
+            {
+                events.push(event);
+            }
+            events.append(&mut state.pending_events);
+        }
+
+        let summaries: Vec<(String, String)> = events
+            .iter()
+            .filter_map(|event| match event {
+                StreamEvent::ReasoningSummaryDelta { id, text } => Some((id.clone(), text.clone())),
+                _ => None,
+            })
+            .collect();
+        // The `.done` text duplicates the streamed deltas and is not re-emitted
+        assert_eq!(
+            summaries,
+            vec![
+                ("rs_3:0".to_string(), "Checking ".to_string()),
+                ("rs_3:0".to_string(), "the files".to_string()),
+            ]
+        );
+        assert!(!events
+            .iter()
+            .any(|event| matches!(event, StreamEvent::ReasoningDelta { .. })));
+        assert!(
+            matches!(events.first(), Some(StreamEvent::ReasoningStart { id, .. }) if id == "rs_3:0")
+        );
+        assert!(matches!(events.last(), Some(StreamEvent::ReasoningEnd { id }) if id == "rs_3:0"));
+    }
+
+    #[test]
+    fn openai_oauth_emits_reasoning_summary_text_done_without_deltas() {
+        let mut state = StreamParseState::default();
+        let done = json!({
+            "type": "response.reasoning_summary_text.done",
+            "item_id": "rs_4",
+            "summary_index": 1,
+            "text": "Summarized plan"
+        });
+
+        let event = parse_openai_oauth_event(
+            Some("response.reasoning_summary_text.done"),
+            &done.to_string(),
+            &mut state,
+        )
+        .expect("parse event")
+        .expect("event");
+        match event {
+            StreamEvent::ReasoningSummaryDelta { id, text } => {
+                assert_eq!(id, "rs_4:1");
+                assert_eq!(text, "Summarized plan");
+            }
+            _ => panic!("Expected ReasoningSummaryDelta, got {:?}", event),
+        }
+    }
+
     #[test]
     fn openai_oauth_emits_reasoning_end_with_encrypted_content_on_output_done() {
         let mut state = ProtocolStreamState::default();
@@ -2134,4 +3480,180 @@ mod tests {
             _ => panic!("Expected ReasoningEnd, got {:?}", event),
         }
     }
+
+    #[tokio::test]
+    async fn slow_start_watchdog_warns_when_first_chunk_is_late() {
+        let mut stream = Box::pin(futures_util::stream::once(async {
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            "data: first"
+        }));
+        let mut watchdog = SlowStartWatchdog::new(Some(Duration::from_millis(20)), Instant::now());
+
+        let first =
+            StreamHandler::next_chunk(&mut stream, Duration::from_secs(5), &mut watchdog).await;
+        match first {
+            ChunkWait::SlowStart(StreamEvent::SlowStart { elapsed_ms }) => {
+                assert!(elapsed_ms >= 20);
+            }
+            _ => panic!("expected slow-start warning"),
+        }
+
+        // The stream is not cancelled and the warning only fires once
+        let second =
+            StreamHandler::next_chunk(&mut stream, Duration::from_secs(5), &mut watchdog).await;
+        assert!(matches!(second, ChunkWait::Item(Some("data: first"))));
+        assert!(watchdog.remaining().is_none());
+    }
+
+    #[tokio::test]
+    async fn slow_start_watchdog_silent_for_fast_stream() {
+        let mut stream = futures_util::stream::iter(vec!["data: first", "data: second"]);
+        let mut watchdog = SlowStartWatchdog::new(Some(Duration::from_millis(50)), Instant::now());
+
+        let first =
+            StreamHandler::next_chunk(&mut stream, Duration::from_secs(5), &mut watchdog).await;
+        assert!(matches!(first, ChunkWait::Item(Some("data: first"))));
+        watchdog.observe(&StreamEvent::TextDelta {
+            text: "hi".to_string(),
+        });
+        assert!(watchdog.remaining().is_none());
+
+        tokio::time::sleep(Duration::from_millis(80)).await;
+        let second =
+            StreamHandler::next_chunk(&mut stream, Duration::from_secs(5), &mut watchdog).await;
+        assert!(matches!(second, ChunkWait::Item(Some("data: second"))));
+    }
+
+    #[test]
+    fn slow_start_event_serializes_as_kebab_case() {
+        let value = serde_json::to_value(StreamEvent::SlowStart { elapsed_ms: 1200 }).unwrap();
+        assert_eq!(value, json!({ "type": "slow-start", "elapsed_ms": 1200 }));
+    }
+
+    async fn preview_handler(dir: &TempDir) -> StreamHandler {
+        let db_path = dir.path().join("talkcody-test.db");
+        let db = Arc::new(Database::new(db_path.to_string_lossy().to_string()));
+        db.connect().await.expect("db connect");
+        db.execute(
+            "CREATE TABLE IF NOT EXISTS settings (key TEXT PRIMARY KEY, value TEXT, updated_at INTEGER)",
+            vec![],
+        )
+        .await
+        .expect("create settings");
+        let api_keys = ApiKeyManager::new(db, dir.path().to_path_buf());
+        StreamHandler::new(ProviderRegistry::new(builtin_providers()), api_keys)
+    }
+
+    fn preview_request(model: &str) -> StreamTextRequest {
+        StreamTextRequest {
+            model: model.to_string(),
+            messages: vec![
+                Message::System {
+                    content: "Be brief".to_string(),
+                    provider_options: None,
+                },
+                Message::User {
+                    content: MessageContent::Text("hi".to_string()),
+                    provider_options: None,
+                },
+            ],
+            tools: None,
+            stream: Some(true),
+            temperature: Some(0.2),
+            max_tokens: None,
+            top_p: None,
+            top_k: None,
+            provider_options: None,
+            request_id: None,
+            trace_context: None,
+            partial_json: None,
+            fallback_models: None,
+            logprobs: None,
+            top_logprobs: None,
+            estimate_usage: false,
+            metadata: None,
+            resume_on_disconnect: false,
+            allowed_tools: None,
+            stream_tool_call_deltas: false,
+            completion_webhook: None,
+            persist_to_session: None,
+            idempotency_key: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn preview_matches_openai_oauth_request() {
+        let dir = TempDir::new().expect("temp dir");
+        let handler = preview_handler(&dir).await;
+        handler
+            .api_keys
+            .set_oauth_setting("openai", "access_token", "oauth-secret")
+            .await
+            .expect("set oauth token");
+        let request = preview_request("gpt-5.2-codex@openai");
+
+        let preview = handler.build_preview(&request).await.expect("preview");
+
+        assert_eq!(preview.provider_id, "openai");
+        assert_eq!(
+            preview.url,
+            "https://chatgpt.com/backend-api/codex/responses"
+        );
+        let provider_config = builtin_providers()
+            .into_iter()
+            .find(|item| item.id == "openai")
+            .expect("openai provider");
+        let provider = OpenAiProvider::new(provider_config.clone());
+        let ctx = ProviderContext {
+            provider_config: &provider_config,
+            api_key_manager: &handler.api_keys,
+            model: &preview.model,
+            messages: &request.messages,
+            tools: None,
+            temperature: request.temperature,
+            max_tokens: None,
+            top_p: None,
+            top_k: None,
+            provider_options: None,
+            trace_context: None,
+            logprobs: None,
+            top_logprobs: None,
+        };
+        let expected = provider
+            .build_oauth_request(&ctx)
+            .await
+            .expect("oauth request");
+        assert_eq!(preview.body, expected);
+        assert_eq!(
+            preview.headers.get("authorization").map(String::as_str),
+            Some("REDACTED")
+        );
+    }
+
+    #[tokio::test]
+    async fn preview_redacts_api_key_header() {
+        let dir = TempDir::new().expect("temp dir");
+        let handler = preview_handler(&dir).await;
+        handler
+            .api_keys
+            .set_setting("api_key_openai", "sk-preview-secret")
+            .await
+            .expect("set api key");
+
+        let preview = handler
+            .build_preview(&preview_request("gpt-4o@openai"))
+            .await
+            .expect("preview");
+
+        assert!(preview.url.ends_with("/chat/completions"));
+        assert!(preview.body.get("messages").is_some());
+        assert_eq!(
+            preview.headers.get("authorization").map(String::as_str),
+            Some("REDACTED")
+        );
+        assert!(preview
+            .headers
+            .values()
+            .all(|value| !value.contains("sk-preview-secret")));
+    }
 }
@@ -0,0 +1,165 @@
+// Structured stream logging. Every record a stream writes carries its
+// `request_id`, `provider_id`, `model` and `trace_id` as `log` key-values, so
+// aggregators can filter by them; the message keeps its readable
+// "[LLM Stream <id>]" prefix for plain-text backends.
+
+use log::Level;
+use std::fmt;
+
+/// Fields attached to the log records of one stream
+#[derive(Debug, Clone)]
+pub struct StreamLog {
+    request_id: String,
+    provider_id: Option<String>,
+    model: String,
+    trace_id: Option<String>,
+}
+
+impl StreamLog {
+    /// Start with the model as requested; the provider is known once resolved
+    pub fn new(request_id: &str, model: &str) -> Self {
+        Self {
+            request_id: request_id.to_string(),
+            provider_id: None,
+            model: model.to_string(),
+            trace_id: None,
+        }
+    }
+
+    /// Record the resolved model key and provider
+    pub fn set_provider(&mut self, provider_id: &str, model: &str) {
+        self.provider_id = Some(provider_id.to_string());
+        self.model = model.to_string();
+    }
+
+    pub fn set_trace_id(&mut self, trace_id: &str) {
+        self.trace_id = Some(trace_id.to_string());
+    }
+
+    pub fn log(&self, level: Level, args: fmt::Arguments) {
+        log::log!(
+            level,
+            request_id = self.request_id.as_str(),
+            provider_id = self.provider_id.as_deref(),
+            model = self.model.as_str(),
+            trace_id = self.trace_id.as_deref();
+            "[LLM Stream {}] {}",
+            self.request_id,
+            args
+        );
+    }
+
+    pub fn error(&self, args: fmt::Arguments) {
+        self.log(Level::Error, args);
+    }
+
+    pub fn warn(&self, args: fmt::Arguments) {
+        self.log(Level::Warn, args);
+    }
+
+    pub fn info(&self, args: fmt::Arguments) {
+        self.log(Level::Info, args);
+    }
+
+    pub fn debug(&self, args: fmt::Arguments) {
+        self.log(Level::Debug, args);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use log::kv::{Error, Key, Value, VisitSource};
+    use log::{Log, Metadata, Record};
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    #[derive(Debug, Clone)]
+    struct Captured {
+        message: String,
+        fields: HashMap<String, String>,
+    }
+
+    struct CapturingLogger {
+        records: Mutex<Vec<Captured>>,
+    }
+
+    struct FieldCollector(HashMap<String, String>);
+
+    impl<'kvs> VisitSource<'kvs> for FieldCollector {
+        fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), Error> {
+            self.0.insert(key.as_str().to_string(), value.to_string());
+            Ok(())
+        }
+    }
+
+    impl Log for CapturingLogger {
+        fn enabled(&self, _: &Metadata) -> bool {
+            true
+        }
+
+        fn log(&self, record: &Record) {
+            let mut fields = FieldCollector(HashMap::new());
+            let _ = record.key_values().visit(&mut fields);
+            if let Ok(mut records) = self.records.lock() {
+                records.push(Captured {
+                    message: record.args().to_string(),
+                    fields: fields.0,
+                });
+            }
+        }
+
+        fn flush(&self) {}
+    }
+
+    static LOGGER: CapturingLogger = CapturingLogger {
+        records: Mutex::new(Vec::new()),
+    };
+
+    fn install_logger() {
+        let _ = log::set_logger(&LOGGER);
+        log::set_max_level(log::LevelFilter::Trace);
+    }
+
+    /// Records logged so far for `request_id`; other tests log concurrently
+    fn captured(request_id: &str) -> Vec<Captured> {
+        LOGGER
+            .records
+            .lock()
+            .expect("records")
+            .iter()
+            .filter(|record| {
+                record.fields.get("request_id").map(String::as_str) == Some(request_id)
+            })
+            .cloned()
+            .collect()
+    }
+
+    #[test]
+    fn records_carry_stream_fields() {
+        install_logger();
+        let mut stream_log = StreamLog::new("log-test-1", "gpt-4o");
+        stream_log.info(format_args!("Starting stream completion"));
+        stream_log.set_provider("openai", "gpt-4o@openai");
+        stream_log.set_trace_id("trace-1");
+        stream_log.warn(format_args!("Request attempt {}/{} failed", 1, 4));
+
+        let records = captured("log-test-1");
+        assert_eq!(records.len(), 2);
+
+        assert_eq!(
+            records[0].message,
+            "[LLM Stream log-test-1] Starting stream completion"
+        );
+        assert_eq!(records[0].fields["model"], "gpt-4o");
+
+        let fields = &records[1].fields;
+        assert_eq!(
+            records[1].message,
+            "[LLM Stream log-test-1] Request attempt 1/4 failed"
+        );
+        assert_eq!(fields["provider_id"], "openai");
+        assert_eq!(fields["model"], "gpt-4o@openai");
+        assert_eq!(fields["trace_id"], "trace-1");
+    }
+}
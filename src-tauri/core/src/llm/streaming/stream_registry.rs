@@ -0,0 +1,140 @@
+// In-flight `llm_stream_text` tasks, keyed by the window that started them.
+// Cancelling or closing one window only aborts its own streams; aborting the
+// task drops the response body and releases the HTTP connection.
+
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use tauri::async_runtime::JoinHandle;
+
+/// `(window_label, request_id)`
+type StreamKey = (String, String);
+
+fn active_streams() -> &'static Mutex<HashMap<StreamKey, JoinHandle<()>>> {
+    static STREAMS: OnceLock<Mutex<HashMap<StreamKey, JoinHandle<()>>>> = OnceLock::new();
+    STREAMS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Track a spawned stream task; a previous task under the same key is aborted
+pub fn register_stream(window_label: &str, request_id: &str, handle: JoinHandle<()>) {
+    let Ok(mut streams) = active_streams().lock() else {
+        return;
+    };
+    // Tasks that finished before they were registered never call `finish_stream`
+    streams.retain(|_, handle| !handle.inner().is_finished());
+    let key = (window_label.to_string(), request_id.to_string());
+    if let Some(previous) = streams.insert(key, handle) {
+        previous.abort();
+    }
+}
+
+/// Forget a stream that ran to completion
+pub fn finish_stream(window_label: &str, request_id: &str) {
+    if let Ok(mut streams) = active_streams().lock() {
+        streams.remove(&(window_label.to_string(), request_id.to_string()));
+    }
+}
+
+/// Abort one stream of a window; returns false if it is not running
+pub fn cancel_stream(window_label: &str, request_id: &str) -> bool {
+    let handle = active_streams().lock().ok().and_then(|mut streams| {
+        streams.remove(&(window_label.to_string(), request_id.to_string()))
+    });
+    match handle {
+        Some(handle) => {
+            handle.abort();
+            true
+        }
+        None => false,
+    }
+}
+
+/// Abort every stream started by `window_label`, leaving other windows untouched.
+/// Returns the number of streams aborted.
+pub fn cancel_all_streams_for_window(window_label: &str) -> usize {
+    let handles: Vec<JoinHandle<()>> = match active_streams().lock() {
+        Ok(mut streams) => {
+            let keys: Vec<StreamKey> = streams
+                .keys()
+                .filter(|(label, _)| label == window_label)
+                .cloned()
+                .collect();
+            keys.iter().filter_map(|key| streams.remove(key)).collect()
+        }
+        Err(_) => return 0,
+    };
+    for handle in &handles {
+        handle.abort();
+    }
+    if !handles.is_empty() {
+        log::info!(
+            "Cancelled {} in-flight stream(s) for window {}",
+            handles.len(),
+            window_label
+        );
+    }
+    handles.len()
+}
+
+/// Request ids of the streams currently running for `window_label`
+pub fn active_stream_ids(window_label: &str) -> Vec<String> {
+    let Ok(streams) = active_streams().lock() else {
+        return Vec::new();
+    };
+    let mut ids: Vec<String> = streams
+        .iter()
+        .filter(|((label, _), handle)| label == window_label && !handle.inner().is_finished())
+        .map(|((_, request_id), _)| request_id.clone())
+        .collect();
+    ids.sort();
+    ids
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    fn spawn_sleeper(finished: Arc<AtomicUsize>) -> JoinHandle<()> {
+        tauri::async_runtime::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(300)).await;
+            finished.fetch_add(1, Ordering::SeqCst);
+        })
+    }
+
+    #[tokio::test]
+    async fn cancelling_one_window_leaves_other_windows_streams_running() {
+        let cancelled = Arc::new(AtomicUsize::new(0));
+        let kept = Arc::new(AtomicUsize::new(0));
+        register_stream("win-cancel-a", "req-1", spawn_sleeper(cancelled.clone()));
+        register_stream("win-cancel-a", "req-2", spawn_sleeper(cancelled.clone()));
+        // Same request id in another window is a distinct stream
+        register_stream("win-cancel-b", "req-1", spawn_sleeper(kept.clone()));
+
+        assert_eq!(cancel_all_streams_for_window("win-cancel-a"), 2);
+        assert!(active_stream_ids("win-cancel-a").is_empty());
+        assert_eq!(active_stream_ids("win-cancel-b"), vec!["req-1".to_string()]);
+
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        assert_eq!(cancelled.load(Ordering::SeqCst), 0);
+        assert_eq!(kept.load(Ordering::SeqCst), 1);
+        finish_stream("win-cancel-b", "req-1");
+    }
+
+    #[tokio::test]
+    async fn cancel_stream_is_scoped_to_the_calling_window() {
+        let finished = Arc::new(AtomicUsize::new(0));
+        register_stream("win-scope-a", "shared", spawn_sleeper(finished.clone()));
+        register_stream("win-scope-b", "shared", spawn_sleeper(finished.clone()));
+
+        assert!(cancel_stream("win-scope-a", "shared"));
+        assert!(!cancel_stream("win-scope-a", "shared"));
+        assert_eq!(active_stream_ids("win-scope-b"), vec!["shared".to_string()]);
+
+        tokio::time::sleep(Duration::from_millis(500)).await;
+        assert_eq!(finished.load(Ordering::SeqCst), 1);
+        finish_stream("win-scope-b", "shared");
+        assert_eq!(cancel_all_streams_for_window("win-scope-b"), 0);
+    }
+}
@@ -0,0 +1,190 @@
+use crate::llm::models::tokenizer::{default_tokenizer, TiktokenTokenizer, CHARS_PER_TOKEN};
+use crate::llm::types::StreamEvent;
+
+/// Minimum growth of the estimate between two `UsageDelta` events
+const EMIT_EVERY_TOKENS: u32 = 8;
+
+/// Estimates output tokens from streamed text so the UI can show a live counter
+/// for providers that only report usage at the end. The authoritative `Usage`
+/// event is echoed as a final, reconciled `UsageDelta`.
+#[derive(Debug, Default)]
+pub struct UsageEstimator {
+    chars: usize,
+    /// Set when the model has a real tokenizer; deltas are then counted as they arrive
+    model: Option<String>,
+    tokens: usize,
+    last_emitted: u32,
+    reconciled: bool,
+}
+
+impl UsageEstimator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Count with `model`'s tokenizer when one is known, else by characters
+    pub fn for_model(model: &str) -> Self {
+        Self {
+            model: TiktokenTokenizer::supports(model).then(|| model.to_string()),
+            ..Self::default()
+        }
+    }
+
+    /// Feed a streamed event; returns a `UsageDelta` when the estimate moved
+    /// far enough, or the reconciled count once real usage arrives
+    pub fn observe(&mut self, event: &StreamEvent) -> Option<StreamEvent> {
+        if self.reconciled {
+            return None;
+        }
+        match event {
+            StreamEvent::TextDelta { text }
+            | StreamEvent::ReasoningDelta { text, .. }
+            | StreamEvent::ReasoningSummaryDelta { text, .. } => {
+                self.chars += text.chars().count();
+                if let Some(model) = self.model.as_deref() {
+                    self.tokens += default_tokenizer().count_tokens(text, model);
+                }
+                let estimate = self.estimate();
+                if estimate < self.last_emitted + EMIT_EVERY_TOKENS {
+                    return None;
+                }
+                self.delta(estimate, false)
+            }
+            StreamEvent::Usage { output_tokens, .. } => {
+                self.reconciled = true;
+                self.delta((*output_tokens).max(0) as u32, true)
+            }
+            // Flush the tail of the estimate when the provider never reported usage
+            StreamEvent::Done { .. } => {
+                let estimate = self.estimate();
+                (estimate > self.last_emitted)
+                    .then(|| self.delta(estimate, false))
+                    .flatten()
+            }
+            _ => None,
+        }
+    }
+
+    fn estimate(&self) -> u32 {
+        if self.model.is_some() {
+            return self.tokens as u32;
+        }
+        self.chars.div_ceil(CHARS_PER_TOKEN) as u32
+    }
+
+    fn delta(&mut self, output_tokens_est: u32, reconciled: bool) -> Option<StreamEvent> {
+        self.last_emitted = output_tokens_est;
+        Some(StreamEvent::UsageDelta {
+            output_tokens_est,
+            reconciled,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn estimates(events: &[StreamEvent]) -> Vec<(u32, bool)> {
+        let mut estimator = UsageEstimator::new();
+        events
+            .iter()
+            .filter_map(|event| estimator.observe(event))
+            .map(|event| match event {
+                StreamEvent::UsageDelta {
+                    output_tokens_est,
+                    reconciled,
+                } => (output_tokens_est, reconciled),
+                other => panic!("Unexpected event: {:?}", other),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn streamed_text_produces_increasing_estimates_then_reconciles() {
+        let mut events: Vec<StreamEvent> = (0..20)
+            .map(|_| StreamEvent::TextDelta {
+                text: "word word word ".to_string(),
+            })
+            .collect();
+        events.push(StreamEvent::Usage {
+            input_tokens: 12,
+            output_tokens: 61,
+            total_tokens: Some(73),
+            cached_input_tokens: None,
+            cache_creation_input_tokens: None,
+        });
+        events.push(StreamEvent::TextDelta {
+            text: "late text after usage".to_string(),
+        });
+
+        let estimates = estimates(&events);
+        let (live, last) = estimates.split_at(estimates.len() - 1);
+        assert!(live.len() > 2);
+        assert!(live.windows(2).all(|pair| pair[0].0 < pair[1].0));
+        assert!(live.iter().all(|(_, reconciled)| !reconciled));
+        assert_eq!(last, &[(61, true)]);
+    }
+
+    #[test]
+    fn done_flushes_estimate_without_provider_usage() {
+        let events = vec![
+            StreamEvent::TextDelta {
+                text: "Hello".to_string(),
+            },
+            StreamEvent::Done {
+                finish_reason: Some("stop".to_string()),
+            },
+        ];
+        assert_eq!(estimates(&events), vec![(2, false)]);
+    }
+
+    #[test]
+    fn for_model_counts_with_tiktoken_when_available() {
+        let events = vec![
+            StreamEvent::TextDelta {
+                text: "tiktoken is great!".to_string(),
+            },
+            StreamEvent::Done {
+                finish_reason: Some("stop".to_string()),
+            },
+        ];
+        let mut estimator = UsageEstimator::for_model("gpt-4");
+        let flushed: Vec<_> = events
+            .iter()
+            .filter_map(|event| estimator.observe(event))
+            .collect();
+        assert!(matches!(
+            flushed.as_slice(),
+            [StreamEvent::UsageDelta {
+                output_tokens_est: 6,
+                reconciled: false
+            }]
+        ));
+
+        // No tokenizer for this model, so it counts like `new()`
+        let mut estimator = UsageEstimator::for_model("claude-sonnet-4");
+        let flushed: Vec<_> = events
+            .iter()
+            .filter_map(|event| estimator.observe(event))
+            .collect();
+        assert!(matches!(
+            flushed.as_slice(),
+            [StreamEvent::UsageDelta {
+                output_tokens_est: 5,
+                reconciled: false
+            }]
+        ));
+    }
+
+    #[test]
+    fn usage_delta_serializes_as_kebab_case() {
+        let value = serde_json::to_value(StreamEvent::UsageDelta {
+            output_tokens_est: 42,
+            reconciled: false,
+        })
+        .unwrap();
+        assert_eq!(value["type"], "usage-delta");
+        assert_eq!(value["output_tokens_est"], 42);
+    }
+}
@@ -0,0 +1,126 @@
+// Per-session usage reporting. The stream handler publishes the token usage and
+// cost of every finished stream that carries a session id; chat history
+// subscribes to the reports and keeps running totals per session.
+
+use crate::llm::ai_services::pricing_service::PricingService;
+use crate::llm::ai_services::types::TokenUsage;
+use crate::llm::types::ModelConfig;
+use crate::storage::SessionUsage;
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+
+/// Usage of one finished stream, attributed to a chat session
+#[derive(Debug, Clone, PartialEq)]
+pub struct SessionUsageReport {
+    pub session_id: String,
+    pub model: String,
+    pub usage: SessionUsage,
+}
+
+fn usage_sink() -> &'static Mutex<Option<UnboundedSender<SessionUsageReport>>> {
+    static SINK: OnceLock<Mutex<Option<UnboundedSender<SessionUsageReport>>>> = OnceLock::new();
+    SINK.get_or_init(|| Mutex::new(None))
+}
+
+/// Start receiving usage reports; replaces any previous subscriber
+pub fn subscribe_usage_reports() -> UnboundedReceiver<SessionUsageReport> {
+    let (tx, rx) = unbounded_channel();
+    if let Ok(mut sink) = usage_sink().lock() {
+        *sink = Some(tx);
+    }
+    rx
+}
+
+/// Publish a usage report; returns false when nobody is listening
+pub fn report_session_usage(report: SessionUsageReport) -> bool {
+    usage_sink()
+        .lock()
+        .ok()
+        .and_then(|sink| sink.as_ref().map(|tx| tx.send(report).is_ok()))
+        .unwrap_or(false)
+}
+
+/// Convert stream token counts into session usage, pricing them with the model's rates
+pub fn session_usage_from_tokens(
+    model_id: &str,
+    usage: &TokenUsage,
+    model_configs: &HashMap<String, ModelConfig>,
+) -> SessionUsage {
+    let cost = PricingService::new()
+        .calculate_cost(model_id, usage, model_configs)
+        .unwrap_or(0.0);
+    SessionUsage {
+        input_tokens: i64::from(usage.input_tokens),
+        output_tokens: i64::from(usage.output_tokens),
+        cached_tokens: i64::from(usage.cached_input_tokens.unwrap_or(0)),
+        cost_cents: cost * 100.0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::types::ModelPricing;
+
+    #[test]
+    fn prices_usage_in_cents() {
+        let mut configs = HashMap::new();
+        configs.insert(
+            "test-model".to_string(),
+            ModelConfig {
+                name: "Test Model".to_string(),
+                image_input: false,
+                image_output: false,
+                audio_input: false,
+                video_input: false,
+                interleaved: false,
+                providers: vec!["test".to_string()],
+                provider_mappings: None,
+                pricing: Some(ModelPricing {
+                    input: "0.000001".to_string(),
+                    output: "0.000002".to_string(),
+                    cached_input: None,
+                    cache_creation: None,
+                }),
+                context_length: None,
+                aliases: Vec::new(),
+                deprecated: None,
+                deprecation_message: None,
+            },
+        );
+        let usage = TokenUsage {
+            input_tokens: 1000,
+            output_tokens: 500,
+            cached_input_tokens: Some(200),
+            cache_creation_input_tokens: None,
+        };
+
+        let session_usage = session_usage_from_tokens("test-model@test", &usage, &configs);
+        assert_eq!(session_usage.input_tokens, 1000);
+        assert_eq!(session_usage.output_tokens, 500);
+        assert_eq!(session_usage.cached_tokens, 200);
+        // (1000 * 0.000001 + 500 * 0.000002) dollars = 0.2 cents
+        assert!((session_usage.cost_cents - 0.2).abs() < 1e-9);
+
+        let unpriced = session_usage_from_tokens("unknown", &usage, &configs);
+        assert_eq!(unpriced.cost_cents, 0.0);
+    }
+
+    #[tokio::test]
+    async fn subscriber_receives_reports() {
+        let mut rx = subscribe_usage_reports();
+        let report = SessionUsageReport {
+            session_id: "session-1".to_string(),
+            model: "test-model".to_string(),
+            usage: SessionUsage {
+                input_tokens: 10,
+                output_tokens: 5,
+                cached_tokens: 0,
+                cost_cents: 0.5,
+            },
+        };
+        assert!(report_session_usage(report.clone()));
+        assert_eq!(rx.recv().await, Some(report));
+    }
+}
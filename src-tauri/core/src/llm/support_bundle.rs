@@ -0,0 +1,244 @@
+// Support bundle: a secret-free snapshot of the effective LLM configuration,
+// attached to bug reports so support can reproduce the user's environment.
+
+use crate::llm::auth::api_key_manager::{ApiKeyManager, LlmState};
+use crate::llm::auth::oauth::oauth_status;
+use crate::llm::providers::provider_registry::ProviderRegistry;
+use crate::{feishu_gateway, telegram_gateway};
+use serde_json::{json, Value};
+use tauri::{AppHandle, Manager, State};
+
+/// Collect providers, models config version, credential presence and OAuth
+/// status. Secrets are never copied: credentials are reported as booleans and
+/// custom header values and custom provider keys are dropped.
+pub async fn build_support_bundle(
+    api_keys: &ApiKeyManager,
+    registry: &ProviderRegistry,
+    app_version: &str,
+    gateways: Value,
+) -> Result<Value, String> {
+    let api_key_map = api_keys.load_api_keys().await?;
+    let oauth_tokens = api_keys.load_oauth_tokens().await?;
+
+    let mut providers = registry.providers();
+    providers.sort_by(|a, b| a.id.cmp(&b.id));
+    let providers: Vec<Value> = providers
+        .iter()
+        .map(|provider| {
+            let mut header_names: Vec<&String> = provider
+                .headers
+                .as_ref()
+                .map(|headers| headers.keys().collect())
+                .unwrap_or_default();
+            header_names.sort();
+            json!({
+                "id": provider.id,
+                "name": provider.name,
+                "protocol": provider.protocol,
+                "baseUrl": provider.base_url,
+                "authType": provider.auth_type,
+                "supportsOAuth": provider.supports_oauth,
+                "headerNames": header_names,
+                "hasExtraBody": provider.extra_body.is_some(),
+            })
+        })
+        .collect();
+
+    let credentials: serde_json::Map<String, Value> = registry
+        .providers()
+        .iter()
+        .map(|provider| {
+            (
+                provider.id.clone(),
+                json!({
+                    "apiKey": api_key_map.contains_key(&provider.id),
+                    "oauth": oauth_tokens.contains_key(&provider.id),
+                }),
+            )
+        })
+        .collect();
+
+    let custom_providers = api_keys.load_custom_providers().await?;
+    let mut custom: Vec<Value> = custom_providers
+        .providers
+        .values()
+        .map(|provider| {
+            json!({
+                "id": provider.id,
+                "name": provider.name,
+                "type": provider.provider_type,
+                "baseUrl": provider.base_url,
+                "enabled": provider.enabled,
+                "hasApiKey": !provider.api_key.trim().is_empty(),
+            })
+        })
+        .collect();
+    custom.sort_by(|a, b| a["id"].as_str().cmp(&b["id"].as_str()));
+
+    let models = match api_keys.load_models_config().await {
+        Ok(config) => json!({
+            "version": config.version,
+            "modelCount": config.models.len(),
+        }),
+        Err(error) => json!({ "error": error }),
+    };
+
+    let oauth = serde_json::to_value(oauth_status(api_keys).await?)
+        .map_err(|e| format!("Failed to serialize OAuth status: {}", e))?;
+
+    Ok(json!({
+        "generatedAt": chrono::Utc::now().to_rfc3339(),
+        "app": {
+            "version": app_version,
+            "os": std::env::consts::OS,
+            "arch": std::env::consts::ARCH,
+        },
+        "providers": providers,
+        "customProviders": {
+            "version": custom_providers.version,
+            "providers": custom,
+        },
+        "models": models,
+        "credentials": credentials,
+        "oauth": oauth,
+        "gateways": gateways,
+    }))
+}
+
+#[tauri::command]
+pub async fn generate_support_bundle(
+    app: AppHandle,
+    state: State<'_, LlmState>,
+) -> Result<Value, String> {
+    let mut gateways = serde_json::Map::new();
+    if let Some(feishu) = app.try_state::<feishu_gateway::FeishuGatewayState>() {
+        let status = feishu_gateway::gateway_status(feishu.inner()).await;
+        gateways.insert("feishu".to_string(), json!(status));
+    }
+    if let Some(telegram) = app.try_state::<telegram_gateway::TelegramGatewayState>() {
+        let status = telegram_gateway::gateway_status(telegram.inner()).await;
+        gateways.insert("telegram".to_string(), json!(status));
+    }
+
+    let app_version = app.package_info().version.to_string();
+    let registry = state.registry.lock().await;
+    let api_keys = state.api_keys.lock().await;
+    build_support_bundle(&api_keys, &registry, &app_version, Value::Object(gateways)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::Database;
+    use crate::llm::types::{
+        AuthType, CustomProviderConfig, CustomProviderType, CustomProvidersConfiguration,
+        ProtocolType, ProviderConfig,
+    };
+    use std::collections::HashMap;
+    use std::sync::Arc;
+    use tempfile::TempDir;
+
+    fn provider(id: &str) -> ProviderConfig {
+        ProviderConfig {
+            id: id.to_string(),
+            name: id.to_string(),
+            protocol: ProtocolType::OpenAiCompatible,
+            base_url: "https://api.example.com/v1".to_string(),
+            api_key_name: format!("api_key_{}", id),
+            supports_oauth: id == "openai",
+            supports_coding_plan: false,
+            supports_international: false,
+            coding_plan_base_url: None,
+            international_base_url: None,
+            headers: Some(HashMap::from([(
+                "X-Org-Token".to_string(),
+                "header-secret".to_string(),
+            )])),
+            extra_body: None,
+            auth_type: AuthType::Bearer,
+            body_transforms: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn support_bundle_has_sections_and_no_secrets() {
+        let dir = TempDir::new().unwrap();
+        let db = Arc::new(Database::new(
+            dir.path().join("bundle.db").to_string_lossy().to_string(),
+        ));
+        db.connect().await.unwrap();
+        db.execute(
+            "CREATE TABLE IF NOT EXISTS settings (key TEXT PRIMARY KEY, value TEXT, updated_at INTEGER)",
+            vec![],
+        )
+        .await
+        .unwrap();
+        let api_keys = ApiKeyManager::new(db, dir.path().to_path_buf());
+        api_keys
+            .set_setting("api_key_deepseek", "sk-deepseek-secret")
+            .await
+            .unwrap();
+        api_keys
+            .set_setting("openai_oauth_access_token", "oauth-access-secret")
+            .await
+            .unwrap();
+        api_keys
+            .save_custom_providers(&CustomProvidersConfiguration {
+                version: "1".to_string(),
+                providers: HashMap::from([(
+                    "gateway".to_string(),
+                    CustomProviderConfig {
+                        id: "gateway".to_string(),
+                        name: "Gateway".to_string(),
+                        provider_type: CustomProviderType::OpenAiCompatible,
+                        base_url: "https://gateway.example.com".to_string(),
+                        api_key: "custom-provider-secret".to_string(),
+                        enabled: true,
+                        description: None,
+                    },
+                )]),
+            })
+            .await
+            .unwrap();
+        let registry = ProviderRegistry::new(vec![provider("openai"), provider("deepseek")]);
+
+        let bundle = build_support_bundle(
+            &api_keys,
+            &registry,
+            "1.2.3",
+            json!({ "feishu": { "running": false } }),
+        )
+        .await
+        .unwrap();
+
+        for section in [
+            "app",
+            "providers",
+            "customProviders",
+            "models",
+            "credentials",
+            "oauth",
+            "gateways",
+        ] {
+            assert!(bundle.get(section).is_some(), "missing section {}", section);
+        }
+        assert_eq!(bundle["app"]["version"], "1.2.3");
+        assert_eq!(bundle["providers"].as_array().unwrap().len(), 2);
+        assert_eq!(bundle["credentials"]["deepseek"]["apiKey"], true);
+        assert_eq!(bundle["credentials"]["openai"]["apiKey"], false);
+        assert_eq!(bundle["credentials"]["openai"]["oauth"], true);
+        assert_eq!(bundle["oauth"]["openai"]["isConnected"], true);
+        assert_eq!(bundle["customProviders"]["providers"][0]["hasApiKey"], true);
+        assert_eq!(bundle["providers"][0]["headerNames"][0], "X-Org-Token");
+
+        let raw = bundle.to_string();
+        for secret in [
+            "sk-deepseek-secret",
+            "oauth-access-secret",
+            "custom-provider-secret",
+            "header-secret",
+        ] {
+            assert!(!raw.contains(secret), "bundle leaked {}", secret);
+        }
+    }
+}
@@ -80,7 +80,6 @@ pub fn fixture_path(dir: &Path, fixture: &ProviderFixture) -> PathBuf {
     dir.join(fixture_file_name(fixture))
 }
 
-#[cfg(test)]
 pub fn load_fixture(path: &Path) -> Result<ProviderFixture, String> {
     let raw = std::fs::read_to_string(path)
         .map_err(|e| format!("Failed to read fixture {}: {}", path.display(), e))?;
@@ -103,7 +102,6 @@ pub fn write_fixture(path: &Path, fixture: &ProviderFixture) -> Result<(), Strin
         .map_err(|e| format!("Failed to write fixture {}: {}", path.display(), e))
 }
 
-#[cfg(test)]
 pub fn build_sse_body(events: &[RecordedSseEvent]) -> String {
     let mut body = String::new();
     for event in events {
@@ -1,8 +1,10 @@
 pub mod fixtures;
 pub mod mock_server;
 pub mod recorder;
+pub mod replay;
 
-pub use recorder::{Recorder, RecordingContext, TestConfig, TestMode};
+pub use recorder::{Recorder, RecordingContext, RecordingEntry, TestConfig, TestMode};
+pub use replay::{replay_base_url, ReplayServer};
 
 #[cfg(test)]
 mod perf_tests;
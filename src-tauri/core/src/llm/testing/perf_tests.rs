@@ -42,6 +42,8 @@ fn perf_openai_build_request() {
         top_k: Some(64),
         provider_options: None,
         extra_body: None,
+        logprobs: None,
+        top_logprobs: None,
     };
 
     let iterations = 300;
@@ -2,9 +2,18 @@ use crate::llm::testing::fixtures::{
     FixtureInput, ProviderFixture, RecordedRequest, RecordedResponse, RecordedSseEvent,
 };
 use crate::llm::types::StreamEvent;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Manifest of recordings kept next to them in the fixture dir
+pub const MANIFEST_FILE_NAME: &str = "index.json";
+
+/// Serializes manifest read-modify-write cycles across concurrent recorders
+static MANIFEST_LOCK: Mutex<()> = Mutex::new(());
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum TestMode {
@@ -18,6 +27,8 @@ pub struct TestConfig {
     pub mode: TestMode,
     pub fixture_dir: PathBuf,
     pub base_url_override: Option<String>,
+    /// Pause between SSE events when replaying recordings
+    pub replay_chunk_delay: Option<Duration>,
 }
 
 impl TestConfig {
@@ -43,11 +54,16 @@ impl TestConfig {
             });
 
         let base_url_override = std::env::var("LLM_TEST_BASE_URL").ok();
+        let replay_chunk_delay = std::env::var("LLM_REPLAY_CHUNK_DELAY_MS")
+            .ok()
+            .and_then(|value| value.parse::<u64>().ok())
+            .map(Duration::from_millis);
 
         Self {
             mode,
             fixture_dir,
             base_url_override,
+            replay_chunk_delay,
         }
     }
 }
@@ -66,6 +82,26 @@ pub struct RecordingContext {
 pub struct Recorder {
     fixture: ProviderFixture,
     path: PathBuf,
+    fixture_dir: PathBuf,
+}
+
+/// One recording listed in the manifest; `path` is relative to the fixture dir
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RecordingEntry {
+    pub id: String,
+    pub provider_id: String,
+    pub protocol: String,
+    pub model: String,
+    pub endpoint_path: String,
+    pub status: u16,
+    /// Milliseconds since Unix epoch
+    pub created_at: i64,
+    pub path: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct RecordingManifest {
+    recordings: Vec<RecordingEntry>,
 }
 
 impl Recorder {
@@ -98,7 +134,25 @@ impl Recorder {
         };
 
         let path = recorded_fixture_path(config, &fixture, &ctx.channel);
-        Some(Self { fixture, path })
+        Some(Self {
+            fixture,
+            path,
+            fixture_dir: config.fixture_dir.clone(),
+        })
+    }
+
+    /// Recordings listed in the manifest of `config.fixture_dir`, oldest first
+    pub fn list_recordings(config: &TestConfig) -> Result<Vec<RecordingEntry>, String> {
+        Ok(read_manifest(&config.fixture_dir)?.recordings)
+    }
+
+    /// Load the fixture for a manifest entry
+    pub fn load_recording(config: &TestConfig, id: &str) -> Result<ProviderFixture, String> {
+        let entry = Self::list_recordings(config)?
+            .into_iter()
+            .find(|entry| entry.id == id)
+            .ok_or_else(|| format!("Recording not found: {}", id))?;
+        crate::llm::testing::fixtures::load_fixture(&config.fixture_dir.join(&entry.path))
     }
 
     pub fn set_test_input(&mut self, input: FixtureInput) {
@@ -131,7 +185,8 @@ impl Recorder {
             *s = status;
             *headers = headers_from_header_map(response_headers);
         }
-        crate::llm::testing::fixtures::write_fixture(&self.path, &self.fixture)
+        crate::llm::testing::fixtures::write_fixture(&self.path, &self.fixture)?;
+        self.append_to_manifest(status)
     }
 
     pub fn finish_error(
@@ -145,10 +200,68 @@ impl Recorder {
             headers: headers_from_header_map(response_headers),
             body: Value::String(body.to_string()),
         };
-        crate::llm::testing::fixtures::write_fixture(&self.path, &self.fixture)
+        crate::llm::testing::fixtures::write_fixture(&self.path, &self.fixture)?;
+        self.append_to_manifest(status)
+    }
+
+    /// Add this recording to the manifest, replacing an earlier entry for the same file
+    fn append_to_manifest(&self, status: u16) -> Result<(), String> {
+        let path = self
+            .path
+            .strip_prefix(&self.fixture_dir)
+            .unwrap_or(&self.path)
+            .to_string_lossy()
+            .to_string();
+        let id = self
+            .path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_else(|| path.clone());
+        let entry = RecordingEntry {
+            id,
+            provider_id: self.fixture.provider_id.clone(),
+            protocol: self.fixture.protocol.clone(),
+            model: self.fixture.model.clone(),
+            endpoint_path: self.fixture.endpoint_path.clone(),
+            status,
+            created_at: chrono::Utc::now().timestamp_millis(),
+            path,
+        };
+
+        let _guard = MANIFEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let mut manifest = read_manifest(&self.fixture_dir)?;
+        manifest
+            .recordings
+            .retain(|existing| existing.id != entry.id);
+        manifest.recordings.push(entry);
+        let raw = serde_json::to_string_pretty(&manifest)
+            .map_err(|e| format!("Failed to serialize recordings manifest: {}", e))?;
+        let manifest_path = self.fixture_dir.join(MANIFEST_FILE_NAME);
+        std::fs::write(&manifest_path, raw).map_err(|e| {
+            format!(
+                "Failed to write recordings manifest {}: {}",
+                manifest_path.display(),
+                e
+            )
+        })
     }
 }
 
+fn read_manifest(dir: &Path) -> Result<RecordingManifest, String> {
+    let path = dir.join(MANIFEST_FILE_NAME);
+    if !path.exists() {
+        return Ok(RecordingManifest::default());
+    }
+    let raw = std::fs::read_to_string(&path).map_err(|e| {
+        format!(
+            "Failed to read recordings manifest {}: {}",
+            path.display(),
+            e
+        )
+    })?;
+    serde_json::from_str(&raw).map_err(|e| format!("Failed to parse recordings manifest: {}", e))
+}
+
 fn recorded_fixture_path(config: &TestConfig, fixture: &ProviderFixture, channel: &str) -> PathBuf {
     let model = fixture.model.replace(['/', ' '], "_");
     let file_name = format!(
@@ -168,15 +281,11 @@ fn headers_from_header_map(map: &reqwest::header::HeaderMap) -> HashMap<String,
     headers
 }
 
-fn redact_headers(headers: &HashMap<String, String>) -> HashMap<String, String> {
+pub(crate) fn redact_headers(headers: &HashMap<String, String>) -> HashMap<String, String> {
     let mut redacted = HashMap::new();
     for (key, value) in headers {
         let lower = key.to_lowercase();
-        if lower == "authorization"
-            || lower == "x-api-key"
-            || lower == "api-key"
-            || lower.contains("token")
-        {
+        if lower == "authorization" || lower.ends_with("api-key") || lower.contains("token") {
             redacted.insert(lower, "REDACTED".to_string());
         } else {
             redacted.insert(lower, value.to_string());
@@ -184,3 +293,78 @@ fn redact_headers(headers: &HashMap<String, String>) -> HashMap<String, String>
     }
     redacted
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn record(config: &TestConfig, model: &str) {
+        let mut recorder = Recorder::from_test_config(
+            config,
+            RecordingContext {
+                provider_id: "openai".to_string(),
+                protocol: "OpenAiCompatible".to_string(),
+                model: model.to_string(),
+                endpoint_path: "v1/chat/completions".to_string(),
+                url: "https://api.openai.com/v1/chat/completions".to_string(),
+                channel: "api".to_string(),
+                request_headers: HashMap::from([(
+                    "Authorization".to_string(),
+                    "Bearer sk-test".to_string(),
+                )]),
+                request_body: serde_json::json!({ "model": model, "stream": true }),
+            },
+        )
+        .expect("recorder in record mode");
+        recorder.record_sse_event(None, r#"{"choices":[{"delta":{"content":"hi"}}]}"#);
+        recorder.record_sse_event(None, "[DONE]");
+        recorder
+            .finish_stream(200, &reqwest::header::HeaderMap::new())
+            .expect("finish stream");
+    }
+
+    #[test]
+    fn recordings_are_indexed_in_manifest() {
+        let dir = TempDir::new().unwrap();
+        let config = TestConfig {
+            mode: TestMode::Record,
+            fixture_dir: dir.path().to_path_buf(),
+            base_url_override: None,
+            replay_chunk_delay: None,
+        };
+        record(&config, "gpt-4o");
+        record(&config, "gpt-4o-mini");
+
+        let recordings = Recorder::list_recordings(&config).unwrap();
+        assert_eq!(recordings.len(), 2);
+        assert!(dir.path().join(MANIFEST_FILE_NAME).exists());
+
+        let entry = recordings
+            .iter()
+            .find(|entry| entry.model == "gpt-4o-mini")
+            .expect("entry for model");
+        assert_eq!(entry.provider_id, "openai");
+        assert_eq!(entry.status, 200);
+        assert_eq!(
+            entry.path,
+            "openai__OpenAiCompatible__gpt-4o-mini__api.json"
+        );
+
+        let fixture = Recorder::load_recording(&config, &entry.id).unwrap();
+        assert_eq!(fixture.model, "gpt-4o-mini");
+        assert_eq!(
+            fixture
+                .request
+                .headers
+                .get("authorization")
+                .map(String::as_str),
+            Some("REDACTED")
+        );
+
+        // Re-recording the same file replaces its entry
+        record(&config, "gpt-4o");
+        assert_eq!(Recorder::list_recordings(&config).unwrap().len(), 2);
+        assert!(Recorder::load_recording(&config, "missing").is_err());
+    }
+}
@@ -0,0 +1,432 @@
+//! Offline replay of recorded provider streams
+//!
+//! Serves fixtures written by the `Recorder` over HTTP so `stream_completion`
+//! goes through its normal request and `parse_stream_event` path without a
+//! live provider. Requests are routed as `/{provider_id}/{endpoint_path}` and
+//! matched on `(provider_id, model, endpoint_path)`, with the model taken from
+//! the JSON body.
+
+use crate::llm::testing::fixtures::{
+    build_sse_body, load_fixture, ProviderFixture, RecordedResponse, RecordedSseEvent,
+};
+use crate::llm::testing::recorder::{TestConfig, MANIFEST_FILE_NAME};
+use std::collections::{HashMap, VecDeque};
+use std::io::Read;
+use std::net::TcpListener;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread;
+use std::time::Duration;
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct ReplayKey {
+    provider_id: String,
+    model: String,
+    endpoint_path: String,
+}
+
+impl ReplayKey {
+    fn new(provider_id: &str, model: &str, endpoint_path: &str) -> Self {
+        Self {
+            provider_id: provider_id.to_string(),
+            model: model.to_string(),
+            endpoint_path: endpoint_path.trim_matches('/').to_string(),
+        }
+    }
+}
+
+pub struct ReplayServer {
+    base_url: String,
+    running: Arc<AtomicBool>,
+    handle: Option<thread::JoinHandle<()>>,
+}
+
+impl ReplayServer {
+    /// Start serving every fixture in `fixture_dir`, sleeping `chunk_delay`
+    /// between SSE events when set
+    pub fn start(fixture_dir: &Path, chunk_delay: Option<Duration>) -> Result<Self, String> {
+        let fixtures = load_replay_fixtures(fixture_dir)?;
+        let listener = TcpListener::bind("127.0.0.1:0")
+            .map_err(|e| format!("Failed to bind replay server: {}", e))?;
+        let addr = listener
+            .local_addr()
+            .map_err(|e| format!("Failed to read replay server address: {}", e))?;
+        let server = tiny_http::Server::from_listener(listener, None)
+            .map_err(|e| format!("Failed to start replay server: {}", e))?;
+
+        let running = Arc::new(AtomicBool::new(true));
+        let running_flag = running.clone();
+        let handle = thread::spawn(move || {
+            while running_flag.load(Ordering::SeqCst) {
+                match server.recv_timeout(Duration::from_millis(50)) {
+                    Ok(Some(request)) => {
+                        if let Err(err) = handle_replay_request(request, &fixtures, chunk_delay) {
+                            log::error!("Replay server error: {}", err);
+                        }
+                    }
+                    Ok(None) => {}
+                    Err(err) => {
+                        log::error!("Replay server recv error: {}", err);
+                    }
+                }
+            }
+        });
+
+        log::info!(
+            "Replay server serving {} on http://{}",
+            fixture_dir.display(),
+            addr
+        );
+        Ok(Self {
+            base_url: format!("http://{}", addr),
+            running,
+            handle: Some(handle),
+        })
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// Base URL to use in place of a provider's own when replaying its recordings
+    pub fn provider_base_url(&self, provider_id: &str) -> String {
+        format!("{}/{}", self.base_url, provider_id)
+    }
+}
+
+impl Drop for ReplayServer {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::SeqCst);
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+/// Base URL for `provider_id` on the shared replay server for `config.fixture_dir`,
+/// starting the server on first use
+pub fn replay_base_url(config: &TestConfig, provider_id: &str) -> Result<String, String> {
+    static SERVERS: OnceLock<Mutex<HashMap<PathBuf, Arc<ReplayServer>>>> = OnceLock::new();
+    let mut servers = SERVERS
+        .get_or_init(|| Mutex::new(HashMap::new()))
+        .lock()
+        .map_err(|_| "Replay server registry poisoned".to_string())?;
+
+    let server = match servers.get(&config.fixture_dir) {
+        Some(server) => server.clone(),
+        None => {
+            let server = Arc::new(ReplayServer::start(
+                &config.fixture_dir,
+                config.replay_chunk_delay,
+            )?);
+            servers.insert(config.fixture_dir.clone(), server.clone());
+            server
+        }
+    };
+    Ok(server.provider_base_url(provider_id))
+}
+
+fn load_replay_fixtures(dir: &Path) -> Result<HashMap<ReplayKey, ProviderFixture>, String> {
+    let entries = std::fs::read_dir(dir)
+        .map_err(|e| format!("Failed to read recordings dir {}: {}", dir.display(), e))?;
+
+    let mut fixtures = HashMap::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|ext| ext.to_str()) != Some("json")
+            || path.file_name().and_then(|name| name.to_str()) == Some(MANIFEST_FILE_NAME)
+        {
+            continue;
+        }
+        match load_fixture(&path) {
+            Ok(fixture) => {
+                let key =
+                    ReplayKey::new(&fixture.provider_id, &fixture.model, &fixture.endpoint_path);
+                fixtures.insert(key, fixture);
+            }
+            Err(err) => log::warn!("Skipping recording {}: {}", path.display(), err),
+        }
+    }
+    Ok(fixtures)
+}
+
+fn find_fixture<'a>(
+    fixtures: &'a HashMap<ReplayKey, ProviderFixture>,
+    provider_id: &str,
+    model: Option<&str>,
+    endpoint_path: &str,
+) -> Option<&'a ProviderFixture> {
+    if let Some(model) = model {
+        return fixtures.get(&ReplayKey::new(provider_id, model, endpoint_path));
+    }
+    // Some protocols put the model in the URL; accept a single candidate
+    let mut candidates = fixtures
+        .iter()
+        .filter(|(key, _)| key.provider_id == provider_id && key.endpoint_path == endpoint_path)
+        .map(|(_, fixture)| fixture);
+    match (candidates.next(), candidates.next()) {
+        (Some(fixture), None) => Some(fixture),
+        _ => None,
+    }
+}
+
+fn handle_replay_request(
+    mut request: tiny_http::Request,
+    fixtures: &HashMap<ReplayKey, ProviderFixture>,
+    chunk_delay: Option<Duration>,
+) -> Result<(), String> {
+    let path = request
+        .url()
+        .split('?')
+        .next()
+        .unwrap_or_default()
+        .trim_start_matches('/')
+        .to_string();
+    let (provider_id, endpoint_path) = path.split_once('/').unwrap_or((path.as_str(), ""));
+
+    let mut body = String::new();
+    request
+        .as_reader()
+        .read_to_string(&mut body)
+        .map_err(|e| format!("Failed to read request body: {}", e))?;
+    let json: serde_json::Value = serde_json::from_str(&body).unwrap_or_default();
+    let model = json.get("model").and_then(|value| value.as_str());
+
+    let Some(fixture) = find_fixture(fixtures, provider_id, model, endpoint_path) else {
+        let message = format!(
+            "No recording for provider '{}', model '{}', endpoint '{}'",
+            provider_id,
+            model.unwrap_or("<none>"),
+            endpoint_path
+        );
+        let response = tiny_http::Response::from_string(message.clone()).with_status_code(404);
+        let _ = request.respond(response);
+        return Err(message);
+    };
+
+    match &fixture.response {
+        RecordedResponse::Stream {
+            status,
+            headers,
+            sse_events,
+        } => {
+            let content_type = headers
+                .get("content-type")
+                .cloned()
+                .unwrap_or_else(|| "text/event-stream".to_string());
+            let response = tiny_http::Response::new(
+                tiny_http::StatusCode(*status),
+                vec![content_type_header(&content_type)?],
+                SseReplayReader::new(sse_events, chunk_delay),
+                None,
+                None,
+            );
+            request
+                .respond(response)
+                .map_err(|e| format!("Failed to send response: {}", e))
+        }
+        RecordedResponse::Json {
+            status,
+            headers,
+            body,
+        } => {
+            let content_type = headers
+                .get("content-type")
+                .cloned()
+                .unwrap_or_else(|| "application/json".to_string());
+            let body = match body {
+                serde_json::Value::String(text) => text.clone(),
+                other => other.to_string(),
+            };
+            let response = tiny_http::Response::from_string(body)
+                .with_status_code(*status)
+                .with_header(content_type_header(&content_type)?);
+            request
+                .respond(response)
+                .map_err(|e| format!("Failed to send response: {}", e))
+        }
+    }
+}
+
+fn content_type_header(value: &str) -> Result<tiny_http::Header, String> {
+    tiny_http::Header::from_bytes("content-type", value)
+        .map_err(|()| "Invalid header: content-type".to_string())
+}
+
+/// Yields recorded SSE events one at a time, pausing between them
+struct SseReplayReader {
+    chunks: VecDeque<Vec<u8>>,
+    current: Vec<u8>,
+    offset: usize,
+    delay: Option<Duration>,
+    started: bool,
+}
+
+impl SseReplayReader {
+    fn new(events: &[RecordedSseEvent], delay: Option<Duration>) -> Self {
+        Self {
+            chunks: events
+                .iter()
+                .map(|event| build_sse_body(std::slice::from_ref(event)).into_bytes())
+                .collect(),
+            current: Vec::new(),
+            offset: 0,
+            delay,
+            started: false,
+        }
+    }
+}
+
+impl Read for SseReplayReader {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        if self.offset >= self.current.len() {
+            let Some(next) = self.chunks.pop_front() else {
+                return Ok(0);
+            };
+            if let Some(delay) = self.delay.filter(|_| self.started) {
+                thread::sleep(delay);
+            }
+            self.started = true;
+            self.current = next;
+            self.offset = 0;
+        }
+
+        let len = buf.len().min(self.current.len() - self.offset);
+        buf[..len].copy_from_slice(&self.current[self.offset..self.offset + len]);
+        self.offset += len;
+        Ok(len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::protocols::{
+        openai_protocol::OpenAiProtocol, LlmProtocol, ProtocolStreamState,
+    };
+    use crate::llm::testing::fixtures::parse_sse_body;
+    use crate::llm::testing::recorder::{Recorder, RecordingContext, TestMode};
+    use crate::llm::types::StreamEvent;
+    use tempfile::TempDir;
+
+    fn recorded_stream() -> Vec<RecordedSseEvent> {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("src/llm/testing/recordings/zhipu__OpenAiCompatible__glm-4.7__custom.json");
+        let fixture = load_fixture(&path).expect("load recording");
+        match fixture.response {
+            RecordedResponse::Stream { sse_events, .. } => sse_events,
+            RecordedResponse::Json { .. } => panic!("expected a stream recording"),
+        }
+    }
+
+    /// Run SSE events through the protocol parser the way `stream_completion` does
+    fn parse_events(events: &[RecordedSseEvent]) -> Vec<StreamEvent> {
+        let protocol = OpenAiProtocol;
+        let mut state = ProtocolStreamState::default();
+        let mut parsed = Vec::new();
+        for event in events {
+            if let Some(stream_event) = protocol
+                .parse_stream_event(event.event.as_deref(), &event.data, &mut state)
+                .expect("parse ok")
+            {
+                parsed.push(stream_event);
+            }
+            parsed.append(&mut state.pending_events);
+        }
+        parsed
+    }
+
+    /// Reasoning ids are generated per parse, so compare them by position only
+    fn normalized(events: &[StreamEvent]) -> Vec<serde_json::Value> {
+        events
+            .iter()
+            .map(|event| {
+                let mut value = serde_json::to_value(event).expect("serialize event");
+                if let Some(id) = value.get_mut("id") {
+                    if id.as_str().is_some_and(|id| id.starts_with("reasoning_")) {
+                        *id = serde_json::json!("reasoning_<normalized>");
+                    }
+                }
+                value
+            })
+            .collect()
+    }
+
+    fn record_fixture(dir: &Path, events: &[RecordedSseEvent]) -> Vec<StreamEvent> {
+        let config = TestConfig {
+            mode: TestMode::Record,
+            fixture_dir: dir.to_path_buf(),
+            base_url_override: None,
+            replay_chunk_delay: None,
+        };
+        let mut recorder = Recorder::from_test_config(
+            &config,
+            RecordingContext {
+                provider_id: "zhipu".to_string(),
+                protocol: "OpenAiCompatible".to_string(),
+                model: "glm-4.7".to_string(),
+                endpoint_path: "api/coding/paas/v4/chat/completions".to_string(),
+                url: "https://open.bigmodel.cn/api/coding/paas/v4/chat/completions".to_string(),
+                channel: "custom".to_string(),
+                request_headers: HashMap::new(),
+                request_body: serde_json::json!({"model": "glm-4.7", "stream": true}),
+            },
+        )
+        .expect("recorder in record mode");
+
+        let parsed = parse_events(events);
+        for event in events {
+            recorder.record_sse_event(event.event.as_deref(), &event.data);
+        }
+        for event in &parsed {
+            recorder.record_expected_event(event);
+        }
+        recorder
+            .finish_stream(200, &reqwest::header::HeaderMap::new())
+            .expect("write fixture");
+        parsed
+    }
+
+    #[tokio::test]
+    async fn replays_recorded_stream_with_identical_events() {
+        let dir = TempDir::new().unwrap();
+        let recorded = record_fixture(dir.path(), &recorded_stream());
+        assert!(!recorded.is_empty());
+
+        let server = ReplayServer::start(dir.path(), Some(Duration::from_millis(1))).unwrap();
+        let url = format!(
+            "{}/api/coding/paas/v4/chat/completions",
+            server.provider_base_url("zhipu")
+        );
+        let response = reqwest::Client::new()
+            .post(&url)
+            .json(&serde_json::json!({"model": "glm-4.7", "stream": true}))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status().as_u16(), 200);
+        let body = response.text().await.unwrap();
+
+        let replayed = parse_events(&parse_sse_body(&body));
+        assert_eq!(normalized(&replayed), normalized(&recorded));
+    }
+
+    #[tokio::test]
+    async fn unknown_model_is_not_replayed() {
+        let dir = TempDir::new().unwrap();
+        record_fixture(dir.path(), &recorded_stream());
+
+        let server = ReplayServer::start(dir.path(), None).unwrap();
+        let response = reqwest::Client::new()
+            .post(format!(
+                "{}/api/coding/paas/v4/chat/completions",
+                server.provider_base_url("zhipu")
+            ))
+            .json(&serde_json::json!({"model": "glm-5"}))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status().as_u16(), 404);
+    }
+}
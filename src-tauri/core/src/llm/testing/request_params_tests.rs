@@ -39,6 +39,18 @@ fn build_test_context(
         provider_options: None,
         request_id: None,
         trace_context: None,
+        partial_json: None,
+        fallback_models: None,
+        logprobs: None,
+        top_logprobs: None,
+        estimate_usage: false,
+        metadata: None,
+        resume_on_disconnect: false,
+        allowed_tools: None,
+        stream_tool_call_deltas: false,
+        completion_webhook: None,
+        persist_to_session: None,
+        idempotency_key: None,
     };
 
     (provider, api_keys, request)
@@ -61,6 +73,8 @@ async fn google_provider_strips_top_k() {
         top_k: request.top_k,
         provider_options: request.provider_options.as_ref(),
         trace_context: request.trace_context.as_ref(),
+        logprobs: request.logprobs,
+        top_logprobs: request.top_logprobs,
     };
 
     let body = provider.build_request(&ctx).await.expect("build request");
@@ -83,6 +97,8 @@ async fn non_google_provider_keeps_top_k() {
         top_k: request.top_k,
         provider_options: request.provider_options.as_ref(),
         trace_context: request.trace_context.as_ref(),
+        logprobs: request.logprobs,
+        top_logprobs: request.top_logprobs,
     };
 
     let body = provider.build_request(&ctx).await.expect("build request");
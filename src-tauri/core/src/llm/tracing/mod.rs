@@ -3,16 +3,21 @@
 // Following OpenTelemetry GenAI semantic conventions
 
 pub mod ids;
+pub mod reader;
+pub mod redaction;
 pub mod schema;
 pub mod types;
 pub mod writer;
 
+pub use reader::TraceReader;
+pub use redaction::{RedactMode, RedactionPolicy};
+pub use types::TraceWriterConfig;
 pub use writer::TraceWriter;
 
 #[cfg(test)]
 mod tests {
     use super::schema;
-    use super::TraceWriter;
+    use super::{TraceWriter, TraceWriterConfig};
     use crate::llm::tracing::types::{attributes, float_attr, int_attr, string_attr};
     use std::collections::HashMap;
     use std::sync::Arc;
@@ -31,7 +36,7 @@ mod tests {
         // Initialize schema
         schema::init_tracing_schema(&db).await.unwrap();
 
-        let writer = TraceWriter::new(db.clone());
+        let writer = TraceWriter::new(db.clone(), TraceWriterConfig::default());
         writer.start();
         (writer, db, temp_dir)
     }
@@ -0,0 +1,1126 @@
+// Read and maintenance operations over persisted traces
+// Writes go through TraceWriter; this side queries and prunes what has been stored
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::database::Database;
+
+use super::ids::generate_event_id;
+use super::schema::queries;
+use super::types::attributes;
+
+/// Token usage recorded for a trace, with a computed anomaly flag.
+/// A trace is anomalous when any of its spans consumed input tokens but produced
+/// no output, which usually means a content filter or a silent provider failure.
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TraceUsageSummary {
+    pub trace_id: String,
+    pub started_at: i64,
+    pub session_id: Option<String>,
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    /// Finish reason of the last span with recorded usage
+    pub finish_reason: Option<String>,
+    pub anomaly: bool,
+    pub anomalous_span_ids: Vec<String>,
+}
+
+/// A stored span event with its payload decoded
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpanEventRecord {
+    pub id: String,
+    pub span_id: String,
+    pub timestamp: i64,
+    pub event_type: String,
+    pub payload: Option<serde_json::Value>,
+}
+
+/// Criteria for `TraceReader::list_traces`; unset fields match every trace
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TraceFilter {
+    /// Matches `gen_ai.request.model` on the trace's root span
+    pub model: Option<String>,
+    /// Inclusive lower bound on `started_at` (ms)
+    pub started_after: Option<i64>,
+    /// Exclusive upper bound on `started_at` (ms)
+    pub started_before: Option<i64>,
+    pub has_error: Option<bool>,
+    /// Only completed traces lasting at least this long
+    pub min_duration_ms: Option<i64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TraceStatus {
+    Ok,
+    /// Some span recorded an `error.type` event
+    Error,
+    /// At least one span is still open
+    InProgress,
+}
+
+/// One row of the trace list
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TraceSummary {
+    pub id: String,
+    pub model: Option<String>,
+    pub started_at: i64,
+    /// None while the trace is still in progress
+    pub duration_ms: Option<i64>,
+    pub status: TraceStatus,
+    /// Sum of `total_tokens` over the trace's `gen_ai.usage` events
+    pub total_tokens: i64,
+}
+
+/// Position after the last trace of a page. Traces are ordered by
+/// `(started_at, id)` descending, so the cursor stays stable as new traces arrive.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TraceCursor {
+    pub started_at: i64,
+    pub id: String,
+}
+
+/// A page of trace summaries; `next_cursor` is None on the last page
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TracePage {
+    pub traces: Vec<TraceSummary>,
+    pub next_cursor: Option<TraceCursor>,
+}
+
+/// Request counts, error rate and latency percentiles for a set of spans
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpanStats {
+    pub total: u64,
+    /// Spans with an `error.type` event
+    pub errors: u64,
+    /// Fraction of spans without errors; 0.0 when there are no spans
+    pub success_rate: f64,
+    /// Latency percentiles over closed spans (`ended_at - started_at`)
+    pub p50_latency_ms: Option<i64>,
+    pub p95_latency_ms: Option<i64>,
+}
+
+/// Aggregate metrics over the spans started within a time window
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TraceMetrics {
+    pub window_ms: i64,
+    #[serde(flatten)]
+    pub overall: SpanStats,
+    /// Keyed by `gen_ai.request.model`; spans without a model are under "unknown"
+    pub by_model: BTreeMap<String, SpanStats>,
+}
+
+/// Rows removed by `TraceReader::delete_trace`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TraceDeletion {
+    pub traces: u64,
+    pub spans: u64,
+    pub events: u64,
+}
+
+#[derive(Default)]
+struct StatsAccumulator {
+    total: u64,
+    errors: u64,
+    latencies: Vec<i64>,
+}
+
+impl StatsAccumulator {
+    fn add(&mut self, has_error: bool, latency_ms: Option<i64>) {
+        self.total += 1;
+        if has_error {
+            self.errors += 1;
+        }
+        if let Some(latency_ms) = latency_ms {
+            self.latencies.push(latency_ms);
+        }
+    }
+
+    fn finish(mut self) -> SpanStats {
+        self.latencies.sort_unstable();
+        SpanStats {
+            total: self.total,
+            errors: self.errors,
+            success_rate: if self.total == 0 {
+                0.0
+            } else {
+                (self.total - self.errors) as f64 / self.total as f64
+            },
+            p50_latency_ms: percentile(&self.latencies, 50.0),
+            p95_latency_ms: percentile(&self.latencies, 95.0),
+        }
+    }
+}
+
+/// Nearest-rank percentile of an ascending slice
+fn percentile(sorted: &[i64], p: f64) -> Option<i64> {
+    if sorted.is_empty() {
+        return None;
+    }
+    let rank = ((p / 100.0) * sorted.len() as f64).ceil() as usize;
+    Some(sorted[rank.clamp(1, sorted.len()) - 1])
+}
+
+/// Reader for trace data stored by TraceWriter
+#[derive(Clone)]
+pub struct TraceReader {
+    db: Arc<Database>,
+}
+
+impl TraceReader {
+    pub fn new(db: Arc<Database>) -> Self {
+        Self { db }
+    }
+
+    /// Delete every trace associated with a session, along with their spans and events.
+    /// Runs in a single transaction and returns the number of traces removed.
+    pub async fn delete_traces_for_session(&self, session_id: &str) -> Result<u64, String> {
+        let params = vec![serde_json::Value::String(session_id.to_string())];
+
+        self.db
+            .transaction(|tx| async move {
+                tx.execute(queries::DELETE_SESSION_SPAN_EVENTS, params.clone())
+                    .await?;
+                tx.execute(queries::DELETE_SESSION_SPANS, params.clone())
+                    .await?;
+                let deleted = tx.execute(queries::DELETE_SESSION_TRACES, params).await?;
+                Ok(deleted.rows_affected)
+            })
+            .await
+            .map_err(|e| format!("Failed to delete traces for session {}: {}", session_id, e))
+    }
+
+    /// Delete one trace with all its spans and their events in a single transaction
+    pub async fn delete_trace(&self, trace_id: &str) -> Result<TraceDeletion, String> {
+        let params = vec![serde_json::Value::String(trace_id.to_string())];
+
+        self.db
+            .transaction(|tx| async move {
+                let events = tx
+                    .execute(queries::DELETE_TRACE_SPAN_EVENTS, params.clone())
+                    .await?;
+                let spans = tx
+                    .execute(queries::DELETE_TRACE_SPANS, params.clone())
+                    .await?;
+                let traces = tx.execute(queries::DELETE_TRACE, params).await?;
+                Ok(TraceDeletion {
+                    traces: traces.rows_affected,
+                    spans: spans.rows_affected,
+                    events: events.rows_affected,
+                })
+            })
+            .await
+            .map_err(|e| format!("Failed to delete trace {}: {}", trace_id, e))
+    }
+
+    /// Error rate and latency percentiles over spans started within `window`
+    pub async fn metrics(&self, window: Duration) -> Result<TraceMetrics, String> {
+        let window_ms = window.as_millis().min(i64::MAX as u128) as i64;
+        let since = chrono::Utc::now()
+            .timestamp_millis()
+            .saturating_sub(window_ms);
+        let result = self
+            .db
+            .query(
+                queries::SPAN_METRICS_ROWS,
+                vec![serde_json::Value::from(since)],
+            )
+            .await?;
+
+        let mut overall = StatsAccumulator::default();
+        let mut by_model: BTreeMap<String, StatsAccumulator> = BTreeMap::new();
+        for row in &result.rows {
+            let has_error = row["has_error"].as_i64().unwrap_or_default() != 0;
+            let latency_ms = row["ended_at"]
+                .as_i64()
+                .zip(row["started_at"].as_i64())
+                .map(|(ended_at, started_at)| (ended_at - started_at).max(0));
+            let model = row["model"].as_str().unwrap_or("unknown").to_string();
+
+            overall.add(has_error, latency_ms);
+            by_model
+                .entry(model)
+                .or_default()
+                .add(has_error, latency_ms);
+        }
+
+        Ok(TraceMetrics {
+            window_ms,
+            overall: overall.finish(),
+            by_model: by_model
+                .into_iter()
+                .map(|(model, stats)| (model, stats.finish()))
+                .collect(),
+        })
+    }
+
+    /// Close spans left open by a crash. Spans still open after `older_than` get
+    /// `ended_at = started_at + older_than` and an `error.type` event marking them
+    /// as orphaned. Returns the number of spans closed.
+    pub async fn close_orphaned_spans(&self, older_than: Duration) -> Result<u64, String> {
+        let bound_ms = older_than.as_millis().min(i64::MAX as u128) as i64;
+        let now = chrono::Utc::now().timestamp_millis();
+        let orphans = self
+            .db
+            .query(
+                queries::ORPHANED_SPANS,
+                vec![serde_json::Value::from(now.saturating_sub(bound_ms))],
+            )
+            .await?
+            .rows;
+        if orphans.is_empty() {
+            return Ok(0);
+        }
+
+        let closed = self
+            .db
+            .transaction(|tx| async move {
+                for row in &orphans {
+                    let Some(span_id) = row["id"].as_str() else {
+                        continue;
+                    };
+                    let started_at = row["started_at"].as_i64().unwrap_or(now);
+                    let ended_at = started_at.saturating_add(bound_ms).min(now);
+                    tx.execute(
+                        queries::CLOSE_SPAN,
+                        vec![
+                            serde_json::Value::from(ended_at),
+                            serde_json::Value::from(span_id),
+                        ],
+                    )
+                    .await?;
+                    tx.execute(
+                        queries::INSERT_SPAN_EVENT,
+                        vec![
+                            serde_json::Value::from(generate_event_id()),
+                            serde_json::Value::from(span_id),
+                            serde_json::Value::from(now),
+                            serde_json::Value::from(attributes::ERROR_TYPE),
+                            serde_json::json!({
+                                "error_type": "orphaned",
+                                "message": "Span was never closed; the app likely exited mid-stream",
+                            })
+                            .to_string()
+                            .into(),
+                        ],
+                    )
+                    .await?;
+                }
+                Ok(orphans.len() as u64)
+            })
+            .await?;
+
+        log::info!("Closed {} orphaned trace spans", closed);
+        Ok(closed)
+    }
+
+    /// Events of one type (e.g. `gen_ai.usage`, `error.type`) on a span, oldest first
+    pub async fn events_of_type(
+        &self,
+        span_id: &str,
+        event_type: &str,
+    ) -> Result<Vec<SpanEventRecord>, String> {
+        let result = self
+            .db
+            .query(
+                queries::SPAN_EVENTS_OF_TYPE,
+                vec![
+                    serde_json::Value::from(span_id),
+                    serde_json::Value::from(event_type),
+                ],
+            )
+            .await?;
+        Ok(result
+            .rows
+            .iter()
+            .map(|row| SpanEventRecord {
+                id: row["id"].as_str().unwrap_or_default().to_string(),
+                span_id: row["span_id"].as_str().unwrap_or_default().to_string(),
+                timestamp: row["timestamp"].as_i64().unwrap_or_default(),
+                event_type: row["event_type"].as_str().unwrap_or_default().to_string(),
+                // Payloads are stored as JSON text; keep anything unparseable as a string
+                payload: row["payload"].as_str().map(|raw| {
+                    serde_json::from_str(raw)
+                        .unwrap_or_else(|_| serde_json::Value::String(raw.to_string()))
+                }),
+            })
+            .collect())
+    }
+
+    /// Up to `limit` traces matching `filter`, newest first, starting after `cursor`.
+    /// Returns the page and the cursor for the next one, if any traces remain.
+    pub async fn list_traces(
+        &self,
+        filter: &TraceFilter,
+        limit: u32,
+        cursor: Option<&TraceCursor>,
+    ) -> Result<(Vec<TraceSummary>, Option<TraceCursor>), String> {
+        if limit == 0 {
+            return Ok((Vec::new(), None));
+        }
+        // Fetch one extra row to learn whether another page exists
+        let result = self
+            .db
+            .query(
+                queries::LIST_TRACE_SUMMARIES,
+                vec![
+                    serde_json::json!(filter.model),
+                    serde_json::json!(filter.started_after),
+                    serde_json::json!(filter.started_before),
+                    serde_json::json!(filter.has_error.map(i64::from)),
+                    serde_json::json!(filter.min_duration_ms),
+                    serde_json::json!(cursor.map(|c| c.started_at)),
+                    serde_json::json!(cursor.map(|c| c.id.as_str())),
+                    serde_json::Value::from(i64::from(limit) + 1),
+                ],
+            )
+            .await?;
+
+        let mut traces: Vec<TraceSummary> = result
+            .rows
+            .iter()
+            .map(|row| {
+                let in_progress = row["ended_at"].is_null();
+                let has_error = row["has_error"].as_i64().unwrap_or_default() != 0;
+                TraceSummary {
+                    id: row["id"].as_str().unwrap_or_default().to_string(),
+                    model: row["model"].as_str().map(str::to_string),
+                    started_at: row["started_at"].as_i64().unwrap_or_default(),
+                    duration_ms: row["duration_ms"].as_i64().map(|d| d.max(0)),
+                    status: if has_error {
+                        TraceStatus::Error
+                    } else if in_progress {
+                        TraceStatus::InProgress
+                    } else {
+                        TraceStatus::Ok
+                    },
+                    total_tokens: row["total_tokens"].as_i64().unwrap_or_default(),
+                }
+            })
+            .collect();
+
+        let next_cursor = if traces.len() > limit as usize {
+            traces.truncate(limit as usize);
+            traces.last().map(|last| TraceCursor {
+                started_at: last.started_at,
+                id: last.id.clone(),
+            })
+        } else {
+            None
+        };
+        Ok((traces, next_cursor))
+    }
+
+    /// Usage summaries for the most recent `limit` traces that recorded usage
+    pub async fn trace_usage_summaries(
+        &self,
+        limit: u32,
+    ) -> Result<Vec<TraceUsageSummary>, String> {
+        self.query_usage_summaries(false, limit).await
+    }
+
+    /// The most recent `limit` traces flagged as zero-output anomalies
+    pub async fn list_anomalous_traces(
+        &self,
+        limit: u32,
+    ) -> Result<Vec<TraceUsageSummary>, String> {
+        self.query_usage_summaries(true, limit).await
+    }
+
+    async fn query_usage_summaries(
+        &self,
+        anomalous_only: bool,
+        limit: u32,
+    ) -> Result<Vec<TraceUsageSummary>, String> {
+        let result = self
+            .db
+            .query(
+                queries::TRACE_USAGE_ROWS,
+                vec![
+                    serde_json::Value::from(anomalous_only as i64),
+                    serde_json::Value::from(limit),
+                ],
+            )
+            .await?;
+
+        // Rows arrive grouped by trace, newest trace first
+        let mut summaries: Vec<TraceUsageSummary> = Vec::new();
+        for row in &result.rows {
+            let trace_id = row["trace_id"].as_str().unwrap_or_default();
+            if summaries.last().map(|s| s.trace_id.as_str()) != Some(trace_id) {
+                summaries.push(TraceUsageSummary {
+                    trace_id: trace_id.to_string(),
+                    started_at: row["started_at"].as_i64().unwrap_or_default(),
+                    session_id: row["session_id"].as_str().map(str::to_string),
+                    input_tokens: 0,
+                    output_tokens: 0,
+                    finish_reason: None,
+                    anomaly: false,
+                    anomalous_span_ids: Vec::new(),
+                });
+            }
+            let Some(summary) = summaries.last_mut() else {
+                continue;
+            };
+            summary.input_tokens += row["input_tokens"].as_i64().unwrap_or_default();
+            summary.output_tokens += row["output_tokens"].as_i64().unwrap_or_default();
+            if let Some(finish_reason) = row["finish_reason"].as_str() {
+                summary.finish_reason = Some(finish_reason.to_string());
+            }
+            if row["zero_output"].as_i64().unwrap_or_default() != 0 {
+                summary.anomaly = true;
+                if let Some(span_id) = row["span_id"].as_str() {
+                    summary.anomalous_span_ids.push(span_id.to_string());
+                }
+            }
+        }
+        Ok(summaries)
+    }
+}
+
+#[tauri::command]
+pub async fn trace_list_anomalous(
+    db: State<'_, Arc<Database>>,
+    limit: Option<u32>,
+) -> Result<Vec<TraceUsageSummary>, String> {
+    TraceReader::new(db.inner().clone())
+        .list_anomalous_traces(limit.unwrap_or(100))
+        .await
+}
+
+#[tauri::command]
+pub async fn trace_list(
+    db: State<'_, Arc<Database>>,
+    filter: Option<TraceFilter>,
+    limit: Option<u32>,
+    cursor: Option<TraceCursor>,
+) -> Result<TracePage, String> {
+    let (traces, next_cursor) = TraceReader::new(db.inner().clone())
+        .list_traces(
+            &filter.unwrap_or_default(),
+            limit.unwrap_or(50),
+            cursor.as_ref(),
+        )
+        .await?;
+    Ok(TracePage {
+        traces,
+        next_cursor,
+    })
+}
+
+#[tauri::command]
+pub async fn trace_metrics(
+    db: State<'_, Arc<Database>>,
+    window_secs: u64,
+) -> Result<TraceMetrics, String> {
+    TraceReader::new(db.inner().clone())
+        .metrics(Duration::from_secs(window_secs))
+        .await
+}
+
+#[tauri::command]
+pub async fn trace_span_events_of_type(
+    db: State<'_, Arc<Database>>,
+    span_id: String,
+    event_type: String,
+) -> Result<Vec<SpanEventRecord>, String> {
+    TraceReader::new(db.inner().clone())
+        .events_of_type(&span_id, &event_type)
+        .await
+}
+
+#[tauri::command]
+pub async fn trace_delete_for_session(
+    db: State<'_, Arc<Database>>,
+    session_id: String,
+) -> Result<u64, String> {
+    TraceReader::new(db.inner().clone())
+        .delete_traces_for_session(&session_id)
+        .await
+}
+
+#[tauri::command]
+pub async fn trace_delete(
+    db: State<'_, Arc<Database>>,
+    trace_id: String,
+) -> Result<TraceDeletion, String> {
+    TraceReader::new(db.inner().clone())
+        .delete_trace(&trace_id)
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::tracing::schema::{ensure_span_event_type_index, init_tracing_schema};
+    use crate::llm::tracing::{TraceWriter, TraceWriterConfig};
+    use std::collections::HashMap;
+    use tempfile::TempDir;
+
+    async fn create_test_setup() -> (TraceWriter, TraceReader, Arc<Database>, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("test_reader.db");
+        let db = Arc::new(Database::new(db_path.to_string_lossy().to_string()));
+        db.connect()
+            .await
+            .expect("Failed to connect to test database");
+        init_tracing_schema(&db).await.unwrap();
+
+        let writer = TraceWriter::new(db.clone(), TraceWriterConfig::default());
+        writer.start();
+        let reader = TraceReader::new(db.clone());
+        (writer, reader, db, temp_dir)
+    }
+
+    fn seed_trace(writer: &TraceWriter, trace_id: &str, session_id: Option<&str>) {
+        let span_id = writer.start_span(
+            trace_id.to_string(),
+            None,
+            "llm.stream_completion".to_string(),
+            HashMap::new(),
+        );
+        if let Some(session_id) = session_id {
+            writer.set_trace_session(trace_id.to_string(), session_id.to_string());
+        }
+        writer.add_event(
+            span_id.clone(),
+            "gen_ai.ttft_ms".to_string(),
+            Some(serde_json::json!({ "ttft_ms": 10 })),
+        );
+        writer.end_span(span_id, chrono::Utc::now().timestamp_millis());
+    }
+
+    fn seed_usage_trace(writer: &TraceWriter, trace_id: &str, output_tokens: i64) -> String {
+        let span_id = writer.start_span(
+            trace_id.to_string(),
+            None,
+            "llm.stream_completion".to_string(),
+            HashMap::new(),
+        );
+        writer.add_event(
+            span_id.clone(),
+            "gen_ai.usage".to_string(),
+            Some(serde_json::json!({
+                "input_tokens": 120,
+                "output_tokens": output_tokens,
+                "total_tokens": 120 + output_tokens,
+            })),
+        );
+        writer.add_event(
+            span_id.clone(),
+            "gen_ai.finish_reason".to_string(),
+            Some(serde_json::json!({ "finish_reason": if output_tokens == 0 { "content_filter" } else { "stop" } })),
+        );
+        writer.end_span(span_id.clone(), chrono::Utc::now().timestamp_millis());
+        span_id
+    }
+
+    async fn count(db: &Database, sql: &str) -> i64 {
+        let result = db.query(sql, vec![]).await.unwrap();
+        result.rows[0]["count"].as_i64().unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_delete_traces_for_session_keeps_other_sessions() {
+        let (writer, reader, db, _temp_dir) = create_test_setup().await;
+
+        seed_trace(&writer, "trace-a1", Some("session-a"));
+        seed_trace(&writer, "trace-a2", Some("session-a"));
+        seed_trace(&writer, "session-a", None);
+        seed_trace(&writer, "trace-b1", Some("session-b"));
+
+        writer.request_flush();
+        tokio::time::sleep(std::time::Duration::from_millis(150)).await;
+
+        let deleted = reader.delete_traces_for_session("session-a").await.unwrap();
+        assert_eq!(deleted, 3);
+
+        assert_eq!(count(&db, "SELECT COUNT(*) AS count FROM traces").await, 1);
+        assert_eq!(count(&db, "SELECT COUNT(*) AS count FROM spans").await, 1);
+        assert_eq!(
+            count(&db, "SELECT COUNT(*) AS count FROM span_events").await,
+            1
+        );
+        let remaining = db
+            .query("SELECT id, metadata FROM traces", vec![])
+            .await
+            .unwrap();
+        assert_eq!(remaining.rows[0]["id"], "trace-b1");
+
+        let deleted_again = reader.delete_traces_for_session("session-a").await.unwrap();
+        assert_eq!(deleted_again, 0);
+    }
+
+    #[tokio::test]
+    async fn test_delete_trace_removes_only_its_spans_and_events() {
+        let (writer, reader, db, _temp_dir) = create_test_setup().await;
+
+        seed_trace(&writer, "trace-1", None);
+        let child_span = writer.start_span(
+            "trace-1".to_string(),
+            None,
+            "tool.execute".to_string(),
+            HashMap::new(),
+        );
+        writer.add_event(
+            child_span.clone(),
+            attributes::HTTP_REQUEST_BODY.to_string(),
+            Some(serde_json::json!({ "prompt": "secret" })),
+        );
+        writer.end_span(child_span, chrono::Utc::now().timestamp_millis());
+        seed_trace(&writer, "trace-2", Some("session-a"));
+
+        writer.request_flush();
+        tokio::time::sleep(std::time::Duration::from_millis(150)).await;
+
+        let deleted = reader.delete_trace("trace-1").await.unwrap();
+        assert_eq!(
+            deleted,
+            TraceDeletion {
+                traces: 1,
+                spans: 2,
+                events: 2,
+            }
+        );
+
+        let remaining = db.query("SELECT id FROM traces", vec![]).await.unwrap();
+        assert_eq!(remaining.rows.len(), 1);
+        assert_eq!(remaining.rows[0]["id"], "trace-2");
+        assert_eq!(count(&db, "SELECT COUNT(*) AS count FROM spans").await, 1);
+        assert_eq!(
+            count(&db, "SELECT COUNT(*) AS count FROM span_events").await,
+            1
+        );
+
+        let deleted_again = reader.delete_trace("trace-1").await.unwrap();
+        assert_eq!(deleted_again, TraceDeletion::default());
+    }
+
+    #[tokio::test]
+    async fn test_zero_output_trace_is_flagged_as_anomaly() {
+        let (writer, reader, _db, _temp_dir) = create_test_setup().await;
+
+        let silent_span = seed_usage_trace(&writer, "trace-silent", 0);
+        seed_usage_trace(&writer, "trace-normal", 48);
+        seed_trace(&writer, "trace-no-usage", None);
+
+        writer.request_flush();
+        tokio::time::sleep(std::time::Duration::from_millis(150)).await;
+
+        let summaries = reader.trace_usage_summaries(10).await.unwrap();
+        assert_eq!(summaries.len(), 2);
+        let silent = summaries
+            .iter()
+            .find(|s| s.trace_id == "trace-silent")
+            .unwrap();
+        assert!(silent.anomaly);
+        assert_eq!(silent.anomalous_span_ids, vec![silent_span]);
+        assert_eq!(silent.input_tokens, 120);
+        assert_eq!(silent.finish_reason.as_deref(), Some("content_filter"));
+        let normal = summaries
+            .iter()
+            .find(|s| s.trace_id == "trace-normal")
+            .unwrap();
+        assert!(!normal.anomaly);
+        assert_eq!(normal.output_tokens, 48);
+
+        let anomalous = reader.list_anomalous_traces(10).await.unwrap();
+        assert_eq!(anomalous.len(), 1);
+        assert_eq!(anomalous[0].trace_id, "trace-silent");
+    }
+
+    async fn insert_span(
+        db: &Database,
+        id: &str,
+        model: Option<&str>,
+        started_at: i64,
+        latency_ms: Option<i64>,
+        errored: bool,
+    ) {
+        db.execute(
+            queries::INSERT_TRACE,
+            vec![
+                serde_json::json!(format!("trace-{}", id)),
+                serde_json::json!(started_at),
+                serde_json::Value::Null,
+                serde_json::Value::Null,
+            ],
+        )
+        .await
+        .unwrap();
+        let attributes = match model {
+            Some(model) => serde_json::json!({ "gen_ai.request.model": model }),
+            None => serde_json::json!({}),
+        };
+        db.execute(
+            queries::INSERT_SPAN,
+            vec![
+                serde_json::json!(id),
+                serde_json::json!(format!("trace-{}", id)),
+                serde_json::Value::Null,
+                serde_json::json!("llm.stream_completion"),
+                serde_json::json!(started_at),
+                serde_json::json!(latency_ms.map(|latency| started_at + latency)),
+                serde_json::json!(attributes.to_string()),
+            ],
+        )
+        .await
+        .unwrap();
+        if errored {
+            db.execute(
+                queries::INSERT_SPAN_EVENT,
+                vec![
+                    serde_json::json!(format!("event-{}", id)),
+                    serde_json::json!(id),
+                    serde_json::json!(started_at),
+                    serde_json::json!("error.type"),
+                    serde_json::json!({ "error_type": "http_error" })
+                        .to_string()
+                        .into(),
+                ],
+            )
+            .await
+            .unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_metrics_compute_error_rate_and_percentiles() {
+        let (_writer, reader, db, _temp_dir) = create_test_setup().await;
+        let now = chrono::Utc::now().timestamp_millis();
+
+        // gpt-4o: latencies 100..=1000 in steps of 100, two of them errored
+        for i in 1..=10 {
+            insert_span(
+                &db,
+                &format!("gpt-{}", i),
+                Some("gpt-4o"),
+                now - 60_000,
+                Some(i * 100),
+                i == 3 || i == 7,
+            )
+            .await;
+        }
+        // claude: one success, one error that never closed
+        insert_span(
+            &db,
+            "claude-ok",
+            Some("claude-sonnet-4"),
+            now - 1_000,
+            Some(50),
+            false,
+        )
+        .await;
+        insert_span(
+            &db,
+            "claude-err",
+            Some("claude-sonnet-4"),
+            now - 1_000,
+            None,
+            true,
+        )
+        .await;
+        // Outside the window
+        insert_span(&db, "old", Some("gpt-4o"), now - 7_200_000, Some(10), true).await;
+
+        let metrics = reader.metrics(Duration::from_secs(3600)).await.unwrap();
+        assert_eq!(metrics.window_ms, 3_600_000);
+        assert_eq!(metrics.overall.total, 12);
+        assert_eq!(metrics.overall.errors, 3);
+        assert!((metrics.overall.success_rate - 0.75).abs() < 1e-9);
+        // 11 closed spans: 50, 100, ..., 1000
+        assert_eq!(metrics.overall.p50_latency_ms, Some(500));
+        assert_eq!(metrics.overall.p95_latency_ms, Some(1000));
+
+        let gpt = &metrics.by_model["gpt-4o"];
+        assert_eq!(gpt.total, 10);
+        assert_eq!(gpt.errors, 2);
+        assert!((gpt.success_rate - 0.8).abs() < 1e-9);
+        assert_eq!(gpt.p50_latency_ms, Some(500));
+        assert_eq!(gpt.p95_latency_ms, Some(1000));
+
+        let claude = &metrics.by_model["claude-sonnet-4"];
+        assert_eq!(claude.total, 2);
+        assert_eq!(claude.errors, 1);
+        assert!((claude.success_rate - 0.5).abs() < 1e-9);
+        assert_eq!(claude.p50_latency_ms, Some(50));
+        assert_eq!(metrics.by_model.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_metrics_for_empty_window() {
+        let (_writer, reader, _db, _temp_dir) = create_test_setup().await;
+        let metrics = reader.metrics(Duration::from_secs(60)).await.unwrap();
+        assert_eq!(metrics.overall, SpanStats::default());
+        assert!(metrics.by_model.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_list_traces_filters_by_has_error() {
+        let (_writer, reader, db, _temp_dir) = create_test_setup().await;
+        let now = chrono::Utc::now().timestamp_millis();
+
+        insert_span(&db, "ok-1", Some("gpt-4o"), now - 3_000, Some(100), false).await;
+        insert_span(&db, "err-1", Some("gpt-4o"), now - 2_000, Some(200), true).await;
+        insert_span(
+            &db,
+            "err-2",
+            Some("claude-sonnet-4"),
+            now - 1_000,
+            None,
+            true,
+        )
+        .await;
+        insert_span(&db, "open-1", Some("gpt-4o"), now - 500, None, false).await;
+
+        let errored = TraceFilter {
+            has_error: Some(true),
+            ..Default::default()
+        };
+        let (traces, next) = reader.list_traces(&errored, 10, None).await.unwrap();
+        let ids: Vec<&str> = traces.iter().map(|t| t.id.as_str()).collect();
+        assert_eq!(ids, vec!["trace-err-2", "trace-err-1"]);
+        assert!(traces.iter().all(|t| t.status == TraceStatus::Error));
+        assert_eq!(traces[1].duration_ms, Some(200));
+        assert_eq!(traces[0].duration_ms, None);
+        assert!(next.is_none());
+
+        let clean = TraceFilter {
+            has_error: Some(false),
+            ..Default::default()
+        };
+        let (traces, _) = reader.list_traces(&clean, 10, None).await.unwrap();
+        assert_eq!(traces.len(), 2);
+        assert_eq!(traces[0].id, "trace-open-1");
+        assert_eq!(traces[0].status, TraceStatus::InProgress);
+        assert_eq!(traces[1].status, TraceStatus::Ok);
+        assert_eq!(traces[1].model.as_deref(), Some("gpt-4o"));
+
+        let slow_gpt_errors = TraceFilter {
+            model: Some("gpt-4o".to_string()),
+            has_error: Some(true),
+            min_duration_ms: Some(150),
+            ..Default::default()
+        };
+        let (traces, _) = reader
+            .list_traces(&slow_gpt_errors, 10, None)
+            .await
+            .unwrap();
+        assert_eq!(traces.len(), 1);
+        assert_eq!(traces[0].id, "trace-err-1");
+    }
+
+    #[tokio::test]
+    async fn test_list_traces_paginates_with_stable_cursor() {
+        let (writer, reader, db, _temp_dir) = create_test_setup().await;
+        let now = chrono::Utc::now().timestamp_millis();
+
+        // Two traces share a start time so the id breaks the tie
+        for (id, offset) in [
+            ("a", 5_000),
+            ("b", 4_000),
+            ("c", 4_000),
+            ("d", 3_000),
+            ("e", 2_000),
+        ] {
+            insert_span(&db, id, Some("gpt-4o"), now - offset, Some(10), false).await;
+        }
+
+        let filter = TraceFilter::default();
+        let mut seen = Vec::new();
+        let mut cursor = None;
+        loop {
+            let (page, next) = reader
+                .list_traces(&filter, 2, cursor.as_ref())
+                .await
+                .unwrap();
+            assert!(page.len() <= 2);
+            seen.extend(page.into_iter().map(|t| t.id));
+            // A trace arriving between pages must not shift later pages
+            if seen.len() == 2 {
+                insert_span(&db, "late", Some("gpt-4o"), now, Some(10), false).await;
+            }
+            match next {
+                Some(next) => cursor = Some(next),
+                None => break,
+            }
+        }
+        assert_eq!(
+            seen,
+            vec!["trace-e", "trace-d", "trace-c", "trace-b", "trace-a"]
+        );
+
+        let window = TraceFilter {
+            started_after: Some(now - 4_000),
+            started_before: Some(now - 2_000),
+            ..Default::default()
+        };
+        let (page, next) = reader.list_traces(&window, 10, None).await.unwrap();
+        let ids: Vec<&str> = page.iter().map(|t| t.id.as_str()).collect();
+        assert_eq!(ids, vec!["trace-d", "trace-c", "trace-b"]);
+        assert!(next.is_none());
+
+        seed_usage_trace(&writer, "trace-usage", 30);
+        writer.request_flush();
+        tokio::time::sleep(std::time::Duration::from_millis(150)).await;
+        let (page, _) = reader.list_traces(&filter, 1, None).await.unwrap();
+        assert_eq!(page[0].id, "trace-usage");
+        assert_eq!(page[0].total_tokens, 150);
+    }
+
+    #[tokio::test]
+    async fn test_close_orphaned_spans_only_touches_stale_open_spans() {
+        let (_writer, reader, db, _temp_dir) = create_test_setup().await;
+        let now = chrono::Utc::now().timestamp_millis();
+        let stale_started = now - 2 * 3_600_000;
+
+        insert_span(
+            &db,
+            "stale-open",
+            Some("gpt-4o"),
+            stale_started,
+            None,
+            false,
+        )
+        .await;
+        insert_span(
+            &db,
+            "recent-open",
+            Some("gpt-4o"),
+            now - 60_000,
+            None,
+            false,
+        )
+        .await;
+        insert_span(
+            &db,
+            "stale-closed",
+            Some("gpt-4o"),
+            stale_started,
+            Some(500),
+            false,
+        )
+        .await;
+
+        let closed = reader
+            .close_orphaned_spans(Duration::from_secs(3600))
+            .await
+            .unwrap();
+        assert_eq!(closed, 1);
+
+        let spans = db
+            .query("SELECT id, ended_at FROM spans ORDER BY id", vec![])
+            .await
+            .unwrap()
+            .rows;
+        let ended_at = |id: &str| {
+            spans
+                .iter()
+                .find(|row| row["id"] == id)
+                .map(|row| row["ended_at"].clone())
+                .unwrap()
+        };
+        assert_eq!(
+            ended_at("stale-open"),
+            serde_json::json!(stale_started + 3_600_000)
+        );
+        assert_eq!(ended_at("recent-open"), serde_json::Value::Null);
+        assert_eq!(
+            ended_at("stale-closed"),
+            serde_json::json!(stale_started + 500)
+        );
+
+        let events = db
+            .query(
+                "SELECT span_id, payload FROM span_events WHERE event_type = 'error.type'",
+                vec![],
+            )
+            .await
+            .unwrap()
+            .rows;
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0]["span_id"], "stale-open");
+        let payload: serde_json::Value =
+            serde_json::from_str(events[0]["payload"].as_str().unwrap()).unwrap();
+        assert_eq!(payload["error_type"], "orphaned");
+
+        // Already-closed orphans are not reprocessed
+        assert_eq!(
+            reader
+                .close_orphaned_spans(Duration::from_secs(3600))
+                .await
+                .unwrap(),
+            0
+        );
+    }
+
+    #[tokio::test]
+    async fn test_events_of_type_returns_only_matching_events() {
+        let (writer, reader, _db, _temp_dir) = create_test_setup().await;
+
+        let span_id = seed_usage_trace(&writer, "trace-typed", 40);
+        writer.add_event(
+            span_id.clone(),
+            attributes::ERROR_TYPE.to_string(),
+            Some(serde_json::json!({ "error_type": "http_error", "status_code": 500 })),
+        );
+        let other_span = seed_usage_trace(&writer, "trace-other", 7);
+        writer.request_flush();
+        tokio::time::sleep(std::time::Duration::from_millis(150)).await;
+
+        let usage = reader
+            .events_of_type(&span_id, "gen_ai.usage")
+            .await
+            .unwrap();
+        assert_eq!(usage.len(), 1);
+        assert_eq!(usage[0].span_id, span_id);
+        assert_eq!(usage[0].event_type, "gen_ai.usage");
+        assert_eq!(
+            usage[0].payload.as_ref().unwrap()["output_tokens"],
+            serde_json::json!(40)
+        );
+
+        let errors = reader
+            .events_of_type(&span_id, attributes::ERROR_TYPE)
+            .await
+            .unwrap();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].payload.as_ref().unwrap()["status_code"], 500);
+
+        assert!(reader
+            .events_of_type(&other_span, attributes::ERROR_TYPE)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_ensure_span_event_type_index_adds_missing_index() {
+        let (_writer, _reader, db, _temp_dir) = create_test_setup().await;
+        const INDEX_COUNT: &str = "SELECT COUNT(*) AS count FROM sqlite_master WHERE type = 'index' AND name = 'idx_span_events_span_type'";
+        assert_eq!(count(&db, INDEX_COUNT).await, 1);
+
+        db.execute("DROP INDEX idx_span_events_span_type", vec![])
+            .await
+            .unwrap();
+        assert_eq!(count(&db, INDEX_COUNT).await, 0);
+
+        assert!(ensure_span_event_type_index(&db).await.unwrap());
+        assert_eq!(count(&db, INDEX_COUNT).await, 1);
+        assert!(!ensure_span_event_type_index(&db).await.unwrap());
+    }
+}
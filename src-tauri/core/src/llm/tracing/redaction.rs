@@ -0,0 +1,233 @@
+// Redaction of prompt and response bodies before they are written to span events
+// Structural metadata (roles, token counts, tool names, finish reasons) is kept
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+use tauri::State;
+
+use super::types::attributes;
+use super::TraceWriter;
+
+/// How message content is stored in traced request/response bodies
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum RedactMode {
+    /// Store bodies as-is
+    #[default]
+    None,
+    /// Keep at most this many characters of each content field
+    Truncate(usize),
+    /// Remove content fields entirely
+    DropContent,
+    /// Replace content with its SHA-256 so identical prompts can still be correlated
+    Hash,
+}
+
+/// Keys whose values carry user or model text rather than structure
+const CONTENT_KEYS: &[&str] = &[
+    "content",
+    "text",
+    "response_text",
+    "system",
+    "instructions",
+    "input",
+    "arguments",
+    "thinking",
+    "reasoning_content",
+];
+
+/// Keys holding tool call arguments, which may be JSON objects rather than strings
+const ARGUMENT_KEYS: &[&str] = &["input", "arguments"];
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RedactionPolicy {
+    pub mode: RedactMode,
+}
+
+impl RedactionPolicy {
+    pub fn new(mode: RedactMode) -> Self {
+        Self { mode }
+    }
+
+    /// Only request/response bodies are redacted; other events carry metadata only
+    pub fn applies_to(&self, event_type: &str) -> bool {
+        self.mode != RedactMode::None
+            && (event_type == attributes::HTTP_REQUEST_BODY
+                || event_type == attributes::HTTP_RESPONSE_BODY)
+    }
+
+    pub fn redact_event(&self, event_type: &str, payload: Option<Value>) -> Option<Value> {
+        match payload {
+            Some(mut payload) if self.applies_to(event_type) => {
+                self.redact(&mut payload);
+                Some(payload)
+            }
+            other => other,
+        }
+    }
+
+    /// Redact content fields anywhere in `value`
+    pub fn redact(&self, value: &mut Value) {
+        match value {
+            Value::Object(map) => {
+                let mut dropped = Vec::new();
+                for (key, field) in map.iter_mut() {
+                    if !CONTENT_KEYS.contains(&key.as_str()) {
+                        self.redact(field);
+                        continue;
+                    }
+                    match field {
+                        Value::String(text) => match self.redact_text(text) {
+                            Some(redacted) => *field = Value::String(redacted),
+                            None => dropped.push(key.clone()),
+                        },
+                        Value::Object(_) if ARGUMENT_KEYS.contains(&key.as_str()) => {
+                            match self.redact_text(&field.to_string()) {
+                                Some(redacted) => *field = Value::String(redacted),
+                                None => dropped.push(key.clone()),
+                            }
+                        }
+                        // Content parts and message lists: keep their shape, redact inside
+                        other => self.redact(other),
+                    }
+                }
+                for key in dropped {
+                    map.remove(&key);
+                }
+            }
+            Value::Array(items) => {
+                for item in items {
+                    self.redact(item);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Returns the replacement text, or None when the field should be removed
+    fn redact_text(&self, text: &str) -> Option<String> {
+        match self.mode {
+            RedactMode::None => Some(text.to_string()),
+            RedactMode::Truncate(limit) => {
+                if text.chars().count() <= limit {
+                    Some(text.to_string())
+                } else {
+                    let kept: String = text.chars().take(limit).collect();
+                    Some(format!("{}…", kept))
+                }
+            }
+            RedactMode::DropContent => None,
+            RedactMode::Hash => Some(format!(
+                "sha256:{}",
+                hex::encode(Sha256::digest(text.as_bytes()))
+            )),
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn trace_set_redaction_mode(
+    writer: State<'_, Arc<TraceWriter>>,
+    mode: RedactMode,
+) -> Result<(), String> {
+    writer.set_redaction_policy(RedactionPolicy::new(mode));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn request_body() -> Value {
+        json!({
+            "model": "gpt-4o",
+            "messages": [
+                {"role": "system", "content": "You are a secret agent"},
+                {"role": "user", "content": [{"type": "text", "text": "my password is hunter2"}]},
+                {
+                    "role": "assistant",
+                    "content": null,
+                    "tool_calls": [{
+                        "id": "call_1",
+                        "type": "function",
+                        "function": {"name": "read_file", "arguments": "{\"path\":\"/etc/passwd\"}"}
+                    }]
+                }
+            ],
+            "tools": [{"type": "function", "function": {"name": "read_file", "parameters": {"type": "object"}}}]
+        })
+    }
+
+    fn response_body() -> Value {
+        json!({
+            "finish_reason": "stop",
+            "ttft_ms": 42,
+            "usage": {"input_tokens": 10, "output_tokens": 20, "total_tokens": 30},
+            "response_text": "Here is the secret"
+        })
+    }
+
+    #[test]
+    fn drop_content_removes_text_but_keeps_metadata() {
+        let policy = RedactionPolicy::new(RedactMode::DropContent);
+
+        let response = policy
+            .redact_event(attributes::HTTP_RESPONSE_BODY, Some(response_body()))
+            .unwrap();
+        assert!(response.get("response_text").is_none());
+        assert_eq!(response["finish_reason"], json!("stop"));
+        assert_eq!(response["ttft_ms"], json!(42));
+        assert_eq!(response["usage"]["input_tokens"], json!(10));
+        assert_eq!(response["usage"]["output_tokens"], json!(20));
+
+        let request = policy
+            .redact_event(attributes::HTTP_REQUEST_BODY, Some(request_body()))
+            .unwrap();
+        let serialized = request.to_string();
+        assert!(!serialized.contains("secret agent"));
+        assert!(!serialized.contains("hunter2"));
+        assert!(!serialized.contains("/etc/passwd"));
+        assert_eq!(request["model"], json!("gpt-4o"));
+        assert_eq!(request["messages"][0]["role"], json!("system"));
+        assert_eq!(request["messages"][1]["content"][0]["type"], json!("text"));
+        assert_eq!(
+            request["messages"][2]["tool_calls"][0]["function"]["name"],
+            json!("read_file")
+        );
+        assert_eq!(request["tools"][0]["function"]["name"], json!("read_file"));
+    }
+
+    #[test]
+    fn truncate_and_hash_rewrite_content() {
+        let truncated = RedactionPolicy::new(RedactMode::Truncate(4))
+            .redact_event(attributes::HTTP_RESPONSE_BODY, Some(response_body()))
+            .unwrap();
+        assert_eq!(truncated["response_text"], json!("Here…"));
+
+        let hashed = RedactionPolicy::new(RedactMode::Hash)
+            .redact_event(attributes::HTTP_RESPONSE_BODY, Some(response_body()))
+            .unwrap();
+        let digest = hashed["response_text"].as_str().unwrap();
+        assert!(digest.starts_with("sha256:"));
+        assert_eq!(digest.len(), "sha256:".len() + 64);
+        assert_eq!(hashed["usage"]["total_tokens"], json!(30));
+    }
+
+    #[test]
+    fn none_and_other_events_are_untouched() {
+        let body = response_body();
+        assert_eq!(
+            RedactionPolicy::default()
+                .redact_event(attributes::HTTP_RESPONSE_BODY, Some(body.clone())),
+            Some(body.clone())
+        );
+        assert_eq!(
+            RedactionPolicy::new(RedactMode::DropContent)
+                .redact_event("gen_ai.usage", Some(body.clone())),
+            Some(body)
+        );
+    }
+}
@@ -1,16 +1,60 @@
 // Database schema for LLM tracing
 // Creates tables for traces, spans, and span events
 
-#[cfg(test)]
 use std::sync::Arc;
 
-#[cfg(test)]
 use crate::database::Database;
 
+/// How long a tracing statement waits on a locked database before failing
+pub const DEFAULT_BUSY_TIMEOUT_MS: u64 = 5_000;
+
+/// Environment variable overriding `DEFAULT_BUSY_TIMEOUT_MS`
+pub const BUSY_TIMEOUT_ENV: &str = "TALKCODY_TRACING_BUSY_TIMEOUT_MS";
+
+/// Busy timeout for the tracing connection, from `BUSY_TIMEOUT_ENV` when set
+pub fn busy_timeout_ms() -> u64 {
+    std::env::var(BUSY_TIMEOUT_ENV)
+        .ok()
+        .and_then(|value| value.trim().parse().ok())
+        .unwrap_or(DEFAULT_BUSY_TIMEOUT_MS)
+}
+
+/// Put the tracing connection in WAL mode so `TraceReader` queries don't block
+/// `flush_batch` writes, and wait up to `busy_timeout_ms` on a locked database
+/// instead of failing with "database is locked"
+pub async fn init_tracing_pragmas(db: &Arc<Database>, busy_timeout_ms: u64) -> Result<(), String> {
+    db.execute("PRAGMA journal_mode=WAL", vec![]).await?;
+    db.execute(&format!("PRAGMA busy_timeout={}", busy_timeout_ms), vec![])
+        .await?;
+    Ok(())
+}
+
+/// Add the `(span_id, event_type)` index to a database created before it existed.
+/// Returns true when the index was created; a missing `span_events` table is
+/// left for the schema setup to create.
+pub async fn ensure_span_event_type_index(db: &Arc<Database>) -> Result<bool, String> {
+    let existing = db
+        .query(
+            "SELECT name, type FROM sqlite_master WHERE name IN ('span_events', 'idx_span_events_span_type')",
+            vec![],
+        )
+        .await?;
+    let has = |name: &str| existing.rows.iter().any(|row| row["name"] == name);
+    if !has("span_events") || has("idx_span_events_span_type") {
+        return Ok(false);
+    }
+    db.execute(queries::CREATE_SPAN_EVENTS_SPAN_TYPE_INDEX, vec![])
+        .await?;
+    log::info!("Added idx_span_events_span_type to span_events");
+    Ok(true)
+}
+
 /// Initializes the tracing database schema
 /// Creates tables and indexes if they don't exist
 #[cfg(test)]
 pub async fn init_tracing_schema(db: &Arc<Database>) -> Result<(), String> {
+    init_tracing_pragmas(db, DEFAULT_BUSY_TIMEOUT_MS).await?;
+
     // Create tables
     db.execute(
         "CREATE TABLE IF NOT EXISTS traces (id TEXT PRIMARY KEY, started_at INTEGER NOT NULL, ended_at INTEGER, metadata TEXT)",
@@ -64,6 +108,8 @@ pub async fn init_tracing_schema(db: &Arc<Database>) -> Result<(), String> {
         vec![],
     )
     .await?;
+    db.execute(queries::CREATE_SPAN_EVENTS_SPAN_TYPE_INDEX, vec![])
+        .await?;
 
     log::info!("LLM tracing schema initialized successfully");
     Ok(())
@@ -84,6 +130,49 @@ pub mod queries {
     /// Insert a new span event
     pub const INSERT_SPAN_EVENT: &str =
         "INSERT INTO span_events (id, span_id, timestamp, event_type, payload) VALUES (?, ?, ?, ?, ?)";
+
+    /// Record the owning session in the trace metadata
+    pub const SET_TRACE_SESSION: &str = "UPDATE traces SET metadata = json_set(COALESCE(metadata, '{}'), '$.session_id', ?) WHERE id = ?";
+
+    /// Traces belonging to a session: tagged via metadata, or keyed by the session id itself
+    pub const SESSION_TRACE_IDS: &str =
+        "SELECT id FROM traces WHERE id = ?1 OR json_extract(metadata, '$.session_id') = ?1";
+
+    pub const DELETE_SESSION_SPAN_EVENTS: &str = "DELETE FROM span_events WHERE span_id IN (SELECT id FROM spans WHERE trace_id IN (SELECT id FROM traces WHERE id = ?1 OR json_extract(metadata, '$.session_id') = ?1))";
+
+    pub const DELETE_SESSION_SPANS: &str = "DELETE FROM spans WHERE trace_id IN (SELECT id FROM traces WHERE id = ?1 OR json_extract(metadata, '$.session_id') = ?1)";
+
+    pub const DELETE_SESSION_TRACES: &str =
+        "DELETE FROM traces WHERE id = ?1 OR json_extract(metadata, '$.session_id') = ?1";
+
+    pub const DELETE_TRACE_SPAN_EVENTS: &str =
+        "DELETE FROM span_events WHERE span_id IN (SELECT id FROM spans WHERE trace_id = ?1)";
+
+    pub const DELETE_TRACE_SPANS: &str = "DELETE FROM spans WHERE trace_id = ?1";
+
+    pub const DELETE_TRACE: &str = "DELETE FROM traces WHERE id = ?1";
+
+    /// Spans started at or after ?1 with their model and whether an error event was recorded
+    pub const SPAN_METRICS_ROWS: &str = "SELECT s.id, s.started_at, s.ended_at, json_extract(s.attributes, '$.\"gen_ai.request.model\"') AS model, EXISTS (SELECT 1 FROM span_events e WHERE e.span_id = s.id AND e.event_type = 'error.type') AS has_error FROM spans s WHERE s.started_at >= ?1";
+
+    pub const CREATE_SPAN_EVENTS_SPAN_TYPE_INDEX: &str =
+        "CREATE INDEX IF NOT EXISTS idx_span_events_span_type ON span_events(span_id, event_type)";
+
+    /// Events of type ?2 recorded on span ?1, oldest first
+    pub const SPAN_EVENTS_OF_TYPE: &str = "SELECT id, span_id, timestamp, event_type, payload FROM span_events WHERE span_id = ?1 AND event_type = ?2 ORDER BY timestamp, id";
+
+    /// Spans that were never closed and started before ?1
+    pub const ORPHANED_SPANS: &str =
+        "SELECT id, started_at FROM spans WHERE ended_at IS NULL AND started_at < ?1";
+
+    /// Recorded usage per span for the latest ?2 traces with usage; ?1 = 1 keeps only traces
+    /// where some span consumed input tokens but produced no output (`zero_output`)
+    pub const TRACE_USAGE_ROWS: &str = "WITH usage AS (SELECT s.trace_id, s.id AS span_id, s.started_at AS span_started_at, COALESCE(json_extract(u.payload, '$.input_tokens'), 0) AS input_tokens, COALESCE(json_extract(u.payload, '$.output_tokens'), 0) AS output_tokens, (SELECT json_extract(f.payload, '$.finish_reason') FROM span_events f WHERE f.span_id = s.id AND f.event_type = 'gen_ai.finish_reason' ORDER BY f.timestamp DESC LIMIT 1) AS finish_reason FROM span_events u JOIN spans s ON s.id = u.span_id WHERE u.event_type = 'gen_ai.usage'), selected AS (SELECT t.id, t.started_at FROM traces t WHERE t.id IN (SELECT trace_id FROM usage WHERE ?1 = 0 OR (input_tokens > 0 AND output_tokens = 0)) ORDER BY t.started_at DESC LIMIT ?2) SELECT usage.trace_id, usage.span_id, selected.started_at, json_extract(t.metadata, '$.session_id') AS session_id, usage.input_tokens, usage.output_tokens, usage.finish_reason, (usage.input_tokens > 0 AND usage.output_tokens = 0) AS zero_output FROM usage JOIN selected ON selected.id = usage.trace_id JOIN traces t ON t.id = usage.trace_id ORDER BY selected.started_at DESC, usage.trace_id, usage.span_started_at";
+
+    /// One summary row per trace, newest first. ?1 model, ?2/?3 started_at range [from, to),
+    /// ?4 has_error (0/1), ?5 minimum duration in ms, ?6/?7 keyset cursor (started_at, id)
+    /// taken from the last row of the previous page, ?8 limit. NULL disables a filter.
+    pub const LIST_TRACE_SUMMARIES: &str = "WITH summary AS (SELECT t.id, t.started_at, (SELECT json_extract(s.attributes, '$.\"gen_ai.request.model\"') FROM spans s WHERE s.trace_id = t.id AND json_extract(s.attributes, '$.\"gen_ai.request.model\"') IS NOT NULL ORDER BY s.parent_span_id IS NOT NULL, s.started_at LIMIT 1) AS model, CASE WHEN EXISTS (SELECT 1 FROM spans s WHERE s.trace_id = t.id AND s.ended_at IS NULL) THEN NULL ELSE COALESCE(t.ended_at, (SELECT MAX(s.ended_at) FROM spans s WHERE s.trace_id = t.id)) END AS ended_at, EXISTS (SELECT 1 FROM span_events e JOIN spans s ON s.id = e.span_id WHERE s.trace_id = t.id AND e.event_type = 'error.type') AS has_error, (SELECT COALESCE(SUM(COALESCE(json_extract(e.payload, '$.total_tokens'), json_extract(e.payload, '$.input_tokens') + json_extract(e.payload, '$.output_tokens'), 0)), 0) FROM span_events e JOIN spans s ON s.id = e.span_id WHERE s.trace_id = t.id AND e.event_type = 'gen_ai.usage') AS total_tokens FROM traces t) SELECT id, started_at, model, ended_at - started_at AS duration_ms, ended_at, has_error, total_tokens FROM summary WHERE (?1 IS NULL OR model = ?1) AND (?2 IS NULL OR started_at >= ?2) AND (?3 IS NULL OR started_at < ?3) AND (?4 IS NULL OR has_error = ?4) AND (?5 IS NULL OR ended_at - started_at >= ?5) AND (?6 IS NULL OR started_at < ?6 OR (started_at = ?6 AND id < ?7)) ORDER BY started_at DESC, id DESC LIMIT ?8";
 }
 
 #[cfg(test)]
@@ -238,4 +327,71 @@ mod tests {
             serde_json::Value::Number(1706611201000i64.into())
         );
     }
+
+    #[tokio::test]
+    async fn test_tracing_pragmas() {
+        let (db, _temp_dir) = create_test_db().await;
+        init_tracing_pragmas(&db, 1234).await.unwrap();
+
+        let mode = db.query("PRAGMA journal_mode", vec![]).await.unwrap();
+        assert_eq!(mode.rows[0]["journal_mode"], "wal");
+        let timeout = db.query("PRAGMA busy_timeout", vec![]).await.unwrap();
+        assert_eq!(timeout.rows[0]["timeout"], 1234);
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_writes_and_reads_under_wal() {
+        let (writer_db, temp_dir) = create_test_db().await;
+        init_tracing_schema(&writer_db).await.unwrap();
+        // A second connection to the same file, like the UI reading traces
+        let reader_db = Arc::new(Database::new(
+            temp_dir
+                .path()
+                .join("test_tracing.db")
+                .to_string_lossy()
+                .to_string(),
+        ));
+        reader_db.connect().await.unwrap();
+        init_tracing_pragmas(&reader_db, DEFAULT_BUSY_TIMEOUT_MS)
+            .await
+            .unwrap();
+
+        let writes = async {
+            for i in 0..200i64 {
+                writer_db
+                    .execute(
+                        queries::INSERT_TRACE,
+                        vec![
+                            serde_json::json!(format!("trace-{}", i)),
+                            serde_json::json!(1706611200000i64 + i),
+                            serde_json::Value::Null,
+                            serde_json::Value::Null,
+                        ],
+                    )
+                    .await?;
+            }
+            Ok::<_, String>(())
+        };
+        let reads = async {
+            for _ in 0..200 {
+                reader_db
+                    .query(
+                        "SELECT id FROM traces ORDER BY started_at DESC LIMIT 20",
+                        vec![],
+                    )
+                    .await?;
+            }
+            Ok::<_, String>(())
+        };
+
+        let (written, read) = tokio::join!(writes, reads);
+        written.expect("writes should not hit a lock");
+        read.expect("reads should not hit a lock");
+
+        let count = reader_db
+            .query("SELECT COUNT(*) AS n FROM traces", vec![])
+            .await
+            .unwrap();
+        assert_eq!(count.rows[0]["n"], 200);
+    }
 }
@@ -3,6 +3,7 @@
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::time::Duration;
 
 /// Represents a complete trace of an LLM operation
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -62,6 +63,11 @@ pub enum TraceCommand {
     CloseSpan { span_id: String, ended_at: i64 },
     /// Add an event to a span
     AddEvent(SpanEvent),
+    /// Associate a trace with a chat session
+    SetTraceSession {
+        trace_id: String,
+        session_id: String,
+    },
     #[cfg(test)]
     /// Flush all pending writes
     Flush,
@@ -90,6 +96,20 @@ pub mod attributes {
 
     // Latency attributes
     pub const GEN_AI_TTFT_MS: &str = "gen_ai.ttft_ms";
+    pub const GEN_AI_SLOW_START: &str = "gen_ai.slow_start";
+
+    // Model fallback attributes
+    pub const GEN_AI_FALLBACK_MODEL: &str = "gen_ai.fallback.model";
+    pub const GEN_AI_FALLBACK_REASON: &str = "gen_ai.fallback.reason";
+
+    // Provider circuit breaker attributes
+    pub const GEN_AI_CIRCUIT_BREAKER: &str = "gen_ai.circuit_breaker";
+
+    // Caller-supplied request metadata
+    pub const TALKCODY_META_PREFIX: &str = "talkcody.meta.";
+
+    /// Namespaces request metadata may not write into
+    pub const RESERVED_PREFIXES: [&str; 2] = ["gen_ai.", "error."];
 }
 
 /// Helper functions for building attributes
@@ -101,6 +121,34 @@ pub fn int_attr(value: i64) -> serde_json::Value {
     serde_json::Value::Number(value.into())
 }
 
+/// Request metadata as span attributes under `talkcody.meta.*`; keys in a
+/// reserved namespace are dropped
+pub fn metadata_attributes(
+    metadata: &HashMap<String, serde_json::Value>,
+) -> HashMap<String, serde_json::Value> {
+    metadata
+        .iter()
+        .filter(|(key, _)| {
+            let reserved = attributes::RESERVED_PREFIXES
+                .iter()
+                .any(|prefix| key.starts_with(prefix));
+            if reserved {
+                log::warn!(
+                    "Dropping request metadata key in reserved namespace: {}",
+                    key
+                );
+            }
+            !reserved
+        })
+        .map(|(key, value)| {
+            (
+                format!("{}{}", attributes::TALKCODY_META_PREFIX, key),
+                value.clone(),
+            )
+        })
+        .collect()
+}
+
 pub fn float_attr(value: f64) -> serde_json::Value {
     serde_json::Number::from_f64(value)
         .map(serde_json::Value::Number)
@@ -112,6 +160,60 @@ pub const BATCH_SIZE: usize = 100;
 pub const BATCH_TIMEOUT_MS: u64 = 50;
 pub const CHANNEL_CAPACITY: usize = 10000;
 
+/// Batching behaviour of a `TraceWriter`; defaults to the constants above
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TraceWriterConfig {
+    batch_size: usize,
+    flush_interval: Duration,
+    channel_capacity: usize,
+}
+
+impl TraceWriterConfig {
+    pub fn new(
+        batch_size: usize,
+        flush_interval: Duration,
+        channel_capacity: usize,
+    ) -> Result<Self, String> {
+        if batch_size == 0 {
+            return Err("Trace batch size must be greater than zero".to_string());
+        }
+        if flush_interval.is_zero() {
+            return Err("Trace flush interval must be greater than zero".to_string());
+        }
+        if channel_capacity == 0 {
+            return Err("Trace channel capacity must be greater than zero".to_string());
+        }
+        Ok(Self {
+            batch_size,
+            flush_interval,
+            channel_capacity,
+        })
+    }
+
+    /// Queued commands that trigger a write without waiting for the interval
+    pub fn batch_size(&self) -> usize {
+        self.batch_size
+    }
+
+    pub fn flush_interval(&self) -> Duration {
+        self.flush_interval
+    }
+
+    pub fn channel_capacity(&self) -> usize {
+        self.channel_capacity
+    }
+}
+
+impl Default for TraceWriterConfig {
+    fn default() -> Self {
+        Self {
+            batch_size: BATCH_SIZE,
+            flush_interval: Duration::from_millis(BATCH_TIMEOUT_MS),
+            channel_capacity: CHANNEL_CAPACITY,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -185,4 +287,39 @@ mod tests {
         );
         assert_eq!(int_attr(42), serde_json::Value::Number(42.into()));
     }
+
+    #[test]
+    fn test_metadata_attributes_are_namespaced() {
+        let metadata = HashMap::from([
+            ("feature".to_string(), string_attr("codegen")),
+            ("experiment".to_string(), string_attr("A")),
+            ("attempt".to_string(), int_attr(2)),
+        ]);
+
+        let attributes = metadata_attributes(&metadata);
+        assert_eq!(attributes.len(), 3);
+        assert_eq!(
+            attributes.get("talkcody.meta.feature"),
+            Some(&string_attr("codegen"))
+        );
+        assert_eq!(
+            attributes.get("talkcody.meta.experiment"),
+            Some(&string_attr("A"))
+        );
+        assert_eq!(attributes.get("talkcody.meta.attempt"), Some(&int_attr(2)));
+        assert!(!attributes.contains_key("feature"));
+    }
+
+    #[test]
+    fn test_metadata_attributes_drop_reserved_keys() {
+        let metadata = HashMap::from([
+            ("gen_ai.request.model".to_string(), string_attr("spoofed")),
+            ("error.type".to_string(), string_attr("none")),
+            ("feature".to_string(), string_attr("codegen")),
+        ]);
+
+        let attributes = metadata_attributes(&metadata);
+        assert_eq!(attributes.len(), 1);
+        assert!(attributes.contains_key("talkcody.meta.feature"));
+    }
 }
@@ -1,20 +1,41 @@
 // Async trace writer with non-blocking channel and batching
 // Ensures stream processing never waits for database writes
 
+use async_trait::async_trait;
 use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::mpsc;
 use tokio::sync::Mutex;
 use tokio::time::interval;
 
-use crate::database::Database;
+use crate::database::{Database, QueryResult};
 
 use super::{
     ids::{generate_event_id, generate_span_id, generate_trace_id},
+    redaction::RedactionPolicy,
     schema::queries,
-    types::{Span, SpanEvent, Trace, TraceCommand, BATCH_SIZE, BATCH_TIMEOUT_MS, CHANNEL_CAPACITY},
+    types::{Span, SpanEvent, Trace, TraceCommand, TraceWriterConfig},
 };
 
+/// Where the background task writes batches; the database outside of tests
+#[async_trait]
+trait TraceStore: Send + Sync {
+    async fn batch(
+        &self,
+        statements: Vec<(String, Vec<serde_json::Value>)>,
+    ) -> Result<Vec<QueryResult>, String>;
+}
+
+#[async_trait]
+impl TraceStore for Database {
+    async fn batch(
+        &self,
+        statements: Vec<(String, Vec<serde_json::Value>)>,
+    ) -> Result<Vec<QueryResult>, String> {
+        Database::batch(self, statements).await
+    }
+}
+
 /// Async trace writer that batches writes to the database
 /// Uses a channel for non-blocking operation
 pub struct TraceWriter {
@@ -22,32 +43,51 @@ pub struct TraceWriter {
     db: Arc<Database>,
     receiver: Arc<Mutex<Option<mpsc::Receiver<TraceCommand>>>>,
     span_trace_ids: Arc<std::sync::Mutex<std::collections::HashMap<String, String>>>,
+    redaction: Arc<std::sync::RwLock<RedactionPolicy>>,
+    config: TraceWriterConfig,
 }
 
 impl TraceWriter {
     /// Creates a new TraceWriter without starting the background task.
     /// Call `start()` to spawn the background processing task.
-    pub fn new(db: Arc<Database>) -> Self {
-        let (sender, receiver) = mpsc::channel::<TraceCommand>(CHANNEL_CAPACITY);
+    pub fn new(db: Arc<Database>, config: TraceWriterConfig) -> Self {
+        let (sender, receiver) = mpsc::channel::<TraceCommand>(config.channel_capacity());
 
         Self {
             sender,
             db,
             receiver: Arc::new(Mutex::new(Some(receiver))),
             span_trace_ids: Arc::new(std::sync::Mutex::new(std::collections::HashMap::new())),
+            redaction: Arc::new(std::sync::RwLock::new(RedactionPolicy::default())),
+            config,
         }
     }
 
+    /// Set how request/response bodies are redacted in subsequently added events
+    pub fn set_redaction_policy(&self, policy: RedactionPolicy) {
+        if let Ok(mut current) = self.redaction.write() {
+            *current = policy;
+        }
+    }
+
+    pub fn redaction_policy(&self) -> RedactionPolicy {
+        self.redaction
+            .read()
+            .map(|policy| *policy)
+            .unwrap_or_default()
+    }
+
     /// Starts the background processing task.
     /// Must be called from within a Tokio runtime context.
     pub fn start(&self) {
         let db = self.db.clone();
         let receiver_guard = self.receiver.clone();
+        let config = self.config;
 
         tokio::spawn(async move {
             let receiver = receiver_guard.lock().await.take();
             if let Some(rx) = receiver {
-                Self::run_writer(db, rx).await;
+                Self::run_writer(db, rx, config).await;
             } else {
                 log::warn!("TraceWriter::start() called but receiver already taken");
             }
@@ -55,9 +95,13 @@ impl TraceWriter {
     }
 
     /// Background task that processes commands and batches writes
-    async fn run_writer(db: Arc<Database>, mut receiver: mpsc::Receiver<TraceCommand>) {
-        let mut batch: Vec<TraceCommand> = Vec::with_capacity(BATCH_SIZE);
-        let mut flush_interval = interval(Duration::from_millis(BATCH_TIMEOUT_MS));
+    async fn run_writer(
+        db: Arc<dyn TraceStore>,
+        mut receiver: mpsc::Receiver<TraceCommand>,
+        config: TraceWriterConfig,
+    ) {
+        let mut batch: Vec<TraceCommand> = Vec::with_capacity(config.batch_size());
+        let mut flush_interval = interval(config.flush_interval());
 
         log::info!("TraceWriter background task started");
 
@@ -82,7 +126,7 @@ impl TraceWriter {
                         }
                         other => {
                             batch.push(other);
-                            if batch.len() >= BATCH_SIZE {
+                            if batch.len() >= config.batch_size() {
                                 Self::flush_batch(&db, &mut batch).await;
                             }
                         }
@@ -110,7 +154,7 @@ impl TraceWriter {
 
     /// Flush a batch of commands to the database
     /// Ensures CreateTrace commands are executed first to satisfy foreign key constraints
-    async fn flush_batch(db: &Arc<Database>, batch: &mut Vec<TraceCommand>) {
+    async fn flush_batch(db: &Arc<dyn TraceStore>, batch: &mut Vec<TraceCommand>) {
         if batch.is_empty() {
             return;
         }
@@ -118,6 +162,7 @@ impl TraceWriter {
         // Separate commands by type to ensure proper execution order
         // CreateTrace must come before CreateSpan to satisfy FK constraints
         let mut trace_inserts: Vec<(String, Vec<serde_json::Value>)> = Vec::new();
+        let mut trace_sessions: Vec<(String, Vec<serde_json::Value>)> = Vec::new();
         let mut span_inserts: Vec<(String, Vec<serde_json::Value>)> = Vec::new();
         let mut span_closes: Vec<(String, Vec<serde_json::Value>)> = Vec::new();
         let mut span_events: Vec<(String, Vec<serde_json::Value>)> = Vec::new();
@@ -179,6 +224,18 @@ impl TraceWriter {
                         ],
                     ));
                 }
+                TraceCommand::SetTraceSession {
+                    trace_id,
+                    session_id,
+                } => {
+                    trace_sessions.push((
+                        queries::SET_TRACE_SESSION.to_string(),
+                        vec![
+                            serde_json::Value::String(session_id),
+                            serde_json::Value::String(trace_id),
+                        ],
+                    ));
+                }
                 _ => {} // Flush and Shutdown are handled separately
             }
         }
@@ -187,6 +244,7 @@ impl TraceWriter {
         // This ensures FK constraints are satisfied
         let mut statements: Vec<(String, Vec<serde_json::Value>)> = Vec::new();
         statements.extend(trace_inserts);
+        statements.extend(trace_sessions);
         statements.extend(span_inserts);
         statements.extend(span_events);
         statements.extend(span_closes);
@@ -309,6 +367,22 @@ impl TraceWriter {
         }
     }
 
+    /// Tag a trace with the chat session it belongs to
+    pub fn set_trace_session(&self, trace_id: String, session_id: String) {
+        match self.sender.try_send(TraceCommand::SetTraceSession {
+            trace_id,
+            session_id,
+        }) {
+            Ok(_) => {}
+            Err(mpsc::error::TrySendError::Full(_)) => {
+                log::warn!("TraceWriter channel full, dropping trace session tag");
+            }
+            Err(mpsc::error::TrySendError::Closed(_)) => {
+                log::error!("TraceWriter channel closed");
+            }
+        }
+    }
+
     pub fn has_span_id(&self, span_id: &str) -> bool {
         self.span_trace_ids
             .lock()
@@ -355,6 +429,7 @@ impl TraceWriter {
     ) {
         let event_id = generate_event_id();
         let now = chrono::Utc::now().timestamp_millis();
+        let payload = self.redaction_policy().redact_event(&event_type, payload);
 
         let event = SpanEvent {
             id: event_id,
@@ -439,6 +514,8 @@ impl Clone for TraceWriter {
             db: self.db.clone(),
             receiver: self.receiver.clone(),
             span_trace_ids: self.span_trace_ids.clone(),
+            redaction: self.redaction.clone(),
+            config: self.config,
         }
     }
 }
@@ -462,7 +539,7 @@ mod tests {
             .await
             .unwrap();
 
-        let writer = TraceWriter::new(db.clone());
+        let writer = TraceWriter::new(db.clone(), TraceWriterConfig::default());
         writer.start();
         (writer, db, temp_dir)
     }
@@ -618,4 +695,106 @@ mod tests {
         assert!(!trace_id2.is_empty());
         assert_ne!(trace_id1, trace_id2);
     }
+
+    #[tokio::test]
+    async fn test_redaction_policy_applies_to_body_events() {
+        let (writer, db, _temp_dir) = create_test_writer().await;
+        writer.set_redaction_policy(RedactionPolicy::new(
+            super::super::redaction::RedactMode::DropContent,
+        ));
+
+        let trace_id = writer.start_trace();
+        let span_id = writer.start_span(trace_id, None, "test.span".to_string(), HashMap::new());
+        writer.add_event(
+            span_id.clone(),
+            super::super::types::attributes::HTTP_RESPONSE_BODY.to_string(),
+            Some(serde_json::json!({
+                "finish_reason": "stop",
+                "usage": {"input_tokens": 3, "output_tokens": 5},
+                "response_text": "sensitive output"
+            })),
+        );
+        writer.request_flush();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        let rows = db
+            .query(
+                "SELECT payload FROM span_events WHERE span_id = ?",
+                vec![serde_json::Value::String(span_id)],
+            )
+            .await
+            .unwrap()
+            .rows;
+        assert_eq!(rows.len(), 1);
+        let payload: serde_json::Value =
+            serde_json::from_str(rows[0]["payload"].as_str().unwrap()).unwrap();
+        assert!(payload.get("response_text").is_none());
+        assert_eq!(payload["finish_reason"], serde_json::json!("stop"));
+        assert_eq!(payload["usage"]["output_tokens"], serde_json::json!(5));
+    }
+
+    #[derive(Default)]
+    struct CountingStore {
+        batches: std::sync::Mutex<Vec<usize>>,
+    }
+
+    #[async_trait]
+    impl TraceStore for CountingStore {
+        async fn batch(
+            &self,
+            statements: Vec<(String, Vec<serde_json::Value>)>,
+        ) -> Result<Vec<QueryResult>, String> {
+            self.batches.lock().unwrap().push(statements.len());
+            Ok(Vec::new())
+        }
+    }
+
+    async fn run_events_through(config: TraceWriterConfig, count: usize) -> Vec<usize> {
+        let store = Arc::new(CountingStore::default());
+        let (tx, rx) = mpsc::channel(config.channel_capacity());
+        for i in 0..count {
+            tx.send(TraceCommand::AddEvent(SpanEvent {
+                id: format!("event-{}", i),
+                span_id: "span".to_string(),
+                timestamp: i as i64,
+                event_type: "test".to_string(),
+                payload: None,
+            }))
+            .await
+            .unwrap();
+        }
+        tx.send(TraceCommand::Shutdown).await.unwrap();
+
+        TraceWriter::run_writer(store.clone(), rx, config).await;
+        let batches = store.batches.lock().unwrap().clone();
+        batches
+    }
+
+    #[tokio::test]
+    async fn test_small_batch_size_flushes_each_command() {
+        let config = TraceWriterConfig::new(1, Duration::from_secs(60), 16).unwrap();
+        let batches = run_events_through(config, 5).await;
+        assert_eq!(batches, vec![1, 1, 1, 1, 1]);
+    }
+
+    #[tokio::test]
+    async fn test_large_batch_size_groups_commands() {
+        let config = TraceWriterConfig::new(100, Duration::from_secs(60), 16).unwrap();
+        let batches = run_events_through(config, 5).await;
+        assert_eq!(batches, vec![5]);
+    }
+
+    #[test]
+    fn test_writer_config_rejects_zero_values() {
+        assert!(TraceWriterConfig::new(0, Duration::from_millis(50), 16).is_err());
+        assert!(TraceWriterConfig::new(10, Duration::ZERO, 16).is_err());
+        assert!(TraceWriterConfig::new(10, Duration::from_millis(50), 0).is_err());
+
+        let defaults = TraceWriterConfig::default();
+        assert_eq!(defaults.batch_size(), super::super::types::BATCH_SIZE);
+        assert_eq!(
+            defaults.channel_capacity(),
+            super::super::types::CHANNEL_CAPACITY
+        );
+    }
 }
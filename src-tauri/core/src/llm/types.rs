@@ -6,6 +6,8 @@ use std::collections::HashMap;
 pub enum ProtocolType {
     OpenAiCompatible,
     Claude,
+    Cohere,
+    Mistral,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -32,6 +34,25 @@ pub struct ProviderConfig {
     pub extra_body: Option<serde_json::Value>,
     #[serde(rename = "authType")]
     pub auth_type: AuthType,
+    /// Declarative edits applied to the request body once it is built
+    #[serde(rename = "bodyTransforms", default)]
+    pub body_transforms: Option<Vec<BodyTransform>>,
+}
+
+/// One edit to an outgoing request body. Paths are JSON pointers, e.g.
+/// `/max_tokens` or `/options/num_ctx`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum BodyTransform {
+    /// Set a value, creating missing parent objects
+    SetPath {
+        path: String,
+        value: serde_json::Value,
+    },
+    /// Remove a value if present
+    RemovePath { path: String },
+    /// Move a value if present, replacing whatever is at the destination
+    RenamePath { from: String, to: String },
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
@@ -62,6 +83,18 @@ pub struct ModelConfig {
     pub provider_mappings: Option<HashMap<String, String>>,
     pub pricing: Option<ModelPricing>,
     pub context_length: Option<u32>,
+    /// Alternate names that resolve to this model (e.g. `gpt4o` for `gpt-4o`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub aliases: Vec<String>,
+    /// Retired upstream; hidden from the available list unless asked for
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deprecated: Option<bool>,
+    #[serde(
+        default,
+        rename = "deprecationMessage",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub deprecation_message: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -99,6 +132,61 @@ pub struct AvailableModel {
     pub input_pricing: Option<String>,
 }
 
+impl ModelConfig {
+    pub fn is_deprecated(&self) -> bool {
+        self.deprecated.unwrap_or(false)
+    }
+}
+
+/// Problem in the models configuration that would otherwise fail silently
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ConfigWarning {
+    /// None of the model's providers is registered or configured as custom
+    UnknownProviders {
+        model: String,
+        providers: Vec<String>,
+    },
+    /// A `providerMappings` entry names a provider that does not exist
+    UnknownMappingProvider { model: String, provider: String },
+    /// Several models answer to the same name, ignoring case or via an alias
+    DuplicateModelKey { key: String, models: Vec<String> },
+}
+
+/// Capability requirements for narrowing the available model list
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ModelCapabilityFilter {
+    pub image_input: bool,
+    pub audio_input: bool,
+    pub video_input: bool,
+    pub min_context_length: Option<u32>,
+    /// Keep models flagged `deprecated` in the results
+    pub include_deprecated: bool,
+}
+
+impl ModelCapabilityFilter {
+    /// Whether a model satisfies every requirement; a model without a known
+    /// context length never satisfies a minimum
+    pub fn matches(&self, model: &AvailableModel, config: Option<&ModelConfig>) -> bool {
+        if !self.include_deprecated && config.is_some_and(ModelConfig::is_deprecated) {
+            return false;
+        }
+        if (self.image_input && !model.image_input)
+            || (self.audio_input && !model.audio_input)
+            || (self.video_input && !model.video_input)
+        {
+            return false;
+        }
+        match self.min_context_length {
+            Some(min) => config
+                .and_then(|config| config.context_length)
+                .is_some_and(|length| length >= min),
+            None => true,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct TraceContext {
     #[serde(rename = "traceId")]
@@ -130,6 +218,45 @@ pub struct StreamTextRequest {
     pub request_id: Option<String>,
     #[serde(rename = "traceContext")]
     pub trace_context: Option<TraceContext>,
+    /// Emit `JsonPartial` events with the best-effort parsed object as JSON text streams
+    #[serde(rename = "partialJson", default)]
+    pub partial_json: Option<bool>,
+    /// Models tried in order when `model` cannot be resolved to an available provider
+    #[serde(rename = "fallbackModels", default)]
+    pub fallback_models: Option<Vec<String>>,
+    /// Request token logprobs; ignored by providers that don't support them
+    #[serde(default)]
+    pub logprobs: Option<bool>,
+    /// Number of alternatives returned per token when `logprobs` is set
+    #[serde(rename = "topLogprobs", default)]
+    pub top_logprobs: Option<u8>,
+    /// Emit `UsageDelta` events with a live output token estimate while streaming
+    #[serde(rename = "estimateUsage", default)]
+    pub estimate_usage: bool,
+    /// Caller tags recorded on the request's trace span under `talkcody.meta.*`
+    #[serde(default)]
+    pub metadata: Option<HashMap<String, serde_json::Value>>,
+    /// Re-send the request when the connection drops mid-stream, without
+    /// repeating text that was already emitted
+    #[serde(rename = "resumeOnDisconnect", default)]
+    pub resume_on_disconnect: bool,
+    /// Tool names the model may call; other tools are removed from the
+    /// request and any call to them is rejected
+    #[serde(rename = "allowedTools", default)]
+    pub allowed_tools: Option<Vec<String>>,
+    /// Emit `ToolCallDelta` events while tool-call arguments stream in
+    #[serde(rename = "streamToolCallDeltas", default)]
+    pub stream_tool_call_deltas: bool,
+    /// http(s) URL that receives a JSON summary once the stream ends
+    #[serde(rename = "completionWebhook", default)]
+    pub completion_webhook: Option<String>,
+    /// Chat session that the assistant message is written to as it streams
+    #[serde(rename = "persistToSession", default)]
+    pub persist_to_session: Option<String>,
+    /// Requests sharing a key while the first is streaming (or just finished)
+    /// are attached to its events instead of calling the provider again
+    #[serde(rename = "idempotencyKey", default)]
+    pub idempotency_key: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -137,6 +264,35 @@ pub struct StreamResponse {
     pub request_id: String,
 }
 
+/// The request `llm_stream_text` would send, with credential headers redacted
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RequestPreview {
+    pub provider_id: String,
+    pub model: String,
+    pub url: String,
+    pub headers: HashMap<String, String>,
+    pub body: serde_json::Value,
+}
+
+/// Why a provider is or is not usable with the current credentials
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub enum AvailabilityReason {
+    HasApiKey,
+    HasOAuth,
+    NoneAuth,
+    TalkCodyJwt,
+    /// Local provider (ollama/lmstudio) that has not been enabled
+    DisabledLocal,
+    MissingCredentials,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ProviderAvailability {
+    pub available: bool,
+    pub reason: AvailabilityReason,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "role", rename_all = "lowercase")]
 pub enum Message {
@@ -234,6 +390,16 @@ pub enum StreamEvent {
         #[serde(default)]
         provider_metadata: Option<serde_json::Value>,
     },
+    /// Raw argument text of a tool call as it streams in; the complete
+    /// `ToolCall` still follows. Only sent when `stream_tool_call_deltas` is set.
+    ToolCallDelta {
+        #[serde(rename = "toolCallId")]
+        tool_call_id: String,
+        #[serde(rename = "toolName")]
+        tool_name: Option<String>,
+        #[serde(rename = "argumentsDelta")]
+        arguments_delta: String,
+    },
     ReasoningStart {
         id: String,
         #[serde(default)]
@@ -248,6 +414,25 @@ pub enum StreamEvent {
     ReasoningEnd {
         id: String,
     },
+    /// Condensed reasoning summary, kept apart from the full/encrypted trace
+    ReasoningSummaryDelta {
+        id: String,
+        text: String,
+    },
+    /// A generated image output item has started
+    ImageStart {
+        id: String,
+    },
+    /// A base64-encoded image for the item. Each one is a complete image:
+    /// partial previews may precede the final image and are superseded by it.
+    ImageDelta {
+        id: String,
+        b64: String,
+        mime: String,
+    },
+    ImageEnd {
+        id: String,
+    },
     Usage {
         input_tokens: i32,
         output_tokens: i32,
@@ -260,12 +445,77 @@ pub enum StreamEvent {
     },
     Error {
         message: String,
+        /// Structured error parsed from the provider's response body, when available
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        provider_error: Option<ProviderError>,
+        /// Machine-readable failure category
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        kind: Option<StreamErrorKind>,
+    },
+    /// Emitted once when no content has arrived within the TTFT warning threshold.
+    /// Informational only; the stream keeps running.
+    SlowStart {
+        elapsed_ms: u64,
+    },
+    /// Best-effort parse of the JSON document assembled from text deltas so far.
+    /// Only emitted when requested via `partialJson`; text deltas still flow.
+    JsonPartial {
+        value: serde_json::Value,
+    },
+    /// Running output token estimate, only emitted when requested via `estimateUsage`.
+    /// `reconciled` marks the final value taken from the provider's `Usage`.
+    UsageDelta {
+        output_tokens_est: u32,
+        #[serde(default)]
+        reconciled: bool,
+    },
+    /// Token logprobs for the text streamed in the preceding chunk
+    Logprobs {
+        tokens: Vec<TokenLogprob>,
     },
     Raw {
         raw_value: String,
     },
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TokenLogprob {
+    pub token: String,
+    pub logprob: f64,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub top_logprobs: Vec<TopLogprob>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TopLogprob {
+    pub token: String,
+    pub logprob: f64,
+}
+
+/// Category of a failed stream, carried on `StreamEvent::Error`
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum StreamErrorKind {
+    Auth,
+    RateLimited,
+    Network,
+    Timeout,
+    Protocol,
+    Http,
+    Cancelled,
+    Config,
+}
+
+/// Error details reported by a provider, normalized from its JSON error envelope
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ProviderError {
+    pub message: String,
+    #[serde(rename = "type", default, skip_serializing_if = "Option::is_none")]
+    pub error_type: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub code: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TranscriptionRequest {
     pub model: String,
@@ -2,12 +2,13 @@
 // This module implements a temporary HTTP server to receive OAuth callbacks
 
 use serde::Serialize;
-use std::net::TcpListener;
+use std::collections::HashMap;
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex, OnceLock};
 use std::thread;
 use std::time::Duration;
 use tauri::Emitter;
+use tokio::sync::oneshot;
 
 /// OAuth callback result sent to frontend via Tauri event
 #[derive(Clone, Serialize)]
@@ -27,15 +28,22 @@ const SERVER_TIMEOUT_SECS: u64 = 300; // 5 minutes timeout
 const PORT_RANGE_START: u16 = 1455;
 const PORT_RANGE_END: u16 = 1475;
 
-/// Check if a port is available
-fn is_port_available(port: u16) -> bool {
-    TcpListener::bind(("127.0.0.1", port)).is_ok()
-}
-
-/// Find an available port starting from the default port
-/// Returns the first available port in the range [PORT_RANGE_START, PORT_RANGE_END]
-fn find_available_port() -> Option<u16> {
-    (PORT_RANGE_START..=PORT_RANGE_END).find(|&port| is_port_available(port))
+/// Bind the default port, or the first free port in the range
+/// [PORT_RANGE_START, PORT_RANGE_END] when it is taken
+fn bind_callback_server() -> Result<(tiny_http::Server, u16), String> {
+    std::iter::once(DEFAULT_PORT)
+        .chain(PORT_RANGE_START..=PORT_RANGE_END)
+        .find_map(|port| {
+            tiny_http::Server::http(("127.0.0.1", port))
+                .ok()
+                .map(|server| (server, port))
+        })
+        .ok_or_else(|| {
+            format!(
+                "All ports from {} to {} are in use. Please close other applications and try again, or use manual code entry instead.",
+                PORT_RANGE_START, PORT_RANGE_END
+            )
+        })
 }
 
 /// Generate success HTML page
@@ -259,74 +267,115 @@ pub async fn start_oauth_callback_server(
 ) -> Result<u16, String> {
     log::info!("Starting OAuth callback server...");
 
-    // Check if default port is available, otherwise try to find another port
-    let port = if is_port_available(DEFAULT_PORT) {
-        DEFAULT_PORT
-    } else {
-        // Try to find an available port in the fallback range
-        match find_available_port() {
-            Some(available_port) => available_port,
-            None => {
-                return Err(format!(
-                    "All ports from {} to {} are in use. Please close other applications and try again, or use manual code entry instead.",
-                    PORT_RANGE_START, PORT_RANGE_END
-                ));
+    let (server, port) = bind_callback_server()?;
+    spawn_callback_server(
+        server,
+        expected_state,
+        Duration::from_secs(SERVER_TIMEOUT_SECS),
+        move |result| {
+            // Emit result to frontend
+            if let Err(e) = window.emit("openai-oauth-callback", &result) {
+                log::error!("Failed to emit OAuth callback event: {:?}", e);
             }
-        }
-    };
+        },
+    );
+
+    log::info!("OAuth callback server started on port {}", port);
+    Ok(port)
+}
+
+/// Start a listener for the redirect of the OAuth flow started with `state`.
+/// The flow's `*_oauth_complete` command, called without a code, then waits
+/// for the code via `wait_for_callback_code`. Returns the port, which the
+/// flow's redirect URI has to use.
+#[tauri::command]
+pub async fn start_oauth_callback_listener(state: String) -> Result<u16, String> {
+    let (server, port) = bind_callback_server()?;
+    listen_for_callback(server, &state, Duration::from_secs(SERVER_TIMEOUT_SECS));
+    log::info!("OAuth callback listener started on port {}", port);
+    Ok(port)
+}
+
+/// Callback results of running listeners, keyed by the OAuth state they expect
+fn pending_callbacks() -> &'static Mutex<HashMap<String, oneshot::Receiver<OAuthCallbackResult>>> {
+    static PENDING: OnceLock<Mutex<HashMap<String, oneshot::Receiver<OAuthCallbackResult>>>> =
+        OnceLock::new();
+    PENDING.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn listen_for_callback(server: tiny_http::Server, state: &str, timeout: Duration) {
+    let (sender, receiver) = oneshot::channel();
+    if let Ok(mut pending) = pending_callbacks().lock() {
+        pending.insert(state.to_string(), receiver);
+    }
+    spawn_callback_server(server, Some(state.to_string()), timeout, move |result| {
+        let _ = sender.send(result);
+    });
+}
+
+/// Wait for the redirect captured by the listener started for `state` and
+/// return its authorization code
+pub async fn wait_for_callback_code(state: &str) -> Result<String, String> {
+    let receiver = pending_callbacks()
+        .lock()
+        .ok()
+        .and_then(|mut pending| pending.remove(state))
+        .ok_or_else(|| "No OAuth callback listener is waiting for this state".to_string())?;
+    let result = receiver
+        .await
+        .map_err(|_| "OAuth callback server stopped unexpectedly".to_string())?;
+    match result.code {
+        Some(code) if result.success => Ok(code),
+        _ => Err(result
+            .error
+            .unwrap_or_else(|| "OAuth callback failed".to_string())),
+    }
+}
+
+/// Serve callbacks on a background thread until one arrives or `timeout` passes,
+/// then hand the result to `on_result`
+fn spawn_callback_server<F>(
+    server: tiny_http::Server,
+    expected_state: Option<String>,
+    timeout: Duration,
+    on_result: F,
+) where
+    F: FnOnce(OAuthCallbackResult) + Send + 'static,
+{
     let shutdown_flag = Arc::new(AtomicBool::new(false));
     let shutdown_flag_clone = shutdown_flag.clone();
 
     // Spawn server in background thread
     thread::spawn(move || {
-        let result = run_callback_server(port, expected_state, shutdown_flag_clone);
-
-        // Emit result to frontend
-        if let Err(e) = window.emit("openai-oauth-callback", &result) {
-            log::error!("Failed to emit OAuth callback event: {:?}", e);
-        }
-
+        let result = run_callback_server(server, expected_state, shutdown_flag_clone);
+        on_result(result);
         log::info!("OAuth callback server stopped");
     });
 
     // Set up timeout auto-shutdown
     let shutdown_flag_timeout = shutdown_flag;
     thread::spawn(move || {
-        thread::sleep(Duration::from_secs(SERVER_TIMEOUT_SECS));
+        thread::sleep(timeout);
         if !shutdown_flag_timeout.load(Ordering::SeqCst) {
             log::info!(
                 "OAuth callback server timed out after {} seconds",
-                SERVER_TIMEOUT_SECS
+                timeout.as_secs()
             );
             shutdown_flag_timeout.store(true, Ordering::SeqCst);
         }
     });
-
-    log::info!("OAuth callback server started on port {}", port);
-    Ok(port)
 }
 
 /// Run the callback server (blocking)
 fn run_callback_server(
-    port: u16,
+    server: tiny_http::Server,
     expected_state: Option<String>,
     shutdown_flag: Arc<AtomicBool>,
 ) -> OAuthCallbackResult {
-    // Create server using tiny_http
-    let server = match tiny_http::Server::http(format!("127.0.0.1:{}", port)) {
-        Ok(s) => s,
-        Err(e) => {
-            log::error!("Failed to start HTTP server: {}", e);
-            return OAuthCallbackResult {
-                success: false,
-                code: None,
-                state: None,
-                error: Some(format!("Failed to start server: {}", e)),
-            };
-        }
-    };
-
-    log::info!("OAuth callback server listening on 127.0.0.1:{}", port);
+    log::info!(
+        "OAuth callback server listening on {:?}",
+        server.server_addr()
+    );
 
     loop {
         // Check shutdown flag
@@ -450,3 +499,108 @@ fn run_callback_server(
         };
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn local_server() -> (tiny_http::Server, u16) {
+        let server = tiny_http::Server::http("127.0.0.1:0").expect("server");
+        let port = match server.server_addr() {
+            tiny_http::ListenAddr::IP(socket_addr) => socket_addr.port(),
+            _ => panic!("Expected IP SocketAddr"),
+        };
+        (server, port)
+    }
+
+    #[tokio::test]
+    async fn listener_hands_code_to_waiting_flow() {
+        let (server, port) = local_server();
+        listen_for_callback(server, "state-123", Duration::from_secs(5));
+        let base = format!("http://127.0.0.1:{}", port);
+        let client = reqwest::Client::new();
+
+        let favicon = client
+            .get(format!("{}/favicon.ico", base))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(favicon.status().as_u16(), 404);
+
+        let response = client
+            .get(format!(
+                "{}/auth/callback?code=auth%2Dcode&state=state-123",
+                base
+            ))
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.status().as_u16(), 200);
+        assert!(response
+            .text()
+            .await
+            .unwrap()
+            .contains("Authorization Successful"));
+
+        let code = wait_for_callback_code("state-123").await.unwrap();
+        assert_eq!(code, "auth-code");
+    }
+
+    #[tokio::test]
+    async fn listener_shuts_down_after_one_callback() {
+        let (server, port) = local_server();
+        listen_for_callback(server, "state-once", Duration::from_secs(5));
+        let url = format!(
+            "http://127.0.0.1:{}/auth/callback?code=a&state=state-once",
+            port
+        );
+        let client = reqwest::Client::new();
+        client.get(&url).send().await.unwrap();
+        wait_for_callback_code("state-once").await.unwrap();
+        tokio::time::sleep(Duration::from_millis(100)).await;
+
+        assert!(client.get(&url).send().await.is_err());
+    }
+
+    #[tokio::test]
+    async fn listener_reports_missing_code() {
+        let (server, port) = local_server();
+        listen_for_callback(server, "state-denied", Duration::from_secs(5));
+        let url = format!(
+            "http://127.0.0.1:{}/auth/callback?error=access_denied&state=state-denied",
+            port
+        );
+        let response = reqwest::get(&url).await.unwrap();
+        assert_eq!(response.status().as_u16(), 400);
+        let err = wait_for_callback_code("state-denied").await.unwrap_err();
+        assert!(err.contains("No authorization code"));
+    }
+
+    #[tokio::test]
+    async fn listener_rejects_state_mismatch() {
+        let (server, port) = local_server();
+        listen_for_callback(server, "state-expected", Duration::from_secs(5));
+        let url = format!(
+            "http://127.0.0.1:{}/auth/callback?code=a&state=state-forged",
+            port
+        );
+        let response = reqwest::get(&url).await.unwrap();
+        assert_eq!(response.status().as_u16(), 400);
+        let err = wait_for_callback_code("state-expected").await.unwrap_err();
+        assert!(err.contains("State mismatch"));
+    }
+
+    #[tokio::test]
+    async fn listener_times_out() {
+        let (server, _) = local_server();
+        listen_for_callback(server, "state-timeout", Duration::from_millis(50));
+        let err = wait_for_callback_code("state-timeout").await.unwrap_err();
+        assert!(err.contains("timed out"));
+    }
+
+    #[tokio::test]
+    async fn waiting_without_listener_fails() {
+        let err = wait_for_callback_code("state-unknown").await.unwrap_err();
+        assert!(err.contains("No OAuth callback listener"));
+    }
+}
@@ -1,10 +1,20 @@
 //! Chat History Repository
 //! Handles CRUD operations for sessions, messages, and events in chat_history.db
 
-use crate::database::Database;
+use crate::database::{Database, Transaction};
 use crate::storage::models::*;
+use std::io::Write;
 use std::sync::Arc;
 
+/// Messages read per query when streaming an NDJSON export
+const EXPORT_BATCH_SIZE: usize = 500;
+
+/// Longest title derived from the first user message, in characters
+const AUTO_TITLE_MAX_CHARS: usize = 60;
+
+/// Titles sessions are created with before anything better is known
+const PLACEHOLDER_SESSION_TITLES: [&str; 2] = ["New Session", "New Chat"];
+
 /// Repository for chat history operations
 #[derive(Clone)]
 pub struct ChatHistoryRepository {
@@ -21,8 +31,8 @@ impl ChatHistoryRepository {
     /// Create a new session
     pub async fn create_session(&self, session: &Session) -> Result<(), String> {
         let sql = r#"
-            INSERT INTO sessions (id, project_id, title, status, created_at, updated_at, last_event_id, metadata)
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            INSERT INTO sessions (id, project_id, title, status, created_at, updated_at, pinned_at, last_event_id, metadata)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
         "#;
 
         self.db
@@ -35,6 +45,7 @@ impl ChatHistoryRepository {
                     serde_json::json!(session.status.as_str()),
                     serde_json::json!(session.created_at),
                     serde_json::json!(session.updated_at),
+                    serde_json::json!(session.pinned_at),
                     serde_json::json!(session.last_event_id),
                     serde_json::json!(session.metadata.as_ref().map(|m| m.to_string())),
                 ],
@@ -110,13 +121,177 @@ impl ChatHistoryRepository {
         Ok(())
     }
 
-    /// List sessions with optional filters
+    /// Title a session after its first user message when it has no real title yet.
+    /// Returns the new title, or `None` if the session kept its existing one.
+    pub async fn maybe_set_auto_title(&self, session_id: &str) -> Result<Option<String>, String> {
+        let session = self
+            .get_session(session_id)
+            .await?
+            .ok_or_else(|| format!("Session not found: {}", session_id))?;
+        let has_title = session.title.as_deref().is_some_and(|title| {
+            !title.trim().is_empty() && !PLACEHOLDER_SESSION_TITLES.contains(&title.trim())
+        });
+        if has_title {
+            return Ok(None);
+        }
+
+        let result = self
+            .db
+            .query(
+                "SELECT * FROM messages WHERE session_id = ? AND role = ? ORDER BY created_at ASC, id ASC LIMIT 1",
+                vec![
+                    serde_json::json!(session_id),
+                    serde_json::json!(MessageRole::User.as_str()),
+                ],
+            )
+            .await?;
+        let Some(row) = result.rows.first() else {
+            return Ok(None);
+        };
+        let title = match row_to_message(row)?.content {
+            MessageContent::Text { text } => derive_auto_title(&text),
+            _ => None,
+        };
+        let Some(title) = title else {
+            return Ok(None);
+        };
+
+        self.update_session_title(session_id, &title).await?;
+        Ok(Some(title))
+    }
+
+    /// Archive a session so it is hidden from default listings but recoverable
+    pub async fn archive_session(&self, session_id: &str) -> Result<(), String> {
+        let session = self
+            .get_session(session_id)
+            .await?
+            .ok_or_else(|| format!("Session not found: {}", session_id))?;
+        if session.status == SessionStatus::Archived {
+            return Ok(());
+        }
+
+        // Remember the status to return to on unarchive
+        self.db
+            .execute(
+                "UPDATE sessions SET metadata = json_set(COALESCE(metadata, '{}'), '$.archivedFromStatus', ?) WHERE id = ?",
+                vec![
+                    serde_json::json!(session.status.as_str()),
+                    serde_json::json!(session_id),
+                ],
+            )
+            .await?;
+
+        self.update_session_status(session_id, SessionStatus::Archived, None)
+            .await
+    }
+
+    /// Restore an archived session to the status it had before archiving
+    pub async fn unarchive_session(&self, session_id: &str) -> Result<(), String> {
+        let session = self
+            .get_session(session_id)
+            .await?
+            .ok_or_else(|| format!("Session not found: {}", session_id))?;
+        if session.status != SessionStatus::Archived {
+            return Ok(());
+        }
+
+        let previous = session
+            .metadata
+            .as_ref()
+            .and_then(|m| m.get("archivedFromStatus"))
+            .and_then(|v| v.as_str())
+            .and_then(|s| s.parse().ok())
+            .filter(|status| *status != SessionStatus::Archived)
+            .unwrap_or(SessionStatus::Completed);
+
+        self.db
+            .execute(
+                "UPDATE sessions SET metadata = json_remove(metadata, '$.archivedFromStatus') WHERE id = ? AND metadata IS NOT NULL",
+                vec![serde_json::json!(session_id)],
+            )
+            .await?;
+
+        self.update_session_status(session_id, previous, None).await
+    }
+
+    /// Pin a session so it is listed ahead of unpinned sessions
+    pub async fn pin_session(&self, session_id: &str) -> Result<(), String> {
+        let result = self
+            .db
+            .execute(
+                "UPDATE sessions SET pinned_at = ? WHERE id = ?",
+                vec![
+                    serde_json::json!(chrono::Utc::now().timestamp()),
+                    serde_json::json!(session_id),
+                ],
+            )
+            .await?;
+        if result.rows_affected == 0 {
+            return Err(format!("Session not found: {}", session_id));
+        }
+        Ok(())
+    }
+
+    pub async fn unpin_session(&self, session_id: &str) -> Result<(), String> {
+        self.db
+            .execute(
+                "UPDATE sessions SET pinned_at = NULL WHERE id = ?",
+                vec![serde_json::json!(session_id)],
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// Delete every session matching `filter` together with its messages, events
+    /// and other per-session rows in one transaction; returns the sessions deleted
+    pub async fn delete_sessions(&self, filter: SessionDeleteFilter) -> Result<u64, String> {
+        let (condition, param) = match &filter {
+            SessionDeleteFilter::ByProject(project_id) if project_id.trim().is_empty() => {
+                return Err("Refusing to delete sessions with an empty filter".to_string());
+            }
+            SessionDeleteFilter::ByProject(project_id) => {
+                ("project_id = ?", serde_json::json!(project_id))
+            }
+            SessionDeleteFilter::OlderThan(timestamp) => {
+                ("updated_at < ?", serde_json::json!(timestamp))
+            }
+            SessionDeleteFilter::ByStatus(status) => {
+                ("status = ?", serde_json::json!(status.as_str()))
+            }
+        };
+
+        self.db
+            .transaction(
+                |tx| async move { delete_sessions_cascade(&tx, condition, vec![param]).await },
+            )
+            .await
+    }
+
+    /// Permanently delete archived sessions last updated before `before_timestamp`
+    pub async fn purge_archived_before(&self, before_timestamp: i64) -> Result<u64, String> {
+        self.db
+            .transaction(|tx| async move {
+                delete_sessions_cascade(
+                    &tx,
+                    "status = 'archived' AND updated_at < ?",
+                    vec![serde_json::json!(before_timestamp)],
+                )
+                .await
+            })
+            .await
+    }
+
+    /// List sessions with optional filters; archived sessions are skipped unless
+    /// `include_archived` is set or the archived status is requested explicitly.
+    /// Pinned sessions come first (most recently pinned first), then the rest by recency.
     pub async fn list_sessions(
         &self,
         project_id: Option<&str>,
         status: Option<SessionStatus>,
         limit: Option<usize>,
         offset: Option<usize>,
+        include_archived: bool,
+        tags: Option<Vec<String>>,
     ) -> Result<Vec<Session>, String> {
         let mut sql = "SELECT * FROM sessions WHERE 1=1".to_string();
         let mut params: Vec<serde_json::Value> = vec![];
@@ -129,9 +304,25 @@ impl ChatHistoryRepository {
         if let Some(s) = status {
             sql.push_str(" AND status = ?");
             params.push(serde_json::json!(s.as_str()));
+        } else if !include_archived {
+            sql.push_str(" AND status != ?");
+            params.push(serde_json::json!(SessionStatus::Archived.as_str()));
         }
 
-        sql.push_str(" ORDER BY updated_at DESC");
+        let tags: Vec<String> = tags
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|tag| normalize_tag(tag))
+            .collect();
+        if !tags.is_empty() {
+            sql.push_str(&format!(
+                " AND id IN (SELECT session_id FROM session_tags WHERE tag IN ({}))",
+                vec!["?"; tags.len()].join(", ")
+            ));
+            params.extend(tags.iter().map(|tag| serde_json::json!(tag)));
+        }
+
+        sql.push_str(" ORDER BY pinned_at IS NULL, pinned_at DESC, updated_at DESC");
 
         if let Some(limit) = limit {
             sql.push_str(&format!(" LIMIT {}", limit));
@@ -146,6 +337,73 @@ impl ChatHistoryRepository {
         Ok(result.rows.iter().map(row_to_session).collect())
     }
 
+    /// Start a new session from the messages of `session_id` up to and including
+    /// `from_message_id`; copied messages get fresh ids with parent links remapped
+    pub async fn branch_session(
+        &self,
+        session_id: &str,
+        from_message_id: &str,
+    ) -> Result<Session, String> {
+        let source = self
+            .get_session(session_id)
+            .await?
+            .ok_or_else(|| format!("Session not found: {}", session_id))?;
+        let messages = self.get_messages(session_id, None, None).await?;
+        let branch_point = messages
+            .iter()
+            .position(|m| m.id == from_message_id)
+            .ok_or_else(|| {
+                format!(
+                    "Message {} not found in session {}",
+                    from_message_id, session_id
+                )
+            })?;
+
+        let now = chrono::Utc::now().timestamp();
+        let branch = Session {
+            id: uuid::Uuid::new_v4().to_string(),
+            project_id: source.project_id.clone(),
+            title: Some(format!(
+                "{} (branch)",
+                source.title.as_deref().unwrap_or("Untitled session")
+            )),
+            status: SessionStatus::Created,
+            created_at: now,
+            updated_at: now,
+            pinned_at: None,
+            last_event_id: None,
+            metadata: Some(serde_json::json!({
+                "branchedFrom": {
+                    "sessionId": session_id,
+                    "messageId": from_message_id,
+                }
+            })),
+        };
+        self.create_session(&branch).await?;
+
+        let mut id_map: std::collections::HashMap<String, String> =
+            std::collections::HashMap::new();
+        for message in &messages[..=branch_point] {
+            let new_id = uuid::Uuid::new_v4().to_string();
+            id_map.insert(message.id.clone(), new_id.clone());
+            let copy = Message {
+                id: new_id,
+                session_id: branch.id.clone(),
+                role: message.role,
+                content: message.content.clone(),
+                created_at: message.created_at,
+                tool_call_id: message.tool_call_id.clone(),
+                parent_id: message
+                    .parent_id
+                    .as_ref()
+                    .and_then(|parent| id_map.get(parent).cloned()),
+            };
+            self.create_message(&copy).await?;
+        }
+
+        Ok(branch)
+    }
+
     /// Delete a session and all related data
     pub async fn delete_session(&self, session_id: &str) -> Result<(), String> {
         self.db
@@ -157,38 +415,77 @@ impl ChatHistoryRepository {
         Ok(())
     }
 
-    // ============== Message Operations ==============
+    // ============== Tag Operations ==============
 
-    /// Create a new message
-    pub async fn create_message(&self, message: &Message) -> Result<(), String> {
-        let sql = r#"
-            INSERT INTO messages (id, session_id, role, content, created_at, tool_call_id, parent_id)
-            VALUES (?, ?, ?, ?, ?, ?, ?)
-        "#;
+    /// Tag a session; tags are trimmed and lowercased, and re-adding a tag is a no-op
+    pub async fn add_tag(&self, session_id: &str, tag: &str) -> Result<(), String> {
+        let tag = normalize_tag(tag).ok_or("Tag cannot be empty")?;
+        self.db
+            .execute(
+                "INSERT OR IGNORE INTO session_tags (session_id, tag) VALUES (?, ?)",
+                vec![serde_json::json!(session_id), serde_json::json!(tag)],
+            )
+            .await?;
+        Ok(())
+    }
 
+    /// Remove a tag from a session
+    pub async fn remove_tag(&self, session_id: &str, tag: &str) -> Result<(), String> {
+        let Some(tag) = normalize_tag(tag) else {
+            return Ok(());
+        };
         self.db
             .execute(
-                sql,
-                vec![
-                    serde_json::json!(message.id),
-                    serde_json::json!(message.session_id),
-                    serde_json::json!(message.role.as_str()),
-                    serde_json::json!(serde_json::to_string(&message.content).unwrap()),
-                    serde_json::json!(message.created_at),
-                    serde_json::json!(message.tool_call_id),
-                    serde_json::json!(message.parent_id),
-                ],
+                "DELETE FROM session_tags WHERE session_id = ? AND tag = ?",
+                vec![serde_json::json!(session_id), serde_json::json!(tag)],
             )
             .await?;
+        Ok(())
+    }
+
+    /// Tags on a session in alphabetical order
+    pub async fn list_tags(&self, session_id: &str) -> Result<Vec<String>, String> {
+        let result = self
+            .db
+            .query(
+                "SELECT tag FROM session_tags WHERE session_id = ? ORDER BY tag",
+                vec![serde_json::json!(session_id)],
+            )
+            .await?;
+
+        Ok(result
+            .rows
+            .iter()
+            .filter_map(|row| row.get("tag").and_then(|v| v.as_str()))
+            .map(|tag| tag.to_string())
+            .collect())
+    }
+
+    // ============== Usage Operations ==============
+
+    /// Add one stream's usage to the session's running totals
+    pub async fn add_usage(&self, session_id: &str, usage: &SessionUsage) -> Result<(), String> {
+        let sql = r#"
+            INSERT INTO session_usage (session_id, input_tokens, output_tokens, cached_tokens, cost_cents, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?)
+            ON CONFLICT(session_id) DO UPDATE SET
+                input_tokens = input_tokens + excluded.input_tokens,
+                output_tokens = output_tokens + excluded.output_tokens,
+                cached_tokens = cached_tokens + excluded.cached_tokens,
+                cost_cents = cost_cents + excluded.cost_cents,
+                updated_at = excluded.updated_at
+        "#;
 
-        // Update session's updated_at timestamp
-        let updated_at = chrono::Utc::now().timestamp();
         self.db
             .execute(
-                "UPDATE sessions SET updated_at = ? WHERE id = ?",
+                sql,
                 vec![
-                    serde_json::json!(updated_at),
-                    serde_json::json!(&message.session_id),
+                    serde_json::json!(session_id),
+                    serde_json::json!(usage.input_tokens),
+                    serde_json::json!(usage.output_tokens),
+                    serde_json::json!(usage.cached_tokens),
+                    serde_json::json!(usage.cost_cents),
+                    serde_json::json!(chrono::Utc::now().timestamp()),
                 ],
             )
             .await?;
@@ -196,38 +493,185 @@ impl ChatHistoryRepository {
         Ok(())
     }
 
-    /// Get messages for a session
+    /// Cumulative usage for a session; zero when nothing has been recorded
+    pub async fn get_session_usage(&self, session_id: &str) -> Result<SessionUsage, String> {
+        let result = self
+            .db
+            .query(
+                "SELECT * FROM session_usage WHERE session_id = ?",
+                vec![serde_json::json!(session_id)],
+            )
+            .await?;
+
+        Ok(result
+            .rows
+            .first()
+            .map(|row| SessionUsage {
+                input_tokens: row
+                    .get("input_tokens")
+                    .and_then(|v| v.as_i64())
+                    .unwrap_or(0),
+                output_tokens: row
+                    .get("output_tokens")
+                    .and_then(|v| v.as_i64())
+                    .unwrap_or(0),
+                cached_tokens: row
+                    .get("cached_tokens")
+                    .and_then(|v| v.as_i64())
+                    .unwrap_or(0),
+                cost_cents: row
+                    .get("cost_cents")
+                    .and_then(|v| v.as_f64())
+                    .unwrap_or(0.0),
+            })
+            .unwrap_or_default())
+    }
+
+    // ============== Message Operations ==============
+
+    /// Create a new message and bump the session's `updated_at` atomically
+    pub async fn create_message(&self, message: &Message) -> Result<(), String> {
+        let sql = r#"
+            INSERT INTO messages (id, session_id, role, content, created_at, tool_call_id, parent_id)
+            VALUES (?, ?, ?, ?, ?, ?, ?)
+        "#;
+        let content = serde_json::to_string(&message.content)
+            .map_err(|e| format!("Failed to serialize message content: {}", e))?;
+
+        self.db
+            .transaction(|tx| async move {
+                tx.execute(
+                    sql,
+                    vec![
+                        serde_json::json!(message.id),
+                        serde_json::json!(message.session_id),
+                        serde_json::json!(message.role.as_str()),
+                        serde_json::json!(content),
+                        serde_json::json!(message.created_at),
+                        serde_json::json!(message.tool_call_id),
+                        serde_json::json!(message.parent_id),
+                    ],
+                )
+                .await?;
+
+                // Update session's updated_at timestamp
+                let updated_at = chrono::Utc::now().timestamp();
+                tx.execute(
+                    "UPDATE sessions SET updated_at = ? WHERE id = ?",
+                    vec![
+                        serde_json::json!(updated_at),
+                        serde_json::json!(&message.session_id),
+                    ],
+                )
+                .await?;
+
+                Ok(())
+            })
+            .await
+    }
+
+    /// Insert a message, or replace the content of the message with the same id.
+    /// Used to persist a message that is still being streamed.
+    pub async fn upsert_message(&self, message: &Message) -> Result<(), String> {
+        let sql = r#"
+            INSERT INTO messages (id, session_id, role, content, created_at, tool_call_id, parent_id)
+            VALUES (?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(id) DO UPDATE SET content = excluded.content
+        "#;
+        let content = serde_json::to_string(&message.content)
+            .map_err(|e| format!("Failed to serialize message content: {}", e))?;
+
+        self.db
+            .transaction(|tx| async move {
+                tx.execute(
+                    sql,
+                    vec![
+                        serde_json::json!(message.id),
+                        serde_json::json!(message.session_id),
+                        serde_json::json!(message.role.as_str()),
+                        serde_json::json!(content),
+                        serde_json::json!(message.created_at),
+                        serde_json::json!(message.tool_call_id),
+                        serde_json::json!(message.parent_id),
+                    ],
+                )
+                .await?;
+
+                let updated_at = chrono::Utc::now().timestamp();
+                tx.execute(
+                    "UPDATE sessions SET updated_at = ? WHERE id = ?",
+                    vec![
+                        serde_json::json!(updated_at),
+                        serde_json::json!(&message.session_id),
+                    ],
+                )
+                .await?;
+
+                Ok(())
+            })
+            .await
+    }
+
+    /// Get messages for a session, newest `limit` before `before_id`, in chronological order
     pub async fn get_messages(
         &self,
         session_id: &str,
         limit: Option<usize>,
         before_id: Option<&str>,
     ) -> Result<Vec<Message>, String> {
+        let before = match before_id {
+            Some(id) => self.row_position("messages", id).await?,
+            None => None,
+        };
+        let (messages, _) = self.fetch_messages(session_id, limit, before).await?;
+        Ok(messages)
+    }
+
+    /// Page backwards through a session's messages. Each page is in chronological
+    /// order; pass `next_cursor` back in to get the page of older messages.
+    pub async fn get_messages_page(
+        &self,
+        session_id: &str,
+        limit: usize,
+        cursor: Option<&str>,
+    ) -> Result<Page<Message>, String> {
+        let before = cursor.map(decode_cursor).transpose()?;
+        let (messages, has_more) = self
+            .fetch_messages(session_id, Some(limit.max(1)), before)
+            .await?;
+        let next_cursor = if has_more {
+            messages.first().map(|m| encode_cursor(m.created_at, &m.id))
+        } else {
+            None
+        };
+        Ok(Page {
+            items: messages,
+            next_cursor,
+        })
+    }
+
+    /// Newest messages strictly before `before` in `(created_at, id)` order,
+    /// returned oldest first along with whether older messages remain
+    async fn fetch_messages(
+        &self,
+        session_id: &str,
+        limit: Option<usize>,
+        before: Option<(i64, String)>,
+    ) -> Result<(Vec<Message>, bool), String> {
         let mut sql = "SELECT * FROM messages WHERE session_id = ?".to_string();
         let mut params: Vec<serde_json::Value> = vec![serde_json::json!(session_id)];
 
-        if let Some(before) = before_id {
-            // Get created_at of the before message
-            let before_result = self
-                .db
-                .query(
-                    "SELECT created_at FROM messages WHERE id = ?",
-                    vec![serde_json::json!(before)],
-                )
-                .await?;
-
-            if let Some(row) = before_result.rows.first() {
-                if let Some(created_at) = row.get("created_at").and_then(|v| v.as_i64()) {
-                    sql.push_str(" AND created_at < ?");
-                    params.push(serde_json::json!(created_at));
-                }
-            }
+        if let Some((created_at, id)) = before {
+            sql.push_str(" AND (created_at, id) < (?, ?)");
+            params.push(serde_json::json!(created_at));
+            params.push(serde_json::json!(id));
         }
 
-        sql.push_str(" ORDER BY created_at DESC");
+        sql.push_str(" ORDER BY created_at DESC, id DESC");
 
+        // Fetch one extra row to learn whether another page exists
         if let Some(limit) = limit {
-            sql.push_str(&format!(" LIMIT {}", limit));
+            sql.push_str(&format!(" LIMIT {}", limit + 1));
         }
 
         let result = self.db.query(&sql, params).await?;
@@ -237,10 +681,112 @@ impl ChatHistoryRepository {
             .iter()
             .map(row_to_message)
             .collect::<Result<Vec<_>, _>>()?;
+        let has_more = limit.is_some_and(|limit| messages.len() > limit);
+        if let Some(limit) = limit {
+            messages.truncate(limit);
+        }
 
         // Reverse to get chronological order
         messages.reverse();
-        Ok(messages)
+        Ok((messages, has_more))
+    }
+
+    /// Look up the `(created_at, id)` sort key of a message or event row
+    async fn row_position(&self, table: &str, id: &str) -> Result<Option<(i64, String)>, String> {
+        let result = self
+            .db
+            .query(
+                &format!("SELECT created_at FROM {} WHERE id = ?", table),
+                vec![serde_json::json!(id)],
+            )
+            .await?;
+
+        Ok(result
+            .rows
+            .first()
+            .and_then(|row| row.get("created_at"))
+            .and_then(|v| v.as_i64())
+            .map(|created_at| (created_at, id.to_string())))
+    }
+
+    /// Replace a message's content, keeping the previous content as a revision
+    pub async fn edit_message(
+        &self,
+        message_id: &str,
+        new_content: &MessageContent,
+    ) -> Result<(), String> {
+        let result = self
+            .db
+            .query(
+                "SELECT session_id, content FROM messages WHERE id = ?",
+                vec![serde_json::json!(message_id)],
+            )
+            .await?;
+        let row = result
+            .rows
+            .first()
+            .ok_or_else(|| format!("Message not found: {}", message_id))?;
+        let session_id = row
+            .get("session_id")
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .to_string();
+        let previous_content = row
+            .get("content")
+            .and_then(|v| v.as_str())
+            .ok_or("Missing content field")?
+            .to_string();
+
+        let edited_at = chrono::Utc::now().timestamp();
+        self.db
+            .execute(
+                "INSERT INTO message_revisions (id, message_id, content, edited_at) VALUES (?, ?, ?, ?)",
+                vec![
+                    serde_json::json!(uuid::Uuid::new_v4().to_string()),
+                    serde_json::json!(message_id),
+                    serde_json::json!(previous_content),
+                    serde_json::json!(edited_at),
+                ],
+            )
+            .await?;
+
+        let content = serde_json::to_string(new_content)
+            .map_err(|e| format!("Failed to serialize message content: {}", e))?;
+        self.db
+            .execute(
+                "UPDATE messages SET content = ? WHERE id = ?",
+                vec![serde_json::json!(content), serde_json::json!(message_id)],
+            )
+            .await?;
+
+        self.db
+            .execute(
+                "UPDATE sessions SET updated_at = ? WHERE id = ?",
+                vec![serde_json::json!(edited_at), serde_json::json!(session_id)],
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    /// Get the prior contents of a message, oldest first
+    pub async fn get_message_revisions(
+        &self,
+        message_id: &str,
+    ) -> Result<Vec<MessageRevision>, String> {
+        let result = self
+            .db
+            .query(
+                "SELECT * FROM message_revisions WHERE message_id = ? ORDER BY edited_at ASC, rowid ASC",
+                vec![serde_json::json!(message_id)],
+            )
+            .await?;
+
+        result
+            .rows
+            .iter()
+            .map(row_to_revision)
+            .collect::<Result<Vec<_>, _>>()
     }
 
     /// Delete all messages for a session
@@ -254,6 +800,153 @@ impl ChatHistoryRepository {
         Ok(())
     }
 
+    /// Full-text search over message text across sessions, best matches first
+    pub async fn search_messages(
+        &self,
+        query: &str,
+        project_id: Option<&str>,
+        limit: usize,
+    ) -> Result<Vec<MessageSearchHit>, String> {
+        let Some(match_expr) = build_fts_query(query) else {
+            return Ok(Vec::new());
+        };
+
+        let mut sql = r#"
+            SELECT messages_fts.session_id AS session_id,
+                   messages_fts.message_id AS message_id,
+                   snippet(messages_fts, 2, '[', ']', '…', 12) AS snippet
+            FROM messages_fts
+            JOIN sessions ON sessions.id = messages_fts.session_id
+            WHERE messages_fts MATCH ?
+        "#
+        .to_string();
+        let mut params: Vec<serde_json::Value> = vec![serde_json::json!(match_expr)];
+
+        if let Some(pid) = project_id {
+            sql.push_str(" AND sessions.project_id = ?");
+            params.push(serde_json::json!(pid));
+        }
+
+        sql.push_str(&format!(" ORDER BY messages_fts.rank LIMIT {}", limit));
+
+        let result = self.db.query(&sql, params).await?;
+
+        Ok(result
+            .rows
+            .iter()
+            .map(|row| MessageSearchHit {
+                session_id: row
+                    .get("session_id")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("")
+                    .to_string(),
+                message_id: row
+                    .get("message_id")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("")
+                    .to_string(),
+                snippet: row
+                    .get("snippet")
+                    .and_then(|v| v.as_str())
+                    .unwrap_or("")
+                    .to_string(),
+            })
+            .collect())
+    }
+
+    /// Export a session with its messages in chronological order
+    pub async fn export_session(
+        &self,
+        session_id: &str,
+        format: ExportFormat,
+    ) -> Result<String, String> {
+        let session = self
+            .get_session(session_id)
+            .await?
+            .ok_or_else(|| format!("Session not found: {}", session_id))?;
+        let messages = self.get_messages(session_id, None, None).await?;
+
+        match format {
+            ExportFormat::Markdown => Ok(render_session_markdown(&session, &messages)),
+            ExportFormat::Json => serde_json::to_string_pretty(&serde_json::json!({
+                "session": session,
+                "messages": messages,
+            }))
+            .map_err(|e| format!("Failed to serialize session export: {}", e)),
+        }
+    }
+
+    /// Stream a session's messages as NDJSON, one message object per line in
+    /// chronological order. Returns the number of messages written.
+    pub async fn export_session_ndjson<W: Write>(
+        &self,
+        session_id: &str,
+        writer: W,
+    ) -> Result<u64, String> {
+        self.export_session_ndjson_batched(session_id, writer, EXPORT_BATCH_SIZE)
+            .await
+    }
+
+    async fn export_session_ndjson_batched<W: Write>(
+        &self,
+        session_id: &str,
+        mut writer: W,
+        batch_size: usize,
+    ) -> Result<u64, String> {
+        if self.get_session(session_id).await?.is_none() {
+            return Err(format!("Session not found: {}", session_id));
+        }
+
+        let mut written = 0u64;
+        let mut after: Option<(i64, String)> = None;
+        loop {
+            let batch = self
+                .fetch_messages_after(session_id, after.take(), batch_size.max(1))
+                .await?;
+            for message in &batch {
+                serde_json::to_writer(&mut writer, message)
+                    .map_err(|e| format!("Failed to write message {}: {}", message.id, e))?;
+                writer
+                    .write_all(b"\n")
+                    .map_err(|e| format!("Failed to write export: {}", e))?;
+                written += 1;
+            }
+            match batch.last() {
+                Some(last) if batch.len() >= batch_size.max(1) => {
+                    after = Some((last.created_at, last.id.clone()));
+                }
+                _ => break,
+            }
+        }
+
+        writer
+            .flush()
+            .map_err(|e| format!("Failed to write export: {}", e))?;
+        Ok(written)
+    }
+
+    /// Up to `limit` messages strictly after `after` in `(created_at, id)` order
+    async fn fetch_messages_after(
+        &self,
+        session_id: &str,
+        after: Option<(i64, String)>,
+        limit: usize,
+    ) -> Result<Vec<Message>, String> {
+        let mut sql = "SELECT * FROM messages WHERE session_id = ?".to_string();
+        let mut params: Vec<serde_json::Value> = vec![serde_json::json!(session_id)];
+
+        if let Some((created_at, id)) = after {
+            sql.push_str(" AND (created_at, id) > (?, ?)");
+            params.push(serde_json::json!(created_at));
+            params.push(serde_json::json!(id));
+        }
+
+        sql.push_str(&format!(" ORDER BY created_at ASC, id ASC LIMIT {}", limit));
+
+        let result = self.db.query(&sql, params).await?;
+        result.rows.iter().map(row_to_message).collect()
+    }
+
     // ============== Event Operations ==============
 
     /// Create a new event
@@ -286,40 +979,72 @@ impl ChatHistoryRepository {
         after_event_id: Option<&str>,
         limit: Option<usize>,
     ) -> Result<Vec<SessionEvent>, String> {
+        let after = match after_event_id {
+            Some(id) => self.row_position("events", id).await?,
+            None => None,
+        };
+        let (events, _) = self.fetch_events(session_id, after, limit).await?;
+        Ok(events)
+    }
+
+    /// Page forwards through a session's events; pass `next_cursor` back in to
+    /// continue after the last event of this page
+    pub async fn get_events_page(
+        &self,
+        session_id: &str,
+        limit: usize,
+        cursor: Option<&str>,
+    ) -> Result<Page<SessionEvent>, String> {
+        let after = cursor.map(decode_cursor).transpose()?;
+        let (events, has_more) = self
+            .fetch_events(session_id, after, Some(limit.max(1)))
+            .await?;
+        let next_cursor = if has_more {
+            events.last().map(|e| encode_cursor(e.created_at, &e.id))
+        } else {
+            None
+        };
+        Ok(Page {
+            items: events,
+            next_cursor,
+        })
+    }
+
+    /// Events strictly after `after` in `(created_at, id)` order, along with
+    /// whether more events remain
+    async fn fetch_events(
+        &self,
+        session_id: &str,
+        after: Option<(i64, String)>,
+        limit: Option<usize>,
+    ) -> Result<(Vec<SessionEvent>, bool), String> {
         let mut sql = "SELECT * FROM events WHERE session_id = ?".to_string();
         let mut params: Vec<serde_json::Value> = vec![serde_json::json!(session_id)];
 
-        if let Some(after_id) = after_event_id {
-            // Get created_at of the after event
-            let after_result = self
-                .db
-                .query(
-                    "SELECT created_at FROM events WHERE id = ?",
-                    vec![serde_json::json!(after_id)],
-                )
-                .await?;
-
-            if let Some(row) = after_result.rows.first() {
-                if let Some(created_at) = row.get("created_at").and_then(|v| v.as_i64()) {
-                    sql.push_str(" AND created_at > ?");
-                    params.push(serde_json::json!(created_at));
-                }
-            }
+        if let Some((created_at, id)) = after {
+            sql.push_str(" AND (created_at, id) > (?, ?)");
+            params.push(serde_json::json!(created_at));
+            params.push(serde_json::json!(id));
         }
 
-        sql.push_str(" ORDER BY created_at ASC");
+        sql.push_str(" ORDER BY created_at ASC, id ASC");
 
         if let Some(limit) = limit {
-            sql.push_str(&format!(" LIMIT {}", limit));
+            sql.push_str(&format!(" LIMIT {}", limit + 1));
         }
 
         let result = self.db.query(&sql, params).await?;
 
-        result
+        let mut events = result
             .rows
             .iter()
             .map(row_to_event)
-            .collect::<Result<Vec<_>, _>>()
+            .collect::<Result<Vec<_>, _>>()?;
+        let has_more = limit.is_some_and(|limit| events.len() > limit);
+        if let Some(limit) = limit {
+            events.truncate(limit);
+        }
+        Ok((events, has_more))
     }
 
     /// Delete old events for a session (cleanup)
@@ -343,6 +1068,144 @@ impl ChatHistoryRepository {
     }
 }
 
+/// Quote each search term so user input is matched literally rather than parsed
+/// as FTS5 syntax; terms are ANDed together
+fn build_fts_query(query: &str) -> Option<String> {
+    let terms: Vec<String> = query
+        .split_whitespace()
+        .map(|term| format!("\"{}\"", term.replace('"', "\"\"")))
+        .collect();
+    if terms.is_empty() {
+        None
+    } else {
+        Some(terms.join(" "))
+    }
+}
+
+/// Single-line title from message text, cut at `AUTO_TITLE_MAX_CHARS`
+fn derive_auto_title(text: &str) -> Option<String> {
+    let collapsed = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    if collapsed.is_empty() {
+        return None;
+    }
+    if collapsed.chars().count() <= AUTO_TITLE_MAX_CHARS {
+        return Some(collapsed);
+    }
+    let truncated: String = collapsed.chars().take(AUTO_TITLE_MAX_CHARS).collect();
+    Some(format!("{}...", truncated.trim_end()))
+}
+
+fn normalize_tag(tag: &str) -> Option<String> {
+    let tag = tag.trim().to_lowercase();
+    (!tag.is_empty()).then_some(tag)
+}
+
+// ============== Cursors ==============
+
+/// Encode a `(created_at, id)` sort key as an opaque pagination cursor
+fn encode_cursor(created_at: i64, id: &str) -> String {
+    use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+    URL_SAFE_NO_PAD.encode(format!("{}:{}", created_at, id))
+}
+
+fn decode_cursor(cursor: &str) -> Result<(i64, String), String> {
+    use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+    let decoded = URL_SAFE_NO_PAD
+        .decode(cursor)
+        .ok()
+        .and_then(|bytes| String::from_utf8(bytes).ok())
+        .ok_or_else(|| format!("Invalid cursor: {}", cursor))?;
+    let (created_at, id) = decoded
+        .split_once(':')
+        .ok_or_else(|| format!("Invalid cursor: {}", cursor))?;
+    let created_at = created_at
+        .parse()
+        .map_err(|_| format!("Invalid cursor: {}", cursor))?;
+    Ok((created_at, id.to_string()))
+}
+
+// ============== Export ==============
+
+fn format_timestamp(timestamp: i64) -> String {
+    chrono::DateTime::from_timestamp(timestamp, 0)
+        .map(|dt| dt.to_rfc3339())
+        .unwrap_or_else(|| timestamp.to_string())
+}
+
+fn role_header(role: MessageRole) -> &'static str {
+    match role {
+        MessageRole::User => "User",
+        MessageRole::Assistant => "Assistant",
+        MessageRole::System => "System",
+        MessageRole::Tool => "Tool",
+    }
+}
+
+fn pretty_json(value: &serde_json::Value) -> String {
+    serde_json::to_string_pretty(value).unwrap_or_else(|_| value.to_string())
+}
+
+fn render_session_markdown(session: &Session, messages: &[Message]) -> String {
+    let mut out = format!(
+        "# {}\n\n",
+        session.title.as_deref().unwrap_or("Untitled session")
+    );
+    out.push_str(&format!("- Session: `{}`\n", session.id));
+    if let Some(project_id) = &session.project_id {
+        out.push_str(&format!("- Project: `{}`\n", project_id));
+    }
+    out.push_str(&format!(
+        "- Created: {}\n",
+        format_timestamp(session.created_at)
+    ));
+    out.push_str(&format!(
+        "- Updated: {}\n",
+        format_timestamp(session.updated_at)
+    ));
+
+    for message in messages {
+        out.push_str(&format!(
+            "\n## {}\n\n_{}_\n\n",
+            role_header(message.role),
+            format_timestamp(message.created_at)
+        ));
+        match &message.content {
+            MessageContent::Text { text } => {
+                out.push_str(text.trim_end());
+                out.push('\n');
+            }
+            MessageContent::ToolCalls { calls } => {
+                for call in calls {
+                    out.push_str(&format!(
+                        "Tool call `{}` (`{}`):\n\n```json\n{}\n```\n",
+                        call.name,
+                        call.id,
+                        pretty_json(&call.input)
+                    ));
+                }
+            }
+            MessageContent::ToolResult { result } => {
+                let status = match result.status {
+                    ToolResultStatus::Success => "success",
+                    ToolResultStatus::Error => "error",
+                };
+                out.push_str(&format!(
+                    "Tool result `{}` (`{}`, {}):\n\n",
+                    result.tool_name, result.tool_call_id, status
+                ));
+                if let Some(output) = &result.output {
+                    out.push_str(&format!("```json\n{}\n```\n", pretty_json(output)));
+                }
+                if let Some(error) = &result.error_message {
+                    out.push_str(&format!("\n```\n{}\n```\n", error));
+                }
+            }
+        }
+    }
+
+    out
+}
+
 // ============== Row Conversions ==============
 
 fn row_to_session(row: &serde_json::Value) -> Session {
@@ -367,6 +1230,7 @@ fn row_to_session(row: &serde_json::Value) -> Session {
             .unwrap_or(SessionStatus::Created),
         created_at: row.get("created_at").and_then(|v| v.as_i64()).unwrap_or(0),
         updated_at: row.get("updated_at").and_then(|v| v.as_i64()).unwrap_or(0),
+        pinned_at: row.get("pinned_at").and_then(|v| v.as_i64()),
         last_event_id: row
             .get("last_event_id")
             .and_then(|v| v.as_str())
@@ -416,6 +1280,31 @@ fn row_to_message(row: &serde_json::Value) -> Result<Message, String> {
     })
 }
 
+fn row_to_revision(row: &serde_json::Value) -> Result<MessageRevision, String> {
+    let content_str = row
+        .get("content")
+        .and_then(|v| v.as_str())
+        .ok_or("Missing content field")?;
+
+    let content: MessageContent = serde_json::from_str(content_str)
+        .map_err(|e| format!("Failed to parse revision content: {}", e))?;
+
+    Ok(MessageRevision {
+        id: row
+            .get("id")
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .to_string(),
+        message_id: row
+            .get("message_id")
+            .and_then(|v| v.as_str())
+            .unwrap_or("")
+            .to_string(),
+        content,
+        edited_at: row.get("edited_at").and_then(|v| v.as_i64()).unwrap_or(0),
+    })
+}
+
 fn row_to_event(row: &serde_json::Value) -> Result<SessionEvent, String> {
     let payload_str = row
         .get("payload")
@@ -446,6 +1335,42 @@ fn row_to_event(row: &serde_json::Value) -> Result<SessionEvent, String> {
     })
 }
 
+/// Delete the sessions matching `condition` (a `sessions` WHERE clause taking
+/// `params`) with every per-session row. Foreign keys are not enforced on these
+/// connections, so the cascade is spelled out; runs in the caller's transaction.
+async fn delete_sessions_cascade(
+    tx: &Transaction,
+    condition: &str,
+    params: Vec<serde_json::Value>,
+) -> Result<u64, String> {
+    let matching = format!("SELECT id FROM sessions WHERE {}", condition);
+    tx.execute(
+        &format!(
+            "DELETE FROM message_revisions WHERE message_id IN (SELECT id FROM messages WHERE session_id IN ({}))",
+            matching
+        ),
+        params.clone(),
+    )
+    .await?;
+    for table in [
+        "messages",
+        "events",
+        "attachments",
+        "session_tags",
+        "session_usage",
+    ] {
+        tx.execute(
+            &format!("DELETE FROM {} WHERE session_id IN ({})", table, matching),
+            params.clone(),
+        )
+        .await?;
+    }
+    let result = tx
+        .execute(&format!("DELETE FROM sessions WHERE {}", condition), params)
+        .await?;
+    Ok(result.rows_affected)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -480,6 +1405,7 @@ mod tests {
             status: SessionStatus::Created,
             created_at: chrono::Utc::now().timestamp(),
             updated_at: chrono::Utc::now().timestamp(),
+            pinned_at: None,
             last_event_id: None,
             metadata: Some(serde_json::json!({"key": "value"})),
         };
@@ -512,6 +1438,7 @@ mod tests {
             status: SessionStatus::Created,
             created_at: chrono::Utc::now().timestamp(),
             updated_at: chrono::Utc::now().timestamp(),
+            pinned_at: None,
             last_event_id: None,
             metadata: None,
         };
@@ -543,6 +1470,7 @@ mod tests {
             status: SessionStatus::Created,
             created_at: chrono::Utc::now().timestamp(),
             updated_at: chrono::Utc::now().timestamp(),
+            pinned_at: None,
             last_event_id: None,
             metadata: None,
         };
@@ -573,4 +1501,882 @@ mod tests {
         assert_eq!(messages.len(), 1);
         assert_eq!(messages[0].id, "msg-1");
     }
+
+    async fn create_session_with_messages(
+        repo: &ChatHistoryRepository,
+        session_id: &str,
+        project_id: Option<&str>,
+        texts: &[&str],
+    ) {
+        let now = chrono::Utc::now().timestamp();
+        let session = Session {
+            id: session_id.to_string(),
+            project_id: project_id.map(|p| p.to_string()),
+            title: None,
+            status: SessionStatus::Created,
+            created_at: now,
+            updated_at: now,
+            pinned_at: None,
+            last_event_id: None,
+            metadata: None,
+        };
+        repo.create_session(&session)
+            .await
+            .expect("Failed to create session");
+
+        for (index, text) in texts.iter().enumerate() {
+            let message = Message {
+                id: format!("{}-msg-{}", session_id, index),
+                session_id: session_id.to_string(),
+                role: MessageRole::User,
+                content: MessageContent::Text {
+                    text: text.to_string(),
+                },
+                created_at: now + index as i64,
+                tool_call_id: None,
+                parent_id: None,
+            };
+            repo.create_message(&message)
+                .await
+                .expect("Failed to create message");
+        }
+    }
+
+    #[tokio::test]
+    async fn test_search_messages_across_sessions() {
+        let (db, _temp) = create_test_db().await;
+        let repo = ChatHistoryRepository::new(db);
+
+        create_session_with_messages(
+            &repo,
+            "search-1",
+            Some("project-a"),
+            &["Refactor the tokenizer module", "Looks good"],
+        )
+        .await;
+        create_session_with_messages(
+            &repo,
+            "search-2",
+            Some("project-b"),
+            &["Why does the tokenizer panic?"],
+        )
+        .await;
+
+        let hits = repo
+            .search_messages("tokenizer", None, 10)
+            .await
+            .expect("Failed to search messages");
+        let mut ids: Vec<&str> = hits.iter().map(|h| h.message_id.as_str()).collect();
+        ids.sort();
+        assert_eq!(ids, vec!["search-1-msg-0", "search-2-msg-0"]);
+        // Snippets come from the extracted text, not the serialized JSON
+        assert!(hits.iter().all(|h| h.snippet.contains("[tokenizer]")));
+        assert!(hits.iter().all(|h| !h.snippet.contains("\"type\"")));
+
+        let scoped = repo
+            .search_messages("tokenizer", Some("project-b"), 10)
+            .await
+            .expect("Failed to search messages");
+        assert_eq!(scoped.len(), 1);
+        assert_eq!(scoped[0].session_id, "search-2");
+        assert_eq!(scoped[0].message_id, "search-2-msg-0");
+    }
+
+    #[tokio::test]
+    async fn test_search_messages_handles_deletes_and_special_characters() {
+        let (db, _temp) = create_test_db().await;
+        let repo = ChatHistoryRepository::new(db);
+
+        create_session_with_messages(&repo, "search-3", None, &["fix \"foo-bar\" OR crash"]).await;
+
+        let hits = repo
+            .search_messages("foo-bar OR", None, 10)
+            .await
+            .expect("Special characters should not break the query");
+        assert_eq!(hits.len(), 1);
+        assert!(repo
+            .search_messages("  ", None, 10)
+            .await
+            .unwrap()
+            .is_empty());
+
+        repo.delete_messages("search-3").await.unwrap();
+        assert!(repo
+            .search_messages("crash", None, 10)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_export_session_markdown_and_json() {
+        let (db, _temp) = create_test_db().await;
+        let repo = ChatHistoryRepository::new(db);
+
+        let now = chrono::Utc::now().timestamp();
+        let session = Session {
+            id: "export-1".to_string(),
+            project_id: Some("project-1".to_string()),
+            title: Some("Fix the build".to_string()),
+            status: SessionStatus::Completed,
+            created_at: now,
+            updated_at: now,
+            pinned_at: None,
+            last_event_id: None,
+            metadata: None,
+        };
+        repo.create_session(&session)
+            .await
+            .expect("Failed to create session");
+
+        let contents = vec![
+            (
+                MessageRole::User,
+                MessageContent::Text {
+                    text: "Why does cargo build fail?".to_string(),
+                },
+            ),
+            (
+                MessageRole::Assistant,
+                MessageContent::ToolCalls {
+                    calls: vec![ToolCall {
+                        id: "call-1".to_string(),
+                        name: "bash".to_string(),
+                        input: serde_json::json!({"command": "cargo build"}),
+                    }],
+                },
+            ),
+            (
+                MessageRole::Tool,
+                MessageContent::ToolResult {
+                    result: StoredToolResult {
+                        tool_call_id: "call-1".to_string(),
+                        tool_name: "bash".to_string(),
+                        input: None,
+                        output: Some(serde_json::json!("error[E0425]")),
+                        status: ToolResultStatus::Error,
+                        error_message: None,
+                    },
+                },
+            ),
+        ];
+        for (index, (role, content)) in contents.into_iter().enumerate() {
+            let message = Message {
+                id: format!("export-msg-{}", index),
+                session_id: "export-1".to_string(),
+                role,
+                content,
+                created_at: now + index as i64,
+                tool_call_id: None,
+                parent_id: None,
+            };
+            repo.create_message(&message)
+                .await
+                .expect("Failed to create message");
+        }
+
+        let markdown = repo
+            .export_session("export-1", ExportFormat::Markdown)
+            .await
+            .expect("Failed to export markdown");
+        assert!(markdown.starts_with("# Fix the build\n"));
+        assert!(markdown.contains("- Created: "));
+        assert!(markdown.contains("## User\n"));
+        assert!(markdown.contains("## Assistant\n"));
+        assert!(markdown.contains("## Tool\n"));
+        assert!(markdown.contains("Why does cargo build fail?"));
+        assert!(markdown.contains("Tool call `bash` (`call-1`):\n\n```json\n"));
+        assert!(markdown.contains("\"command\": \"cargo build\""));
+        assert!(markdown.contains("Tool result `bash` (`call-1`, error)"));
+        assert!(
+            markdown.find("## User").unwrap() < markdown.find("## Assistant").unwrap(),
+            "messages should be in chronological order"
+        );
+
+        let json = repo
+            .export_session("export-1", ExportFormat::Json)
+            .await
+            .expect("Failed to export json");
+        let parsed: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed["session"]["title"], "Fix the build");
+        let messages = parsed["messages"].as_array().unwrap();
+        assert_eq!(messages.len(), 3);
+        assert_eq!(messages[1]["content"]["type"], "tool_calls");
+
+        assert!(repo
+            .export_session("missing", ExportFormat::Json)
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_export_session_ndjson_writes_one_line_per_message() {
+        let (db, _temp) = create_test_db().await;
+        let repo = ChatHistoryRepository::new(db);
+
+        let texts: Vec<String> = (0..10).map(|i| format!("message {}", i)).collect();
+        let text_refs: Vec<&str> = texts.iter().map(String::as_str).collect();
+        create_session_with_messages(&repo, "ndjson-1", None, &text_refs).await;
+
+        let mut buffer: Vec<u8> = Vec::new();
+        // A small batch size exercises paging across several queries
+        let written = repo
+            .export_session_ndjson_batched("ndjson-1", &mut buffer, 3)
+            .await
+            .expect("Failed to export ndjson");
+        assert_eq!(written, 10);
+
+        let output = String::from_utf8(buffer).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines.len(), 10);
+        for (index, line) in lines.iter().enumerate() {
+            let message: Message = serde_json::from_str(line).unwrap();
+            assert_eq!(message.id, format!("ndjson-1-msg-{}", index));
+        }
+
+        let mut default_batch: Vec<u8> = Vec::new();
+        repo.export_session_ndjson("ndjson-1", &mut default_batch)
+            .await
+            .unwrap();
+        assert_eq!(default_batch, output.into_bytes());
+
+        assert!(repo
+            .export_session_ndjson("missing", Vec::new())
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_archived_sessions_hidden_by_default() {
+        let (db, _temp) = create_test_db().await;
+        let repo = ChatHistoryRepository::new(db);
+
+        create_session_with_messages(&repo, "archive-1", Some("project-1"), &["keep me"]).await;
+        create_session_with_messages(&repo, "archive-2", Some("project-1"), &["hide me"]).await;
+        repo.update_session_status("archive-2", SessionStatus::Running, None)
+            .await
+            .unwrap();
+
+        repo.archive_session("archive-2").await.unwrap();
+        // Archiving twice keeps the original status to restore
+        repo.archive_session("archive-2").await.unwrap();
+
+        let listed = repo
+            .list_sessions(Some("project-1"), None, None, None, false, None)
+            .await
+            .unwrap();
+        let ids: Vec<&str> = listed.iter().map(|s| s.id.as_str()).collect();
+        assert_eq!(ids, vec!["archive-1"]);
+
+        let all = repo
+            .list_sessions(Some("project-1"), None, None, None, true, None)
+            .await
+            .unwrap();
+        assert_eq!(all.len(), 2);
+
+        let archived = repo
+            .list_sessions(None, Some(SessionStatus::Archived), None, None, false, None)
+            .await
+            .unwrap();
+        assert_eq!(archived.len(), 1);
+        assert_eq!(archived[0].id, "archive-2");
+
+        repo.unarchive_session("archive-2").await.unwrap();
+        let restored = repo.get_session("archive-2").await.unwrap().unwrap();
+        assert_eq!(restored.status, SessionStatus::Running);
+        assert!(restored
+            .metadata
+            .as_ref()
+            .and_then(|m| m.get("archivedFromStatus"))
+            .is_none());
+        assert_eq!(
+            repo.list_sessions(Some("project-1"), None, None, None, false, None)
+                .await
+                .unwrap()
+                .len(),
+            2
+        );
+    }
+
+    #[tokio::test]
+    async fn test_purge_archived_before() {
+        let (db, _temp) = create_test_db().await;
+        let repo = ChatHistoryRepository::new(db.clone());
+
+        create_session_with_messages(&repo, "purge-1", None, &["old"]).await;
+        create_session_with_messages(&repo, "purge-2", None, &["active"]).await;
+        repo.edit_message(
+            "purge-1-msg-0",
+            &MessageContent::Text {
+                text: "older".to_string(),
+            },
+        )
+        .await
+        .unwrap();
+        repo.add_tag("purge-1", "stale").await.unwrap();
+        repo.archive_session("purge-1").await.unwrap();
+
+        let cutoff = chrono::Utc::now().timestamp() + 60;
+        let purged = repo.purge_archived_before(cutoff).await.unwrap();
+        assert_eq!(purged, 1);
+
+        assert!(repo.get_session("purge-1").await.unwrap().is_none());
+        assert!(repo
+            .get_messages("purge-1", None, None)
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(repo
+            .get_message_revisions("purge-1-msg-0")
+            .await
+            .unwrap()
+            .is_empty());
+        assert_eq!(count_rows(&db, "session_tags", "purge-1").await, 0);
+        assert!(repo.get_session("purge-2").await.unwrap().is_some());
+        assert_eq!(repo.purge_archived_before(cutoff).await.unwrap(), 0);
+    }
+
+    fn text_of(content: &MessageContent) -> &str {
+        match content {
+            MessageContent::Text { text } => text,
+            _ => panic!("expected text content"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_edit_message_twice_keeps_revisions_in_order() {
+        let (db, _temp) = create_test_db().await;
+        let repo = ChatHistoryRepository::new(db.clone());
+
+        create_session_with_messages(&repo, "edit-1", None, &["first draft"]).await;
+        db.execute(
+            "UPDATE sessions SET updated_at = 0 WHERE id = ?",
+            vec![serde_json::json!("edit-1")],
+        )
+        .await
+        .unwrap();
+
+        for text in ["second draft", "final prompt"] {
+            repo.edit_message(
+                "edit-1-msg-0",
+                &MessageContent::Text {
+                    text: text.to_string(),
+                },
+            )
+            .await
+            .expect("Failed to edit message");
+        }
+
+        let messages = repo.get_messages("edit-1", None, None).await.unwrap();
+        assert_eq!(text_of(&messages[0].content), "final prompt");
+
+        let revisions = repo.get_message_revisions("edit-1-msg-0").await.unwrap();
+        let texts: Vec<&str> = revisions.iter().map(|r| text_of(&r.content)).collect();
+        assert_eq!(texts, vec!["first draft", "second draft"]);
+        assert!(revisions.iter().all(|r| r.message_id == "edit-1-msg-0"));
+
+        let session = repo.get_session("edit-1").await.unwrap().unwrap();
+        assert!(session.updated_at > 0);
+
+        assert!(repo
+            .edit_message(
+                "missing",
+                &MessageContent::Text {
+                    text: "x".to_string()
+                }
+            )
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_branch_session_copies_messages_up_to_branch_point() {
+        let (db, _temp) = create_test_db().await;
+        let repo = ChatHistoryRepository::new(db);
+
+        let now = chrono::Utc::now().timestamp();
+        let session = Session {
+            id: "branch-src".to_string(),
+            project_id: Some("project-1".to_string()),
+            title: Some("Original".to_string()),
+            status: SessionStatus::Completed,
+            created_at: now,
+            updated_at: now,
+            pinned_at: None,
+            last_event_id: None,
+            metadata: None,
+        };
+        repo.create_session(&session).await.unwrap();
+        for index in 0..4 {
+            let message = Message {
+                id: format!("m{}", index),
+                session_id: "branch-src".to_string(),
+                role: if index % 2 == 0 {
+                    MessageRole::User
+                } else {
+                    MessageRole::Assistant
+                },
+                content: MessageContent::Text {
+                    text: format!("message {}", index),
+                },
+                created_at: now + index,
+                tool_call_id: None,
+                parent_id: (index > 0).then(|| format!("m{}", index - 1)),
+            };
+            repo.create_message(&message).await.unwrap();
+        }
+
+        let branch = repo.branch_session("branch-src", "m2").await.unwrap();
+        assert_ne!(branch.id, "branch-src");
+        assert_eq!(branch.project_id, Some("project-1".to_string()));
+        assert_eq!(branch.title, Some("Original (branch)".to_string()));
+
+        let copied = repo.get_messages(&branch.id, None, None).await.unwrap();
+        let texts: Vec<&str> = copied.iter().map(|m| text_of(&m.content)).collect();
+        assert_eq!(texts, vec!["message 0", "message 1", "message 2"]);
+        assert!(copied.iter().all(|m| !m.id.starts_with('m')));
+        assert_eq!(copied[0].parent_id, None);
+        assert_eq!(copied[1].parent_id.as_deref(), Some(copied[0].id.as_str()));
+        assert_eq!(copied[2].parent_id.as_deref(), Some(copied[1].id.as_str()));
+
+        // The source session is untouched
+        assert_eq!(
+            repo.get_messages("branch-src", None, None)
+                .await
+                .unwrap()
+                .len(),
+            4
+        );
+        assert!(repo.branch_session("branch-src", "missing").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_message_pages_with_shared_timestamps() {
+        let (db, _temp) = create_test_db().await;
+        let repo = ChatHistoryRepository::new(db);
+
+        create_session_with_messages(&repo, "page-1", None, &[]).await;
+        let now = chrono::Utc::now().timestamp();
+        for index in 0..7 {
+            let message = Message {
+                id: format!("msg-{}", index),
+                session_id: "page-1".to_string(),
+                role: MessageRole::User,
+                content: MessageContent::Text {
+                    text: format!("message {}", index),
+                },
+                // Everything lands in the same second
+                created_at: now,
+                tool_call_id: None,
+                parent_id: None,
+            };
+            repo.create_message(&message).await.unwrap();
+        }
+
+        let mut seen = Vec::new();
+        let mut cursor: Option<String> = None;
+        loop {
+            let page = repo
+                .get_messages_page("page-1", 3, cursor.as_deref())
+                .await
+                .unwrap();
+            assert!(page.items.len() <= 3);
+            // Pages go backwards; prepend to rebuild chronological order
+            let mut ids: Vec<String> = page.items.iter().map(|m| m.id.clone()).collect();
+            ids.extend(seen);
+            seen = ids;
+            match page.next_cursor {
+                Some(next) => cursor = Some(next),
+                None => break,
+            }
+        }
+
+        let expected: Vec<String> = (0..7).map(|i| format!("msg-{}", i)).collect();
+        assert_eq!(seen, expected);
+
+        // before_id stays consistent with the cursor ordering
+        let older = repo
+            .get_messages("page-1", Some(2), Some("msg-4"))
+            .await
+            .unwrap();
+        let ids: Vec<&str> = older.iter().map(|m| m.id.as_str()).collect();
+        assert_eq!(ids, vec!["msg-2", "msg-3"]);
+
+        assert!(repo
+            .get_messages_page("page-1", 3, Some("not a cursor"))
+            .await
+            .is_err());
+    }
+
+    #[tokio::test]
+    async fn test_event_pages_with_shared_timestamps() {
+        let (db, _temp) = create_test_db().await;
+        let repo = ChatHistoryRepository::new(db);
+
+        create_session_with_messages(&repo, "page-2", None, &[]).await;
+        let now = chrono::Utc::now().timestamp();
+        for index in 0..5 {
+            let event = SessionEvent {
+                id: format!("evt-{}", index),
+                session_id: "page-2".to_string(),
+                event_type: EventType::Token,
+                payload: serde_json::json!({ "index": index }),
+                created_at: now,
+            };
+            repo.create_event(&event).await.unwrap();
+        }
+
+        let mut seen = Vec::new();
+        let mut cursor: Option<String> = None;
+        loop {
+            let page = repo
+                .get_events_page("page-2", 2, cursor.as_deref())
+                .await
+                .unwrap();
+            seen.extend(page.items.iter().map(|e| e.id.clone()));
+            match page.next_cursor {
+                Some(next) => cursor = Some(next),
+                None => break,
+            }
+        }
+
+        let expected: Vec<String> = (0..5).map(|i| format!("evt-{}", i)).collect();
+        assert_eq!(seen, expected);
+
+        let resumed = repo
+            .get_events("page-2", Some("evt-2"), None)
+            .await
+            .unwrap();
+        let ids: Vec<&str> = resumed.iter().map(|e| e.id.as_str()).collect();
+        assert_eq!(ids, vec!["evt-3", "evt-4"]);
+    }
+
+    #[tokio::test]
+    async fn test_add_usage_accumulates() {
+        let (db, _temp) = create_test_db().await;
+        let repo = ChatHistoryRepository::new(db);
+
+        create_session_with_messages(&repo, "usage-1", None, &[]).await;
+        assert_eq!(
+            repo.get_session_usage("usage-1").await.unwrap(),
+            SessionUsage::default()
+        );
+
+        repo.add_usage(
+            "usage-1",
+            &SessionUsage {
+                input_tokens: 100,
+                output_tokens: 20,
+                cached_tokens: 40,
+                cost_cents: 0.25,
+            },
+        )
+        .await
+        .unwrap();
+        repo.add_usage(
+            "usage-1",
+            &SessionUsage {
+                input_tokens: 50,
+                output_tokens: 10,
+                cached_tokens: 0,
+                cost_cents: 0.5,
+            },
+        )
+        .await
+        .unwrap();
+
+        let totals = repo.get_session_usage("usage-1").await.unwrap();
+        assert_eq!(totals.input_tokens, 150);
+        assert_eq!(totals.output_tokens, 30);
+        assert_eq!(totals.cached_tokens, 40);
+        assert!((totals.cost_cents - 0.75).abs() < 1e-9);
+
+        // Other sessions are unaffected
+        assert_eq!(
+            repo.get_session_usage("usage-2").await.unwrap(),
+            SessionUsage::default()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_session_tags_and_filtering() {
+        let (db, _temp) = create_test_db().await;
+        let repo = ChatHistoryRepository::new(db);
+
+        create_session_with_messages(&repo, "tags-1", None, &[]).await;
+        create_session_with_messages(&repo, "tags-2", None, &[]).await;
+        create_session_with_messages(&repo, "tags-3", None, &[]).await;
+
+        repo.add_tag("tags-1", "Refactor").await.unwrap();
+        // Duplicate after normalization is a no-op
+        repo.add_tag("tags-1", "  refactor ").await.unwrap();
+        repo.add_tag("tags-1", "bugfix").await.unwrap();
+        repo.add_tag("tags-2", "BUGFIX").await.unwrap();
+        assert!(repo.add_tag("tags-3", "   ").await.is_err());
+
+        assert_eq!(
+            repo.list_tags("tags-1").await.unwrap(),
+            vec!["bugfix".to_string(), "refactor".to_string()]
+        );
+        assert_eq!(
+            repo.list_tags("tags-2").await.unwrap(),
+            vec!["bugfix".to_string()]
+        );
+
+        let ids = |sessions: Vec<Session>| {
+            let mut ids: Vec<String> = sessions.into_iter().map(|s| s.id).collect();
+            ids.sort();
+            ids
+        };
+
+        let refactor = repo
+            .list_sessions(
+                None,
+                None,
+                None,
+                None,
+                false,
+                Some(vec!["Refactor".to_string()]),
+            )
+            .await
+            .unwrap();
+        assert_eq!(ids(refactor), vec!["tags-1"]);
+
+        let any = repo
+            .list_sessions(
+                None,
+                None,
+                None,
+                None,
+                false,
+                Some(vec!["refactor".to_string(), "bugfix".to_string()]),
+            )
+            .await
+            .unwrap();
+        assert_eq!(ids(any), vec!["tags-1", "tags-2"]);
+
+        repo.remove_tag("tags-2", "bugfix").await.unwrap();
+        assert!(repo.list_tags("tags-2").await.unwrap().is_empty());
+
+        let unfiltered = repo
+            .list_sessions(None, None, None, None, false, Some(vec![]))
+            .await
+            .unwrap();
+        assert_eq!(unfiltered.len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_pinned_sessions_sort_first() {
+        let (db, _temp) = create_test_db().await;
+        let repo = ChatHistoryRepository::new(db.clone());
+
+        for (id, updated_at) in [("pin-old", 100), ("pin-mid", 200), ("pin-recent", 300)] {
+            create_session_with_messages(&repo, id, None, &[]).await;
+            db.execute(
+                "UPDATE sessions SET updated_at = ? WHERE id = ?",
+                vec![serde_json::json!(updated_at), serde_json::json!(id)],
+            )
+            .await
+            .unwrap();
+        }
+        let ids = |sessions: Vec<Session>| -> Vec<String> {
+            sessions.into_iter().map(|s| s.id).collect()
+        };
+
+        repo.pin_session("pin-old").await.unwrap();
+        let listed = repo
+            .list_sessions(None, None, None, None, false, None)
+            .await
+            .unwrap();
+        assert!(listed[0].pinned_at.is_some());
+        assert_eq!(ids(listed), vec!["pin-old", "pin-recent", "pin-mid"]);
+
+        // Most recently pinned first
+        repo.pin_session("pin-mid").await.unwrap();
+        for (id, pinned_at) in [("pin-old", 1_000), ("pin-mid", 2_000)] {
+            db.execute(
+                "UPDATE sessions SET pinned_at = ? WHERE id = ?",
+                vec![serde_json::json!(pinned_at), serde_json::json!(id)],
+            )
+            .await
+            .unwrap();
+        }
+        assert_eq!(
+            ids(repo
+                .list_sessions(None, None, None, None, false, None)
+                .await
+                .unwrap()),
+            vec!["pin-mid", "pin-old", "pin-recent"]
+        );
+
+        repo.unpin_session("pin-mid").await.unwrap();
+        assert!(repo
+            .get_session("pin-mid")
+            .await
+            .unwrap()
+            .unwrap()
+            .pinned_at
+            .is_none());
+        assert_eq!(
+            ids(repo
+                .list_sessions(None, None, None, None, false, None)
+                .await
+                .unwrap()),
+            vec!["pin-old", "pin-recent", "pin-mid"]
+        );
+        assert!(repo.pin_session("missing").await.is_err());
+    }
+
+    async fn count_rows(db: &Database, table: &str, session_id: &str) -> i64 {
+        db.query(
+            &format!("SELECT COUNT(*) AS n FROM {} WHERE session_id = ?", table),
+            vec![serde_json::json!(session_id)],
+        )
+        .await
+        .unwrap()
+        .rows[0]["n"]
+            .as_i64()
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_delete_sessions_by_project() {
+        let (db, _temp) = create_test_db().await;
+        let repo = ChatHistoryRepository::new(db.clone());
+        create_session_with_messages(&repo, "bulk-a1", Some("project-a"), &["one", "two"]).await;
+        create_session_with_messages(&repo, "bulk-a2", Some("project-a"), &["three"]).await;
+        create_session_with_messages(&repo, "bulk-b1", Some("project-b"), &["four"]).await;
+        repo.add_tag("bulk-a1", "cleanup").await.unwrap();
+
+        let deleted = repo
+            .delete_sessions(SessionDeleteFilter::ByProject("project-a".to_string()))
+            .await
+            .unwrap();
+
+        assert_eq!(deleted, 2);
+        assert!(repo.get_session("bulk-a1").await.unwrap().is_none());
+        assert_eq!(count_rows(&db, "messages", "bulk-a1").await, 0);
+        assert_eq!(count_rows(&db, "session_tags", "bulk-a1").await, 0);
+        assert!(repo.get_session("bulk-b1").await.unwrap().is_some());
+        assert_eq!(count_rows(&db, "messages", "bulk-b1").await, 1);
+    }
+
+    #[tokio::test]
+    async fn test_delete_sessions_older_than() {
+        let (db, _temp) = create_test_db().await;
+        let repo = ChatHistoryRepository::new(db.clone());
+        create_session_with_messages(&repo, "bulk-old", None, &["stale"]).await;
+        create_session_with_messages(&repo, "bulk-new", None, &["fresh"]).await;
+        db.execute(
+            "UPDATE sessions SET updated_at = ? WHERE id = ?",
+            vec![serde_json::json!(100), serde_json::json!("bulk-old")],
+        )
+        .await
+        .unwrap();
+
+        let deleted = repo
+            .delete_sessions(SessionDeleteFilter::OlderThan(1_000))
+            .await
+            .unwrap();
+
+        assert_eq!(deleted, 1);
+        assert!(repo.get_session("bulk-old").await.unwrap().is_none());
+        assert_eq!(count_rows(&db, "messages", "bulk-old").await, 0);
+        assert!(repo.get_session("bulk-new").await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_delete_sessions_by_status() {
+        let (db, _temp) = create_test_db().await;
+        let repo = ChatHistoryRepository::new(db.clone());
+        create_session_with_messages(&repo, "bulk-done", None, &["done"]).await;
+        create_session_with_messages(&repo, "bulk-running", None, &["busy"]).await;
+        repo.update_session_status("bulk-done", SessionStatus::Completed, None)
+            .await
+            .unwrap();
+        repo.update_session_status("bulk-running", SessionStatus::Running, None)
+            .await
+            .unwrap();
+
+        let deleted = repo
+            .delete_sessions(SessionDeleteFilter::ByStatus(SessionStatus::Completed))
+            .await
+            .unwrap();
+
+        assert_eq!(deleted, 1);
+        assert!(repo.get_session("bulk-done").await.unwrap().is_none());
+        assert_eq!(count_rows(&db, "messages", "bulk-done").await, 0);
+        assert!(repo.get_session("bulk-running").await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_delete_sessions_rejects_empty_filter() {
+        let (db, _temp) = create_test_db().await;
+        let repo = ChatHistoryRepository::new(db);
+        create_session_with_messages(&repo, "bulk-keep", None, &["keep"]).await;
+
+        assert!(repo
+            .delete_sessions(SessionDeleteFilter::ByProject("  ".to_string()))
+            .await
+            .is_err());
+        assert!(repo.get_session("bulk-keep").await.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn test_auto_title_from_first_user_message() {
+        let (db, _temp) = create_test_db().await;
+        let repo = ChatHistoryRepository::new(db);
+        create_session_with_messages(
+            &repo,
+            "auto-title",
+            None,
+            &[
+                "  Fix the flaky\nlogin test in CI  ",
+                "and also look at the release pipeline",
+            ],
+        )
+        .await;
+
+        let title = repo.maybe_set_auto_title("auto-title").await.unwrap();
+        assert_eq!(title.as_deref(), Some("Fix the flaky login test in CI"));
+        assert_eq!(
+            repo.get_session("auto-title").await.unwrap().unwrap().title,
+            title
+        );
+        // Second call keeps the derived title
+        assert!(repo
+            .maybe_set_auto_title("auto-title")
+            .await
+            .unwrap()
+            .is_none());
+
+        let long = "word ".repeat(40);
+        create_session_with_messages(&repo, "auto-long", None, &[long.as_str()]).await;
+        repo.update_session_title("auto-long", "New Session")
+            .await
+            .unwrap();
+        let title = repo
+            .maybe_set_auto_title("auto-long")
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(title.ends_with("..."));
+        assert!(title.chars().count() <= AUTO_TITLE_MAX_CHARS + 3);
+    }
+
+    #[tokio::test]
+    async fn test_auto_title_keeps_existing_title() {
+        let (db, _temp) = create_test_db().await;
+        let repo = ChatHistoryRepository::new(db);
+        create_session_with_messages(&repo, "titled", None, &["something else"]).await;
+        repo.update_session_title("titled", "Release planning")
+            .await
+            .unwrap();
+
+        assert!(repo.maybe_set_auto_title("titled").await.unwrap().is_none());
+        assert_eq!(
+            repo.get_session("titled").await.unwrap().unwrap().title,
+            Some("Release planning".to_string())
+        );
+    }
 }
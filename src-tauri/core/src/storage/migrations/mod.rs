@@ -96,23 +96,24 @@ impl<'a> MigrationRunner<'a> {
     }
 
     async fn apply_migration(&self, migration: &Migration) -> Result<(), String> {
-        // Execute migration in transaction
-        self.db.execute(migration.up_sql, vec![]).await?;
-
-        // Record migration
+        // The schema change and its record commit or roll back together
         let now = chrono::Utc::now().timestamp();
         self.db
-            .execute(
-                "INSERT INTO _migrations (version, name, applied_at) VALUES (?, ?, ?)",
-                vec![
-                    serde_json::json!(migration.version),
-                    serde_json::json!(migration.name),
-                    serde_json::json!(now),
-                ],
-            )
-            .await?;
-
-        Ok(())
+            .transaction(|tx| async move {
+                tx.execute_batch(migration.up_sql).await?;
+                tx.execute(
+                    "INSERT INTO _migrations (version, name, applied_at) VALUES (?, ?, ?)",
+                    vec![
+                        serde_json::json!(migration.version),
+                        serde_json::json!(migration.name),
+                        serde_json::json!(now),
+                    ],
+                )
+                .await?;
+                Ok(())
+            })
+            .await
+            .map_err(|e| format!("Migration {} failed: {}", migration.version, e))
     }
 }
 
@@ -214,6 +215,148 @@ pub fn chat_history_migrations() -> MigrationRegistry {
         down_sql: Some("DROP INDEX IF EXISTS idx_attachments_message;"),
     });
 
+    // Migrations 6-10: Full-text search over message text.
+    // Content is stored as serialized MessageContent, so text messages index
+    // their `text` field and other content is indexed as raw JSON.
+    registry.register(Migration {
+        version: 6,
+        name: "create_messages_fts_table",
+        up_sql: r#"
+            CREATE VIRTUAL TABLE IF NOT EXISTS messages_fts USING fts5(
+                message_id UNINDEXED,
+                session_id UNINDEXED,
+                text
+            )
+        "#,
+        down_sql: Some("DROP TABLE IF EXISTS messages_fts;"),
+    });
+
+    registry.register(Migration {
+        version: 7,
+        name: "create_messages_fts_insert_trigger",
+        up_sql: r#"
+            CREATE TRIGGER IF NOT EXISTS messages_fts_insert AFTER INSERT ON messages
+            BEGIN
+                INSERT INTO messages_fts (message_id, session_id, text)
+                VALUES (
+                    new.id,
+                    new.session_id,
+                    CASE WHEN json_valid(new.content)
+                        THEN COALESCE(json_extract(new.content, '$.text'), new.content)
+                        ELSE new.content
+                    END
+                );
+            END
+        "#,
+        down_sql: Some("DROP TRIGGER IF EXISTS messages_fts_insert;"),
+    });
+
+    registry.register(Migration {
+        version: 8,
+        name: "create_messages_fts_delete_trigger",
+        up_sql: r#"
+            CREATE TRIGGER IF NOT EXISTS messages_fts_delete AFTER DELETE ON messages
+            BEGIN
+                DELETE FROM messages_fts WHERE message_id = old.id;
+            END
+        "#,
+        down_sql: Some("DROP TRIGGER IF EXISTS messages_fts_delete;"),
+    });
+
+    registry.register(Migration {
+        version: 9,
+        name: "create_messages_fts_update_trigger",
+        up_sql: r#"
+            CREATE TRIGGER IF NOT EXISTS messages_fts_update AFTER UPDATE OF content ON messages
+            BEGIN
+                UPDATE messages_fts
+                SET text = CASE WHEN json_valid(new.content)
+                    THEN COALESCE(json_extract(new.content, '$.text'), new.content)
+                    ELSE new.content
+                END
+                WHERE message_id = new.id;
+            END
+        "#,
+        down_sql: Some("DROP TRIGGER IF EXISTS messages_fts_update;"),
+    });
+
+    registry.register(Migration {
+        version: 10,
+        name: "backfill_messages_fts",
+        up_sql: r#"
+            INSERT INTO messages_fts (message_id, session_id, text)
+            SELECT
+                id,
+                session_id,
+                CASE WHEN json_valid(content)
+                    THEN COALESCE(json_extract(content, '$.text'), content)
+                    ELSE content
+                END
+            FROM messages
+            WHERE id NOT IN (SELECT message_id FROM messages_fts)
+        "#,
+        down_sql: Some("DELETE FROM messages_fts;"),
+    });
+
+    // Migration 11: Prior contents of edited messages
+    registry.register(Migration {
+        version: 11,
+        name: "create_message_revisions_table",
+        up_sql: r#"
+            CREATE TABLE message_revisions (
+                id TEXT PRIMARY KEY,
+                message_id TEXT NOT NULL,
+                content TEXT NOT NULL,
+                edited_at INTEGER NOT NULL,
+                FOREIGN KEY (message_id) REFERENCES messages(id) ON DELETE CASCADE
+            )
+        "#,
+        down_sql: Some("DROP TABLE message_revisions;"),
+    });
+
+    // Migration 12: Cumulative token usage and cost per session
+    registry.register(Migration {
+        version: 12,
+        name: "create_session_usage_table",
+        up_sql: r#"
+            CREATE TABLE session_usage (
+                session_id TEXT PRIMARY KEY,
+                input_tokens INTEGER NOT NULL DEFAULT 0,
+                output_tokens INTEGER NOT NULL DEFAULT 0,
+                cached_tokens INTEGER NOT NULL DEFAULT 0,
+                cost_cents REAL NOT NULL DEFAULT 0,
+                updated_at INTEGER NOT NULL,
+                FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE
+            )
+        "#,
+        down_sql: Some("DROP TABLE session_usage;"),
+    });
+
+    // Migration 13: Free-form tags for organizing sessions
+    registry.register(Migration {
+        version: 13,
+        name: "create_session_tags_table",
+        up_sql: r#"
+            CREATE TABLE session_tags (
+                session_id TEXT NOT NULL,
+                tag TEXT NOT NULL,
+                PRIMARY KEY (session_id, tag),
+                FOREIGN KEY (session_id) REFERENCES sessions(id) ON DELETE CASCADE
+            )
+        "#,
+        down_sql: Some("DROP TABLE session_tags;"),
+    });
+
+    // Migration 14: Pinned sessions sort ahead of recency
+    registry.register(Migration {
+        version: 14,
+        name: "add_pinned_at_to_sessions",
+        up_sql: r#"
+            ALTER TABLE sessions ADD COLUMN pinned_at INTEGER
+        "#,
+        down_sql: Some("ALTER TABLE sessions DROP COLUMN pinned_at;"),
+    });
+
     registry
 }
 
@@ -302,19 +445,24 @@ pub async fn run_all_migrations(
     let mut results = HashMap::new();
 
     // Chat history migrations
-    let chat_registry = chat_history_migrations();
-    let chat_runner = MigrationRunner::new(chat_history_db, &chat_registry);
-    results.insert("chat_history", chat_runner.migrate().await?);
+    results.insert(
+        "chat_history",
+        chat_history_db
+            .run_migrations(&chat_history_migrations())
+            .await?,
+    );
 
     // Agents migrations
-    let agents_registry = agents_migrations();
-    let agents_runner = MigrationRunner::new(agents_db, &agents_registry);
-    results.insert("agents", agents_runner.migrate().await?);
+    results.insert(
+        "agents",
+        agents_db.run_migrations(&agents_migrations()).await?,
+    );
 
     // Settings migrations
-    let settings_registry = settings_migrations();
-    let settings_runner = MigrationRunner::new(settings_db, &settings_registry);
-    results.insert("settings", settings_runner.migrate().await?);
+    results.insert(
+        "settings",
+        settings_db.run_migrations(&settings_migrations()).await?,
+    );
 
     Ok(results)
 }
@@ -322,11 +470,105 @@ pub async fn run_all_migrations(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::database::Database;
+    use tempfile::TempDir;
+
+    fn notes_migrations(count: usize) -> MigrationRegistry {
+        let mut registry = MigrationRegistry::new("notes");
+        registry.register(Migration {
+            version: 1,
+            name: "create_notes_table",
+            up_sql: r#"
+                CREATE TABLE notes (id TEXT PRIMARY KEY, body TEXT NOT NULL);
+                CREATE INDEX idx_notes_body ON notes(body);
+            "#,
+            down_sql: None,
+        });
+        registry.register(Migration {
+            version: 2,
+            name: "add_session_id_to_notes",
+            up_sql: "ALTER TABLE notes ADD COLUMN session_id TEXT",
+            down_sql: None,
+        });
+        registry.migrations.truncate(count);
+        registry
+    }
+
+    async fn create_test_db() -> (Database, TempDir) {
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join("migrations.db");
+        let db = Database::new(db_path.to_string_lossy().to_string());
+        db.connect().await.expect("Failed to connect");
+        (db, temp_dir)
+    }
+
+    #[tokio::test]
+    async fn running_migrations_twice_is_idempotent() {
+        let (db, _temp) = create_test_db().await;
+        let registry = notes_migrations(2);
+
+        assert_eq!(
+            db.run_migrations(&registry).await.unwrap(),
+            vec!["1: create_notes_table", "2: add_session_id_to_notes"]
+        );
+        assert!(db.run_migrations(&registry).await.unwrap().is_empty());
+
+        db.execute(
+            "INSERT INTO notes (id, body, session_id) VALUES (?, ?, ?)",
+            vec![
+                serde_json::json!("n1"),
+                serde_json::json!("hello"),
+                serde_json::json!("s1"),
+            ],
+        )
+        .await
+        .expect("Both migrations should have been applied");
+    }
+
+    #[tokio::test]
+    async fn new_migration_is_applied_exactly_once() {
+        let (db, _temp) = create_test_db().await;
+        db.run_migrations(&notes_migrations(1)).await.unwrap();
+
+        let registry = notes_migrations(2);
+        assert_eq!(
+            db.run_migrations(&registry).await.unwrap(),
+            vec!["2: add_session_id_to_notes"]
+        );
+        assert!(db.run_migrations(&registry).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn failed_migration_is_rolled_back() {
+        let (db, _temp) = create_test_db().await;
+        let mut broken = MigrationRegistry::new("broken");
+        broken.register(Migration {
+            version: 1,
+            name: "half_applied",
+            up_sql: "CREATE TABLE partial (id TEXT); INSERT INTO missing_table VALUES (1);",
+            down_sql: None,
+        });
+
+        assert!(db.run_migrations(&broken).await.is_err());
+        let tables = db
+            .query(
+                "SELECT name FROM sqlite_master WHERE type = 'table' AND name = 'partial'",
+                vec![],
+            )
+            .await
+            .unwrap();
+        assert!(tables.rows.is_empty());
+        let recorded = db
+            .query("SELECT version FROM _migrations", vec![])
+            .await
+            .unwrap();
+        assert!(recorded.rows.is_empty());
+    }
 
     #[test]
     fn test_chat_history_migrations_count() {
         let registry = chat_history_migrations();
-        assert_eq!(registry.migrations().len(), 5);
+        assert_eq!(registry.migrations().len(), 14);
     }
 
     #[test]
@@ -166,6 +166,7 @@ mod tests {
             status: SessionStatus::Created,
             created_at: chrono::Utc::now().timestamp(),
             updated_at: chrono::Utc::now().timestamp(),
+            pinned_at: None,
             last_event_id: None,
             metadata: None,
         };
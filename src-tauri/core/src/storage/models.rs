@@ -29,6 +29,8 @@ pub enum SessionStatus {
     Error,
     /// Session was cancelled by user
     Cancelled,
+    /// Session was archived; hidden from listings until unarchived or purged
+    Archived,
 }
 
 impl SessionStatus {
@@ -40,6 +42,7 @@ impl SessionStatus {
             SessionStatus::Completed => "completed",
             SessionStatus::Error => "error",
             SessionStatus::Cancelled => "cancelled",
+            SessionStatus::Archived => "archived",
         }
     }
 }
@@ -55,6 +58,7 @@ impl std::str::FromStr for SessionStatus {
             "completed" => Ok(SessionStatus::Completed),
             "error" => Ok(SessionStatus::Error),
             "cancelled" => Ok(SessionStatus::Cancelled),
+            "archived" => Ok(SessionStatus::Archived),
             _ => Err(format!("Unknown session status: {}", s)),
         }
     }
@@ -70,6 +74,9 @@ pub struct Session {
     pub status: SessionStatus,
     pub created_at: i64,
     pub updated_at: i64,
+    /// When the session was pinned to the top of listings
+    #[serde(default)]
+    pub pinned_at: Option<i64>,
     /// Last event ID for SSE resume
     pub last_event_id: Option<EventId>,
     /// Additional metadata as JSON object
@@ -126,6 +133,27 @@ pub struct Message {
     pub parent_id: Option<MessageId>,
 }
 
+/// Token usage and cost, either for one stream or accumulated over a session
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionUsage {
+    pub input_tokens: i64,
+    pub output_tokens: i64,
+    pub cached_tokens: i64,
+    pub cost_cents: f64,
+}
+
+/// A previous version of an edited message
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MessageRevision {
+    pub id: String,
+    pub message_id: MessageId,
+    /// Content the message had before the edit
+    pub content: MessageContent,
+    pub edited_at: i64,
+}
+
 /// Content of a message - can be text or structured content
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "camelCase")]
@@ -238,6 +266,42 @@ pub struct SessionEvent {
     pub created_at: i64,
 }
 
+/// One page of results with an opaque cursor for the next page, if any
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    pub next_cursor: Option<String>,
+}
+
+/// A message matching a full-text search
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MessageSearchHit {
+    pub session_id: SessionId,
+    pub message_id: MessageId,
+    /// Matching excerpt with the hit wrapped in `[` and `]`
+    pub snippet: String,
+}
+
+/// Which sessions `ChatHistoryRepository::delete_sessions` removes
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", tag = "type", content = "value")]
+pub enum SessionDeleteFilter {
+    ByProject(String),
+    /// Sessions last updated before this timestamp
+    OlderThan(i64),
+    ByStatus(SessionStatus),
+}
+
+/// Output format for exporting a session
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Markdown,
+    Json,
+}
+
 /// An AI agent configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -125,7 +125,7 @@ impl TelegramGateway {
     }
 }
 
-type TelegramGatewayState = Arc<Mutex<TelegramGateway>>;
+pub type TelegramGatewayState = Arc<Mutex<TelegramGateway>>;
 
 fn config_path<R: Runtime>(app_handle: &AppHandle<R>) -> Result<PathBuf, String> {
     let app_data_dir = app_handle
@@ -1075,15 +1075,19 @@ pub struct TelegramGatewayStatus {
 pub async fn telegram_get_status(
     state: State<'_, TelegramGatewayState>,
 ) -> Result<TelegramGatewayStatus, String> {
+    Ok(gateway_status(state.inner()).await)
+}
+
+pub async fn gateway_status(state: &TelegramGatewayState) -> TelegramGatewayStatus {
     let gateway = state.lock().await;
-    Ok(TelegramGatewayStatus {
+    TelegramGatewayStatus {
         running: gateway.running,
         last_update_id: gateway.last_update_id,
         last_poll_at_ms: gateway.last_poll_at_ms,
         last_error: gateway.last_error.clone(),
         last_error_at_ms: gateway.last_error_at_ms,
         backoff_ms: gateway.backoff_ms,
-    })
+    }
 }
 
 #[tauri::command]
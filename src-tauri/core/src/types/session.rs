@@ -48,6 +48,7 @@ impl SessionManager {
             status: SessionStatus::Created,
             created_at: now,
             updated_at: now,
+            pinned_at: None,
             last_event_id: None,
             metadata: None,
         };
@@ -258,10 +259,12 @@ impl SessionManager {
         status: Option<SessionStatus>,
         limit: Option<usize>,
         offset: Option<usize>,
+        include_archived: bool,
+        tags: Option<Vec<String>>,
     ) -> Result<Vec<Session>, String> {
         self.storage
             .chat_history
-            .list_sessions(project_id, status, limit, offset)
+            .list_sessions(project_id, status, limit, offset, include_archived, tags)
             .await
     }
 
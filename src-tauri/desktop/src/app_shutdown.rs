@@ -0,0 +1,216 @@
+// Coordinated teardown on app exit. Steps run in a fixed order, each on its own
+// thread with a deadline, so one hung subsystem cannot keep the process alive.
+
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+
+use crate::feishu_gateway::{self, FeishuGatewayState};
+use crate::llm::tracing::writer::TraceWriter;
+use crate::AppState;
+
+const FEISHU_STOP_TIMEOUT: Duration = Duration::from_secs(2);
+const TRACE_FLUSH_TIMEOUT: Duration = Duration::from_secs(3);
+const WATCHER_CLEANUP_TIMEOUT: Duration = Duration::from_secs(2);
+
+pub struct ShutdownStep {
+    pub name: &'static str,
+    pub timeout: Duration,
+    action: Box<dyn FnOnce() + Send + 'static>,
+}
+
+impl ShutdownStep {
+    pub fn new(
+        name: &'static str,
+        timeout: Duration,
+        action: impl FnOnce() + Send + 'static,
+    ) -> Self {
+        Self {
+            name,
+            timeout,
+            action: Box::new(action),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StepOutcome {
+    Completed,
+    TimedOut,
+    Panicked,
+}
+
+pub struct AppShutdown;
+
+impl AppShutdown {
+    /// Stop the Feishu gateway, flush and shut down the trace writer, then
+    /// stop file watchers
+    pub fn run(app_handle: &AppHandle) {
+        log::info!("Running shutdown steps");
+        let started = Instant::now();
+        let outcomes = Self::run_steps(Self::steps(app_handle));
+        for (name, outcome) in &outcomes {
+            if *outcome != StepOutcome::Completed {
+                log::warn!("Shutdown step {} did not complete: {:?}", name, outcome);
+            }
+        }
+        log::info!("Shutdown steps finished in {:?}", started.elapsed());
+    }
+
+    fn steps(app_handle: &AppHandle) -> Vec<ShutdownStep> {
+        let feishu = app_handle
+            .try_state::<FeishuGatewayState>()
+            .map(|state| state.inner().clone());
+        let trace_writer = app_handle
+            .try_state::<Arc<TraceWriter>>()
+            .map(|writer| writer.inner().clone());
+        let watcher_handle = app_handle.clone();
+
+        vec![
+            ShutdownStep::new("feishu_stop", FEISHU_STOP_TIMEOUT, move || {
+                if let Some(state) = feishu {
+                    if feishu_gateway::stop_gateway_blocking(&state) {
+                        log::info!("[FeishuGateway] Stopped on app exit");
+                    }
+                }
+            }),
+            // The writer flushes its pending batch before acknowledging shutdown
+            ShutdownStep::new("trace_writer", TRACE_FLUSH_TIMEOUT, move || {
+                if let Some(writer) = trace_writer {
+                    writer.shutdown_blocking();
+                }
+            }),
+            ShutdownStep::new("file_watchers", WATCHER_CLEANUP_TIMEOUT, move || {
+                if let Some(app_state) = watcher_handle.try_state::<AppState>() {
+                    if let Ok(mut watcher_guard) = app_state.file_watcher.lock() {
+                        if let Some(mut watcher) = watcher_guard.take() {
+                            watcher.stop();
+                        }
+                    }
+                    app_state.window_registry.cleanup_all_watchers();
+                }
+            }),
+        ]
+    }
+
+    /// Run steps in order, abandoning any that outlive their timeout
+    pub fn run_steps(steps: Vec<ShutdownStep>) -> Vec<(&'static str, StepOutcome)> {
+        steps
+            .into_iter()
+            .map(|step| {
+                let (done_tx, done_rx) = mpsc::channel();
+                let action = step.action;
+                let spawned = thread::Builder::new()
+                    .name(format!("shutdown-{}", step.name))
+                    .spawn(move || {
+                        action();
+                        let _ = done_tx.send(());
+                    });
+                let outcome = match spawned {
+                    Ok(_) => match done_rx.recv_timeout(step.timeout) {
+                        Ok(()) => StepOutcome::Completed,
+                        Err(mpsc::RecvTimeoutError::Timeout) => StepOutcome::TimedOut,
+                        // The sender is dropped without sending only when the action panics
+                        Err(mpsc::RecvTimeoutError::Disconnected) => StepOutcome::Panicked,
+                    },
+                    Err(e) => {
+                        log::error!("Failed to spawn shutdown step {}: {}", step.name, e);
+                        StepOutcome::Panicked
+                    }
+                };
+                (step.name, outcome)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    fn recording_step(
+        name: &'static str,
+        ran: &Arc<Mutex<Vec<&'static str>>>,
+        delay: Duration,
+        timeout: Duration,
+    ) -> ShutdownStep {
+        let ran = ran.clone();
+        ShutdownStep::new(name, timeout, move || {
+            thread::sleep(delay);
+            ran.lock().unwrap().push(name);
+        })
+    }
+
+    #[test]
+    fn steps_run_in_order() {
+        let ran = Arc::new(Mutex::new(Vec::new()));
+        let outcomes = AppShutdown::run_steps(vec![
+            recording_step(
+                "feishu_stop",
+                &ran,
+                Duration::from_millis(20),
+                FEISHU_STOP_TIMEOUT,
+            ),
+            recording_step("trace_writer", &ran, Duration::ZERO, TRACE_FLUSH_TIMEOUT),
+            recording_step(
+                "file_watchers",
+                &ran,
+                Duration::ZERO,
+                WATCHER_CLEANUP_TIMEOUT,
+            ),
+        ]);
+
+        assert_eq!(
+            *ran.lock().unwrap(),
+            vec!["feishu_stop", "trace_writer", "file_watchers"]
+        );
+        assert!(outcomes
+            .iter()
+            .all(|(_, outcome)| *outcome == StepOutcome::Completed));
+    }
+
+    #[test]
+    fn slow_trace_flush_is_bounded_by_its_timeout() {
+        let ran = Arc::new(Mutex::new(Vec::new()));
+        let started = Instant::now();
+        let outcomes = AppShutdown::run_steps(vec![
+            recording_step(
+                "trace_writer",
+                &ran,
+                Duration::from_secs(5),
+                Duration::from_millis(100),
+            ),
+            recording_step(
+                "file_watchers",
+                &ran,
+                Duration::ZERO,
+                WATCHER_CLEANUP_TIMEOUT,
+            ),
+        ]);
+
+        assert!(started.elapsed() < Duration::from_secs(2));
+        assert_eq!(
+            outcomes,
+            vec![
+                ("trace_writer", StepOutcome::TimedOut),
+                ("file_watchers", StepOutcome::Completed),
+            ]
+        );
+        assert_eq!(*ran.lock().unwrap(), vec!["file_watchers"]);
+    }
+
+    #[test]
+    fn panicking_step_does_not_stop_later_steps() {
+        let ran = Arc::new(Mutex::new(Vec::new()));
+        let outcomes = AppShutdown::run_steps(vec![
+            ShutdownStep::new("feishu_stop", FEISHU_STOP_TIMEOUT, || panic!("boom")),
+            recording_step("trace_writer", &ran, Duration::ZERO, TRACE_FLUSH_TIMEOUT),
+        ]);
+
+        assert_eq!(outcomes[0], ("feishu_stop", StepOutcome::Panicked));
+        assert_eq!(outcomes[1], ("trace_writer", StepOutcome::Completed));
+    }
+}
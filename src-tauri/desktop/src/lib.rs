@@ -1,5 +1,6 @@
 // Re-export desktop app implementation from core modules.
 
+pub mod app_shutdown;
 pub mod dock_menu;
 pub mod file_watcher;
 pub mod keep_awake;
@@ -42,6 +43,7 @@ use analytics::AnalyticsState;
 use code_navigation::{CodeNavState, CodeNavigationService};
 use database::Database;
 use file_watcher::FileWatcher;
+use llm::tracing::types::TraceWriterConfig;
 use llm::tracing::writer::TraceWriter;
 use script_executor::{ScriptExecutionRequest, ScriptExecutionResult, ScriptExecutor};
 use serde::{Deserialize, Serialize};
@@ -760,7 +762,7 @@ where
     R: tauri::Runtime,
     M: Manager<R>,
 {
-    let trace_writer = Arc::new(TraceWriter::new(database));
+    let trace_writer = Arc::new(TraceWriter::new(database, TraceWriterConfig::default()));
     let trace_writer_clone = trace_writer.clone();
     tauri::async_runtime::spawn(async move {
         trace_writer_clone.start();
@@ -801,6 +803,10 @@ pub fn run() {
                 cleanup_old_logs(&log_dir, 3);
             }
             let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
+            // Swap in databases staged by `db_restore` before anything opens them
+            if let Err(e) = database::apply_pending_restore(&app_data_dir) {
+                log::error!("Failed to apply pending database restore: {}", e);
+            }
             let db_path = app_data_dir.join("talkcody.db");
             let db_path_str = db_path.to_string_lossy().to_string();
             let database = Arc::new(Database::new(db_path_str));
@@ -837,6 +843,36 @@ pub fn run() {
             // Initialize LLM tracing
             init_trace_writer_state(app, database.clone());
 
+            // Close spans left open by a previous crash so durations stay meaningful
+            let orphan_database = database.clone();
+            tauri::async_runtime::spawn(async move {
+                if !orphan_database.is_connected().await {
+                    if let Err(e) = orphan_database.connect().await {
+                        log::warn!("Skipping orphaned span recovery: {}", e);
+                        return;
+                    }
+                }
+                if let Err(e) = llm::tracing::schema::init_tracing_pragmas(
+                    &orphan_database,
+                    llm::tracing::schema::busy_timeout_ms(),
+                )
+                .await
+                {
+                    log::warn!("Failed to configure tracing database: {}", e);
+                }
+                if let Err(e) =
+                    llm::tracing::schema::ensure_span_event_type_index(&orphan_database).await
+                {
+                    log::warn!("Failed to add span event type index: {}", e);
+                }
+                if let Err(e) = llm::tracing::TraceReader::new(orphan_database)
+                    .close_orphaned_spans(std::time::Duration::from_secs(60 * 60))
+                    .await
+                {
+                    log::warn!("Failed to close orphaned trace spans: {}", e);
+                }
+            });
+
             let app_data_dir = app.path().app_data_dir().map_err(|e| e.to_string())?;
             let llm_state = llm::auth::api_key_manager::LlmState::new(
                 database.clone(),
@@ -845,6 +881,12 @@ pub fn run() {
             );
             app.manage(llm_state);
 
+            let health_monitor = Arc::new(
+                llm::providers::health_monitor::ProviderHealthMonitor::new(Default::default()),
+            );
+            health_monitor.start(app.handle().clone());
+            app.manage(health_monitor);
+
             let model_sync_handle = app.handle().clone();
             let model_sync_data_dir = app_data_dir.clone();
             tauri::async_runtime::spawn(async move {
@@ -895,6 +937,7 @@ pub fn run() {
                                         headers: None,
                                         extra_body: None,
                                         auth_type: crate::llm::types::AuthType::Bearer,
+                                        body_transforms: None,
                                     });
                                 }
                             }
@@ -935,10 +978,20 @@ pub fn run() {
                     project_id: None,
                     root_path: None,
                     file_watcher: None,
+                    geometry: None,
                 };
                 let _ = app_state
                     .window_registry
                     .register_window("main".to_string(), state);
+
+                // Reopen the project windows from the previous session
+                match app_state.window_registry.restore_saved_windows(app.handle()) {
+                    Ok(restored) if !restored.is_empty() => {
+                        log::info!("Restored {} window(s) from last session", restored.len())
+                    }
+                    Ok(_) => {}
+                    Err(e) => log::error!("Failed to restore saved windows: {}", e),
+                }
             }
 
             // Initialize dock menu on macOS
@@ -1006,6 +1059,15 @@ pub fn run() {
             database::db_execute,
             database::db_query,
             database::db_batch,
+            database::db_backup,
+            database::db_restore,
+            llm::tracing::reader::trace_delete,
+            llm::tracing::reader::trace_delete_for_session,
+            llm::tracing::reader::trace_list,
+            llm::tracing::reader::trace_list_anomalous,
+            llm::tracing::reader::trace_metrics,
+            llm::tracing::reader::trace_span_events_of_type,
+            llm::tracing::redaction::trace_set_redaction_mode,
             http_proxy::proxy_fetch,
             http_proxy::stream_fetch,
             git::git_get_status,
@@ -1066,13 +1128,22 @@ pub fn run() {
             lsp::lsp_get_server_status,
             lsp::lsp_download_server,
             oauth_callback_server::start_oauth_callback_server,
+            oauth_callback_server::start_oauth_callback_listener,
             llm_commands::llm_stream_text,
+            llm_commands::llm_cancel_stream,
+            llm_commands::llm_preview_request,
+            llm::streaming::compare::llm_stream_compare,
+            llm::streaming::compare::llm_stream_compare_cancel,
             llm_commands::llm_list_available_models,
+            llm_commands::llm_test_provider,
+            llm_commands::llm_provider_health,
             llm_commands::llm_register_custom_provider,
             llm_commands::llm_check_model_updates,
             llm_commands::llm_get_provider_configs,
             llm_commands::llm_get_models_config,
             llm_commands::llm_is_model_available,
+            llm_commands::llm_provider_availability,
+            llm_commands::llm_validate_models_config,
             llm_commands::llm_transcribe_audio,
             llm_commands::llm_generate_image,
             llm_commands::llm_download_image,
@@ -1082,7 +1153,11 @@ pub fn run() {
             llm_commands::llm_generate_title,
             llm_commands::llm_compact_context,
             llm_commands::llm_enhance_prompt,
+            llm::auth::api_key_manager::llm_get_setting,
             llm::auth::api_key_manager::llm_set_setting,
+            llm::auth::api_key_manager::llm_migrate_secrets_to_keyring,
+            llm::auth::api_key_manager::settings_export,
+            llm::auth::api_key_manager::settings_import,
             llm::auth::oauth::llm_openai_oauth_start,
             llm::auth::oauth::llm_openai_oauth_complete,
             llm::auth::oauth::llm_openai_oauth_refresh,
@@ -1098,7 +1173,16 @@ pub fn run() {
             llm::auth::oauth::llm_github_copilot_oauth_refresh,
             llm::auth::oauth::llm_github_copilot_oauth_disconnect,
             llm::auth::oauth::llm_github_copilot_oauth_tokens,
+            llm::auth::qwen_oauth::llm_qwen_oauth_start,
+            llm::auth::qwen_oauth::llm_qwen_oauth_poll,
+            llm::auth::qwen_oauth::llm_qwen_oauth_disconnect,
+            llm::auth::oauth::llm_generic_oauth_set_config,
+            llm::auth::oauth::llm_generic_oauth_start,
+            llm::auth::oauth::llm_generic_oauth_complete,
             llm::auth::oauth::llm_oauth_status,
+            llm::support_bundle::generate_support_bundle,
+            llm::auth::oauth::llm_oauth_list_accounts,
+            llm::auth::oauth::llm_oauth_switch_account,
             device_id::get_device_id,
             keep_awake::keep_awake_acquire,
             keep_awake::keep_awake_release,
@@ -1116,10 +1200,16 @@ pub fn run() {
             feishu_gateway::feishu_set_config,
             feishu_gateway::feishu_start,
             feishu_gateway::feishu_stop,
+            feishu_gateway::feishu_reconnect,
             feishu_gateway::feishu_get_status,
             feishu_gateway::feishu_is_running,
             feishu_gateway::feishu_send_message,
             feishu_gateway::feishu_edit_message,
+            feishu_gateway::feishu_stream_edit,
+            feishu_gateway::feishu_send_card,
+            feishu_gateway::feishu_send_markdown,
+            feishu_gateway::feishu_send_image,
+            feishu_gateway::feishu_reply_message,
         ])
         .on_window_event(|window, event| {
             if let WindowEvent::CloseRequested { .. } = event {
@@ -1165,10 +1255,8 @@ pub fn run() {
                     analytics::send_session_end_sync(analytics_state.inner());
                 }
 
-                // Shutdown trace writer
-                if let Some(trace_writer) = app_handle.try_state::<Arc<TraceWriter>>() {
-                    trace_writer.inner().shutdown_blocking();
-                }
+                // Stop Feishu, flush traces and stop watchers, each time-boxed
+                app_shutdown::AppShutdown::run(app_handle);
 
                 // Close database connection to release file handles
                 if let Some(db) = app_handle.try_state::<Arc<Database>>() {
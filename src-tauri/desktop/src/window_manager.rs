@@ -2,10 +2,15 @@ use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::collections::HashMap;
 use std::fs;
+use std::path::Path;
 use std::sync::{Arc, Mutex};
-use tauri::{AppHandle, Manager, Runtime, WebviewUrl, WebviewWindowBuilder};
+use tauri::{
+    AppHandle, Emitter, Manager, PhysicalPosition, PhysicalSize, Runtime, WebviewUrl,
+    WebviewWindowBuilder,
+};
 
 use crate::file_watcher::FileWatcher;
+use crate::llm::streaming::stream_registry;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WindowInfo {
@@ -13,12 +18,57 @@ pub struct WindowInfo {
     pub project_id: Option<String>,
     pub root_path: Option<String>,
     pub title: String,
+    #[serde(flatten)]
+    pub geometry: Option<WindowGeometry>,
+}
+
+/// Outer position and size of a window in physical pixels, as stored in windows-state.json
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WindowGeometry {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Physical bounds of a monitor, used to keep restored windows on screen
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MonitorBounds {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// Delivers an event to a single window by label
+pub trait WindowEmitter {
+    fn emit_to_window<S: Serialize + Clone>(
+        &self,
+        label: &str,
+        event: &str,
+        payload: S,
+    ) -> Result<(), String>;
+}
+
+impl<R: Runtime> WindowEmitter for AppHandle<R> {
+    fn emit_to_window<S: Serialize + Clone>(
+        &self,
+        label: &str,
+        event: &str,
+        payload: S,
+    ) -> Result<(), String> {
+        let window = self
+            .get_webview_window(label)
+            .ok_or_else(|| format!("Window {} not found", label))?;
+        window.emit(event, payload).map_err(|e| e.to_string())
+    }
 }
 
 pub struct WindowState {
     pub project_id: Option<String>,
     pub root_path: Option<String>,
     pub file_watcher: Option<FileWatcher>,
+    pub geometry: Option<WindowGeometry>,
 }
 
 #[derive(Clone)]
@@ -53,6 +103,8 @@ impl WindowRegistry {
                 watcher.stop();
             }
         }
+        // Abort this window's in-flight LLM streams; other windows keep theirs
+        stream_registry::cancel_all_streams_for_window(label);
         Ok(())
     }
 
@@ -65,6 +117,7 @@ impl WindowRegistry {
                 project_id: state.project_id.clone(),
                 root_path: state.root_path.clone(),
                 title: build_window_title(state.root_path.as_ref()),
+                geometry: state.geometry,
             });
         }
         Ok(infos)
@@ -112,6 +165,112 @@ impl WindowRegistry {
         Ok(())
     }
 
+    pub fn update_window_geometry(
+        &self,
+        label: &str,
+        geometry: WindowGeometry,
+    ) -> Result<(), String> {
+        let mut windows = self.windows.lock().map_err(|e| e.to_string())?;
+        if let Some(state) = windows.get_mut(label) {
+            state.geometry = Some(geometry);
+        }
+        Ok(())
+    }
+
+    fn window_project(&self, label: &str) -> Option<(Option<String>, Option<String>)> {
+        let windows = self.windows.lock().ok()?;
+        windows
+            .get(label)
+            .map(|state| (state.project_id.clone(), state.root_path.clone()))
+    }
+
+    /// Emit an event to every registered window, continuing past per-window failures
+    pub fn emit_to_all<R: Runtime, S: Serialize + Clone>(
+        &self,
+        app_handle: &AppHandle<R>,
+        event: &str,
+        payload: S,
+    ) -> Vec<(String, Result<(), String>)> {
+        self.emit_with(app_handle, event, payload)
+    }
+
+    fn emit_with<E: WindowEmitter, S: Serialize + Clone>(
+        &self,
+        emitter: &E,
+        event: &str,
+        payload: S,
+    ) -> Vec<(String, Result<(), String>)> {
+        let mut labels: Vec<String> = match self.windows.lock() {
+            Ok(windows) => windows.keys().cloned().collect(),
+            Err(e) => {
+                log::error!("Failed to acquire lock for emit_to_all: {}", e);
+                return Vec::new();
+            }
+        };
+        labels.sort();
+
+        labels
+            .into_iter()
+            .map(|label| {
+                let result = emitter.emit_to_window(&label, event, payload.clone());
+                if let Err(ref e) = result {
+                    log::warn!("Failed to emit {} to window {}: {}", event, label, e);
+                }
+                (label, result)
+            })
+            .collect()
+    }
+
+    /// Recreate the project windows recorded in windows-state.json and restart their
+    /// file watchers. Projects that are already open are left alone, so calling this
+    /// again does not duplicate windows. Returns the labels of newly created windows.
+    pub fn restore_saved_windows(&self, app_handle: &AppHandle) -> Result<Vec<String>, String> {
+        let state_file = window_state_file(app_handle)?;
+        let saved_windows = load_restorable_windows(&state_file)?;
+        let mut restored = Vec::new();
+
+        for saved in saved_windows {
+            if self.find_window_by_project(&saved.root_path)?.is_some() {
+                log::info!(
+                    "Project {} is already open, skipping restore",
+                    saved.root_path
+                );
+                continue;
+            }
+
+            let label = create_window(
+                app_handle,
+                self,
+                saved.project_id.clone(),
+                Some(saved.root_path.clone()),
+                false,
+            )?;
+            if let Err(e) = relabel_window_state_in_file(&state_file, &saved.label, &label) {
+                log::warn!(
+                    "Failed to update restored window state for {}: {}",
+                    label,
+                    e
+                );
+            }
+
+            let mut watcher = FileWatcher::new().map_err(|e| e.to_string())?;
+            match watcher.watch_directory(&saved.root_path, app_handle.clone(), Some(label.clone()))
+            {
+                Ok(()) => self.set_window_file_watcher(&label, Some(watcher))?,
+                Err(e) => log::error!(
+                    "Failed to start file watching for restored window {}: {}",
+                    label,
+                    e
+                ),
+            }
+
+            log::info!("Restored window {} for {}", label, saved.root_path);
+            restored.push(label);
+        }
+
+        Ok(restored)
+    }
+
     /// Stop all file watchers across all windows
     /// This should be called when the application exits to release file handles
     pub fn cleanup_all_watchers(&self) {
@@ -248,6 +407,261 @@ fn remove_window_state_from_file<R: Runtime>(
     Ok(())
 }
 
+/// Smallest window size accepted when restoring saved geometry
+const MIN_WINDOW_WIDTH: u32 = 400;
+const MIN_WINDOW_HEIGHT: u32 = 300;
+
+/// How much of the window's top-left area must be on a monitor for the saved position to be used
+const MIN_VISIBLE_WIDTH: i64 = 100;
+const MIN_VISIBLE_HEIGHT: i64 = 50;
+
+fn window_state_file<R: Runtime>(app_handle: &AppHandle<R>) -> Result<std::path::PathBuf, String> {
+    let app_data_dir = app_handle
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data dir: {}", e))?;
+    Ok(app_data_dir.join("windows-state.json"))
+}
+
+/// A project window recorded in windows-state.json
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SavedWindow {
+    label: String,
+    project_id: Option<String>,
+    root_path: String,
+}
+
+fn read_state_file(state_file: &Path) -> Result<Option<Value>, String> {
+    if !state_file.exists() {
+        return Ok(None);
+    }
+    let content = fs::read_to_string(state_file)
+        .map_err(|e| format!("Failed to read windows-state.json: {}", e))?;
+    serde_json::from_str(&content)
+        .map(Some)
+        .map_err(|e| format!("Failed to parse windows-state.json: {}", e))
+}
+
+fn write_state_file(state_file: &Path, state: &Value) -> Result<(), String> {
+    let updated_content = serde_json::to_string_pretty(state)
+        .map_err(|e| format!("Failed to serialize state: {}", e))?;
+    fs::write(state_file, updated_content)
+        .map_err(|e| format!("Failed to write windows-state.json: {}", e))
+}
+
+/// Collect the non-main project windows worth restoring, pruning entries whose
+/// project directory no longer exists (and repeated entries for the same project)
+fn load_restorable_windows(state_file: &Path) -> Result<Vec<SavedWindow>, String> {
+    let Some(mut state) = read_state_file(state_file)? else {
+        return Ok(Vec::new());
+    };
+    let Some(windows) = state.get_mut("windows").and_then(|w| w.as_array_mut()) else {
+        return Ok(Vec::new());
+    };
+
+    let mut restorable: Vec<SavedWindow> = Vec::new();
+    let mut kept = Vec::with_capacity(windows.len());
+    let original_len = windows.len();
+
+    // Walk newest-first so the latest entry for a project wins
+    for window in windows.drain(..).rev() {
+        let Ok(saved) = serde_json::from_value::<SavedWindow>(window.clone()) else {
+            // Main window or windows without a project are kept untouched
+            kept.push(window);
+            continue;
+        };
+        if saved.label == "main" {
+            kept.push(window);
+            continue;
+        }
+        if !Path::new(&saved.root_path).is_dir() {
+            log::info!(
+                "Pruning saved window {}: {} no longer exists",
+                saved.label,
+                saved.root_path
+            );
+            continue;
+        }
+        if restorable
+            .iter()
+            .any(|existing| existing.root_path == saved.root_path)
+        {
+            continue;
+        }
+        restorable.push(saved);
+        kept.push(window);
+    }
+    kept.reverse();
+    restorable.reverse();
+
+    let pruned = kept.len() != original_len;
+    *windows = kept;
+    if pruned {
+        write_state_file(state_file, &state)?;
+    }
+
+    Ok(restorable)
+}
+
+/// Point a saved window entry at the label of the window recreated for it
+fn relabel_window_state_in_file(
+    state_file: &Path,
+    old_label: &str,
+    new_label: &str,
+) -> Result<(), String> {
+    let Some(mut state) = read_state_file(state_file)? else {
+        return Ok(());
+    };
+    let entry = state
+        .get_mut("windows")
+        .and_then(|w| w.as_array_mut())
+        .and_then(|windows| {
+            windows
+                .iter_mut()
+                .find(|window| window.get("label").and_then(|l| l.as_str()) == Some(old_label))
+        });
+    if let Some(entry) = entry {
+        entry["label"] = Value::from(new_label);
+        write_state_file(state_file, &state)?;
+    }
+    Ok(())
+}
+
+/// Write a window's geometry into windows-state.json, adding an entry for it if missing
+fn save_window_geometry_to_file(
+    state_file: &Path,
+    window_label: &str,
+    project_id: Option<&str>,
+    root_path: Option<&str>,
+    geometry: WindowGeometry,
+) -> Result<(), String> {
+    let mut state =
+        read_state_file(state_file)?.unwrap_or_else(|| serde_json::json!({ "windows": [] }));
+
+    if !state.get("windows").is_some_and(Value::is_array) {
+        state["windows"] = Value::Array(Vec::new());
+    }
+    let windows = state["windows"]
+        .as_array_mut()
+        .ok_or_else(|| "windows-state.json has no windows list".to_string())?;
+
+    let index = windows
+        .iter()
+        .position(|window| window.get("label").and_then(|l| l.as_str()) == Some(window_label));
+    let entry = match index {
+        Some(index) => &mut windows[index],
+        None => {
+            let mut entry = serde_json::json!({ "label": window_label });
+            if let Some(project_id) = project_id {
+                entry["projectId"] = Value::from(project_id);
+            }
+            if let Some(root_path) = root_path {
+                entry["rootPath"] = Value::from(root_path);
+            }
+            windows.push(entry);
+            windows.last_mut().expect("entry was just pushed")
+        }
+    };
+    entry["x"] = Value::from(geometry.x);
+    entry["y"] = Value::from(geometry.y);
+    entry["width"] = Value::from(geometry.width);
+    entry["height"] = Value::from(geometry.height);
+
+    if let Some(parent) = state_file.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create app data dir: {}", e))?;
+    }
+    write_state_file(state_file, &state)
+}
+
+/// Read the last saved geometry for a project window from windows-state.json
+fn load_window_geometry_from_file(state_file: &Path, root_path: &str) -> Option<WindowGeometry> {
+    let content = fs::read_to_string(state_file).ok()?;
+    let state: Value = serde_json::from_str(&content).ok()?;
+    state
+        .get("windows")?
+        .as_array()?
+        .iter()
+        .rev()
+        .find(|window| window.get("rootPath").and_then(|p| p.as_str()) == Some(root_path))
+        .and_then(|window| serde_json::from_value(window.clone()).ok())
+}
+
+/// Keep saved geometry only if enough of the window lands on a connected monitor,
+/// shrinking it to fit that monitor; None means the window should use the defaults
+fn clamp_geometry_to_monitors(
+    geometry: WindowGeometry,
+    monitors: &[MonitorBounds],
+) -> Option<WindowGeometry> {
+    if geometry.width < MIN_WINDOW_WIDTH || geometry.height < MIN_WINDOW_HEIGHT {
+        return None;
+    }
+
+    // Check the area around the title bar so the window can still be grabbed and moved
+    let visible_right = i64::from(geometry.x) + i64::from(geometry.width).min(MIN_VISIBLE_WIDTH);
+    let visible_bottom = i64::from(geometry.y) + i64::from(geometry.height).min(MIN_VISIBLE_HEIGHT);
+    let monitor = monitors.iter().find(|monitor| {
+        let left = i64::from(monitor.x);
+        let top = i64::from(monitor.y);
+        let right = left + i64::from(monitor.width);
+        let bottom = top + i64::from(monitor.height);
+        i64::from(geometry.x) >= left
+            && i64::from(geometry.y) >= top
+            && visible_right <= right
+            && visible_bottom <= bottom
+    })?;
+
+    let width = geometry.width.min(monitor.width);
+    let height = geometry.height.min(monitor.height);
+    let max_x = i64::from(monitor.x) + i64::from(monitor.width - width);
+    let max_y = i64::from(monitor.y) + i64::from(monitor.height - height);
+    Some(WindowGeometry {
+        x: i64::from(geometry.x).min(max_x) as i32,
+        y: i64::from(geometry.y).min(max_y) as i32,
+        width,
+        height,
+    })
+}
+
+fn current_window_geometry<R: Runtime>(window: &tauri::WebviewWindow<R>) -> Option<WindowGeometry> {
+    let position = window.outer_position().ok()?;
+    let size = window.outer_size().ok()?;
+    Some(WindowGeometry {
+        x: position.x,
+        y: position.y,
+        width: size.width,
+        height: size.height,
+    })
+}
+
+/// Look up the saved geometry for a project and drop it if it would be off-screen
+fn restored_window_geometry<R: Runtime>(
+    app_handle: &AppHandle<R>,
+    root_path: &str,
+) -> Option<WindowGeometry> {
+    let state_file = window_state_file(app_handle).ok()?;
+    let geometry = load_window_geometry_from_file(&state_file, root_path)?;
+    let monitors: Vec<MonitorBounds> = app_handle
+        .available_monitors()
+        .ok()?
+        .iter()
+        .map(|monitor| MonitorBounds {
+            x: monitor.position().x,
+            y: monitor.position().y,
+            width: monitor.size().width,
+            height: monitor.size().height,
+        })
+        .collect();
+    let clamped = clamp_geometry_to_monitors(geometry, &monitors);
+    if clamped.is_none() {
+        log::info!(
+            "Saved geometry for {} is off-screen, using default window size",
+            root_path
+        );
+    }
+    clamped
+}
+
 fn register_window_with_cleanup<R: Runtime>(
     window: &tauri::WebviewWindow<R>,
     window_registry: &WindowRegistry,
@@ -260,7 +674,40 @@ fn register_window_with_cleanup<R: Runtime>(
     let label_clone = label.clone();
     let app_handle = window.app_handle().clone();
 
+    let event_window = window.clone();
+
     window.on_window_event(move |event| {
+        if let tauri::WindowEvent::Moved(_) | tauri::WindowEvent::Resized(_) = event {
+            if event_window.is_minimized().unwrap_or(false)
+                || event_window.is_maximized().unwrap_or(false)
+            {
+                return;
+            }
+            let Some(geometry) = current_window_geometry(&event_window) else {
+                return;
+            };
+            let _ = registry_clone.update_window_geometry(&label_clone, geometry);
+            let (project_id, root_path) = registry_clone
+                .window_project(&label_clone)
+                .unwrap_or_default();
+            let result = window_state_file(&app_handle).and_then(|state_file| {
+                save_window_geometry_to_file(
+                    &state_file,
+                    &label_clone,
+                    project_id.as_deref(),
+                    root_path.as_deref(),
+                    geometry,
+                )
+            });
+            if let Err(e) = result {
+                log::warn!(
+                    "Failed to persist geometry for window {}: {}",
+                    label_clone,
+                    e
+                );
+            }
+        }
+
         if let tauri::WindowEvent::Destroyed = event {
             log::info!(
                 "Window {} is being destroyed, cleaning up registry and state file",
@@ -324,17 +771,32 @@ pub fn create_window<R: Runtime>(
         "/"
     };
 
+    let geometry = root_path
+        .as_deref()
+        .and_then(|path| restored_window_geometry(app_handle, path));
+
     let window = WebviewWindowBuilder::new(app_handle, &label, WebviewUrl::App(url_path.into()))
         .title(&title)
         .inner_size(1200.0, 800.0)
         .build()
         .map_err(|e| format!("Failed to create window: {}", e))?;
 
+    // Restore the last geometry for this project, if any
+    if let Some(geometry) = geometry {
+        if let Err(e) = window
+            .set_size(PhysicalSize::new(geometry.width, geometry.height))
+            .and_then(|_| window.set_position(PhysicalPosition::new(geometry.x, geometry.y)))
+        {
+            log::warn!("Failed to restore geometry for window {}: {}", label, e);
+        }
+    }
+
     // Register window in registry and set up cleanup handler
     let state = WindowState {
         project_id,
         root_path,
         file_watcher: None,
+        geometry,
     };
     register_window_with_cleanup(&window, window_registry, label.clone(), state)?;
 
@@ -360,6 +822,7 @@ mod tests {
             project_id: Some("project-1".to_string()),
             root_path: Some("/path/to/project".to_string()),
             file_watcher: None,
+            geometry: None,
         };
 
         let result = registry.register_window("window-1".to_string(), state);
@@ -380,6 +843,7 @@ mod tests {
             project_id: Some("project-1".to_string()),
             root_path: Some("/path/to/project".to_string()),
             file_watcher: None,
+            geometry: None,
         };
 
         registry
@@ -409,12 +873,14 @@ mod tests {
             project_id: Some("project-1".to_string()),
             root_path: Some("/path/to/project1".to_string()),
             file_watcher: None,
+            geometry: None,
         };
 
         let state2 = WindowState {
             project_id: Some("project-2".to_string()),
             root_path: Some("/path/to/project2".to_string()),
             file_watcher: None,
+            geometry: None,
         };
 
         registry
@@ -446,6 +912,7 @@ mod tests {
             project_id: Some("old-project".to_string()),
             root_path: Some("/old/path".to_string()),
             file_watcher: None,
+            geometry: None,
         };
 
         registry
@@ -488,6 +955,7 @@ mod tests {
                 project_id: Some(format!("project-{}", i)),
                 root_path: Some(format!("/path/to/project{}", i)),
                 file_watcher: None,
+                geometry: None,
             };
             registry
                 .register_window(format!("window-{}", i), state)
@@ -505,6 +973,7 @@ mod tests {
             project_id: Some("project-1".to_string()),
             root_path: Some("/path/to/project".to_string()),
             title: "Project - TalkCody".to_string(),
+            geometry: None,
         };
 
         let json = serde_json::to_string(&info).unwrap();
@@ -525,6 +994,7 @@ mod tests {
             project_id: None,
             root_path: None,
             title: "TalkCody".to_string(),
+            geometry: None,
         };
 
         let json = serde_json::to_string(&info).unwrap();
@@ -545,6 +1015,7 @@ mod tests {
             project_id: None,
             root_path: Some("/path/to/project".to_string()),
             file_watcher: None,
+            geometry: None,
         };
         registry
             .register_window("window-1".to_string(), state_with_path)
@@ -555,6 +1026,7 @@ mod tests {
             project_id: None,
             root_path: None,
             file_watcher: None,
+            geometry: None,
         };
         registry
             .register_window("window-2".to_string(), state_without_path)
@@ -615,6 +1087,7 @@ mod tests {
                     project_id: Some(format!("project-{}", i)),
                     root_path: Some(format!("/path/{}", i)),
                     file_watcher: None,
+                    geometry: None,
                 };
                 registry_clone
                     .register_window(format!("window-{}", i), state)
@@ -649,6 +1122,7 @@ mod tests {
                 project_id: Some(format!("project-{}", i)),
                 root_path: Some(format!("/path/{}", i)),
                 file_watcher: None, // No watcher
+                geometry: None,
             };
             registry
                 .register_window(format!("window-{}", i), state)
@@ -675,6 +1149,7 @@ mod tests {
                 project_id: Some(format!("project-{}", i)),
                 root_path: Some(format!("/path/{}", i)),
                 file_watcher: watcher,
+                geometry: None,
             };
             registry
                 .register_window(format!("window-{}", i), state)
@@ -700,6 +1175,7 @@ mod tests {
                 project_id: Some(format!("project-{}", i)),
                 root_path: Some(format!("/path/{}", i)),
                 file_watcher: watcher,
+                geometry: None,
             };
             registry
                 .register_window(format!("window-{}", i), state)
@@ -725,6 +1201,7 @@ mod tests {
                 project_id: Some(format!("project-{}", i)),
                 root_path: Some(format!("/path/{}", i)),
                 file_watcher: watcher,
+                geometry: None,
             };
             registry
                 .register_window(format!("window-{}", i), state)
@@ -758,6 +1235,7 @@ mod tests {
             project_id: Some("project-1".to_string()),
             root_path: Some("/path/1".to_string()),
             file_watcher: None,
+            geometry: None,
         };
         registry
             .register_window("window-1".to_string(), state)
@@ -789,6 +1267,7 @@ mod tests {
                 project_id: Some(format!("project-{}", i)),
                 root_path: Some(format!("/path/to/project{}", i)),
                 file_watcher: None,
+                geometry: None,
             };
             registry
                 .register_window(format!("window-{}", i), state)
@@ -820,6 +1299,7 @@ mod tests {
                 project_id: Some(format!("project-{}", i)),
                 root_path: Some(format!("/path/{}", i)),
                 file_watcher: None,
+                geometry: None,
             };
             registry
                 .register_window(format!("window-{}", i), state)
@@ -842,6 +1322,40 @@ mod tests {
         assert_eq!(windows.len(), 2);
     }
 
+    #[tokio::test]
+    async fn test_unregister_window_cancels_only_its_streams() {
+        let registry = WindowRegistry::new();
+        for label in ["stream-window-1", "stream-window-2"] {
+            registry
+                .register_window(
+                    label.to_string(),
+                    WindowState {
+                        project_id: None,
+                        root_path: None,
+                        file_watcher: None,
+                        geometry: None,
+                    },
+                )
+                .unwrap();
+            let handle = tauri::async_runtime::spawn(async {
+                tokio::time::sleep(std::time::Duration::from_secs(5)).await;
+            });
+            stream_registry::register_stream(label, "req-1", handle);
+        }
+
+        registry.unregister_window("stream-window-1").unwrap();
+
+        assert!(stream_registry::active_stream_ids("stream-window-1").is_empty());
+        assert_eq!(
+            stream_registry::active_stream_ids("stream-window-2"),
+            vec!["req-1".to_string()]
+        );
+        assert_eq!(
+            stream_registry::cancel_all_streams_for_window("stream-window-2"),
+            1
+        );
+    }
+
     #[test]
     fn test_unregister_window_stops_its_watcher() {
         // Test that unregistering a window properly stops its file watcher
@@ -852,6 +1366,7 @@ mod tests {
             project_id: Some("project-1".to_string()),
             root_path: Some("/path/1".to_string()),
             file_watcher: None,
+            geometry: None,
         };
         registry
             .register_window("window-1".to_string(), state)
@@ -879,6 +1394,7 @@ mod tests {
             project_id: Some("project-1".to_string()),
             root_path: Some("/path/1".to_string()),
             file_watcher: None,
+            geometry: None,
         };
         registry
             .register_window("window-1".to_string(), state)
@@ -916,6 +1432,7 @@ mod tests {
             project_id: Some("project-1".to_string()),
             root_path: Some("/path/1".to_string()),
             file_watcher: None,
+            geometry: None,
         };
         registry
             .register_window("window-1".to_string(), state)
@@ -945,6 +1462,7 @@ mod tests {
             project_id: Some("talkcody".to_string()),
             root_path: Some("/Users/kks/mygit/talkcody".to_string()),
             file_watcher: None,
+            geometry: None,
         };
         registry
             .register_window("window-talkcody".to_string(), state1)
@@ -959,6 +1477,7 @@ mod tests {
             project_id: Some("trader".to_string()),
             root_path: Some("/Users/kks/mygit/trader".to_string()),
             file_watcher: None,
+            geometry: None,
         };
         registry
             .register_window("window-trader".to_string(), state2)
@@ -987,4 +1506,362 @@ mod tests {
             Some("/Users/kks/mygit/trader".to_string())
         );
     }
+
+    fn monitor(x: i32, y: i32, width: u32, height: u32) -> MonitorBounds {
+        MonitorBounds {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    #[test]
+    fn test_window_info_geometry_serialization_round_trip() {
+        let info = WindowInfo {
+            label: "window-1".to_string(),
+            project_id: Some("project-1".to_string()),
+            root_path: Some("/path/to/project".to_string()),
+            title: "Project - TalkCody".to_string(),
+            geometry: Some(WindowGeometry {
+                x: -20,
+                y: 40,
+                width: 1280,
+                height: 720,
+            }),
+        };
+
+        let json = serde_json::to_value(&info).unwrap();
+        assert_eq!(json["x"], -20);
+        assert_eq!(json["width"], 1280);
+
+        let parsed: WindowInfo = serde_json::from_value(json).unwrap();
+        assert_eq!(parsed.geometry, info.geometry);
+    }
+
+    #[test]
+    fn test_save_and_load_window_geometry() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let state_file = temp_dir.path().join("windows-state.json");
+        fs::write(
+            &state_file,
+            r#"{"windows":[{"label":"window-1","projectId":"project-1","rootPath":"/path/a"}],"lastActive":"window-1"}"#,
+        )
+        .unwrap();
+
+        let geometry = WindowGeometry {
+            x: 100,
+            y: 200,
+            width: 1300,
+            height: 900,
+        };
+        save_window_geometry_to_file(&state_file, "window-1", None, None, geometry).unwrap();
+        let other = WindowGeometry {
+            x: 0,
+            y: 0,
+            width: 800,
+            height: 600,
+        };
+        save_window_geometry_to_file(&state_file, "window-2", None, Some("/path/b"), other)
+            .unwrap();
+
+        assert_eq!(
+            load_window_geometry_from_file(&state_file, "/path/a"),
+            Some(geometry)
+        );
+        assert_eq!(
+            load_window_geometry_from_file(&state_file, "/path/b"),
+            Some(other)
+        );
+        assert_eq!(load_window_geometry_from_file(&state_file, "/path/c"), None);
+
+        // Existing entries keep their other fields
+        let state: Value = serde_json::from_str(&fs::read_to_string(&state_file).unwrap()).unwrap();
+        assert_eq!(state["lastActive"], "window-1");
+        assert_eq!(state["windows"][0]["projectId"], "project-1");
+        assert_eq!(state["windows"].as_array().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_load_window_geometry_without_saved_geometry() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let state_file = temp_dir.path().join("windows-state.json");
+        assert_eq!(load_window_geometry_from_file(&state_file, "/path/a"), None);
+
+        fs::write(
+            &state_file,
+            r#"{"windows":[{"label":"window-1","rootPath":"/path/a"}]}"#,
+        )
+        .unwrap();
+        assert_eq!(load_window_geometry_from_file(&state_file, "/path/a"), None);
+    }
+
+    #[test]
+    fn test_clamp_geometry_keeps_on_screen_window() {
+        let monitors = [monitor(0, 0, 1920, 1080), monitor(1920, 0, 2560, 1440)];
+        let geometry = WindowGeometry {
+            x: 2000,
+            y: 100,
+            width: 1200,
+            height: 800,
+        };
+        assert_eq!(
+            clamp_geometry_to_monitors(geometry, &monitors),
+            Some(geometry)
+        );
+    }
+
+    #[test]
+    fn test_clamp_geometry_rejects_off_screen_window() {
+        let monitors = [monitor(0, 0, 1920, 1080)];
+        // Saved on a monitor that is no longer connected
+        let geometry = WindowGeometry {
+            x: 2200,
+            y: 100,
+            width: 1200,
+            height: 800,
+        };
+        assert_eq!(clamp_geometry_to_monitors(geometry, &monitors), None);
+        // Title bar above the top of the screen
+        let geometry = WindowGeometry {
+            x: 100,
+            y: -400,
+            width: 1200,
+            height: 800,
+        };
+        assert_eq!(clamp_geometry_to_monitors(geometry, &monitors), None);
+        assert_eq!(clamp_geometry_to_monitors(geometry, &[]), None);
+    }
+
+    #[test]
+    fn test_clamp_geometry_shrinks_and_moves_into_monitor() {
+        let monitors = [monitor(0, 0, 1440, 900)];
+        let geometry = WindowGeometry {
+            x: 1000,
+            y: 500,
+            width: 2000,
+            height: 800,
+        };
+        assert_eq!(
+            clamp_geometry_to_monitors(geometry, &monitors),
+            Some(WindowGeometry {
+                x: 0,
+                y: 100,
+                width: 1440,
+                height: 800,
+            })
+        );
+    }
+
+    #[test]
+    fn test_clamp_geometry_rejects_tiny_window() {
+        let monitors = [monitor(0, 0, 1920, 1080)];
+        let geometry = WindowGeometry {
+            x: 0,
+            y: 0,
+            width: 10,
+            height: 10,
+        };
+        assert_eq!(clamp_geometry_to_monitors(geometry, &monitors), None);
+    }
+
+    struct MockEmitter {
+        received: Mutex<Vec<(String, String, Value)>>,
+        failing: Vec<String>,
+    }
+
+    impl MockEmitter {
+        fn new(failing: &[&str]) -> Self {
+            Self {
+                received: Mutex::new(Vec::new()),
+                failing: failing.iter().map(|label| label.to_string()).collect(),
+            }
+        }
+    }
+
+    impl WindowEmitter for MockEmitter {
+        fn emit_to_window<S: Serialize + Clone>(
+            &self,
+            label: &str,
+            event: &str,
+            payload: S,
+        ) -> Result<(), String> {
+            if self.failing.iter().any(|failing| failing == label) {
+                return Err(format!("Window {} not found", label));
+            }
+            self.received.lock().unwrap().push((
+                label.to_string(),
+                event.to_string(),
+                serde_json::to_value(payload).unwrap(),
+            ));
+            Ok(())
+        }
+    }
+
+    fn register_labels(registry: &WindowRegistry, labels: &[&str]) {
+        for label in labels {
+            let state = WindowState {
+                project_id: None,
+                root_path: None,
+                file_watcher: None,
+                geometry: None,
+            };
+            registry.register_window(label.to_string(), state).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_emit_to_all_reaches_every_window() {
+        let registry = WindowRegistry::new();
+        register_labels(&registry, &["window-2", "main", "window-1"]);
+        let emitter = MockEmitter::new(&[]);
+
+        let results = registry.emit_with(
+            &emitter,
+            "settings-changed",
+            serde_json::json!({ "reload": "providers" }),
+        );
+
+        let labels: Vec<&str> = results.iter().map(|(label, _)| label.as_str()).collect();
+        assert_eq!(labels, vec!["main", "window-1", "window-2"]);
+        assert!(results.iter().all(|(_, result)| result.is_ok()));
+
+        let received = emitter.received.lock().unwrap();
+        assert_eq!(received.len(), 3);
+        for (_, event, payload) in received.iter() {
+            assert_eq!(event, "settings-changed");
+            assert_eq!(payload["reload"], "providers");
+        }
+    }
+
+    #[test]
+    fn test_emit_to_all_collects_failures_without_aborting() {
+        let registry = WindowRegistry::new();
+        register_labels(&registry, &["main", "window-1", "window-2"]);
+        let emitter = MockEmitter::new(&["window-1"]);
+
+        let results = registry.emit_with(&emitter, "settings-changed", ());
+
+        assert_eq!(results.len(), 3);
+        let failed: Vec<&str> = results
+            .iter()
+            .filter(|(_, result)| result.is_err())
+            .map(|(label, _)| label.as_str())
+            .collect();
+        assert_eq!(failed, vec!["window-1"]);
+
+        let received: Vec<String> = emitter
+            .received
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(label, _, _)| label.clone())
+            .collect();
+        assert_eq!(received, vec!["main".to_string(), "window-2".to_string()]);
+    }
+
+    #[test]
+    fn test_emit_to_all_empty_registry() {
+        let registry = WindowRegistry::new();
+        let emitter = MockEmitter::new(&[]);
+        assert!(registry.emit_with(&emitter, "event", ()).is_empty());
+        assert!(emitter.received.lock().unwrap().is_empty());
+    }
+
+    fn write_saved_windows(state_file: &Path, windows: Value) {
+        fs::write(
+            state_file,
+            serde_json::to_string(&serde_json::json!({ "windows": windows, "lastActive": "main" }))
+                .unwrap(),
+        )
+        .unwrap();
+    }
+
+    fn saved_labels(state_file: &Path) -> Vec<String> {
+        let state: Value = serde_json::from_str(&fs::read_to_string(state_file).unwrap()).unwrap();
+        state["windows"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|w| w["label"].as_str().unwrap().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_load_restorable_windows_prunes_missing_paths() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let project = temp_dir.path().join("project-a");
+        fs::create_dir(&project).unwrap();
+        let project = project.to_string_lossy().to_string();
+        let missing = temp_dir.path().join("deleted-project");
+        let missing = missing.to_string_lossy().to_string();
+        let state_file = temp_dir.path().join("windows-state.json");
+        write_saved_windows(
+            &state_file,
+            serde_json::json!([
+                { "label": "main" },
+                { "label": "window-1", "projectId": "p1", "rootPath": project, "x": 10 },
+                { "label": "window-2", "projectId": "p2", "rootPath": missing },
+            ]),
+        );
+
+        let restorable = load_restorable_windows(&state_file).unwrap();
+        assert_eq!(
+            restorable,
+            vec![SavedWindow {
+                label: "window-1".to_string(),
+                project_id: Some("p1".to_string()),
+                root_path: project,
+            }]
+        );
+        assert_eq!(saved_labels(&state_file), vec!["main", "window-1"]);
+
+        let state: Value = serde_json::from_str(&fs::read_to_string(&state_file).unwrap()).unwrap();
+        assert_eq!(state["lastActive"], "main");
+        assert_eq!(state["windows"][1]["x"], 10);
+    }
+
+    #[test]
+    fn test_load_restorable_windows_keeps_latest_entry_per_project() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let project = temp_dir.path().to_string_lossy().to_string();
+        let state_file = temp_dir.path().join("windows-state.json");
+        write_saved_windows(
+            &state_file,
+            serde_json::json!([
+                { "label": "window-old", "rootPath": project },
+                { "label": "window-new", "rootPath": project },
+            ]),
+        );
+
+        let restorable = load_restorable_windows(&state_file).unwrap();
+        assert_eq!(restorable.len(), 1);
+        assert_eq!(restorable[0].label, "window-new");
+        assert_eq!(saved_labels(&state_file), vec!["window-new"]);
+
+        // A second pass finds nothing left to prune
+        assert_eq!(load_restorable_windows(&state_file).unwrap(), restorable);
+        assert_eq!(saved_labels(&state_file), vec!["window-new"]);
+    }
+
+    #[test]
+    fn test_load_restorable_windows_without_state_file() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let state_file = temp_dir.path().join("windows-state.json");
+        assert!(load_restorable_windows(&state_file).unwrap().is_empty());
+        assert!(!state_file.exists());
+    }
+
+    #[test]
+    fn test_relabel_window_state_in_file() {
+        let temp_dir = tempfile::TempDir::new().unwrap();
+        let state_file = temp_dir.path().join("windows-state.json");
+        write_saved_windows(
+            &state_file,
+            serde_json::json!([{ "label": "window-1", "rootPath": "/path/a" }]),
+        );
+
+        relabel_window_state_in_file(&state_file, "window-1", "window-2").unwrap();
+        assert_eq!(saved_labels(&state_file), vec!["window-2"]);
+    }
 }
@@ -251,6 +251,7 @@ pub async fn chat(
                 status: SessionStatus::Running,
                 created_at: now,
                 updated_at: now,
+                pinned_at: None,
                 last_event_id: None,
                 metadata: None,
             };
@@ -24,6 +24,7 @@ pub async fn create_session(
         status: SessionStatus::Created,
         created_at: now,
         updated_at: now,
+        pinned_at: None,
         last_event_id: None,
         metadata: None,
     };
@@ -84,6 +85,11 @@ pub async fn list_sessions(
             status,
             query.limit,
             query.offset,
+            query.include_archived.unwrap_or(false),
+            query
+                .tags
+                .as_deref()
+                .map(|tags| tags.split(',').map(|tag| tag.to_string()).collect()),
         )
         .await
     {
@@ -29,6 +29,7 @@ pub async fn create_task(
                     status: SessionStatus::Running,
                     created_at: chrono::Utc::now().timestamp(),
                     updated_at: chrono::Utc::now().timestamp(),
+                    pinned_at: None,
                     last_event_id: None,
                     metadata: None,
                 })
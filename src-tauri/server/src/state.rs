@@ -5,6 +5,10 @@ use talkcody_core::core::types::{EventSender, RuntimeEvent};
 use talkcody_core::core::CoreRuntime;
 use talkcody_core::llm::auth::api_key_manager::ApiKeyManager;
 use talkcody_core::llm::providers::provider_registry::ProviderRegistry;
+use talkcody_core::llm::streaming::message_persistence::{
+    persist_snapshot, subscribe_message_snapshots,
+};
+use talkcody_core::llm::streaming::usage_report::subscribe_usage_reports;
 use talkcody_core::platform::Platform;
 use talkcody_core::storage::Storage;
 use talkcody_core::streaming::StreamingManager;
@@ -168,6 +172,39 @@ impl ServerStateFactory {
         let storage =
             Storage::new(config.data_root.clone(), config.attachments_root.clone()).await?;
 
+        // Roll up per-session token usage reported by LLM streams
+        let mut usage_reports = subscribe_usage_reports();
+        let chat_history = storage.chat_history.clone();
+        tokio::spawn(async move {
+            while let Some(report) = usage_reports.recv().await {
+                if let Err(e) = chat_history
+                    .add_usage(&report.session_id, &report.usage)
+                    .await
+                {
+                    log::warn!(
+                        "[ServerState] Failed to record usage for session {}: {}",
+                        report.session_id,
+                        e
+                    );
+                }
+            }
+        });
+
+        // Write streamed assistant messages into the sessions that asked for it
+        let mut message_snapshots = subscribe_message_snapshots();
+        let chat_history = storage.chat_history.clone();
+        tokio::spawn(async move {
+            while let Some(snapshot) = message_snapshots.recv().await {
+                if let Err(e) = persist_snapshot(&chat_history, &snapshot).await {
+                    log::warn!(
+                        "[ServerState] Failed to persist streamed message for session {}: {}",
+                        snapshot.session_id,
+                        e
+                    );
+                }
+            }
+        });
+
         // Create provider registry and API key manager
         let provider_registry = ProviderRegistry::default();
         let db = storage.settings.get_db();
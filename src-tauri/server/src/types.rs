@@ -32,6 +32,7 @@ pub struct SessionResponse {
     pub status: SessionStatus,
     pub created_at: i64,
     pub updated_at: i64,
+    pub pinned_at: Option<i64>,
     pub last_event_id: Option<String>,
     pub metadata: Option<serde_json::Value>,
 }
@@ -45,6 +46,7 @@ impl From<Session> for SessionResponse {
             status: session.status,
             created_at: session.created_at,
             updated_at: session.updated_at,
+            pinned_at: session.pinned_at,
             last_event_id: session.last_event_id,
             metadata: session.metadata,
         }
@@ -58,6 +60,9 @@ pub struct ListSessionsQuery {
     pub status: Option<String>,
     pub limit: Option<usize>,
     pub offset: Option<usize>,
+    pub include_archived: Option<bool>,
+    /// Comma-separated tags; sessions with any of them are returned
+    pub tags: Option<String>,
 }
 
 // ============== Message Types ==============
@@ -51,6 +51,8 @@ export interface TokenExchangeResult {
   type: 'success' | 'failed' | 'pending';
   tokens?: GitHubCopilotOAuthTokens;
   error?: string;
+  // Seconds to wait before the next poll when GitHub asked to slow down
+  interval?: number;
 }
 
 /**
@@ -73,12 +75,14 @@ export async function startDeviceCodeFlow(enterpriseUrl?: string): Promise<OAuth
  */
 export async function pollForAccessToken(
   deviceCode: string,
-  enterpriseUrl?: string
+  enterpriseUrl?: string,
+  interval?: number
 ): Promise<TokenExchangeResult> {
   logger.info('[GitHubCopilotOAuth] Polling for access token via Rust');
   const result = await llmClient.pollGitHubCopilotOAuthDeviceCode({
     deviceCode,
     enterpriseUrl,
+    interval,
   });
 
   if (result.type === 'success' && result.tokens) {
@@ -94,7 +98,7 @@ export async function pollForAccessToken(
   }
 
   if (result.type === 'pending') {
-    return { type: 'pending' };
+    return { type: 'pending', interval: result.interval };
   }
 
   return {
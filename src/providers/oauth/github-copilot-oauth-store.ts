@@ -162,12 +162,16 @@ export const useGitHubCopilotOAuthStore = create<GitHubCopilotOAuthStore>((set,
 
     set({ isPolling: true, error: null });
 
-    const pollIntervalMs = intervalMs ?? 5000;
+    let pollIntervalMs = intervalMs ?? 5000;
     const deadlineMs = expiresAtMs ?? Date.now() + 10 * 60 * 1000;
 
     try {
       while (Date.now() < deadlineMs) {
-        const result = await pollForAccessToken(deviceCode, enterpriseUrl || undefined);
+        const result = await pollForAccessToken(
+          deviceCode,
+          enterpriseUrl || undefined,
+          Math.round(pollIntervalMs / 1000)
+        );
 
         if (result.type === 'success' && result.tokens) {
           logger.info('[GitHubCopilotOAuth] OAuth completed successfully');
@@ -191,6 +195,11 @@ export const useGitHubCopilotOAuthStore = create<GitHubCopilotOAuthStore>((set,
           throw new Error(result.error || 'Token exchange failed');
         }
 
+        if (result.interval) {
+          pollIntervalMs = Math.max(pollIntervalMs, result.interval * 1000);
+          set({ intervalMs: pollIntervalMs });
+        }
+
         await new Promise((resolve) => setTimeout(resolve, pollIntervalMs));
       }
 
@@ -813,6 +813,15 @@ export class LLMService {
                   case 'reasoning-end':
                     streamProcessor.processReasoningEnd(delta.id, streamCallbacks);
                     break;
+                  case 'reasoning-summary-delta':
+                    streamProcessor.processReasoningDelta(
+                      delta.id || 'default',
+                      delta.text || '',
+                      undefined,
+                      streamContext,
+                      streamCallbacks
+                    );
+                    break;
                   case 'usage': {
                     const requestDuration = Date.now() - requestStartTime;
                     const normalizedUsage = UsageTokenUtils.normalizeUsageTokens(
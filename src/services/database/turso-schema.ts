@@ -401,6 +401,9 @@ export class TursoSchema {
       'CREATE INDEX IF NOT EXISTS idx_span_events_timestamp ON span_events(timestamp DESC)'
     );
     await db.execute('CREATE INDEX IF NOT EXISTS idx_span_events_type ON span_events(event_type)');
+    await db.execute(
+      'CREATE INDEX IF NOT EXISTS idx_span_events_span_type ON span_events(span_id, event_type)'
+    );
 
     // API usage events indexes
     await db.execute(
@@ -75,6 +75,10 @@ export class LlmClient {
       onAbort = () => {
         logger.info(`[LLM Client ${requestId}] Abort signal received, stopping`);
         stop();
+        // Abort the backend stream too so the provider connection is released
+        invoke<boolean>('llm_cancel_stream', { requestId }).catch((error) => {
+          logger.warn(`[LLM Client ${requestId}] Failed to cancel backend stream:`, error);
+        });
       };
       abortSignal.addEventListener('abort', onAbort, { once: true });
     }
@@ -315,6 +319,7 @@ export class LlmClient {
   async pollGitHubCopilotOAuthDeviceCode(params: {
     deviceCode: string;
     enterpriseUrl?: string;
+    interval?: number;
   }): Promise<{
     type: 'success' | 'failed' | 'pending';
     tokens?: {
@@ -324,6 +329,7 @@ export class LlmClient {
       enterpriseUrl?: string;
     };
     error?: string;
+    interval?: number;
   }> {
     return invoke('llm_github_copilot_oauth_poll_device_code', { request: params });
   }
@@ -364,6 +370,10 @@ export class LlmClient {
     githubCopilot?: {
       isConnected?: boolean | null;
     } | null;
+    qwen?: {
+      expiresAt?: number | null;
+      isConnected?: boolean | null;
+    } | null;
   } | null> {
     return invoke('llm_oauth_status');
   }
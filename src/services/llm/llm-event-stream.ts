@@ -120,6 +120,12 @@ export function logStreamEvent(event: StreamEvent, requestId: string): void {
     case 'reasoning-end':
       logger.debug(`[LLM Stream ${requestId}] Reasoning end: ${event.id}`);
       break;
+    case 'reasoning-summary-delta':
+      logger.debug(`[LLM Stream ${requestId}] Reasoning summary delta: ${event.text.length} chars`);
+      break;
+    case 'slow-start':
+      logger.warn(`[LLM Stream ${requestId}] No content after ${event.elapsed_ms}ms`);
+      break;
     case 'usage':
       logger.debug(
         `[LLM Stream ${requestId}] Usage: ${event.input_tokens} in, ${event.output_tokens} out`
@@ -84,12 +84,67 @@ export type StreamTextRequest = {
   providerOptions?: ProviderOptions;
   requestId?: string | null;
   traceContext?: TraceContext | null;
+  partialJson?: boolean | null;
+  fallbackModels?: string[] | null;
+  logprobs?: boolean | null;
+  topLogprobs?: number | null;
+  estimateUsage?: boolean;
+  metadata?: Record<string, unknown> | null;
+  resumeOnDisconnect?: boolean;
+  allowedTools?: string[] | null;
+  streamToolCallDeltas?: boolean;
+  completionWebhook?: string | null;
+  persistToSession?: string | null;
+  idempotencyKey?: string | null;
 };
 
 export type StreamResponse = {
   request_id: string;
 };
 
+export type RequestPreview = {
+  providerId: string;
+  model: string;
+  url: string;
+  headers: Record<string, string>;
+  body: unknown;
+};
+
+export type AvailabilityReason =
+  | 'HasApiKey'
+  | 'HasOAuth'
+  | 'NoneAuth'
+  | 'TalkCodyJwt'
+  | 'DisabledLocal'
+  | 'MissingCredentials';
+
+export type ProviderAvailability = {
+  available: boolean;
+  reason: AvailabilityReason;
+};
+
+export type HealthStatus = {
+  healthy: boolean;
+  latencyMs: number | null;
+  checkedAt: number;
+  error: string | null;
+};
+
+export type StreamErrorKind =
+  | 'auth'
+  | 'rate_limited'
+  | 'network'
+  | 'timeout'
+  | 'protocol'
+  | 'http'
+  | 'cancelled'
+  | 'config';
+
+export type ConfigWarning =
+  | { kind: 'unknown_providers'; model: string; providers: string[] }
+  | { kind: 'unknown_mapping_provider'; model: string; provider: string }
+  | { kind: 'duplicate_model_key'; key: string; models: string[] };
+
 export type StreamEvent =
   | { type: 'text-start' }
   | { type: 'text-delta'; text: string }
@@ -100,6 +155,12 @@ export type StreamEvent =
       input: unknown;
       providerMetadata?: ProviderOptions;
     }
+  | {
+      type: 'tool-call-delta';
+      toolCallId: string;
+      toolName?: string | null;
+      argumentsDelta: string;
+    }
   | {
       type: 'reasoning-start';
       id: string;
@@ -115,6 +176,14 @@ export type StreamEvent =
       type: 'reasoning-end';
       id: string;
     }
+  | {
+      type: 'reasoning-summary-delta';
+      id: string;
+      text: string;
+    }
+  | { type: 'image-start'; id: string }
+  | { type: 'image-delta'; id: string; b64: string; mime: string }
+  | { type: 'image-end'; id: string }
   | {
       type: 'usage';
       input_tokens: number;
@@ -124,7 +193,24 @@ export type StreamEvent =
       cache_creation_input_tokens?: number | null;
     }
   | { type: 'done'; finish_reason?: string | null }
-  | { type: 'error'; message: string; name?: string }
+  | {
+      type: 'error';
+      message: string;
+      name?: string;
+      provider_error?: { message: string; type?: string; code?: string };
+      kind?: StreamErrorKind;
+    }
+  | { type: 'slow-start'; elapsed_ms: number }
+  | { type: 'json-partial'; value: unknown }
+  | { type: 'usage-delta'; output_tokens_est: number; reconciled?: boolean }
+  | {
+      type: 'logprobs';
+      tokens: Array<{
+        token: string;
+        logprob: number;
+        top_logprobs?: Array<{ token: string; logprob: number }>;
+      }>;
+    }
   | { type: 'raw'; raw_value: string };
 
 export type AvailableModel = {
@@ -152,8 +238,14 @@ export type ProviderConfig = {
   headers?: Record<string, string> | null;
   extraBody?: unknown;
   authType: string;
+  bodyTransforms?: BodyTransform[] | null;
 };
 
+export type BodyTransform =
+  | { op: 'set_path'; path: string; value: unknown }
+  | { op: 'remove_path'; path: string }
+  | { op: 'rename_path'; from: string; to: string };
+
 export type TranscriptionRequest = {
   model: string;
   audioBase64: string;
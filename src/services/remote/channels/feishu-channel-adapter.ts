@@ -33,7 +33,7 @@ function toRemoteAttachment(attachment: FeishuRemoteAttachment): RemoteAttachmen
 function toRemoteInboundMessage(message: FeishuInboundMessage): RemoteInboundMessage {
   return {
     channelId: 'feishu',
-    chatId: message.openId,
+    chatId: message.chatId,
     messageId: message.messageId,
     text: message.text,
     username: null,
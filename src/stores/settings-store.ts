@@ -1,4 +1,5 @@
 // src/stores/settings-store.ts
+import { invoke } from '@tauri-apps/api/core';
 import { create } from 'zustand';
 import { logger } from '@/lib/logger';
 import { GROK_CODE_FAST } from '@/providers/config/model-config';
@@ -21,6 +22,15 @@ function generateDefaultApiKeySettings(): Record<string, string> {
   return settings;
 }
 
+// Mirrors `is_sensitive_key` in the Rust settings backend. These settings may live in
+// the OS keyring, so they are read and written through the backend, not the table.
+const SENSITIVE_OAUTH_FIELDS = ['access_token', 'refresh_token', 'copilot_token'];
+
+function isSensitiveKey(key: string): boolean {
+  if (key.startsWith('api_key_')) return true;
+  return key.includes('_oauth_') && SENSITIVE_OAUTH_FIELDS.some((field) => key.includes(field));
+}
+
 // All settings managed by the store
 interface SettingsState {
   // UI Settings
@@ -440,6 +450,9 @@ class SettingsDatabase {
 
   async get(key: string): Promise<string> {
     if (!this.db) throw new Error('Database not initialized');
+    if (isSensitiveKey(key)) {
+      return (await invoke<string | null>('llm_get_setting', { key })) || '';
+    }
 
     const result = await this.db.select<{ value: string }[]>(
       'SELECT value FROM settings WHERE key = $1',
@@ -452,22 +465,40 @@ class SettingsDatabase {
   async getBatch(keys: readonly string[]): Promise<Record<string, string>> {
     if (!this.db) throw new Error('Database not initialized');
 
-    const placeholders = keys.map((_, i) => `$${i + 1}`).join(', ');
-    const result = await this.db.select<{ key: string; value: string }[]>(
-      `SELECT key, value FROM settings WHERE key IN (${placeholders})`,
-      [...keys]
-    );
-
+    const secretKeys = keys.filter(isSensitiveKey);
+    const plainKeys = keys.filter((key) => !isSensitiveKey(key));
     const settingsMap: Record<string, string> = {};
-    for (const row of result) {
-      settingsMap[row.key] = row.value;
+
+    if (plainKeys.length > 0) {
+      const placeholders = plainKeys.map((_, i) => `$${i + 1}`).join(', ');
+      const result = await this.db.select<{ key: string; value: string }[]>(
+        `SELECT key, value FROM settings WHERE key IN (${placeholders})`,
+        plainKeys
+      );
+      for (const row of result) {
+        settingsMap[row.key] = row.value;
+      }
     }
 
+    const secrets = await Promise.all(
+      secretKeys.map((key) => invoke<string | null>('llm_get_setting', { key }))
+    );
+    secretKeys.forEach((key, i) => {
+      const value = secrets[i];
+      if (value !== null && value !== undefined) {
+        settingsMap[key] = value;
+      }
+    });
+
     return settingsMap;
   }
 
   async set(key: string, value: string): Promise<void> {
     if (!this.db) throw new Error('Database not initialized');
+    if (isSensitiveKey(key)) {
+      await invoke('llm_set_setting', { key, value });
+      return;
+    }
 
     const now = Date.now();
     await this.db.execute(
@@ -482,12 +513,19 @@ class SettingsDatabase {
     const entries = Object.entries(settings);
     const now = Date.now();
 
-    const statements = entries.map(([key, value]) => ({
-      sql: 'INSERT OR REPLACE INTO settings (key, value, updated_at) VALUES ($1, $2, $3)',
-      params: [key, value, now],
-    }));
-
-    await this.db.batch(statements);
+    for (const [key, value] of entries.filter(([key]) => isSensitiveKey(key))) {
+      await invoke('llm_set_setting', { key, value });
+    }
+    const statements = entries
+      .filter(([key]) => !isSensitiveKey(key))
+      .map(([key, value]) => ({
+        sql: 'INSERT OR REPLACE INTO settings (key, value, updated_at) VALUES ($1, $2, $3)',
+        params: [key, value, now],
+      }));
+
+    if (statements.length > 0) {
+      await this.db.batch(statements);
+    }
   }
 }
 
@@ -226,6 +226,9 @@ export class TestIPCHandler {
     if (cmd === 'llm_transcribe_audio') {
       return { text: 'Test transcript', language: 'en', duration: 1.5 };
     }
+    if (cmd === 'llm_get_setting') {
+      return null;
+    }
     if (cmd === 'llm_set_setting') {
       return null;
     }
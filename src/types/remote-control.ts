@@ -78,6 +78,11 @@ export interface FeishuRemoteConfig {
   encryptKey: string;
   verificationToken: string;
   allowedOpenIds: string[];
+  allowGroupChats?: boolean;
+  editIntervalMs?: number;
+  dedupCapacity?: number;
+  dedupTtlSecs?: number;
+  allowedAttachmentMimeTypes?: string[] | null;
 }
 
 export interface FeishuInboundMessage {
@@ -85,6 +90,7 @@ export interface FeishuInboundMessage {
   messageId: string;
   text: string;
   openId: string;
+  chatType: string;
   date: number;
   attachments?: FeishuRemoteAttachment[];
 }
@@ -92,6 +98,7 @@ export interface FeishuInboundMessage {
 export interface FeishuSendMessageRequest {
   openId: string;
   text: string;
+  replyTo?: string;
 }
 
 export interface FeishuSendMessageResponse {
@@ -109,6 +116,17 @@ export interface FeishuGatewayStatus {
   lastError?: string | null;
   lastErrorAtMs?: number | null;
   backoffMs?: number | null;
+  reconnectCount?: number;
+  lastConnectedAtMs?: number | null;
+  consecutiveFailures?: number;
+  throttle?: FeishuThrottleStatus;
+}
+
+export interface FeishuThrottleStatus {
+  availableTokens: number;
+  capacity: number;
+  frequencyLimitRetries: number;
+  throttledUntilMs?: number | null;
 }
 
 export interface TelegramRemoteConfig {